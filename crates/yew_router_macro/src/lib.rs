@@ -1,6 +1,9 @@
 extern crate proc_macro;
 use proc_macro::TokenStream;
+use proc_macro_hack::proc_macro_hack;
 
+mod route;
+mod span;
 mod switch;
 
 /// Implements the `Switch` trait based on attributes present on the struct or enum variants.
@@ -25,10 +28,61 @@ mod switch;
 /// _everything_, and the next 3 path sections respectively.
 /// `{1:field_name}` is the same as `{field_name}`.
 ///
+/// If something follows `{*:field_name}`, the capture stops at its first occurrence, so
+/// `#[to = "/{*:path}/edit"]` captures "a/b" from "/a/b/edit", but only "a" from "/a/edit/b/edit".
+/// `{**:field_name}` stops at its last occurrence within the path instead, capturing "a/edit/b".
+///
+/// Sections are separated by `/` by default, but a capture can declare another separator, like
+/// `{3:date;sep=-}`, which captures "2020-05-01" from "/posts/2020-05-01-my-title" when used in
+/// `#[to = "/posts/{3:date;sep=-}-{slug}"]`.
+/// `{field_name;sep=-}` captures a single section, stopping at the first `-`.
+///
+/// A capture stops at the first `.` that follows it, so `#[to = "/v/{major}.{minor}"]` assigns "1"
+/// and "2.3" from "/v/1.2.3".
+/// A capture of the `stem` kind captures the stem of a file name instead, stopping at the last `.`
+/// of the section, so `#[to = "/files/{name:stem}.{ext}"]` assigns "archive.tar" and "gz" from
+/// "/files/archive.tar.gz", and builds the same route from them.
+/// The same applies to literal extensions, like `/files/{name:stem}.pdf`.
+/// As an extension that contains a `.` would be split at that `.` when the route is matched,
+/// `try_build_route` fails for it with `SwitchError::Unbuildable`.
+///
+/// A capture can be restricted to a kind of value, like `{id:u32}` or `{id:uuid}`, in which case
+/// routes whose section isn't of that kind don't match at all, so `#[to = "/users/{id:u32}"]`
+/// doesn't match "/users/new", and doesn't stop a later `#[to = "/users/new"]` from being reached.
+/// The integer types from `u8` to `u128` and `i8` to `i128`, `usize`, `isize`, and `uuid` are
+/// supported, and `{:u32}` restricts an unnamed capture.
+///
+/// Path segments can contain matrix parameters, like `/users;role={role}/list`.
+/// Captured parameter values end at the next `;` or `/`, and each parameter must be present, in the
+/// order that the matcher string lists them.
+///
+/// A query parameter that is repeated, like in "/posts?tag=rust&tag=web", can be captured into a
+/// `Vec` with `{*:name}`, so `#[to = "/posts?tag={*:tags}"]` assigns `["rust", "web"]` to a
+/// `tags: Vec<String>` field.
+/// The values have to directly follow each other, and building the route repeats the parameter
+/// for every element.
+///
+/// Query values can also be partly literal, like `#[to = "/issues?filter=status:{status}"]`,
+/// which only matches values that start with "status:", and captures the rest.
+/// Captures in a value have to be separated by literals, like `?range={from}..{to}`.
+///
+/// If a field shouldn't share its name with its capture, `#[switch(rename = "user_id")]` on the
+/// field binds it to the `{user_id}` capture instead, so fields can be renamed without touching
+/// the matcher strings.
+///
+/// Values can be checked while matching, so a route whose captures have the right form but aren't
+/// acceptable, like a slug that isn't known, falls through to the next variant.
+/// `#[switch(validate)]` on a field checks its value with the `Validate` implementation of the
+/// field's type, and `#[switch(validate = "is_known_slug")]` with a `fn(&FieldType) -> bool`.
+///
 /// Tuple-structs and Tuple-enum-variants are also supported.
 /// If you don't want to specify keys that don't correspond to any specific field,
 /// `{}`, `{*}`, and `{4}` also denote valid capture sections when used on structs and variants without named fields.
 /// In datastructures without field names, the captures will be assigned in order - left to right.
+/// If there is a field for each of the sections of a numbered capture, each section is assigned to
+/// its own field, so `#[to = "/archive/{2}"]` assigns "2020" and "05" from "/archive/2020/05" to
+/// the fields of `Archive(String, String)`.
+/// Otherwise, the sections are assigned to a single field, like "2020/05".
 ///
 /// # Note
 /// It should be mentioned that the derived function for matching will try enum variants in order,
@@ -40,6 +94,46 @@ mod switch;
 /// This can be mitigated by specifying a `!` at the end of your route to inform the matcher that if
 /// any characters are left after matching the route matcher string, the match should fail.
 /// This means that `[to = "/!"]` will match "/" and _only_ "/".
+/// The same can be written as `#[to("/", exact)]`, so a variant listed before the routes nested
+/// beneath it doesn't have to know about `!`.
+///
+/// An empty matcher, `[to = ""]`, is equivalent to `[to = "!"]` and matches only the root.
+/// The root path can be written as either "" or "/", in matchers as well as in routes, so
+/// `[to = ""]` and `[to = "/!"]` both match an empty route and "/".
+/// This makes them useful for the index of a nested switch, which receives an empty route when the
+/// outer matcher consumed all of it.
+///
+/// The `#[to]` attribute can be left off of enum variants, in which case the matcher string is
+/// generated from the name of the variant and its fields: `UserProfile { id: u32 }` is matched by
+/// `/user-profile/{id}`, `Post(u32)` by `/post/{}`, and `Settings` by `/settings`.
+/// Generated matcher strings behave like written ones, so they match any route that they're a
+/// prefix of.
+/// Variant names are converted to kebab-case by default, which can be changed for the whole enum
+/// with `#[switch(rename_all = "snake_case")]`.
+/// `lowercase`, `snake_case`, `kebab-case`, `camelCase` and `PascalCase` are supported.
+///
+/// If declaration order isn't convenient, for example because the enum is generated by another
+/// macro, `#[priority = 10]` can be placed on variants to try them before variants with a lower
+/// priority.
+/// Variants without the attribute have a priority of 0, and variants with the same priority are
+/// tried in declaration order.
+///
+/// `#[switch(deny_shadowed)]` on an enum makes the derive reject variants that can never be
+/// matched because a variant before them matches all of the same routes, like `#[to = "/about"]`
+/// listed after `#[to = "{*:all}"]`.
+/// Stable proc macros can't emit warnings, so shadowed variants are allowed without it.
+///
+/// `#[meta(title = "Settings", requires_auth)]` attaches static metadata to a variant or struct,
+/// which can be read at runtime with `Switch::meta`.
+/// Entries are either flags or keys with string values.
+/// Some keys are understood by the router itself, like `shortcut`, which `RouteShortcuts` navigates
+/// to the variant with.
+///
+/// `#[switch(component = "UserPage")]` on a variant declares the component that it's routed to,
+/// which implements `RoutedComponent` for the enum, so a `Router` given
+/// `Router::render_components` renders the component without a `match`.
+/// The props of the component are built from the fields of the variant: named fields are given to
+/// the props of the same name, and a single unnamed field is converted into the props with `From`.
 ///
 /// -----
 /// There are other attributes as well.
@@ -49,6 +143,20 @@ mod switch;
 /// The `#[rest]` attributes are good if you just want to delegate the whole matching of a variant to a specific
 /// wrapped struct or enum that also implements `Switch`.
 ///
+/// `#[grammar = "v2"]` can be placed on the enum or struct itself to opt all of its matcher strings
+/// into a newer version of the matcher grammar.
+/// Version 2 allows special characters in path and fragment literals to be escaped with a `\`,
+/// so `#[to = r"/hello\!"]` will match "/hello!" instead of treating `!` as the end token.
+/// It reserves `[`, `]`, `(`, `)`, and `|` in those literals for optional segments and alternation,
+/// so they have to be escaped as well.
+/// Matcher strings default to version 1, so their meaning will not change as new syntax is added.
+///
+/// `#[query = "lenient"]` can be placed on the enum or struct itself to guarantee that extra query
+/// parameters and fragments never prevent a matcher from matching, even if it ends with `!`.
+/// `#[query = "strict"]` does the opposite, and rejects routes containing query parameters that the
+/// matcher string doesn't mention.
+/// Both default to `"structural"`, where only the structure of the matcher string decides.
+///
 /// ------
 /// # Example
 /// ```
@@ -76,11 +184,22 @@ mod switch;
 /// }
 /// ```
 /// Check out the examples directory in the repository to see some more usages of the routing syntax.
-#[proc_macro_derive(Switch, attributes(to, rest, end))]
+#[proc_macro_derive(
+    Switch,
+    attributes(to, rest, end, grammar, priority, query, meta, switch)
+)]
 pub fn switch(tokens: TokenStream) -> TokenStream {
     crate::switch::switch_impl(tokens)
 }
 
+/// Validates a route matcher string at compile time and expands to a `RouteMatcher`.
+///
+/// Documentation for users lives on the re-export in `yew_router`.
+#[proc_macro_hack]
+pub fn route(tokens: TokenStream) -> TokenStream {
+    crate::route::route_impl(tokens)
+}
+
 #[proc_macro_attribute]
 pub fn to(_: TokenStream, _: TokenStream) -> TokenStream {
     TokenStream::new()
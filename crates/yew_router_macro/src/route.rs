@@ -0,0 +1,124 @@
+use crate::switch::{build_settings, shadow::ShadowMatcherToken};
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    punctuated::Punctuated,
+    Error, Lit, LitStr, Meta, NestedMeta, Token,
+};
+use yew_router_route_parser::{
+    parse_str_and_optimize_tokens_with_version, FieldType, GrammarVersion, QueryMode,
+};
+
+/// The arguments of `route!`: a matcher string, optionally followed by the settings of the
+/// matcher, like `route!(r"/hello\!", grammar = "v2", case_insensitive)`.
+struct RouteInput {
+    matcher_string: LitStr,
+    complete: bool,
+    case_insensitive: bool,
+    grammar_version: GrammarVersion,
+    query_mode: QueryMode,
+}
+
+impl Parse for RouteInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let matcher_string: LitStr = input.parse()?;
+        let mut route_input = RouteInput {
+            matcher_string,
+            complete: true,
+            case_insensitive: false,
+            grammar_version: GrammarVersion::V1,
+            query_mode: QueryMode::Structural,
+        };
+        if input.is_empty() {
+            return Ok(route_input);
+        }
+        input.parse::<Token![,]>()?;
+        let options = Punctuated::<NestedMeta, Token![,]>::parse_terminated(input)?;
+        for option in options {
+            match option {
+                NestedMeta::Meta(Meta::Path(ref path)) if path.is_ident("case_insensitive") => {
+                    route_input.case_insensitive = true
+                }
+                NestedMeta::Meta(Meta::Path(ref path)) if path.is_ident("incomplete") => {
+                    route_input.complete = false
+                }
+                NestedMeta::Meta(Meta::NameValue(ref mnv)) if mnv.path.is_ident("grammar") => {
+                    route_input.grammar_version = grammar_version(&mnv.lit)?
+                }
+                NestedMeta::Meta(Meta::NameValue(ref mnv)) if mnv.path.is_ident("query") => {
+                    route_input.query_mode = query_mode(&mnv.lit)?
+                }
+                other => {
+                    return Err(Error::new_spanned(
+                        other,
+                        "Unknown option. Expected `grammar`, `query`, `case_insensitive` or \
+                         `incomplete`",
+                    ))
+                }
+            }
+        }
+        Ok(route_input)
+    }
+}
+
+fn grammar_version(lit: &Lit) -> syn::Result<GrammarVersion> {
+    match lit {
+        Lit::Str(s) if s.value() == "v1" => Ok(GrammarVersion::V1),
+        Lit::Str(s) if s.value() == "v2" => Ok(GrammarVersion::V2),
+        _ => Err(Error::new_spanned(
+            lit,
+            "Unknown grammar version. Expected `\"v1\"` or `\"v2\"`",
+        )),
+    }
+}
+
+fn query_mode(lit: &Lit) -> syn::Result<QueryMode> {
+    match lit {
+        Lit::Str(s) if s.value() == "structural" => Ok(QueryMode::Structural),
+        Lit::Str(s) if s.value() == "strict" => Ok(QueryMode::Strict),
+        Lit::Str(s) if s.value() == "lenient" => Ok(QueryMode::Lenient),
+        _ => Err(Error::new_spanned(
+            lit,
+            "Unknown query mode. Expected `\"structural\"`, `\"strict\"` or `\"lenient\"`",
+        )),
+    }
+}
+
+/// Parses the matcher string at compile time, expanding to a `RouteMatcher` containing the
+/// resulting tokens.
+pub fn route_impl(input: TokenStream) -> TokenStream {
+    let RouteInput {
+        matcher_string,
+        complete,
+        case_insensitive,
+        grammar_version,
+        query_mode,
+    } = parse_macro_input!(input as RouteInput);
+
+    let tokens = match parse_str_and_optimize_tokens_with_version(
+        &matcher_string.value(),
+        FieldType::Unnamed,
+        grammar_version,
+    ) {
+        Ok(tokens) => tokens
+            .into_iter()
+            .map(ShadowMatcherToken::from)
+            .collect::<Vec<_>>(),
+        Err(error) => {
+            return crate::span::parse_error(&matcher_string, &error)
+                .to_compile_error()
+                .into()
+        }
+    };
+
+    let settings = build_settings(complete, case_insensitive, grammar_version, query_mode);
+    let token_stream = quote! {
+        ::yew_router::matcher::RouteMatcher {
+            tokens: ::std::vec![#(#tokens),*],
+            settings: #settings,
+        }
+    };
+    TokenStream::from(token_stream)
+}
@@ -0,0 +1,191 @@
+//! Points errors in matcher strings at the offending characters of the string literal.
+use proc_macro2::{Literal, TokenTree};
+use quote::ToTokens;
+use std::ops::Range;
+use syn::{Error, LitStr};
+use yew_router_route_parser::PrettyParseError;
+
+/// Creates an error for a matcher string that couldn't be parsed.
+///
+/// The error underlines the character that the parser failed at, if the compiler supports
+/// narrowing spans to part of a literal, and the whole literal otherwise.
+/// Only nightly compilers support it, as `Literal::subspan` always returns `None` on stable, so
+/// the message of the error, which marks the character with a caret, is what points at it there.
+pub fn parse_error(literal: &LitStr, error: &PrettyParseError) -> Error {
+    let span = literal_token(literal)
+        .and_then(|token| {
+            let range = source_range(&token, error.span())?;
+            token.subspan(range)
+        })
+        .unwrap_or_else(|| literal.span());
+    Error::new(span, error)
+}
+
+/// Gets the token of the literal, which knows how the literal was written in the source.
+fn literal_token(literal: &LitStr) -> Option<Literal> {
+    match literal.to_token_stream().into_iter().next()? {
+        TokenTree::Literal(token) => Some(token),
+        _ => None,
+    }
+}
+
+/// Maps a byte range of the value of a string literal to the byte range of the literal as it was
+/// written in the source, which includes the quotes and escape sequences.
+///
+/// Failures at the end of the value point at the closing quote.
+fn source_range(literal: &Literal, range: Range<usize>) -> Option<Range<usize>> {
+    let source = literal.to_string();
+    let offsets = value_offsets(&source)?;
+    let source_offset = |value_offset: usize| {
+        offsets
+            .iter()
+            .find(|(value, _)| *value == value_offset)
+            .map(|(_, source)| *source)
+    };
+    let start = source_offset(range.start)?;
+    if range.start == range.end {
+        Some(start..start + 1)
+    } else {
+        Some(start..source_offset(range.end)?)
+    }
+}
+
+/// Pairs the offset of every character boundary in the value of a string literal with its offset
+/// in the source of the literal.
+///
+/// The last pair holds the end of the value and the offset of the closing quote.
+fn value_offsets(source: &str) -> Option<Vec<(usize, usize)>> {
+    let mut offsets = Vec::new();
+    let mut value_offset = 0;
+    if source.starts_with('r') {
+        // Raw strings don't have escape sequences.
+        let start = source.find('"')? + 1;
+        let end = source.rfind('"')?;
+        for (index, c) in source[start..end].char_indices() {
+            offsets.push((index, start + index));
+            value_offset = index + c.len_utf8();
+        }
+        offsets.push((value_offset, end));
+        return Some(offsets);
+    }
+
+    let end = source.rfind('"')?;
+    let mut chars = source[..end].char_indices().skip(1).peekable();
+    while let Some((index, c)) = chars.next() {
+        offsets.push((value_offset, index));
+        if c != '\\' {
+            value_offset += c.len_utf8();
+            continue;
+        }
+        match chars.next()?.1 {
+            'u' => {
+                let mut code = String::new();
+                for (_, c) in chars.by_ref() {
+                    match c {
+                        '{' => {}
+                        '}' => break,
+                        _ => code.push(c),
+                    }
+                }
+                let c = u32::from_str_radix(&code, 16)
+                    .ok()
+                    .and_then(std::char::from_u32)?;
+                value_offset += c.len_utf8();
+            }
+            'x' => {
+                chars.next();
+                chars.next();
+                value_offset += 1;
+            }
+            '\n' => {
+                // A line continuation skips the whitespace at the start of the next line.
+                offsets.pop();
+                while let Some((_, c)) = chars.peek() {
+                    if c.is_whitespace() {
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+            }
+            _ => value_offset += 1,
+        }
+    }
+    offsets.push((value_offset, end));
+    Some(offsets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn literal(source: &str) -> Literal {
+        match source
+            .parse::<proc_macro2::TokenStream>()
+            .unwrap()
+            .into_iter()
+            .next()
+        {
+            Some(TokenTree::Literal(literal)) => literal,
+            _ => panic!("{} is not a literal", source),
+        }
+    }
+
+    #[test]
+    fn value_offsets_of_plain_strings() {
+        assert_eq!(
+            value_offsets(r#""/{a}""#),
+            Some(vec![(0, 1), (1, 2), (2, 3), (3, 4), (4, 5)])
+        );
+        assert_eq!(value_offsets(r#""""#), Some(vec![(0, 1)]));
+    }
+
+    #[test]
+    fn value_offsets_skip_escape_sequences() {
+        // The value is a tab, an `é`, and an `x`.
+        assert_eq!(
+            value_offsets(r#""\t\u{e9}x""#),
+            Some(vec![(0, 1), (1, 3), (3, 9), (4, 10)])
+        );
+        // The value is "aAb".
+        assert_eq!(
+            value_offsets(r#""a\x41b""#),
+            Some(vec![(0, 1), (1, 2), (2, 6), (3, 7)])
+        );
+        // The value is "ab", as the line continuation and the indentation that follows it aren't
+        // part of it.
+        assert_eq!(
+            value_offsets("\"a\\\n    b\""),
+            Some(vec![(0, 1), (1, 8), (2, 9)])
+        );
+    }
+
+    #[test]
+    fn value_offsets_of_raw_strings() {
+        assert_eq!(
+            value_offsets(r###"r#"\{"#"###),
+            Some(vec![(0, 3), (1, 4), (2, 5)])
+        );
+    }
+
+    #[test]
+    fn source_range_covers_escape_sequences() {
+        let token = literal(r#""/\u{e9}/{0a}""#);
+        assert_eq!(source_range(&token, 1..3), Some(2..8));
+        assert_eq!(source_range(&token, 5..6), Some(10..11));
+    }
+
+    #[test]
+    fn source_range_at_the_end_points_at_the_closing_quote() {
+        let token = literal(r#""/{a""#);
+        assert_eq!(source_range(&token, 3..3), Some(4..5));
+        let token = literal(r##"r#"/{a"#"##);
+        assert_eq!(source_range(&token, 3..3), Some(6..7));
+    }
+
+    #[test]
+    fn source_range_inside_a_character_is_rejected() {
+        let token = literal(r#""/é""#);
+        assert_eq!(source_range(&token, 2..3), None);
+    }
+}
@@ -1,31 +1,46 @@
 use crate::switch::{
     enum_impl::generate_enum_impl,
+    rename::generated_matcher_string,
     shadow::{ShadowCaptureVariant, ShadowMatcherToken},
     struct_impl::generate_struct_impl,
 };
 use proc_macro::TokenStream;
 use proc_macro2::Span;
 use quote::quote;
-use syn::{export::TokenStream2, parse_macro_input, Data, DeriveInput, Fields, Ident, Variant};
+use std::cmp::Reverse;
+use syn::{
+    export::TokenStream2, parse_macro_input, Data, DeriveInput, Field, Fields, Ident, LitStr, Path,
+    Type, Variant,
+};
+use yew_router_route_parser::{CaptureKind, GrammarVersion, QueryMode};
 
 mod attribute;
 mod enum_impl;
-mod shadow;
+mod rename;
+pub mod shadow;
 mod struct_impl;
 
-use self::attribute::AttrToken;
+use self::attribute::{AttrToken, FieldValidator};
 
 /// Holds data that is required to derive Switch for a struct or a single enum variant.
 pub struct SwitchItem {
     pub matcher: Vec<ShadowMatcherToken>,
+    /// The matcher string as it was written in the attributes.
+    pub matcher_string: String,
     pub ident: Ident,
     pub fields: Fields,
+    /// The entries of the `meta` attributes.
+    pub meta: Vec<(String, Option<String>)>,
+    /// The component that the variant is routed to.
+    pub component: Option<Path>,
 }
 
 pub fn switch_impl(input: TokenStream) -> TokenStream {
     let input: DeriveInput = parse_macro_input!(input as DeriveInput);
 
     let ident: Ident = input.ident;
+    let grammar_version = AttrToken::grammar_version(&input.attrs);
+    let query_mode = AttrToken::query_mode(&input.attrs);
 
     match input.data {
         Data::Struct(ds) => {
@@ -33,21 +48,39 @@ pub fn switch_impl(input: TokenStream) -> TokenStream {
                 Fields::Unnamed(_) | Fields::Unit => yew_router_route_parser::FieldType::Unnamed,
                 Fields::Named(_) => yew_router_route_parser::FieldType::Named,
             };
-            let matcher = AttrToken::convert_attributes_to_tokens(input.attrs)
-                .into_iter()
-                .enumerate()
-                .map(|(index, at)| at.into_shadow_matcher_tokens(index, field_type))
-                .flatten()
-                .collect::<Vec<_>>();
+            if AttrToken::priority(&input.attrs) != 0 {
+                panic!("`priority` can only be used on enum variants.")
+            }
+            let meta = AttrToken::meta(&input.attrs);
+            if AttrToken::rename_all(&input.attrs).is_some() {
+                panic!("`rename_all` can only be used on enums.")
+            }
+            if AttrToken::deny_shadowed(&input.attrs) {
+                panic!("`deny_shadowed` can only be used on enums.")
+            }
+            let attr_tokens = AttrToken::convert_attributes_to_tokens(input.attrs);
+            let matcher_string = AttrToken::matcher_string(&attr_tokens);
+            let matcher = match shadow_matcher_tokens(attr_tokens, field_type, grammar_version) {
+                Ok(matcher) => matcher,
+                Err(error) => return error.to_compile_error().into(),
+            };
+            assert_end_terminates(&matcher);
+            let matcher = bind_numbered_sections(matcher, &ds.fields);
+            let matcher = rename_captures(matcher, &ds.fields);
             let switch_item = SwitchItem {
                 matcher,
+                matcher_string,
                 ident,
                 fields: ds.fields,
+                meta,
+                component: None,
             };
-            generate_struct_impl(switch_item)
+            generate_struct_impl(switch_item, grammar_version, query_mode)
         }
         Data::Enum(de) => {
-            let switch_variants = de
+            let rename_rule = AttrToken::rename_all(&input.attrs).unwrap_or_default();
+            let deny_shadowed = AttrToken::deny_shadowed(&input.attrs);
+            let prioritized_variants = de
                 .variants
                 .into_iter()
                 .map(|variant: Variant| {
@@ -57,25 +90,194 @@ pub fn switch_impl(input: TokenStream) -> TokenStream {
                         }
                         Fields::Named(_) => yew_router_route_parser::FieldType::Named,
                     };
-                    let matcher = AttrToken::convert_attributes_to_tokens(variant.attrs)
-                        .into_iter()
-                        .enumerate()
-                        .map(|(index, at)| at.into_shadow_matcher_tokens(index, field_type))
-                        .flatten()
-                        .collect::<Vec<_>>();
-                    SwitchItem {
+                    let priority = AttrToken::priority(&variant.attrs);
+                    let meta = AttrToken::meta(&variant.attrs);
+                    let component = AttrToken::component(&variant.attrs);
+                    let mut attr_tokens = AttrToken::convert_attributes_to_tokens(variant.attrs);
+                    if attr_tokens.is_empty() {
+                        let generated =
+                            generated_matcher_string(&variant.ident, &variant.fields, rename_rule);
+                        let generated = LitStr::new(&generated, variant.ident.span());
+                        attr_tokens.push(AttrToken::To(generated));
+                    }
+                    let matcher_string = AttrToken::matcher_string(&attr_tokens);
+                    let matcher = shadow_matcher_tokens(attr_tokens, field_type, grammar_version)?;
+                    assert_end_terminates(&matcher);
+                    let matcher = bind_numbered_sections(matcher, &variant.fields);
+                    let matcher = rename_captures(matcher, &variant.fields);
+                    let switch_item = SwitchItem {
                         matcher,
+                        matcher_string,
                         ident: variant.ident,
                         fields: variant.fields,
-                    }
+                        meta,
+                        component,
+                    };
+                    Ok((priority, switch_item))
                 })
+                .collect::<syn::Result<Vec<(u64, SwitchItem)>>>();
+            let mut prioritized_variants = match prioritized_variants {
+                Ok(prioritized_variants) => prioritized_variants,
+                Err(error) => return error.to_compile_error().into(),
+            };
+            // Variants with a higher priority are tried first.
+            // The sort is stable, so declaration order breaks ties.
+            prioritized_variants.sort_by_key(|(priority, _)| Reverse(*priority));
+            let switch_variants = prioritized_variants
+                .into_iter()
+                .map(|(_, switch_item)| switch_item)
                 .collect::<Vec<SwitchItem>>();
-            generate_enum_impl(ident, switch_variants)
+            generate_enum_impl(
+                ident,
+                switch_variants,
+                grammar_version,
+                query_mode,
+                deny_shadowed,
+            )
         }
         Data::Union(_du) => panic!("Deriving FromCaptures not supported for Unions."),
     }
 }
 
+/// Parses the matcher strings of the attributes, and joins their tokens into a single matcher.
+fn shadow_matcher_tokens(
+    attr_tokens: Vec<AttrToken>,
+    field_type: yew_router_route_parser::FieldType,
+    grammar_version: GrammarVersion,
+) -> syn::Result<Vec<ShadowMatcherToken>> {
+    let mut matcher = Vec::new();
+    for (index, at) in attr_tokens.into_iter().enumerate() {
+        matcher.extend(at.into_shadow_matcher_tokens(index, field_type, grammar_version)?);
+    }
+    Ok(matcher)
+}
+
+/// Ensures that the end token, if present, is the last token of the matcher.
+///
+/// Tokens after the end token could never be matched, so this indicates that the attributes were
+/// specified in the wrong order.
+fn assert_end_terminates(matcher: &[ShadowMatcherToken]) {
+    let end_position = matcher.iter().position(|token| match token {
+        ShadowMatcherToken::End => true,
+        _ => false,
+    });
+    if let Some(position) = end_position {
+        if position != matcher.len() - 1 {
+            panic!(
+                "Nothing may follow the end token (`!` or `#[end]`), as it could never be matched."
+            )
+        }
+    }
+}
+
+/// Splits numbered unnamed captures, like `{2}`, into a capture for each of their sections, if the
+/// tuple has a field for each of those sections.
+///
+/// Otherwise, the sections of a numbered capture are captured together, into a single field.
+fn bind_numbered_sections(
+    matcher: Vec<ShadowMatcherToken>,
+    fields: &Fields,
+) -> Vec<ShadowMatcherToken> {
+    let field_count = match fields {
+        Fields::Unnamed(fields_unnamed) => fields_unnamed.unnamed.len(),
+        Fields::Named(_) | Fields::Unit => return matcher,
+    };
+    let captures = matcher
+        .iter()
+        .filter(|token| match token {
+            ShadowMatcherToken::Capture(_) => true,
+            _ => false,
+        })
+        .count();
+    let sections: usize = matcher
+        .iter()
+        .map(|token| match token {
+            ShadowMatcherToken::Capture(ShadowCaptureVariant::NumberedUnnamed { sections }) => {
+                *sections
+            }
+            ShadowMatcherToken::Capture(_) => 1,
+            _ => 0,
+        })
+        .sum();
+    if sections == captures || sections != field_count {
+        return matcher;
+    }
+    let mut tokens = Vec::with_capacity(matcher.len());
+    for token in matcher {
+        match token {
+            ShadowMatcherToken::Capture(ShadowCaptureVariant::NumberedUnnamed { sections }) => {
+                for section in 0..sections {
+                    if section > 0 {
+                        tokens.push(ShadowMatcherToken::Exact("/".to_string()));
+                    }
+                    tokens.push(ShadowMatcherToken::Capture(ShadowCaptureVariant::Unnamed));
+                }
+            }
+            token => tokens.push(token),
+        }
+    }
+    tokens
+}
+
+/// Renames the captures that fields are bound to with `#[switch(rename = "...")]` after those
+/// fields, so the rest of the derive can find the field of a capture by its name.
+///
+/// The matcher string itself, which is reported by `Switch::routes`, is left as it was written.
+fn rename_captures(
+    mut matcher: Vec<ShadowMatcherToken>,
+    fields: &Fields,
+) -> Vec<ShadowMatcherToken> {
+    let fields_named = match fields {
+        Fields::Named(fields_named) => fields_named,
+        Fields::Unnamed(fields_unnamed) => {
+            if fields_unnamed
+                .unnamed
+                .iter()
+                .any(|field| AttrToken::rename(&field.attrs).is_some())
+            {
+                panic!("`rename` can only be used on named fields, as the captures of tuples are assigned in order.")
+            }
+            return matcher;
+        }
+        Fields::Unit => return matcher,
+    };
+    // Pairs of capture names and the names of the fields they are bound to.
+    let renames: Vec<(String, String)> = fields_named
+        .named
+        .iter()
+        .filter_map(|field| {
+            let rename = AttrToken::rename(&field.attrs)?;
+            let ident = field.ident.as_ref()?.to_string();
+            Some((rename, ident))
+        })
+        .collect();
+    if renames.is_empty() {
+        return matcher;
+    }
+    for token in &mut matcher {
+        let name = match token {
+            ShadowMatcherToken::Capture(ShadowCaptureVariant::Named(name))
+            | ShadowMatcherToken::Capture(ShadowCaptureVariant::ManyNamed(name))
+            | ShadowMatcherToken::Capture(ShadowCaptureVariant::GreedyManyNamed(name))
+            | ShadowMatcherToken::Capture(ShadowCaptureVariant::NumberedNamed { name, .. })
+            | ShadowMatcherToken::Capture(ShadowCaptureVariant::SeparatedNamed { name, .. })
+            | ShadowMatcherToken::Capture(ShadowCaptureVariant::TypedNamed { name, .. })
+            | ShadowMatcherToken::Capture(ShadowCaptureVariant::RepeatedNamed { name, .. }) => name,
+            _ => continue,
+        };
+        if let Some((_, ident)) = renames.iter().find(|(rename, _)| rename == name) {
+            *name = ident.clone();
+        } else if let Some((rename, ident)) = renames.iter().find(|(_, ident)| ident == name) {
+            panic!(
+                "The capture `{{{ident}}}` can't be bound to the field `{ident}`, because that field is renamed to `{rename}`.",
+                ident = ident,
+                rename = rename
+            )
+        }
+    }
+    matcher
+}
+
 trait Flatten<T> {
     /// Because flatten is a nightly feature. I'm making a new variant of the function here for
     /// stable use. The naming is changed to avoid this getting clobbered when object_flattening
@@ -92,12 +294,15 @@ impl<T> Flatten<T> for Option<Option<T>> {
     }
 }
 
-fn build_matcher_from_tokens(tokens: &[ShadowMatcherToken]) -> TokenStream2 {
+fn build_matcher_from_tokens(
+    tokens: &[ShadowMatcherToken],
+    grammar_version: GrammarVersion,
+    query_mode: QueryMode,
+) -> TokenStream2 {
+    // Allow incomplete matches. // TODO investigate if this is necessary here.
+    let settings = build_settings(false, true, grammar_version, query_mode);
     quote! {
-        let settings = ::yew_router::matcher::MatcherSettings {
-            complete: false, // Allow incomplete matches. // TODO investigate if this is necessary here.
-            case_insensitive: true,
-        };
+        let settings = #settings;
         let matcher = ::yew_router::matcher::RouteMatcher {
             tokens: ::std::vec![#(#tokens),*],
             settings
@@ -105,6 +310,298 @@ fn build_matcher_from_tokens(tokens: &[ShadowMatcherToken]) -> TokenStream2 {
     }
 }
 
+/// Builds the `MatcherSettings` that a generated matcher is created with.
+pub fn build_settings(
+    complete: bool,
+    case_insensitive: bool,
+    grammar_version: GrammarVersion,
+    query_mode: QueryMode,
+) -> TokenStream2 {
+    let grammar_version = build_grammar_version(grammar_version);
+    let query_mode = match query_mode {
+        QueryMode::Structural => quote! {::yew_router::matcher::QueryMode::Structural},
+        QueryMode::Strict => quote! {::yew_router::matcher::QueryMode::Strict},
+        QueryMode::Lenient => quote! {::yew_router::matcher::QueryMode::Lenient},
+    };
+    quote! {
+        ::yew_router::matcher::MatcherSettings {
+            complete: #complete,
+            case_insensitive: #case_insensitive,
+            grammar_version: #grammar_version,
+            query_mode: #query_mode,
+        }
+    }
+}
+
+/// Builds the path of a `GrammarVersion` variant.
+fn build_grammar_version(grammar_version: GrammarVersion) -> TokenStream2 {
+    match grammar_version {
+        GrammarVersion::V1 => quote! {::yew_router::matcher::GrammarVersion::V1},
+        GrammarVersion::V2 => quote! {::yew_router::matcher::GrammarVersion::V2},
+    }
+}
+
+/// Builds a `RouteInfo` describing the route of a struct or enum variant.
+fn build_route_info(switch_item: &SwitchItem, grammar_version: GrammarVersion) -> TokenStream2 {
+    let SwitchItem {
+        matcher,
+        matcher_string,
+        ident,
+        fields,
+        ..
+    } = switch_item;
+    let name = ident.to_string();
+    let captures = matcher
+        .iter()
+        .filter_map(|token| match token {
+            ShadowMatcherToken::Capture(capture) => Some(capture),
+            _ => None,
+        })
+        .enumerate()
+        .map(|(index, capture)| {
+            let (name, ty) = match fields {
+                Fields::Named(fields_named) => {
+                    let name = match capture {
+                        ShadowCaptureVariant::Named(name)
+                        | ShadowCaptureVariant::ManyNamed(name)
+                        | ShadowCaptureVariant::GreedyManyNamed(name)
+                        | ShadowCaptureVariant::NumberedNamed { name, .. }
+                        | ShadowCaptureVariant::SeparatedNamed { name, .. }
+                        | ShadowCaptureVariant::TypedNamed { name, .. }
+                        | ShadowCaptureVariant::RepeatedNamed { name, .. } => Some(name.clone()),
+                        ShadowCaptureVariant::Unnamed
+                        | ShadowCaptureVariant::ManyUnnamed
+                        | ShadowCaptureVariant::GreedyManyUnnamed
+                        | ShadowCaptureVariant::NumberedUnnamed { .. }
+                        | ShadowCaptureVariant::SeparatedUnnamed { .. }
+                        | ShadowCaptureVariant::TypedUnnamed { .. }
+                        | ShadowCaptureVariant::RepeatedUnnamed { .. } => None,
+                    };
+                    let ty = name.as_ref().and_then(|name| {
+                        fields_named
+                            .named
+                            .iter()
+                            .find(|field| field.ident.as_ref().map_or(false, |i| i == name))
+                            .map(|field| &field.ty)
+                    });
+                    (name, ty)
+                }
+                Fields::Unnamed(fields_unnamed) => (
+                    None,
+                    fields_unnamed
+                        .unnamed
+                        .iter()
+                        .nth(index)
+                        .map(|field| &field.ty),
+                ),
+                Fields::Unit => (None, None),
+            };
+            let name = optional_str(name);
+            let ty = type_name(ty);
+            quote! {
+                ::yew_router::switch::CaptureInfo {
+                    name: #name,
+                    ty: #ty,
+                }
+            }
+        });
+    // Matchers without captures only match the route made up of their literals.
+    let has_captures = matcher.iter().any(|token| match token {
+        ShadowMatcherToken::Capture(_) => true,
+        _ => false,
+    });
+    let route = if has_captures {
+        None
+    } else {
+        Some(
+            matcher
+                .iter()
+                .filter_map(|token| match token {
+                    ShadowMatcherToken::Exact(literal) => Some(literal.as_str()),
+                    _ => None,
+                })
+                .collect::<String>(),
+        )
+    };
+    let route = optional_str(route);
+    let grammar_version = build_grammar_version(grammar_version);
+    let meta = build_route_meta(switch_item);
+    quote! {
+        ::yew_router::switch::RouteInfo {
+            name: #name,
+            matcher: #matcher_string,
+            captures: ::std::vec![#(#captures),*],
+            route: #route,
+            grammar_version: #grammar_version,
+            meta: #meta,
+        }
+    }
+}
+
+/// Builds the `RouteMeta` of a struct or enum variant.
+fn build_route_meta(switch_item: &SwitchItem) -> TokenStream2 {
+    let entries = switch_item.meta.iter().map(|(key, value)| {
+        let value = optional_str(value.clone());
+        quote! {(#key, #value)}
+    });
+    quote! {
+        ::yew_router::switch::RouteMeta::new(&[#(#entries),*])
+    }
+}
+
+/// Builds the check of a field with `#[switch(validate)]`, which rejects the struct or enum variant
+/// when the value that was created for the field isn't valid, so the next one is tried.
+///
+/// The check expects the value to be bound to `val`, the state to `s`, and the captured string
+/// to `value`.
+fn build_field_validation(field: &Field, item_name: &str, field_name: &str) -> TokenStream2 {
+    let field_ty = &field.ty;
+    let is_valid = match AttrToken::validator(&field.attrs) {
+        Some(FieldValidator::Trait) => {
+            quote! {<#field_ty as ::yew_router::switch::Validate>::validate(&val)}
+        }
+        Some(FieldValidator::Function(path)) => quote! {{
+            let validate: fn(&#field_ty) -> bool = #path;
+            validate(&val)
+        }},
+        None => return quote! {},
+    };
+    quote! {
+        if !#is_valid {
+            ::yew_router::switch::reject_field(#item_name, #field_name, value);
+            return (::std::option::Option::None, s)
+        }
+    }
+}
+
+/// Skips the matching of a struct or enum variant that is gated behind a feature flag with
+/// `#[meta(flag = "name")]` while the flag is disabled.
+///
+/// The matching is expected to bind the state that it didn't consume to `state`, and the flags to
+/// `flags`.
+fn build_flag_guard(switch_item: &SwitchItem, matching: TokenStream2) -> TokenStream2 {
+    let flag = match switch_item.meta.iter().find(|(key, _)| key == "flag") {
+        Some((_, Some(flag))) => flag,
+        Some((_, None)) => {
+            panic!("`flag` in `meta` must name the flag, like `#[meta(flag = \"beta\")]`")
+        }
+        None => return matching,
+    };
+    quote! {
+        let mut state = if flags.is_enabled(#flag) {
+            #matching
+            state
+        } else {
+            state
+        };
+    }
+}
+
+/// A field that the rest of the route is delegated to.
+pub(crate) enum NestedField<'a> {
+    Named(&'a Ident),
+    Unnamed { index: usize },
+}
+
+/// Finds the field that is captured by a trailing `{*}` section, which holds the nested route.
+pub(crate) fn nested_field(switch_item: &SwitchItem) -> Option<NestedField> {
+    let tokens: Vec<&ShadowMatcherToken> = switch_item
+        .matcher
+        .iter()
+        .filter(|token| match token {
+            ShadowMatcherToken::End => false,
+            _ => true,
+        })
+        .collect();
+    let capture = match tokens.last()? {
+        ShadowMatcherToken::Capture(capture) => capture,
+        _ => return None,
+    };
+    match (capture, &switch_item.fields) {
+        (ShadowCaptureVariant::ManyNamed(name), Fields::Named(fields_named))
+        | (ShadowCaptureVariant::GreedyManyNamed(name), Fields::Named(fields_named)) => {
+            fields_named
+                .named
+                .iter()
+                .filter_map(|field| field.ident.as_ref())
+                .find(|ident| *ident == name)
+                .map(NestedField::Named)
+        }
+        (ShadowCaptureVariant::ManyNamed(_), Fields::Unnamed(fields_unnamed))
+        | (ShadowCaptureVariant::GreedyManyNamed(_), Fields::Unnamed(fields_unnamed))
+        | (ShadowCaptureVariant::ManyUnnamed, Fields::Unnamed(fields_unnamed))
+        | (ShadowCaptureVariant::GreedyManyUnnamed, Fields::Unnamed(fields_unnamed)) => {
+            // Captures are assigned to unnamed fields in order.
+            let index = tokens
+                .iter()
+                .filter(|token| match token {
+                    ShadowMatcherToken::Capture(_) => true,
+                    _ => false,
+                })
+                .count()
+                - 1;
+            if index < fields_unnamed.unnamed.len() {
+                Some(NestedField::Unnamed { index })
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Expands to a `Vec<RouteMeta>` of the item's metadata, followed by that of the nested route, if
+/// the item has one.
+///
+/// The nested route is expected to be bound to `nested`.
+fn build_nested_meta(switch_item: &SwitchItem, has_nested: bool) -> TokenStream2 {
+    let route_meta = build_route_meta(switch_item);
+    if has_nested {
+        quote! {
+            let mut nested_meta = ::std::vec![#route_meta];
+            nested_meta.extend(::yew_router::Switch::nested_meta(nested));
+            nested_meta
+        }
+    } else {
+        quote! {
+            ::std::vec![#route_meta]
+        }
+    }
+}
+
+/// Expands to an `Option<String>` of the nested route, if the item has one.
+///
+/// The nested route is expected to be bound to `nested`.
+fn build_nested_route(has_nested: bool) -> TokenStream2 {
+    if has_nested {
+        quote! {
+            let mut buf = ::std::string::String::new();
+            ::yew_router::Switch::build_route_section::<()>(nested, &mut buf);
+            ::std::option::Option::Some(buf)
+        }
+    } else {
+        quote! {
+            ::std::option::Option::None
+        }
+    }
+}
+
+/// Expands to an `Option<&'static str>`.
+fn optional_str(s: Option<String>) -> TokenStream2 {
+    match s {
+        Some(s) => quote! {::std::option::Option::Some(#s)},
+        None => quote! {::std::option::Option::None},
+    }
+}
+
+/// Expands to an `Option<&'static str>` holding the name of the type, as `stringify!` renders it.
+fn type_name(ty: Option<&Type>) -> TokenStream2 {
+    match ty {
+        Some(ty) => quote! {::std::option::Option::Some(::std::stringify!(#ty))},
+        None => quote! {::std::option::Option::None},
+    }
+}
+
 /// Enum indicating which sort of writer is needed.
 pub(crate) enum FieldType {
     Named,
@@ -112,62 +609,192 @@ pub(crate) enum FieldType {
     Unit,
 }
 
+/// Which method of `Switch` the serializer makes up the body of.
+#[derive(Clone, Copy)]
+pub(crate) enum Build {
+    /// `build_encoded_route_section`, which builds every value as it is.
+    Infallible,
+    /// `try_build_route_section`, which fails for values that wouldn't switch back to themselves.
+    Fallible,
+}
+
+/// Determines if the capture at the index is the extension of a file name, like `{ext}` in
+/// `/files/{name:stem}.{ext}`.
+///
+/// This mirrors `yew_router_route_parser::matcher::is_extension` for the shadow tokens.
+fn is_extension(matcher: &[ShadowMatcherToken], index: usize) -> bool {
+    index >= 2
+        && match (&matcher[index - 2], &matcher[index - 1]) {
+            (
+                ShadowMatcherToken::Capture(ShadowCaptureVariant::TypedNamed {
+                    kind: CaptureKind::Stem,
+                    ..
+                }),
+                ShadowMatcherToken::Exact(literal),
+            )
+            | (
+                ShadowMatcherToken::Capture(ShadowCaptureVariant::TypedUnnamed {
+                    kind: CaptureKind::Stem,
+                }),
+                ShadowMatcherToken::Exact(literal),
+            ) => !literal.contains(|c| "/?#&=".contains(c)),
+            _ => false,
+        }
+}
+
 /// This assumes that the variant/struct has been destructured.
-fn write_for_token(token: &ShadowMatcherToken, naming_scheme: FieldType) -> TokenStream2 {
-    match token {
+fn write_for_token(
+    matcher: &[ShadowMatcherToken],
+    index: usize,
+    naming_scheme: FieldType,
+    build: Build,
+    item_name: &str,
+) -> TokenStream2 {
+    match &matcher[index] {
         ShadowMatcherToken::Exact(lit) => {
             quote! {
-                write!(buf, #lit).unwrap();
+                write!(buf, "{}", #lit).unwrap();
             }
         }
-        ShadowMatcherToken::Capture(capture) => match naming_scheme {
-            FieldType::Named | FieldType::Unit => match &capture {
-                ShadowCaptureVariant::Named(name)
-                | ShadowCaptureVariant::ManyNamed(name)
-                | ShadowCaptureVariant::NumberedNamed { name, .. } => {
-                    let name = Ident::new(&name, Span::call_site());
+        ShadowMatcherToken::Capture(capture) => {
+            let (field, field_name, buf) = match naming_scheme {
+                // Unit variants have no field that holds the captured value, so it is left
+                // out of the route.
+                FieldType::Unit => return quote! {},
+                FieldType::Named => match &capture {
+                    ShadowCaptureVariant::Named(name)
+                    | ShadowCaptureVariant::ManyNamed(name)
+                    | ShadowCaptureVariant::GreedyManyNamed(name)
+                    | ShadowCaptureVariant::NumberedNamed { name, .. }
+                    | ShadowCaptureVariant::SeparatedNamed { name, .. }
+                    | ShadowCaptureVariant::TypedNamed { name, .. }
+                    | ShadowCaptureVariant::RepeatedNamed { name, .. } => (
+                        Ident::new(&name, Span::call_site()),
+                        name.clone(),
+                        quote! {buf},
+                    ),
+                    ShadowCaptureVariant::Unnamed
+                    | ShadowCaptureVariant::ManyUnnamed
+                    | ShadowCaptureVariant::GreedyManyUnnamed
+                    | ShadowCaptureVariant::NumberedUnnamed { .. }
+                    | ShadowCaptureVariant::SeparatedUnnamed { .. }
+                    | ShadowCaptureVariant::TypedUnnamed { .. }
+                    | ShadowCaptureVariant::RepeatedUnnamed { .. } => {
+                        panic!("Unnamed matcher sections not allowed for named field types")
+                    }
+                },
+                FieldType::Unnamed { index } => (
+                    unnamed_field_index_item(index),
+                    index.to_string(),
+                    quote! {&mut buf},
+                ),
+            };
+            let build_section = match build {
+                Build::Infallible => quote! {
+                    #field.build_encoded_route_section(#buf, encoding)
+                },
+                Build::Fallible => quote! {
+                    #field.try_build_route_section(#buf, encoding)?
+                },
+            };
+            match &capture {
+                // The values are built joined by `&`, with the `&`s within them escaped, so
+                // each `&` is then replaced with the parameter that precedes the next value.
+                // They are built onto the route, so they are encoded for the query.
+                ShadowCaptureVariant::RepeatedNamed { param, .. }
+                | ShadowCaptureVariant::RepeatedUnnamed { param } => {
+                    let separator = format!("&{}=", param);
                     quote! {
-                        state = state.or(#name.build_route_section(buf));
+                        let __start = buf.len();
+                        state = state.or(#build_section);
+                        let __section = buf.split_off(__start);
+                        write!(buf, "{}", __section.replace('&', #separator)).unwrap();
                     }
                 }
-                ShadowCaptureVariant::Unnamed
-                | ShadowCaptureVariant::ManyUnnamed
-                | ShadowCaptureVariant::NumberedUnnamed { .. } => {
-                    panic!("Unnamed matcher sections not allowed for named field types")
-                }
-            },
-            FieldType::Unnamed { index } => {
-                let name = unnamed_field_index_item(index);
-                quote! {
-                    state = state.or(#name.build_route_section(&mut buf));
-                }
+                // An extension that contains a `.` would be split at it when the route is matched,
+                // so the stem would end there instead.
+                _ if is_extension(matcher, index) => match build {
+                    Build::Infallible => quote! {
+                        state = state.or(#build_section);
+                    },
+                    Build::Fallible => quote! {
+                        let __start = buf.len();
+                        state = state.or(#build_section);
+                        if buf[__start..].contains('.') {
+                            return ::std::result::Result::Err(
+                                ::yew_router::switch::SwitchError::Unbuildable {
+                                    item: #item_name,
+                                    field: #field_name,
+                                    value: buf[__start..].to_string(),
+                                }
+                            );
+                        }
+                    },
+                },
+                _ => quote! {
+                    state = state.or(#build_section);
+                },
             }
-        },
+        }
         ShadowMatcherToken::End => quote! {},
     }
 }
 
-/// The serializer makes up the body of `build_route_section`.
+/// Expands to the writers of the tokens of a struct or enum variant, which expect its fields to
+/// be destructured.
+fn build_writers(switch_item: &SwitchItem, build: Build, item_name: &str) -> Vec<TokenStream2> {
+    let SwitchItem {
+        matcher, fields, ..
+    } = switch_item;
+    let mut item_count = 0;
+    (0..matcher.len())
+        .map(|index| {
+            let naming_scheme = match (fields, &matcher[index]) {
+                (Fields::Named(_), _) => FieldType::Named,
+                (Fields::Unnamed(_), ShadowMatcherToken::Capture(_)) => {
+                    item_count += 1;
+                    FieldType::Unnamed {
+                        index: item_count - 1,
+                    }
+                }
+                // Its either a literal, or something that will panic currently
+                (Fields::Unnamed(_), _) | (Fields::Unit, _) => FieldType::Unit,
+            };
+            write_for_token(matcher, index, naming_scheme, build, item_name)
+        })
+        .collect()
+}
+
+/// Expands to the end of the serializer, which returns the state.
+fn return_state(build: Build) -> TokenStream2 {
+    match build {
+        Build::Infallible => quote! {
+            return state;
+        },
+        Build::Fallible => quote! {
+            return ::std::result::Result::Ok(state);
+        },
+    }
+}
+
+/// The serializer makes up the body of `build_encoded_route_section`, or of
+/// `try_build_route_section`.
 pub fn build_serializer_for_enum(
     switch_items: &[SwitchItem],
     enum_ident: &Ident,
     match_item: &Ident,
+    build: Build,
 ) -> TokenStream2 {
     let variants = switch_items.iter().map(|switch_item: &SwitchItem| {
-        let SwitchItem {
-            matcher,
-            ident,
-            fields,
-        } = switch_item;
+        let SwitchItem { ident, fields, .. } = switch_item;
+        let item_name = format!("{}::{}", enum_ident, ident);
+        let writers = build_writers(switch_item, build, &item_name);
         match fields {
             Fields::Named(fields_named) => {
                 let field_names = fields_named
                     .named
                     .iter()
                     .filter_map(|named| named.ident.as_ref());
-                let writers = matcher
-                    .iter()
-                    .map(|token| write_for_token(token, FieldType::Named));
                 quote! {
                     #enum_ident::#ident{#(#field_names),*} => {
                         #(#writers)*
@@ -180,17 +807,6 @@ pub fn build_serializer_for_enum(
                     .iter()
                     .enumerate()
                     .map(|(index, _)| unnamed_field_index_item(index));
-                let mut item_count = 0;
-                let writers = matcher.iter().map(|token| {
-                    if let ShadowMatcherToken::Capture(_) = &token {
-                        let ts = write_for_token(token, FieldType::Unnamed { index: item_count });
-                        item_count += 1;
-                        ts
-                    } else {
-                        // Its either a literal, or something that will panic currently
-                        write_for_token(token, FieldType::Unit)
-                    }
-                });
                 quote! {
                     #enum_ident::#ident(#(#field_names),*) => {
                         #(#writers)*
@@ -198,9 +814,6 @@ pub fn build_serializer_for_enum(
                 }
             }
             Fields::Unit => {
-                let writers = matcher
-                    .iter()
-                    .map(|token| write_for_token(token, FieldType::Unit));
                 quote! {
                     #enum_ident::#ident => {
                         #(#writers)*
@@ -209,34 +822,32 @@ pub fn build_serializer_for_enum(
             }
         }
     });
+    let return_state = return_state(build);
     quote! {
         use ::std::fmt::Write as __Write; // TODO: is importing this here hygienic?
         let mut state: Option<T> = None;
         match #match_item {
             #(#variants)*,
         }
-        return state;
+        #return_state
     }
 }
 
-pub fn build_serializer_for_struct(switch_item: &SwitchItem, item: &Ident) -> TokenStream2 {
-    let SwitchItem {
-        matcher,
-        ident,
-        fields,
-    } = switch_item;
-    let destructor_and_writers = match fields {
+pub fn build_serializer_for_struct(
+    switch_item: &SwitchItem,
+    item: &Ident,
+    build: Build,
+) -> TokenStream2 {
+    let SwitchItem { ident, fields, .. } = switch_item;
+    let writers = build_writers(switch_item, build, &ident.to_string());
+    let destructor = match fields {
         Fields::Named(fields_named) => {
             let field_names = fields_named
                 .named
                 .iter()
                 .filter_map(|named| named.ident.as_ref());
-            let writers = matcher
-                .iter()
-                .map(|token| write_for_token(token, FieldType::Named));
             quote! {
                 let #ident{#(#field_names),*} = #item;
-                #(#writers)*
             }
         }
         Fields::Unnamed(fields_unnamed) => {
@@ -245,36 +856,19 @@ pub fn build_serializer_for_struct(switch_item: &SwitchItem, item: &Ident) -> To
                 .iter()
                 .enumerate()
                 .map(|(index, _)| unnamed_field_index_item(index));
-            let mut item_count = 0;
-            let writers = matcher.iter().map(|token| {
-                if let ShadowMatcherToken::Capture(_) = &token {
-                    let ts = write_for_token(token, FieldType::Unnamed { index: item_count });
-                    item_count += 1;
-                    ts
-                } else {
-                    // Its either a literal, or something that will panic currently
-                    write_for_token(token, FieldType::Unit)
-                }
-            });
             quote! {
                 let #ident(#(#field_names),*) = #item;
-                #(#writers)*
-            }
-        }
-        Fields::Unit => {
-            let writers = matcher
-                .iter()
-                .map(|token| write_for_token(token, FieldType::Unit));
-            quote! {
-                #(#writers)*
             }
         }
+        Fields::Unit => quote! {},
     };
+    let return_state = return_state(build);
     quote! {
         use ::std::fmt::Write as _;
         let mut state: Option<T> = None;
-        #destructor_and_writers
-        return state;
+        #destructor
+        #(#writers)*
+        #return_state
     }
 }
 
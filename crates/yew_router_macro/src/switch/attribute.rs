@@ -1,17 +1,30 @@
-use crate::switch::shadow::{ShadowCaptureVariant, ShadowMatcherToken};
-use syn::{Attribute, Lit, Meta, MetaNameValue};
+use crate::switch::{
+    rename::RenameRule,
+    shadow::{ShadowCaptureVariant, ShadowMatcherToken},
+};
+use syn::{Attribute, Lit, LitStr, Meta, MetaList, MetaNameValue, NestedMeta, Path};
+use yew_router_route_parser::{GrammarVersion, QueryMode};
 
 pub enum AttrToken {
-    To(String),
+    /// The literal is kept, so errors in the matcher string can point into it.
+    To(LitStr),
     End,
     Rest(Option<String>),
 }
 
+/// How the value of a field is checked before the route matches.
+pub enum FieldValidator {
+    /// The `Validate` implementation of the field's type is used.
+    Trait,
+    /// The function is called with a reference to the value, and returns whether it is valid.
+    Function(Path),
+}
+
 impl AttrToken {
     pub fn convert_attributes_to_tokens(attributes: Vec<Attribute>) -> Vec<Self> {
-        fn get_meta_name_value_str(mnv: &MetaNameValue) -> Option<String> {
+        fn get_meta_name_value_str(mnv: &MetaNameValue) -> Option<LitStr> {
             match &mnv.lit {
-                Lit::Str(s) => Some(s.value()),
+                Lit::Str(s) => Some(s.clone()),
                 _ => None,
             }
         }
@@ -19,7 +32,7 @@ impl AttrToken {
         attributes
             .iter()
             .filter_map(|attr: &Attribute| attr.parse_meta().ok())
-            .filter_map(|meta: Meta| match meta {
+            .flat_map(|meta: Meta| match meta {
                 Meta::NameValue(mnv) => mnv
                     .path
                     .clone()
@@ -32,11 +45,13 @@ impl AttrToken {
                         )),
                         "rest" => Some(AttrToken::Rest(Some(
                             get_meta_name_value_str(&mnv)
-                                .expect("Value provided after `rest` must be a String"),
+                                .expect("Value provided after `rest` must be a String")
+                                .value(),
                         ))),
                         _ => None,
                     })
-                    .next(),
+                    .take(1)
+                    .collect::<Vec<_>>(),
                 Meta::Path(path) => path
                     .get_ident()
                     .into_iter()
@@ -45,9 +60,233 @@ impl AttrToken {
                         "rest" => Some(AttrToken::Rest(None)),
                         _ => None,
                     })
-                    .next(),
+                    .take(1)
+                    .collect::<Vec<_>>(),
+                Meta::List(list) => {
+                    if list.path.is_ident("to") {
+                        to_list_tokens(list)
+                    } else {
+                        vec![]
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Gets the grammar version specified by a `#[grammar = "v2"]` attribute, defaulting to V1 if
+    /// none is present.
+    pub fn grammar_version(attributes: &[Attribute]) -> GrammarVersion {
+        attributes
+            .iter()
+            .filter_map(|attr: &Attribute| attr.parse_meta().ok())
+            .filter_map(|meta: Meta| match meta {
+                Meta::NameValue(mnv) if mnv.path.is_ident("grammar") => match &mnv.lit {
+                    Lit::Str(s) => match s.value().as_str() {
+                        "v1" => Some(GrammarVersion::V1),
+                        "v2" => Some(GrammarVersion::V2),
+                        other => panic!(
+                            "Unknown grammar version: `{}`. Expected `v1` or `v2`",
+                            other
+                        ),
+                    },
+                    _ => panic!("Value provided after `grammar` must be a String"),
+                },
+                _ => None,
+            })
+            .next()
+            .unwrap_or_default()
+    }
+
+    /// Gets the query mode specified by a `#[query = "lenient"]` attribute, defaulting to
+    /// `Structural` if none is present.
+    pub fn query_mode(attributes: &[Attribute]) -> QueryMode {
+        attributes
+            .iter()
+            .filter_map(|attr: &Attribute| attr.parse_meta().ok())
+            .filter_map(|meta: Meta| match meta {
+                Meta::NameValue(mnv) if mnv.path.is_ident("query") => match &mnv.lit {
+                    Lit::Str(s) => match s.value().as_str() {
+                        "structural" => Some(QueryMode::Structural),
+                        "strict" => Some(QueryMode::Strict),
+                        "lenient" => Some(QueryMode::Lenient),
+                        other => panic!(
+                            "Unknown query mode: `{}`. Expected `structural`, `strict` or `lenient`",
+                            other
+                        ),
+                    },
+                    _ => panic!("Value provided after `query` must be a String"),
+                },
+                _ => None,
+            })
+            .next()
+            .unwrap_or_default()
+    }
+
+    /// Gets the priority specified by a `#[priority = 10]` attribute, defaulting to 0 if none is
+    /// present.
+    pub fn priority(attributes: &[Attribute]) -> u64 {
+        attributes
+            .iter()
+            .filter_map(|attr: &Attribute| attr.parse_meta().ok())
+            .filter_map(|meta: Meta| match meta {
+                Meta::NameValue(mnv) if mnv.path.is_ident("priority") => match &mnv.lit {
+                    Lit::Int(i) => Some(
+                        i.base10_parse::<u64>()
+                            .expect("Value provided after `priority` must be a positive integer"),
+                    ),
+                    _ => panic!("Value provided after `priority` must be an integer"),
+                },
+                _ => None,
+            })
+            .next()
+            .unwrap_or_default()
+    }
+
+    /// Gets the name of the capture that a field is bound to, as specified by a
+    /// `#[switch(rename = "user_id")]` attribute on the field.
+    pub fn rename(attributes: &[Attribute]) -> Option<String> {
+        field_options(attributes)
+            .into_iter()
+            .filter_map(|nested_meta| match nested_meta {
+                NestedMeta::Meta(Meta::NameValue(ref mnv)) if mnv.path.is_ident("rename") => {
+                    match &mnv.lit {
+                        Lit::Str(s) => Some(s.value()),
+                        _ => panic!("Value provided after `rename` must be a String"),
+                    }
+                }
+                _ => None,
+            })
+            .next()
+    }
+
+    /// Gets the check that the value of a field must pass for the route to match, as specified by
+    /// a `#[switch(validate)]` or `#[switch(validate = "is_known_slug")]` attribute on the field.
+    pub fn validator(attributes: &[Attribute]) -> Option<FieldValidator> {
+        field_options(attributes)
+            .into_iter()
+            .filter_map(|nested_meta| match nested_meta {
+                NestedMeta::Meta(Meta::Path(ref path)) if path.is_ident("validate") => {
+                    Some(FieldValidator::Trait)
+                }
+                NestedMeta::Meta(Meta::NameValue(ref mnv)) if mnv.path.is_ident("validate") => {
+                    match &mnv.lit {
+                        Lit::Str(s) => {
+                            Some(FieldValidator::Function(s.parse::<Path>().unwrap_or_else(
+                                |_| panic!("`{}` isn't the path of a function", s.value()),
+                            )))
+                        }
+                        _ => panic!("Value provided after `validate` must be a String"),
+                    }
+                }
+                _ => None,
+            })
+            .next()
+    }
+
+    /// Gets the component that a variant is routed to, as specified by a
+    /// `#[switch(component = "UserPage")]` attribute on the variant.
+    pub fn component(attributes: &[Attribute]) -> Option<Path> {
+        variant_options(attributes)
+            .into_iter()
+            .filter_map(|nested_meta| match nested_meta {
+                NestedMeta::Meta(Meta::NameValue(ref mnv)) if mnv.path.is_ident("component") => {
+                    match &mnv.lit {
+                        Lit::Str(s) => Some(s.parse::<Path>().unwrap_or_else(|_| {
+                            panic!("`{}` isn't the path of a component", s.value())
+                        })),
+                        _ => panic!("Value provided after `component` must be a String"),
+                    }
+                }
+                _ => None,
+            })
+            .next()
+    }
+
+    /// Gets the rule used to generate the matcher strings of variants without a `#[to]`, as
+    /// specified by a `#[switch(rename_all = "snake_case")]` attribute on the enum.
+    pub fn rename_all(attributes: &[Attribute]) -> Option<RenameRule> {
+        enum_options(attributes)
+            .into_iter()
+            .filter_map(|nested_meta| match nested_meta {
+                NestedMeta::Meta(Meta::NameValue(ref mnv)) if mnv.path.is_ident("rename_all") => {
+                    match &mnv.lit {
+                        Lit::Str(s) => Some(RenameRule::from_str(&s.value()).unwrap_or_else(|| {
+                            panic!(
+                                "Unknown rule: `{}`. Expected `lowercase`, `snake_case`, `kebab-case`, `camelCase` or `PascalCase`",
+                                s.value()
+                            )
+                        })),
+                        _ => panic!("Value provided after `rename_all` must be a String"),
+                    }
+                }
                 _ => None,
             })
+            .next()
+    }
+
+    /// Determines if variants that can never be matched are an error, as specified by a
+    /// `#[switch(deny_shadowed)]` attribute on the enum.
+    pub fn deny_shadowed(attributes: &[Attribute]) -> bool {
+        enum_options(attributes)
+            .into_iter()
+            .any(|nested_meta| match nested_meta {
+                NestedMeta::Meta(Meta::Path(ref path)) => path.is_ident("deny_shadowed"),
+                _ => false,
+            })
+    }
+
+    /// Gets the entries of `#[meta(title = "Settings", requires_auth)]` attributes, in the order
+    /// they were declared.
+    ///
+    /// Flags, like `requires_auth`, don't have a value.
+    pub fn meta(attributes: &[Attribute]) -> Vec<(String, Option<String>)> {
+        let mut entries: Vec<(String, Option<String>)> = Vec::new();
+        let nested_metas = attributes
+            .iter()
+            .filter_map(|attr: &Attribute| attr.parse_meta().ok())
+            .filter(|meta: &Meta| meta.path().is_ident("meta"))
+            .map(|meta: Meta| match meta {
+                Meta::List(list) => list.nested,
+                _ => panic!(
+                    "`meta` must contain a list of entries, like `#[meta(title = \"Home\")]`"
+                ),
+            })
+            .flatten();
+        for nested_meta in nested_metas {
+            let entry = match nested_meta {
+                NestedMeta::Meta(Meta::Path(path)) => (path_key(&path), None),
+                NestedMeta::Meta(Meta::NameValue(mnv)) => {
+                    let value = match &mnv.lit {
+                        Lit::Str(s) => s.value(),
+                        _ => panic!(
+                            "Value provided for `{}` in `meta` must be a String",
+                            path_key(&mnv.path)
+                        ),
+                    };
+                    (path_key(&mnv.path), Some(value))
+                }
+                _ => panic!(
+                    "Entries in `meta` must be flags like `requires_auth`, or keys with values like `title = \"Home\"`"
+                ),
+            };
+            if entries.iter().any(|(key, _)| *key == entry.0) {
+                panic!("`{}` is specified more than once in `meta`", entry.0)
+            }
+            entries.push(entry);
+        }
+        entries
+    }
+
+    /// Reassembles the route matcher string that the attributes describe.
+    pub fn matcher_string(tokens: &[AttrToken]) -> String {
+        tokens
+            .iter()
+            .map(|token| match token {
+                AttrToken::To(matcher_string) => matcher_string.value(),
+                AttrToken::End => "!".to_string(),
+                AttrToken::Rest(Some(capture_name)) => format!("{{*:{}}}", capture_name),
+                AttrToken::Rest(None) => "{*}".to_string(),
+            })
             .collect()
     }
 
@@ -57,22 +296,143 @@ impl AttrToken {
         self,
         id: usize,
         field_type: yew_router_route_parser::FieldType,
-    ) -> Vec<ShadowMatcherToken> {
+        grammar_version: GrammarVersion,
+    ) -> syn::Result<Vec<ShadowMatcherToken>> {
         match self {
             AttrToken::To(matcher_string) => {
-                yew_router_route_parser::parse_str_and_optimize_tokens(&matcher_string, field_type)
-                    .expect("Invalid Matcher") // This is the point where users should see an error message if their matcher string has some syntax error.
+                let value = matcher_string.value();
+                // This is the point where users should see an error message if their matcher
+                // string has some syntax error.
+                let tokens = yew_router_route_parser::parse_str_and_optimize_tokens_with_version(
+                    &value,
+                    field_type,
+                    grammar_version,
+                )
+                .map_err(|error| crate::span::parse_error(&matcher_string, &error))?;
+                Ok(tokens
                     .into_iter()
                     .map(crate::switch::shadow::ShadowMatcherToken::from)
-                    .collect()
+                    .collect())
             }
-            AttrToken::End => vec![ShadowMatcherToken::End],
-            AttrToken::Rest(Some(capture_name)) => vec![ShadowMatcherToken::Capture(
+            AttrToken::End => Ok(vec![ShadowMatcherToken::End]),
+            AttrToken::Rest(Some(capture_name)) => Ok(vec![ShadowMatcherToken::Capture(
                 ShadowCaptureVariant::ManyNamed(capture_name),
-            )],
-            AttrToken::Rest(None) => vec![ShadowMatcherToken::Capture(
+            )]),
+            AttrToken::Rest(None) => Ok(vec![ShadowMatcherToken::Capture(
                 ShadowCaptureVariant::ManyNamed(id.to_string()),
-            )],
+            )]),
+        }
+    }
+}
+
+/// Converts `#[to("/home", exact)]` into its tokens.
+///
+/// `exact` is the same as ending the matcher string with `!`.
+fn to_list_tokens(list: MetaList) -> Vec<AttrToken> {
+    let mut nested = list.nested.into_iter();
+    let matcher_string = match nested.next() {
+        Some(NestedMeta::Lit(Lit::Str(s))) => s,
+        _ => {
+            panic!("`to` must start with the route matcher string, like `#[to(\"/home\", exact)]`")
+        }
+    };
+    let mut tokens = vec![AttrToken::To(matcher_string)];
+    for option in nested {
+        match option {
+            NestedMeta::Meta(Meta::Path(ref path)) if path.is_ident("exact") => {
+                if let Some(AttrToken::End) = tokens.last() {
+                    panic!("`exact` is specified more than once in `to`")
+                }
+                tokens.push(AttrToken::End)
+            }
+            _ => panic!("Unknown option in `to`. Expected `exact`"),
         }
     }
+    tokens
+}
+
+/// Gets the options of `#[switch(...)]` attributes.
+fn switch_options(attributes: &[Attribute]) -> impl Iterator<Item = NestedMeta> + '_ {
+    attributes
+        .iter()
+        .filter_map(|attr: &Attribute| attr.parse_meta().ok())
+        .filter(|meta: &Meta| meta.path().is_ident("switch"))
+        .flat_map(|meta: Meta| match meta {
+            Meta::List(list) => list.nested,
+            _ => {
+                panic!("`switch` must contain a list of options, like `#[switch(rename = \"id\")]`")
+            }
+        })
+}
+
+/// Gets the options of `#[switch(...)]` attributes on an enum, after checking that they are all
+/// known ones.
+fn enum_options(attributes: &[Attribute]) -> Vec<NestedMeta> {
+    switch_options(attributes)
+        .inspect(|nested_meta| match nested_meta {
+            NestedMeta::Meta(Meta::NameValue(mnv)) if mnv.path.is_ident("rename_all") => {}
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("deny_shadowed") => {}
+            _ => panic!(
+                "Unknown option in `switch` on an enum. Expected `rename_all` or `deny_shadowed`"
+            ),
+        })
+        .collect()
+}
+
+/// Gets the options of `#[switch(...)]` attributes on an enum variant, after checking that they
+/// are all known ones.
+fn variant_options(attributes: &[Attribute]) -> Vec<NestedMeta> {
+    switch_options(attributes)
+        .inspect(|nested_meta| match nested_meta {
+            NestedMeta::Meta(Meta::NameValue(mnv)) if mnv.path.is_ident("component") => {}
+            _ => panic!("Unknown option in `switch` on a variant. Expected `component`"),
+        })
+        .collect()
+}
+
+/// Gets the options of `#[switch(...)]` attributes on a field, after checking that they are all
+/// known ones.
+fn field_options(attributes: &[Attribute]) -> Vec<NestedMeta> {
+    switch_options(attributes)
+        .inspect(|nested_meta| match nested_meta {
+            NestedMeta::Meta(Meta::NameValue(mnv))
+                if mnv.path.is_ident("rename") || mnv.path.is_ident("validate") => {}
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("validate") => {}
+            _ => panic!("Unknown option in `switch` on a field. Expected `rename` or `validate`"),
+        })
+        .collect()
+}
+
+/// Gets the key of a `meta` entry, which must be a single identifier.
+fn path_key(path: &Path) -> String {
+    path.get_ident()
+        .map(ToString::to_string)
+        .expect("Keys in `meta` must be identifiers")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::{parse_quote, DeriveInput};
+
+    #[test]
+    fn component_is_found_among_other_attributes() {
+        let item: DeriveInput = parse_quote! {
+            #[to = "/users/{id}"]
+            #[switch(component = "pages::UserPage")]
+            struct Unused;
+        };
+        let component = AttrToken::component(&item.attrs).unwrap();
+        assert_eq!(quote::quote!(#component).to_string(), "pages :: UserPage");
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown option in `switch` on a variant")]
+    fn unknown_variant_options_after_component_are_rejected() {
+        let item: DeriveInput = parse_quote! {
+            #[switch(component = "UserPage", rename_all = "snake_case")]
+            struct Unused;
+        };
+        AttrToken::component(&item.attrs);
+    }
 }
@@ -1,31 +1,149 @@
-use crate::switch::{build_serializer_for_enum, SwitchItem};
+use crate::switch::{
+    attribute::AttrToken, build_field_validation, build_flag_guard, build_nested_meta,
+    build_nested_route, build_route_info, build_route_meta, build_serializer_for_enum,
+    nested_field, Build, NestedField, SwitchItem,
+};
 use proc_macro::TokenStream;
 use proc_macro2::Span;
 use quote::quote;
-use syn::{export::TokenStream2, Field, Fields, Ident, Type};
+use syn::{export::TokenStream2, Error, Field, Fields, Ident, Type};
+use yew_router_route_parser::{
+    shadows, to_matcher_string, CaptureVariant, GrammarVersion, MatcherToken, QueryMode,
+};
 
-pub fn generate_enum_impl(enum_ident: Ident, switch_variants: Vec<SwitchItem>) -> TokenStream {
+pub fn generate_enum_impl(
+    enum_ident: Ident,
+    switch_variants: Vec<SwitchItem>,
+    grammar_version: GrammarVersion,
+    query_mode: QueryMode,
+    deny_shadowed: bool,
+) -> TokenStream {
     let variant_matchers = switch_variants.iter().map(|sv| {
         let SwitchItem {
             matcher,
             ident,
             fields,
+            matcher_string,
+            ..
         } = sv;
-        let build_from_captures = build_variant_from_captures(&enum_ident, ident, fields);
-        let matcher = super::build_matcher_from_tokens(&matcher);
+        let build_from_captures =
+            build_variant_from_captures(&enum_ident, ident, fields, matcher_string);
+        let matcher = super::build_matcher_from_tokens(&matcher, grammar_version, query_mode);
 
-        quote! {
-            #matcher
-            #build_from_captures
-        }
+        build_flag_guard(
+            sv,
+            quote! {
+                #matcher
+                #build_from_captures
+            },
+        )
     });
 
     let match_item = Ident::new("self", Span::call_site());
-    let serializer = build_serializer_for_enum(&switch_variants, &enum_ident, &match_item);
+    let serializer = build_serializer_for_enum(
+        &switch_variants,
+        &enum_ident,
+        &match_item,
+        Build::Infallible,
+    );
+    let fallible_serializer =
+        build_serializer_for_enum(&switch_variants, &enum_ident, &match_item, Build::Fallible);
+    // In strict mode, an earlier variant rejects routes with query parameters that only a later
+    // variant mentions, so shadowing can't be determined from the matchers alone.
+    let shadowed_errors = if !deny_shadowed || query_mode == QueryMode::Strict {
+        quote! {}
+    } else {
+        build_shadowed_route_errors(&enum_ident, &switch_variants)
+    };
+    let routed_component = build_routed_component(&enum_ident, &switch_variants);
+    let route_infos = switch_variants
+        .iter()
+        .map(|sv| build_route_info(sv, grammar_version));
+    let route_metas = switch_variants.iter().map(|sv| {
+        let ident = &sv.ident;
+        let route_meta = build_route_meta(sv);
+        match sv.fields {
+            Fields::Named(_) => quote! {#enum_ident::#ident{..} => #route_meta},
+            Fields::Unnamed(_) => quote! {#enum_ident::#ident(..) => #route_meta},
+            Fields::Unit => quote! {#enum_ident::#ident => #route_meta},
+        }
+    });
+    let nested_metas = switch_variants.iter().map(|sv| {
+        let ident = &sv.ident;
+        match (nested_field(sv), &sv.fields) {
+            (Some(NestedField::Named(name)), _) => {
+                let nested_meta = build_nested_meta(sv, true);
+                quote! {#enum_ident::#ident{#name: nested, ..} => {#nested_meta}}
+            }
+            (Some(NestedField::Unnamed { index }), Fields::Unnamed(fields_unnamed)) => {
+                let fields = (0..fields_unnamed.unnamed.len()).map(|field_index| {
+                    if field_index == index {
+                        quote! {nested}
+                    } else {
+                        quote! {_}
+                    }
+                });
+                let nested_meta = build_nested_meta(sv, true);
+                quote! {#enum_ident::#ident(#(#fields),*) => {#nested_meta}}
+            }
+            (_, Fields::Named(_)) => {
+                let nested_meta = build_nested_meta(sv, false);
+                quote! {#enum_ident::#ident{..} => {#nested_meta}}
+            }
+            (_, Fields::Unnamed(_)) => {
+                let nested_meta = build_nested_meta(sv, false);
+                quote! {#enum_ident::#ident(..) => {#nested_meta}}
+            }
+            (_, Fields::Unit) => {
+                let nested_meta = build_nested_meta(sv, false);
+                quote! {#enum_ident::#ident => {#nested_meta}}
+            }
+        }
+    });
+
+    let nested_routes = switch_variants.iter().map(|sv| {
+        let ident = &sv.ident;
+        match (nested_field(sv), &sv.fields) {
+            (Some(NestedField::Named(name)), _) => {
+                let nested_route = build_nested_route(true);
+                quote! {#enum_ident::#ident{#name: nested, ..} => {#nested_route}}
+            }
+            (Some(NestedField::Unnamed { index }), Fields::Unnamed(fields_unnamed)) => {
+                let fields = (0..fields_unnamed.unnamed.len()).map(|field_index| {
+                    if field_index == index {
+                        quote! {nested}
+                    } else {
+                        quote! {_}
+                    }
+                });
+                let nested_route = build_nested_route(true);
+                quote! {#enum_ident::#ident(#(#fields),*) => {#nested_route}}
+            }
+            (_, Fields::Named(_)) => {
+                let nested_route = build_nested_route(false);
+                quote! {#enum_ident::#ident{..} => {#nested_route}}
+            }
+            (_, Fields::Unnamed(_)) => {
+                let nested_route = build_nested_route(false);
+                quote! {#enum_ident::#ident(..) => {#nested_route}}
+            }
+            (_, Fields::Unit) => {
+                let nested_route = build_nested_route(false);
+                quote! {#enum_ident::#ident => {#nested_route}}
+            }
+        }
+    });
 
     let token_stream = quote! {
         impl ::yew_router::Switch for #enum_ident {
             fn from_route_part<T: ::yew_router::route::RouteState>(route: ::yew_router::route::Route<T>) -> (::std::option::Option<Self>, ::std::option::Option<T>) {
+                Self::from_route_part_with_flags(route, &::yew_router::switch::FeatureFlags::all())
+            }
+
+            fn from_route_part_with_flags<T: ::yew_router::route::RouteState>(
+                route: ::yew_router::route::Route<T>,
+                flags: &::yew_router::switch::FeatureFlags,
+            ) -> (::std::option::Option<Self>, ::std::option::Option<T>) {
                 let mut state = route.state;
                 let route_string = route.route;
                 #(#variant_matchers)*
@@ -33,20 +151,213 @@ pub fn generate_enum_impl(enum_ident: Ident, switch_variants: Vec<SwitchItem>) -
                 return (::std::option::Option::None, state)
             }
 
-            fn build_route_section<T>(self, mut buf: &mut ::std::string::String) -> ::std::option::Option<T> {
+            fn build_route_section<T>(self, buf: &mut ::std::string::String) -> ::std::option::Option<T> {
+                ::yew_router::Switch::build_encoded_route_section(self, buf, ::std::option::Option::None)
+            }
+
+            fn build_encoded_route_section<T>(
+                self,
+                mut buf: &mut ::std::string::String,
+                encoding: ::std::option::Option<&::yew_router::route::RouteEncoding>,
+            ) -> ::std::option::Option<T> {
                 #serializer
             }
+
+            fn try_build_route_section<T>(
+                self,
+                mut buf: &mut ::std::string::String,
+                encoding: ::std::option::Option<&::yew_router::route::RouteEncoding>,
+            ) -> ::std::result::Result<::std::option::Option<T>, ::yew_router::switch::SwitchError> {
+                #fallible_serializer
+            }
+
+            fn routes() -> ::std::vec::Vec<::yew_router::switch::RouteInfo> {
+                ::std::vec![#(#route_infos),*]
+            }
+
+            fn meta(&self) -> ::yew_router::switch::RouteMeta {
+                match self {
+                    #(#route_metas),*
+                }
+            }
+
+            fn nested_meta(&self) -> ::std::vec::Vec<::yew_router::switch::RouteMeta> {
+                match self {
+                    #(#nested_metas),*
+                }
+            }
+
+            fn nested_route(self) -> ::std::option::Option<::std::string::String> {
+                match self {
+                    #(#nested_routes),*
+                }
+            }
         }
+
+        #shadowed_errors
+        #routed_component
     };
     TokenStream::from(token_stream)
 }
 
+/// Implements `RoutedComponent` if any of the variants declares the component that it's routed
+/// to with `#[switch(component = "UserPage")]`.
+///
+/// Otherwise nothing is generated, so enums that aren't rendered by a Router don't need yew.
+fn build_routed_component(enum_ident: &Ident, switch_variants: &[SwitchItem]) -> TokenStream2 {
+    if switch_variants.iter().all(|sv| sv.component.is_none()) {
+        return quote! {};
+    }
+    let arms = switch_variants.iter().filter_map(|sv| {
+        let component = sv.component.as_ref()?;
+        let ident = &sv.ident;
+        let arm = match &sv.fields {
+            Fields::Named(fields_named) => {
+                let field_names: Vec<&Ident> = fields_named
+                    .named
+                    .iter()
+                    .filter_map(|field| field.ident.as_ref())
+                    .collect();
+                quote! {
+                    #enum_ident::#ident{#(#field_names),*} => ::std::result::Result::Ok(
+                        ::yew::html! { <#component #(#field_names = #field_names)* /> }
+                    )
+                }
+            }
+            Fields::Unnamed(fields_unnamed) if fields_unnamed.unnamed.len() == 1 => quote! {
+                #enum_ident::#ident(field) => {
+                    let props: <#component as ::yew::Component>::Properties =
+                        ::std::convert::From::from(field);
+                    ::std::result::Result::Ok(::yew::html! { <#component with props /> })
+                }
+            },
+            Fields::Unnamed(_) => panic!(
+                "`component` can't be used on `{}`, as props can only be built from named fields or a single unnamed field",
+                ident
+            ),
+            Fields::Unit => quote! {
+                #enum_ident::#ident => ::std::result::Result::Ok(
+                    ::yew::html! { <#component /> }
+                )
+            },
+        };
+        Some(arm)
+    });
+    quote! {
+        impl ::yew_router::router::RoutedComponent for #enum_ident {
+            fn render_component<CTX: ::yew::Component>(self) -> ::std::result::Result<::yew::Html<CTX>, Self> {
+                #[allow(unreachable_patterns)]
+                match self {
+                    #(#arms,)*
+                    other => ::std::result::Result::Err(other),
+                }
+            }
+        }
+    }
+}
+
+/// Reports variants that can never be switched to, because a variant before them will always
+/// match the same routes, as errors.
+fn build_shadowed_route_errors(enum_ident: &Ident, switch_variants: &[SwitchItem]) -> TokenStream2 {
+    let matchers: Vec<Vec<MatcherToken>> = switch_variants
+        .iter()
+        .map(|sv| sv.matcher.iter().map(MatcherToken::from).collect())
+        .collect();
+    let errors = switch_variants.iter().enumerate().filter_map(|(index, later)| {
+        switch_variants[..index]
+            .iter()
+            .zip(&matchers)
+            .find(|(earlier, earlier_matcher)| {
+                // A variant behind a feature flag stops shadowing the ones after it when the flag
+                // is disabled.
+                earlier.meta.iter().all(|(key, _)| key != "flag")
+                    && always_constructible(earlier, earlier_matcher)
+                    && shadows(earlier_matcher, &matchers[index])
+            })
+            .map(|(earlier, earlier_matcher)| {
+                // The matchers are shown normalized, as variants without `#[to]` generate theirs.
+                let note = format!(
+                    "The route for `{enum_ident}::{later}` (`{later_matcher}`) can never be matched, because `{enum_ident}::{earlier}` (`{earlier_matcher}`) comes before it and matches all of the same routes.",
+                    enum_ident = enum_ident,
+                    later = later.ident,
+                    later_matcher = to_matcher_string(&matchers[index]),
+                    earlier = earlier.ident,
+                    earlier_matcher = to_matcher_string(earlier_matcher)
+                );
+                Error::new(later.ident.span(), note).to_compile_error()
+            })
+    });
+    quote! {
+        #(#errors)*
+    }
+}
+
+/// Determines if the variant can always be created once its matcher has matched.
+///
+/// This is only known for unit variants and variants whose fields are all `String`s that are
+/// always captured and aren't validated.
+fn always_constructible(switch_item: &SwitchItem, matcher: &[MatcherToken]) -> bool {
+    let captures = matcher.iter().filter_map(|token| match token {
+        MatcherToken::Capture(capture) => Some(capture),
+        _ => None,
+    });
+    match &switch_item.fields {
+        Fields::Unit => true,
+        Fields::Named(named_fields) => {
+            let names: Vec<&str> = captures
+                .filter_map(|capture| match capture {
+                    CaptureVariant::Named(name)
+                    | CaptureVariant::ManyNamed(name)
+                    | CaptureVariant::GreedyManyNamed(name)
+                    | CaptureVariant::NumberedNamed { name, .. }
+                    | CaptureVariant::SeparatedNamed { name, .. }
+                    | CaptureVariant::TypedNamed { name, .. }
+                    | CaptureVariant::RepeatedNamed { name, .. } => Some(name.as_str()),
+                    _ => None,
+                })
+                .collect();
+            named_fields.named.iter().all(|field| {
+                accepts_any_capture(field)
+                    && field
+                        .ident
+                        .as_ref()
+                        .map(|ident| names.contains(&ident.to_string().as_str()))
+                        .unwrap_or(false)
+            })
+        }
+        Fields::Unnamed(unnamed_fields) => {
+            unnamed_fields.unnamed.len() <= captures.count()
+                && unnamed_fields.unnamed.iter().all(accepts_any_capture)
+        }
+    }
+}
+
+/// Determines if the field can be created from any captured section.
+fn accepts_any_capture(field: &Field) -> bool {
+    is_string(&field.ty) && AttrToken::validator(&field.attrs).is_none()
+}
+
+/// Determines if the type is `String`, which can be created from any captured section.
+fn is_string(ty: &Type) -> bool {
+    match ty {
+        Type::Path(type_path) => type_path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident == "String" && segment.arguments.is_empty())
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
 /// Once the 'captures' exists, attempt to populate the fields from the list of captures.
 fn build_variant_from_captures(
     enum_ident: &Ident,
     variant_ident: &Ident,
     fields: &Fields,
+    matcher_string: &str,
 ) -> TokenStream2 {
+    let item_name = format!("{}::{}", enum_ident, variant_ident);
     match fields {
         Fields::Named(named_fields) => {
             let fields: Vec<TokenStream2> = named_fields
@@ -56,34 +367,42 @@ fn build_variant_from_captures(
                     let field_ty: &Type = &field.ty;
                     field.ident.as_ref().map(|i: &Ident| {
                         let key = i.to_string();
-                        (i, key, field_ty)
+                        let validation = build_field_validation(field, &item_name, &key);
+                        (i, key, field_ty, validation)
                     })
                 })
-                .map(|(field_name, key, field_ty): (&Ident, String, &Type)| {
+                .map(|(field_name, key, field_ty, validation): (&Ident, String, &Type, TokenStream2)| {
                     quote! {
                         #field_name: {
-                            let (v, s) = match captures.remove(#key) {
+                            let (v, s, value) = match captures.remove(#key) {
                                 ::std::option::Option::Some(value) => {
-                                    <#field_ty as ::yew_router::Switch>::from_route_part(
+                                    let (v, s) = <#field_ty as ::yew_router::Switch>::from_route_part_with_flags(
                                         ::yew_router::route::Route {
-                                            route: value,
+                                            route: value.clone(),
                                             state,
-                                        }
-                                    )
+                                        },
+                                        flags,
+                                    );
+                                    (v, s, ::std::option::Option::Some(value))
                                 }
                                 ::std::option::Option::None => {
                                     (
                                         <#field_ty as ::yew_router::Switch>::key_not_available(),
                                         state,
+                                        ::std::option::Option::None,
                                     )
                                 }
                             };
                             match v {
                                 ::std::option::Option::Some(val) => {
+                                    #validation
                                     state = s; // Set state for the next var.
                                     val
                                 },
-                                ::std::option::Option::None => return (None, s) // Failed
+                                ::std::option::Option::None => {
+                                    ::yew_router::switch::reject_field(#item_name, #key, value);
+                                    return (None, s) // Failed
+                                }
                             }
                         }
                     }
@@ -91,7 +410,9 @@ fn build_variant_from_captures(
                 .collect();
 
             quote! {
-                let mut state = if let ::std::option::Option::Some(mut captures) = matcher.capture_route_into_map(&route_string).ok().map(|x| x.1) {
+                let match_result = matcher.capture_route_into_map(&route_string);
+                ::yew_router::debug::log_match_attempt(#item_name, #matcher_string, &route_string, &match_result);
+                let mut state = if let ::std::option::Option::Some(mut captures) = match_result.ok().map(|x| x.1) {
                     let create_item = || {
                          (
                             ::std::option::Option::Some(
@@ -114,39 +435,50 @@ fn build_variant_from_captures(
             }
         }
         Fields::Unnamed(unnamed_fields) => {
-            let fields = unnamed_fields.unnamed.iter().map(|f: &Field| {
+            let fields = unnamed_fields.unnamed.iter().enumerate().map(|(index, f): (usize, &Field)| {
                 let field_ty = &f.ty;
+                let field_name_str = index.to_string();
+                let validation = build_field_validation(f, &item_name, &field_name_str);
                 quote! {
                     {
-                        let (v, s) = match drain.next() {
+                        let (v, s, value) = match drain.next() {
                             ::std::option::Option::Some((_key, value)) => {
-                                <#field_ty as ::yew_router::Switch>::from_route_part(
+                                let (v, s) = <#field_ty as ::yew_router::Switch>::from_route_part_with_flags(
                                     ::yew_router::route::Route {
-                                        route: value,
+                                        route: value.clone(),
                                         state,
-                                    }
-                                )
+                                    },
+                                    flags,
+                                );
+                                (v, s, ::std::option::Option::Some(value))
                             },
                             ::std::option::Option::None => {
                                 (
                                     <#field_ty as ::yew_router::Switch>::key_not_available(),
                                     state,
+                                    ::std::option::Option::None,
                                 )
                             }
                         };
                         match v {
                             ::std::option::Option::Some(val) => {
+                                #validation
                                 state = s; // Set state for the next var.
                                 val
                             },
-                            ::std::option::Option::None => return (None, s) // Failed
+                            ::std::option::Option::None => {
+                                ::yew_router::switch::reject_field(#item_name, #field_name_str, value);
+                                return (None, s) // Failed
+                            }
                         }
                     }
                 }
             });
 
             quote! {
-                let mut state = if let ::std::option::Option::Some(mut captures) = matcher.capture_route_into_vec(&route_string).ok().map(|x| x.1) {
+                let match_result = matcher.capture_route_into_vec(&route_string);
+                ::yew_router::debug::log_match_attempt(#item_name, #matcher_string, &route_string, &match_result);
+                let mut state = if let ::std::option::Option::Some(mut captures) = match_result.ok().map(|x| x.1) {
                     let mut drain = captures.drain(..);
                     let create_item = || {
                          (
@@ -170,7 +502,9 @@ fn build_variant_from_captures(
         }
         Fields::Unit => {
             quote! {
-                let mut state = if let ::std::option::Option::Some(_captures) = matcher.capture_route_into_map(&route_string).ok().map(|x| x.1) {
+                let match_result = matcher.capture_route_into_map(&route_string);
+                ::yew_router::debug::log_match_attempt(#item_name, #matcher_string, &route_string, &match_result);
+                let mut state = if let ::std::option::Option::Some(_captures) = match_result.ok().map(|x| x.1) {
                     return (::std::option::Option::Some(#enum_ident::#variant_ident), state);
                 } else {
                     state
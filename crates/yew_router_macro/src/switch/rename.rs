@@ -0,0 +1,132 @@
+use crate::switch::attribute::AttrToken;
+use syn::{Fields, Ident};
+
+/// How the names of variants are converted into path segments when their matcher strings are
+/// generated.
+#[derive(Clone, Copy)]
+pub enum RenameRule {
+    /// `UserProfile` becomes `userprofile`.
+    LowerCase,
+    /// `UserProfile` becomes `user_profile`.
+    SnakeCase,
+    /// `UserProfile` becomes `user-profile`.
+    KebabCase,
+    /// `UserProfile` becomes `userProfile`.
+    CamelCase,
+    /// `UserProfile` is left as it is.
+    PascalCase,
+}
+
+impl Default for RenameRule {
+    fn default() -> Self {
+        RenameRule::KebabCase
+    }
+}
+
+impl RenameRule {
+    pub fn from_str(rule: &str) -> Option<Self> {
+        match rule {
+            "lowercase" => Some(RenameRule::LowerCase),
+            "snake_case" => Some(RenameRule::SnakeCase),
+            "kebab-case" => Some(RenameRule::KebabCase),
+            "camelCase" => Some(RenameRule::CamelCase),
+            "PascalCase" => Some(RenameRule::PascalCase),
+            _ => None,
+        }
+    }
+
+    /// Converts a name written in PascalCase.
+    pub fn apply(self, name: &str) -> String {
+        let words = words(name);
+        match self {
+            RenameRule::LowerCase => words.concat().to_lowercase(),
+            RenameRule::SnakeCase => words.join("_").to_lowercase(),
+            RenameRule::KebabCase => words.join("-").to_lowercase(),
+            RenameRule::CamelCase => words
+                .iter()
+                .enumerate()
+                .map(|(index, word)| {
+                    if index == 0 {
+                        word.to_lowercase()
+                    } else {
+                        capitalize(word)
+                    }
+                })
+                .collect(),
+            RenameRule::PascalCase => words.iter().map(|word| capitalize(word)).collect(),
+        }
+    }
+}
+
+/// Generates a matcher string for a variant that doesn't specify one.
+///
+/// The name of the variant makes up the first segment, followed by a segment capturing each of
+/// its fields, so `UserProfile { id }` becomes `/user-profile/{id}`, and `Post(u32)` becomes
+/// `/post/{}`.
+pub fn generated_matcher_string(ident: &Ident, fields: &Fields, rule: RenameRule) -> String {
+    let mut matcher_string = format!("/{}", rule.apply(&ident.to_string()));
+    match fields {
+        Fields::Named(fields_named) => {
+            for field in &fields_named.named {
+                let name = AttrToken::rename(&field.attrs).unwrap_or_else(|| {
+                    field
+                        .ident
+                        .as_ref()
+                        .expect("Named fields have identifiers")
+                        .to_string()
+                });
+                matcher_string.push_str(&format!("/{{{}}}", name));
+            }
+        }
+        Fields::Unnamed(fields_unnamed) => {
+            for _ in &fields_unnamed.unnamed {
+                matcher_string.push_str("/{}");
+            }
+        }
+        Fields::Unit => {}
+    }
+    matcher_string
+}
+
+/// Splits a PascalCase name into its words.
+///
+/// A run of capitals is treated as a single word, so `HTTPServer` is split into `HTTP` and
+/// `Server`.
+fn words(name: &str) -> Vec<String> {
+    let chars: Vec<char> = name.chars().collect();
+    let mut words = Vec::new();
+    let mut word = String::new();
+    for (index, c) in chars.iter().enumerate() {
+        if *c == '_' {
+            if !word.is_empty() {
+                words.push(std::mem::replace(&mut word, String::new()));
+            }
+            continue;
+        }
+        let starts_word = c.is_uppercase()
+            && index > 0
+            && (!chars[index - 1].is_uppercase()
+                || chars
+                    .get(index + 1)
+                    .map_or(false, |next| next.is_lowercase()));
+        if starts_word && !word.is_empty() {
+            words.push(std::mem::replace(&mut word, String::new()));
+        }
+        word.push(*c);
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
@@ -1,6 +1,7 @@
+use proc_macro2::{Ident, Span};
 use quote::{quote, ToTokens};
 use syn::export::TokenStream2;
-use yew_router_route_parser::{CaptureVariant, MatcherToken};
+use yew_router_route_parser::{CaptureKind, CaptureVariant, MatcherToken};
 
 impl ToTokens for ShadowMatcherToken {
     fn to_tokens(&self, ts: &mut TokenStream2) {
@@ -33,6 +34,8 @@ pub enum ShadowCaptureVariant {
     Unnamed,
     /// {*}
     ManyUnnamed,
+    /// {**}
+    GreedyManyUnnamed,
     /// {5}
     NumberedUnnamed {
         /// Number of sections to match.
@@ -42,8 +45,30 @@ pub enum ShadowCaptureVariant {
     Named(String),
     /// {*:name} - captures over many sections and adds it to the map with a given name.
     ManyNamed(String),
+    /// {**:name} - like {*:name}, but stops at the last occurrence of what follows it.
+    GreedyManyNamed(String),
     /// {2:name} - captures a fixed number of sections with a given name.
     NumberedNamed { sections: usize, name: String },
+    /// {;sep=-} - captures a fixed number of sections that are separated by a character other
+    /// than `/`.
+    SeparatedUnnamed { sections: usize, separator: char },
+    /// {name;sep=-} - captures a fixed number of sections that are separated by a character other
+    /// than `/`, with a given name.
+    SeparatedNamed {
+        sections: usize,
+        separator: char,
+        name: String,
+    },
+    /// {:u32} - captures a section, which only matches if it is of the given kind.
+    TypedUnnamed { kind: CaptureKind },
+    /// {name:u32} - captures a section with a given name, which only matches if it is of the
+    /// given kind.
+    TypedNamed { kind: CaptureKind, name: String },
+    /// tag={*} - captures the value of a query parameter, along with the values of the
+    /// occurrences of the parameter that directly follow it.
+    RepeatedUnnamed { param: String },
+    /// tag={*:name} - captures the values of a repeated query parameter with a given name.
+    RepeatedNamed { param: String, name: String },
 }
 
 impl ToTokens for ShadowCaptureVariant {
@@ -55,6 +80,9 @@ impl ToTokens for ShadowCaptureVariant {
             ShadowCaptureVariant::ManyNamed(name) => {
                 quote! {::yew_router::matcher::CaptureVariant::ManyNamed(#name.to_string())}
             }
+            ShadowCaptureVariant::GreedyManyNamed(name) => {
+                quote! {::yew_router::matcher::CaptureVariant::GreedyManyNamed(#name.to_string())}
+            }
             ShadowCaptureVariant::NumberedNamed { sections, name } => {
                 quote! {::yew_router::matcher::CaptureVariant::NumberedNamed{sections: #sections, name: #name.to_string()}}
             }
@@ -64,14 +92,52 @@ impl ToTokens for ShadowCaptureVariant {
             ShadowCaptureVariant::ManyUnnamed => {
                 quote! {::yew_router::matcher::CaptureVariant::ManyUnnamed}
             }
+            ShadowCaptureVariant::GreedyManyUnnamed => {
+                quote! {::yew_router::matcher::CaptureVariant::GreedyManyUnnamed}
+            }
             ShadowCaptureVariant::NumberedUnnamed { sections } => {
                 quote! {::yew_router::matcher::CaptureVariant::NumberedUnnamed{sections: #sections}}
             }
+            ShadowCaptureVariant::SeparatedUnnamed {
+                sections,
+                separator,
+            } => {
+                quote! {::yew_router::matcher::CaptureVariant::SeparatedUnnamed{sections: #sections, separator: #separator}}
+            }
+            ShadowCaptureVariant::SeparatedNamed {
+                sections,
+                separator,
+                name,
+            } => {
+                quote! {::yew_router::matcher::CaptureVariant::SeparatedNamed{sections: #sections, separator: #separator, name: #name.to_string()}}
+            }
+            ShadowCaptureVariant::TypedUnnamed { kind } => {
+                let kind = kind_tokens(*kind);
+                quote! {::yew_router::matcher::CaptureVariant::TypedUnnamed{kind: #kind}}
+            }
+            ShadowCaptureVariant::TypedNamed { kind, name } => {
+                let kind = kind_tokens(*kind);
+                quote! {::yew_router::matcher::CaptureVariant::TypedNamed{kind: #kind, name: #name.to_string()}}
+            }
+            ShadowCaptureVariant::RepeatedUnnamed { param } => {
+                quote! {::yew_router::matcher::CaptureVariant::RepeatedUnnamed{param: #param.to_string()}}
+            }
+            ShadowCaptureVariant::RepeatedNamed { param, name } => {
+                quote! {::yew_router::matcher::CaptureVariant::RepeatedNamed{param: #param.to_string(), name: #name.to_string()}}
+            }
         };
         ts.extend(t)
     }
 }
 
+/// Expands to the `CaptureKind` variant.
+fn kind_tokens(kind: CaptureKind) -> TokenStream2 {
+    // The variants of `CaptureKind` don't have any fields, so their debug representation is their
+    // name.
+    let variant = Ident::new(&format!("{:?}", kind), Span::call_site());
+    quote! {::yew_router::matcher::CaptureKind::#variant}
+}
+
 impl From<MatcherToken> for ShadowMatcherToken {
     fn from(mt: MatcherToken) -> Self {
         use MatcherToken as MT;
@@ -90,12 +156,95 @@ impl From<CaptureVariant> for ShadowCaptureVariant {
         match cv {
             CaptureVariant::Named(name) => SCV::Named(name),
             CaptureVariant::ManyNamed(name) => SCV::ManyNamed(name),
+            CaptureVariant::GreedyManyNamed(name) => SCV::GreedyManyNamed(name),
             CaptureVariant::NumberedNamed { sections, name } => {
                 SCV::NumberedNamed { sections, name }
             }
             CaptureVariant::Unnamed => SCV::Unnamed,
             CaptureVariant::ManyUnnamed => SCV::ManyUnnamed,
+            CaptureVariant::GreedyManyUnnamed => SCV::GreedyManyUnnamed,
             CaptureVariant::NumberedUnnamed { sections } => SCV::NumberedUnnamed { sections },
+            CaptureVariant::SeparatedUnnamed {
+                sections,
+                separator,
+            } => SCV::SeparatedUnnamed {
+                sections,
+                separator,
+            },
+            CaptureVariant::SeparatedNamed {
+                sections,
+                separator,
+                name,
+            } => SCV::SeparatedNamed {
+                sections,
+                separator,
+                name,
+            },
+            CaptureVariant::TypedUnnamed { kind } => SCV::TypedUnnamed { kind },
+            CaptureVariant::TypedNamed { kind, name } => SCV::TypedNamed { kind, name },
+            CaptureVariant::RepeatedUnnamed { param } => SCV::RepeatedUnnamed { param },
+            CaptureVariant::RepeatedNamed { param, name } => SCV::RepeatedNamed { param, name },
+        }
+    }
+}
+
+impl<'a> From<&'a ShadowMatcherToken> for MatcherToken {
+    fn from(smt: &'a ShadowMatcherToken) -> Self {
+        use MatcherToken as MT;
+        use ShadowMatcherToken as SOT;
+        match smt {
+            SOT::Exact(s) => MT::Exact(s.clone()),
+            SOT::Capture(capture) => MT::Capture(capture.into()),
+            SOT::End => MT::End,
+        }
+    }
+}
+
+impl<'a> From<&'a ShadowCaptureVariant> for CaptureVariant {
+    fn from(scv: &'a ShadowCaptureVariant) -> Self {
+        use ShadowCaptureVariant as SCV;
+        match scv {
+            SCV::Named(name) => CaptureVariant::Named(name.clone()),
+            SCV::ManyNamed(name) => CaptureVariant::ManyNamed(name.clone()),
+            SCV::GreedyManyNamed(name) => CaptureVariant::GreedyManyNamed(name.clone()),
+            SCV::NumberedNamed { sections, name } => CaptureVariant::NumberedNamed {
+                sections: *sections,
+                name: name.clone(),
+            },
+            SCV::Unnamed => CaptureVariant::Unnamed,
+            SCV::ManyUnnamed => CaptureVariant::ManyUnnamed,
+            SCV::GreedyManyUnnamed => CaptureVariant::GreedyManyUnnamed,
+            SCV::NumberedUnnamed { sections } => CaptureVariant::NumberedUnnamed {
+                sections: *sections,
+            },
+            SCV::SeparatedUnnamed {
+                sections,
+                separator,
+            } => CaptureVariant::SeparatedUnnamed {
+                sections: *sections,
+                separator: *separator,
+            },
+            SCV::SeparatedNamed {
+                sections,
+                separator,
+                name,
+            } => CaptureVariant::SeparatedNamed {
+                sections: *sections,
+                separator: *separator,
+                name: name.clone(),
+            },
+            SCV::TypedUnnamed { kind } => CaptureVariant::TypedUnnamed { kind: *kind },
+            SCV::TypedNamed { kind, name } => CaptureVariant::TypedNamed {
+                kind: *kind,
+                name: name.clone(),
+            },
+            SCV::RepeatedUnnamed { param } => CaptureVariant::RepeatedUnnamed {
+                param: param.clone(),
+            },
+            SCV::RepeatedNamed { param, name } => CaptureVariant::RepeatedNamed {
+                param: param.clone(),
+                name: name.clone(),
+            },
         }
     }
 }
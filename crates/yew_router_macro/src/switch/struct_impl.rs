@@ -1,26 +1,83 @@
-use crate::switch::SwitchItem;
+use crate::switch::{Build, NestedField, SwitchItem};
 use proc_macro2::{Ident, Span};
 use quote::quote;
 use syn::{
     export::{TokenStream, TokenStream2},
-    Field, Fields, Type,
+    Field, Fields, Index, Type,
 };
+use yew_router_route_parser::{GrammarVersion, QueryMode};
 
-pub fn generate_struct_impl(item: SwitchItem) -> TokenStream {
+pub fn generate_struct_impl(
+    item: SwitchItem,
+    grammar_version: GrammarVersion,
+    query_mode: QueryMode,
+) -> TokenStream {
     let SwitchItem {
         matcher,
         ident,
         fields,
+        matcher_string,
+        ..
     } = &item;
-    let build_from_captures = build_struct_from_captures(&ident, &fields);
-    let matcher = super::build_matcher_from_tokens(&matcher);
+    let build_from_captures = super::build_flag_guard(
+        &item,
+        build_struct_from_captures(&ident, &fields, &matcher_string),
+    );
+    let matcher = super::build_matcher_from_tokens(&matcher, grammar_version, query_mode);
 
     let match_item = Ident::new("self", Span::call_site());
-    let serializer = super::build_serializer_for_struct(&item, &match_item);
+    let serializer = super::build_serializer_for_struct(&item, &match_item, Build::Infallible);
+    let fallible_serializer =
+        super::build_serializer_for_struct(&item, &match_item, Build::Fallible);
+    let route_info = super::build_route_info(&item, grammar_version);
+    let route_meta = super::build_route_meta(&item);
+    let nested_meta = match super::nested_field(&item) {
+        Some(NestedField::Named(name)) => {
+            let nested_meta = super::build_nested_meta(&item, true);
+            quote! {
+                let nested = &self.#name;
+                #nested_meta
+            }
+        }
+        Some(NestedField::Unnamed { index }) => {
+            let index = Index::from(index);
+            let nested_meta = super::build_nested_meta(&item, true);
+            quote! {
+                let nested = &self.#index;
+                #nested_meta
+            }
+        }
+        None => super::build_nested_meta(&item, false),
+    };
+    let nested_route = match super::nested_field(&item) {
+        Some(NestedField::Named(name)) => {
+            let nested_route = super::build_nested_route(true);
+            quote! {
+                let nested = self.#name;
+                #nested_route
+            }
+        }
+        Some(NestedField::Unnamed { index }) => {
+            let index = Index::from(index);
+            let nested_route = super::build_nested_route(true);
+            quote! {
+                let nested = self.#index;
+                #nested_route
+            }
+        }
+        None => super::build_nested_route(false),
+    };
 
     let token_stream = quote! {
         impl ::yew_router::Switch for #ident {
             fn from_route_part<T: ::yew_router::route::RouteState>(route: ::yew_router::route::Route<T>) -> (::std::option::Option<Self>, ::std::option::Option<T>) {
+                Self::from_route_part_with_flags(route, &::yew_router::switch::FeatureFlags::all())
+            }
+
+            fn from_route_part_with_flags<T: ::yew_router::route::RouteState>(
+                route: ::yew_router::route::Route<T>,
+                flags: &::yew_router::switch::FeatureFlags,
+            ) -> (::std::option::Option<Self>, ::std::option::Option<T>) {
 
                 #matcher
                 let mut state = route.state;
@@ -31,15 +88,52 @@ pub fn generate_struct_impl(item: SwitchItem) -> TokenStream {
                 return (::std::option::Option::None, state)
             }
 
-            fn build_route_section<T>(self, mut buf: &mut ::std::string::String) -> ::std::option::Option<T> {
+            fn build_route_section<T>(self, buf: &mut ::std::string::String) -> ::std::option::Option<T> {
+                ::yew_router::Switch::build_encoded_route_section(self, buf, ::std::option::Option::None)
+            }
+
+            fn build_encoded_route_section<T>(
+                self,
+                mut buf: &mut ::std::string::String,
+                encoding: ::std::option::Option<&::yew_router::route::RouteEncoding>,
+            ) -> ::std::option::Option<T> {
                 #serializer
             }
+
+            fn try_build_route_section<T>(
+                self,
+                mut buf: &mut ::std::string::String,
+                encoding: ::std::option::Option<&::yew_router::route::RouteEncoding>,
+            ) -> ::std::result::Result<::std::option::Option<T>, ::yew_router::switch::SwitchError> {
+                #fallible_serializer
+            }
+
+            fn routes() -> ::std::vec::Vec<::yew_router::switch::RouteInfo> {
+                ::std::vec![#route_info]
+            }
+
+            fn meta(&self) -> ::yew_router::switch::RouteMeta {
+                #route_meta
+            }
+
+            fn nested_meta(&self) -> ::std::vec::Vec<::yew_router::switch::RouteMeta> {
+                #nested_meta
+            }
+
+            fn nested_route(self) -> ::std::option::Option<::std::string::String> {
+                #nested_route
+            }
         }
     };
     TokenStream::from(token_stream)
 }
 
-fn build_struct_from_captures(ident: &Ident, fields: &Fields) -> TokenStream2 {
+fn build_struct_from_captures(
+    ident: &Ident,
+    fields: &Fields,
+    matcher_string: &str,
+) -> TokenStream2 {
+    let item_name = ident.to_string();
     match fields {
         Fields::Named(named_fields) => {
             let fields: Vec<TokenStream2> = named_fields
@@ -49,34 +143,42 @@ fn build_struct_from_captures(ident: &Ident, fields: &Fields) -> TokenStream2 {
                     let field_ty: &Type = &field.ty;
                     field.ident.as_ref().map(|i| {
                         let key = i.to_string();
-                        (i, key, field_ty)
+                        let validation = super::build_field_validation(field, &item_name, &key);
+                        (i, key, field_ty, validation)
                     })
                 })
-                .map(|(field_name, key, field_ty): (&Ident, String, &Type)| {
+                .map(|(field_name, key, field_ty, validation): (&Ident, String, &Type, TokenStream2)| {
                     quote! {
                         #field_name: {
-                            let (v, s) = match captures.remove(#key) {
+                            let (v, s, value) = match captures.remove(#key) {
                                 ::std::option::Option::Some(value) => {
-                                    <#field_ty as ::yew_router::Switch>::from_route_part(
+                                    let (v, s) = <#field_ty as ::yew_router::Switch>::from_route_part_with_flags(
                                         ::yew_router::route::Route {
-                                            route: value,
+                                            route: value.clone(),
                                             state,
-                                        }
-                                    )
+                                        },
+                                        flags,
+                                    );
+                                    (v, s, ::std::option::Option::Some(value))
                                 }
                                 ::std::option::Option::None => {
                                     (
                                         <#field_ty as ::yew_router::Switch>::key_not_available(),
                                         state,
+                                        ::std::option::Option::None,
                                     )
                                 }
                             };
                             match v {
                                 ::std::option::Option::Some(val) => {
+                                    #validation
                                     state = s; // Set state for the next var.
                                     val
                                 },
-                                ::std::option::Option::None => return (::std::option::Option::None, s) // Failed
+                                ::std::option::Option::None => {
+                                    ::yew_router::switch::reject_field(#item_name, #key, value);
+                                    return (::std::option::Option::None, s) // Failed
+                                }
                             }
                         }
                     }
@@ -84,7 +186,9 @@ fn build_struct_from_captures(ident: &Ident, fields: &Fields) -> TokenStream2 {
                 .collect();
 
             return quote! {
-                if let ::std::option::Option::Some(mut captures) = matcher.capture_route_into_map(&route_string).ok().map(|x| x.1) {
+                let match_result = matcher.capture_route_into_map(&route_string);
+                ::yew_router::debug::log_match_attempt(#item_name, #matcher_string, &route_string, &match_result);
+                if let ::std::option::Option::Some(mut captures) = match_result.ok().map(|x| x.1) {
                     return (
                         ::std::option::Option::Some(
                             #ident {
@@ -97,39 +201,50 @@ fn build_struct_from_captures(ident: &Ident, fields: &Fields) -> TokenStream2 {
             };
         }
         Fields::Unnamed(unnamed_fields) => {
-            let fields = unnamed_fields.unnamed.iter().map(|f: &Field| {
+            let fields = unnamed_fields.unnamed.iter().enumerate().map(|(index, f): (usize, &Field)| {
                 let field_ty = &f.ty;
+                let field_name_str = index.to_string();
+                let validation = super::build_field_validation(f, &item_name, &field_name_str);
                 quote! {
                     {
-                        let (v, s) = match drain.next() {
+                        let (v, s, value) = match drain.next() {
                             ::std::option::Option::Some((_key, value)) => {
-                                <#field_ty as ::yew_router::Switch>::from_route_part(
+                                let (v, s) = <#field_ty as ::yew_router::Switch>::from_route_part_with_flags(
                                     ::yew_router::route::Route {
-                                        route: value,
+                                        route: value.clone(),
                                         state,
-                                    }
-                                )
+                                    },
+                                    flags,
+                                );
+                                (v, s, ::std::option::Option::Some(value))
                             },
                             ::std::option::Option::None => {
                                 (
                                     <#field_ty as ::yew_router::Switch>::key_not_available(),
                                     state,
+                                    ::std::option::Option::None,
                                 )
                             }
                         };
                         match v {
                             ::std::option::Option::Some(val) => {
+                                #validation
                                 state = s; // Set state for the next var.
                                 val
                             },
-                            ::std::option::Option::None => return (::std::option::Option::None, s) // Failed
+                            ::std::option::Option::None => {
+                                ::yew_router::switch::reject_field(#item_name, #field_name_str, value);
+                                return (::std::option::Option::None, s) // Failed
+                            }
                         }
                     }
                 }
             });
 
             quote! {
-                if let Some(mut captures) = matcher.capture_route_into_vec(&route_string).ok().map(|x| x.1) {
+                let match_result = matcher.capture_route_into_vec(&route_string);
+                ::yew_router::debug::log_match_attempt(#item_name, #matcher_string, &route_string, &match_result);
+                if let Some(mut captures) = match_result.ok().map(|x| x.1) {
                     let mut drain = captures.drain(..);
                     return (
                         ::std::option::Option::Some(
@@ -144,7 +259,9 @@ fn build_struct_from_captures(ident: &Ident, fields: &Fields) -> TokenStream2 {
         }
         Fields::Unit => {
             return quote! {
-                let mut state = if let ::std::option::Option::Some(_captures) = matcher.capture_route_into_map(&route_string).ok().map(|x| x.1) {
+                let match_result = matcher.capture_route_into_map(&route_string);
+                ::yew_router::debug::log_match_attempt(#item_name, #matcher_string, &route_string, &match_result);
+                let mut state = if let ::std::option::Option::Some(_captures) = match_result.ok().map(|x| x.1) {
                     return (::std::option::Option::Some(#ident), state);
                 } else {
                     state
@@ -0,0 +1,172 @@
+//! Matches routes against a matcher string, and prints what they capture, without a browser.
+//!
+//! ```text
+//! yew-router-match [OPTIONS] <MATCHER> [ROUTE]...
+//! ```
+//!
+//! If no routes are given, they are read from the standard input, one per line, so files of route
+//! fixtures can be checked in CI.
+//! The exit status is 0 if every route matched, 1 if a route didn't match, and 2 if the arguments
+//! or the matcher string are invalid.
+//!
+//! Options:
+//! * `--v2` - Parses the matcher string with the V2 grammar.
+//! * `--case-insensitive` - Matches literals regardless of their case.
+//! * `--incomplete` - Lets routes match without being consumed entirely.
+//! * `--query-mode=<structural|strict|lenient>` - Sets how query parameters and fragments that
+//!   the matcher string doesn't mention are treated.
+//!
+//! It is built with the "cli" feature:
+//!
+//! ```text
+//! cargo run --features cli --bin yew-router-match -- "/users/{id}" /users/5
+//! ```
+
+use nom::combinator::all_consuming;
+use std::{
+    env,
+    io::{self, BufRead},
+    process,
+};
+use yew_router_route_parser::{
+    matcher::match_into_vec, parse_str_and_optimize_tokens_with_version, FieldType, GrammarVersion,
+    MatcherSettings, MatcherToken, QueryMode,
+};
+
+const USAGE: &str = "usage: yew-router-match [--v2] [--case-insensitive] [--incomplete] \
+                     [--query-mode=<structural|strict|lenient>] <MATCHER> [ROUTE]...";
+
+fn main() {
+    let (settings, args) = match parse_settings(env::args().skip(1)) {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            eprintln!("{}\n{}", error, USAGE);
+            process::exit(2)
+        }
+    };
+    let mut args = args.into_iter();
+    let matcher_string = match args.next() {
+        Some(matcher_string) => matcher_string,
+        None => {
+            eprintln!("{}", USAGE);
+            process::exit(2)
+        }
+    };
+    let tokens = match parse_str_and_optimize_tokens_with_version(
+        &matcher_string,
+        FieldType::Unnamed,
+        settings.grammar_version,
+    ) {
+        Ok(tokens) => tokens,
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(2)
+        }
+    };
+
+    let mut routes: Vec<String> = args.collect();
+    if routes.is_empty() {
+        let stdin = io::stdin();
+        for line in stdin.lock().lines() {
+            match line {
+                Ok(line) if line.trim().is_empty() => {}
+                Ok(line) => routes.push(line),
+                Err(error) => {
+                    eprintln!("{}", error);
+                    process::exit(2)
+                }
+            }
+        }
+    }
+    let mut all_matched = true;
+    for route in &routes {
+        let (matched, report) = report(&tokens, settings, route.trim());
+        all_matched &= matched;
+        println!("{}", report);
+    }
+    if !all_matched {
+        process::exit(1)
+    }
+}
+
+/// Reads the options from the start of the arguments, returning the settings and the remaining
+/// arguments.
+fn parse_settings<I: Iterator<Item = String>>(
+    args: I,
+) -> Result<(MatcherSettings, Vec<String>), String> {
+    let mut settings = MatcherSettings::default();
+    let mut rest = Vec::new();
+    for arg in args {
+        if !rest.is_empty() || !arg.starts_with("--") {
+            rest.push(arg);
+            continue;
+        }
+        match arg.as_str() {
+            "--v2" => settings.grammar_version = GrammarVersion::V2,
+            "--case-insensitive" => settings.case_insensitive = true,
+            "--incomplete" => settings.complete = false,
+            "--query-mode=structural" => settings.query_mode = QueryMode::Structural,
+            "--query-mode=strict" => settings.query_mode = QueryMode::Strict,
+            "--query-mode=lenient" => settings.query_mode = QueryMode::Lenient,
+            _ => return Err(format!("unknown option '{}'", arg)),
+        }
+    }
+    Ok((settings, rest))
+}
+
+/// Matches the route, and describes what it captured, or that it didn't match.
+fn report(tokens: &[MatcherToken], settings: MatcherSettings, route: &str) -> (bool, String) {
+    let result = if settings.complete {
+        all_consuming(match_into_vec(tokens, &settings))(route)
+    } else {
+        match_into_vec(tokens, &settings)(route)
+    };
+    match result {
+        Ok((_, captures)) => {
+            let captures = captures
+                .iter()
+                .map(|(name, value)| format!("{}={:?}", name, value))
+                .collect::<Vec<_>>();
+            if captures.is_empty() {
+                (true, format!("{} matches", route))
+            } else {
+                (
+                    true,
+                    format!("{} matches, capturing {}", route, captures.join(" ")),
+                )
+            }
+        }
+        Err(_) => (false, format!("{} doesn't match", route)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reports_captures_and_failures() {
+        let args = vec!["--query-mode=strict", "/users/{id}?page={page}", "/users/5"];
+        let (settings, rest) =
+            parse_settings(args.into_iter().map(String::from)).expect("options are valid");
+        assert_eq!(settings.query_mode, QueryMode::Strict);
+        let tokens = parse_str_and_optimize_tokens_with_version(
+            &rest[0],
+            FieldType::Unnamed,
+            settings.grammar_version,
+        )
+        .expect("matcher is valid");
+        assert_eq!(
+            report(&tokens, settings, "/users/5?page=2"),
+            (
+                true,
+                "/users/5?page=2 matches, capturing id=\"5\" page=\"2\"".to_string()
+            )
+        );
+        assert_eq!(
+            report(&tokens, settings, "/posts/5"),
+            (false, "/posts/5 doesn't match".to_string())
+        );
+        assert!(parse_settings(vec!["--verbose".to_string()].into_iter()).is_err());
+    }
+}
@@ -1,30 +1,19 @@
 use crate::{
     error::{ExpectedToken, ParserErrorReason},
     parser::{CaptureOrExact, RefCaptureVariant, RouteParserToken},
-    ParseError,
+    settings::RESERVED_CHARS,
+    CaptureKind, FieldType, GrammarVersion, ParseError,
 };
 use nom::{
     branch::alt,
-    bytes::complete::take_till1,
-    character::{
-        complete::{char, digit1},
-        is_digit,
-    },
-    combinator::{map, map_parser},
+    bytes::complete::{tag, take, take_till1},
+    character::complete::{anychar, char, digit1},
+    combinator::{map, map_parser, opt},
     error::ErrorKind,
-    sequence::{delimited, separated_pair},
+    sequence::{delimited, preceded, separated_pair, terminated},
     IResult,
 };
 
-/// Indicates if the parser is working to create a matcher for a datastructure with named or unnamed fields.
-#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Ord, PartialOrd)]
-pub enum FieldType {
-    /// For Thing { field: String }
-    Named,
-    /// for Thing(String)
-    Unnamed,
-}
-
 pub fn get_slash(i: &str) -> IResult<&str, RouteParserToken, ParseError> {
     map(char('/'), |_: char| RouteParserToken::Separator)(i)
         .map_err(|_: nom::Err<()>| nom::Err::Error(ParseError::expected(ExpectedToken::Separator)))
@@ -77,61 +66,186 @@ fn get_star(i: &str) -> IResult<&str, (), ParseError> {
         .map_err(|_: nom::Err<()>| nom::Err::Error(ParseError::expected(ExpectedToken::Star)))
 }
 
+fn get_double_star(i: &str) -> IResult<&str, (), ParseError> {
+    map(tag("**"), |_: &str| ())(i)
+        .map_err(|_: nom::Err<()>| nom::Err::Error(ParseError::expected(ExpectedToken::Star)))
+}
+
 fn get_colon(i: &str) -> IResult<&str, (), ParseError> {
     map(char(':'), |_: char| ())(i)
         .map_err(|_: nom::Err<()>| nom::Err::Error(ParseError::expected(ExpectedToken::Colon)))
 }
 
-fn rust_ident(i: &str) -> IResult<&str, &str, ParseError> {
-    let invalid_ident_chars = r##" \|/{[]()?+=-!@#$%^&*~`'";:"##;
-    map_parser(take_till1(move |c| c == '}'), move |i: &str| {
-        match take_till1::<_, _, ()>(|c| invalid_ident_chars.contains(c))(i) {
-            Ok((remain, got)) => {
-                if got.len() > 0 && got.starts_with(|c: char| is_digit(c as u8)) {
-                    Err(nom::Err::Failure(ParseError {
-                        reason: Some(ParserErrorReason::BadRustIdent(got.chars().next().unwrap())),
-                        expected: vec![ExpectedToken::Ident],
-                        offset: 1,
-                    }))
-                } else if remain.len() > 0 {
-                    Err(nom::Err::Failure(ParseError {
-                        reason: Some(ParserErrorReason::BadRustIdent(
-                            remain.chars().next().unwrap(),
-                        )),
-                        expected: vec![ExpectedToken::CloseBracket, ExpectedToken::Ident],
-                        offset: got.len() + 1,
-                    }))
-                } else {
-                    Ok((i, i))
+fn get_semicolon(i: &str) -> IResult<&str, (), ParseError> {
+    map(char(';'), |_: char| ())(i)
+        .map_err(|_: nom::Err<()>| nom::Err::Error(ParseError::expected(ExpectedToken::Semicolon)))
+}
+
+/// Parses the identifier of a named capture.
+///
+/// The start of the capture's contents is used to point failures at the offending character.
+fn rust_ident<'a>(
+    capture_start: &'a str,
+) -> impl Fn(&'a str) -> IResult<&'a str, &'a str, ParseError> {
+    move |i: &str| {
+        let invalid_ident_chars = r##" \|/{[]()?+=-!@#$%^&*~`'";:"##;
+        // The identifier can be a part of the contents that ends before the rest of them, so the
+        // distance is measured between the starts of the strings.
+        let ident_offset = 1 + (i.as_ptr() as usize - capture_start.as_ptr() as usize);
+        map_parser(take_till1(move |c| c == '}'), move |i: &'a str| {
+            match take_till1::<_, _, ()>(|c| invalid_ident_chars.contains(c))(i) {
+                Ok((remain, got)) => {
+                    if got.len() > 0 && got.starts_with(|c: char| c.is_ascii_digit()) {
+                        Err(nom::Err::Failure(ParseError {
+                            reason: Some(ParserErrorReason::BadRustIdent(
+                                got.chars().next().unwrap(),
+                            )),
+                            expected: vec![ExpectedToken::Ident],
+                            offset: ident_offset,
+                        }))
+                    } else if remain.len() > 0 {
+                        Err(nom::Err::Failure(ParseError {
+                            reason: Some(ParserErrorReason::BadRustIdent(
+                                remain.chars().next().unwrap(),
+                            )),
+                            expected: vec![ExpectedToken::CloseBracket, ExpectedToken::Ident],
+                            offset: ident_offset + got.len(),
+                        }))
+                    } else {
+                        Ok((i, i))
+                    }
+                }
+                Err(_) => {
+                    Ok((i, i)) // TODO this might be right?
                 }
             }
-            Err(_) => {
-                Ok((i, i)) // TODO this might be right?
-            }
+        })(i)
+    }
+}
+
+/// Moves the offset of a failure that occurred partway into a token, so it is relative to the start
+/// of the token.
+fn offset_failure<'a, O>(
+    token_start: &'a str,
+    i: &'a str,
+    result: IResult<&'a str, O, ParseError>,
+) -> IResult<&'a str, O, ParseError> {
+    let consumed = token_start.len() - i.len();
+    result.map_err(|e| match e {
+        nom::Err::Error(mut error) => {
+            error.offset += consumed;
+            nom::Err::Error(error)
+        }
+        nom::Err::Failure(mut error) => {
+            error.offset += consumed;
+            nom::Err::Failure(error)
         }
-    })(i)
+        incomplete => incomplete,
+    })
 }
 
 fn exact_impl(i: &str) -> IResult<&str, &str, ParseError> {
-    let special_chars = r##"/?&#={}!"##; // TODO these might allow escaping one day.
-    take_till1(move |c| special_chars.contains(c))(i).map_err(|x: nom::Err<(&str, ErrorKind)>| {
-        let s = match x {
-            nom::Err::Error((s, _)) => s,
-            nom::Err::Failure((s, _)) => s,
-            nom::Err::Incomplete(_) => panic!(),
-        };
-        nom::Err::Error(ParseError {
+    exact_impl_until(i, r##"/?&#={}!"##)
+}
+
+/// Takes a literal, stopping at any of the provided special characters.
+///
+/// Literals also stop where a matrix parameter begins, but a `;` that isn't followed by `key=` is
+/// part of the literal.
+fn exact_impl_until<'a>(
+    i: &'a str,
+    special_chars: &'static str,
+) -> IResult<&'a str, &'a str, ParseError> {
+    let end = i
+        .char_indices()
+        .find(|&(index, c)| {
+            special_chars.contains(c) || (c == ';' && begins_matrix_param(&i[index..]))
+        })
+        .map_or(i.len(), |(index, _)| index);
+    if end == 0 {
+        Err(nom::Err::Error(ParseError {
             reason: Some(ParserErrorReason::BadLiteral),
             expected: vec![ExpectedToken::Literal],
-            offset: 1 + i.len() - s.len(),
-        })
-    })
+            offset: 0,
+        }))
+    } else {
+        Ok((&i[end..], &i[..end]))
+    }
+}
+
+/// Characters that can't appear in the key of a matrix parameter.
+const INVALID_MATRIX_KEY_CHARS: &str = r##"/?&#={}!;\"##;
+
+fn matrix_key(i: &str) -> IResult<&str, &str, ParseError> {
+    take_till1(|c| INVALID_MATRIX_KEY_CHARS.contains(c))(i).map_err(
+        |_: nom::Err<(&str, ErrorKind)>| {
+            nom::Err::Error(ParseError::expected(ExpectedToken::Literal))
+        },
+    )
+}
+
+/// Determines if the input starts with `;key=`.
+fn begins_matrix_param(i: &str) -> bool {
+    preceded(get_semicolon, terminated(matrix_key, get_eq))(i).is_ok()
 }
 
 pub fn exact(i: &str) -> IResult<&str, RouteParserToken, ParseError> {
     map(exact_impl, RouteParserToken::Exact)(i)
 }
 
+/// Matches a literal that may contain escaped characters.
+///
+/// Literal runs are broken up at each escape, so an escaped character is produced as its own
+/// `Exact` token containing just the character after the `\`.
+fn exact_escaped(i: &str) -> IResult<&str, RouteParserToken<'_>, ParseError> {
+    if let Some(c) = i.chars().next().filter(|&c| RESERVED_CHARS.contains(c)) {
+        return Err(nom::Err::Failure(ParseError {
+            reason: Some(ParserErrorReason::ReservedCharacter(c)),
+            expected: vec![],
+            offset: 0,
+        }));
+    }
+    let escaped_char = |i| {
+        preceded(char('\\'), take(1usize))(i).map_err(|_: nom::Err<()>| {
+            nom::Err::Error(ParseError::expected(ExpectedToken::Literal))
+        })
+    };
+    alt((
+        map(
+            |i| exact_impl_until(i, r##"/?&#={}!\[]()|"##),
+            RouteParserToken::Exact,
+        ),
+        map(escaped_char, RouteParserToken::Exact),
+    ))(i)
+}
+
+/// Matches a literal according to the rules of the grammar version.
+pub fn exact_for<'a>(
+    version: GrammarVersion,
+) -> impl Fn(&'a str) -> IResult<&'a str, RouteParserToken<'a>, ParseError> {
+    move |i: &str| match version {
+        GrammarVersion::V1 => exact(i),
+        GrammarVersion::V2 => exact_escaped(i),
+    }
+}
+
+/// Matches a literal directly following another literal.
+///
+/// This only happens in grammars that allow escapes, where a literal can be broken up into many
+/// tokens. In V1, this never matches.
+pub fn continue_exact<'a>(
+    version: GrammarVersion,
+) -> impl Fn(&'a str) -> IResult<&'a str, RouteParserToken<'a>, ParseError> {
+    move |i: &str| match version {
+        GrammarVersion::V1 => Err(nom::Err::Error(ParseError {
+            reason: None,
+            expected: vec![],
+            offset: 0,
+        })),
+        GrammarVersion::V2 => exact_escaped(i),
+    }
+}
+
 pub fn capture<'a>(
     field_type: FieldType,
 ) -> impl Fn(&'a str) -> IResult<&'a str, RouteParserToken<'a>, ParseError> {
@@ -161,25 +275,35 @@ fn capture_single_impl<'a>(
     }
 }
 
-/// Captures {ident}, {*:ident}, {<number>:ident}
+/// Captures {ident}, {*:ident}, {<number>:ident}, {ident:kind}, and their separated forms, like
+/// {ident;sep=-}
 fn capture_impl<'a>(
     field_type: FieldType,
 ) -> impl Fn(&'a str) -> IResult<&'a str, RefCaptureVariant, ParseError> {
     move |i: &str| match field_type {
         FieldType::Named => {
             let inner = alt((
+                separated::named_capture_impl,
+                named::greedy_many_capture_impl,
                 named::many_capture_impl,
                 named::numbered_capture_impl,
+                named::typed_capture_impl,
                 named::single_capture_impl,
             ));
             delimited(get_open_bracket, inner, get_close_bracket)(i)
         }
         FieldType::Unnamed => {
             let inner = alt((
+                separated::unnamed_capture_impl,
+                separated::named_capture_impl,
+                named::greedy_many_capture_impl,
                 named::many_capture_impl,
+                unnamed::greedy_many_capture_impl,
                 unnamed::many_capture_impl,
                 named::numbered_capture_impl,
                 unnamed::numbered_capture_impl,
+                named::typed_capture_impl,
+                unnamed::typed_capture_impl,
                 named::single_capture_impl,
                 unnamed::single_capture_impl,
             ));
@@ -191,25 +315,39 @@ fn capture_impl<'a>(
 mod named {
     use super::*;
     pub fn single_capture_impl(i: &str) -> IResult<&str, RefCaptureVariant, ParseError> {
-        map(rust_ident, |key| RefCaptureVariant::Named(key))(i)
+        map(rust_ident(i), |key| RefCaptureVariant::Named(key))(i)
     }
 
     pub fn many_capture_impl(i: &str) -> IResult<&str, RefCaptureVariant, ParseError> {
         map(
-            separated_pair(get_star, get_colon, rust_ident),
+            separated_pair(get_star, get_colon, rust_ident(i)),
             |(_, key)| RefCaptureVariant::ManyNamed(key),
         )(i)
     }
 
-    pub fn numbered_capture_impl(i: &str) -> IResult<&str, RefCaptureVariant, ParseError> {
+    pub fn greedy_many_capture_impl(i: &str) -> IResult<&str, RefCaptureVariant<'_>, ParseError> {
         map(
-            separated_pair(digit1, get_colon, rust_ident),
-            |(number, key)| RefCaptureVariant::NumberedNamed {
-                sections: number.parse().unwrap(),
-                name: key,
-            },
+            separated_pair(get_double_star, get_colon, rust_ident(i)),
+            |(_, key)| RefCaptureVariant::GreedyManyNamed(key),
         )(i)
     }
+
+    pub fn numbered_capture_impl(i: &str) -> IResult<&str, RefCaptureVariant, ParseError> {
+        let (ii, (number, key)) = separated_pair(digit1, get_colon, rust_ident(i))(i)?;
+        let variant = RefCaptureVariant::NumberedNamed {
+            sections: section_count(number)?,
+            name: key,
+        };
+        Ok((ii, variant))
+    }
+
+    /// Captures {ident:kind}
+    pub fn typed_capture_impl(i: &str) -> IResult<&str, RefCaptureVariant<'_>, ParseError> {
+        let (ii, name) = map_parser(take_till1(|c| c == ':' || c == '}'), rust_ident(i))(i)?;
+        let (ii, _) = get_colon(ii)?;
+        let (ii, kind) = capture_kind(i)(ii)?;
+        Ok((ii, RefCaptureVariant::TypedNamed { kind, name }))
+    }
 }
 
 mod unnamed {
@@ -225,10 +363,132 @@ mod unnamed {
         map(get_star, |_| RefCaptureVariant::ManyUnnamed)(i)
     }
 
+    pub fn greedy_many_capture_impl(i: &str) -> IResult<&str, RefCaptureVariant<'_>, ParseError> {
+        map(get_double_star, |_| RefCaptureVariant::GreedyManyUnnamed)(i)
+    }
+
     pub fn numbered_capture_impl(i: &str) -> IResult<&str, RefCaptureVariant, ParseError> {
-        map(digit1, |number: &str| RefCaptureVariant::NumberedUnnamed {
-            sections: number.parse().unwrap(),
-        })(i)
+        let (ii, number) = digit1(i)?;
+        let variant = RefCaptureVariant::NumberedUnnamed {
+            sections: section_count(number)?,
+        };
+        Ok((ii, variant))
+    }
+
+    /// Captures {:kind}
+    pub fn typed_capture_impl(i: &str) -> IResult<&str, RefCaptureVariant<'_>, ParseError> {
+        let (ii, _) = get_colon(i)?;
+        let (ii, kind) = capture_kind(i)(ii)?;
+        Ok((ii, RefCaptureVariant::TypedUnnamed { kind }))
+    }
+}
+
+mod separated {
+    use super::*;
+
+    /// Characters that can't separate sections, because they have another meaning in routes or
+    /// matchers.
+    const INVALID_SEPARATORS: &str = " /?&#={}!*:;\\";
+
+    const SEPARATOR_PREFIX: &str = ";sep=";
+
+    /// Succeeds without consuming anything if the capture declares a separator.
+    ///
+    /// This prevents the separated captures from reporting failures for captures that aren't
+    /// separated.
+    fn declares_separator(i: &str) -> IResult<&str, (), ParseError> {
+        let end = i.find('}').unwrap_or(i.len());
+        if i[..end].contains(SEPARATOR_PREFIX) {
+            Ok((i, ()))
+        } else {
+            Err(nom::Err::Error(ParseError {
+                reason: None,
+                expected: vec![],
+                offset: 0,
+            }))
+        }
+    }
+
+    /// Parses `;sep=<char>`.
+    ///
+    /// The start of the capture's contents is used to point failures at the separator.
+    fn separator<'a>(
+        capture_start: &'a str,
+    ) -> impl Fn(&'a str) -> IResult<&'a str, char, ParseError> {
+        move |i: &str| {
+            let (ii, separator) = preceded(tag(SEPARATOR_PREFIX), anychar)(i)?;
+            if INVALID_SEPARATORS.contains(separator) {
+                Err(nom::Err::Failure(ParseError {
+                    reason: Some(ParserErrorReason::BadSeparator(separator)),
+                    expected: vec![],
+                    offset: 1 + capture_start.len() - i.len() + SEPARATOR_PREFIX.len(),
+                }))
+            } else {
+                Ok((ii, separator))
+            }
+        }
+    }
+
+    fn sections(number: Option<&str>) -> Result<usize, nom::Err<ParseError>> {
+        number.map_or(Ok(1), section_count)
+    }
+
+    /// Captures {;sep=c} and {<number>;sep=c}
+    pub fn unnamed_capture_impl(i: &str) -> IResult<&str, RefCaptureVariant<'_>, ParseError> {
+        let (i, _) = declares_separator(i)?;
+        let (ii, number) = opt(digit1)(i)?;
+        let (ii, separator) = separator(i)(ii)?;
+        let variant = RefCaptureVariant::SeparatedUnnamed {
+            sections: sections(number)?,
+            separator,
+        };
+        Ok((ii, variant))
+    }
+
+    /// Captures {ident;sep=c} and {<number>:ident;sep=c}
+    pub fn named_capture_impl(i: &str) -> IResult<&str, RefCaptureVariant<'_>, ParseError> {
+        let (i, _) = declares_separator(i)?;
+        let (ii, number) = opt(terminated(digit1, get_colon))(i)?;
+        let (ii, name) = map_parser(take_till1(|c| c == ';'), rust_ident(i))(ii)?;
+        let (ii, separator) = separator(i)(ii)?;
+        let variant = RefCaptureVariant::SeparatedNamed {
+            sections: sections(number)?,
+            separator,
+            name,
+        };
+        Ok((ii, variant))
+    }
+}
+
+/// Converts the digits at the start of a capture into the number of sections that it captures.
+///
+/// The digits are always preceded by the `{` of the capture.
+fn section_count(number: &str) -> Result<usize, nom::Err<ParseError>> {
+    number.parse().map_err(|_| {
+        nom::Err::Failure(ParseError {
+            reason: Some(ParserErrorReason::TooManySections),
+            expected: vec![],
+            offset: 1,
+        })
+    })
+}
+
+/// Parses the kind of a typed capture, like the `u32` in `{id:u32}`.
+///
+/// The start of the capture's contents is used to point failures at the kind.
+fn capture_kind<'a>(
+    capture_start: &'a str,
+) -> impl Fn(&'a str) -> IResult<&'a str, CaptureKind, ParseError> {
+    move |i: &str| {
+        let end = i.find('}').unwrap_or(i.len());
+        match CaptureKind::from_name(&i[..end]) {
+            Some(kind) => Ok((&i[end..], kind)),
+            None => Err(nom::Err::Failure(ParseError {
+                reason: Some(ParserErrorReason::UnknownCaptureKind),
+                expected: vec![],
+                offset: 1 + capture_start.len() - i.len(),
+            })),
+        }
     }
 }
 
@@ -245,16 +505,106 @@ fn cap_or_exact<'a>(
 }
 
 /// Matches a query
+///
+/// The value can also be a many capture, like `tag={*:tags}`, which captures every value of the
+/// query parameter if it is repeated.
+/// Otherwise, it can be continued by `query_value`, like `{status}` in `filter=status:{status}`.
 pub fn query<'a>(
     field_type: FieldType,
 ) -> impl Fn(&'a str) -> IResult<&'a str, RouteParserToken<'a>, ParseError> {
     move |i: &str| {
-        map(
-            separated_pair(exact_impl, get_eq, cap_or_exact(field_type)),
-            |(ident, capture_or_exact)| RouteParserToken::Query {
+        let (ii, ident) = terminated(exact_impl, get_eq)(i)?;
+        let capture_or_exact = alt((
+            map(repeated_capture_impl(field_type), |capture| match capture {
+                RefCaptureVariant::ManyNamed(name) => {
+                    CaptureOrExact::Capture(RefCaptureVariant::RepeatedNamed { param: ident, name })
+                }
+                _ => CaptureOrExact::Capture(RefCaptureVariant::RepeatedUnnamed { param: ident }),
+            }),
+            cap_or_exact(field_type),
+        ))(ii);
+        let (ii, capture_or_exact) = offset_failure(i, ii, capture_or_exact)?;
+        Ok((
+            ii,
+            RouteParserToken::Query {
                 ident,
                 capture_or_exact,
             },
+        ))
+    }
+}
+
+/// Matches the next part of a query parameter's value, which is a capture after a literal, or a
+/// literal after a capture, like `{status}` in `filter=status:{status}`.
+///
+/// Nothing can follow a many capture, like `tag={*:tags}`.
+pub fn query_value<'a>(
+    field_type: FieldType,
+    previous: CaptureOrExact<'a>,
+) -> impl Fn(&'a str) -> IResult<&'a str, RouteParserToken<'a>, ParseError> {
+    move |i: &str| {
+        let part = match previous {
+            CaptureOrExact::Exact(_) => {
+                map(capture_single_impl(field_type), CaptureOrExact::Capture)(i)
+            }
+            CaptureOrExact::Capture(RefCaptureVariant::RepeatedNamed { .. })
+            | CaptureOrExact::Capture(RefCaptureVariant::RepeatedUnnamed { .. }) => Err(
+                nom::Err::Error(ParseError::expected(ExpectedToken::QuerySeparator)),
+            ),
+            CaptureOrExact::Capture(_) => map(exact_impl, CaptureOrExact::Exact)(i),
+        };
+        let (ii, part) = part?;
+        Ok((ii, RouteParserToken::QueryValue(part)))
+    }
+}
+
+/// Captures {*:ident}, or {*} if unnamed captures are allowed.
+fn repeated_capture_impl<'a>(
+    field_type: FieldType,
+) -> impl Fn(&'a str) -> IResult<&'a str, RefCaptureVariant<'a>, ParseError> {
+    move |i: &str| match field_type {
+        FieldType::Named => delimited(
+            get_open_bracket,
+            named::many_capture_impl,
+            get_close_bracket,
+        )(i),
+        FieldType::Unnamed => delimited(
+            get_open_bracket,
+            alt((named::many_capture_impl, unnamed::many_capture_impl)),
+            get_close_bracket,
+        )(i),
+    }
+}
+
+/// Matches a matrix parameter within a path segment, like `;key=value` or `;key={capture}`.
+///
+/// Captured values end at the next `;`, so they are represented as captures separated by it.
+pub fn matrix_param<'a>(
+    field_type: FieldType,
+) -> impl Fn(&'a str) -> IResult<&'a str, RouteParserToken<'a>, ParseError> {
+    move |i: &str| {
+        let value = |ii| offset_failure(i, ii, cap_or_exact(field_type)(ii));
+        map(
+            preceded(get_semicolon, separated_pair(matrix_key, get_eq, value)),
+            |(ident, capture_or_exact)| RouteParserToken::MatrixParam {
+                ident,
+                capture_or_exact: match capture_or_exact {
+                    CaptureOrExact::Capture(RefCaptureVariant::Named(name)) => {
+                        CaptureOrExact::Capture(RefCaptureVariant::SeparatedNamed {
+                            sections: 1,
+                            separator: ';',
+                            name,
+                        })
+                    }
+                    CaptureOrExact::Capture(_) => {
+                        CaptureOrExact::Capture(RefCaptureVariant::SeparatedUnnamed {
+                            sections: 1,
+                            separator: ';',
+                        })
+                    }
+                    exact => exact,
+                },
+            },
         )(i)
     }
 }
@@ -281,6 +631,28 @@ mod test {
     fn query_section_capture_named() {
         query(FieldType::Named)("lorem={ipsum}").expect("should parse");
     }
+    #[test]
+    fn query_section_mixed() {
+        let (rest, token) =
+            query(FieldType::Named)("filter=status:{status}&page=1").expect("should parse");
+        assert_eq!(
+            token,
+            RouteParserToken::Query {
+                ident: "filter",
+                capture_or_exact: CaptureOrExact::Exact("status:"),
+            }
+        );
+        let (rest, token) = query_value(FieldType::Named, CaptureOrExact::Exact("status:"))(rest)
+            .expect("should parse");
+        assert_eq!(
+            token,
+            RouteParserToken::QueryValue(CaptureOrExact::Capture(RefCaptureVariant::Named(
+                "status"
+            )))
+        );
+        assert_eq!(rest, "&page=1");
+    }
+
     #[test]
     fn query_section_capture_named_fails_without_key() {
         query(FieldType::Named)("lorem={}").expect_err("should not parse");
@@ -290,12 +662,147 @@ mod test {
         query(FieldType::Unnamed)("lorem={}").expect("should parse");
     }
 
+    #[test]
+    fn escaped_literal_v2() {
+        let (rest, token) = exact_for(GrammarVersion::V2)(r"\{lorem").expect("should parse");
+        assert_eq!(token, RouteParserToken::Exact("{"));
+        assert_eq!(rest, "lorem");
+    }
+
+    #[test]
+    fn reserved_characters_v2() {
+        let (rest, token) = exact_for(GrammarVersion::V2)("lorem(ipsum)").expect("should parse");
+        assert_eq!(token, RouteParserToken::Exact("lorem"));
+        assert_eq!(rest, "(ipsum)");
+        let e = exact_for(GrammarVersion::V2)(rest).expect_err("should not parse");
+        assert_eq!(
+            e,
+            nom::Err::Failure(ParseError {
+                reason: Some(ParserErrorReason::ReservedCharacter('(')),
+                expected: vec![],
+                offset: 0,
+            })
+        );
+        let (_, token) = exact_for(GrammarVersion::V2)(r"\|").expect("should parse");
+        assert_eq!(token, RouteParserToken::Exact("|"));
+        let (_, token) = exact_for(GrammarVersion::V1)("lorem(ipsum)").expect("should parse");
+        assert_eq!(token, RouteParserToken::Exact("lorem(ipsum)"));
+    }
+
+    #[test]
+    fn escapes_are_literals_in_v1() {
+        let (rest, token) = exact_for(GrammarVersion::V1)(r"lo\rem").expect("should parse");
+        assert_eq!(token, RouteParserToken::Exact(r"lo\rem"));
+        assert_eq!(rest, "");
+    }
+
+    #[test]
+    fn greedy_many_captures() {
+        let (_, named) = capture(FieldType::Named)("{**:path}").expect("should parse");
+        assert_eq!(
+            named,
+            RouteParserToken::Capture(RefCaptureVariant::GreedyManyNamed("path"))
+        );
+        let (_, unnamed) = capture(FieldType::Unnamed)("{**}").expect("should parse");
+        assert_eq!(
+            unnamed,
+            RouteParserToken::Capture(RefCaptureVariant::GreedyManyUnnamed)
+        );
+    }
+
+    #[test]
+    fn literal_stops_at_matrix_param() {
+        let (rest, token) = exact("users;role=admin").expect("should parse");
+        assert_eq!(token, RouteParserToken::Exact("users"));
+        assert_eq!(rest, ";role=admin");
+    }
+
+    #[test]
+    fn semicolon_without_key_is_literal() {
+        let (rest, token) = exact("users;admin").expect("should parse");
+        assert_eq!(token, RouteParserToken::Exact("users;admin"));
+        assert_eq!(rest, "");
+    }
+
+    #[test]
+    fn matrix_param_capture_is_separated() {
+        let (_, token) = matrix_param(FieldType::Named)(";role={role}").expect("should parse");
+        assert_eq!(
+            token,
+            RouteParserToken::MatrixParam {
+                ident: "role",
+                capture_or_exact: CaptureOrExact::Capture(RefCaptureVariant::SeparatedNamed {
+                    sections: 1,
+                    separator: ';',
+                    name: "role"
+                })
+            }
+        );
+    }
+
+    #[test]
+    fn typed_captures() {
+        let (_, named) = capture(FieldType::Named)("{id:u32}").expect("should parse");
+        assert_eq!(
+            named,
+            RouteParserToken::Capture(RefCaptureVariant::TypedNamed {
+                kind: CaptureKind::U32,
+                name: "id"
+            })
+        );
+        let (_, unnamed) = capture(FieldType::Unnamed)("{:uuid}").expect("should parse");
+        assert_eq!(
+            unnamed,
+            RouteParserToken::Capture(RefCaptureVariant::TypedUnnamed {
+                kind: CaptureKind::Uuid
+            })
+        );
+    }
+
+    #[test]
+    fn repeated_query_captures() {
+        let (_, named) = query(FieldType::Named)("tag={*:tags}").expect("should parse");
+        assert_eq!(
+            named,
+            RouteParserToken::Query {
+                ident: "tag",
+                capture_or_exact: CaptureOrExact::Capture(RefCaptureVariant::RepeatedNamed {
+                    param: "tag",
+                    name: "tags"
+                })
+            }
+        );
+        let (_, unnamed) = query(FieldType::Unnamed)("tag={*}").expect("should parse");
+        assert_eq!(
+            unnamed,
+            RouteParserToken::Query {
+                ident: "tag",
+                capture_or_exact: CaptureOrExact::Capture(RefCaptureVariant::RepeatedUnnamed {
+                    param: "tag"
+                })
+            }
+        );
+    }
+
+    #[test]
+    fn literal_failure_points_at_first_character() {
+        let e = exact("}").expect_err("should not parse");
+        assert_eq!(
+            e,
+            nom::Err::Error(ParseError {
+                reason: Some(ParserErrorReason::BadLiteral),
+                expected: vec![ExpectedToken::Literal],
+                offset: 0,
+            })
+        );
+    }
+
     #[test]
     fn non_leading_numbers_in_ident() {
-        rust_ident("hello5").expect("sholud parse");
+        rust_ident("hello5")("hello5").expect("sholud parse");
     }
     #[test]
     fn leading_numbers_in_ident_fails() {
-        rust_ident("5hello").expect_err("sholud not parse");
+        rust_ident("5hello")("5hello").expect_err("sholud not parse");
     }
 }
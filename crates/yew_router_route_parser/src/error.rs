@@ -1,7 +1,11 @@
+use crate::CaptureKind;
 use nom::error::ErrorKind;
-use std::fmt;
+use std::{fmt, ops::Range};
 
 /// Parser error that can print itself in a human-readable format.
+///
+/// The `Display` implementation renders the input with a caret underlining where the failure
+/// occurred, while the accessors expose the same information in a machine-readable form.
 #[derive(Clone, PartialEq)]
 pub struct PrettyParseError<'a> {
     /// Inner error
@@ -17,7 +21,38 @@ fn offset(input: &str, substring: &str) -> usize {
     input.len() - substring.len()
 }
 
-impl<'a> fmt::Debug for PrettyParseError<'a> {
+impl<'a> PrettyParseError<'a> {
+    /// The byte offset into the input at which the parse failed.
+    pub fn offset(&self) -> usize {
+        offset(self.input, self.remaining) + self.error.offset
+    }
+
+    /// The byte range of the input that caused the failure.
+    ///
+    /// This covers the character at `offset()`, or is empty if the failure occurred at the end
+    /// of the input.
+    pub fn span(&self) -> Range<usize> {
+        let start = self.offset().min(self.input.len());
+        let len = self.input[start..]
+            .chars()
+            .next()
+            .map(char::len_utf8)
+            .unwrap_or(0);
+        start..start + len
+    }
+
+    /// The tokens that would have allowed the parse to continue.
+    pub fn expected(&self) -> &[ExpectedToken] {
+        &self.error.expected
+    }
+
+    /// A concrete reason for the failure, if one could be determined.
+    pub fn reason(&self) -> Option<ParserErrorReason> {
+        self.error.reason
+    }
+}
+
+impl<'a> fmt::Display for PrettyParseError<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str("Could not parse route.")?;
         f.write_str("\n")?;
@@ -27,9 +62,9 @@ impl<'a> fmt::Debug for PrettyParseError<'a> {
         f.write_str(self.input)?;
         f.write_str("\n")?;
 
-        let offset = offset(self.input, self.remaining);
-        let offset = offset + self.error.offset;
-        let pad = (0..offset + route_str.len())
+        // The caret is placed under the character, not the byte, that the failure occurred at.
+        let offset = self.span().start;
+        let pad = (0..self.input[..offset].chars().count() + route_str.len())
             .map(|_| '-')
             .collect::<String>();
         f.write_str(&format!("{}^", pad))?;
@@ -61,6 +96,15 @@ impl<'a> fmt::Debug for PrettyParseError<'a> {
     }
 }
 
+/// The human-readable rendering is used here so that `expect`ing a parse shows the caret.
+impl<'a> fmt::Debug for PrettyParseError<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        <Self as fmt::Display>::fmt(self, f)
+    }
+}
+
+impl<'a> std::error::Error for PrettyParseError<'a> {}
+
 /// Error for parsing the route
 #[derive(Debug, Clone, PartialEq)]
 pub struct ParseError {
@@ -108,6 +152,7 @@ impl nom::error::ParseError<&str> for ParseError {
     }
 }
 
+/// A token that the parser expected to encounter.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ExpectedToken {
     ///  /
@@ -116,9 +161,9 @@ pub enum ExpectedToken {
     Literal,
     ///  {name}.
     CaptureNamed,
-    // {5:name}
+    /// {5:name}
     CaptureNumberedNamed,
-    // {*:name}
+    /// {*:name}
     CaptureManyNamed,
     ///  ?
     QueryBegin,
@@ -126,7 +171,7 @@ pub enum ExpectedToken {
     QuerySeparator,
     ///  x={y}
     QueryCapture,
-    // x=y
+    /// x=y
     QueryLiteral,
     /// \#
     FragmentBegin,
@@ -144,6 +189,8 @@ pub enum ExpectedToken {
     Star,
     /// :
     Colon,
+    /// ;
+    Semicolon,
 }
 
 impl fmt::Display for ExpectedToken {
@@ -166,6 +213,7 @@ impl fmt::Display for ExpectedToken {
             ExpectedToken::Equals => f.write_str("="),
             ExpectedToken::Star => f.write_str("*"),
             ExpectedToken::Colon => f.write_str(":"),
+            ExpectedToken::Semicolon => f.write_str(";"),
         }
     }
 }
@@ -187,8 +235,17 @@ pub enum ParserErrorReason {
     MultipleQuestions,
     /// The provided ident within a capture group could never match with a valid rust identifier.
     BadRustIdent(char),
+    /// The provided separator of a capture group can't separate sections, because it has another
+    /// meaning in routes or matchers.
+    BadSeparator(char),
+    /// The number of sections that a capture group captures is too large to be represented.
+    TooManySections,
+    /// The kind that a typed capture group is restricted to doesn't exist.
+    UnknownCaptureKind,
     /// A bad literal.
     BadLiteral,
+    /// A character that is reserved for syntax that isn't supported yet appeared unescaped.
+    ReservedCharacter(char),
     /// Invalid state
     InvalidState,
     /// Internal check on valid state transitions
@@ -228,6 +285,22 @@ impl fmt::Display for ParserErrorReason {
                     c
                 ))?;
             }
+            ParserErrorReason::BadSeparator(c) => {
+                f.write_str(&format!(
+                    "The character: '{}' can't be used to separate the sections of a capture.",
+                    c
+                ))?;
+            }
+            ParserErrorReason::TooManySections => {
+                f.write_str("The number of sections to capture is too large.")?;
+            }
+            ParserErrorReason::UnknownCaptureKind => {
+                let kinds: Vec<&str> = CaptureKind::names().collect();
+                f.write_str(&format!(
+                    "Unknown capture kind. Expected one of: {}.",
+                    kinds.join(", ")
+                ))?;
+            }
             ParserErrorReason::EndAfterCapture => {
                 f.write_str("The end token (!) can't appear after a capture ({}).")?;
             }
@@ -237,6 +310,12 @@ impl fmt::Display for ParserErrorReason {
             ParserErrorReason::BadLiteral => {
                 f.write_str("Malformed literal.")?;
             }
+            ParserErrorReason::ReservedCharacter(c) => {
+                f.write_str(&format!(
+                    "The character: '{}' is reserved for future syntax. Escape it with a '\\' to match it literally.",
+                    c
+                ))?;
+            }
         }
         Ok(())
     }
@@ -248,3 +327,95 @@ pub(crate) fn get_reason(err: &mut nom::Err<ParseError>) -> &mut Option<ParserEr
         nom::Err::Incomplete(_) => panic!("Incomplete not possible"),
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{parser::parse, FieldType};
+
+    #[test]
+    fn span_points_at_failure() {
+        let e = parse("/lorem//", FieldType::Unnamed).expect_err("should not parse");
+        assert_eq!(e.offset(), 7);
+        assert_eq!(e.span(), 7..8);
+        assert_eq!(e.reason(), Some(ParserErrorReason::DoubleSlash));
+    }
+
+    #[test]
+    fn span_includes_sub_parser_offset() {
+        let e = parse("/{lor#m}", FieldType::Unnamed).expect_err("should not parse");
+        assert_eq!(&e.input[e.span()], "#");
+        assert_eq!(
+            e.expected(),
+            &[ExpectedToken::CloseBracket, ExpectedToken::Ident]
+        );
+    }
+
+    #[test]
+    fn span_points_into_captures_after_their_start() {
+        let e = parse("/{*:0a}", FieldType::Named).expect_err("should not parse");
+        assert_eq!(e.reason(), Some(ParserErrorReason::BadRustIdent('0')));
+        assert_eq!(e.offset(), 4);
+        let e = parse("/{3:lor#m}", FieldType::Named).expect_err("should not parse");
+        assert_eq!(&e.input[e.span()], "#");
+        let e = parse("/{lor#m:u32}", FieldType::Named).expect_err("should not parse");
+        assert_eq!(&e.input[e.span()], "#");
+    }
+
+    #[test]
+    fn span_points_into_query_and_matrix_values() {
+        let e = parse("/lorem?ipsum={0a}", FieldType::Named).expect_err("should not parse");
+        assert_eq!(e.reason(), Some(ParserErrorReason::BadRustIdent('0')));
+        assert_eq!(e.offset(), 14);
+        let e = parse("/lorem?ipsum=a{b}c{0d}", FieldType::Named).expect_err("should not parse");
+        assert_eq!(e.offset(), 19);
+        let e = parse("/lorem;ipsum={0a}", FieldType::Named).expect_err("should not parse");
+        assert_eq!(e.offset(), 14);
+    }
+
+    #[test]
+    fn section_count_overflow_is_an_error() {
+        let e = parse("/{99999999999999999999999:lorem}", FieldType::Unnamed)
+            .expect_err("should not parse");
+        assert_eq!(e.reason(), Some(ParserErrorReason::TooManySections));
+        assert_eq!(e.offset(), 2);
+        let e = parse("/{99999999999999999999999;sep=-}", FieldType::Unnamed)
+            .expect_err("should not parse");
+        assert_eq!(e.reason(), Some(ParserErrorReason::TooManySections));
+    }
+
+    #[test]
+    fn unknown_capture_kind_is_an_error() {
+        let e = parse("/{lorem:u33}", FieldType::Named).expect_err("should not parse");
+        assert_eq!(e.reason(), Some(ParserErrorReason::UnknownCaptureKind));
+        assert_eq!(&e.input[e.span()], "u");
+    }
+
+    #[test]
+    fn display_underlines_failure() {
+        let e = parse("//", FieldType::Unnamed).expect_err("should not parse");
+        let rendered = e.to_string();
+        let mut lines = rendered.lines();
+        assert_eq!(lines.next(), Some("Could not parse route."));
+        assert_eq!(lines.next(), Some("Route: //"));
+        assert_eq!(lines.next(), Some("--------^"));
+    }
+
+    #[test]
+    fn display_underlines_multibyte_failure() {
+        let e = parse("/bücher//", FieldType::Unnamed).expect_err("should not parse");
+        assert_eq!(e.offset(), 9);
+        let rendered = e.to_string();
+        let mut lines = rendered.lines();
+        assert_eq!(lines.nth(1), Some("Route: /bücher//"));
+        assert_eq!(lines.next(), Some("---------------^"));
+    }
+
+    #[test]
+    fn multibyte_idents_are_not_digits() {
+        let e = parse("/{0a}", FieldType::Named).expect_err("should not parse");
+        assert_eq!(e.reason(), Some(ParserErrorReason::BadRustIdent('0')));
+        // U+0130 truncates to the byte of `0`, which must not make it look like a digit.
+        assert!(parse("/{\u{130}d}", FieldType::Named).is_ok());
+    }
+}
@@ -0,0 +1,160 @@
+//! Kinds of values that captures can be restricted to, like `{id:u32}`.
+
+/// The kind of value that a typed capture accepts.
+///
+/// Routes containing a section that isn't of the right kind don't match, so the next route can be
+/// tried instead, like `/users/{id:u32}` not matching `/users/new`.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CaptureKind {
+    /// u8
+    U8,
+    /// u16
+    U16,
+    /// u32
+    U32,
+    /// u64
+    U64,
+    /// u128
+    U128,
+    /// usize
+    Usize,
+    /// i8
+    I8,
+    /// i16
+    I16,
+    /// i32
+    I32,
+    /// i64
+    I64,
+    /// i128
+    I128,
+    /// isize
+    Isize,
+    /// uuid - either hyphenated, like `5dc48134-35b5-4b8c-aa93-767bf00ae1d8`, or 32 hexadecimal
+    /// digits without hyphens.
+    Uuid,
+    /// stem - the stem of a file name, which ends at the last `.` of the section, so
+    /// `/files/{name:stem}.{ext}` captures "archive.tar" and "gz" from "/files/archive.tar.gz".
+    ///
+    /// Other captures end at the first `.` that follows them.
+    Stem,
+}
+
+/// Every kind, in the order they are listed in errors.
+const KINDS: [CaptureKind; 14] = [
+    CaptureKind::U8,
+    CaptureKind::U16,
+    CaptureKind::U32,
+    CaptureKind::U64,
+    CaptureKind::U128,
+    CaptureKind::Usize,
+    CaptureKind::I8,
+    CaptureKind::I16,
+    CaptureKind::I32,
+    CaptureKind::I64,
+    CaptureKind::I128,
+    CaptureKind::Isize,
+    CaptureKind::Uuid,
+    CaptureKind::Stem,
+];
+
+impl CaptureKind {
+    /// Gets the kind with the name used in matcher strings.
+    pub fn from_name(name: &str) -> Option<Self> {
+        KINDS.iter().cloned().find(|kind| kind.name() == name)
+    }
+
+    /// The name of the kind, as it is written in matcher strings.
+    pub fn name(self) -> &'static str {
+        match self {
+            CaptureKind::U8 => "u8",
+            CaptureKind::U16 => "u16",
+            CaptureKind::U32 => "u32",
+            CaptureKind::U64 => "u64",
+            CaptureKind::U128 => "u128",
+            CaptureKind::Usize => "usize",
+            CaptureKind::I8 => "i8",
+            CaptureKind::I16 => "i16",
+            CaptureKind::I32 => "i32",
+            CaptureKind::I64 => "i64",
+            CaptureKind::I128 => "i128",
+            CaptureKind::Isize => "isize",
+            CaptureKind::Uuid => "uuid",
+            CaptureKind::Stem => "stem",
+        }
+    }
+
+    /// Iterates over the names of every kind.
+    pub fn names() -> impl Iterator<Item = &'static str> {
+        KINDS.iter().map(|kind| kind.name())
+    }
+
+    /// Determines if a captured section is of this kind.
+    ///
+    /// Integers must fit into their type, so `256` isn't a `u8`.
+    pub fn accepts(self, section: &str) -> bool {
+        match self {
+            CaptureKind::U8 => section.parse::<u8>().is_ok(),
+            CaptureKind::U16 => section.parse::<u16>().is_ok(),
+            CaptureKind::U32 => section.parse::<u32>().is_ok(),
+            CaptureKind::U64 => section.parse::<u64>().is_ok(),
+            CaptureKind::U128 => section.parse::<u128>().is_ok(),
+            CaptureKind::Usize => section.parse::<usize>().is_ok(),
+            CaptureKind::I8 => section.parse::<i8>().is_ok(),
+            CaptureKind::I16 => section.parse::<i16>().is_ok(),
+            CaptureKind::I32 => section.parse::<i32>().is_ok(),
+            CaptureKind::I64 => section.parse::<i64>().is_ok(),
+            CaptureKind::I128 => section.parse::<i128>().is_ok(),
+            CaptureKind::Isize => section.parse::<isize>().is_ok(),
+            CaptureKind::Uuid => is_uuid(section),
+            CaptureKind::Stem => true,
+        }
+    }
+}
+
+fn is_uuid(section: &str) -> bool {
+    let is_hex = |group: &str| group.chars().all(|c| c.is_ascii_hexdigit());
+    if section.len() == 32 {
+        return is_hex(section);
+    }
+    let mut groups = section.split('-');
+    [8, 4, 4, 4, 12].iter().all(|len| {
+        groups
+            .next()
+            .filter(|group| group.len() == *len && is_hex(group))
+            .is_some()
+    }) && groups.next().is_none()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn names_round_trip() {
+        for kind in KINDS.iter() {
+            assert_eq!(CaptureKind::from_name(kind.name()), Some(*kind));
+        }
+        assert_eq!(CaptureKind::from_name("String"), None);
+    }
+
+    #[test]
+    fn integers_must_fit() {
+        assert!(CaptureKind::U8.accepts("255"));
+        assert!(!CaptureKind::U8.accepts("256"));
+        assert!(!CaptureKind::U32.accepts("-1"));
+        assert!(CaptureKind::I32.accepts("-1"));
+        assert!(!CaptureKind::U32.accepts("new"));
+        assert!(!CaptureKind::U32.accepts(""));
+    }
+
+    #[test]
+    fn uuids() {
+        assert!(CaptureKind::Uuid.accepts("5dc48134-35b5-4b8c-aa93-767bf00ae1d8"));
+        assert!(CaptureKind::Uuid.accepts("5dc4813435b54b8caa93767bf00ae1d8"));
+        assert!(!CaptureKind::Uuid.accepts("5dc48134-35b5-4b8c-aa93-767bf00ae1d"));
+        assert!(!CaptureKind::Uuid.accepts("5dc48134-35b5-4b8c-aa93-767bf00ae1d8-"));
+        assert!(!CaptureKind::Uuid.accepts("5dc48134-35b5-4b8c-aa93-767bf00ae1dg"));
+    }
+}
@@ -1,7 +1,21 @@
 //! Parser for yew-router's matcher syntax.
 //! This syntax allows specifying if a route should produce an enum variant or struct,
 //! and allows capturing sections from the route to be incorporated into its associated variant or struct.
+//!
+//! ## Features
+//! * "serde" - If enabled, `MatcherToken`, `CaptureVariant`, `CaptureKind`, `GrammarVersion` and
+//!   `QueryMode` implement `Serialize` and `Deserialize`, so optimized matchers can be stored as data instead
+//!   of being parsed from strings at runtime.
+//! * "std" - Enabled by default. Parsing matcher strings relies on the standard library.
+//!   Without it, the crate is `no_std` and only requires `alloc`: the token types, the conversion
+//!   of parser tokens into matcher tokens, the analysis of matchers in `shadows`, and matching
+//!   routes against tokens in `matcher` remain available, so tools that load already optimized
+//!   matchers can still reuse them.
+//! * "cli" - If enabled, the `yew-router-match` binary will be built, which prints what routes
+//!   capture when they are matched against a matcher string, like for checking route fixtures in
+//!   CI.
 
+#![cfg_attr(not(feature = "std"), no_std)]
 #![deny(
     missing_docs,
     missing_debug_implementations,
@@ -13,23 +27,48 @@
     unused_qualifications
 )]
 
+extern crate alloc;
+
+#[cfg(feature = "std")]
 mod core;
+#[cfg(feature = "std")]
 mod error;
+mod kind;
+mod lint;
+pub mod matcher;
 pub mod parser;
-pub use crate::core::FieldType;
-pub use error::{ParseError, PrettyParseError};
+mod settings;
+#[cfg(feature = "std")]
+pub use error::{ExpectedToken, ParseError, ParserErrorReason, PrettyParseError};
+pub use kind::CaptureKind;
+pub use lint::shadows;
+pub use settings::{FieldType, GrammarVersion, MatcherSettings, QueryMode};
 mod optimizer;
-pub use optimizer::{convert_tokens, parse_str_and_optimize_tokens};
+use alloc::string::String;
+#[cfg(feature = "std")]
+pub use optimizer::{
+    capture_names, capture_names_with_version, parse_str_and_optimize_tokens,
+    parse_str_and_optimize_tokens_with_version,
+};
+pub use optimizer::{convert_tokens, is_anchored, to_matcher_string};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "std")]
 use std::collections::HashMap;
 
 /// Alias of `HashMap<&'a str, String>` that represent strings captured from a route.
 ///
 /// Captures contain keys corresponding to named match sections,
 /// and values containing the content captured by those sections.
+#[cfg(feature = "std")]
 pub type Captures<'a> = HashMap<&'a str, String>;
 
 /// Tokens used to determine how to match and capture sections from a URL.
+///
+/// Tokens only own plain data, so they are `Send` and `Sync`, and optimized matchers can be kept
+/// in statics and shared between threads.
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum MatcherToken {
     /// Section-related tokens can be condensed into a match.
     Exact(String),
@@ -38,16 +77,21 @@ pub enum MatcherToken {
     /// End token - if the string hasn't been consumed entirely, then the parse will fail.
     /// This is useful for being able to specify more general matchers for variants that would
     /// otherwise match above more specific variants.
+    ///
+    /// If present, this is always the last token.
     End,
 }
 
 /// Variants that indicate how part of a string should be captured.
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum CaptureVariant {
     /// {}
     Unnamed,
     /// {*}
     ManyUnnamed,
+    /// {**} - like {*}, but stops at the last occurrence of what follows it instead of the first.
+    GreedyManyUnnamed,
     /// {5}
     NumberedUnnamed {
         /// Number of sections to match.
@@ -57,6 +101,9 @@ pub enum CaptureVariant {
     Named(String),
     /// {*:name} - captures over many sections and adds it to the map with a given name.
     ManyNamed(String),
+    /// {**:name} - like {*:name}, but stops at the last occurrence of what follows it instead of
+    /// the first.
+    GreedyManyNamed(String),
     /// {2:name} - captures a fixed number of sections with a given name.
     NumberedNamed {
         /// Number of sections to match.
@@ -64,4 +111,85 @@ pub enum CaptureVariant {
         /// The key to be entered in the `Matches` map.
         name: String,
     },
+    /// {;sep=-} or {3;sep=-} - captures a fixed number of sections that are separated by a
+    /// character other than `/`.
+    SeparatedUnnamed {
+        /// Number of sections to match.
+        sections: usize,
+        /// The character that separates the sections.
+        separator: char,
+    },
+    /// {name;sep=-} or {3:name;sep=-} - captures a fixed number of sections that are separated by
+    /// a character other than `/`, with a given name.
+    SeparatedNamed {
+        /// Number of sections to match.
+        sections: usize,
+        /// The character that separates the sections.
+        separator: char,
+        /// The key to be entered in the `Matches` map.
+        name: String,
+    },
+    /// {:u32} - captures a section, which only matches if it is of the given kind.
+    TypedUnnamed {
+        /// The kind of value that the section must contain.
+        kind: CaptureKind,
+    },
+    /// {name:u32} - captures a section with a given name, which only matches if it is of the
+    /// given kind.
+    TypedNamed {
+        /// The kind of value that the section must contain.
+        kind: CaptureKind,
+        /// The key to be entered in the `Matches` map.
+        name: String,
+    },
+    /// ?param={*} - captures every value of a query parameter that is repeated, like
+    /// `?tag=a&tag=b`.
+    ///
+    /// The values must follow each other, and are captured joined by `&`, like `a&b`.
+    RepeatedUnnamed {
+        /// The name of the query parameter.
+        param: String,
+    },
+    /// ?param={*:name} - captures every value of a query parameter that is repeated, like
+    /// `?tag=a&tag=b`, with a given name.
+    ///
+    /// The values must follow each other, and are captured joined by `&`, like `a&b`.
+    RepeatedNamed {
+        /// The name of the query parameter.
+        param: String,
+        /// The key to be entered in the `Matches` map.
+        name: String,
+    },
+}
+
+impl CaptureVariant {
+    /// Gets the key that the capture is entered into the `Matches` map with, if it is named.
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            CaptureVariant::Named(name)
+            | CaptureVariant::ManyNamed(name)
+            | CaptureVariant::GreedyManyNamed(name)
+            | CaptureVariant::NumberedNamed { name, .. }
+            | CaptureVariant::SeparatedNamed { name, .. }
+            | CaptureVariant::TypedNamed { name, .. }
+            | CaptureVariant::RepeatedNamed { name, .. } => Some(name),
+            CaptureVariant::Unnamed
+            | CaptureVariant::ManyUnnamed
+            | CaptureVariant::GreedyManyUnnamed
+            | CaptureVariant::NumberedUnnamed { .. }
+            | CaptureVariant::SeparatedUnnamed { .. }
+            | CaptureVariant::TypedUnnamed { .. }
+            | CaptureVariant::RepeatedUnnamed { .. } => None,
+        }
+    }
+
+    /// Gets the kind of value that the capture is restricted to, if it is typed.
+    pub fn kind(&self) -> Option<CaptureKind> {
+        match self {
+            CaptureVariant::TypedUnnamed { kind } | CaptureVariant::TypedNamed { kind, .. } => {
+                Some(*kind)
+            }
+            _ => None,
+        }
+    }
 }
@@ -0,0 +1,226 @@
+//! Analysis of matchers to find routes that can never be reached.
+use crate::{CaptureVariant, MatcherToken};
+use ::core::{iter::Peekable, slice::Iter};
+
+/// Characters that a single section capture can't consume.
+const INVALID_CAPTURE_CHARACTERS: &str = " */#&?{}=";
+/// Characters that a many section capture can't consume.
+const INVALID_MANY_CAPTURE_CHARACTERS: &str = " #&?=";
+
+/// Determines if the `earlier` matcher shadows the `later` one.
+///
+/// A matcher is shadowed if every route it matches would already be matched by a matcher that is
+/// tried before it.
+/// This assumes the matching rules used by a derived `Switch`:
+/// literals are compared case insensitively and a matcher doesn't need to consume the whole route
+/// unless it ends with the end token.
+///
+/// The analysis is conservative: it only returns true if it can prove that `later` is shadowed.
+/// It doesn't know whether the captured sections can be converted into the fields of a variant,
+/// so callers should only consider shadowing by matchers whose captures can't fail to convert.
+pub fn shadows(earlier: &[MatcherToken], later: &[MatcherToken]) -> bool {
+    if earlier == later {
+        return true;
+    }
+    let mut earlier = earlier.iter().peekable();
+    let mut later = later.iter().peekable();
+    // Literal text from `later` that has yet to be matched by `earlier`.
+    let mut pending: &str = "";
+
+    while let Some(token) = earlier.next() {
+        match token {
+            MatcherToken::Exact(literal) => {
+                if literal.is_empty() {
+                    continue;
+                }
+                if pending.is_empty() {
+                    match later.next() {
+                        Some(MatcherToken::Exact(next)) => pending = next.as_str(),
+                        _ => return false,
+                    }
+                }
+                if pending.len() < literal.len()
+                    || !pending.is_char_boundary(literal.len())
+                    || pending[..literal.len()].to_lowercase() != literal.to_lowercase()
+                {
+                    return false;
+                }
+                pending = &pending[literal.len()..];
+            }
+            MatcherToken::Capture(capture) => match earlier.peek() {
+                None => return captures_remainder(capture, pending, later),
+                Some(MatcherToken::Exact(delimiter)) => {
+                    if !stops_at_first_delimiter(capture) {
+                        return false;
+                    }
+                    if !pending.is_empty() {
+                        // The capture consumes everything up to the first occurrence of the
+                        // delimiter, which is then matched by the next exact token.
+                        match pending.find(delimiter.as_str()) {
+                            Some(position) => pending = &pending[position..],
+                            None => return false,
+                        }
+                    } else {
+                        match later.next() {
+                            Some(MatcherToken::Capture(later_capture))
+                                if stops_at_first_delimiter(later_capture)
+                                    || later_capture.kind().is_some() =>
+                            {
+                                match later.peek() {
+                                    // Both captures stop at the same place.
+                                    Some(MatcherToken::Exact(next)) if next == delimiter => {}
+                                    // The delimiter will be found at the latest where the later
+                                    // capture stops, and nothing else needs to match after it.
+                                    Some(MatcherToken::Exact(next))
+                                        if next.starts_with(delimiter.as_str())
+                                            && earlier.len() == 1 =>
+                                    {
+                                        return true
+                                    }
+                                    _ => return false,
+                                }
+                            }
+                            _ => return false,
+                        }
+                    }
+                }
+                Some(_) => return false,
+            },
+            MatcherToken::End => {
+                return pending.is_empty() && later.next() == Some(&MatcherToken::End);
+            }
+        }
+    }
+    // The earlier matcher has matched a prefix of every route that the later one matches.
+    true
+}
+
+/// Determines if a capture that ends the earlier matcher will match whatever remains of the
+/// later matcher.
+fn captures_remainder(
+    capture: &CaptureVariant,
+    pending: &str,
+    mut later: Peekable<Iter<MatcherToken>>,
+) -> bool {
+    let invalid_characters = match capture {
+        CaptureVariant::Named(_) | CaptureVariant::Unnamed => INVALID_CAPTURE_CHARACTERS,
+        CaptureVariant::ManyNamed(_)
+        | CaptureVariant::ManyUnnamed
+        | CaptureVariant::GreedyManyNamed(_)
+        | CaptureVariant::GreedyManyUnnamed => INVALID_MANY_CAPTURE_CHARACTERS,
+        // Typed captures can reject what they capture.
+        CaptureVariant::NumberedNamed { .. }
+        | CaptureVariant::NumberedUnnamed { .. }
+        | CaptureVariant::SeparatedNamed { .. }
+        | CaptureVariant::SeparatedUnnamed { .. }
+        | CaptureVariant::TypedNamed { .. }
+        | CaptureVariant::TypedUnnamed { .. }
+        | CaptureVariant::RepeatedNamed { .. }
+        | CaptureVariant::RepeatedUnnamed { .. } => return false,
+    };
+    if let Some(first) = pending.chars().next() {
+        return !invalid_characters.contains(first);
+    }
+    match later.next() {
+        Some(MatcherToken::Exact(next)) => match next.chars().next() {
+            Some(first) => !invalid_characters.contains(first),
+            None => false,
+        },
+        // Many captures also match when nothing is left.
+        None | Some(MatcherToken::End) => invalid_characters == INVALID_MANY_CAPTURE_CHARACTERS,
+        // A capture that ends the later matcher captures at least one valid character.
+        Some(MatcherToken::Capture(CaptureVariant::Named(_)))
+        | Some(MatcherToken::Capture(CaptureVariant::Unnamed))
+        | Some(MatcherToken::Capture(CaptureVariant::TypedNamed { .. }))
+        | Some(MatcherToken::Capture(CaptureVariant::TypedUnnamed { .. })) => {
+            matches!(later.next(), None | Some(MatcherToken::End))
+        }
+        Some(_) => false,
+    }
+}
+
+/// Determines if the capture stops at the first occurrence of the exact token following it.
+///
+/// Numbered captures stop after a fixed number of sections instead, and greedy captures stop at
+/// the last occurrence.
+fn stops_at_first_delimiter(capture: &CaptureVariant) -> bool {
+    matches!(
+        capture,
+        CaptureVariant::Named(_)
+            | CaptureVariant::Unnamed
+            | CaptureVariant::ManyNamed(_)
+            | CaptureVariant::ManyUnnamed
+    )
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+    use crate::{parse_str_and_optimize_tokens, FieldType};
+
+    fn check(earlier: &str, later: &str) -> bool {
+        let earlier = parse_str_and_optimize_tokens(earlier, FieldType::Unnamed).expect("parse");
+        let later = parse_str_and_optimize_tokens(later, FieldType::Unnamed).expect("parse");
+        shadows(&earlier, &later)
+    }
+
+    #[test]
+    fn many_capture_shadows_everything() {
+        assert!(check("{*:all}", "/about"));
+        assert!(check("/{*:all}", "/about/{id}"));
+        assert!(check("/{*}", "/"));
+    }
+
+    #[test]
+    fn greedy_many_capture_shadows_everything() {
+        assert!(check("{**:all}", "/about"));
+        assert!(check("/{**}", "/about/{id}"));
+    }
+
+    #[test]
+    fn identical_shadows() {
+        assert!(check("/about", "/about"));
+        assert!(check("/{id}/edit", "/{id}/edit"));
+    }
+
+    #[test]
+    fn prefix_shadows() {
+        assert!(check("/variant", "/variant/stuff"));
+        assert!(check("/Variant", "/variant"));
+    }
+
+    #[test]
+    fn anchored_prefix_does_not_shadow() {
+        assert!(!check("/variant!", "/variant/stuff"));
+        assert!(!check("/variant!", "/variant"));
+        assert!(check("/variant!", "/variant!"));
+    }
+
+    #[test]
+    fn capture_shadows_literal() {
+        assert!(check("/{id}", "/about"));
+        assert!(check("/{id}/edit", "/about/edit"));
+        assert!(check("/{id}/edit", "/{name}/edit/more"));
+    }
+
+    #[test]
+    fn capture_does_not_shadow_missing_section() {
+        assert!(!check("/{id}", "/"));
+        assert!(!check("/{id}/edit", "/about/view"));
+    }
+
+    #[test]
+    fn unrelated_routes_do_not_shadow() {
+        assert!(!check("/about", "/contact"));
+        assert!(!check("/about/more", "/about"));
+        assert!(!check("/{2:id}", "/a/b"));
+    }
+
+    #[test]
+    fn typed_capture_does_not_shadow_other_values() {
+        assert!(!check("/users/{id:u32}", "/users/new"));
+        assert!(!check("/users/{id:u32}/edit", "/users/{name}/edit"));
+        assert!(check("/users/{id}", "/users/{id:u32}"));
+        assert!(check("/users/{id}/edit", "/users/{:u32}/edit"));
+    }
+}
@@ -0,0 +1,1030 @@
+//! Matching of routes against optimized matcher tokens.
+//!
+//! Matching doesn't rely on the standard library, so tools that load already optimized matchers
+//! can match routes without it.
+
+#[cfg(feature = "std")]
+mod parts;
+mod util;
+
+#[cfg(feature = "std")]
+pub use parts::match_parts;
+pub use util::is_extension;
+
+#[cfg(feature = "std")]
+use crate::Captures;
+use crate::{CaptureKind, CaptureVariant, MatcherSettings, MatcherToken, QueryMode};
+use ::core::{iter::Peekable, slice::Iter};
+use alloc::{
+    borrow::Cow,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use log::trace;
+use nom::{
+    bytes::complete::{is_not, tag, take_till1},
+    error::ErrorKind,
+    sequence::terminated,
+    IResult,
+};
+use util::{consume_until, has_delimiter, next_delimiter, tag_possibly_case_sensitive};
+
+/// Captures that borrow both their keys from the tokens and their values from the route.
+///
+/// Matching only collects these, so attempts that fail don't allocate any strings.
+/// Only the values of repeated query parameters, which are joined together, are owned.
+pub type BorrowedCaptures<'a, 'b> = Vec<(&'b str, Cow<'a, str>)>;
+
+/// Allows abstracting over converting the captures of a successful match into a HashMap
+/// (Captures) or a Vec.
+trait CaptureCollection<'b> {
+    fn from_borrowed(captures: BorrowedCaptures<'_, 'b>) -> Self;
+}
+
+#[cfg(feature = "std")]
+impl<'b> CaptureCollection<'b> for Captures<'b> {
+    fn from_borrowed(captures: BorrowedCaptures<'_, 'b>) -> Self {
+        captures
+            .into_iter()
+            .map(|(key, value)| (key, value.to_string()))
+            .collect()
+    }
+}
+
+// TODO try to change the impl target to just Vec<String>
+impl<'b> CaptureCollection<'b> for Vec<(&'b str, String)> {
+    fn from_borrowed(captures: BorrowedCaptures<'_, 'b>) -> Self {
+        captures
+            .into_iter()
+            .map(|(key, value)| (key, value.to_string()))
+            .collect()
+    }
+}
+
+/// Matches a route, collecting the captures into a map.
+#[cfg(feature = "std")]
+#[allow(clippy::trivially_copy_pass_by_ref)]
+pub fn match_into_map<'a, 'b: 'a>(
+    tokens: &'b [MatcherToken],
+    settings: &'b MatcherSettings,
+) -> impl Fn(&'a str) -> IResult<&'a str, Captures<'b>> {
+    move |i: &str| matcher_impl(tokens, *settings, i)
+}
+
+/// Matches a route, collecting the captures into a vector.
+#[allow(clippy::trivially_copy_pass_by_ref)]
+pub fn match_into_vec<'a, 'b: 'a>(
+    tokens: &'b [MatcherToken],
+    settings: &'b MatcherSettings,
+) -> impl Fn(&'a str) -> IResult<&'a str, Vec<(&'b str, String)>> {
+    // TODO this return type is not ideal.
+    move |i: &str| matcher_impl(tokens, *settings, i)
+}
+
+fn matcher_impl<'a, 'b: 'a, CAP: CaptureCollection<'b>>(
+    tokens: &'b [MatcherToken],
+    settings: MatcherSettings,
+    i: &'a str,
+) -> IResult<&'a str, CAP> {
+    match settings.query_mode {
+        QueryMode::Structural => {
+            let (rest, captures) = match_borrowed(tokens, settings, i)?;
+            Ok((rest, CAP::from_borrowed(captures)))
+        }
+        QueryMode::Strict => {
+            let (rest, captures) = match_borrowed(tokens, settings, i)?;
+            if let Some(unknown_param) = find_unknown_query_param(tokens, settings, i) {
+                trace!("Unknown query parameter: {:?}", unknown_param);
+                return Err(nom::Err::Error((unknown_param, ErrorKind::Verify)));
+            }
+            Ok((rest, CAP::from_borrowed(captures)))
+        }
+        QueryMode::Lenient => {
+            let lenient = lenient_route(tokens, settings, i);
+            // The captures are copied out of the lenient route before it is dropped, but the
+            // errors and the remaining input have to refer to the original route.
+            let result = match_borrowed(tokens, settings, &lenient);
+            match result {
+                Ok((rest, captures)) => Ok((
+                    original_suffix(i, &lenient, rest.len()),
+                    CAP::from_borrowed(captures),
+                )),
+                Err(nom::Err::Error((rest, kind))) => Err(nom::Err::Error((
+                    original_suffix(i, &lenient, rest.len()),
+                    kind,
+                ))),
+                Err(nom::Err::Failure((rest, kind))) => Err(nom::Err::Failure((
+                    original_suffix(i, &lenient, rest.len()),
+                    kind,
+                ))),
+                Err(nom::Err::Incomplete(needed)) => Err(nom::Err::Incomplete(needed)),
+            }
+        }
+    }
+}
+
+/// Reassembles the literal text of the matcher.
+///
+/// The grammar doesn't allow the names of query parameters to be captured, so only the literals
+/// need to be inspected to find out which query parameters the matcher mentions.
+fn literal_template(tokens: &[MatcherToken]) -> String {
+    tokens
+        .iter()
+        .filter_map(|token| match token {
+            MatcherToken::Exact(literal) => Some(literal.as_str()),
+            MatcherToken::Capture(_) | MatcherToken::End => None,
+        })
+        .collect()
+}
+
+fn is_known_param(known_names: &[&str], param: &str, settings: MatcherSettings) -> bool {
+    let name = param_name(param);
+    known_names.iter().any(|known| {
+        if settings.case_insensitive {
+            known.eq_ignore_ascii_case(name)
+        } else {
+            *known == name
+        }
+    })
+}
+
+/// Finds the first query parameter in the route whose name isn't mentioned in the matcher.
+fn find_unknown_query_param<'a>(
+    tokens: &[MatcherToken],
+    settings: MatcherSettings,
+    route: &'a str,
+) -> Option<&'a str> {
+    let template = literal_template(tokens);
+    let known_names: Vec<&str> = query_params(&template).map(param_name).collect();
+    query_params(route).find(|param| !is_known_param(&known_names, param, settings))
+}
+
+/// Removes the query parameters and fragment that the matcher doesn't mention from the route.
+fn lenient_route<'a>(
+    tokens: &[MatcherToken],
+    settings: MatcherSettings,
+    route: &'a str,
+) -> Cow<'a, str> {
+    let template = literal_template(tokens);
+    let (_, template_query, template_fragment) = split_route(&template);
+    let (path, query, fragment) = split_route(route);
+
+    let mut lenient = path.to_string();
+    if !template_query.is_empty() && !query.is_empty() {
+        let known_names: Vec<&str> = query_params(&template).map(param_name).collect();
+        let known_params: Vec<&str> = query_params(route)
+            .filter(|param| is_known_param(&known_names, param, settings))
+            .collect();
+        lenient.push('?');
+        lenient.push_str(&known_params.join("&"));
+    }
+    if !template_fragment.is_empty() {
+        lenient.push_str(fragment);
+    }
+
+    if lenient == route {
+        Cow::Borrowed(route)
+    } else {
+        Cow::Owned(lenient)
+    }
+}
+
+/// Finds the part of the original route that corresponds to a suffix of the lenient route.
+///
+/// Only the query and fragment are changed by `lenient_route`, so suffixes that start in the
+/// path map to the same position in the original route.
+/// If all of the lenient route was consumed, the parts that were removed are considered consumed
+/// as well.
+fn original_suffix<'a>(original: &'a str, lenient: &str, suffix_len: usize) -> &'a str {
+    let position = lenient.len() - suffix_len;
+    let (path, _, _) = split_route(original);
+    if suffix_len == 0 {
+        &original[original.len()..]
+    } else if position <= path.len() {
+        &original[position..]
+    } else if original.ends_with(&lenient[position..]) {
+        &original[original.len() - suffix_len..]
+    } else {
+        &original[path.len()..]
+    }
+}
+
+/// Splits a route into its path, query, and fragment.
+///
+/// The query and fragment keep their leading `?` and `#`.
+fn split_route(route: &str) -> (&str, &str, &str) {
+    let (before_fragment, fragment) = route.split_at(route.find('#').unwrap_or(route.len()));
+    let (path, query) =
+        before_fragment.split_at(before_fragment.find('?').unwrap_or(before_fragment.len()));
+    (path, query, fragment)
+}
+
+/// Iterates over the `name=value` pairs in the query section of a route.
+fn query_params(route: &str) -> impl Iterator<Item = &str> {
+    let (_, query, _) = split_route(route);
+    query
+        .trim_start_matches('?')
+        .split('&')
+        .filter(|param| !param.is_empty())
+}
+
+fn param_name(param: &str) -> &str {
+    param.split('=').next().unwrap_or(param)
+}
+
+/// Matches a route, without copying the captures out of it.
+///
+/// Unlike `match_into_map` and `match_into_vec`, the match doesn't need to consume the whole
+/// route, regardless of whether the settings say it is `complete`.
+///
+/// The root path can be written as either "" or "/", in the matcher as well as in the route, so
+/// the matcher `""` matches "/", and the matcher `"/"` matches the empty route that the index of a
+/// nested switch receives.
+pub fn match_borrowed<'a, 'b: 'a>(
+    tokens: &'b [MatcherToken],
+    settings: MatcherSettings,
+    i: &'a str,
+) -> IResult<&'a str, BorrowedCaptures<'a, 'b>> {
+    let route_path = &i[..i.find(&['?', '#'][..]).unwrap_or(i.len())];
+    match tokens.first() {
+        Some(MatcherToken::End) if route_path == "/" => {
+            match_tokens(tokens, settings, &i[1..], false)
+        }
+        Some(MatcherToken::Exact(literal)) => {
+            let starts_with_root = |literal: &str| literal.starts_with(&['?', '#'][..]);
+            if route_path == "/" && starts_with_root(literal) {
+                match_tokens(tokens, settings, &i[1..], false)
+            } else if route_path.is_empty()
+                && literal.starts_with('/')
+                && (literal.len() == 1 || starts_with_root(&literal[1..]))
+            {
+                match_tokens(tokens, settings, i, true)
+            } else {
+                match_tokens(tokens, settings, i, false)
+            }
+        }
+        _ => match_tokens(tokens, settings, i, false),
+    }
+}
+
+/// Matches the tokens against the start of the input.
+///
+/// If `omit_root_slash` is set, the first token is a literal that starts with the `/` of the root
+/// path, which is matched without it.
+fn match_tokens<'a, 'b: 'a>(
+    tokens: &'b [MatcherToken],
+    settings: MatcherSettings,
+    mut i: &'a str,
+    mut omit_root_slash: bool,
+) -> IResult<&'a str, BorrowedCaptures<'a, 'b>> {
+    trace!("Attempting to match path: {:?} using: {:?}", i, tokens);
+
+    let mut iter = tokens.iter().peekable();
+
+    let mut captures: BorrowedCaptures<'a, 'b> = Vec::new();
+
+    while let Some(token) = iter.next() {
+        i = match token {
+            MatcherToken::Exact(literal) => {
+                let literal = if omit_root_slash {
+                    omit_root_slash = false;
+                    &literal[1..]
+                } else {
+                    literal.as_str()
+                };
+                trace!("Matching '{}' against literal: '{}'", i, literal);
+                tag_possibly_case_sensitive(literal, !settings.case_insensitive)(i)?.0
+            }
+            MatcherToken::Capture(capture) => match &capture {
+                CaptureVariant::Named(name) => capture_named(i, &mut iter, &name, &mut captures)?,
+                CaptureVariant::ManyNamed(name) => {
+                    capture_many_named(i, &mut iter, &name, &mut captures)?
+                }
+                CaptureVariant::NumberedNamed { sections, name } => capture_numbered_named(
+                    i,
+                    &mut iter,
+                    Some((name, &mut captures)),
+                    *sections,
+                    '/',
+                )?,
+                CaptureVariant::Unnamed => capture_named(i, &mut iter, "", &mut captures)?,
+                CaptureVariant::ManyUnnamed => capture_many_named(i, &mut iter, "", &mut captures)?,
+                CaptureVariant::GreedyManyNamed(name) => {
+                    capture_greedy_many_named(i, &mut iter, name, &mut captures)?
+                }
+                CaptureVariant::GreedyManyUnnamed => {
+                    capture_greedy_many_named(i, &mut iter, "", &mut captures)?
+                }
+                CaptureVariant::NumberedUnnamed { sections } => {
+                    capture_numbered_named(i, &mut iter, Some(("", &mut captures)), *sections, '/')?
+                }
+                CaptureVariant::SeparatedNamed {
+                    sections,
+                    separator,
+                    name,
+                } => capture_numbered_named(
+                    i,
+                    &mut iter,
+                    Some((name, &mut captures)),
+                    *sections,
+                    *separator,
+                )?,
+                CaptureVariant::SeparatedUnnamed {
+                    sections,
+                    separator,
+                } => capture_numbered_named(
+                    i,
+                    &mut iter,
+                    Some(("", &mut captures)),
+                    *sections,
+                    *separator,
+                )?,
+                CaptureVariant::TypedNamed { kind, name } => {
+                    capture_typed(i, &mut iter, name, *kind, &mut captures)?
+                }
+                CaptureVariant::TypedUnnamed { kind } => {
+                    capture_typed(i, &mut iter, "", *kind, &mut captures)?
+                }
+                CaptureVariant::RepeatedNamed { param, name } => {
+                    capture_repeated(i, param, name, settings, &mut captures)?
+                }
+                CaptureVariant::RepeatedUnnamed { param } => {
+                    capture_repeated(i, param, "", settings, &mut captures)?
+                }
+            },
+            MatcherToken::End => {
+                if !i.is_empty() {
+                    // this is approximately correct, but ultimately doesn't matter
+                    return Err(nom::Err::Failure((i, ErrorKind::Eof)));
+                } else {
+                    i
+                }
+            }
+        };
+    }
+    trace!("Route Matched");
+
+    Ok((i, captures))
+}
+
+fn capture_named<'a, 'b: 'a>(
+    i: &'a str,
+    iter: &mut Peekable<Iter<MatcherToken>>,
+    capture_key: &'b str,
+    matches: &mut BorrowedCaptures<'a, 'b>,
+) -> Result<&'a str, nom::Err<(&'a str, ErrorKind)>> {
+    log::trace!("Matching Named ({})", capture_key);
+    if has_delimiter(iter) {
+        let delimiter = next_delimiter(iter);
+        let (ii, captured) = consume_until(delimiter)(i)?;
+        matches.push((capture_key, captured.into()));
+        Ok(ii)
+    } else {
+        let (ii, captured) = valid_capture_characters(i)?;
+        matches.push((capture_key, captured.into()));
+        Ok(ii)
+    }
+}
+
+/// Captures the stem of a file name, up to the last occurrence of the extension that follows it
+/// within the segment, so `/files/{name:stem}.{ext}` captures "archive.tar" and "gz" from
+/// "/files/archive.tar.gz".
+fn capture_stem<'a, 'b: 'a>(
+    i: &'a str,
+    iter: &mut Peekable<Iter<MatcherToken>>,
+    capture_key: &'b str,
+    matches: &mut BorrowedCaptures<'a, 'b>,
+) -> Result<&'a str, nom::Err<(&'a str, ErrorKind)>> {
+    log::trace!("Matching Stem ({})", capture_key);
+    let delimiter = next_delimiter(iter);
+    let limit = i
+        .find(|c| INVALID_CAPTURE_CHARACTERS.contains(c))
+        .unwrap_or(i.len());
+    let end = (0..=limit)
+        .rev()
+        .filter(|index| i.is_char_boundary(*index))
+        .find(|index| delimiter(&i[*index..]).is_ok())
+        .ok_or(nom::Err::Error((i, ErrorKind::ManyTill)))?;
+    matches.push((capture_key, i[..end].into()));
+    Ok(&i[end..])
+}
+
+/// Captures a section like `capture_named`, but fails if the section isn't of the given kind, so
+/// that the route can be matched by another matcher instead.
+fn capture_typed<'a, 'b: 'a>(
+    i: &'a str,
+    iter: &mut Peekable<Iter<MatcherToken>>,
+    capture_key: &'b str,
+    kind: CaptureKind,
+    matches: &mut BorrowedCaptures<'a, 'b>,
+) -> Result<&'a str, nom::Err<(&'a str, ErrorKind)>> {
+    log::trace!("Matching Typed ({}: {})", capture_key, kind.name());
+    let ii = if kind == CaptureKind::Stem && has_delimiter(iter) {
+        capture_stem(i, iter, capture_key, matches)?
+    } else {
+        capture_named(i, iter, capture_key, matches)?
+    };
+    match matches.last() {
+        Some((_, captured)) if kind.accepts(captured) => Ok(ii),
+        _ => Err(nom::Err::Error((i, ErrorKind::Verify))),
+    }
+}
+
+fn capture_many_named<'a, 'b>(
+    i: &'a str,
+    iter: &mut Peekable<Iter<MatcherToken>>,
+    capture_key: &'b str,
+    matches: &mut BorrowedCaptures<'a, 'b>,
+) -> Result<&'a str, nom::Err<(&'a str, ErrorKind)>> {
+    log::trace!("Matching NumberedUnnamed ({})", capture_key);
+    if has_delimiter(iter) {
+        let delimiter = next_delimiter(iter);
+        let (ii, captured) = consume_until(delimiter)(i)?;
+        matches.push((capture_key, captured.into()));
+        Ok(ii)
+    } else if i.is_empty() {
+        matches.push((capture_key, i.into())); // TODO Is this a thing I want?
+        Ok(i) // Match even if nothing is left
+    } else {
+        let (ii, c) = valid_many_capture_characters(i)?;
+        matches.push((capture_key, c.into()));
+        Ok(ii)
+    }
+}
+
+/// Captures many sections, up to the last occurrence of the delimiter that follows the capture.
+///
+/// The capture can't extend past the path, so `/{**:path}/edit` captures "a/edit/b" from
+/// "/a/edit/b/edit?tab=1".
+fn capture_greedy_many_named<'a, 'b>(
+    i: &'a str,
+    iter: &mut Peekable<Iter<MatcherToken>>,
+    capture_key: &'b str,
+    matches: &mut BorrowedCaptures<'a, 'b>,
+) -> Result<&'a str, nom::Err<(&'a str, ErrorKind)>> {
+    log::trace!("Matching GreedyManyNamed ({})", capture_key);
+    if has_delimiter(iter) {
+        let delimiter = next_delimiter(iter);
+        let limit = i
+            .find(|c| INVALID_MANY_CAPTURE_CHARACTERS.contains(c))
+            .unwrap_or(i.len());
+        let end = (0..=limit)
+            .rev()
+            .filter(|index| i.is_char_boundary(*index))
+            .find(|index| delimiter(&i[*index..]).is_ok())
+            .ok_or(nom::Err::Error((i, ErrorKind::ManyTill)))?;
+        matches.push((capture_key, i[..end].into()));
+        Ok(&i[end..])
+    } else {
+        capture_many_named(i, iter, capture_key, matches)
+    }
+}
+
+fn capture_numbered_named<'a, 'b>(
+    mut i: &'a str,
+    iter: &mut Peekable<Iter<MatcherToken>>,
+    name_and_captures: Option<(&'b str, &mut BorrowedCaptures<'a, 'b>)>,
+    mut sections: usize,
+    separator: char,
+) -> Result<&'a str, nom::Err<(&'a str, ErrorKind)>> {
+    log::trace!(
+        "Matching NumberedNamed ({}, separated by {:?})",
+        sections,
+        separator
+    );
+    // The sections are adjacent, so everything that is consumed is captured.
+    let start = i;
+    let separator_tag = separator.to_string();
+    let separated_section = terminated(
+        valid_section_characters(separator),
+        tag(separator_tag.as_str()),
+    );
+
+    if has_delimiter(iter) {
+        while sections > 0 {
+            if sections > 1 {
+                let (ii, _) = separated_section(i)?;
+                i = ii;
+            } else {
+                let delimiter = next_delimiter(iter);
+                let (ii, section) = consume_until(delimiter)(i)?;
+                // A section can't run into the next one, or into the next path segment.
+                if separator != '/' && section.contains(&[separator, '/'][..]) {
+                    return Err(nom::Err::Error((i, ErrorKind::Verify)));
+                }
+                i = ii;
+            }
+            sections -= 1;
+        }
+    } else {
+        while sections > 0 {
+            if sections > 1 {
+                let (ii, _) = separated_section(i)?;
+                i = ii;
+            } else {
+                // Don't consume the next character on the last section
+                let (ii, _) = valid_section_characters(separator)(i)?;
+                i = ii;
+            }
+            sections -= 1;
+        }
+    }
+
+    if let Some((name, captures)) = name_and_captures {
+        captures.push((name, start[..start.len() - i.len()].into()));
+    }
+    Ok(i)
+}
+
+/// Captures the value of a query parameter, along with the values of the occurrences of the same
+/// parameter that directly follow it.
+///
+/// The values are captured joined by `&`, so `?tag={*:tags}` captures "a&b" from "?tag=a&tag=b".
+fn capture_repeated<'a, 'b>(
+    i: &'a str,
+    param: &str,
+    capture_key: &'b str,
+    settings: MatcherSettings,
+    matches: &mut BorrowedCaptures<'a, 'b>,
+) -> Result<&'a str, nom::Err<(&'a str, ErrorKind)>> {
+    log::trace!("Matching Repeated ({}: {})", capture_key, param);
+    let (mut i, first) = valid_capture_characters(i)?;
+    let mut captured = Cow::Borrowed(first);
+
+    let next_param = format!("&{}=", param);
+    let after_next_param = |i: &'a str| {
+        let prefix = i.get(..next_param.len())?;
+        let is_next_param = if settings.case_insensitive {
+            prefix.eq_ignore_ascii_case(&next_param)
+        } else {
+            prefix == next_param
+        };
+        if is_next_param {
+            Some(&i[next_param.len()..])
+        } else {
+            None
+        }
+    };
+    while let Some((ii, value)) =
+        after_next_param(i).and_then(|rest| valid_capture_characters(rest).ok())
+    {
+        let captured = captured.to_mut();
+        captured.push('&');
+        captured.push_str(value);
+        i = ii;
+    }
+
+    matches.push((capture_key, captured));
+    Ok(i)
+}
+
+/// Characters that don't interfere with parsing logic for capturing characters
+const INVALID_CAPTURE_CHARACTERS: &str = " */#&?{}=";
+
+fn valid_capture_characters(i: &str) -> IResult<&str, &str> {
+    is_not(INVALID_CAPTURE_CHARACTERS)(i)
+}
+
+/// Characters that can be captured in a section that ends at the separator.
+fn valid_section_characters<'a>(separator: char) -> impl Fn(&'a str) -> IResult<&'a str, &'a str> {
+    const INVALID_CHARACTERS: &str = " */#&?{}=";
+    move |i: &str| take_till1(|c| INVALID_CHARACTERS.contains(c) || c == separator)(i)
+}
+
+const INVALID_MANY_CAPTURE_CHARACTERS: &str = " #&?=";
+
+fn valid_many_capture_characters(i: &str) -> IResult<&str, &str> {
+    is_not(INVALID_MANY_CAPTURE_CHARACTERS)(i)
+}
+
+#[cfg(all(test, feature = "std"))]
+mod integration_test {
+    use super::*;
+
+    use crate::FieldType;
+    //    use nom::combinator::all_consuming;
+
+    #[test]
+    fn match_query_after_path() {
+        let x = crate::parse_str_and_optimize_tokens("/a/path?lorem=ipsum", FieldType::Unnamed)
+            .expect("Should parse");
+        matcher_impl::<Captures>(&x, MatcherSettings::default(), "/a/path?lorem=ipsum")
+            .expect("should match");
+    }
+
+    #[test]
+    fn match_query_after_path_trailing_slash() {
+        let x = crate::parse_str_and_optimize_tokens("/a/path/?lorem=ipsum", FieldType::Unnamed)
+            .expect("Should parse");
+        matcher_impl::<Captures>(&x, MatcherSettings::default(), "/a/path/?lorem=ipsum")
+            .expect("should match");
+    }
+
+    #[test]
+    fn match_query() {
+        let x = crate::parse_str_and_optimize_tokens("?lorem=ipsum", FieldType::Unnamed)
+            .expect("Should parse");
+        matcher_impl::<Captures>(&x, MatcherSettings::default(), "?lorem=ipsum")
+            .expect("should match");
+    }
+
+    #[test]
+    fn named_capture_query() {
+        let x = crate::parse_str_and_optimize_tokens("?lorem={ipsum}", FieldType::Unnamed)
+            .expect("Should parse");
+        let (_, matches) = matcher_impl::<Captures>(&x, MatcherSettings::default(), "?lorem=ipsum")
+            .expect("should match");
+        assert_eq!(matches["ipsum"], "ipsum".to_string())
+    }
+
+    #[test]
+    fn match_n_paths_3() {
+        let x = crate::parse_str_and_optimize_tokens("/{*:cap}/thing", FieldType::Unnamed)
+            .expect("Should parse");
+        let matches: Captures =
+            matcher_impl(&x, MatcherSettings::default(), "/anything/other/thing")
+                .expect("should match")
+                .1;
+        assert_eq!(matches["cap"], "anything/other".to_string())
+    }
+
+    #[test]
+    fn match_n_paths_4() {
+        let x = crate::parse_str_and_optimize_tokens("/{*:cap}/thing", FieldType::Unnamed)
+            .expect("Should parse");
+        let matches: Captures =
+            matcher_impl(&x, MatcherSettings::default(), "/anything/thing/thing")
+                .expect("should match")
+                .1;
+        assert_eq!(matches["cap"], "anything".to_string())
+    }
+
+    #[test]
+    fn match_path_5() {
+        let x = crate::parse_str_and_optimize_tokens("/{cap}/thing", FieldType::Unnamed)
+            .expect("Should parse");
+        let matches: Captures =
+            matcher_impl(&x, MatcherSettings::default(), "/anything/thing/thing")
+                .expect("should match")
+                .1;
+        assert_eq!(matches["cap"], "anything".to_string())
+    }
+
+    #[test]
+    fn match_fragment() {
+        let x = crate::parse_str_and_optimize_tokens("#test", FieldType::Unnamed)
+            .expect("Should parse");
+        matcher_impl::<Captures>(&x, MatcherSettings::default(), "#test").expect("should match");
+    }
+
+    #[test]
+    fn match_fragment_after_path() {
+        let x = crate::parse_str_and_optimize_tokens("/a/path/#test", FieldType::Unnamed)
+            .expect("Should parse");
+        matcher_impl::<Captures>(&x, MatcherSettings::default(), "/a/path/#test")
+            .expect("should match");
+    }
+
+    #[test]
+    fn match_fragment_after_path_no_slash() {
+        let x = crate::parse_str_and_optimize_tokens("/a/path#test", FieldType::Unnamed)
+            .expect("Should parse");
+        matcher_impl::<Captures>(&x, MatcherSettings::default(), "/a/path#test")
+            .expect("should match");
+    }
+
+    #[test]
+    fn match_fragment_after_query() {
+        let x =
+            crate::parse_str_and_optimize_tokens("/a/path?query=thing#test", FieldType::Unnamed)
+                .expect("Should parse");
+        matcher_impl::<Captures>(&x, MatcherSettings::default(), "/a/path?query=thing#test")
+            .expect("should match");
+    }
+
+    #[test]
+    fn match_fragment_after_query_capture() {
+        let x = crate::parse_str_and_optimize_tokens(
+            "/a/path?query={capture}#test",
+            FieldType::Unnamed,
+        )
+        .expect("Should parse");
+        matcher_impl::<Captures>(&x, MatcherSettings::default(), "/a/path?query=thing#test")
+            .expect("should match");
+    }
+
+    #[test]
+    fn capture_as_only_token() {
+        let x = crate::parse_str_and_optimize_tokens("{any}", FieldType::Unnamed)
+            .expect("Should parse");
+        matcher_impl::<Captures>(&x, MatcherSettings::default(), "literally_anything")
+            .expect("should match");
+    }
+
+    #[test]
+    fn case_insensitive() {
+        let x = crate::parse_str_and_optimize_tokens("/hello", FieldType::Unnamed)
+            .expect("Should parse");
+        let settings = MatcherSettings {
+            case_insensitive: true,
+            ..Default::default()
+        };
+        matcher_impl::<Captures>(&x, settings, "/HeLLo").expect("should match");
+    }
+
+    #[test]
+    fn multibyte_literals_and_captures() {
+        let x =
+            crate::parse_str_and_optimize_tokens("/kategorie/bücher/{titel}", FieldType::Unnamed)
+                .expect("Should parse");
+        let (_, captures) =
+            matcher_impl::<Captures>(&x, MatcherSettings::default(), "/kategorie/bücher/straße")
+                .expect("should match");
+        assert_eq!(captures["titel"], "straße");
+        matcher_impl::<Captures>(&x, MatcherSettings::default(), "/kategorie/buecher/a")
+            .expect_err("should not match");
+
+        let settings = MatcherSettings {
+            case_insensitive: true,
+            ..Default::default()
+        };
+        matcher_impl::<Captures>(&x, settings, "/Kategorie/BÜCHER/a").expect("should match");
+    }
+
+    #[test]
+    fn strict_query_rejects_unknown_params() {
+        let x = crate::parse_str_and_optimize_tokens("/list?page={page}", FieldType::Unnamed)
+            .expect("Should parse");
+        let settings = MatcherSettings {
+            complete: false,
+            query_mode: QueryMode::Strict,
+            ..Default::default()
+        };
+        matcher_impl::<Captures>(&x, settings, "/list?page=2").expect("should match");
+        let (rest, _) = match_borrowed(&x, settings, "/list?page=2&pgae=3")
+            .expect("should match without checking the query");
+        assert_eq!(rest, "&pgae=3");
+        let err = matcher_impl::<Captures>(&x, settings, "/list?page=2&pgae=3#frag")
+            .expect_err("should not match");
+        assert_eq!(err, nom::Err::Error(("pgae=3", ErrorKind::Verify)));
+    }
+
+    #[test]
+    fn strict_query_without_query_in_matcher() {
+        let x = crate::parse_str_and_optimize_tokens("/list", FieldType::Unnamed)
+            .expect("Should parse");
+        let settings = MatcherSettings {
+            complete: false,
+            query_mode: QueryMode::Strict,
+            ..Default::default()
+        };
+        matcher_impl::<Captures>(&x, settings, "/list#a?b").expect("should match");
+        matcher_impl::<Captures>(&x, settings, "/list?").expect("should match");
+        matcher_impl::<Captures>(&x, settings, "/list?page=2").expect_err("should not match");
+    }
+
+    #[test]
+    fn lenient_query_ignores_unmentioned_sections() {
+        let x = crate::parse_str_and_optimize_tokens("/list!", FieldType::Unnamed)
+            .expect("Should parse");
+        let settings = MatcherSettings {
+            query_mode: QueryMode::Lenient,
+            ..Default::default()
+        };
+        let (rest, _) =
+            matcher_impl::<Captures>(&x, settings, "/list?page=2#top").expect("should match");
+        assert_eq!(rest, "");
+        let err =
+            matcher_impl::<Captures>(&x, settings, "/lists?page=2").expect_err("should not match");
+        assert_eq!(err, nom::Err::Failure(("s?page=2", ErrorKind::Eof)));
+    }
+
+    #[test]
+    fn lenient_query_ignores_extra_params() {
+        let x = crate::parse_str_and_optimize_tokens("/list?page={page}!", FieldType::Unnamed)
+            .expect("Should parse");
+        let settings = MatcherSettings {
+            query_mode: QueryMode::Lenient,
+            ..Default::default()
+        };
+        let (_, matches) = matcher_impl::<Captures>(&x, settings, "/list?sort=asc&page=2#top")
+            .expect("should match");
+        assert_eq!(matches["page"], "2".to_string());
+        matcher_impl::<Captures>(&x, settings, "/list?sort=asc").expect_err("should not match");
+    }
+
+    #[test]
+    fn end_token() {
+        let x = crate::parse_str_and_optimize_tokens("/lorem!", FieldType::Unnamed)
+            .expect("Should parse");
+
+        matcher_impl::<Captures>(&x, Default::default(), "/lorem/ipsum")
+            .expect_err("should not match");
+    }
+
+    #[test]
+    fn root_path_is_empty_or_slash() {
+        let matches = |matcher: &str, route: &str| {
+            let x = crate::parse_str_and_optimize_tokens(matcher, FieldType::Unnamed)
+                .expect("Should parse");
+            match matcher_impl::<Captures>(&x, Default::default(), route) {
+                Ok((rest, _)) => rest.is_empty(),
+                Err(_) => false,
+            }
+        };
+        for matcher in &["", "!", "/", "/!"] {
+            assert!(
+                matches(matcher, ""),
+                "{} should match the empty route",
+                matcher
+            );
+            assert!(matches(matcher, "/"), "{} should match /", matcher);
+            assert!(!matches(matcher, "//"), "{} should not match //", matcher);
+            assert!(
+                !matches(matcher, "/lorem"),
+                "{} should not match /lorem",
+                matcher
+            );
+        }
+        assert!(matches("?tab={tab}", "/?tab=posts"));
+        assert!(matches("/?tab={tab}", "?tab=posts"));
+        assert!(matches("#{section}", "/#top"));
+        assert!(!matches("/lorem", "lorem"));
+    }
+
+    #[test]
+    fn capture_before_end_token() {
+        let x = crate::parse_str_and_optimize_tokens("/lorem/{cap}!", FieldType::Unnamed)
+            .expect("Should parse");
+        let (_, matches) =
+            matcher_impl::<Captures>(&x, Default::default(), "/lorem/ipsum").expect("should match");
+        assert_eq!(matches["cap"], "ipsum".to_string());
+        matcher_impl::<Captures>(&x, Default::default(), "/lorem/ipsum/dolor")
+            .expect_err("should not match");
+    }
+
+    #[test]
+    fn many_capture_before_end_token() {
+        let x = crate::parse_str_and_optimize_tokens("/lorem/{*:cap}!", FieldType::Unnamed)
+            .expect("Should parse");
+        let (_, matches) = matcher_impl::<Captures>(&x, Default::default(), "/lorem/ipsum/dolor")
+            .expect("should match");
+        assert_eq!(matches["cap"], "ipsum/dolor".to_string());
+    }
+
+    #[test]
+    fn separated_captures() {
+        let x = crate::parse_str_and_optimize_tokens(
+            "/posts/{3:date;sep=-}-{slug}",
+            FieldType::Unnamed,
+        )
+        .expect("Should parse");
+        let (_, matches) =
+            matcher_impl::<Captures>(&x, Default::default(), "/posts/2020-05-01-my-title")
+                .expect("should match");
+        assert_eq!(matches["date"], "2020-05-01".to_string());
+        assert_eq!(matches["slug"], "my-title".to_string());
+        matcher_impl::<Captures>(&x, Default::default(), "/posts/2020-05")
+            .expect_err("should not match");
+    }
+
+    #[test]
+    fn separated_capture_stops_at_separator() {
+        let x = crate::parse_str_and_optimize_tokens("/files/{name;sep=.}", FieldType::Unnamed)
+            .expect("Should parse");
+        let (rest, matches) = matcher_impl::<Captures>(&x, Default::default(), "/files/report.pdf")
+            .expect("should match");
+        assert_eq!(matches["name"], "report".to_string());
+        assert_eq!(rest, ".pdf");
+    }
+
+    #[test]
+    fn file_extension() {
+        let x = crate::parse_str_and_optimize_tokens("/files/{name:stem}.{ext}", FieldType::Named)
+            .expect("Should parse");
+        let (rest, matches) =
+            matcher_impl::<Captures>(&x, Default::default(), "/files/archive.tar.gz?v=1.2")
+                .expect("should match");
+        assert_eq!(matches["name"], "archive.tar".to_string());
+        assert_eq!(matches["ext"], "gz".to_string());
+        assert_eq!(rest, "?v=1.2");
+        matcher_impl::<Captures>(&x, Default::default(), "/files/archive")
+            .expect_err("should not match");
+
+        let x =
+            crate::parse_str_and_optimize_tokens("/files/{name:stem}.pdf/raw", FieldType::Named)
+                .expect("Should parse");
+        let (_, matches) = matcher_impl::<Captures>(&x, Default::default(), "/files/v1.2.pdf/raw")
+            .expect("should match");
+        assert_eq!(matches["name"], "v1.2".to_string());
+    }
+
+    #[test]
+    fn dot_between_captures_within_segment() {
+        let x = crate::parse_str_and_optimize_tokens("{sub}.{domain}.com/", FieldType::Named)
+            .expect("Should parse");
+        let (_, matches) =
+            matcher_impl::<Captures>(&x, Default::default(), "a.b.com/").expect("should match");
+        assert_eq!(matches["sub"], "a".to_string());
+        assert_eq!(matches["domain"], "b".to_string());
+    }
+
+    #[test]
+    fn capture_without_stem_kind_stops_at_first_dot() {
+        let x = crate::parse_str_and_optimize_tokens("/v/{major}.{minor}", FieldType::Named)
+            .expect("Should parse");
+        let (_, matches) =
+            matcher_impl::<Captures>(&x, Default::default(), "/v/1.2.3").expect("should match");
+        assert_eq!(matches["major"], "1".to_string());
+        assert_eq!(matches["minor"], "2.3".to_string());
+    }
+
+    #[test]
+    fn many_capture_stops_at_first_delimiter() {
+        let x = crate::parse_str_and_optimize_tokens("/{*:path}/edit", FieldType::Unnamed)
+            .expect("Should parse");
+        let (rest, matches) =
+            matcher_impl::<Captures>(&x, Default::default(), "/a/b/edit").expect("should match");
+        assert_eq!(matches["path"], "a/b".to_string());
+        assert_eq!(rest, "");
+        let (rest, matches) = matcher_impl::<Captures>(&x, Default::default(), "/a/edit/b/edit")
+            .expect("should match");
+        assert_eq!(matches["path"], "a".to_string());
+        assert_eq!(rest, "/b/edit");
+    }
+
+    #[test]
+    fn greedy_many_capture_stops_at_last_delimiter() {
+        let x = crate::parse_str_and_optimize_tokens("/{**:path}/edit", FieldType::Unnamed)
+            .expect("Should parse");
+        let (rest, matches) =
+            matcher_impl::<Captures>(&x, Default::default(), "/a/edit/b/edit?tab=1")
+                .expect("should match");
+        assert_eq!(matches["path"], "a/edit/b".to_string());
+        assert_eq!(rest, "?tab=1");
+        matcher_impl::<Captures>(&x, Default::default(), "/a/b?next=/edit")
+            .expect_err("should not match");
+    }
+
+    #[test]
+    fn matrix_params() {
+        let x = crate::parse_str_and_optimize_tokens("/users;role={role}/list", FieldType::Named)
+            .expect("Should parse");
+        let (_, matches) =
+            matcher_impl::<Captures>(&x, Default::default(), "/users;role=admin/list")
+                .expect("should match");
+        assert_eq!(matches["role"], "admin".to_string());
+        matcher_impl::<Captures>(&x, Default::default(), "/users;role=admin;team=a/list")
+            .expect_err("should not match");
+        matcher_impl::<Captures>(&x, Default::default(), "/users/list")
+            .expect_err("should not match");
+    }
+
+    #[test]
+    fn partly_captured_query_value() {
+        let x = crate::parse_str_and_optimize_tokens(
+            "/issues?filter=status:{status}&range={from}..{to}",
+            FieldType::Named,
+        )
+        .expect("Should parse");
+        let (_, matches) = matcher_impl::<Captures>(
+            &x,
+            Default::default(),
+            "/issues?filter=status:open&range=1..20",
+        )
+        .expect("should match");
+        assert_eq!(matches["status"], "open".to_string());
+        assert_eq!(matches["from"], "1".to_string());
+        assert_eq!(matches["to"], "20".to_string());
+        matcher_impl::<Captures>(
+            &x,
+            Default::default(),
+            "/issues?filter=label:bug&range=1..20",
+        )
+        .expect_err("should not match");
+    }
+
+    #[test]
+    fn repeated_query_params() {
+        let x = crate::parse_str_and_optimize_tokens(
+            "/posts?tag={*:tags}&page={page}",
+            FieldType::Named,
+        )
+        .expect("Should parse");
+        let (_, matches) =
+            matcher_impl::<Captures>(&x, Default::default(), "/posts?tag=a&tag=b&page=2")
+                .expect("should match");
+        assert_eq!(matches["tags"], "a&b".to_string());
+        assert_eq!(matches["page"], "2".to_string());
+        let (_, matches) = matcher_impl::<Captures>(&x, Default::default(), "/posts?tag=a&page=2")
+            .expect("should match");
+        assert_eq!(matches["tags"], "a".to_string());
+        // Values that don't directly follow each other aren't captured.
+        let (rest, matches) =
+            matcher_impl::<Captures>(&x, Default::default(), "/posts?tag=a&page=2&tag=b")
+                .expect("should match");
+        assert_eq!(matches["tags"], "a".to_string());
+        assert_eq!(rest, "&tag=b");
+    }
+}
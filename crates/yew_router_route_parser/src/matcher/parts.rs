@@ -0,0 +1,371 @@
+//! Matching of routes that have already been split into their path, query, and fragment.
+use super::{match_borrowed, match_tokens, BorrowedCaptures};
+use crate::{CaptureVariant, Captures, MatcherSettings, MatcherToken, QueryMode};
+use alloc::borrow::Cow;
+
+/// A query parameter of a matcher.
+struct Param {
+    name: String,
+    /// The tokens that match the value, which don't include the `=`.
+    value: Vec<MatcherToken>,
+}
+
+/// The tokens of a matcher, split into the parts of a route that they match.
+#[derive(Default)]
+struct SplitTokens {
+    path: Vec<MatcherToken>,
+    query: Vec<Param>,
+    fragment: Option<Vec<MatcherToken>>,
+    anchored: bool,
+}
+
+impl SplitTokens {
+    /// Splits the tokens at the `?` and `#` that start the query and fragment, and at the `&`s
+    /// that separate query parameters.
+    ///
+    /// Query parameters are collected with everything up to their `=` as their name, as the names
+    /// of query parameters can't be captured.
+    fn new(tokens: &[MatcherToken]) -> Self {
+        let mut split = SplitTokens::default();
+        // The literal text of a query parameter, up to the `=`.
+        let mut pending_name: Option<String> = None;
+        for token in tokens {
+            match token {
+                MatcherToken::Exact(literal) => {
+                    let mut literal = literal.as_str();
+                    while !literal.is_empty() {
+                        let boundaries: &[char] = if split.fragment.is_some() {
+                            &[]
+                        } else if pending_name.is_some() {
+                            &['=', '&', '#']
+                        } else if !split.query.is_empty() {
+                            &['&', '#']
+                        } else {
+                            &['?', '#']
+                        };
+                        let end = literal.find(boundaries).unwrap_or(literal.len());
+                        let (text, rest) = literal.split_at(end);
+                        if let Some(name) = pending_name.as_mut() {
+                            name.push_str(text)
+                        } else if !text.is_empty() {
+                            split.current().push(MatcherToken::Exact(text.to_string()));
+                        }
+                        let mut rest = rest.chars();
+                        match rest.next() {
+                            Some('=') => {
+                                let name = pending_name.take().unwrap_or_default();
+                                split.query.push(Param {
+                                    name,
+                                    value: Vec::new(),
+                                })
+                            }
+                            Some('?') | Some('&') => pending_name = Some(String::new()),
+                            Some('#') => {
+                                pending_name = None;
+                                split.fragment = Some(Vec::new())
+                            }
+                            _ => {}
+                        }
+                        literal = rest.as_str();
+                    }
+                }
+                MatcherToken::Capture(_) | MatcherToken::End => {
+                    if let MatcherToken::End = token {
+                        split.anchored = true;
+                    }
+                    split.current().push(token.clone())
+                }
+            }
+        }
+        split
+    }
+
+    /// Gets the tokens that the next token belongs to.
+    fn current(&mut self) -> &mut Vec<MatcherToken> {
+        if let Some(fragment) = self.fragment.as_mut() {
+            fragment
+        } else if let Some(param) = self.query.last_mut() {
+            &mut param.value
+        } else {
+            &mut self.path
+        }
+    }
+}
+
+/// Matches a route that has already been split into its path, query parameters, and fragment.
+///
+/// Query parameters are compared by name and value, so their values can contain characters like
+/// `&` and `=` without having to be escaped.
+/// A value that is matched by a lone capture is captured in its entirety.
+/// A lone repeated capture also captures the parameters with the same name that follow it, joined
+/// by `&`, with the `&`s within the values escaped as `%26`.
+///
+/// # Example
+/// ```
+/// use yew_router_route_parser::{
+///     matcher::match_parts, parse_str_and_optimize_tokens, FieldType, MatcherSettings,
+/// };
+///
+/// let tokens = parse_str_and_optimize_tokens("/users/{id}?tab={tab}", FieldType::Named).unwrap();
+/// let query = [("tab", "posts & replies")];
+/// let captures = match_parts(&tokens, MatcherSettings::default(), "/users/42", &query, None)
+///     .expect("should match");
+/// assert_eq!(captures["id"], "42".to_string());
+/// assert_eq!(captures["tab"], "posts & replies".to_string());
+/// ```
+pub fn match_parts<'a, 'b>(
+    tokens: &'b [MatcherToken],
+    settings: MatcherSettings,
+    path: &'a str,
+    query: &[(&'a str, &'a str)],
+    fragment: Option<&'a str>,
+) -> Option<Captures<'b>> {
+    let split = SplitTokens::new(tokens);
+    let must_consume = settings.complete || split.anchored;
+    let names_match = |name: &str, expected: &str| {
+        if settings.case_insensitive {
+            name.eq_ignore_ascii_case(expected)
+        } else {
+            name == expected
+        }
+    };
+    let is_known = |name: &str| {
+        split
+            .query
+            .iter()
+            .any(|param| names_match(name, &param.name))
+    };
+
+    let (rest, mut captures) = match_borrowed(&split.path, settings, path).ok()?;
+    if !rest.is_empty() && (must_consume || !split.query.is_empty() || split.fragment.is_some()) {
+        return None;
+    }
+
+    if settings.query_mode == QueryMode::Strict && query.iter().any(|(name, _)| !is_known(name)) {
+        return None;
+    }
+    let pairs: Vec<&(&str, &str)> = if settings.query_mode == QueryMode::Lenient {
+        query.iter().filter(|(name, _)| is_known(name)).collect()
+    } else {
+        query.iter().collect()
+    };
+    let mut pairs = pairs.into_iter().peekable();
+    for param in &split.query {
+        let (name, value) = pairs.next()?;
+        if !names_match(name, &param.name) {
+            return None;
+        }
+        match repeated_capture_key(&param.value) {
+            Some(key) => {
+                // The values are joined by `&`, so the `&`s within them are escaped.
+                let mut values = vec![value.replace('&', "%26")];
+                while let Some((next_name, next_value)) = pairs.peek() {
+                    if !names_match(next_name, name) {
+                        break;
+                    }
+                    values.push(next_value.replace('&', "%26"));
+                    pairs.next();
+                }
+                captures.push((key, Cow::Owned(values.join("&"))));
+            }
+            None => captures.extend(match_value(&param.value, settings, value)?),
+        }
+    }
+    let query_consumed = pairs.next().is_none();
+    if !query_consumed && (must_consume || split.fragment.is_some()) {
+        return None;
+    }
+
+    match (&split.fragment, fragment) {
+        (Some(fragment_tokens), Some(fragment)) => {
+            let (rest, fragment_captures) =
+                match_tokens(fragment_tokens, settings, fragment, false).ok()?;
+            if !rest.is_empty() && must_consume {
+                return None;
+            }
+            captures.extend(fragment_captures);
+        }
+        (Some(_), None) => return None,
+        (None, Some(_)) => {
+            if must_consume && settings.query_mode != QueryMode::Lenient {
+                return None;
+            }
+        }
+        (None, None) => {}
+    }
+
+    // The keys borrow from the split tokens, so they are replaced with the keys of the matcher.
+    Some(
+        captures
+            .into_iter()
+            .map(|(key, value)| (original_key(tokens, key), value.to_string()))
+            .collect(),
+    )
+}
+
+/// Matches the whole value of a query parameter.
+fn match_value<'a>(
+    tokens: &'a [MatcherToken],
+    settings: MatcherSettings,
+    value: &'a str,
+) -> Option<BorrowedCaptures<'a, 'a>> {
+    let tokens_without_end = match tokens.split_last() {
+        Some((MatcherToken::End, tokens)) => tokens,
+        _ => tokens,
+    };
+    match tokens_without_end {
+        [MatcherToken::Capture(capture)] => {
+            if capture.kind().filter(|kind| !kind.accepts(value)).is_some() {
+                return None;
+            }
+            Some(vec![(capture_key(capture), value.into())])
+        }
+        _ => match match_tokens(tokens, settings, value, false) {
+            Ok(("", captures)) => Some(captures),
+            _ => None,
+        },
+    }
+}
+
+/// Gets the key that a capture is stored under.
+fn capture_key(capture: &CaptureVariant) -> &str {
+    match capture {
+        CaptureVariant::Named(name)
+        | CaptureVariant::ManyNamed(name)
+        | CaptureVariant::GreedyManyNamed(name)
+        | CaptureVariant::NumberedNamed { name, .. }
+        | CaptureVariant::SeparatedNamed { name, .. }
+        | CaptureVariant::TypedNamed { name, .. }
+        | CaptureVariant::RepeatedNamed { name, .. } => name,
+        CaptureVariant::Unnamed
+        | CaptureVariant::ManyUnnamed
+        | CaptureVariant::GreedyManyUnnamed
+        | CaptureVariant::NumberedUnnamed { .. }
+        | CaptureVariant::SeparatedUnnamed { .. }
+        | CaptureVariant::TypedUnnamed { .. }
+        | CaptureVariant::RepeatedUnnamed { .. } => "",
+    }
+}
+
+/// Gets the key of the capture if the value of a query parameter is matched by a lone repeated
+/// capture, which also captures the values of the occurrences of the parameter that follow it.
+fn repeated_capture_key(tokens: &[MatcherToken]) -> Option<&str> {
+    let tokens_without_end = match tokens.split_last() {
+        Some((MatcherToken::End, tokens)) => tokens,
+        _ => tokens,
+    };
+    match tokens_without_end {
+        [MatcherToken::Capture(capture @ CaptureVariant::RepeatedNamed { .. })]
+        | [MatcherToken::Capture(capture @ CaptureVariant::RepeatedUnnamed { .. })] => {
+            Some(capture_key(capture))
+        }
+        _ => None,
+    }
+}
+
+/// Finds the key in the tokens that is equal to the given key.
+fn original_key<'b>(tokens: &'b [MatcherToken], key: &str) -> &'b str {
+    tokens
+        .iter()
+        .filter_map(|token| match token {
+            MatcherToken::Capture(capture) => Some(capture_key(capture)),
+            _ => None,
+        })
+        .find(|original| *original == key)
+        .unwrap_or("")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{parse_str_and_optimize_tokens, FieldType};
+
+    fn settings() -> MatcherSettings {
+        MatcherSettings::default()
+    }
+
+    fn tokens(matcher: &str) -> Vec<MatcherToken> {
+        parse_str_and_optimize_tokens(matcher, FieldType::Unnamed).expect("should parse")
+    }
+
+    #[test]
+    fn path_query_and_fragment() {
+        let matcher = tokens("/users/{id}?tab={tab}#{section}");
+        let captures = match_parts(
+            &matcher,
+            settings(),
+            "/users/42",
+            &[("tab", "posts")],
+            Some("top"),
+        )
+        .expect("should match");
+        assert_eq!(captures["id"], "42".to_string());
+        assert_eq!(captures["tab"], "posts".to_string());
+        assert_eq!(captures["section"], "top".to_string());
+    }
+
+    #[test]
+    fn values_are_not_escaped() {
+        let matcher = tokens("/search?q={q}&page=2");
+        let captures = match_parts(
+            &matcher,
+            settings(),
+            "/search",
+            &[("q", "salt & pepper=good"), ("page", "2")],
+            None,
+        )
+        .expect("should match");
+        assert_eq!(captures["q"], "salt & pepper=good".to_string());
+        assert!(match_parts(
+            &matcher,
+            settings(),
+            "/search",
+            &[("q", "salt"), ("page", "3")],
+            None
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn anchored_matchers_consume_everything() {
+        let matcher = tokens("/users!");
+        let prefix = MatcherSettings {
+            complete: false,
+            ..settings()
+        };
+        assert!(match_parts(&matcher, prefix, "/users", &[], None).is_some());
+        assert!(match_parts(&matcher, prefix, "/users/42", &[], None).is_none());
+        assert!(match_parts(&matcher, prefix, "/users", &[("a", "b")], None).is_none());
+        let root = tokens("");
+        assert!(match_parts(&root, prefix, "/", &[], None).is_some());
+    }
+
+    #[test]
+    fn extra_parts_need_lenient_mode() {
+        let matcher = tokens("/search?q={q}");
+        let query = [("sort", "new"), ("q", "salt"), ("page", "2")];
+        assert!(match_parts(&matcher, settings(), "/search", &query, None).is_none());
+        let lenient = MatcherSettings {
+            query_mode: QueryMode::Lenient,
+            ..settings()
+        };
+        let captures =
+            match_parts(&matcher, lenient, "/search", &query, Some("top")).expect("should match");
+        assert_eq!(captures["q"], "salt".to_string());
+    }
+
+    #[test]
+    fn repeated_params_are_joined() {
+        let matcher = tokens("/posts?tag={*:tags}&page={page}");
+        let captures = match_parts(
+            &matcher,
+            settings(),
+            "/posts",
+            &[("tag", "rust"), ("tag", "salt & pepper"), ("page", "2")],
+            None,
+        )
+        .expect("should match");
+        assert_eq!(captures["tags"], "rust&salt %26 pepper".to_string());
+        assert_eq!(captures["page"], "2".to_string());
+    }
+}
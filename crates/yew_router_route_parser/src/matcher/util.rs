@@ -1,14 +1,13 @@
+use crate::{CaptureKind, MatcherToken};
+use ::core::{iter::Peekable, slice::Iter};
+use alloc::{string::String, vec::Vec};
 use nom::{
     bytes::complete::{tag, tag_no_case},
-    character::complete::anychar,
-    combinator::{cond, map, peek, rest},
+    combinator::{cond, map, rest},
     error::{ErrorKind, ParseError},
-    multi::many_till,
     sequence::pair,
     IResult,
 };
-use std::{iter::Peekable, rc::Rc, slice::Iter};
-use yew_router_route_parser::MatcherToken;
 
 /// Allows a configurable tag that can optionally be case insensitive.
 pub fn tag_possibly_case_sensitive<'a, 'b: 'a>(
@@ -38,42 +37,76 @@ pub fn alternative(alternatives: Vec<String>) -> impl Fn(&str) -> IResult<&str,
 }
 
 /// Consumes the input until the provided parser succeeds.
-/// The consumed input is returned in the form of an allocated string.
+/// The consumed input is returned as a slice of the input.
 /// # Note
 /// `stop_parser` only peeks its input.
-pub fn consume_until<'a, F, E>(stop_parser: F) -> impl Fn(&'a str) -> IResult<&'a str, String, E>
+pub fn consume_until<'a, F, E>(stop_parser: F) -> impl Fn(&'a str) -> IResult<&'a str, &'a str, E>
 where
     E: ParseError<&'a str>,
     F: Fn(&'a str) -> IResult<&'a str, &'a str, E>,
 {
-    // In order for the returned fn to be Fn instead of FnOnce, wrap the inner fn in an RC.
-    let f = Rc::new(many_till(
-        anychar,
-        peek(stop_parser), // once this succeeds, stop folding.
-    ));
-    move |i: &str| {
-        let (i, (first, _stop)): (&str, (Vec<char>, &str)) = (f)(i)?;
-        let ret = first.into_iter().collect();
-        Ok((i, ret))
+    move |i: &'a str| {
+        for index in (0..=i.len()).filter(|index| i.is_char_boundary(*index)) {
+            let remaining = &i[index..];
+            match stop_parser(remaining) {
+                Ok(_) => return Ok((remaining, &i[..index])),
+                Err(nom::Err::Error(_)) => {} // Keep consuming.
+                Err(e) => return Err(e),
+            }
+        }
+        let end = &i[i.len()..];
+        Err(nom::Err::Error(E::append(
+            end,
+            ErrorKind::ManyTill,
+            E::from_error_kind(end, ErrorKind::Eof),
+        )))
+    }
+}
+
+/// Determines if the next token can be used to find where a capture ends.
+///
+/// The End token terminates the token stream, so a capture followed by it is treated the same as a
+/// capture at the very end of the tokens.
+pub fn has_delimiter(iter: &mut Peekable<Iter<MatcherToken>>) -> bool {
+    match iter.peek() {
+        Some(MatcherToken::End) | None => false,
+        Some(_) => true,
     }
 }
 
+/// Characters that end a path segment, or a query parameter's value.
+const SEGMENT_TERMINATORS: &str = "/?#&=";
+
+/// Determines if the capture at the index is the extension of a file name, like `{ext}` in
+/// `/files/{name:stem}.{ext}`.
+///
+/// A `.` in the value of an extension would end the stem there instead when the route is matched.
+pub fn is_extension(tokens: &[MatcherToken], index: usize) -> bool {
+    index >= 2
+        && match (&tokens[index - 2], &tokens[index - 1]) {
+            (MatcherToken::Capture(stem), MatcherToken::Exact(literal)) => {
+                stem.kind() == Some(CaptureKind::Stem)
+                    && !literal.contains(|c| SEGMENT_TERMINATORS.contains(c))
+            }
+            _ => false,
+        }
+}
+
 /// Produces a parser combinator that searches for the next possible set of strings of
 /// characters used to terminate a forward search.
 ///
 /// # Panics
 /// This function assumes that the next item after a Capture must be an Exact.
 /// If this is violated, this function will panic.
-pub fn next_delimiter<'a>(
-    iter: &mut Peekable<Iter<MatcherToken>>,
-) -> impl Fn(&'a str) -> IResult<&'a str, &'a str> {
-    let t: MatcherToken = iter
+pub fn next_delimiter<'a, 'b>(
+    iter: &mut Peekable<Iter<'b, MatcherToken>>,
+) -> impl Fn(&'a str) -> IResult<&'a str, &'a str> + 'b {
+    let t: &'b MatcherToken = iter
         .peek()
         .copied()
-        .cloned()
         .expect("There must be at least one token to peak in next_delimiter");
 
-    move |i: &'a str| match &t {
+    move |i: &'a str| match t {
         MatcherToken::Exact(sequence) => tag(sequence.as_str())(i),
         MatcherToken::End => rest(i),
         MatcherToken::Capture(_) => {
@@ -85,12 +118,13 @@ pub fn next_delimiter<'a>(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloc::vec;
 
     #[test]
     fn consume_until_simple() {
         let parser = consume_until::<_, ()>(tag("z"));
         let parsed = parser("abcz").expect("Should parse");
-        assert_eq!(parsed, ("z", "abc".to_string()))
+        assert_eq!(parsed, ("z", "abc"))
     }
 
     #[test]
@@ -121,7 +155,7 @@ mod tests {
                 .collect(),
         ));
         let parsed = parser("first_stuff_abc").expect("should parse");
-        assert_eq!(parsed, ("abc", "first_stuff_".to_string()))
+        assert_eq!(parsed, ("abc", "first_stuff_"))
     }
 
     #[test]
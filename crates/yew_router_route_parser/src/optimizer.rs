@@ -1,24 +1,64 @@
+#[cfg(feature = "std")]
 use crate::{
-    error::PrettyParseError,
-    parser::{parse, CaptureOrExact, RefCaptureVariant, RouteParserToken},
+    error::PrettyParseError, parser::parse_with_version, CaptureKind, FieldType, GrammarVersion,
+};
+use crate::{
+    parser::{CaptureOrExact, RefCaptureVariant, RouteParserToken},
+    settings::RESERVED_CHARS,
+    CaptureVariant, MatcherToken,
+};
+use ::core::mem;
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
 };
-
-use crate::{core::FieldType, CaptureVariant, MatcherToken};
 
 impl<'a> From<RefCaptureVariant<'a>> for CaptureVariant {
     fn from(v: RefCaptureVariant<'a>) -> Self {
         match v {
             RefCaptureVariant::Named(s) => CaptureVariant::Named(s.to_string()),
             RefCaptureVariant::ManyNamed(s) => CaptureVariant::ManyNamed(s.to_string()),
+            RefCaptureVariant::GreedyManyNamed(s) => CaptureVariant::GreedyManyNamed(s.to_string()),
             RefCaptureVariant::NumberedNamed { sections, name } => CaptureVariant::NumberedNamed {
                 sections,
                 name: name.to_string(),
             },
             RefCaptureVariant::Unnamed => CaptureVariant::Unnamed,
             RefCaptureVariant::ManyUnnamed => CaptureVariant::ManyUnnamed,
+            RefCaptureVariant::GreedyManyUnnamed => CaptureVariant::GreedyManyUnnamed,
             RefCaptureVariant::NumberedUnnamed { sections } => {
                 CaptureVariant::NumberedUnnamed { sections }
             }
+            RefCaptureVariant::SeparatedUnnamed {
+                sections,
+                separator,
+            } => CaptureVariant::SeparatedUnnamed {
+                sections,
+                separator,
+            },
+            RefCaptureVariant::SeparatedNamed {
+                sections,
+                separator,
+                name,
+            } => CaptureVariant::SeparatedNamed {
+                sections,
+                separator,
+                name: name.to_string(),
+            },
+            RefCaptureVariant::TypedUnnamed { kind } => CaptureVariant::TypedUnnamed { kind },
+            RefCaptureVariant::TypedNamed { kind, name } => CaptureVariant::TypedNamed {
+                kind,
+                name: name.to_string(),
+            },
+            RefCaptureVariant::RepeatedUnnamed { param } => CaptureVariant::RepeatedUnnamed {
+                param: param.to_string(),
+            },
+            RefCaptureVariant::RepeatedNamed { param, name } => CaptureVariant::RepeatedNamed {
+                param: param.to_string(),
+                name: name.to_string(),
+            },
         }
     }
 }
@@ -32,88 +72,414 @@ impl<'a> From<CaptureOrExact<'a>> for MatcherToken {
     }
 }
 
-impl<'a> RouteParserToken<'a> {
-    fn as_str(&self) -> &str {
-        match self {
-            RouteParserToken::Separator => "/",
-            RouteParserToken::Exact(literal) => &literal,
-            RouteParserToken::QueryBegin => "?",
-            RouteParserToken::QuerySeparator => "&",
-            RouteParserToken::FragmentBegin => "#",
-            RouteParserToken::Capture { .. }
-            | RouteParserToken::Query { .. }
-            | RouteParserToken::End => unreachable!(),
-        }
-    }
-}
-
 /// Parse the provided "matcher string" and then optimize the tokens.
+#[cfg(feature = "std")]
 pub fn parse_str_and_optimize_tokens(
     i: &str,
     field_type: FieldType,
 ) -> Result<Vec<MatcherToken>, PrettyParseError> {
-    let tokens = parse(i, field_type)?;
+    parse_str_and_optimize_tokens_with_version(i, field_type, GrammarVersion::V1)
+}
+
+/// Parse the provided "matcher string" using a specific version of the grammar and then optimize
+/// the tokens.
+#[cfg(feature = "std")]
+pub fn parse_str_and_optimize_tokens_with_version(
+    i: &str,
+    field_type: FieldType,
+    version: GrammarVersion,
+) -> Result<Vec<MatcherToken>, PrettyParseError<'_>> {
+    let tokens = parse_with_version(i, field_type, version)?;
     Ok(convert_tokens(&tokens))
 }
 
+/// Gets the names of the named captures of a matcher string, in the order they appear, along with
+/// the kind of value each is restricted to, or `None` if it isn't typed.
+///
+/// This lets tools, like ones that generate code for routes, inspect matcher strings without
+/// walking their tokens.
+///
+/// # Example
+/// ```
+/// use yew_router_route_parser::{capture_names, CaptureKind};
+///
+/// let names = capture_names("/users/{id:u32}/{*:rest}").unwrap();
+/// assert_eq!(
+///     names,
+///     vec![
+///         ("id".to_string(), Some(CaptureKind::U32)),
+///         ("rest".to_string(), None)
+///     ]
+/// );
+/// ```
+#[cfg(feature = "std")]
+pub fn capture_names(
+    matcher: &str,
+) -> Result<Vec<(String, Option<CaptureKind>)>, PrettyParseError<'_>> {
+    capture_names_with_version(matcher, GrammarVersion::V1)
+}
+
+/// Gets the names of the named captures of a matcher string written in a specific version of the
+/// grammar, along with the kinds of value they are restricted to.
+#[cfg(feature = "std")]
+pub fn capture_names_with_version(
+    matcher: &str,
+    version: GrammarVersion,
+) -> Result<Vec<(String, Option<CaptureKind>)>, PrettyParseError<'_>> {
+    // Unnamed field types accept every capture, named or not.
+    let tokens = parse_str_and_optimize_tokens_with_version(matcher, FieldType::Unnamed, version)?;
+    let names = tokens
+        .iter()
+        .filter_map(|token| match token {
+            MatcherToken::Capture(capture) => capture
+                .name()
+                .map(|name| (name.to_string(), capture.kind())),
+            _ => None,
+        })
+        .collect();
+    Ok(names)
+}
+
 /// Converts a slice of `RouteParserToken` into a Vec of MatcherTokens.
 ///
 /// In the process of converting the tokens, this function will condense multiple RouteParserTokens
 /// that represent literals into one Exact variant if multiple reducible tokens happen to occur in a row.
+///
+/// If an `End` token is present, it is guaranteed to be the last token produced.
+/// Any tokens after it are discarded.
+///
+/// Every sequence of tokens can be converted, including ones that the parser wouldn't produce.
 pub fn convert_tokens(tokens: &[RouteParserToken]) -> Vec<MatcherToken> {
     let mut new_tokens = vec![];
-    let mut run: Vec<RouteParserToken> = vec![];
+    // Literal text that hasn't been pushed as an Exact token yet.
+    let mut run = String::new();
 
     for token in tokens.iter() {
         match token {
-            RouteParserToken::QueryBegin
-            | RouteParserToken::FragmentBegin
-            | RouteParserToken::Separator
-            | RouteParserToken::QuerySeparator
-            | RouteParserToken::Exact(_) => run.push(*token),
+            RouteParserToken::Separator => run.push('/'),
+            RouteParserToken::QueryBegin => run.push('?'),
+            RouteParserToken::QuerySeparator => run.push('&'),
+            RouteParserToken::FragmentBegin => run.push('#'),
+            RouteParserToken::Exact(literal) => run.push_str(literal),
             RouteParserToken::Capture(cap) => {
-                new_tokens.push(MatcherToken::Exact(
-                    run.iter().map(RouteParserToken::as_str).collect(),
-                ));
-                run = vec![];
+                new_tokens.push(MatcherToken::Exact(mem::take(&mut run)));
                 new_tokens.push(MatcherToken::Capture(CaptureVariant::from(*cap)))
             }
             RouteParserToken::Query {
                 ident,
                 capture_or_exact,
-            } => match capture_or_exact {
-                CaptureOrExact::Exact(s) => {
-                    run.push(RouteParserToken::Exact(ident));
-                    run.push(RouteParserToken::Exact("="));
-                    run.push(RouteParserToken::Exact(s));
-                }
-                CaptureOrExact::Capture(cap) => {
-                    let sequence = run
-                        .iter()
-                        .map(RouteParserToken::as_str)
-                        .chain(Some(*ident))
-                        .chain(Some("="))
-                        .collect();
-                    new_tokens.push(MatcherToken::Exact(sequence));
-                    run = vec![];
-                    new_tokens.push(MatcherToken::Capture(CaptureVariant::from(*cap)))
-                }
-            },
+            } => {
+                run.push_str(ident);
+                run.push('=');
+                push_capture_or_exact(&mut new_tokens, &mut run, capture_or_exact);
+            }
+            RouteParserToken::QueryValue(capture_or_exact) => {
+                push_capture_or_exact(&mut new_tokens, &mut run, capture_or_exact)
+            }
+            RouteParserToken::MatrixParam {
+                ident,
+                capture_or_exact,
+            } => {
+                // Unlike queries, matrix parameters aren't preceded by a token of their own.
+                run.push(';');
+                run.push_str(ident);
+                run.push('=');
+                push_capture_or_exact(&mut new_tokens, &mut run, capture_or_exact);
+            }
             RouteParserToken::End => {
-                let sequence = run.iter().map(RouteParserToken::as_str).collect();
-                new_tokens.push(MatcherToken::Exact(sequence));
-                run = vec![];
+                if !run.is_empty() {
+                    new_tokens.push(MatcherToken::Exact(run));
+                }
                 new_tokens.push(MatcherToken::End);
+                return new_tokens;
             }
         }
     }
 
     // Empty the run at the end.
     if !run.is_empty() {
-        new_tokens.push(MatcherToken::Exact(
-            run.iter().map(RouteParserToken::as_str).collect(),
-        ));
+        new_tokens.push(MatcherToken::Exact(run));
     }
 
     new_tokens
 }
+
+/// Adds a literal to the run, or a capture after the literal text that precedes it.
+fn push_capture_or_exact(
+    new_tokens: &mut Vec<MatcherToken>,
+    run: &mut String,
+    capture_or_exact: &CaptureOrExact,
+) {
+    match capture_or_exact {
+        CaptureOrExact::Exact(s) => run.push_str(s),
+        CaptureOrExact::Capture(cap) => {
+            new_tokens.push(MatcherToken::Exact(mem::take(run)));
+            new_tokens.push(MatcherToken::Capture(CaptureVariant::from(*cap)))
+        }
+    }
+}
+
+/// Reconstructs a matcher string from optimized tokens.
+///
+/// The string is canonical, so matcher strings that only differ in how they are written, like the
+/// empty matcher and `!`, are reconstructed the same way.
+/// Characters in path and fragment literals that would otherwise begin a capture or end the
+/// matcher, `{`, `}`, `!`, and `\`, as well as the characters that are reserved for future syntax,
+/// are escaped with a `\`, which only `GrammarVersion::V2` understands.
+/// Literals in query values and matrix parameter values are written as they are.
+///
+/// The string parses back into the same tokens, unless they were parsed from a matcher string
+/// that escapes a character which is otherwise part of the structure of a route, like `\=` or
+/// `\?`, as it is written without the escape.
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "std")]
+/// # {
+/// use yew_router_route_parser::{parse_str_and_optimize_tokens, to_matcher_string, FieldType};
+///
+/// let tokens = parse_str_and_optimize_tokens("/users/{id}/{*:rest}?tab={tab}!", FieldType::Named)
+///     .unwrap();
+/// assert_eq!(to_matcher_string(&tokens), "/users/{id}/{*:rest}?tab={tab}!");
+/// # }
+/// ```
+pub fn to_matcher_string(tokens: &[MatcherToken]) -> String {
+    let mut matcher = String::new();
+    // Escapes aren't understood in the query or in the values of matrix parameters, where they
+    // are part of the literal.
+    let mut in_query = false;
+    let mut in_matrix_value = false;
+    let mut in_fragment = false;
+    for token in tokens {
+        match token {
+            MatcherToken::Exact(literal) => {
+                for c in literal.chars() {
+                    if !in_query
+                        && !in_matrix_value
+                        && ("{}!\\".contains(c) || RESERVED_CHARS.contains(c))
+                    {
+                        matcher.push('\\');
+                    }
+                    matcher.push(c);
+                    match c {
+                        '=' if !in_query && !in_fragment => in_matrix_value = true,
+                        '/' | ';' => in_matrix_value = false,
+                        '?' if !in_fragment => {
+                            in_query = true;
+                            in_matrix_value = false;
+                        }
+                        '#' => {
+                            in_query = false;
+                            in_matrix_value = false;
+                            in_fragment = true;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            MatcherToken::Capture(capture) => {
+                matcher.push('{');
+                matcher.push_str(&capture_contents(capture));
+                matcher.push('}');
+            }
+            MatcherToken::End => matcher.push('!'),
+        }
+    }
+    matcher
+}
+
+/// Writes what is between the brackets of a capture.
+fn capture_contents(capture: &CaptureVariant) -> String {
+    match capture {
+        CaptureVariant::Unnamed => String::new(),
+        CaptureVariant::ManyUnnamed | CaptureVariant::RepeatedUnnamed { .. } => "*".to_string(),
+        CaptureVariant::GreedyManyUnnamed => "**".to_string(),
+        CaptureVariant::NumberedUnnamed { sections } => sections.to_string(),
+        CaptureVariant::Named(name) => name.clone(),
+        CaptureVariant::ManyNamed(name) | CaptureVariant::RepeatedNamed { name, .. } => {
+            format!("*:{}", name)
+        }
+        CaptureVariant::GreedyManyNamed(name) => format!("**:{}", name),
+        CaptureVariant::NumberedNamed { sections, name } => format!("{}:{}", sections, name),
+        // Matrix parameters are captured up to the next `;`, without declaring it.
+        CaptureVariant::SeparatedUnnamed {
+            sections: 1,
+            separator: ';',
+        } => String::new(),
+        CaptureVariant::SeparatedNamed {
+            sections: 1,
+            separator: ';',
+            name,
+        } => name.clone(),
+        CaptureVariant::SeparatedUnnamed {
+            sections: 1,
+            separator,
+        } => format!(";sep={}", separator),
+        CaptureVariant::SeparatedUnnamed {
+            sections,
+            separator,
+        } => format!("{};sep={}", sections, separator),
+        CaptureVariant::SeparatedNamed {
+            sections: 1,
+            separator,
+            name,
+        } => format!("{};sep={}", name, separator),
+        CaptureVariant::SeparatedNamed {
+            sections,
+            separator,
+            name,
+        } => format!("{}:{};sep={}", sections, name, separator),
+        CaptureVariant::TypedUnnamed { kind } => format!(":{}", kind.name()),
+        CaptureVariant::TypedNamed { kind, name } => format!("{}:{}", name, kind.name()),
+    }
+}
+
+/// Determines if the tokens will only match if the whole route is consumed.
+///
+/// This is the case when the tokens are terminated by an `End` token, which is written as `!` in a
+/// matcher string.
+pub fn is_anchored(tokens: &[MatcherToken]) -> bool {
+    tokens.last() == Some(&MatcherToken::End)
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn matcher_string_round_trip() {
+        let matchers = [
+            "/users/{id:u32}/{*:rest}",
+            "/posts/{3:date;sep=-}-{slug}?tag={*:tags}&q={q}#{frag}!",
+            "/users;role={role}/list",
+            "/archive/{2:date}/{**:path}/edit",
+            "{tenant}.example.com/{:uuid}",
+            r"/\{literal\}/\!",
+            r"/v\(1\|2\)/\[draft\]?q=(a|b)",
+        ];
+        for matcher in matchers.iter() {
+            let tokens = parse_str_and_optimize_tokens_with_version(
+                matcher,
+                FieldType::Unnamed,
+                GrammarVersion::V2,
+            )
+            .expect("should parse");
+            assert_eq!(&to_matcher_string(&tokens), matcher);
+        }
+        assert_eq!(to_matcher_string(&[MatcherToken::End]), "!");
+    }
+
+    #[test]
+    fn end_terminates_tokens() {
+        let tokens = convert_tokens(&[
+            RouteParserToken::Separator,
+            RouteParserToken::End,
+            RouteParserToken::Exact("lorem"),
+        ]);
+        assert_eq!(
+            tokens,
+            vec![MatcherToken::Exact("/".to_string()), MatcherToken::End]
+        );
+    }
+
+    #[test]
+    fn converts_tokens_the_parser_would_not_produce() {
+        let tokens = convert_tokens(&[
+            RouteParserToken::QuerySeparator,
+            RouteParserToken::Capture(RefCaptureVariant::Unnamed),
+            RouteParserToken::Capture(RefCaptureVariant::Unnamed),
+            RouteParserToken::FragmentBegin,
+            RouteParserToken::QueryBegin,
+        ]);
+        assert_eq!(
+            tokens,
+            vec![
+                MatcherToken::Exact("&".to_string()),
+                MatcherToken::Capture(CaptureVariant::Unnamed),
+                MatcherToken::Exact("".to_string()),
+                MatcherToken::Capture(CaptureVariant::Unnamed),
+                MatcherToken::Exact("#?".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn no_empty_exact_before_end() {
+        let tokens = parse_str_and_optimize_tokens("{cap}!", FieldType::Unnamed).unwrap();
+        assert_eq!(tokens.last(), Some(&MatcherToken::End));
+        assert_ne!(
+            tokens[tokens.len() - 2],
+            MatcherToken::Exact("".to_string())
+        );
+    }
+
+    #[test]
+    fn capture_names_skip_unnamed_captures() {
+        let names = capture_names("/{}/{id:u32}/{:uuid}/{2:pair}?tag={*:tags}&q={}#{}").unwrap();
+        assert_eq!(
+            names,
+            vec![
+                ("id".to_string(), Some(CaptureKind::U32)),
+                ("pair".to_string(), None),
+                ("tags".to_string(), None),
+            ]
+        );
+        assert!(capture_names("/{lorem").is_err());
+        let names = capture_names_with_version(r"/\{{lorem}", GrammarVersion::V2).unwrap();
+        assert_eq!(names, vec![("lorem".to_string(), None)]);
+    }
+
+    #[test]
+    fn matrix_params() {
+        let tokens =
+            parse_str_and_optimize_tokens("/users;role={role};all=yes/list", FieldType::Named)
+                .unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                MatcherToken::Exact("/users;role=".to_string()),
+                MatcherToken::Capture(CaptureVariant::SeparatedNamed {
+                    sections: 1,
+                    separator: ';',
+                    name: "role".to_string()
+                }),
+                MatcherToken::Exact(";all=yes/list".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn repeated_query_params() {
+        let tokens =
+            parse_str_and_optimize_tokens("/posts?tag={*:tags}&page={page}", FieldType::Named)
+                .unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                MatcherToken::Exact("/posts?tag=".to_string()),
+                MatcherToken::Capture(CaptureVariant::RepeatedNamed {
+                    param: "tag".to_string(),
+                    name: "tags".to_string()
+                }),
+                MatcherToken::Exact("&page=".to_string()),
+                MatcherToken::Capture(CaptureVariant::Named("page".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn anchored() {
+        let tokens = parse_str_and_optimize_tokens("/lorem!", FieldType::Unnamed).unwrap();
+        assert!(is_anchored(&tokens));
+        let tokens = parse_str_and_optimize_tokens("/lorem", FieldType::Unnamed).unwrap();
+        assert!(!is_anchored(&tokens));
+    }
+
+    #[test]
+    fn tokens_are_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<MatcherToken>();
+        assert_send_sync::<CaptureVariant>();
+        assert_send_sync::<PrettyParseError>();
+    }
+}
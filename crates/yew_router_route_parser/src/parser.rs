@@ -1,11 +1,17 @@
 //! Parser that consumes a string and produces the first representation of the matcher.
+//!
+//! The tokens are available without the "std" feature, but parsing them requires it.
+use crate::CaptureKind;
+#[cfg(feature = "std")]
 use crate::{
     core::{
-        capture, capture_single, exact, get_and, get_end, get_hash, get_question, get_slash, query,
+        capture, capture_single, continue_exact, exact_for, get_and, get_end, get_hash,
+        get_question, get_slash, matrix_param, query, query_value,
     },
     error::{get_reason, ParseError, ParserErrorReason, PrettyParseError},
-    FieldType,
+    FieldType, GrammarVersion,
 };
+#[cfg(feature = "std")]
 use nom::{branch::alt, IResult};
 
 /// Tokens generated from parsing a route matcher string.
@@ -27,6 +33,21 @@ pub enum RouteParserToken<'a> {
         /// Identifier
         ident: &'a str,
         /// Capture or match
+        ///
+        /// This is the first part of the value, which `QueryValue` tokens may continue.
+        capture_or_exact: CaptureOrExact<'a>,
+    },
+    /// Continues the value of the preceding `Query`, like `{status}` in `filter=status:{status}`.
+    ///
+    /// Literals and captures alternate, and nothing continues a repeated capture, like `{*:tags}`.
+    QueryValue(CaptureOrExact<'a>),
+    /// Match ;x=y within a path segment
+    MatrixParam {
+        /// Identifier
+        ident: &'a str,
+        /// Capture or match
+        ///
+        /// Captures are separated by `;`, so they stop at the next matrix parameter.
         capture_or_exact: CaptureOrExact<'a>,
     },
     /// Match \#
@@ -48,6 +69,8 @@ pub enum RefCaptureVariant<'a> {
     Unnamed,
     /// {*}
     ManyUnnamed,
+    /// {**} - like {*}, but stops at the last occurrence of what follows it instead of the first.
+    GreedyManyUnnamed,
     /// {5}
     NumberedUnnamed {
         /// Number of sections to match.
@@ -57,6 +80,9 @@ pub enum RefCaptureVariant<'a> {
     Named(&'a str),
     /// {*:name} - captures over many sections and adds it to the map with a given name.
     ManyNamed(&'a str),
+    /// {**:name} - like {*:name}, but stops at the last occurrence of what follows it instead of
+    /// the first.
+    GreedyManyNamed(&'a str),
     /// {2:name} - captures a fixed number of sections with a given name.
     NumberedNamed {
         /// Number of sections to match.
@@ -64,6 +90,51 @@ pub enum RefCaptureVariant<'a> {
         /// The key to be entered in the `Matches` map.
         name: &'a str,
     },
+    /// {;sep=-} or {3;sep=-} - captures a fixed number of sections that are separated by a
+    /// character other than `/`.
+    SeparatedUnnamed {
+        /// Number of sections to match.
+        sections: usize,
+        /// The character that separates the sections.
+        separator: char,
+    },
+    /// {name;sep=-} or {3:name;sep=-} - captures a fixed number of sections that are separated by
+    /// a character other than `/`, with a given name.
+    SeparatedNamed {
+        /// Number of sections to match.
+        sections: usize,
+        /// The character that separates the sections.
+        separator: char,
+        /// The key to be entered in the `Matches` map.
+        name: &'a str,
+    },
+    /// {:u32} - captures a section, which only matches if it is of the given kind.
+    TypedUnnamed {
+        /// The kind of value that the section must contain.
+        kind: CaptureKind,
+    },
+    /// {name:u32} - captures a section with a given name, which only matches if it is of the
+    /// given kind.
+    TypedNamed {
+        /// The kind of value that the section must contain.
+        kind: CaptureKind,
+        /// The key to be entered in the `Matches` map.
+        name: &'a str,
+    },
+    /// ?param={*} - captures every value of a query parameter that is repeated, like
+    /// `?tag=a&tag=b`.
+    RepeatedUnnamed {
+        /// The name of the query parameter.
+        param: &'a str,
+    },
+    /// ?param={*:name} - captures every value of a query parameter that is repeated, like
+    /// `?tag=a&tag=b`, with a given name.
+    RepeatedNamed {
+        /// The name of the query parameter.
+        param: &'a str,
+        /// The key to be entered in the `Matches` map.
+        name: &'a str,
+    },
 }
 
 /// Either a Capture, or an Exact match
@@ -75,8 +146,9 @@ pub enum CaptureOrExact<'a> {
     Capture(RefCaptureVariant<'a>),
 }
 
+#[cfg(feature = "std")]
 /// Represents the states the parser can be in.
-#[derive(Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 enum ParserState<'a> {
     None,
     Path { prev_token: RouteParserToken<'a> },
@@ -85,6 +157,7 @@ enum ParserState<'a> {
     Fragment { prev_token: RouteParserToken<'a> },
     End,
 }
+#[cfg(feature = "std")]
 impl<'a> ParserState<'a> {
     /// Given a new route parser token, transition to a new state.
     ///
@@ -100,7 +173,9 @@ impl<'a> ParserState<'a> {
                 | RouteParserToken::Capture(_) => Ok(ParserState::Path { prev_token: token }),
                 RouteParserToken::QueryBegin => Ok(ParserState::FirstQuery { prev_token: token }),
                 RouteParserToken::QuerySeparator // TODO this may be possible in the future.
-                | RouteParserToken::Query { .. } => Err(ParserErrorReason::NotAllowedStateTransition),
+                | RouteParserToken::Query { .. }
+                | RouteParserToken::QueryValue(_)
+                | RouteParserToken::MatrixParam { .. } => Err(ParserErrorReason::NotAllowedStateTransition),
                 RouteParserToken::FragmentBegin => Ok(ParserState::Fragment { prev_token: token }),
                 RouteParserToken::End => Ok(ParserState::End)
             },
@@ -120,7 +195,11 @@ impl<'a> ParserState<'a> {
                         _ => Err(ParserErrorReason::NotAllowedStateTransition),
                     },
                     RouteParserToken::Exact(_) => match token {
-                        RouteParserToken::Separator | RouteParserToken::Capture(_) => {
+                        // Adjacent literals only occur when a literal contains escapes.
+                        RouteParserToken::Separator
+                        | RouteParserToken::Exact(_)
+                        | RouteParserToken::Capture(_)
+                        | RouteParserToken::MatrixParam { .. } => {
                             Ok(ParserState::Path { prev_token: token })
                         }
                         RouteParserToken::QueryBegin => {
@@ -133,7 +212,22 @@ impl<'a> ParserState<'a> {
                         _ => Err(ParserErrorReason::NotAllowedStateTransition),
                     },
                     RouteParserToken::Capture(_) => match token {
-                        RouteParserToken::Separator | RouteParserToken::Exact(_) => {
+                        RouteParserToken::Separator
+                        | RouteParserToken::Exact(_)
+                        | RouteParserToken::MatrixParam { .. } => {
+                            Ok(ParserState::Path { prev_token: token })
+                        }
+                        RouteParserToken::QueryBegin => {
+                            Ok(ParserState::FirstQuery { prev_token: token })
+                        }
+                        RouteParserToken::FragmentBegin => {
+                            Ok(ParserState::Fragment { prev_token: token })
+                        }
+                        RouteParserToken::End => Ok(ParserState::End),
+                        _ => Err(ParserErrorReason::NotAllowedStateTransition),
+                    },
+                    RouteParserToken::MatrixParam { .. } => match token {
+                        RouteParserToken::Separator | RouteParserToken::MatrixParam { .. } => {
                             Ok(ParserState::Path { prev_token: token })
                         }
                         RouteParserToken::QueryBegin => {
@@ -156,7 +250,10 @@ impl<'a> ParserState<'a> {
                     }
                     _ => Err(ParserErrorReason::NotAllowedStateTransition),
                 },
-                RouteParserToken::Query { .. } => match token {
+                RouteParserToken::Query { .. } | RouteParserToken::QueryValue(_) => match token {
+                    RouteParserToken::QueryValue(_) => {
+                        Ok(ParserState::FirstQuery { prev_token: token })
+                    }
                     RouteParserToken::QuerySeparator => {
                         Ok(ParserState::NthQuery { prev_token: token })
                     }
@@ -175,7 +272,10 @@ impl<'a> ParserState<'a> {
                     }
                     _ => Err(ParserErrorReason::NotAllowedStateTransition),
                 },
-                RouteParserToken::Query { .. } => match token {
+                RouteParserToken::Query { .. } | RouteParserToken::QueryValue(_) => match token {
+                    RouteParserToken::QueryValue(_) => {
+                        Ok(ParserState::NthQuery { prev_token: token })
+                    }
                     RouteParserToken::QuerySeparator => {
                         Ok(ParserState::NthQuery { prev_token: token })
                     }
@@ -199,6 +299,7 @@ impl<'a> ParserState<'a> {
     }
 }
 
+#[cfg(feature = "std")]
 /// Parse a matching string into a vector of RouteParserTokens.
 ///
 /// The parsing logic involves using a state machine.
@@ -207,25 +308,100 @@ impl<'a> ParserState<'a> {
 /// due to the fact that erroneous tokens can't be fed into the transition function.
 ///
 /// This continues until the string is exhausted, or none of the parsers for the current state can parse the current input.
+///
+/// Literals and captures may precede the first `/`, which allows matching a host section like
+/// `{tenant}.example.com/dashboard` against routes that include the host.
 pub fn parse(
-    mut i: &str,
+    i: &str,
     field_type: FieldType,
-) -> Result<Vec<RouteParserToken>, PrettyParseError> {
-    let input = i;
-    let mut tokens: Vec<RouteParserToken> = vec![];
-    let mut state = ParserState::None;
-
-    loop {
-        let (ii, token) = parse_impl(i, &state, field_type).map_err(|e| match e {
-            nom::Err::Error(e) | nom::Err::Failure(e) => PrettyParseError {
-                error: e,
-                input,
-                remaining: i,
-            },
-            _ => panic!("parser should not be incomplete"),
-        })?;
-        i = ii;
-        state = state.transition(token.clone()).map_err(|reason| {
+) -> Result<Vec<RouteParserToken<'_>>, PrettyParseError<'_>> {
+    parse_with_version(i, field_type, GrammarVersion::V1)
+}
+
+#[cfg(feature = "std")]
+/// Parse a matching string written in a specific version of the grammar into a vector of
+/// RouteParserTokens.
+///
+/// An empty matching string denotes the root, and is equivalent to `!`.
+pub fn parse_with_version(
+    i: &str,
+    field_type: FieldType,
+    version: GrammarVersion,
+) -> Result<Vec<RouteParserToken<'_>>, PrettyParseError<'_>> {
+    tokenize_with_version(i, field_type, version).collect()
+}
+
+#[cfg(feature = "std")]
+/// Lazily parse a matching string, yielding RouteParserTokens one at a time.
+///
+/// This produces the same tokens as `parse`, without collecting them into a vector,
+/// which is useful for tooling that only needs to inspect or transform matcher strings.
+/// If an error is encountered, it is yielded and the iterator ends.
+///
+/// # Example
+/// ```
+/// use yew_router_route_parser::{parser::{tokenize, RouteParserToken}, FieldType};
+/// let mut tokens = tokenize("/lorem", FieldType::Unnamed);
+/// assert_eq!(tokens.next(), Some(Ok(RouteParserToken::Separator)));
+/// assert_eq!(tokens.next(), Some(Ok(RouteParserToken::Exact("lorem"))));
+/// assert_eq!(tokens.next(), None);
+/// ```
+pub fn tokenize(i: &str, field_type: FieldType) -> Tokenizer<'_> {
+    tokenize_with_version(i, field_type, GrammarVersion::V1)
+}
+
+#[cfg(feature = "std")]
+/// Lazily parse a matching string written in a specific version of the grammar.
+pub fn tokenize_with_version(
+    i: &str,
+    field_type: FieldType,
+    version: GrammarVersion,
+) -> Tokenizer<'_> {
+    Tokenizer {
+        input: i,
+        remaining: i,
+        state: ParserState::None,
+        field_type,
+        version,
+        done: false,
+    }
+}
+
+#[cfg(feature = "std")]
+/// Iterator over the RouteParserTokens of a matching string.
+///
+/// Created by `tokenize`.
+#[derive(Debug, Clone)]
+pub struct Tokenizer<'a> {
+    input: &'a str,
+    remaining: &'a str,
+    state: ParserState<'a>,
+    field_type: FieldType,
+    version: GrammarVersion,
+    done: bool,
+}
+
+#[cfg(feature = "std")]
+impl<'a> Tokenizer<'a> {
+    /// The part of the matching string that hasn't been tokenized yet.
+    pub fn remaining(&self) -> &'a str {
+        self.remaining
+    }
+
+    fn next_token(&mut self) -> Result<RouteParserToken<'a>, PrettyParseError<'a>> {
+        let input = self.input;
+        let i = self.remaining;
+        let (ii, token) =
+            parse_impl(i, &self.state, self.field_type, self.version).map_err(|e| match e {
+                nom::Err::Error(e) | nom::Err::Failure(e) => PrettyParseError {
+                    error: e,
+                    input,
+                    remaining: i,
+                },
+                _ => panic!("parser should not be incomplete"),
+            })?;
+        self.remaining = ii;
+        self.state = self.state.clone().transition(token).map_err(|reason| {
             let error = ParseError {
                 reason: Some(reason),
                 expected: vec![],
@@ -234,24 +410,41 @@ pub fn parse(
             PrettyParseError {
                 error,
                 input,
-                remaining: i,
+                remaining: ii,
             }
         })?;
-        tokens.push(token);
+        Ok(token)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Result<RouteParserToken<'a>, PrettyParseError<'a>>;
 
-        // If there is no more input, break out of the loop
-        if i.is_empty() {
-            break;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        if self.input.is_empty() {
+            // An empty matcher only matches the root.
+            self.done = true;
+            return Some(Ok(RouteParserToken::End));
         }
+        let token = self.next_token();
+        // Stop after an error, or if there is no more input.
+        self.done = token.is_err() || self.remaining.is_empty();
+        Some(token)
     }
-    Ok(tokens)
 }
 
+#[cfg(feature = "std")]
 fn parse_impl<'a>(
     i: &'a str,
-    state: &ParserState,
+    state: &ParserState<'a>,
     field_type: FieldType,
+    version: GrammarVersion,
 ) -> IResult<&'a str, RouteParserToken<'a>, ParseError> {
+    let exact = exact_for(version);
     match state {
         ParserState::None => alt((
             get_slash,
@@ -288,6 +481,8 @@ fn parse_impl<'a>(
             }
             RouteParserToken::Exact(_) => {
                 alt((
+                    matrix_param(field_type),
+                    continue_exact(version),
                     get_slash,
                     capture(field_type),
                     get_question,
@@ -306,19 +501,32 @@ fn parse_impl<'a>(
                 })
             }
             RouteParserToken::Capture(_) => {
-                alt((get_slash, exact, get_question, get_hash, get_end))(i).map_err(
-                    |mut e: nom::Err<ParseError>| {
-                        // Detect likely failures if the above failed to match.
-                        let reason: &mut Option<ParserErrorReason> = get_reason(&mut e);
-                        *reason = capture(field_type)(i)
-                            .map(|_| ParserErrorReason::AdjacentCaptures)
-                            .or_else(|_| get_and(i).map(|_| ParserErrorReason::AndBeforeQuestion))
-                            .ok()
-                            .or(*reason);
-                        e
-                    },
-                )
+                alt((
+                    get_slash,
+                    matrix_param(field_type),
+                    exact,
+                    get_question,
+                    get_hash,
+                    get_end,
+                ))(i)
+                .map_err(|mut e: nom::Err<ParseError>| {
+                    // Detect likely failures if the above failed to match.
+                    let reason: &mut Option<ParserErrorReason> = get_reason(&mut e);
+                    *reason = capture(field_type)(i)
+                        .map(|_| ParserErrorReason::AdjacentCaptures)
+                        .or_else(|_| get_and(i).map(|_| ParserErrorReason::AndBeforeQuestion))
+                        .ok()
+                        .or(*reason);
+                    e
+                })
             }
+            RouteParserToken::MatrixParam { .. } => alt((
+                matrix_param(field_type),
+                get_slash,
+                get_question,
+                get_hash,
+                get_end,
+            ))(i),
             _ => Err(nom::Err::Failure(ParseError {
                 reason: Some(ParserErrorReason::InvalidState),
                 expected: vec![],
@@ -337,16 +545,25 @@ fn parse_impl<'a>(
                     e
                 })
             }
-            RouteParserToken::Query { .. } => {
-                alt((get_and, get_hash, get_end))(i).map_err(|mut e: nom::Err<ParseError>| {
-                    // Detect likely failures if the above failed to match.
-                    let reason: &mut Option<ParserErrorReason> = get_reason(&mut e);
-                    *reason = get_question(i)
-                        .map(|_| ParserErrorReason::MultipleQuestions)
-                        .ok()
-                        .or(*reason);
-                    e
-                })
+            RouteParserToken::Query {
+                capture_or_exact: part,
+                ..
+            }
+            | RouteParserToken::QueryValue(part) => {
+                alt((query_value(field_type, *part), get_and, get_hash, get_end))(i).map_err(
+                    |mut e: nom::Err<ParseError>| {
+                        // Detect likely failures if the above failed to match.
+                        let reason: &mut Option<ParserErrorReason> = get_reason(&mut e);
+                        *reason = get_question(i)
+                            .map(|_| ParserErrorReason::MultipleQuestions)
+                            .or_else(|_| {
+                                capture(field_type)(i).map(|_| ParserErrorReason::AdjacentCaptures)
+                            })
+                            .ok()
+                            .or(*reason);
+                        e
+                    },
+                )
             }
             _ => Err(nom::Err::Failure(ParseError {
                 reason: Some(ParserErrorReason::InvalidState),
@@ -366,16 +583,25 @@ fn parse_impl<'a>(
                     e
                 })
             }
-            RouteParserToken::Query { .. } => {
-                alt((get_and, get_hash, get_end))(i).map_err(|mut e: nom::Err<ParseError>| {
-                    // Detect likely failures if the above failed to match.
-                    let reason: &mut Option<ParserErrorReason> = get_reason(&mut e);
-                    *reason = get_question(i)
-                        .map(|_| ParserErrorReason::MultipleQuestions)
-                        .ok()
-                        .or(*reason);
-                    e
-                })
+            RouteParserToken::Query {
+                capture_or_exact: part,
+                ..
+            }
+            | RouteParserToken::QueryValue(part) => {
+                alt((query_value(field_type, *part), get_and, get_hash, get_end))(i).map_err(
+                    |mut e: nom::Err<ParseError>| {
+                        // Detect likely failures if the above failed to match.
+                        let reason: &mut Option<ParserErrorReason> = get_reason(&mut e);
+                        *reason = get_question(i)
+                            .map(|_| ParserErrorReason::MultipleQuestions)
+                            .or_else(|_| {
+                                capture(field_type)(i).map(|_| ParserErrorReason::AdjacentCaptures)
+                            })
+                            .ok()
+                            .or(*reason);
+                        e
+                    },
+                )
             }
             _ => Err(nom::Err::Failure(ParseError {
                 reason: Some(ParserErrorReason::InvalidState),
@@ -385,7 +611,9 @@ fn parse_impl<'a>(
         },
         ParserState::Fragment { prev_token } => match prev_token {
             RouteParserToken::FragmentBegin => alt((exact, capture_single(field_type), get_end))(i),
-            RouteParserToken::Exact(_) => alt((capture_single(field_type), get_end))(i),
+            RouteParserToken::Exact(_) => {
+                alt((continue_exact(version), capture_single(field_type), get_end))(i)
+            }
             RouteParserToken::Capture(_) => alt((exact, get_end))(i),
             //                .map_err(|mut e: nom::Err<ParseError>| {
             //                    // Detect likely failures if the above failed to match.
@@ -408,20 +636,56 @@ fn parse_impl<'a>(
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod test {
     //    use super::*;
-    use super::parse as actual_parse;
-    use crate::{parser::RouteParserToken, FieldType, PrettyParseError};
+    use super::{parse as actual_parse, parse_with_version as actual_parse_with_version, tokenize};
+    use crate::{
+        parser::{RefCaptureVariant, RouteParserToken},
+        FieldType,
+        GrammarVersion::V2,
+        PrettyParseError,
+    };
 
     // Call all tests to parse with the Unnamed variant
     fn parse(i: &str) -> Result<Vec<RouteParserToken>, PrettyParseError> {
         actual_parse(i, FieldType::Unnamed)
     }
 
+    #[test]
+    fn tokenize_matches_parse() {
+        let i = "/lorem/{ipsum}?dolor={sit}#amet";
+        let tokens = tokenize(i, FieldType::Unnamed)
+            .collect::<Result<Vec<_>, _>>()
+            .expect("should parse");
+        assert_eq!(tokens, parse(i).expect("should parse"));
+    }
+
+    #[test]
+    fn tokenize_stops_after_error() {
+        let mut tokens = tokenize("/lorem/{ipsum}{dolor}/sit", FieldType::Unnamed);
+        assert!(tokens.by_ref().take(4).all(|token| token.is_ok()));
+        assert!(tokens.next().expect("should yield the error").is_err());
+        assert_eq!(tokens.next(), None);
+    }
+
     mod does_parse {
         use super::*;
 
+        #[test]
+        fn host_section() {
+            let tokens = parse("{tenant}.example.com:8080/dashboard").expect("should parse");
+            assert_eq!(
+                tokens,
+                vec![
+                    RouteParserToken::Capture(RefCaptureVariant::Named("tenant")),
+                    RouteParserToken::Exact(".example.com:8080"),
+                    RouteParserToken::Separator,
+                    RouteParserToken::Exact("dashboard"),
+                ]
+            );
+        }
+
         #[test]
         fn slash() {
             parse("/").expect("should parse");
@@ -447,6 +711,11 @@ mod test {
             parse("/lorem/{*:ipsum}").expect("should parse");
         }
 
+        #[test]
+        fn capture_greedy_rest_before_literal() {
+            parse("/lorem/{**:ipsum}/dolor").expect("should parse");
+        }
+
         #[test]
         fn capture_numbered_in_path() {
             parse("/lorem/{5:ipsum}").expect("should parse");
@@ -492,12 +761,6 @@ mod test {
         use super::*;
         use crate::error::{ExpectedToken, ParserErrorReason};
 
-        // TODO, should empty be ok?
-        #[test]
-        fn empty() {
-            parse("").expect_err("Should not parse");
-        }
-
         #[test]
         fn double_slash() {
             let x = parse("//").expect_err("Should not parse");
@@ -537,6 +800,13 @@ mod test {
             let x = parse("/hello!!").expect_err("Should not parse");
             assert_eq!(x.error.reason, Some(ParserErrorReason::TokensAfterEndToken));
         }
+
+        #[test]
+        fn bad_separator() {
+            let x = parse("/{lorem;sep=/}").expect_err("Should not parse");
+            assert_eq!(x.error.reason, Some(ParserErrorReason::BadSeparator('/')));
+            assert_eq!(x.offset(), 12);
+        }
     }
 
     mod correct_parse {
@@ -588,6 +858,30 @@ mod test {
             assert_eq!(parsed, expected);
         }
 
+        #[test]
+        fn matrix_params() {
+            let parsed = parse("/users;role=admin;team={team}/list").unwrap();
+            let expected = vec![
+                RouteParserToken::Separator,
+                RouteParserToken::Exact("users"),
+                RouteParserToken::MatrixParam {
+                    ident: "role",
+                    capture_or_exact: CaptureOrExact::Exact("admin"),
+                },
+                RouteParserToken::MatrixParam {
+                    ident: "team",
+                    capture_or_exact: CaptureOrExact::Capture(RefCaptureVariant::SeparatedNamed {
+                        sections: 1,
+                        separator: ';',
+                        name: "team",
+                    }),
+                },
+                RouteParserToken::Separator,
+                RouteParserToken::Exact("list"),
+            ];
+            assert_eq!(parsed, expected);
+        }
+
         #[test]
         fn query() {
             let parsed = parse("?query=this").unwrap();
@@ -642,6 +936,30 @@ mod test {
             assert_eq!(parsed, expected);
         }
 
+        #[test]
+        fn exact_with_multibyte_characters() {
+            let parsed = parse("/kategorie/bücher/{titel}").unwrap();
+            let expected = vec![
+                RouteParserToken::Separator,
+                RouteParserToken::Exact("kategorie"),
+                RouteParserToken::Separator,
+                RouteParserToken::Exact("bücher"),
+                RouteParserToken::Separator,
+                RouteParserToken::Capture(RefCaptureVariant::Named("titel")),
+            ];
+            assert_eq!(parsed, expected);
+            let parsed = parse("/日本?語=ü#€").unwrap();
+            assert_eq!(parsed[1], RouteParserToken::Exact("日本"));
+            assert_eq!(
+                parsed[3],
+                RouteParserToken::Query {
+                    ident: "語",
+                    capture_or_exact: CaptureOrExact::Exact("ü"),
+                }
+            );
+            assert_eq!(parsed[5], RouteParserToken::Exact("€"));
+        }
+
         #[test]
         fn exact_fragment() {
             let parsed = parse("#lorem").unwrap();
@@ -724,6 +1042,23 @@ mod test {
             assert_eq!(parsed, expected);
         }
 
+        #[test]
+        fn query_value_continues_query() {
+            let parsed = parse("?range={from}..{to}").unwrap();
+            let expected = vec![
+                RouteParserToken::QueryBegin,
+                RouteParserToken::Query {
+                    ident: "range",
+                    capture_or_exact: CaptureOrExact::Capture(RefCaptureVariant::Named("from")),
+                },
+                RouteParserToken::QueryValue(CaptureOrExact::Exact("..")),
+                RouteParserToken::QueryValue(CaptureOrExact::Capture(RefCaptureVariant::Named(
+                    "to",
+                ))),
+            ];
+            assert_eq!(parsed, expected);
+        }
+
         #[test]
         fn end_after_frag_capture() {
             let parsed = parse("#{cap}!").unwrap();
@@ -740,5 +1075,87 @@ mod test {
             let parsed = parse("!").unwrap();
             assert_eq!(parsed, vec![RouteParserToken::End]);
         }
+
+        #[test]
+        fn separated_captures() {
+            let parsed = parse("/{year;sep=-}/{3:date;sep=-}/{;sep=.}/{2;sep=_}").unwrap();
+            let expected = vec![
+                RouteParserToken::Separator,
+                RouteParserToken::Capture(RefCaptureVariant::SeparatedNamed {
+                    sections: 1,
+                    separator: '-',
+                    name: "year",
+                }),
+                RouteParserToken::Separator,
+                RouteParserToken::Capture(RefCaptureVariant::SeparatedNamed {
+                    sections: 3,
+                    separator: '-',
+                    name: "date",
+                }),
+                RouteParserToken::Separator,
+                RouteParserToken::Capture(RefCaptureVariant::SeparatedUnnamed {
+                    sections: 1,
+                    separator: '.',
+                }),
+                RouteParserToken::Separator,
+                RouteParserToken::Capture(RefCaptureVariant::SeparatedUnnamed {
+                    sections: 2,
+                    separator: '_',
+                }),
+            ];
+            assert_eq!(parsed, expected);
+        }
+
+        #[test]
+        fn empty_is_end() {
+            let parsed = parse("").unwrap();
+            assert_eq!(parsed, vec![RouteParserToken::End]);
+        }
+
+        #[test]
+        fn escaped_path_v2() {
+            let parsed =
+                actual_parse_with_version(r"/lorem\!/ipsum", FieldType::Unnamed, V2).unwrap();
+            let expected = vec![
+                RouteParserToken::Separator,
+                RouteParserToken::Exact("lorem"),
+                RouteParserToken::Exact("!"),
+                RouteParserToken::Separator,
+                RouteParserToken::Exact("ipsum"),
+            ];
+            assert_eq!(parsed, expected);
+        }
+
+        #[test]
+        fn escaped_fragment_v2() {
+            let parsed = actual_parse_with_version(r"#\{lorem\}", FieldType::Unnamed, V2).unwrap();
+            let expected = vec![
+                RouteParserToken::FragmentBegin,
+                RouteParserToken::Exact("{"),
+                RouteParserToken::Exact("lorem"),
+                RouteParserToken::Exact("}"),
+            ];
+            assert_eq!(parsed, expected);
+        }
+
+        #[test]
+        fn escape_not_recognized_in_v1() {
+            parse(r"/lorem\{").expect_err("should not parse");
+        }
+
+        #[test]
+        fn reserved_characters_v2() {
+            use crate::ParserErrorReason;
+            let e = actual_parse_with_version("/lorem[/{ipsum}]", FieldType::Unnamed, V2)
+                .expect_err("should not parse");
+            assert_eq!(e.reason(), Some(ParserErrorReason::ReservedCharacter('[')));
+            assert_eq!(e.offset(), 6);
+            let e = actual_parse_with_version("#lorem|ipsum", FieldType::Unnamed, V2)
+                .expect_err("should not parse");
+            assert_eq!(e.reason(), Some(ParserErrorReason::ReservedCharacter('|')));
+            actual_parse_with_version(r"/lorem\[ipsum\]", FieldType::Unnamed, V2)
+                .expect("should parse");
+            parse("/lorem[ipsum]").expect("should parse");
+        }
     }
 }
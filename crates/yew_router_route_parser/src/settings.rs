@@ -0,0 +1,81 @@
+//! Settings that determine how matcher strings are parsed and matched.
+
+/// Indicates if the parser is working to create a matcher for a datastructure with named or unnamed fields.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Ord, PartialOrd)]
+pub enum FieldType {
+    /// For Thing { field: String }
+    Named,
+    /// for Thing(String)
+    Unnamed,
+}
+
+/// The version of the matcher grammar that a matcher string is written in.
+///
+/// New syntax is only added to newer versions, so the meaning of an existing matcher string never
+/// silently changes.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Ord, PartialOrd, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GrammarVersion {
+    /// The original grammar.
+    #[default]
+    V1,
+    /// Extends V1 by allowing special characters in path and fragment literals to be escaped
+    /// with a `\`.
+    ///
+    /// The characters `[`, `]`, `(`, `)`, and `|` are reserved for optional segments and
+    /// alternation, which aren't supported yet, so they must be escaped in path and fragment
+    /// literals as well.
+    V2,
+}
+
+/// Characters that `GrammarVersion::V2` reserves for optional segments and alternation.
+///
+/// They aren't allowed unescaped in path and fragment literals, so giving them a meaning later
+/// doesn't change what existing matcher strings match.
+pub(crate) const RESERVED_CHARS: &str = "[]()|";
+
+/// Determines how a matcher treats query parameters and fragments in the route that its matcher
+/// string doesn't mention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum QueryMode {
+    /// Extra query parameters and fragments are only rejected if the structure of the matcher
+    /// string rejects them, for example if the matcher is `complete`, or ends with `!`.
+    #[default]
+    Structural,
+    /// Any query parameter whose name isn't mentioned in the matcher string causes the match to
+    /// fail, so typos like `?pgae=2` aren't silently ignored.
+    Strict,
+    /// Extra query parameters, and fragments, never cause the match to fail.
+    ///
+    /// If the matcher string has no query, the query of the route is ignored, and if it has no
+    /// fragment, the fragment of the route is ignored.
+    /// Query parameters following the ones that the matcher string mentions are ignored as well.
+    Lenient,
+}
+
+/// Settings used for the matcher.
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MatcherSettings {
+    /// A matcher must consume all of the input to succeed.
+    pub complete: bool,
+    /// All literal matches do not care about case.
+    pub case_insensitive: bool,
+    /// The version of the grammar used to parse the matcher string.
+    pub grammar_version: GrammarVersion,
+    /// How query parameters and fragments that aren't mentioned in the matcher string are treated.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub query_mode: QueryMode,
+}
+
+impl Default for MatcherSettings {
+    fn default() -> Self {
+        MatcherSettings {
+            complete: true,
+            case_insensitive: false,
+            grammar_version: GrammarVersion::V1,
+            query_mode: QueryMode::Structural,
+        }
+    }
+}
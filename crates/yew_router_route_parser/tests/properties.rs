@@ -0,0 +1,109 @@
+//! Property tests for parsing and optimizing matcher strings.
+//!
+//! Matcher strings are often written by hand, or generated by tooling, so no input should be able
+//! to make the parser panic.
+#![cfg(feature = "std")]
+use proptest::prelude::*;
+use yew_router_route_parser::{
+    convert_tokens, parse_str_and_optimize_tokens_with_version,
+    parser::{parse_with_version, tokenize_with_version},
+    shadows, to_matcher_string, FieldType, GrammarVersion, MatcherToken,
+};
+
+fn field_type() -> impl Strategy<Value = FieldType> {
+    prop_oneof![Just(FieldType::Named), Just(FieldType::Unnamed)]
+}
+
+fn grammar_version() -> impl Strategy<Value = GrammarVersion> {
+    prop_oneof![Just(GrammarVersion::V1), Just(GrammarVersion::V2)]
+}
+
+/// Strings made mostly of the characters that have a meaning in the grammar.
+fn matcher_string() -> impl Strategy<Value = String> {
+    prop_oneof![
+        "[a-z0-9/{}*:;?&=#!\\\\._ -]{0,32}",
+        any::<String>(),
+        prop::collection::vec(
+            prop_oneof![
+                "[a-z]{1,6}".prop_map(|s| format!("/{}", s)),
+                "[a-z]{1,6}".prop_map(|s| format!("/{{{}}}", s)),
+                "[a-z]{1,6}".prop_map(|s| format!("/{{*:{}}}", s)),
+                "[a-z]{1,6}".prop_map(|s| format!("/{{**:{}}}", s)),
+                (1usize..4, "[a-z]{1,6}").prop_map(|(n, s)| format!("/{{{}:{}}}", n, s)),
+                "[a-z]{1,6}".prop_map(|s| format!(";{}={{{}}}", s, s)),
+                "[a-z]{1,6}".prop_map(|s| format!("?{}={{{}}}", s, s)),
+                "[a-z]{1,6}".prop_map(|s| format!("&{}={{{}}}", s, s)),
+                "[a-z]{1,6}".prop_map(|s| format!("#{}", s)),
+                Just("!".to_string()),
+            ],
+            0..6
+        )
+        .prop_map(|sections| sections.concat()),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn parse_and_optimize_never_panics(
+        matcher in matcher_string(),
+        field_type in field_type(),
+        version in grammar_version(),
+    ) {
+        let _ = parse_str_and_optimize_tokens_with_version(&matcher, field_type, version);
+    }
+
+    #[test]
+    fn tokenize_agrees_with_parse(
+        matcher in matcher_string(),
+        field_type in field_type(),
+        version in grammar_version(),
+    ) {
+        let parsed = parse_with_version(&matcher, field_type, version).ok();
+        let tokenized = tokenize_with_version(&matcher, field_type, version)
+            .collect::<Result<Vec<_>, _>>()
+            .ok();
+        // An empty matcher string is turned into the end token by `parse`, but not by `tokenize`.
+        if !matcher.is_empty() {
+            prop_assert_eq!(parsed, tokenized);
+        }
+    }
+
+    #[test]
+    fn optimized_tokens_are_well_formed(
+        matcher in matcher_string(),
+        field_type in field_type(),
+        version in grammar_version(),
+    ) {
+        if let Ok(tokens) = parse_with_version(&matcher, field_type, version) {
+            let optimized = convert_tokens(&tokens);
+            // The end token can only be the last token.
+            if let Some(position) = optimized.iter().position(|t| *t == MatcherToken::End) {
+                prop_assert_eq!(position, optimized.len() - 1);
+            }
+            // Literals are always condensed.
+            for pair in optimized.windows(2) {
+                if let [MatcherToken::Exact(_), MatcherToken::Exact(_)] = pair {
+                    prop_assert!(false, "literals weren't condensed: {:?}", optimized);
+                }
+            }
+            prop_assert!(shadows(&optimized, &optimized));
+        }
+    }
+
+    /// Escaped literals can contain characters that are structural elsewhere, like an escaped `=`
+    /// in the path, which the reconstructed matcher string doesn't escape, so only V1 strings are
+    /// reconstructed.
+    #[test]
+    fn matcher_strings_round_trip(
+        matcher in matcher_string(),
+        field_type in field_type(),
+    ) {
+        let parsed = parse_str_and_optimize_tokens_with_version(&matcher, field_type, GrammarVersion::V1);
+        if let Ok(tokens) = parsed {
+            let reconstructed = to_matcher_string(&tokens);
+            let reparsed =
+                parse_str_and_optimize_tokens_with_version(&reconstructed, field_type, GrammarVersion::V2);
+            prop_assert_eq!(reparsed.ok(), Some(tokens), "reconstructed as {}", reconstructed);
+        }
+    }
+}
@@ -3,7 +3,11 @@ use crate::{
     page::{Page, PageProps},
 };
 use yew::{html::ChildrenWithProps, prelude::*, Properties};
-use yew_router::{agent::RouteRequest::GetCurrentRoute, matcher::RouteMatcher, prelude::*};
+use yew_router::{
+    agent::{RouteBroadcast, RouteRequest::GetCurrentRoute},
+    matcher::RouteMatcher,
+    prelude::*,
+};
 
 pub struct Guide {
     router_agent: Box<dyn Bridge<RouteAgent>>,
@@ -25,7 +29,8 @@ impl Component for Guide {
     type Properties = GuideProps;
 
     fn create(props: Self::Properties, mut link: ComponentLink<Self>) -> Self {
-        let callback = link.send_back(Msg::UpdateRoute);
+        let callback =
+            link.send_back(|broadcast: RouteBroadcast| Msg::UpdateRoute(broadcast.route));
         let router_agent = RouteAgent::bridge(callback);
         Guide {
             router_agent,
@@ -0,0 +1,18 @@
+//! Matches arbitrary routes against arbitrary matcher strings.
+//!
+//! The first line of the input is the matcher string, and the rest is the route.
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use yew_router::matcher::RouteMatcher;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(input) = std::str::from_utf8(data) {
+        let mut lines = input.splitn(2, '\n');
+        let matcher = lines.next().unwrap_or_default();
+        let route = lines.next().unwrap_or_default();
+        if let Ok(matcher) = RouteMatcher::try_from(matcher) {
+            let _ = matcher.capture_route_into_map(route);
+            let _ = matcher.capture_route_into_vec(route);
+        }
+    }
+});
@@ -0,0 +1,16 @@
+//! Parses and optimizes arbitrary matcher strings.
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use yew_router::yew_router_route_parser::{
+    parse_str_and_optimize_tokens_with_version, FieldType, GrammarVersion,
+};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(matcher) = std::str::from_utf8(data) {
+        for field_type in &[FieldType::Named, FieldType::Unnamed] {
+            for version in &[GrammarVersion::V1, GrammarVersion::V2] {
+                let _ = parse_str_and_optimize_tokens_with_version(matcher, *field_type, *version);
+            }
+        }
+    }
+});
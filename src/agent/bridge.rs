@@ -1,10 +1,12 @@
 //! Bridge to RouteAgent.
 use crate::{
-    agent::{AgentState, RouteAgent},
-    route::Route,
+    agent::{DefaultNamespace, RouteAgent, RouteBroadcast, RouteNamespace},
+    route::{Route, RouterState},
+    switch::Switch,
 };
 use std::{
     fmt::{Debug, Error as FmtError, Formatter},
+    marker::PhantomData,
     ops::{Deref, DerefMut},
 };
 use yew::{
@@ -15,16 +17,23 @@ use yew::{
 /// A wrapped bridge to the route agent.
 ///
 /// A component that owns this can send and receive messages from the agent.
-pub struct RouteAgentBridge<T>(Box<dyn Bridge<RouteAgent<T>>>)
+pub struct RouteAgentBridge<T = (), N = DefaultNamespace>(Box<dyn Bridge<RouteAgent<T, N>>>)
 where
-    for<'de> T: AgentState<'de>;
+    T: RouterState,
+    N: RouteNamespace;
 
-impl<T> RouteAgentBridge<T>
+impl<T, N> RouteAgentBridge<T, N>
 where
-    for<'de> T: AgentState<'de>,
+    T: RouterState,
+    N: RouteNamespace,
 {
     /// Creates a new bridge.
     pub fn new(callback: Callback<Route<T>>) -> Self {
+        Self::with_broadcasts(callback.reform(|broadcast: RouteBroadcast<T>| broadcast.route))
+    }
+
+    /// Creates a new bridge, which also tells whether each navigation was forced.
+    pub fn with_broadcasts(callback: Callback<RouteBroadcast<T>>) -> Self {
         let router_agent = RouteAgent::bridge(callback);
         RouteAgentBridge(router_agent)
     }
@@ -34,26 +43,115 @@ where
     /// Directly spawn a new Router
     pub fn spawn(callback: Callback<Route<T>>) -> Self {
         use yew::agent::Discoverer;
+        let callback = callback.reform(|broadcast: RouteBroadcast<T>| broadcast.route);
         let router_agent = Context::spawn_or_join(Some(callback));
         RouteAgentBridge(router_agent)
     }
 }
 
-impl<T: for<'de> AgentState<'de>> Debug for RouteAgentBridge<T> {
+impl<T: RouterState, N: RouteNamespace> Debug for RouteAgentBridge<T, N> {
     fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
         f.debug_tuple("RouteAgentBridge").finish()
     }
 }
 
-impl<T: for<'de> AgentState<'de>> Deref for RouteAgentBridge<T> {
-    type Target = Box<dyn Bridge<RouteAgent<T>>>;
+impl<T: RouterState, N: RouteNamespace> Deref for RouteAgentBridge<T, N> {
+    type Target = Box<dyn Bridge<RouteAgent<T, N>>>;
 
     fn deref(&self) -> &Self::Target {
         &self.0
     }
 }
-impl<T: for<'de> AgentState<'de>> DerefMut for RouteAgentBridge<T> {
+impl<T: RouterState, N: RouteNamespace> DerefMut for RouteAgentBridge<T, N> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         &mut self.0
     }
 }
+
+/// A wrapped bridge to the route agent, which switches every route it receives into `SW`.
+///
+/// Components that own this receive the variant of `SW` that each route matched, or `None` if it
+/// matched none of them, instead of routes that they would have to match themselves.
+/// Requests are sent the same way as through a `RouteAgentBridge`.
+///
+/// # Example
+/// ```
+/// use yew_router::{agent::SwitchAgentBridge, Switch};
+/// use yew::Callback;
+///
+/// #[derive(Switch, Clone)]
+/// enum AppRoute {
+///     #[to = "/users/{id}"]
+///     User { id: u32 },
+///     #[to = "/"]
+///     Home,
+/// }
+///
+/// # fn dont_execute() {
+/// let callback = Callback::from(|route: Option<AppRoute>| {
+///     if let Some(AppRoute::User { id }) = route {
+///         // ...
+///     }
+/// });
+/// let bridge = SwitchAgentBridge::<AppRoute, ()>::new(callback);
+/// # }
+/// ```
+pub struct SwitchAgentBridge<SW, T = (), N = DefaultNamespace>
+where
+    SW: Switch + 'static,
+    T: RouterState,
+    N: RouteNamespace,
+{
+    bridge: RouteAgentBridge<T, N>,
+    _switch: PhantomData<SW>,
+}
+
+impl<SW, T, N> SwitchAgentBridge<SW, T, N>
+where
+    SW: Switch + 'static,
+    T: RouterState,
+    N: RouteNamespace,
+{
+    /// Creates a new bridge, which emits the variant that each route matched.
+    pub fn new(callback: Callback<Option<SW>>) -> Self {
+        SwitchAgentBridge {
+            bridge: RouteAgentBridge::new(callback.reform(SW::switch::<T>)),
+            _switch: PhantomData,
+        }
+    }
+}
+
+impl<SW, T, N> Debug for SwitchAgentBridge<SW, T, N>
+where
+    SW: Switch + 'static,
+    T: RouterState,
+    N: RouteNamespace,
+{
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        f.debug_tuple("SwitchAgentBridge").finish()
+    }
+}
+
+impl<SW, T, N> Deref for SwitchAgentBridge<SW, T, N>
+where
+    SW: Switch + 'static,
+    T: RouterState,
+    N: RouteNamespace,
+{
+    type Target = Box<dyn Bridge<RouteAgent<T, N>>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.bridge
+    }
+}
+
+impl<SW, T, N> DerefMut for SwitchAgentBridge<SW, T, N>
+where
+    SW: Switch + 'static,
+    T: RouterState,
+    N: RouteNamespace,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.bridge
+    }
+}
@@ -0,0 +1,26 @@
+//! The routes that a `RouteAgent` sends to the components connected to it.
+use crate::route::Route;
+use serde::{Deserialize, Serialize};
+
+/// A route that a `RouteAgent` sends to the components connected to it, after navigating to it,
+/// or in reply to `RouteRequest::GetCurrentRoute`.
+///
+/// `RouteAgentBridge::new` only passes on the route, while
+/// `RouteAgentBridge::with_broadcasts` passes on the whole broadcast.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RouteBroadcast<T = ()> {
+    /// The route, with the state that was stored along with it.
+    pub route: Route<T>,
+    /// Whether the navigation was forced with `RouteRequest::ForceChangeRoute`, in which case a
+    /// `Router` renders the route again even if it already has it.
+    pub forced: bool,
+}
+
+impl<T> From<Route<T>> for RouteBroadcast<T> {
+    fn from(route: Route<T>) -> Self {
+        RouteBroadcast {
+            route,
+            forced: false,
+        }
+    }
+}
@@ -1,5 +1,9 @@
 //! Dispatcher to RouteAgent.
-use crate::agent::{AgentState, RouteAgent};
+use crate::{
+    agent::{shared::with_shared, DefaultNamespace, RouteAgent, RouteNamespace, RouteRequest},
+    route::{QueryError, QueryPairs, Route, RouterState},
+};
+use serde::Serialize;
 use std::{
     fmt::{Debug, Error as FmtError, Formatter},
     ops::{Deref, DerefMut},
@@ -9,44 +13,106 @@ use yew::agent::{Dispatched, Dispatcher};
 /// A wrapped dispatcher to the route agent.
 ///
 /// A component that owns and instance of this can send messages to the RouteAgent, but not receive them.
-pub struct RouteAgentDispatcher<T>(Dispatcher<RouteAgent<T>>)
+pub struct RouteAgentDispatcher<T = (), N = DefaultNamespace>(Dispatcher<RouteAgent<T, N>>)
 where
-    for<'de> T: AgentState<'de>;
+    T: RouterState,
+    N: RouteNamespace;
 
-impl<T> RouteAgentDispatcher<T>
+impl<T, N> RouteAgentDispatcher<T, N>
 where
-    for<'de> T: AgentState<'de>,
+    T: RouterState,
+    N: RouteNamespace,
 {
     /// Creates a new bridge.
     pub fn new() -> Self {
         let dispatcher = RouteAgent::dispatcher();
         RouteAgentDispatcher(dispatcher)
     }
+
+    /// Gets the route that the agent most recently navigated to, along with its state.
+    ///
+    /// Unlike sending `RouteRequest::GetCurrentRoute` through a bridge, this answers immediately,
+    /// so components that only need to know the route once don't have to subscribe to the agent.
+    pub fn current_route(&self) -> Route<T> {
+        with_shared::<T, N, Route<T>, _>(|current_route| current_route.clone())
+    }
+
+    /// Changes the query of the current route to the fields of `query`, keeping its path,
+    /// fragment, and state, and alerts connected components to the route change.
+    ///
+    /// See [`QueryPairs::from_serialize`](../route/struct.QueryPairs.html#method.from_serialize)
+    /// for how the fields are written.
+    ///
+    /// # Example
+    /// ```
+    /// use serde::Serialize;
+    /// use yew_router::agent::RouteAgentDispatcher;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Filter {
+    ///     sort: &'static str,
+    ///     page: u32,
+    /// }
+    ///
+    /// # fn dont_execute() {
+    /// let mut dispatcher = RouteAgentDispatcher::<()>::new();
+    /// dispatcher
+    ///     .push_query(&Filter {
+    ///         sort: "name",
+    ///         page: 2,
+    ///     })
+    ///     .unwrap();
+    /// # }
+    /// ```
+    pub fn push_query<Q: Serialize + ?Sized>(&mut self, query: &Q) -> Result<(), QueryError> {
+        let query = QueryPairs::from_serialize(query)?;
+        self.send(RouteRequest::UpdateRoute {
+            path: None,
+            query: Some(query.to_string()),
+            fragment: None,
+            state: None,
+        });
+        Ok(())
+    }
+
+    /// Changes the fragment of the current route, keeping its path, query, and state, and alerts
+    /// connected components to the route change.
+    ///
+    /// The fragment may be given with or without its `#`, and an empty one removes it.
+    pub fn push_fragment(&mut self, fragment: &str) {
+        self.send(RouteRequest::UpdateRoute {
+            path: None,
+            query: None,
+            fragment: Some(fragment.to_string()),
+            state: None,
+        });
+    }
 }
 
-impl<T> Default for RouteAgentDispatcher<T>
+impl<T, N> Default for RouteAgentDispatcher<T, N>
 where
-    for<'de> T: AgentState<'de>,
+    T: RouterState,
+    N: RouteNamespace,
 {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl<T: for<'de> AgentState<'de>> Debug for RouteAgentDispatcher<T> {
+impl<T: RouterState, N: RouteNamespace> Debug for RouteAgentDispatcher<T, N> {
     fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
         f.debug_tuple("RouteAgentDispatcher").finish()
     }
 }
 
-impl<T: for<'de> AgentState<'de>> Deref for RouteAgentDispatcher<T> {
-    type Target = Dispatcher<RouteAgent<T>>;
+impl<T: RouterState, N: RouteNamespace> Deref for RouteAgentDispatcher<T, N> {
+    type Target = Dispatcher<RouteAgent<T, N>>;
 
     fn deref(&self) -> &Self::Target {
         &self.0
     }
 }
-impl<T: for<'de> AgentState<'de>> DerefMut for RouteAgentDispatcher<T> {
+impl<T: RouterState, N: RouteNamespace> DerefMut for RouteAgentDispatcher<T, N> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         &mut self.0
     }
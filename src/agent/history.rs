@@ -0,0 +1,115 @@
+//! Records the routes that a `RouteAgent` navigated to, for time-travel debugging.
+use crate::{
+    agent::{shared::with_shared, NavigationKind},
+    route::Route,
+};
+use std::collections::VecDeque;
+
+/// A route that was navigated to, as recorded by a `RouteAgent`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryEntry<T = ()> {
+    /// The route that was navigated to, with the state that was stored along with it.
+    pub route: Route<T>,
+    /// When the navigation happened, in milliseconds since the Unix epoch.
+    pub timestamp: f64,
+    /// How the route was changed.
+    pub kind: NavigationKind,
+}
+
+/// The most recent navigations, up to a limit.
+#[derive(Debug)]
+pub(crate) struct RouteHistory<T> {
+    limit: usize,
+    entries: VecDeque<HistoryEntry<T>>,
+}
+
+impl<T> Default for RouteHistory<T> {
+    fn default() -> Self {
+        RouteHistory {
+            limit: 0,
+            entries: VecDeque::new(),
+        }
+    }
+}
+
+impl<T> RouteHistory<T> {
+    /// Sets how many entries are kept, dropping the oldest ones if there are too many.
+    ///
+    /// A limit of 0 stops recording.
+    pub(crate) fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+        while self.entries.len() > limit {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Records an entry, dropping the oldest one if the limit is reached.
+    pub(crate) fn record(&mut self, entry: HistoryEntry<T>) {
+        if self.limit == 0 {
+            return;
+        }
+        if self.entries.len() == self.limit {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+}
+
+/// Gives access to the history of the agent with the state `T` and namespace `N`.
+///
+/// The history is kept outside of the agent, so it can be read without a bridge to the agent.
+pub(crate) fn with_history<T: 'static, N: 'static, R>(
+    f: impl FnOnce(&mut RouteHistory<T>) -> R,
+) -> R {
+    with_shared::<T, N, RouteHistory<T>, _>(f)
+}
+
+/// Gets the recorded entries, from oldest to newest.
+pub(crate) fn entries<T: Clone + 'static, N: 'static>() -> Vec<HistoryEntry<T>> {
+    with_history::<T, N, _>(|history| history.entries.iter().cloned().collect())
+}
+
+/// Gets the recorded entry at the index, counting from the oldest entry.
+pub(crate) fn entry<T: Clone + 'static, N: 'static>(index: usize) -> Option<HistoryEntry<T>> {
+    with_history::<T, N, _>(|history| history.entries.get(index).cloned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(route: &str) -> HistoryEntry<()> {
+        HistoryEntry {
+            route: Route::from(route),
+            timestamp: 0.0,
+            kind: NavigationKind::Push,
+        }
+    }
+
+    fn routes(history: &RouteHistory<()>) -> Vec<&str> {
+        history
+            .entries
+            .iter()
+            .map(|entry| entry.route.route.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn does_not_record_without_limit() {
+        let mut history = RouteHistory::default();
+        history.record(entry("/a"));
+        assert!(history.entries.is_empty());
+    }
+
+    #[test]
+    fn drops_oldest_entries() {
+        let mut history = RouteHistory::default();
+        history.set_limit(2);
+        history.record(entry("/a"));
+        history.record(entry("/b"));
+        history.record(entry("/c"));
+        assert_eq!(routes(&history), vec!["/b", "/c"]);
+        history.set_limit(1);
+        assert_eq!(routes(&history), vec!["/c"]);
+    }
+}
@@ -0,0 +1,230 @@
+//! Turns clicks on plain links into navigations of a `RouteAgent`.
+use stdweb::{
+    unstable::TryInto,
+    web::{
+        document,
+        event::{ClickEvent, IEvent, IMouseEvent, MouseButton},
+        window, Element, EventListenerHandle, IElement, IEventTarget, INode,
+    },
+};
+
+use crate::{
+    agent::{DefaultNamespace, RouteAgentDispatcher, RouteNamespace, RouteRequest},
+    route::{Route, RouterState},
+    service::{decode_route, encode_route, RouteCodec},
+    strategy::RoutingStrategy,
+};
+use std::{
+    cell::RefCell,
+    fmt::{Debug, Error as FmtError, Formatter},
+    rc::Rc,
+};
+use yew::agent::Bridge;
+
+/// The attribute that keeps a link from being intercepted.
+const IGNORE_ATTRIBUTE: &str = "data-router-ignore";
+
+type Exclusions = Rc<RefCell<Vec<Rc<dyn Fn(&str) -> bool>>>>;
+
+/// Makes clicks on plain `<a href="/internal">` links change the route through the `RouteAgent` of
+/// the namespace `N`, instead of loading the page again.
+///
+/// This lets html that the app doesn't render itself, like content from markdown or a CMS, take
+/// part in navigation without rewriting its links to `RouterLink`s.
+///
+/// A click is left to the browser if:
+/// * a modifier key or a button other than the primary one is pressed, or the click was already
+/// handled with `prevent_default`,
+/// * the link has a `target` other than `_self`, a `download` attribute, `rel="external"`, or a
+/// `data-router-ignore` attribute,
+/// * the link leads to another origin, or only changes the fragment of the current page,
+/// * the url of the route that the namespace's strategy finds in the link isn't the link itself,
+/// like `/about` when the route is kept in the fragment,
+/// * or one of the exclusions added with `excluding` applies to the route.
+///
+/// Clicks are listened to until this is dropped.
+///
+/// # Example
+/// ```
+/// use yew_router::agent::LinkInterceptor;
+///
+/// # fn dont_execute() {
+/// let interceptor = LinkInterceptor::<()>::document()
+///     .excluding(|route: &str| route.starts_with("/api/"));
+/// # }
+/// ```
+pub struct LinkInterceptor<T = (), N = DefaultNamespace>
+where
+    T: RouterState,
+    N: RouteNamespace,
+{
+    listener: Option<EventListenerHandle>,
+    /// Shared with the listener, so exclusions can be added after it is attached.
+    exclusions: Exclusions,
+    /// Shared with the listener, which sends the navigations through it.
+    _dispatcher: Rc<RefCell<RouteAgentDispatcher<T, N>>>,
+}
+
+impl<T, N> LinkInterceptor<T, N>
+where
+    T: RouterState,
+    N: RouteNamespace,
+{
+    /// Intercepts clicks on links anywhere in the document.
+    pub fn document() -> Self {
+        Self::attach(None)
+    }
+
+    /// Intercepts clicks on links inside the element, like the root element of the app.
+    pub fn within(root: Element) -> Self {
+        Self::attach(Some(root))
+    }
+
+    /// Leaves clicks on links to routes that the function returns `true` for to the browser.
+    ///
+    /// This can be called several times, in which case routes that any of the functions return
+    /// `true` for are excluded.
+    pub fn excluding<F: Fn(&str) -> bool + 'static>(self, exclude: F) -> Self {
+        self.exclusions.borrow_mut().push(Rc::new(exclude));
+        self
+    }
+
+    fn attach(root: Option<Element>) -> Self {
+        let dispatcher = Rc::new(RefCell::new(RouteAgentDispatcher::<T, N>::new()));
+        let exclusions: Exclusions = Rc::new(RefCell::new(Vec::new()));
+        let strategy = N::strategy();
+        let codec = N::codec();
+
+        let listener = {
+            let dispatcher = dispatcher.clone();
+            let exclusions = exclusions.clone();
+            let root = root.clone();
+            move |event: ClickEvent| {
+                let codec = codec.as_ref().map(|codec| &**codec);
+                let route = match link_route(&event, root.as_ref(), &*strategy, codec) {
+                    Some(route) => route,
+                    None => return,
+                };
+                // The exclusions are copied out first, so they can add exclusions themselves.
+                let exclusions = exclusions.borrow().clone();
+                if exclusions.iter().any(|exclude| exclude(&route)) {
+                    return;
+                }
+                event.prevent_default();
+                dispatcher
+                    .borrow_mut()
+                    .send(RouteRequest::ChangeRoute(Route { route, state: None }));
+            }
+        };
+        let listener = match &root {
+            Some(root) => root.add_event_listener(listener),
+            None => document().add_event_listener(listener),
+        };
+
+        LinkInterceptor {
+            listener: Some(listener),
+            exclusions,
+            _dispatcher: dispatcher,
+        }
+    }
+}
+
+impl<T, N> Drop for LinkInterceptor<T, N>
+where
+    T: RouterState,
+    N: RouteNamespace,
+{
+    fn drop(&mut self) {
+        if let Some(listener) = self.listener.take() {
+            listener.remove();
+        }
+    }
+}
+
+impl<T: RouterState, N: RouteNamespace> Debug for LinkInterceptor<T, N> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        f.debug_struct("LinkInterceptor")
+            .field("exclusions", &self.exclusions.borrow().len())
+            .finish()
+    }
+}
+
+/// Gets the route of the link that was clicked, if the click should be turned into a navigation.
+fn link_route(
+    event: &ClickEvent,
+    root: Option<&Element>,
+    strategy: &dyn RoutingStrategy,
+    codec: Option<&dyn RouteCodec>,
+) -> Option<String> {
+    if event.default_prevented()
+        || event.button() != MouseButton::Left
+        || event.ctrl_key()
+        || event.meta_key()
+        || event.shift_key()
+        || event.alt_key()
+    {
+        return None;
+    }
+
+    let target: Element = event.target()?.try_into().ok()?;
+    let link: Element = js! {
+        var link = @{target}.closest("a[href]");
+        // The links of SVG documents don't have their href as a string.
+        return link && typeof link.href === "string" ? link : null;
+    }
+    .try_into()
+    .ok()?;
+    if root.map_or(false, |root| !root.contains(&link)) {
+        return None;
+    }
+    let opens_elsewhere = link
+        .get_attribute("target")
+        .map_or(false, |target| !target.is_empty() && target != "_self");
+    let is_external = link.get_attribute("rel").map_or(false, |rel| {
+        rel.split_whitespace().any(|value| value == "external")
+    });
+    if opens_elsewhere
+        || is_external
+        || link.has_attribute("download")
+        || link.has_attribute(IGNORE_ATTRIBUTE)
+    {
+        return None;
+    }
+
+    let location = window().location()?;
+    let parts: Vec<String> = js! {
+        var link = @{&link};
+        return [link.href, link.origin, link.pathname, link.search, link.hash];
+    }
+    .try_into()
+    .ok()?;
+    let (href, origin, path, query, fragment) = match parts.as_slice() {
+        [href, origin, path, query, fragment] => (href, origin, path, query, fragment),
+        _ => return None,
+    };
+    let current_href = location.href().ok()?;
+    let without_fragment = |url: &str| url.split('#').next().unwrap_or_default().to_string();
+    if *origin != location.origin().ok()?
+        || (!fragment.is_empty() && without_fragment(href) == without_fragment(&current_href))
+    {
+        return None;
+    }
+
+    let route = decode_route(codec, &strategy.route_from_url(path, query, fragment));
+    let url = strategy.url_for_route(
+        &encode_route(codec, &route),
+        &location.pathname().ok()?,
+        &location.search().ok()?,
+        &location.hash().ok()?,
+    );
+    let resolved_url: String = js! {
+        return new URL(@{url}, @{current_href}).href;
+    }
+    .try_into()
+    .ok()?;
+    if resolved_url == *href {
+        Some(route)
+    } else {
+        None
+    }
+}
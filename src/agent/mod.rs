@@ -2,38 +2,87 @@
 //!
 //! It wraps a route service and allows calls to be sent to it to update every subscriber,
 //! or just the element that made the request.
-use crate::service::RouteService;
+use crate::service::{CallbackHandle, DeepLink, RouteService, StateBudget};
 
 use yew::prelude::worker::*;
 
 use std::collections::HashSet;
 
 use serde::{Deserialize, Serialize};
-use std::fmt::{Debug, Error as FmtError, Formatter};
+use std::{
+    fmt::{Debug, Error as FmtError, Formatter},
+    marker::PhantomData,
+    time::Duration,
+};
 
-use crate::route::{Route, RouteState};
+use crate::{
+    debug::log_navigation,
+    matcher::Normalization,
+    route::{splice_route_string, Route, RouterState},
+};
 use log::trace;
+use yew::services::{timeout::TimeoutTask, TimeoutService};
+
+mod namespace;
+pub use namespace::{DefaultNamespace, RouteNamespace};
 
 mod bridge;
-pub use bridge::RouteAgentBridge;
+pub use bridge::{RouteAgentBridge, SwitchAgentBridge};
+
+mod broadcast;
+pub use broadcast::RouteBroadcast;
 
 mod dispatcher;
 pub use dispatcher::RouteAgentDispatcher;
 
+mod navigation;
+pub use navigation::{Navigation, NavigationKind, NavigationListener};
+
+mod history;
+pub use history::HistoryEntry;
+
+mod link_interceptor;
+pub use link_interceptor::LinkInterceptor;
+
+mod policy;
+pub use policy::NavigationPolicy;
+use policy::{Admission, NavigationBurst};
+
+mod shared;
+
+#[cfg(feature = "store")]
+mod store;
+#[cfg(feature = "store")]
+pub use store::{NavigationAction, RouteStoreSync};
+
+#[cfg(feature = "tab_sync")]
+mod tab_sync;
+#[cfg(feature = "tab_sync")]
+use tab_sync::TabSyncListener;
+#[cfg(feature = "tab_sync")]
+pub use tab_sync::{TabSync, TabSyncMode};
+
 /// Any state that can be used in the router agent must meet the criteria of this trait.
-pub trait AgentState<'de>: RouteState + Serialize + Deserialize<'de> + Debug {}
-impl<'de, T> AgentState<'de> for T where T: RouteState + Serialize + Deserialize<'de> + Debug {}
+#[deprecated(note = "Use `RouterState`, which every type of this crate is bound on, instead")]
+pub trait AgentState<'de>: RouterState {}
+#[allow(deprecated)]
+impl<'de, T: RouterState> AgentState<'de> for T {}
 
 /// Internal Message used for the RouteAgent.
 #[derive(Debug)]
-pub enum Msg<T> {
+pub enum Msg<T = ()> {
     /// Message for when the route is changed.
     BrowserNavigationRouteChanged((String, T)),
+    /// Message for when another tab of the app navigated to a route.
+    #[cfg(feature = "tab_sync")]
+    ExternalNavigation(Route<T>),
+    /// Message for when the request that waits to be handled by the navigation policy is due.
+    NavigationDue,
 }
 
 /// Input message type for interacting with the `RouteAgent'.
 #[derive(Serialize, Deserialize, Debug)]
-pub enum RouteRequest<T> {
+pub enum RouteRequest<T = ()> {
     /// Replaces the most recent Route with a new one and alerts connected components to the route
     /// change.
     ReplaceRoute(Route<T>),
@@ -41,15 +90,110 @@ pub enum RouteRequest<T> {
     /// the route change.
     ReplaceRouteNoBroadcast(Route<T>),
     /// Changes the route using a Route struct and alerts connected components to the route change.
+    ///
+    /// Like the other navigation requests, this does nothing if the route and its state are
+    /// already current, so clicking a link twice doesn't add a history entry or cause a render.
     ChangeRoute(Route<T>),
+    /// Changes the route like `ChangeRoute`, but alerts connected components even if the route
+    /// and its state are already current, so they render it again.
+    ///
+    /// If the route is already current, its history entry is replaced instead of duplicated.
+    ForceChangeRoute(Route<T>),
     /// Changes the route using a Route struct, but does not alert connected components to the
     /// route change.
     ChangeRouteNoBroadcast(Route<T>),
+    /// Changes the route to one relative to the current route, like `../sibling`, and alerts
+    /// connected components to the route change.
+    ///
+    /// This lets nested components navigate without knowing where they are mounted.
+    /// See [`RouteService::resolve_relative`](../service/struct.RouteService.html#method.resolve_relative)
+    /// for how the route is resolved.
+    ChangeRouteRelative(Route<T>),
+    /// Moves the current route to the url of another locale, and alerts connected components to
+    /// the route change, even if the canonical route stays the same.
+    ///
+    /// This only works for namespaces whose strategy localizes routes, like
+    /// [`LocalizedStrategy`](../strategy/struct.LocalizedStrategy.html).
+    /// The state of the current route is kept.
+    SwitchLocale(String),
+    /// Saves the current route and its state to the deep link, and redirects the browser to
+    /// another url, like the authorization endpoint of an OAuth provider.
+    ///
+    /// See [`RouteService::redirect_with_deep_link`](../service/struct.RouteService.html#method.redirect_with_deep_link).
+    RedirectWithDeepLink {
+        /// Where the route is saved.
+        deep_link: DeepLink,
+        /// The url that is redirected to.
+        url: String,
+    },
+    /// Replaces the current route with the one saved to the deep link before the app was
+    /// redirected away, and alerts connected components to the route change.
+    ///
+    /// This does nothing if no route was saved, or it was saved too long ago.
+    /// See [`RouteService::restore_deep_link`](../service/struct.RouteService.html#method.restore_deep_link).
+    RestoreDeepLink(DeepLink),
+    /// Replaces the current route with the one that it remembers as the one to return to, like
+    /// the page that a guard redirected to the login page from, or with the provided route if it
+    /// doesn't remember one, and alerts connected components to the route change.
+    ///
+    /// See [`RouteService::take_intended_route`](../service/struct.RouteService.html#method.take_intended_route).
+    ReturnToIntendedRoute(Route<T>),
     /// Gets the current route.
     GetCurrentRoute,
+    /// Sets how routes read from the browser are normalized before they are sent to connected
+    /// components.
+    ///
+    /// This is useful for cleaning up inbound links that contain duplicate slashes or `.` and `..`
+    /// segments.
+    SetNormalization(Normalization),
+    /// Sets whether the host is included in routes, so that matchers can match on subdomains.
+    SetIncludeHost(bool),
+    /// Sets a limit on the size of the state stored in history entries, and what is done with
+    /// state that exceeds it.
+    ///
+    /// See [`RouteService::set_state_budget`](../service/struct.RouteService.html#method.set_state_budget).
+    SetStateBudget(Option<StateBudget>),
+    /// Sets how bursts of navigation requests are collapsed, like the ones of a slider that
+    /// updates the query on every input event.
+    ///
+    /// The request that waits to be handled, if there is one, is handled right away.
+    /// See [`NavigationPolicy`](enum.NavigationPolicy.html).
+    SetNavigationPolicy(NavigationPolicy),
+    /// Changes only the provided parts of the route in a single history entry, and alerts
+    /// connected components to the route change.
+    ///
+    /// The query and fragment may be provided with or without their `?` and `#`, and providing an
+    /// empty one removes it from the route.
+    /// If no state is provided, the state of the current route is kept.
+    UpdateRoute {
+        /// The new path.
+        path: Option<String>,
+        /// The new query.
+        query: Option<String>,
+        /// The new fragment.
+        fragment: Option<String>,
+        /// The new state.
+        state: Option<T>,
+    },
+    /// Sets how many of the most recent navigations are recorded, for time-travel debugging.
+    ///
+    /// Recording is disabled by default, and a limit of 0 disables it again.
+    /// The recorded entries can be read with `RouteAgent::recorded_history`.
+    SetHistoryLimit(usize),
+    /// Replaces the route with a recorded entry, counting from the oldest one, and alerts
+    /// connected components to the route change.
+    ///
+    /// The jump itself isn't recorded, so the recorded history can be moved around in freely.
+    JumpToHistoryEntry(usize),
+    /// Shares the navigations of the agent with the other tabs of the app, and handles theirs, or
+    /// stops doing so if `None` is given.
+    ///
+    /// See [`TabSync`](struct.TabSync.html).
+    #[cfg(feature = "tab_sync")]
+    SetTabSync(Option<TabSync>),
 }
 
-impl<T> Transferable for RouteRequest<T> where for<'de> T: Serialize + Deserialize<'de> {}
+impl<T: RouterState> Transferable for RouteRequest<T> {}
 
 /// The RouteAgent holds on to the RouteService singleton and mediates access to it.
 ///
@@ -61,22 +205,40 @@ impl<T> Transferable for RouteRequest<T> where for<'de> T: Serialize + Deseriali
 ///
 /// If you use multiple agents with different types, then the Agents won't be able to communicate to
 /// each other and associated components may not work as intended.
-pub struct RouteAgent<T>
+///
+/// Each [`RouteNamespace`](trait.RouteNamespace.html) `N` gets its own agent, which can control a
+/// different part of the url.
+///
+/// An agent only lives while bridges or dispatchers are connected to it.
+/// Once the last one is dropped, like when the app's `Router` is unmounted, it stops listening to
+/// the browser, and the next bridge or dispatcher creates a new agent.
+pub struct RouteAgent<T = (), N = DefaultNamespace>
 where
-    for<'de> T: AgentState<'de>,
+    T: RouterState,
+    N: RouteNamespace,
 {
     // In order to have the AgentLink<Self> below, apparently T must be constrained like this.
     // Unfortunately, this means that everything related to an agent requires this constraint.
-    link: AgentLink<RouteAgent<T>>,
+    link: AgentLink<RouteAgent<T, N>>,
     /// The service through which communication with the browser happens.
     route_service: RouteService<T>,
+    /// Identifies the callback through which the service reports browser navigation.
+    callback_handle: CallbackHandle,
     /// A list of all entities connected to the router.
     /// When a route changes, either initiated by the browser or by the app,
     /// the route change will be broadcast to all listening entities.
     subscribers: HashSet<HandlerId>,
+    /// Shares navigations with the other tabs, and listens to theirs.
+    #[cfg(feature = "tab_sync")]
+    tab_sync: Option<(TabSync, TabSyncListener)>,
+    /// Collapses bursts of navigation requests according to the navigation policy.
+    navigation_burst: NavigationBurst<(RouteRequest<T>, HandlerId)>,
+    /// Handles the request that waits in `navigation_burst` once it is due.
+    navigation_due: Option<TimeoutTask>,
+    namespace: PhantomData<N>,
 }
 
-impl<T: for<'de> AgentState<'de>> Debug for RouteAgent<T> {
+impl<T: RouterState, N: RouteNamespace> Debug for RouteAgent<T, N> {
     fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
         f.debug_struct("RouteAgent")
             .field("link", &"-")
@@ -86,35 +248,145 @@ impl<T: for<'de> AgentState<'de>> Debug for RouteAgent<T> {
     }
 }
 
-impl<T> Agent for RouteAgent<T>
+impl<T, N> RouteAgent<T, N>
 where
-    for<'de> T: AgentState<'de>,
+    T: RouterState,
+    N: RouteNamespace,
+{
+    /// Gets the navigations that were recorded after recording was enabled with
+    /// `RouteRequest::SetHistoryLimit`, from oldest to newest.
+    pub fn recorded_history() -> Vec<HistoryEntry<T>> {
+        history::entries::<T, N>()
+    }
+
+    /// Determines if the route and its state are the ones that the agent most recently navigated to.
+    fn is_current(&self, route_string: &str, state: &Option<T>) -> bool {
+        shared::with_shared::<T, N, Route<T>, _>(|current_route| {
+            current_route.route == route_string && is_same_state(&current_route.state, state)
+        })
+    }
+
+    /// Sends the route to every connected component.
+    fn broadcast(&self, broadcast: RouteBroadcast<T>) {
+        for sub in &self.subscribers {
+            self.link.response(*sub, broadcast.clone());
+        }
+    }
+
+    /// Reports the navigation to the current route to every `NavigationListener`, and records it.
+    fn record_navigation(&mut self, kind: NavigationKind, state: Option<T>) {
+        let route = self.notify_navigation(kind, state);
+        let entry = HistoryEntry {
+            route,
+            timestamp: self.route_service.now(),
+            kind,
+        };
+        #[cfg(feature = "tab_sync")]
+        {
+            // Navigations that followed another tab aren't shared, so tabs don't echo each other.
+            if let Some((tab_sync, _)) = &self.tab_sync {
+                if kind != NavigationKind::External {
+                    tab_sync.broadcast(&entry.route);
+                }
+            }
+        }
+        history::with_history::<T, N, _>(|history| history.record(entry));
+    }
+
+    /// Reports the navigation to the current route to every `NavigationListener`, returning the
+    /// current route.
+    fn notify_navigation(&mut self, kind: NavigationKind, state: Option<T>) -> Route<T> {
+        let mut to = Route::current_route(&self.route_service);
+        to.state = state;
+        let from = shared::with_shared::<T, N, Route<T>, _>(|current_route| {
+            std::mem::replace(current_route, to.clone())
+        });
+        navigation::notify::<T, N>(&Navigation {
+            from,
+            to: to.clone(),
+            kind,
+        });
+        to
+    }
+}
+
+/// Determines if two states stored along with the same route are the same.
+///
+/// Routes that are navigated to with links have no state, while the ones that the browser
+/// navigates back and forth to have the state stored in their history entry, which is the default
+/// state for the former, so a missing state is the default one.
+fn is_same_state<T: Default + PartialEq + Clone>(current: &Option<T>, state: &Option<T>) -> bool {
+    current.clone().unwrap_or_default() == state.clone().unwrap_or_default()
+}
+
+impl<T, N> Agent for RouteAgent<T, N>
+where
+    T: RouterState,
+    N: RouteNamespace,
 {
     type Input = RouteRequest<T>;
     type Message = Msg<T>;
-    type Output = Route<T>;
+    type Output = RouteBroadcast<T>;
     type Reach = Context;
 
-    fn create(link: AgentLink<RouteAgent<T>>) -> Self {
+    fn create(link: AgentLink<RouteAgent<T, N>>) -> Self {
         let callback = link.send_back(Msg::BrowserNavigationRouteChanged);
         let mut route_service = RouteService::new();
-        route_service.register_callback(callback);
+        route_service.set_shared_strategy(N::strategy());
+        route_service.set_codec(N::codec());
+        let callback_handle = route_service.register_callback(callback);
+        let route = Route::current_route(&route_service);
+        shared::with_shared::<T, N, Route<T>, _>(|current_route| *current_route = route);
 
         RouteAgent {
             link,
             route_service,
+            callback_handle,
             subscribers: HashSet::new(),
+            #[cfg(feature = "tab_sync")]
+            tab_sync: None,
+            navigation_burst: NavigationBurst::default(),
+            navigation_due: None,
+            namespace: PhantomData,
         }
     }
 
     fn update(&mut self, msg: Self::Message) {
         match msg {
-            Msg::BrowserNavigationRouteChanged((_route_string, state)) => {
+            Msg::BrowserNavigationRouteChanged((route_string, state)) => {
                 trace!("Browser navigated");
+                log_navigation("browser", &route_string);
+                self.discard_navigation_burst();
                 let mut route = Route::current_route(&self.route_service);
                 route.state = Some(state);
-                for sub in &self.subscribers {
-                    self.link.response(*sub, route.clone());
+                self.record_navigation(NavigationKind::Pop, route.state.clone());
+                self.broadcast(route.into());
+            }
+            #[cfg(feature = "tab_sync")]
+            Msg::ExternalNavigation(route) => {
+                let mode = match &self.tab_sync {
+                    Some((tab_sync, _)) => tab_sync.mode(),
+                    None => return,
+                };
+                if mode == TabSyncMode::Follow {
+                    let route_string: String = route.to_string();
+                    if self.is_current(&route_string, &route.state) {
+                        trace!("Ignoring navigation to the current route");
+                        return;
+                    }
+                    log_navigation("ExternalNavigation", &route_string);
+                    self.route_service
+                        .set_route(&route_string, route.state.clone().unwrap_or_default());
+                    self.record_navigation(NavigationKind::External, route.state);
+                }
+                let route = Route::current_route(&self.route_service);
+                self.broadcast(route.into());
+            }
+            Msg::NavigationDue => {
+                self.navigation_due = None;
+                let now = self.route_service.now();
+                if let Some((msg, who)) = self.navigation_burst.release(now) {
+                    self.handle_request(msg, who);
                 }
             }
         }
@@ -124,47 +396,279 @@ where
         self.subscribers.insert(id);
     }
 
+    fn disconnected(&mut self, id: HandlerId) {
+        self.subscribers.remove(&id);
+    }
+
+    fn destroy(&mut self) {
+        // The agent is destroyed once its last bridge or dispatcher is dropped, and created again
+        // by the next one, which registers a new callback.
+        trace!("Tearing down the route agent");
+        self.route_service.deregister_callback(self.callback_handle);
+        #[cfg(feature = "tab_sync")]
+        {
+            self.tab_sync = None;
+        }
+    }
+
     fn handle(&mut self, msg: Self::Input, who: HandlerId) {
+        if !policy::is_collapsible(&msg) {
+            if policy::is_navigation(&msg) {
+                // The navigation is newer than the request that waits to be handled.
+                self.discard_navigation_burst();
+            }
+            self.handle_request(msg, who);
+            return;
+        }
+        let now = self.route_service.now();
+        let request = (msg, who);
+        if let Some((flushed, from)) = self.navigation_burst.flush_before(&request, now) {
+            self.handle_request(flushed, from);
+        }
+        match self.navigation_burst.admit(request, now) {
+            Admission::Now((msg, who)) => self.handle_request(msg, who),
+            Admission::After(delay) => {
+                // Replacing the previous timeout cancels it.
+                self.navigation_due = Some(TimeoutService::new().spawn(
+                    Duration::from_millis(u64::from(delay)),
+                    self.link.send_back(|_| Msg::NavigationDue),
+                ));
+            }
+            Admission::Queued => {}
+        }
+    }
+}
+
+impl<T, N> RouteAgent<T, N>
+where
+    T: RouterState,
+    N: RouteNamespace,
+{
+    /// Discards the request that waits to be handled by the navigation policy, if there is one.
+    fn discard_navigation_burst(&mut self) {
+        self.navigation_burst.discard();
+        self.navigation_due = None;
+    }
+
+    /// Handles a request once the navigation policy lets it through.
+    fn handle_request(&mut self, msg: RouteRequest<T>, who: HandlerId) {
         match msg {
             RouteRequest::ReplaceRoute(route) => {
                 let route_string: String = route.to_string();
+                if self.is_current(&route_string, &route.state) {
+                    trace!("Ignoring navigation to the current route");
+                    return;
+                }
+                log_navigation("ReplaceRoute", &route_string);
                 self.route_service
-                    .replace_route(&route_string, route.state.unwrap_or_default());
+                    .replace_route(&route_string, route.state.clone().unwrap_or_default());
+                self.record_navigation(NavigationKind::Replace, route.state);
                 let route = Route::current_route(&self.route_service);
-                for sub in &self.subscribers {
-                    self.link.response(*sub, route.clone());
-                }
+                self.broadcast(route.into());
             }
             RouteRequest::ReplaceRouteNoBroadcast(route) => {
                 let route_string: String = route.to_string();
+                if self.is_current(&route_string, &route.state) {
+                    trace!("Ignoring navigation to the current route");
+                    return;
+                }
+                log_navigation("ReplaceRouteNoBroadcast", &route_string);
                 self.route_service
-                    .replace_route(&route_string, route.state.unwrap_or_default());
+                    .replace_route(&route_string, route.state.clone().unwrap_or_default());
+                self.record_navigation(NavigationKind::Replace, route.state);
             }
             RouteRequest::ChangeRoute(route) => {
                 let route_string: String = route.to_string();
+                if self.is_current(&route_string, &route.state) {
+                    trace!("Ignoring navigation to the current route");
+                    return;
+                }
+                log_navigation("ChangeRoute", &route_string);
                 // set the route
                 self.route_service
-                    .set_route(&route_string, route.state.unwrap_or_default());
+                    .set_route(&route_string, route.state.clone().unwrap_or_default());
+                self.record_navigation(NavigationKind::Push, route.state);
                 // get the new route. This will contain a default state object
                 let route = Route::current_route(&self.route_service);
                 // broadcast it to all listening components
-                for sub in &self.subscribers {
-                    self.link.response(*sub, route.clone());
+                self.broadcast(route.into());
+            }
+            RouteRequest::ForceChangeRoute(route) => {
+                let route_string: String = route.to_string();
+                log_navigation("ForceChangeRoute", &route_string);
+                let state = route.state.clone().unwrap_or_default();
+                if self.is_current(&route_string, &route.state) {
+                    self.route_service.replace_route(&route_string, state);
+                    self.record_navigation(NavigationKind::Replace, route.state);
+                } else {
+                    self.route_service.set_route(&route_string, state);
+                    self.record_navigation(NavigationKind::Push, route.state);
                 }
+                let route = Route::current_route(&self.route_service);
+                self.broadcast(RouteBroadcast {
+                    route,
+                    forced: true,
+                });
             }
             RouteRequest::ChangeRouteNoBroadcast(route) => {
                 let route_string: String = route.to_string();
+                if self.is_current(&route_string, &route.state) {
+                    trace!("Ignoring navigation to the current route");
+                    return;
+                }
+                log_navigation("ChangeRouteNoBroadcast", &route_string);
                 self.route_service
-                    .set_route(&route_string, route.state.unwrap_or_default());
+                    .set_route(&route_string, route.state.clone().unwrap_or_default());
+                self.record_navigation(NavigationKind::Push, route.state);
+            }
+            RouteRequest::ChangeRouteRelative(route) => {
+                let route_string = self.route_service.resolve_relative(&route.route);
+                if self.is_current(&route_string, &route.state) {
+                    trace!("Ignoring navigation to the current route");
+                    return;
+                }
+                log_navigation("ChangeRouteRelative", &route_string);
+                self.route_service
+                    .set_route(&route_string, route.state.clone().unwrap_or_default());
+                self.record_navigation(NavigationKind::Push, route.state);
+                let route = Route::current_route(&self.route_service);
+                self.broadcast(route.into());
+            }
+            RouteRequest::UpdateRoute {
+                path,
+                query,
+                fragment,
+                state,
+            } => {
+                let current_route = Route::current_route(&self.route_service);
+                let route_string = splice_route_string(
+                    &current_route.route,
+                    path.as_ref().map(String::as_str),
+                    query.as_ref().map(String::as_str),
+                    fragment.as_ref().map(String::as_str),
+                );
+                log_navigation("UpdateRoute", &route_string);
+                let state = state.or_else(|| {
+                    shared::with_shared::<T, N, Route<T>, _>(|current_route| {
+                        current_route.state.clone()
+                    })
+                });
+                if self.is_current(&route_string, &state) {
+                    trace!("Ignoring navigation to the current route");
+                    return;
+                }
+                self.route_service
+                    .set_route(&route_string, state.clone().unwrap_or_default());
+                self.record_navigation(NavigationKind::Push, state);
+                let route = Route::current_route(&self.route_service);
+                self.broadcast(route.into());
+            }
+            RouteRequest::SwitchLocale(locale) => {
+                let state = shared::with_shared::<T, N, Route<T>, _>(|current_route| {
+                    current_route.state.clone()
+                });
+                if !self
+                    .route_service
+                    .switch_locale(&locale, state.clone().unwrap_or_default())
+                {
+                    log::warn!("The route can't be switched to the locale '{}'", locale);
+                    return;
+                }
+                let route = Route::current_route(&self.route_service);
+                log_navigation("SwitchLocale", &route.route);
+                self.record_navigation(NavigationKind::Push, state);
+                self.broadcast(route.into());
+            }
+            RouteRequest::RedirectWithDeepLink { deep_link, url } => {
+                let state = shared::with_shared::<T, N, Route<T>, _>(|current_route| {
+                    current_route.state.clone()
+                });
+                log_navigation("RedirectWithDeepLink", &url);
+                self.route_service.redirect_with_deep_link(
+                    &deep_link,
+                    state.unwrap_or_default(),
+                    &url,
+                );
+            }
+            RouteRequest::RestoreDeepLink(deep_link) => {
+                let restored = match self.route_service.restore_deep_link(&deep_link) {
+                    Some(restored) => restored,
+                    None => return,
+                };
+                log_navigation("RestoreDeepLink", &restored.route);
+                self.record_navigation(NavigationKind::Replace, restored.state);
+                let route = Route::current_route(&self.route_service);
+                self.broadcast(route.into());
+            }
+            RouteRequest::ReturnToIntendedRoute(fallback) => {
+                let route = match self.route_service.take_intended_route() {
+                    Some(intended) => Route {
+                        route: intended,
+                        state: fallback.state,
+                    },
+                    None => fallback,
+                };
+                self.handle_request(RouteRequest::ReplaceRoute(route), who);
             }
             RouteRequest::GetCurrentRoute => {
                 let route = Route::current_route(&self.route_service);
-                self.link.response(who, route.clone());
+                self.link.response(who, route.into());
+            }
+            RouteRequest::SetNormalization(normalization) => {
+                self.route_service.set_normalization(normalization);
+            }
+            RouteRequest::SetIncludeHost(include_host) => {
+                self.route_service.set_include_host(include_host);
+            }
+            RouteRequest::SetStateBudget(state_budget) => {
+                self.route_service.set_state_budget(state_budget);
+            }
+            RouteRequest::SetNavigationPolicy(navigation_policy) => {
+                self.navigation_burst.set_policy(navigation_policy);
+                self.navigation_due = None;
+                let now = self.route_service.now();
+                if let Some((msg, who)) = self.navigation_burst.release(now) {
+                    self.handle_request(msg, who);
+                }
+            }
+            RouteRequest::SetHistoryLimit(limit) => {
+                history::with_history::<T, N, _>(|history| history.set_limit(limit));
+            }
+            #[cfg(feature = "tab_sync")]
+            RouteRequest::SetTabSync(tab_sync) => {
+                // The previous listener is dropped first, which stops it from listening.
+                self.tab_sync = None;
+                self.tab_sync = tab_sync.map(|tab_sync| {
+                    let listener = tab_sync.listen(self.link.send_back(Msg::ExternalNavigation));
+                    (tab_sync, listener)
+                });
+            }
+            RouteRequest::JumpToHistoryEntry(index) => {
+                if let Some(entry) = history::entry::<T, N>(index) {
+                    let route_string: String = entry.route.to_string();
+                    log_navigation("JumpToHistoryEntry", &route_string);
+                    let state = entry.route.state;
+                    self.route_service
+                        .replace_route(&route_string, state.clone().unwrap_or_default());
+                    self.notify_navigation(NavigationKind::Replace, state);
+                    let route = Route::current_route(&self.route_service);
+                    self.broadcast(route.into());
+                }
             }
         }
     }
+}
 
-    fn disconnected(&mut self, id: HandlerId) {
-        self.subscribers.remove(&id);
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn missing_states_are_the_default_state() {
+        assert!(is_same_state::<()>(&Some(()), &None));
+        assert!(is_same_state::<u32>(&None, &Some(0)));
+        assert!(is_same_state::<u32>(&Some(1), &Some(1)));
+        assert!(!is_same_state::<u32>(&None, &Some(1)));
+        assert!(!is_same_state::<u32>(&Some(1), &Some(2)));
     }
 }
@@ -0,0 +1,55 @@
+//! Namespaces that allow multiple independent route agents to exist in one app.
+use crate::{
+    service::RouteCodec,
+    strategy::{self, RoutingStrategy},
+};
+use std::{fmt::Debug, rc::Rc};
+
+/// Identifies a `RouteAgent` and the part of the url that it controls.
+///
+/// Agents are singletons per type, so every namespace gets its own `RouteAgent`, and `Router`s,
+/// bridges, and dispatchers that use the same namespace all talk to that agent.
+/// This allows, for example, one `Router` to switch on the path, while another switches on a query
+/// parameter that controls a side panel.
+///
+/// # Example
+/// ```
+/// use std::rc::Rc;
+/// use yew_router::{
+///     agent::RouteNamespace,
+///     strategy::{QueryStrategy, RoutingStrategy},
+/// };
+///
+/// #[derive(Debug)]
+/// pub struct Panel;
+/// impl RouteNamespace for Panel {
+///     fn strategy() -> Rc<dyn RoutingStrategy> {
+///         Rc::new(QueryStrategy::new("panel"))
+///     }
+/// }
+/// ```
+pub trait RouteNamespace: Debug + 'static {
+    /// Where in the url the route of this namespace is stored.
+    ///
+    /// This is called once, when the agent for the namespace is created.
+    fn strategy() -> Rc<dyn RoutingStrategy> {
+        strategy::default_strategy()
+    }
+
+    /// The codec that transforms the query and fragment of the routes of this namespace in the
+    /// url, like to sign the tokens they carry.
+    ///
+    /// This is called once, when the agent for the namespace is created, as well as by every
+    /// `RouterLink` of the namespace when it renders.
+    fn codec() -> Option<Rc<dyn RouteCodec>> {
+        None
+    }
+}
+
+/// The namespace that is used when none is specified.
+///
+/// It uses the strategy set by `strategy::set_default_strategy`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct DefaultNamespace;
+
+impl RouteNamespace for DefaultNamespace {}
@@ -0,0 +1,133 @@
+//! A stream of the navigations performed by a `RouteAgent`, for analytics and pageview tracking.
+use crate::{
+    agent::{shared::with_shared, DefaultNamespace, RouteAgentDispatcher, RouteNamespace},
+    route::{Route, RouterState},
+};
+use std::{
+    cell::Cell,
+    fmt::{Debug, Error as FmtError, Formatter},
+};
+use yew::Callback;
+
+/// How the route was changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NavigationKind {
+    /// A new history entry was created, by requests like `ChangeRoute` or `ChangeRouteRelative`.
+    Push,
+    /// The current history entry was replaced, by `ReplaceRoute` or `ReplaceRouteNoBroadcast`.
+    Replace,
+    /// The browser moved through its history, because the back or forward buttons were pressed, or
+    /// the url was edited.
+    Pop,
+    /// Another tab of the app navigated, and this one followed it, because they share their
+    /// navigations through a `TabSync`.
+    External,
+}
+
+/// A change from one route to another.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Navigation<T = ()> {
+    /// The route before the navigation.
+    pub from: Route<T>,
+    /// The route after the navigation, with the state that was stored along with it.
+    pub to: Route<T>,
+    /// How the route was changed.
+    pub kind: NavigationKind,
+}
+
+type Listeners<T> = Vec<(usize, Callback<Navigation<T>>)>;
+
+thread_local! {
+    static NEXT_LISTENER_ID: Cell<usize> = Cell::new(0);
+}
+
+fn with_listeners<T: 'static, N: 'static, R>(f: impl FnOnce(&mut Listeners<T>) -> R) -> R {
+    with_shared::<T, N, Listeners<T>, _>(f)
+}
+
+/// Sends the navigation to every listener of the agent.
+pub(crate) fn notify<T: Clone + 'static, N: 'static>(navigation: &Navigation<T>) {
+    // The callbacks are copied out first, so listeners can be added or dropped while handling it.
+    let callbacks: Vec<Callback<Navigation<T>>> = with_listeners::<T, N, _>(|listeners| {
+        listeners
+            .iter()
+            .map(|(_, callback)| callback.clone())
+            .collect()
+    });
+    for callback in callbacks {
+        callback.emit(navigation.clone())
+    }
+}
+
+/// Receives every navigation performed by the `RouteAgent` of the namespace `N`.
+///
+/// Unlike a `RouteAgentBridge`, which only receives the new route, this reports where the
+/// navigation came from and how it happened, which is what analytics and pageview tracking need.
+/// Navigations that don't broadcast their change to bridges are reported too.
+///
+/// The callback stops being called once this is dropped.
+///
+/// # Example
+/// ```
+/// use yew::Callback;
+/// use yew_router::agent::{Navigation, NavigationListener};
+///
+/// # fn dont_execute() {
+/// let listener = NavigationListener::<()>::new(Callback::from(|navigation: Navigation<()>| {
+///     println!(
+///         "{:?} from {} to {}",
+///         navigation.kind, navigation.from, navigation.to
+///     )
+/// }));
+/// # }
+/// ```
+pub struct NavigationListener<T = (), N = DefaultNamespace>
+where
+    T: RouterState,
+    N: RouteNamespace,
+{
+    id: usize,
+    /// Keeps the agent alive while this is listening to it.
+    _dispatcher: RouteAgentDispatcher<T, N>,
+}
+
+impl<T, N> NavigationListener<T, N>
+where
+    T: RouterState,
+    N: RouteNamespace,
+{
+    /// Starts listening to navigations.
+    pub fn new(callback: Callback<Navigation<T>>) -> Self {
+        let id = NEXT_LISTENER_ID.with(|next_id| {
+            let id = next_id.get();
+            next_id.set(id + 1);
+            id
+        });
+        with_listeners::<T, N, _>(|listeners| listeners.push((id, callback)));
+        NavigationListener {
+            id,
+            _dispatcher: RouteAgentDispatcher::new(),
+        }
+    }
+}
+
+impl<T, N> Drop for NavigationListener<T, N>
+where
+    T: RouterState,
+    N: RouteNamespace,
+{
+    fn drop(&mut self) {
+        let id = self.id;
+        with_listeners::<T, N, _>(|listeners| {
+            listeners.retain(|(listener_id, _)| *listener_id != id)
+        });
+    }
+}
+
+impl<T: RouterState, N: RouteNamespace> Debug for NavigationListener<T, N> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        f.debug_struct("NavigationListener")
+            .field("id", &self.id)
+            .finish()
+    }
+}
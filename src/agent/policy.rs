@@ -0,0 +1,363 @@
+//! Collapses bursts of navigations, like the ones of a slider that updates the query of the route
+//! on every input event.
+use crate::agent::RouteRequest;
+use serde::{Deserialize, Serialize};
+use std::mem;
+
+/// How a `RouteAgent` handles bursts of navigation requests.
+///
+/// Only the `ReplaceRoute`, `ReplaceRouteNoBroadcast`, `ChangeRoute`, `ChangeRouteNoBroadcast`
+/// and `UpdateRoute` requests are collapsed, in which case only the last request of a burst is
+/// handled, so it is always applied.
+/// A burst only collapses requests of the same kind from the same sender, and the parts of the
+/// route that its `UpdateRoute`s change are merged, so an update of the query followed by an
+/// update of the fragment changes both.
+/// A request of another kind, or from another sender, has the request that is waiting handled
+/// first, so no history entry is lost.
+/// Other navigations, like the browser's back button, discard the request that is waiting to be
+/// handled, as they are newer.
+///
+/// It is set with `RouteRequest::SetNavigationPolicy`.
+#[derive(Debug, PartialEq, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum NavigationPolicy {
+    /// Every request is handled right away, which is the default.
+    #[default]
+    Immediate,
+    /// Requests are handled once no other request was made for this many milliseconds.
+    Debounce(u32),
+    /// At most one request is handled every this many milliseconds, and the last request that was
+    /// made meanwhile is handled at the end of the interval.
+    Throttle(u32),
+}
+
+/// Determines if the request is collapsed with the ones that follow it by a `NavigationPolicy`.
+pub(crate) fn is_collapsible<T>(request: &RouteRequest<T>) -> bool {
+    match request {
+        RouteRequest::ReplaceRoute(_)
+        | RouteRequest::ReplaceRouteNoBroadcast(_)
+        | RouteRequest::ChangeRoute(_)
+        | RouteRequest::ChangeRouteNoBroadcast(_)
+        | RouteRequest::UpdateRoute { .. } => true,
+        _ => false,
+    }
+}
+
+/// Determines if the request navigates, rather than configuring the agent or reading from it.
+pub(crate) fn is_navigation<T>(request: &RouteRequest<T>) -> bool {
+    match request {
+        RouteRequest::GetCurrentRoute
+        | RouteRequest::SetNormalization(_)
+        | RouteRequest::SetIncludeHost(_)
+        | RouteRequest::SetStateBudget(_)
+        | RouteRequest::SetNavigationPolicy(_)
+        | RouteRequest::SetHistoryLimit(_) => false,
+        #[cfg(feature = "tab_sync")]
+        RouteRequest::SetTabSync(_) => false,
+        _ => true,
+    }
+}
+
+/// A request that a `NavigationBurst` can collapse with the requests that follow it.
+pub(crate) trait Collapsible {
+    /// Determines if the request can be collapsed with the one that was made after it.
+    fn collapses_with(&self, next: &Self) -> bool;
+
+    /// Collapses the request with the one that was made after it, into the request that is
+    /// handled instead of both.
+    fn collapse(self, next: Self) -> Self;
+}
+
+impl<T> Collapsible for RouteRequest<T> {
+    fn collapses_with(&self, next: &Self) -> bool {
+        mem::discriminant(self) == mem::discriminant(next)
+    }
+
+    fn collapse(self, next: Self) -> Self {
+        match (self, next) {
+            (
+                RouteRequest::UpdateRoute {
+                    path,
+                    query,
+                    fragment,
+                    state,
+                },
+                RouteRequest::UpdateRoute {
+                    path: next_path,
+                    query: next_query,
+                    fragment: next_fragment,
+                    state: next_state,
+                },
+            ) => RouteRequest::UpdateRoute {
+                path: next_path.or(path),
+                query: next_query.or(query),
+                fragment: next_fragment.or(fragment),
+                state: next_state.or(state),
+            },
+            (_, next) => next,
+        }
+    }
+}
+
+/// A request along with its sender, which is only collapsed with the requests of the same sender.
+impl<R: Collapsible, S: PartialEq> Collapsible for (R, S) {
+    fn collapses_with(&self, next: &Self) -> bool {
+        self.1 == next.1 && self.0.collapses_with(&next.0)
+    }
+
+    fn collapse(self, next: Self) -> Self {
+        (self.0.collapse(next.0), next.1)
+    }
+}
+
+/// What is done with a request that was given to a `NavigationBurst`.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Admission<R> {
+    /// The request is handled right away.
+    Now(R),
+    /// The request is handled once this many milliseconds have passed, instead of any request that
+    /// was scheduled before.
+    After(u32),
+    /// The request is handled when the request that was scheduled before would have been.
+    Queued,
+}
+
+/// Collapses the requests of a burst according to a `NavigationPolicy`.
+#[derive(Debug)]
+pub(crate) struct NavigationBurst<R> {
+    policy: NavigationPolicy,
+    /// The last request, which waits to be handled.
+    pending: Option<R>,
+    /// When the last request was handled, in milliseconds.
+    last_handled: Option<f64>,
+}
+
+impl<R> Default for NavigationBurst<R> {
+    fn default() -> Self {
+        NavigationBurst {
+            policy: NavigationPolicy::default(),
+            pending: None,
+            last_handled: None,
+        }
+    }
+}
+
+impl<R: Collapsible> NavigationBurst<R> {
+    /// Changes the policy, which applies to the requests that are made from now on.
+    pub(crate) fn set_policy(&mut self, policy: NavigationPolicy) {
+        self.policy = policy;
+    }
+
+    /// Takes the request that waits to be handled if it can't be collapsed with the request that
+    /// was made at `now`, so it is handled before it.
+    pub(crate) fn flush_before(&mut self, request: &R, now: f64) -> Option<R> {
+        let collapses = self
+            .pending
+            .as_ref()
+            .map_or(true, |pending| pending.collapses_with(request));
+        if collapses {
+            None
+        } else {
+            self.release(now)
+        }
+    }
+
+    /// Decides when a request that was made at `now` is handled.
+    ///
+    /// The request that waits to be handled must have been flushed with `flush_before` if it
+    /// can't be collapsed with this one.
+    pub(crate) fn admit(&mut self, request: R, now: f64) -> Admission<R> {
+        match self.policy {
+            NavigationPolicy::Immediate => Admission::Now(request),
+            NavigationPolicy::Debounce(interval) => {
+                self.wait(request);
+                Admission::After(interval)
+            }
+            NavigationPolicy::Throttle(interval) => {
+                let elapsed = self.last_handled.map(|last_handled| now - last_handled);
+                let is_due = elapsed.map_or(true, |elapsed| elapsed >= f64::from(interval));
+                if self.pending.is_none() && is_due {
+                    self.last_handled = Some(now);
+                    return Admission::Now(request);
+                }
+                let is_scheduled = self.pending.is_some();
+                self.wait(request);
+                if is_scheduled {
+                    Admission::Queued
+                } else {
+                    let remaining = f64::from(interval) - elapsed.unwrap_or(0.0);
+                    Admission::After(remaining.max(0.0) as u32)
+                }
+            }
+        }
+    }
+
+    /// Makes the request wait to be handled, collapsed with the one that is already waiting.
+    fn wait(&mut self, request: R) {
+        let request = match self.pending.take() {
+            Some(pending) => pending.collapse(request),
+            None => request,
+        };
+        self.pending = Some(request);
+    }
+
+    /// Takes the request that waits to be handled, once its time has come at `now`.
+    pub(crate) fn release(&mut self, now: f64) -> Option<R> {
+        let request = self.pending.take()?;
+        self.last_handled = Some(now);
+        Some(request)
+    }
+
+    /// Discards the request that waits to be handled, if there is one.
+    pub(crate) fn discard(&mut self) {
+        self.pending = None;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::route::Route;
+
+    fn change(route: &str) -> RouteRequest<()> {
+        RouteRequest::ChangeRoute(Route::from(route))
+    }
+
+    fn replace(route: &str) -> RouteRequest<()> {
+        RouteRequest::ReplaceRoute(Route::from(route))
+    }
+
+    fn update(query: Option<&str>, fragment: Option<&str>) -> RouteRequest<()> {
+        RouteRequest::UpdateRoute {
+            path: None,
+            query: query.map(str::to_string),
+            fragment: fragment.map(str::to_string),
+            state: None,
+        }
+    }
+
+    /// Describes a request, as requests can't be compared.
+    fn describe(request: RouteRequest<()>) -> String {
+        match request {
+            RouteRequest::ChangeRoute(route) => format!("change {}", route.route),
+            RouteRequest::ReplaceRoute(route) => format!("replace {}", route.route),
+            RouteRequest::UpdateRoute {
+                path,
+                query,
+                fragment,
+                ..
+            } => format!("update {:?} {:?} {:?}", path, query, fragment),
+            other => format!("{:?}", other),
+        }
+    }
+
+    fn described(admission: Admission<RouteRequest<()>>) -> Admission<String> {
+        match admission {
+            Admission::Now(request) => Admission::Now(describe(request)),
+            Admission::After(delay) => Admission::After(delay),
+            Admission::Queued => Admission::Queued,
+        }
+    }
+
+    /// Gives the request to the burst like the agent does, returning the request that was flushed
+    /// before it, if any, and what is done with it.
+    fn make(
+        burst: &mut NavigationBurst<RouteRequest<()>>,
+        request: RouteRequest<()>,
+        now: f64,
+    ) -> (Option<String>, Admission<String>) {
+        let flushed = burst.flush_before(&request, now).map(describe);
+        (flushed, described(burst.admit(request, now)))
+    }
+
+    #[test]
+    fn debounce_handles_the_last_request_of_a_burst() {
+        let mut burst = NavigationBurst::default();
+        burst.set_policy(NavigationPolicy::Debounce(100));
+        assert_eq!(
+            make(&mut burst, change("/a"), 0.0),
+            (None, Admission::After(100))
+        );
+        assert_eq!(
+            make(&mut burst, change("/b"), 50.0),
+            (None, Admission::After(100))
+        );
+        assert_eq!(
+            burst.release(150.0).map(describe),
+            Some("change /b".to_string())
+        );
+        assert!(burst.release(200.0).is_none());
+    }
+
+    #[test]
+    fn throttle_handles_one_request_per_interval() {
+        let mut burst = NavigationBurst::default();
+        burst.set_policy(NavigationPolicy::Throttle(100));
+        assert_eq!(
+            make(&mut burst, change("/a"), 0.0),
+            (None, Admission::Now("change /a".to_string()))
+        );
+        assert_eq!(
+            make(&mut burst, change("/b"), 30.0),
+            (None, Admission::After(70))
+        );
+        assert_eq!(
+            make(&mut burst, change("/c"), 60.0),
+            (None, Admission::Queued)
+        );
+        assert_eq!(
+            burst.release(100.0).map(describe),
+            Some("change /c".to_string())
+        );
+        assert_eq!(
+            make(&mut burst, change("/d"), 150.0),
+            (None, Admission::After(50))
+        );
+        burst.discard();
+        assert!(burst.release(200.0).is_none());
+        assert_eq!(
+            make(&mut burst, change("/e"), 400.0),
+            (None, Admission::Now("change /e".to_string()))
+        );
+    }
+
+    #[test]
+    fn requests_of_another_kind_flush_the_waiting_request() {
+        let mut burst = NavigationBurst::default();
+        burst.set_policy(NavigationPolicy::Debounce(100));
+        make(&mut burst, change("/a"), 0.0);
+        assert_eq!(
+            make(&mut burst, replace("/b"), 50.0),
+            (Some("change /a".to_string()), Admission::After(100))
+        );
+        assert_eq!(
+            burst.release(150.0).map(describe),
+            Some("replace /b".to_string())
+        );
+    }
+
+    #[test]
+    fn updates_of_different_parts_are_merged() {
+        let mut burst = NavigationBurst::default();
+        burst.set_policy(NavigationPolicy::Throttle(100));
+        make(&mut burst, update(Some("page=1"), None), 0.0);
+        make(&mut burst, update(Some("page=2"), None), 10.0);
+        assert_eq!(
+            make(&mut burst, update(None, Some("results")), 20.0),
+            (None, Admission::Queued)
+        );
+        assert_eq!(
+            burst.release(100.0).map(describe),
+            Some(r#"update None Some("page=2") Some("results")"#.to_string())
+        );
+    }
+
+    #[test]
+    fn requests_of_other_senders_are_not_collapsed() {
+        let first = (change("/a"), 1);
+        assert!(first.collapses_with(&(change("/b"), 1)));
+        assert!(!first.collapses_with(&(change("/b"), 2)));
+        assert!(!first.collapses_with(&(replace("/b"), 1)));
+        let (request, sender) = first.collapse((change("/b"), 1));
+        assert_eq!((describe(request), sender), ("change /b".to_string(), 1));
+    }
+}
@@ -0,0 +1,28 @@
+//! State that a `RouteAgent` shares with the handles to it, without going through messages.
+use std::{
+    any::{Any, TypeId},
+    cell::RefCell,
+    collections::HashMap,
+};
+
+thread_local! {
+    static SHARED: RefCell<HashMap<TypeId, Box<dyn Any>>> = RefCell::new(HashMap::new());
+}
+
+/// Gives access to the value of type `V` that belongs to the agent with the state `T` and the
+/// namespace `N`, creating it if it doesn't exist yet.
+///
+/// The value is borrowed while `f` runs, so `f` must not access it again.
+pub(crate) fn with_shared<T: 'static, N: 'static, V: Default + 'static, R>(
+    f: impl FnOnce(&mut V) -> R,
+) -> R {
+    SHARED.with(|shared| {
+        let mut shared = shared.borrow_mut();
+        let value = shared
+            .entry(TypeId::of::<(T, N, V)>())
+            .or_insert_with(|| Box::new(V::default()));
+        f(value
+            .downcast_mut()
+            .expect("Shared values are keyed by their type"))
+    })
+}
@@ -0,0 +1,74 @@
+//! Synchronization of the RouteAgent with an external state store.
+//!
+//! Applications that centralize their state in a Redux-style store can use this to mirror route
+//! changes into the store, and to have store actions request navigation.
+use crate::{
+    agent::{RouteAgentBridge, RouteRequest},
+    route::{Route, RouterState},
+};
+use std::fmt::{Debug, Error as FmtError, Formatter};
+use yew::Callback;
+
+/// An action belonging to a store that may request that the route be changed.
+///
+/// Implement this for your store's action type so that navigation requests can be recognized and
+/// forwarded to the `RouteAgent`.
+pub trait NavigationAction<T> {
+    /// Produces a request for the `RouteAgent` if this action represents a navigation.
+    ///
+    /// Actions that have nothing to do with routing should return `None`.
+    fn route_request(&self) -> Option<RouteRequest<T>>;
+}
+
+/// Keeps a store in sync with the route managed by the `RouteAgent`.
+///
+/// Every route change, whether it was initiated by the browser or by the app, is converted into a
+/// store action and sent to the provided dispatch callback.
+/// Store actions that request navigation can be handed to `apply` to change the route.
+///
+/// # Note
+/// Navigation requested through `apply` that broadcasts its change will be dispatched back to
+/// the store as well, so the store should treat route actions as the source of truth for the route.
+pub struct RouteStoreSync<T = ()>
+where
+    T: RouterState,
+{
+    bridge: RouteAgentBridge<T>,
+}
+
+impl<T> RouteStoreSync<T>
+where
+    T: RouterState,
+{
+    /// Connects to the `RouteAgent` and dispatches the current route, as well as every subsequent
+    /// route change to the store.
+    pub fn new<A>(dispatch: Callback<A>) -> Self
+    where
+        A: From<Route<T>> + 'static,
+    {
+        let callback = Callback::from(move |route: Route<T>| dispatch.emit(A::from(route)));
+        let mut bridge = RouteAgentBridge::new(callback);
+        bridge.send(RouteRequest::GetCurrentRoute);
+        RouteStoreSync { bridge }
+    }
+
+    /// Forwards the action to the `RouteAgent` if it requests navigation.
+    ///
+    /// Returns true if the action resulted in a request being sent.
+    pub fn apply<A: NavigationAction<T>>(&mut self, action: &A) -> bool {
+        if let Some(request) = action.route_request() {
+            self.bridge.send(request);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl<T: RouterState> Debug for RouteStoreSync<T> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        f.debug_struct("RouteStoreSync")
+            .field("bridge", &self.bridge)
+            .finish()
+    }
+}
@@ -0,0 +1,160 @@
+//! Shares the navigations of a `RouteAgent` with the other tabs of the app.
+//!
+//! Navigations are written to `localStorage`, which fires a `storage` event in every other tab of
+//! the same origin.
+
+use stdweb::{unstable::TryInto, Value};
+
+use crate::route::{Route, RouterState};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Debug, Error as FmtError, Formatter};
+use yew::Callback;
+
+/// The key that navigations are shared under, unless another one is given.
+const DEFAULT_KEY: &str = "yew_router_tab_sync";
+
+/// What the other tabs of the app do when one of them navigates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TabSyncMode {
+    /// The other tabs navigate to the same route, with a new history entry.
+    Follow,
+    /// The other tabs stay on their route, but send it to their connected components again, so
+    /// views that show data which the navigation may have changed can reload it.
+    Notify,
+}
+
+/// Shares the navigations of a `RouteAgent` with the `RouteAgent`s of the other tabs of the app,
+/// which are given one too through `RouteRequest::SetTabSync`.
+///
+/// Every navigation of the agent, except for ones that came from another tab, is stored in
+/// `localStorage`, and the agents of the other tabs are told about it by the browser.
+/// They handle it according to their [`TabSyncMode`](enum.TabSyncMode.html), and report it to
+/// `NavigationListener`s as a `NavigationKind::External` navigation if they follow it.
+///
+/// Only tabs of the same origin that use the same key share their navigations.
+///
+/// # Example
+/// ```
+/// use yew_router::{
+///     agent::{RouteAgentDispatcher, RouteRequest, TabSync, TabSyncMode},
+///     prelude::*,
+/// };
+///
+/// # fn dont_execute() {
+/// let mut dispatcher = RouteAgentDispatcher::<()>::new();
+/// dispatcher.send(RouteRequest::SetTabSync(Some(
+///     TabSync::new(TabSyncMode::Follow).with_key("inbox"),
+/// )));
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TabSync {
+    key: String,
+    mode: TabSyncMode,
+}
+
+impl TabSync {
+    /// Creates a tab sync that handles the navigations of other tabs according to `mode`.
+    pub fn new(mode: TabSyncMode) -> Self {
+        TabSync {
+            key: DEFAULT_KEY.to_string(),
+            mode,
+        }
+    }
+
+    /// Sets the key that navigations are shared under, so apps sharing an origin, or agents with
+    /// different namespaces, don't follow each other.
+    pub fn with_key(self, key: &str) -> Self {
+        TabSync {
+            key: key.to_string(),
+            ..self
+        }
+    }
+
+    /// What is done when another tab navigates.
+    pub fn mode(&self) -> TabSyncMode {
+        self.mode
+    }
+
+    /// Tells the other tabs that this one navigated to the route.
+    pub(crate) fn broadcast<T: RouterState>(&self, route: &Route<T>) {
+        let route_string = &route.route;
+        let state = route.state.clone().unwrap_or_default();
+        // The time is included, so navigating to the same route again still changes the stored
+        // value, which is what makes the browser fire the event.
+        let shared: bool = js! {
+            try {
+                var navigation = JSON.stringify({
+                    route: @{route_string},
+                    state: @{state},
+                    sentAt: Date.now()
+                });
+                window.localStorage.setItem(@{&self.key}, navigation);
+                return true;
+            } catch (error) {
+                return false;
+            }
+        }
+        .try_into()
+        .unwrap_or(false);
+        if !shared {
+            log::warn!("The navigation couldn't be shared under '{}'", self.key);
+        }
+    }
+
+    /// Calls the callback with the routes that the other tabs navigate to, until the returned
+    /// listener is dropped.
+    pub(crate) fn listen<T: RouterState>(&self, callback: Callback<Route<T>>) -> TabSyncListener {
+        let on_navigation = move |navigation: String| {
+            let navigation: Value = js! {
+                try {
+                    var navigation = JSON.parse(@{navigation});
+                    return navigation && typeof navigation.route === "string" ? navigation : null;
+                } catch (error) {
+                    return null;
+                }
+            };
+            let route: String = match js! { return @{&navigation}.route; }.try_into() {
+                Ok(route) => route,
+                Err(_) => return,
+            };
+            let state: Value = js! { return @{&navigation}.state; };
+            callback.emit(Route {
+                route,
+                state: Some(T::try_from(state).unwrap_or_default()),
+            })
+        };
+        let listener = js! {
+            var key = @{&self.key};
+            var on_navigation = @{on_navigation};
+            var listener = function(event) {
+                if (event.key === key && event.newValue !== null) {
+                    on_navigation(event.newValue);
+                }
+            };
+            listener.on_navigation = on_navigation;
+            window.addEventListener("storage", listener);
+            return listener;
+        };
+        TabSyncListener(listener)
+    }
+}
+
+/// Listens to the navigations of the other tabs, until it is dropped.
+pub(crate) struct TabSyncListener(Value);
+
+impl Drop for TabSyncListener {
+    fn drop(&mut self) {
+        js! { @(no_return)
+            var listener = @{&self.0};
+            window.removeEventListener("storage", listener);
+            listener.on_navigation.drop();
+        }
+    }
+}
+
+impl Debug for TabSyncListener {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        f.debug_struct("TabSyncListener").finish()
+    }
+}
@@ -32,6 +32,7 @@ macro_rules! define_router_state {
             #[doc = ">](route/struct.Route.html)."]
             pub type Route = $crate::route::Route<$StateT>;
 
+            #[cfg(feature="service")]
             #[doc = "Alias to [RouteService<"]
             #[doc = $StateName]
             #[doc = ">](route_service/struct.RouteService.html)."]
@@ -49,12 +50,30 @@ macro_rules! define_router_state {
             #[doc = ">](agent/bridge/struct.RouteAgentBridge.html)`."]
             pub type RouteAgentBridge = $crate::agent::RouteAgentBridge<$StateT>;
 
+            #[cfg(feature="agent")]
+            #[doc = "Alias to [SwitchAgentBridge<SW, "]
+            #[doc = $StateName]
+            #[doc = ">](agent/struct.SwitchAgentBridge.html)`."]
+            pub type SwitchAgentBridge<SW> = $crate::agent::SwitchAgentBridge<SW, $StateT>;
+
             #[cfg(feature="agent")]
             #[doc = "Alias to [RouteAgentDispatcher<"]
             #[doc = $StateName]
             #[doc = ">](agent/struct.RouteAgentDispatcher.html)`."]
             pub type RouteAgentDispatcher = $crate::agent::RouteAgentDispatcher<$StateT>;
 
+            #[cfg(feature="agent")]
+            #[doc = "Alias to [NavigationListener<"]
+            #[doc = $StateName]
+            #[doc = ">](agent/struct.NavigationListener.html)`."]
+            pub type NavigationListener = $crate::agent::NavigationListener<$StateT>;
+
+            #[cfg(feature="store")]
+            #[doc = "Alias to [RouteStoreSync<"]
+            #[doc = $StateName]
+            #[doc = ">](agent/struct.RouteStoreSync.html)`."]
+            pub type RouteStoreSync = $crate::agent::RouteStoreSync<$StateT>;
+
             #[cfg(feature="components")]
             #[doc = "Alias to [RouterLink<"]
             #[doc = $StateName]
@@ -67,6 +86,12 @@ macro_rules! define_router_state {
             #[doc = ">](components/struct.RouterButton.html)`."]
             pub type RouterButton = $crate::components::RouterButton<$StateT>;
 
+            #[cfg(feature="components")]
+            #[doc = "Alias to [Breadcrumbs<"]
+            #[doc = $StateName]
+            #[doc = ">](components/struct.Breadcrumbs.html)`."]
+            pub type Breadcrumbs<SW> = $crate::components::Breadcrumbs<$StateT, SW>;
+
             #[cfg(feature="router")]
             #[doc = "Alias to [Router<"]
             #[doc = $StateName]
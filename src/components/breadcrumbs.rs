@@ -0,0 +1,220 @@
+//! A component that renders a breadcrumb trail for the current route.
+use crate::{
+    agent::{DefaultNamespace, RouteAgentBridge, RouteNamespace, RouteRequest},
+    components::RouterLink,
+    route::Route,
+    switch::{breadcrumbs, Switch},
+};
+use std::marker::PhantomData;
+use yew::prelude::*;
+
+use crate::route::RouterState;
+
+/// A navigation Component that links to each ancestor of the current route that has a title.
+///
+/// The trail is built by [`breadcrumbs`](../switch/fn.breadcrumbs.html), from the `title`s given
+/// to the routes of `SW` with `#[meta(title = "...")]`.
+/// The last crumb is the current page, which is marked with `aria-current="page"`.
+///
+/// # Example
+/// ```
+/// use yew::prelude::*;
+/// use yew_router::{components::Breadcrumbs, Switch};
+///
+/// #[derive(Switch)]
+/// enum AppRoute {
+///     #[to = "/settings"]
+///     #[meta(title = "Settings")]
+///     Settings,
+///     #[to = "/"]
+///     #[meta(title = "Home")]
+///     Home,
+/// }
+///
+/// # pub struct Model {}
+/// # impl Component for Model {
+/// #   type Message = ();
+/// #   type Properties = ();
+/// #   fn create(_: Self::Properties, _link: ComponentLink<Self>) -> Self {
+/// #       Model {}
+/// #   }
+/// #   fn update(&mut self, msg: Self::Message) -> ShouldRender {
+/// #        false
+/// #   }
+/// # }
+/// impl Renderable<Model> for Model {
+///     fn view(&self) -> Html<Self> {
+///         html! {
+///             <Breadcrumbs<(), AppRoute> classes="breadcrumbs" />
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Breadcrumbs<T: RouterState, SW: Switch + 'static, N: RouteNamespace = DefaultNamespace> {
+    route: Route<T>,
+    props: BreadcrumbsProps,
+    router_agent: RouteAgentBridge<T, N>,
+    phantom_data: PhantomData<SW>,
+}
+
+/// Properties for `Breadcrumbs`.
+#[derive(Properties, Default, Clone, Debug, PartialEq)]
+pub struct BreadcrumbsProps {
+    /// Classes to be added to the `<nav>` element.
+    pub classes: String,
+}
+
+impl<T, SW, N> Component for Breadcrumbs<T, SW, N>
+where
+    T: RouterState,
+    SW: Switch + 'static,
+    N: RouteNamespace,
+{
+    type Message = Route<T>;
+    type Properties = BreadcrumbsProps;
+
+    fn create(props: Self::Properties, mut link: ComponentLink<Self>) -> Self {
+        let callback = link.send_back(|route: Route<T>| route);
+        Breadcrumbs {
+            route: Default::default(),
+            props,
+            router_agent: RouteAgentBridge::new(callback),
+            phantom_data: PhantomData,
+        }
+    }
+
+    fn mounted(&mut self) -> ShouldRender {
+        self.router_agent.send(RouteRequest::GetCurrentRoute);
+        false
+    }
+
+    fn update(&mut self, route: Self::Message) -> ShouldRender {
+        let did_change = self.route != route;
+        self.route = route;
+        did_change
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props = props;
+        true
+    }
+}
+
+impl<T, SW, N> Renderable<Breadcrumbs<T, SW, N>> for Breadcrumbs<T, SW, N>
+where
+    T: RouterState,
+    SW: Switch + 'static,
+    N: RouteNamespace,
+{
+    fn view(&self) -> Html<Self> {
+        let crumbs = crumbs::<SW, T>(&self.route.route)
+            .into_iter()
+            .map(|(title, route, aria)| {
+                html! {
+                    <li>
+                        <RouterLink<T, N> link=route.route, text=title.to_string(), aria=aria, />
+                    </li>
+                }
+            });
+
+        html! {
+            <nav class=self.props.classes.clone(), aria-label="Breadcrumb",>
+                <ol>
+                    {for crumbs}
+                </ol>
+            </nav>
+        }
+    }
+}
+
+/// Gets the crumbs of the trail for a route, along with the aria attributes of their links, which
+/// mark the last one as the current page.
+fn crumbs<SW: Switch, T>(route: &str) -> Vec<(&'static str, Route<T>, Vec<(String, String)>)> {
+    let trail = breadcrumbs::<SW, T>(route);
+    let current = trail.len().saturating_sub(1);
+    trail
+        .into_iter()
+        .enumerate()
+        .map(|(index, (title, route))| {
+            let aria = if index == current {
+                vec![("current".to_string(), "page".to_string())]
+            } else {
+                Vec::new()
+            };
+            (title, route, aria)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{route::RouteState, switch::RouteMeta};
+
+    /// `/settings{*}` with a nested `/profile`, and `/`, like a derived `Switch` would switch them.
+    enum AppRoute {
+        SettingsProfile,
+        Home,
+    }
+
+    impl Switch for AppRoute {
+        fn from_route_part<T: RouteState>(part: Route<T>) -> (Option<Self>, Option<T>) {
+            let switched = match part.route.as_str() {
+                "/settings/profile" => Some(AppRoute::SettingsProfile),
+                "/" => Some(AppRoute::Home),
+                _ => None,
+            };
+            (switched, part.state)
+        }
+
+        fn build_route_section<T>(self, _route: &mut String) -> Option<T> {
+            None
+        }
+
+        fn nested_meta(&self) -> Vec<RouteMeta> {
+            match self {
+                AppRoute::SettingsProfile => vec![
+                    RouteMeta::new(&[("title", Some("Settings"))]),
+                    RouteMeta::new(&[("title", Some("Profile"))]),
+                ],
+                AppRoute::Home => vec![RouteMeta::new(&[("title", Some("Home"))])],
+            }
+        }
+
+        fn nested_route(self) -> Option<String> {
+            match self {
+                AppRoute::SettingsProfile => Some("/profile".to_string()),
+                AppRoute::Home => None,
+            }
+        }
+    }
+
+    #[test]
+    fn every_ancestor_gets_a_crumb_and_the_last_is_current() {
+        let crumbs = crumbs::<AppRoute, ()>("/settings/profile");
+        let crumbs: Vec<_> = crumbs
+            .iter()
+            .map(|(title, route, aria)| (*title, route.route.as_str(), aria.len()))
+            .collect();
+        assert_eq!(
+            crumbs,
+            vec![
+                ("Home", "/", 0),
+                ("Settings", "/settings", 0),
+                ("Profile", "/settings/profile", 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn routes_that_dont_switch_have_no_crumbs() {
+        let crumbs = crumbs::<AppRoute, ()>("/about");
+        assert_eq!(crumbs.len(), 1);
+        assert_eq!(crumbs[0].0, "Home");
+        assert_eq!(
+            crumbs[0].2,
+            vec![("current".to_string(), "page".to_string())]
+        );
+    }
+}
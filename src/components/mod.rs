@@ -3,17 +3,25 @@
 //! At least one bridge to the agent needs to exist for these to work.
 //! This can be done transitively by using a `Router` component, which owns a bridge to the agent.
 
+mod breadcrumbs;
 mod router_button;
 mod router_link;
 
-use yew::Properties;
+use yew::{virtual_dom::VNode, Component, Html, Properties};
 
-pub use self::{router_button::RouterButton, router_link::RouterLink};
-use crate::RouterState;
+pub use self::{
+    breadcrumbs::{Breadcrumbs, BreadcrumbsProps},
+    router_button::RouterButton,
+    router_link::RouterLink,
+};
+use crate::{
+    agent::RouteRequest,
+    route::{Route, RouterState},
+};
 
 /// Properties for `RouterButton` and `RouterLink`.
 #[derive(Properties, Default, Clone, Debug, PartialEq)]
-pub struct Props<T: for<'de> RouterState<'de>> {
+pub struct Props<T: RouterState = ()> {
     /// The route that will be set when the component is clicked.
     pub link: String,
     /// The state to set when changing the route.
@@ -21,9 +29,62 @@ pub struct Props<T: for<'de> RouterState<'de>> {
     /// The text to display.
     pub text: String,
     /// Disable the component.
+    ///
+    /// Disabled components don't change the route when clicked, and are marked with
+    /// `aria-disabled`.
     pub disabled: bool,
+    /// Navigate even if the route is already current, so it is rendered again.
+    pub force: bool,
     /// Classes to be added to component.
     pub classes: String,
+    /// The id of the rendered element.
+    pub id: Option<String>,
+    /// `aria-*` attributes to add to the rendered element.
+    ///
+    /// The names may be given with or without the `aria-` prefix, so `("current", "page")` is
+    /// rendered as `aria-current="page"`.
+    pub aria: Vec<(String, String)>,
+}
+
+impl<T: RouterState> Props<T> {
+    /// Gets the request that the component sends when it is clicked, or `None` if it is disabled.
+    pub(crate) fn click_request(&self) -> Option<RouteRequest<T>> {
+        if self.disabled {
+            return None;
+        }
+        let route = Route {
+            route: self.link.clone(),
+            state: self.state.clone(),
+        };
+        if self.force {
+            Some(RouteRequest::ForceChangeRoute(route))
+        } else {
+            Some(RouteRequest::ChangeRoute(route))
+        }
+    }
+}
+
+/// Adds the attributes from the props that can't be set statically in `html!` to the element.
+fn add_pass_through_attributes<T, COMP>(node: &mut Html<COMP>, props: &Props<T>)
+where
+    T: RouterState,
+    COMP: Component,
+{
+    if let VNode::VTag(tag) = node {
+        if let Some(id) = &props.id {
+            tag.add_attribute("id", id);
+        }
+        for (name, value) in &props.aria {
+            if name.starts_with("aria-") {
+                tag.add_attribute(name, value);
+            } else {
+                tag.add_attribute(&format!("aria-{}", name), value);
+            }
+        }
+        if props.disabled {
+            tag.add_attribute("aria-disabled", &"true");
+        }
+    }
 }
 
 /// Message for `RouterButton` and `RouterLink`.
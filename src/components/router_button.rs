@@ -1,21 +1,21 @@
 //! A component wrapping a `<button>` tag that changes the route.
-use crate::{
-    agent::{RouteAgentDispatcher, RouteRequest},
-    route::Route,
-};
+use crate::agent::{DefaultNamespace, RouteAgentDispatcher, RouteNamespace};
 use yew::prelude::*;
 
-use super::{Msg, Props};
-use crate::RouterState;
+use super::{add_pass_through_attributes, Msg, Props};
+use crate::route::RouterState;
 
 /// Changes the route when clicked.
+///
+/// The route is changed through the `RouteAgent` of the
+/// [namespace](../agent/trait.RouteNamespace.html) `N`.
 #[derive(Debug)]
-pub struct RouterButton<T: for<'de> RouterState<'de>> {
-    router: RouteAgentDispatcher<T>,
+pub struct RouterButton<T: RouterState = (), N: RouteNamespace = DefaultNamespace> {
+    router: RouteAgentDispatcher<T, N>,
     props: Props<T>,
 }
 
-impl<T: for<'de> RouterState<'de>> Component for RouterButton<T> {
+impl<T: RouterState, N: RouteNamespace> Component for RouterButton<T, N> {
     type Message = Msg;
     type Properties = Props<T>;
 
@@ -27,11 +27,9 @@ impl<T: for<'de> RouterState<'de>> Component for RouterButton<T> {
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match msg {
             Msg::Clicked => {
-                let route = Route {
-                    route: self.props.link.clone(),
-                    state: self.props.state.clone(),
-                };
-                self.router.send(RouteRequest::ChangeRoute(route));
+                if let Some(request) = self.props.click_request() {
+                    self.router.send(request);
+                }
                 false
             }
         }
@@ -43,9 +41,9 @@ impl<T: for<'de> RouterState<'de>> Component for RouterButton<T> {
     }
 }
 
-impl<T: for<'de> RouterState<'de>> Renderable<RouterButton<T>> for RouterButton<T> {
-    fn view(&self) -> Html<RouterButton<T>> {
-        html! {
+impl<T: RouterState, N: RouteNamespace> Renderable<RouterButton<T, N>> for RouterButton<T, N> {
+    fn view(&self) -> Html<RouterButton<T, N>> {
+        let mut button = html! {
             <button
                 class=self.props.classes.clone(),
                 onclick=|_| Msg::Clicked,
@@ -53,6 +51,8 @@ impl<T: for<'de> RouterState<'de>> Renderable<RouterButton<T>> for RouterButton<
             >
                 {&self.props.text}
             </button>
-        }
+        };
+        add_pass_through_attributes(&mut button, &self.props);
+        button
     }
 }
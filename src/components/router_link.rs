@@ -1,21 +1,21 @@
 //! A component wrapping an `<a>` tag that changes the route.
-use crate::{
-    agent::{RouteAgentDispatcher, RouteRequest},
-    route::Route,
-};
+use crate::agent::{DefaultNamespace, RouteAgentDispatcher, RouteNamespace};
 use yew::prelude::*;
 
-use super::{Msg, Props};
-use crate::RouterState;
+use super::{add_pass_through_attributes, Msg, Props};
+use crate::route::RouterState;
 
 /// An anchor tag Component that when clicked, will navigate to the provided route.
+///
+/// The route is changed through the `RouteAgent` of the
+/// [namespace](../agent/trait.RouteNamespace.html) `N`.
 #[derive(Debug)]
-pub struct RouterLink<T: for<'de> RouterState<'de>> {
-    router: RouteAgentDispatcher<T>,
+pub struct RouterLink<T: RouterState = (), N: RouteNamespace = DefaultNamespace> {
+    router: RouteAgentDispatcher<T, N>,
     props: Props<T>,
 }
 
-impl<T: for<'de> RouterState<'de>> Component for RouterLink<T> {
+impl<T: RouterState, N: RouteNamespace> Component for RouterLink<T, N> {
     type Message = Msg;
     type Properties = Props<T>;
 
@@ -27,11 +27,9 @@ impl<T: for<'de> RouterState<'de>> Component for RouterLink<T> {
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match msg {
             Msg::Clicked => {
-                let route = Route {
-                    route: self.props.link.clone(),
-                    state: self.props.state.clone(),
-                };
-                self.router.send(RouteRequest::ChangeRoute(route));
+                if let Some(request) = self.props.click_request() {
+                    self.router.send(request);
+                }
                 false
             }
         }
@@ -43,12 +41,16 @@ impl<T: for<'de> RouterState<'de>> Component for RouterLink<T> {
     }
 }
 
-impl<T: for<'de> RouterState<'de>> Renderable<RouterLink<T>> for RouterLink<T> {
+impl<T: RouterState, N: RouteNamespace> Renderable<RouterLink<T, N>> for RouterLink<T, N> {
     fn view(&self) -> Html<Self> {
         use stdweb::web::event::IEvent;
-        let target: &str = &self.props.link;
+        let codec = N::codec();
+        let route =
+            crate::service::encode_route(codec.as_ref().map(|codec| &**codec), &self.props.link);
+        let href = crate::strategy::url_for_current_location(&*N::strategy(), &route);
+        let target: &str = &href;
 
-        html! {
+        let mut link = html! {
             <a
                 class=self.props.classes.clone(),
                 onclick=|event | {
@@ -60,6 +62,8 @@ impl<T: for<'de> RouterState<'de>> Renderable<RouterLink<T>> for RouterLink<T> {
             >
                 {&self.props.text}
             </a>
-        }
+        };
+        add_pass_through_attributes(&mut link, &self.props);
+        link
     }
 }
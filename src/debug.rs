@@ -0,0 +1,198 @@
+//! Instrumentation for figuring out why a route does or doesn't match.
+//!
+//! When the "debug" feature is enabled, every navigation, every variant that is tried while
+//! switching, why its matcher rejected the route, and how long switching took, are logged at the
+//! debug level using the `log` crate.
+//! Without the feature, these functions do nothing.
+#![cfg_attr(not(feature = "debug"), allow(unused_variables))]
+use nom::IResult;
+
+/// Logs a navigation that was requested by the app or performed by the browser.
+pub fn log_navigation(kind: &str, route: &str) {
+    #[cfg(feature = "debug")]
+    log::debug!("Navigation ({}): '{}'", kind, route);
+}
+
+/// Logs the result of matching a route using the matcher of a struct or enum variant.
+///
+/// This is called by the code generated by the `Switch` derive.
+#[doc(hidden)]
+pub fn log_match_attempt<O>(item: &str, matcher: &str, route: &str, result: &IResult<&str, O>) {
+    #[cfg(feature = "debug")]
+    match result {
+        Ok((rest, _)) if rest.is_empty() => {
+            log::debug!("`{}` (\"{}\") matched '{}'", item, matcher, route)
+        }
+        Ok((rest, _)) => log::debug!(
+            "`{}` (\"{}\") matched '{}', ignoring '{}'",
+            item,
+            matcher,
+            route,
+            rest
+        ),
+        Err(nom::Err::Error((rest, kind))) | Err(nom::Err::Failure((rest, kind))) => log::debug!(
+            "`{}` (\"{}\") rejected '{}' at position {}: {}",
+            item,
+            matcher,
+            route,
+            route.len() - rest.len(),
+            kind.description()
+        ),
+        Err(nom::Err::Incomplete(_)) => log::debug!(
+            "`{}` (\"{}\") rejected '{}': the route ended too early",
+            item,
+            matcher,
+            route
+        ),
+    }
+}
+
+/// Logs that a route matched, but a field couldn't be created from what was captured.
+///
+/// This is called by the code generated by the `Switch` derive.
+#[doc(hidden)]
+pub fn log_field_rejected(item: &str, field: &str) {
+    #[cfg(feature = "debug")]
+    log::debug!(
+        "`{}` matched, but its field `{}` couldn't be created from the route",
+        item,
+        field
+    );
+}
+
+/// Measures how long switching a route takes.
+#[derive(Debug)]
+pub(crate) struct SwitchTimer {
+    #[cfg(feature = "debug")]
+    route: String,
+    #[cfg(feature = "debug")]
+    start: f64,
+}
+
+impl SwitchTimer {
+    /// Starts timing an attempt to switch the route.
+    pub(crate) fn start(route: &str) -> Self {
+        SwitchTimer {
+            #[cfg(feature = "debug")]
+            route: route.to_string(),
+            #[cfg(feature = "debug")]
+            start: now(),
+        }
+    }
+
+    /// Logs the outcome and how long it took.
+    pub(crate) fn finish(self, matched: bool) {
+        #[cfg(feature = "debug")]
+        {
+            let outcome = if matched { "matched" } else { "did not match" };
+            log::debug!(
+                "Switching '{}' {} after {:.3}ms",
+                self.route,
+                outcome,
+                now() - self.start
+            );
+        }
+    }
+}
+
+/// The current time in milliseconds.
+#[cfg(all(feature = "debug", feature = "service", target_arch = "wasm32"))]
+fn now() -> f64 {
+    stdweb::web::Date::now()
+}
+
+/// The current time isn't known on wasm32 without the browser APIs, so durations are logged as 0.
+#[cfg(all(feature = "debug", not(feature = "service"), target_arch = "wasm32"))]
+fn now() -> f64 {
+    0.0
+}
+
+/// The current time in milliseconds.
+#[cfg(all(feature = "debug", not(target_arch = "wasm32")))]
+fn now() -> f64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as f64 * 1000.0 + f64::from(d.subsec_nanos()) / 1_000_000.0)
+        .unwrap_or_default()
+}
+
+#[cfg(all(test, feature = "debug"))]
+mod test {
+    use super::*;
+    use log::{LevelFilter, Log, Metadata, Record};
+    use nom::error::ErrorKind;
+    use std::cell::RefCell;
+
+    thread_local! {
+        static LOGGED: RefCell<Vec<String>> = RefCell::new(Vec::new());
+    }
+
+    /// Keeps the messages logged by the thread of each test.
+    struct CaptureLogger;
+
+    impl Log for CaptureLogger {
+        fn enabled(&self, _metadata: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            LOGGED.with(|logged| logged.borrow_mut().push(record.args().to_string()));
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: CaptureLogger = CaptureLogger;
+
+    /// Takes the messages logged while `f` ran.
+    fn logged(f: impl FnOnce()) -> Vec<String> {
+        // Only the first test sets the logger.
+        let _ = log::set_logger(&LOGGER);
+        log::set_max_level(LevelFilter::Debug);
+        LOGGED.with(|logged| logged.borrow_mut().clear());
+        f();
+        LOGGED.with(|logged| logged.replace(Vec::new()))
+    }
+
+    #[test]
+    fn navigations_are_logged() {
+        assert_eq!(
+            logged(|| log_navigation("ChangeRoute", "/users")),
+            vec!["Navigation (ChangeRoute): '/users'"]
+        );
+    }
+
+    #[test]
+    fn match_attempts_are_logged_with_the_position_of_the_rejection() {
+        let matched: IResult<&str, ()> = Ok(("", ()));
+        let partly_matched: IResult<&str, ()> = Ok(("/edit", ()));
+        let rejected: IResult<&str, ()> = Err(nom::Err::Error(("42", ErrorKind::Tag)));
+        let messages = logged(|| {
+            log_match_attempt("User", "/users/{id}", "/users/42", &matched);
+            log_match_attempt("User", "/users/{id}", "/users/42/edit", &partly_matched);
+            log_match_attempt("Post", "/posts/{id}", "/users/42", &rejected);
+        });
+        assert_eq!(messages[0], "`User` (\"/users/{id}\") matched '/users/42'");
+        assert_eq!(
+            messages[1],
+            "`User` (\"/users/{id}\") matched '/users/42/edit', ignoring '/edit'"
+        );
+        assert!(
+            messages[2].starts_with("`Post` (\"/posts/{id}\") rejected '/users/42' at position 7")
+        );
+    }
+
+    #[test]
+    fn rejected_fields_and_switch_outcomes_are_logged() {
+        let messages = logged(|| {
+            log_field_rejected("User", "id");
+            SwitchTimer::start("/users/x").finish(false);
+        });
+        assert_eq!(
+            messages[0],
+            "`User` matched, but its field `id` couldn't be created from the route"
+        );
+        assert!(messages[1].starts_with("Switching '/users/x' did not match after "));
+    }
+}
@@ -7,6 +7,8 @@
 //! Among them are:
 //! * RouteService - Hooks into the History API and listens to `PopStateEvent`s to respond to users
 //!   clicking the back/forwards buttons.
+//! * RoutingStrategy - Determines whether the route is kept in the path, fragment, or a query
+//!   parameter of the url, for hosts that can't serve the app from every path.
 //! * RouteAgent - A singleton agent that owns a RouteService that provides an easy place for other
 //!   components and agents to hook into it.
 //! * Switch - A trait/derive macro that allows specification of how enums or structs can be constructed
@@ -16,12 +18,27 @@
 //! * Route - A struct containing an the route string and state.
 //! * RouteButton & RouteLink - Wrapper components around buttons and anchor tags respectively that
 //!   allow users to change the route.
+//! * Breadcrumbs - A component that links to the ancestors of the current route, using the titles
+//!   attached to Switch variants with `#[meta(title = "...")]`.
 //!
 //! ## State and Aliases
 //! Because the History API allows you to store data along with a route string,
 //! most types have at type parameter that allows you to specify which type is being stored.
 //! As this behavior is uncommon, aliases using the unit type (`()`) are provided to remove the
 //! need to specify the storage type you likely aren't using.
+//! Types whose state parameter isn't followed by one without a default, like `RouteAgent` and
+//! `RouterLink`, also default their state to `()`.
+//! The state stays the first parameter of `Router`, `Outlet`, and `Breadcrumbs`, like
+//! `Router<(), AppRoute, Msg>`, so it has no default there, and the aliases are the way to leave
+//! it out.
+//!
+//! Every type is bound on the same [`RouterState`](route/trait.RouterState.html) trait, which
+//! any state that can be stored along with routes implements, so signatures that are generic over
+//! the state only need `T: RouterState`.
+//! The methods of `Switch` only require the fewer bounds of
+//! [`RouteState`](route/trait.RouteState.html), which every `RouterState` meets.
+//! Bounds that were written as `for<'de> T: RouterState<'de>` become `T: RouterState`, and
+//! `AgentState` is kept as a deprecated alias of `RouterState`.
 //!
 //! If you want to store state using the history API, it is recommended that you generate your own
 //! aliases using the `define_router_state` macro.
@@ -29,17 +46,47 @@
 //! routing. If you specify your own router_state aliases and functions, you will want to disable
 //! the `unit_alias` feature to prevent the default `()` aliases from showing up in the prelude.
 //!
+//! ## API surfaces
+//! The crate is split into surfaces that build on each other, each behind a feature:
+//! * Matching - `matcher`, `route`, `switch`, `strategy`, `manifest`, and `sitemap`, which match
+//!   routes and build them, and are always included.
+//!   These don't depend on yew or stdweb, so servers can match the routes of an app, or render its
+//!   sitemap, with `default-features = false`.
+//! * Derive - The `Switch` derive macro and the `route!` macro, with the "derive" feature.
+//! * Browser services - The `RouteService`, which reads and changes the browser's url, with the
+//!   "service" feature, which brings in yew and stdweb.
+//! * Agents - The `RouteAgent` and the types that talk to it, with the "agent" feature.
+//! * Yew components - The `Router` and the accessory components, with the "router" and
+//!   "components" features.
+//!
 //! ## Features
 //! This crate has some feature-flags that allow you to not include some parts in your compilation.
 //! * "default" - Everything is included by default.
-//! * "core" - The fully feature complete ("router", "components", "matchers"), but without
+//! * "core" - The fully feature complete ("router", "components", "derive"), but without
 //!   unit_alias.
 //! * "unit_alias" - If enabled, a module will be added to the route and expanded within the prelude
 //! for aliases of Router types to their `()` variants.
 //! * "router" - If enabled, the Router component and its dependent infrastructure (including
 //!   "agent") will be included.
-//! * "agent" - If enabled, the RouteAgent and its associated types will be included.
+//! * "agent" - If enabled, the RouteAgent and its associated types (including "service") will be
+//!   included.
+//! * "service" - If enabled, the RouteService, and everything else that needs yew and stdweb to
+//!   talk to the browser, will be included.
+//! * "derive" - If enabled, the `Switch` derive macro and the `route!` macro will be included.
 //! * "components" - If enabled, the accessory components will be made available.
+//! * "store" - If enabled, `RouteStoreSync` will be made available for keeping an external state
+//!   store in sync with the RouteAgent. This is not included in "core".
+//! * "tab_sync" - If enabled, `TabSync` will be made available for sharing navigations between the
+//!   tabs of an app. This is not included in "core".
+//! * "testing" - If enabled, `MockHistory` will be made available for testing components and
+//!   agents that route without a browser. This is not included in "core".
+//! * "debug" - If enabled, navigations and the reasons why routes did or didn't match will be
+//!   logged using the `log` crate. This is not included in "core".
+//! * "url" - If enabled, Routes can be created from, and matchers can match against, `url::Url`s.
+//!   This is not included in "core".
+//! * "unicode-normalization" - If enabled, `Normalization` can normalize routes to Unicode NFC,
+//!   so they match matchers regardless of how their characters are composed. This is not included
+//!   in "core".
 
 #![deny(
     missing_docs,
@@ -55,11 +102,17 @@
 // It can be dealt with at the same time.
 #![allow(macro_expanded_macro_exports_accessed_by_absolute_paths)]
 
+#[cfg(feature = "service")]
+#[macro_use]
+extern crate stdweb;
+
 pub use yew_router_route_parser;
 
 #[macro_use]
 mod alias;
+#[cfg(feature = "service")]
 pub mod service;
+pub mod strategy;
 
 #[cfg(feature = "agent")]
 pub mod agent;
@@ -72,6 +125,9 @@ pub mod components;
 #[cfg(feature = "router")]
 pub mod router;
 
+#[cfg(feature = "testing")]
+pub mod testing;
+
 /// Contains aliases and functions for working with this library using a state of type  `()`.
 #[cfg(feature = "unit_alias")]
 pub mod unit_state {
@@ -85,13 +141,13 @@ pub mod prelude {
     #[cfg(feature = "unit_alias")]
     pub use super::unit_state::*;
     pub use crate::switch::Switch;
+    #[cfg(feature = "derive")]
     pub use yew_router_macro::Switch;
     // State restrictions
     #[cfg(feature = "agent")]
+    #[allow(deprecated)]
     pub use crate::agent::AgentState;
-    pub use crate::route::RouteState;
-    #[cfg(feature = "router")]
-    pub use crate::router::RouterState;
+    pub use crate::route::{RouteState, RouterState};
 }
 
 pub use alias::*;
@@ -101,11 +157,45 @@ pub mod matcher;
 pub use matcher::Captures;
 
 #[cfg(feature = "agent")]
+#[allow(deprecated)]
 pub use crate::agent::AgentState;
-pub use crate::route::RouteState;
-#[cfg(feature = "router")]
-pub use crate::router::RouterState;
+pub use crate::route::{RouteState, RouterState};
 
 pub mod switch;
 pub use switch::Switch;
+#[cfg(feature = "derive")]
 pub use yew_router_macro::Switch;
+
+pub mod debug;
+
+pub mod manifest;
+
+pub mod sitemap;
+
+/// Creates a `RouteMatcher` from a route matcher string, validating the string at compile time.
+///
+/// A malformed matcher string will fail to compile, instead of causing an error when
+/// `RouteMatcher::try_from` is called at runtime.
+///
+/// The matcher string can be followed by the settings of the matcher, which otherwise default to
+/// those of `MatcherSettings::default()`:
+/// * `grammar = "v2"` - Parses the matcher string with a newer version of the grammar.
+/// * `query = "strict"` or `query = "lenient"` - Sets the `QueryMode` of the matcher.
+/// * `case_insensitive` - Literals match regardless of their case.
+/// * `incomplete` - The matcher doesn't have to consume the whole route to match.
+///
+/// # Example
+/// ```
+/// use yew_router::route;
+/// let matcher = route!("/users/{id}");
+/// let (_, captures) = matcher
+///     .capture_route_into_map("/users/42")
+///     .expect("should match");
+/// assert_eq!(captures["id"], "42".to_string());
+///
+/// let matcher = route!(r"/hello\!", grammar = "v2", case_insensitive);
+/// assert!(matcher.capture_route_into_map("/HELLO!").is_ok());
+/// ```
+#[cfg(feature = "derive")]
+#[proc_macro_hack::proc_macro_hack]
+pub use yew_router_macro::route;
@@ -0,0 +1,374 @@
+//! Exports the routes of a `Switch` as a manifest that other tools can read.
+//!
+//! The manifest implements `Serialize`, so it can be written as JSON, YAML, or any other format
+//! that has a `serde` serializer, for backends and end-to-end tests that need to know the routes
+//! of the app.
+use crate::switch::{RouteInfo, Switch};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use yew_router_route_parser::{
+    parser::{tokenize_with_version, CaptureOrExact, RouteParserToken},
+    FieldType,
+};
+
+/// The routes of a `Switch`, in the order that they are tried.
+///
+/// # Example
+/// ```
+/// use yew_router::{manifest::RouteManifest, Switch};
+///
+/// #[derive(Switch)]
+/// enum AppRoute {
+///     #[to = "/users/{id}/posts?page={page}"]
+///     #[meta(title = "Posts")]
+///     Posts { id: u32, page: u32 },
+///     #[to = "/!"]
+///     Home,
+/// }
+///
+/// let manifest = RouteManifest::of::<AppRoute>();
+/// let posts = &manifest.routes[0];
+/// assert_eq!(posts.path, "/users/{id}/posts");
+/// assert_eq!(posts.query[0].name, "page");
+/// assert_eq!(posts.captures[1].ty, Some("u32"));
+/// assert!(manifest.routes[1].exact);
+///
+/// let json = serde_json::to_string(&manifest).unwrap();
+/// assert!(json.contains(r#""section":"query""#));
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RouteManifest {
+    /// The routes, in the order that they are tried.
+    pub routes: Vec<ManifestRoute>,
+}
+
+/// A route of a `Switch`, split into its sections.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ManifestRoute {
+    /// Name of the enum variant or struct that is created by the route.
+    pub name: &'static str,
+    /// The route matcher string.
+    pub matcher: &'static str,
+    /// The path section of the matcher, with its captures written as `{name}`.
+    pub path: String,
+    /// The parameters of the query section of the matcher, in the order they appear.
+    pub query: Vec<ManifestQueryParam>,
+    /// The fragment section of the matcher, without its `#`.
+    pub fragment: Option<String>,
+    /// Whether the route has to match entirely, because its matcher ends with `!`.
+    pub exact: bool,
+    /// The captures of the matcher, in the order they appear.
+    pub captures: Vec<ManifestCapture>,
+    /// The metadata attached with `#[meta(...)]` attributes, where flags don't have a value.
+    pub meta: BTreeMap<&'static str, Option<&'static str>>,
+}
+
+/// A parameter of the query section of a route.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ManifestQueryParam {
+    /// The name of the parameter.
+    pub name: String,
+    /// The value of the parameter, which may be a capture like `{page}`.
+    pub value: String,
+}
+
+/// A section of a route that is captured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ManifestCapture {
+    /// The name of the capture, which is missing for tuple structs and variants.
+    pub name: Option<&'static str>,
+    /// The type of the field that the capture is converted into, as `stringify!` renders it.
+    pub ty: Option<&'static str>,
+    /// The section of the route that the capture is in.
+    pub section: RouteSection,
+}
+
+/// The sections of a route.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RouteSection {
+    /// The path, before the `?` and `#`.
+    Path,
+    /// The query, after the `?`.
+    Query,
+    /// The fragment, after the `#`.
+    Fragment,
+}
+
+impl RouteManifest {
+    /// Describes the routes of `SW`.
+    pub fn of<SW: Switch>() -> Self {
+        RouteManifest::from_routes(SW::routes())
+    }
+
+    /// Describes the routes that a `Switch` reports.
+    pub fn from_routes(routes: Vec<RouteInfo>) -> Self {
+        RouteManifest {
+            routes: routes.into_iter().map(ManifestRoute::from).collect(),
+        }
+    }
+}
+
+impl From<RouteInfo> for ManifestRoute {
+    fn from(info: RouteInfo) -> Self {
+        let layout = Layout::of(&info);
+        let path = &info.matcher[..layout.query_start];
+        let fragment = if layout.fragment_start < layout.end {
+            // Skips the `#`.
+            Some(info.matcher[layout.fragment_start + 1..layout.end].to_string())
+        } else {
+            None
+        };
+
+        // The captures are listed in the order they appear, so the sections they are in follow
+        // from how many captures each section has.
+        let query_captures = layout.path_captures + layout.query_captures;
+        let captures = info
+            .captures
+            .iter()
+            .enumerate()
+            .map(|(index, capture)| ManifestCapture {
+                name: capture.name,
+                ty: capture.ty,
+                section: if index < layout.path_captures {
+                    RouteSection::Path
+                } else if index < query_captures {
+                    RouteSection::Query
+                } else {
+                    RouteSection::Fragment
+                },
+            })
+            .collect();
+
+        ManifestRoute {
+            name: info.name,
+            matcher: info.matcher,
+            path: path.to_string(),
+            query: layout.query,
+            fragment,
+            exact: layout.exact,
+            captures,
+            meta: info.meta.iter().collect(),
+        }
+    }
+}
+
+/// Where the sections of a matcher string are, and how many captures they have, read from the
+/// tokens that the matcher string is parsed into.
+struct Layout {
+    query_start: usize,
+    fragment_start: usize,
+    /// Where the `!` at the end of the matcher string starts, or its length if there is none.
+    end: usize,
+    exact: bool,
+    query: Vec<ManifestQueryParam>,
+    path_captures: usize,
+    query_captures: usize,
+}
+
+impl Layout {
+    /// Reads the layout of the matcher string of a route.
+    ///
+    /// Matcher strings that can't be parsed, which the derive never produces, are treated as a
+    /// path without captures.
+    fn of(info: &RouteInfo) -> Self {
+        let matcher = info.matcher;
+        let mut query_start = None;
+        let mut fragment_start = None;
+        let mut end = None;
+        let mut query = Vec::new();
+        let mut path_captures = 0;
+        let mut query_captures = 0;
+        let mut section = RouteSection::Path;
+        let mut tokens = tokenize_with_version(matcher, FieldType::Unnamed, info.grammar_version);
+        loop {
+            let start = matcher.len() - tokens.remaining().len();
+            let token = match tokens.next() {
+                Some(Ok(token)) => token,
+                Some(Err(error)) => {
+                    log::error!(
+                        "The matcher of {} can't be parsed for the manifest: {}",
+                        info.name,
+                        error
+                    );
+                    return Layout::path_only(matcher);
+                }
+                None => break,
+            };
+            let captures = match token {
+                RouteParserToken::Capture(_)
+                | RouteParserToken::MatrixParam {
+                    capture_or_exact: CaptureOrExact::Capture(_),
+                    ..
+                } => 1,
+                RouteParserToken::Query {
+                    ident,
+                    capture_or_exact,
+                } => {
+                    let token_end = matcher.len() - tokens.remaining().len();
+                    query.push(ManifestQueryParam {
+                        name: ident.to_string(),
+                        // Skips the name and the `=`.
+                        value: matcher[start + ident.len() + 1..token_end].to_string(),
+                    });
+                    match capture_or_exact {
+                        CaptureOrExact::Capture(_) => 1,
+                        CaptureOrExact::Exact(_) => 0,
+                    }
+                }
+                RouteParserToken::QueryValue(part) => {
+                    let token_end = matcher.len() - tokens.remaining().len();
+                    if let Some(param) = query.last_mut() {
+                        param.value.push_str(&matcher[start..token_end]);
+                    }
+                    match part {
+                        CaptureOrExact::Capture(_) => 1,
+                        CaptureOrExact::Exact(_) => 0,
+                    }
+                }
+                RouteParserToken::QueryBegin => {
+                    query_start = Some(start);
+                    section = RouteSection::Query;
+                    0
+                }
+                RouteParserToken::FragmentBegin => {
+                    fragment_start = Some(start);
+                    section = RouteSection::Fragment;
+                    0
+                }
+                RouteParserToken::End => {
+                    end = Some(start);
+                    0
+                }
+                _ => 0,
+            };
+            match section {
+                RouteSection::Path => path_captures += captures,
+                RouteSection::Query => query_captures += captures,
+                RouteSection::Fragment => {}
+            }
+        }
+        let exact = end.is_some();
+        let end = end.unwrap_or(matcher.len());
+        let fragment_start = fragment_start.unwrap_or(end);
+        Layout {
+            query_start: query_start.unwrap_or(fragment_start),
+            fragment_start,
+            end,
+            exact,
+            query,
+            path_captures,
+            query_captures,
+        }
+    }
+
+    /// The layout of a matcher string that is entirely made up of its path.
+    fn path_only(matcher: &str) -> Self {
+        Layout {
+            query_start: matcher.len(),
+            fragment_start: matcher.len(),
+            end: matcher.len(),
+            exact: false,
+            query: Vec::new(),
+            path_captures: 0,
+            query_captures: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        matcher::GrammarVersion,
+        switch::{CaptureInfo, RouteMeta},
+    };
+
+    fn capture(name: &'static str) -> CaptureInfo {
+        CaptureInfo {
+            name: Some(name),
+            ty: Some("String"),
+        }
+    }
+
+    #[test]
+    fn sections_are_split() {
+        let route = ManifestRoute::from(RouteInfo {
+            name: "Search",
+            matcher: "/search/{scope}?q={q}&sort=new#{anchor}!",
+            captures: vec![capture("scope"), capture("q"), capture("anchor")],
+            route: None,
+            grammar_version: GrammarVersion::V1,
+            meta: RouteMeta::new(&[("title", Some("Search")), ("public", None)]),
+        });
+        assert_eq!(route.path, "/search/{scope}");
+        assert_eq!(
+            route.query,
+            vec![
+                ManifestQueryParam {
+                    name: "q".to_string(),
+                    value: "{q}".to_string(),
+                },
+                ManifestQueryParam {
+                    name: "sort".to_string(),
+                    value: "new".to_string(),
+                },
+            ]
+        );
+        assert_eq!(route.fragment, Some("{anchor}".to_string()));
+        assert!(route.exact);
+        let sections: Vec<RouteSection> = route.captures.iter().map(|c| c.section).collect();
+        assert_eq!(
+            sections,
+            vec![
+                RouteSection::Path,
+                RouteSection::Query,
+                RouteSection::Fragment
+            ]
+        );
+        assert_eq!(route.meta.get("title"), Some(&Some("Search")));
+        assert_eq!(route.meta.get("public"), Some(&None));
+    }
+
+    #[test]
+    fn routes_without_query_or_fragment() {
+        let route = ManifestRoute::from(RouteInfo {
+            name: "Files",
+            matcher: "/files/{*:path}",
+            captures: vec![capture("path")],
+            route: None,
+            grammar_version: GrammarVersion::V1,
+            meta: RouteMeta::default(),
+        });
+        assert_eq!(route.path, "/files/{*:path}");
+        assert!(route.query.is_empty());
+        assert_eq!(route.fragment, None);
+        assert!(!route.exact);
+        assert_eq!(route.captures[0].section, RouteSection::Path);
+    }
+
+    #[test]
+    fn escapes_are_not_section_boundaries() {
+        let route = ManifestRoute::from(RouteInfo {
+            name: "Escaped",
+            matcher: r"/what\?/\{{id}\}?q={q}#\#{anchor}",
+            captures: vec![capture("id"), capture("q"), capture("anchor")],
+            route: None,
+            grammar_version: GrammarVersion::V2,
+            meta: RouteMeta::default(),
+        });
+        assert_eq!(route.path, r"/what\?/\{{id}\}");
+        assert_eq!(route.query[0].value, "{q}");
+        assert_eq!(route.fragment, Some(r"\#{anchor}".to_string()));
+        assert!(!route.exact);
+        let sections: Vec<RouteSection> = route.captures.iter().map(|c| c.section).collect();
+        assert_eq!(
+            sections,
+            vec![
+                RouteSection::Path,
+                RouteSection::Query,
+                RouteSection::Fragment
+            ]
+        );
+    }
+}
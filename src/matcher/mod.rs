@@ -1,17 +1,32 @@
 //! Module for matching route strings based on tokens generated from the yew_router_route_parser
 //! crate.
 
-mod matcher_impl;
-mod util;
+mod normalization;
+
+pub(crate) use normalization::resolve_dot_segments;
+pub use normalization::Normalization;
 
 use nom::{combinator::all_consuming, IResult};
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
-use yew_router_route_parser::{parse_str_and_optimize_tokens, PrettyParseError};
+use yew_router_route_parser::{
+    is_anchored,
+    matcher::{is_extension, match_into_map, match_into_vec, match_parts},
+    parse_str_and_optimize_tokens_with_version, to_matcher_string, PrettyParseError,
+};
 
-pub use yew_router_route_parser::{CaptureVariant, Captures, MatcherToken};
+pub use yew_router_route_parser::{
+    CaptureKind, CaptureVariant, Captures, GrammarVersion, MatcherSettings, MatcherToken, QueryMode,
+};
 
 /// Attempts to match routes, transform the route to Component props and render that Component.
-#[derive(Debug, PartialEq, Clone)]
+///
+/// Matchers can be serialized, so that they can be generated ahead of time and loaded as data,
+/// instead of being parsed from matcher strings at runtime.
+///
+/// Matchers are `Send` and `Sync` and have no interior mutability, so a table of matchers can be
+/// built once, stored in a static, and used from many threads at the same time.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct RouteMatcher {
     /// Tokens used to determine how the matcher will match a route string.
     pub tokens: Vec<MatcherToken>,
@@ -19,24 +34,6 @@ pub struct RouteMatcher {
     pub settings: MatcherSettings,
 }
 
-/// Settings used for the matcher.
-#[derive(Debug, PartialEq, Clone, Copy)]
-pub struct MatcherSettings {
-    /// A matcher must consume all of the input to succeed.
-    pub complete: bool,
-    /// All literal matches do not care about case.
-    pub case_insensitive: bool,
-}
-
-impl Default for MatcherSettings {
-    fn default() -> Self {
-        MatcherSettings {
-            complete: true,
-            case_insensitive: false,
-        }
-    }
-}
-
 impl RouteMatcher {
     /// Attempt to create a RouteMatcher from a "matcher string".
     pub fn try_from(i: &str) -> Result<Self, PrettyParseError> {
@@ -47,7 +44,11 @@ impl RouteMatcher {
     /// Creates a new Matcher with settings.
     pub fn new(i: &str, settings: MatcherSettings) -> Result<Self, PrettyParseError> {
         Ok(RouteMatcher {
-            tokens: parse_str_and_optimize_tokens(i, yew_router_route_parser::FieldType::Unnamed)?, /* TODO this field type should be a superset of Named, but it would be better to source this from settings, and make sure that the macro generates settings as such. */
+            tokens: parse_str_and_optimize_tokens_with_version(
+                i,
+                yew_router_route_parser::FieldType::Unnamed, /* TODO this field type should be a superset of Named, but it would be better to source this from settings, and make sure that the macro generates settings as such. */
+                settings.grammar_version,
+            )?,
             settings,
         })
     }
@@ -58,9 +59,9 @@ impl RouteMatcher {
         i: &'a str,
     ) -> IResult<&'a str, Captures<'a>> {
         if self.settings.complete {
-            all_consuming(matcher_impl::match_into_map(&self.tokens, &self.settings))(i)
+            all_consuming(match_into_map(&self.tokens, &self.settings))(i)
         } else {
-            matcher_impl::match_into_map(&self.tokens, &self.settings)(i)
+            match_into_map(&self.tokens, &self.settings)(i)
         }
     }
 
@@ -71,10 +72,186 @@ impl RouteMatcher {
     ) -> IResult<&'a str, Vec<(&'b str, String)>> {
         // TODO this return type mandates that a key exist, which for the purposes of this function, may not be present.
         if self.settings.complete {
-            all_consuming(matcher_impl::match_into_vec(&self.tokens, &self.settings))(i)
+            all_consuming(match_into_vec(&self.tokens, &self.settings))(i)
         } else {
-            matcher_impl::match_into_vec(&self.tokens, &self.settings)(i)
+            match_into_vec(&self.tokens, &self.settings)(i)
+        }
+    }
+
+    /// Match the path, query, and fragment of a parsed url, collecting the results into a map.
+    ///
+    /// The scheme, host, and port of the url are ignored.
+    /// The url is matched in parts, like by `capture_parts_into_map`, with the names and values of
+    /// query parameters decoded.
+    ///
+    /// # Example
+    /// ```
+    /// use yew_router::matcher::RouteMatcher;
+    /// let matcher = RouteMatcher::try_from("/users/{id}?tab={tab}").expect("should parse");
+    /// let url = url::Url::parse("https://example.com/users/42?tab=posts%20%26%20replies")
+    ///     .expect("should parse");
+    /// let captures = matcher.capture_url_into_map(&url).expect("should match");
+    /// assert_eq!(captures["id"], "42".to_string());
+    /// assert_eq!(captures["tab"], "posts & replies".to_string());
+    /// ```
+    #[cfg(feature = "url")]
+    pub fn capture_url_into_map<'b>(&'b self, url: &url::Url) -> Option<Captures<'b>> {
+        let query: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        let query: Vec<(&str, &str)> = query
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect();
+        self.capture_parts_into_map(url.path(), &query, url.fragment())
+    }
+
+    /// Match a route that has already been split into its path, query parameters, and fragment,
+    /// collecting the results into a map.
+    ///
+    /// Servers usually receive urls in this form, and don't have to reassemble them to match them.
+    /// Query parameters are compared by name and value, without being escaped, and a value that is
+    /// matched by a lone capture, like `{q}` in `?q={q}`, is captured in its entirety.
+    ///
+    /// # Example
+    /// ```
+    /// use yew_router::matcher::RouteMatcher;
+    /// let matcher = RouteMatcher::try_from("/search?q={q}").expect("should parse");
+    /// let captures = matcher
+    ///     .capture_parts_into_map("/search", &[("q", "salt & pepper")], None)
+    ///     .expect("should match");
+    /// assert_eq!(captures["q"], "salt & pepper".to_string());
+    /// ```
+    pub fn capture_parts_into_map<'b>(
+        &'b self,
+        path: &str,
+        query: &[(&str, &str)],
+        fragment: Option<&str>,
+    ) -> Option<Captures<'b>> {
+        match_parts(&self.tokens, self.settings, path, query, fragment)
+    }
+
+    /// Determines if this matcher will only match routes that it consumes entirely.
+    ///
+    /// A matcher is anchored if its matcher string ends with the end token (`!`).
+    /// Anchored matchers can't be used to match a prefix of a route.
+    pub fn is_anchored(&self) -> bool {
+        is_anchored(&self.tokens)
+    }
+
+    /// Reconstructs a matcher string for this matcher, like for listing the routes of an app.
+    ///
+    /// The string is normalized, so it may differ from the one the matcher was created from.
+    ///
+    /// # Example
+    /// ```
+    /// use yew_router::matcher::RouteMatcher;
+    /// let matcher = RouteMatcher::try_from("/users/{id}").expect("should parse");
+    /// assert_eq!(matcher.to_matcher_string(), "/users/{id}");
+    /// ```
+    pub fn to_matcher_string(&self) -> String {
+        to_matcher_string(&self.tokens)
+    }
+
+    /// Creates a matcher that matches a route matched by this matcher, followed by one matched by
+    /// `other`, so nested routes can be built from the matchers of their parts.
+    ///
+    /// A single `/` is kept between the two, whether either of them has it or not, unless `other`
+    /// starts with a query or fragment.
+    /// The end token of this matcher is dropped, as the joined routes continue past it, and the
+    /// settings of this matcher are used.
+    ///
+    /// # Example
+    /// ```
+    /// use yew_router::matcher::RouteMatcher;
+    /// let api_base = RouteMatcher::try_from("/api/{version}/").expect("should parse");
+    /// let users = RouteMatcher::try_from("/users/{id}").expect("should parse");
+    /// let matcher = api_base.join(&users);
+    /// assert_eq!(matcher, RouteMatcher::try_from("/api/{version}/users/{id}").unwrap());
+    /// let (_, captures) = matcher
+    ///     .capture_route_into_map("/api/v2/users/42")
+    ///     .expect("should match");
+    /// assert_eq!(captures["id"], "42".to_string());
+    /// ```
+    pub fn join(&self, other: &RouteMatcher) -> RouteMatcher {
+        let mut tokens: Vec<MatcherToken> = self
+            .tokens
+            .iter()
+            .filter(|token| **token != MatcherToken::End)
+            .cloned()
+            .collect();
+        let ends_with_slash = match tokens.last() {
+            Some(MatcherToken::Exact(literal)) => literal.ends_with('/'),
+            _ => false,
+        };
+        let separator = if ends_with_slash { "" } else { "/" };
+
+        let mut rest = other.tokens.iter().peekable();
+        let joint = match rest.peek() {
+            Some(MatcherToken::Exact(literal))
+                if literal.starts_with('?') || literal.starts_with('#') =>
+            {
+                None
+            }
+            Some(MatcherToken::Exact(literal)) => {
+                let joint = format!("{}{}", separator, literal.trim_start_matches('/'));
+                rest.next();
+                Some(joint)
+            }
+            Some(MatcherToken::Capture(_)) => Some(separator.to_string()),
+            Some(MatcherToken::End) | None => None,
+        };
+        if let Some(joint) = joint {
+            match tokens.last_mut() {
+                Some(MatcherToken::Exact(literal)) => literal.push_str(&joint),
+                _ if !joint.is_empty() => tokens.push(MatcherToken::Exact(joint)),
+                _ => {}
+            }
+        }
+        for token in rest {
+            match (tokens.last_mut(), token) {
+                (Some(MatcherToken::Exact(literal)), MatcherToken::Exact(next)) => {
+                    literal.push_str(next)
+                }
+                _ => tokens.push(token.clone()),
+            }
+        }
+
+        RouteMatcher {
+            tokens,
+            settings: self.settings,
+        }
+    }
+
+    /// Builds a route that the matcher matches, filling each named capture with its value.
+    ///
+    /// Returns `None` if a capture has no value, or the matcher has unnamed or repeated captures,
+    /// whose values can't be filled in.
+    /// It is also `None` if the extension of a file name, like `{ext}` in
+    /// `/files/{name:stem}.{ext}`, contains a `.`, as the route would then be split at that `.`
+    /// when it is matched.
+    pub(crate) fn fill<F: Fn(&str) -> Option<String>>(&self, value_of: F) -> Option<String> {
+        let mut route = String::new();
+        for (index, token) in self.tokens.iter().enumerate() {
+            match token {
+                MatcherToken::Exact(literal) => route.push_str(literal),
+                MatcherToken::End => {}
+                MatcherToken::Capture(capture) => match capture {
+                    CaptureVariant::Named(name)
+                    | CaptureVariant::ManyNamed(name)
+                    | CaptureVariant::GreedyManyNamed(name)
+                    | CaptureVariant::NumberedNamed { name, .. }
+                    | CaptureVariant::SeparatedNamed { name, .. }
+                    | CaptureVariant::TypedNamed { name, .. } => {
+                        let value = value_of(name)?;
+                        if value.contains('.') && is_extension(&self.tokens, index) {
+                            return None;
+                        }
+                        route.push_str(&value)
+                    }
+                    _ => return None,
+                },
+            }
         }
+        Some(route)
     }
 
     /// Gets a set of all names that will be captured.
@@ -89,13 +266,21 @@ impl RouteMatcher {
                         MatcherToken::Exact(_) | MatcherToken::End => {}
                         MatcherToken::Capture(capture) => match &capture {
                             CaptureVariant::ManyNamed(name)
+                            | CaptureVariant::GreedyManyNamed(name)
                             | CaptureVariant::Named(name)
-                            | CaptureVariant::NumberedNamed { name, .. } => {
+                            | CaptureVariant::NumberedNamed { name, .. }
+                            | CaptureVariant::SeparatedNamed { name, .. }
+                            | CaptureVariant::TypedNamed { name, .. }
+                            | CaptureVariant::RepeatedNamed { name, .. } => {
                                 acc.insert(&name);
                             }
                             CaptureVariant::Unnamed
                             | CaptureVariant::ManyUnnamed
-                            | CaptureVariant::NumberedUnnamed { .. } => {}
+                            | CaptureVariant::GreedyManyUnnamed
+                            | CaptureVariant::NumberedUnnamed { .. }
+                            | CaptureVariant::SeparatedUnnamed { .. }
+                            | CaptureVariant::TypedUnnamed { .. }
+                            | CaptureVariant::RepeatedUnnamed { .. } => {}
                         },
                     }
                     acc
@@ -123,6 +308,140 @@ mod tests {
         }
     }
 
+    #[test]
+    fn matcher_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<RouteMatcher>();
+        assert_send_sync::<MatcherSettings>();
+    }
+
+    #[test]
+    fn shared_between_threads() {
+        let matcher =
+            std::sync::Arc::new(RouteMatcher::try_from("/users/{id}").expect("should parse"));
+        let handles: Vec<_> = (0..4)
+            .map(|n| {
+                let matcher = matcher.clone();
+                std::thread::spawn(move || {
+                    let route = format!("/users/{}", n);
+                    let (_, matches) = matcher
+                        .capture_route_into_map(&route)
+                        .expect("should match");
+                    assert_eq!(matches["id"], n.to_string());
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().expect("should not panic");
+        }
+    }
+
+    #[test]
+    fn anchored() {
+        let matcher = RouteMatcher::try_from("/lorem!").expect("should parse");
+        assert!(matcher.is_anchored());
+        let matcher = RouteMatcher::try_from("/lorem").expect("should parse");
+        assert!(!matcher.is_anchored());
+    }
+
+    #[test]
+    fn join_keeps_a_single_separator() {
+        let join = |prefix: &str, suffix: &str| {
+            RouteMatcher::try_from(prefix)
+                .expect("should parse")
+                .join(&RouteMatcher::try_from(suffix).expect("should parse"))
+        };
+        let matcher = |i: &str| RouteMatcher::try_from(i).expect("should parse");
+        assert_eq!(join("/api", "/users"), matcher("/api/users"));
+        assert_eq!(join("/api/", "/users"), matcher("/api/users"));
+        assert_eq!(join("/api/", "users"), matcher("/api/users"));
+        assert_eq!(join("/api", "users"), matcher("/api/users"));
+        assert_eq!(join("/", "/users"), matcher("/users"));
+        assert_eq!(join("", "/users"), matcher("/users"));
+        assert_eq!(
+            join("/api/{version}", "{id}"),
+            matcher("/api/{version}/{id}")
+        );
+        assert_eq!(
+            join("/api/{version}", "/users"),
+            matcher("/api/{version}/users")
+        );
+        assert_eq!(join("/search", "?q={q}"), matcher("/search?q={q}"));
+        assert_eq!(join("/page", "#{section}"), matcher("/page#{section}"));
+        assert_eq!(join("/api!", "/users!"), matcher("/api/users!"));
+        // An empty matcher only matches the root, so nothing can follow the prefix.
+        assert_eq!(join("/api", ""), matcher("/api!"));
+    }
+
+    #[test]
+    fn empty_matches_root() {
+        let matcher = RouteMatcher::try_from("").expect("should parse");
+        matcher.capture_route_into_map("").expect("should match");
+        matcher.capture_route_into_map("/").expect("should match");
+        matcher
+            .capture_route_into_map("/lorem")
+            .expect_err("should not match");
+        matcher
+            .capture_route_into_map("//")
+            .expect_err("should not match");
+    }
+
+    #[test]
+    fn host_capture() {
+        let matcher =
+            RouteMatcher::try_from("{tenant}.example.com/dashboard").expect("should parse");
+        let (_, matches) = matcher
+            .capture_route_into_map("acme.example.com/dashboard")
+            .expect("should match");
+        assert_eq!(matches["tenant"], "acme".to_string());
+        matcher
+            .capture_route_into_map("acme.example.org/dashboard")
+            .expect_err("should not match");
+    }
+
+    #[test]
+    fn fill_file_extension() {
+        let matcher = RouteMatcher::try_from("/files/{name:stem}.{ext}").expect("should parse");
+        let fill = |name: &str, ext: &str| {
+            matcher.fill(|capture| match capture {
+                "name" => Some(name.to_string()),
+                _ => Some(ext.to_string()),
+            })
+        };
+        assert_eq!(
+            fill("archive.tar", "gz"),
+            Some("/files/archive.tar.gz".to_string())
+        );
+        assert_eq!(fill("archive", "tar.gz"), None);
+    }
+
+    #[test]
+    fn typed_capture() {
+        let matcher = RouteMatcher::try_from("/users/{id:u32}").expect("should parse");
+        let (_, matches) = matcher
+            .capture_route_into_map("/users/42")
+            .expect("should match");
+        assert_eq!(matches["id"], "42".to_string());
+        matcher
+            .capture_route_into_map("/users/new")
+            .expect_err("should not match");
+        matcher
+            .capture_route_into_map("/users/99999999999")
+            .expect_err("should not match");
+        assert!(matcher.capture_names().contains("id"));
+    }
+
+    #[test]
+    fn typed_capture_before_literal() {
+        let matcher = RouteMatcher::try_from("/posts/{:uuid}/edit").expect("should parse");
+        matcher
+            .capture_route_into_vec("/posts/5dc48134-35b5-4b8c-aa93-767bf00ae1d8/edit")
+            .expect("should match");
+        matcher
+            .capture_route_into_vec("/posts/latest/edit")
+            .expect_err("should not match");
+    }
+
     #[test]
     fn basic_separator() {
         let tokens = vec![RouteParserToken::Separator];
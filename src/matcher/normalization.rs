@@ -0,0 +1,261 @@
+//! Normalization of routes before they are matched.
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+/// Describes how the path of a route should be cleaned up before it is matched.
+///
+/// Links from emails and third parties frequently contain artifacts like duplicate slashes or
+/// `.` and `..` segments that would otherwise prevent a route from matching.
+///
+/// Only the path is normalized, the query and fragment are left untouched.
+/// By default, no normalization is performed.
+///
+/// Browsers percent-encode the non-ASCII characters of a url, so matchers containing unicode, like
+/// `/kategorie/bücher`, only match the routes read from the browser if `decode_unicode` is set.
+#[derive(Debug, Default, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub struct Normalization {
+    /// Collapse runs of `/` into a single `/`, so `/a//b` becomes `/a/b`.
+    pub collapse_slashes: bool,
+    /// Resolve `.` and `..` segments, so `/a/./b/../c` becomes `/a/c`.
+    pub resolve_dot_segments: bool,
+    /// Lowercase the path, so `/About` becomes `/about`.
+    pub lowercase_path: bool,
+    /// Percent-decode the non-ASCII characters of the path, so `/b%C3%BCcher` becomes `/bücher`.
+    ///
+    /// Encoded ASCII characters, like `%2F`, are left alone, as decoding them could change which
+    /// sections the path is made up of.
+    pub decode_unicode: bool,
+    /// Normalize the path to Unicode Normalization Form C, so a `u` followed by a combining
+    /// diaeresis becomes a `ü`, and compares equal to a matcher containing it.
+    ///
+    /// Matchers should be written in NFC, which most editors do.
+    ///
+    /// This is ignored unless the `unicode-normalization` feature is enabled.
+    #[serde(default)]
+    pub nfc: bool,
+}
+
+impl Normalization {
+    /// Normalization that performs every cleanup step.
+    pub fn all() -> Self {
+        Normalization {
+            collapse_slashes: true,
+            resolve_dot_segments: true,
+            lowercase_path: true,
+            decode_unicode: true,
+            nfc: true,
+        }
+    }
+
+    /// Normalizes the path of the route.
+    ///
+    /// The route is only copied if it needs to change.
+    ///
+    /// # Example
+    /// ```
+    /// use yew_router::matcher::Normalization;
+    /// let normalization = Normalization::all();
+    /// assert_eq!(normalization.normalize("/Users//./42/../7?Tab=Posts"), "/users/7?Tab=Posts");
+    /// ```
+    pub fn normalize<'a>(&self, route: &'a str) -> Cow<'a, str> {
+        if *self == Normalization::default() {
+            return Cow::Borrowed(route);
+        }
+        let path_end = route.find(|c| c == '?' || c == '#').unwrap_or(route.len());
+        let (path, rest) = route.split_at(path_end);
+
+        let mut normalized: Cow<str> = Cow::Borrowed(path);
+        if self.decode_unicode && normalized.contains('%') {
+            if let Some(decoded) = decode_unicode(&normalized) {
+                normalized = Cow::Owned(decoded);
+            }
+        }
+        #[cfg(feature = "unicode-normalization")]
+        {
+            if self.nfc && !unicode_normalization::is_nfc(&normalized) {
+                use unicode_normalization::UnicodeNormalization;
+                normalized = Cow::Owned(normalized.nfc().collect());
+            }
+        }
+        if self.collapse_slashes && normalized.contains("//") {
+            normalized = Cow::Owned(collapse_slashes(&normalized));
+        }
+        if self.resolve_dot_segments
+            && normalized
+                .split('/')
+                .any(|segment| segment == "." || segment == "..")
+        {
+            normalized = Cow::Owned(resolve_dot_segments(&normalized));
+        }
+        if self.lowercase_path && normalized.chars().any(char::is_uppercase) {
+            normalized = Cow::Owned(normalized.to_lowercase());
+        }
+
+        match normalized {
+            Cow::Borrowed(_) => Cow::Borrowed(route),
+            Cow::Owned(mut path) => {
+                path.push_str(rest);
+                Cow::Owned(path)
+            }
+        }
+    }
+}
+
+fn collapse_slashes(path: &str) -> String {
+    let mut collapsed = String::with_capacity(path.len());
+    for c in path.chars() {
+        if !(c == '/' && collapsed.ends_with('/')) {
+            collapsed.push(c);
+        }
+    }
+    collapsed
+}
+
+/// Decodes the percent-encoded bytes that aren't ASCII.
+///
+/// Returns `None` if nothing was decoded, or if the decoded bytes aren't valid UTF-8.
+fn decode_unicode(path: &str) -> Option<String> {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = if bytes[index] == b'%' {
+            path.get(index + 1..index + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .filter(|byte| !byte.is_ascii())
+        } else {
+            None
+        };
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    if decoded.len() == bytes.len() {
+        return None;
+    }
+    String::from_utf8(decoded).ok()
+}
+
+/// Resolves `.` and `..` segments, without going above the root.
+pub(crate) fn resolve_dot_segments(path: &str) -> String {
+    let absolute = path.starts_with('/');
+    let path = if absolute { &path[1..] } else { path };
+    let segments: Vec<&str> = path.split('/').collect();
+    let mut resolved: Vec<&str> = Vec::with_capacity(segments.len());
+    for (index, segment) in segments.iter().enumerate() {
+        let is_last = index == segments.len() - 1;
+        match *segment {
+            "." => {}
+            ".." => {
+                resolved.pop();
+            }
+            segment => resolved.push(segment),
+        }
+        // A trailing dot segment refers to a directory, so the trailing slash is kept.
+        if is_last && (*segment == "." || *segment == "..") {
+            resolved.push("");
+        }
+    }
+    let resolved = resolved.join("/");
+    if absolute {
+        format!("/{}", resolved)
+    } else {
+        resolved
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_does_nothing() {
+        let normalization = Normalization::default();
+        assert_eq!(normalization.normalize("/A//./b/.."), "/A//./b/..");
+    }
+
+    #[test]
+    fn collapses_slashes() {
+        let normalization = Normalization {
+            collapse_slashes: true,
+            ..Default::default()
+        };
+        assert_eq!(normalization.normalize("//a///b/"), "/a/b/");
+        assert_eq!(normalization.normalize("/a?b=//c"), "/a?b=//c");
+    }
+
+    #[test]
+    fn resolves_dot_segments() {
+        let normalization = Normalization {
+            resolve_dot_segments: true,
+            ..Default::default()
+        };
+        assert_eq!(normalization.normalize("/a/./b"), "/a/b");
+        assert_eq!(normalization.normalize("/a/b/.."), "/a/");
+        assert_eq!(normalization.normalize("/a/../../b"), "/b");
+        assert_eq!(normalization.normalize("/."), "/");
+        assert_eq!(normalization.normalize("/a/..?q=1#f"), "/?q=1#f");
+        assert_eq!(normalization.normalize("/a..b/.c"), "/a..b/.c");
+    }
+
+    #[test]
+    fn lowercases_path() {
+        let normalization = Normalization {
+            lowercase_path: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            normalization.normalize("/About?Q=V#Frag"),
+            "/about?Q=V#Frag"
+        );
+    }
+
+    #[test]
+    fn decodes_unicode() {
+        let normalization = Normalization {
+            decode_unicode: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            normalization.normalize("/kategorie/b%C3%BCcher%2F%20?q=%C3%BC"),
+            "/kategorie/bücher%2F%20?q=%C3%BC"
+        );
+        // Bytes that don't decode to a character are left encoded.
+        assert_eq!(normalization.normalize("/b%C3%28"), "/b%C3%28");
+    }
+
+    #[cfg(feature = "unicode-normalization")]
+    #[test]
+    fn normalizes_to_nfc() {
+        let normalization = Normalization {
+            nfc: true,
+            ..Default::default()
+        };
+        assert_eq!(normalization.normalize("/bu\u{308}cher"), "/b\u{fc}cher");
+    }
+
+    #[cfg(not(feature = "unicode-normalization"))]
+    #[test]
+    fn nfc_is_ignored_without_its_feature() {
+        let normalization = Normalization {
+            nfc: true,
+            ..Default::default()
+        };
+        assert_eq!(normalization.normalize("/bu\u{308}cher"), "/bu\u{308}cher");
+    }
+
+    #[test]
+    fn borrows_when_unchanged() {
+        match Normalization::all().normalize("/a/b?c") {
+            Cow::Borrowed(route) => assert_eq!(route, "/a/b?c"),
+            Cow::Owned(_) => panic!("should not have copied the route"),
+        }
+    }
+}
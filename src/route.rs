@@ -1,36 +1,160 @@
 //! Wrapper around route url string, and associated history state.
+use crate::matcher::resolve_dot_segments;
+#[cfg(feature = "service")]
 use crate::service::RouteService;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{fmt, ops::Deref};
+#[cfg(feature = "service")]
 use stdweb::{unstable::TryFrom, JsSerialize, Value};
+#[cfg(feature = "service")]
 use yew::agent::Transferable;
 
+mod encoding;
+mod query;
+pub use encoding::{EncodeSet, RouteEncoding};
+pub use query::{QueryError, QueryPairs};
+
+/// The query parameter that holds the route that the app was redirected away from, like the page
+/// that a guard sent to `/login?return_to=%2Fsettings`.
+pub const RETURN_TO_PARAM: &str = "return_to";
+
 /// Any state that can be stored by the History API must meet the criteria of this trait.
+///
+/// This is the only bound of the state in the methods of `Switch`, so implementations of
+/// `Switch` don't have to require more of the state than they use.
+#[cfg(feature = "service")]
 pub trait RouteState: Clone + Default + JsSerialize + TryFrom<Value> + 'static {}
+#[cfg(feature = "service")]
 impl<T> RouteState for T where T: Clone + Default + JsSerialize + TryFrom<Value> + 'static {}
 
+/// Any state that can be stored along with a route must meet the criteria of this trait.
+///
+/// Without the `service` feature, the state doesn't have to be convertible to JavaScript values.
+#[cfg(not(feature = "service"))]
+pub trait RouteState: Clone + Default + 'static {}
+#[cfg(not(feature = "service"))]
+impl<T> RouteState for T where T: Clone + Default + 'static {}
+
+/// The state that is stored along with routes, which every type of this crate that holds a state
+/// is bound on.
+///
+/// It is implemented for every type that meets its criteria, so it never has to be implemented
+/// by hand.
+/// The state is stored in history entries by the `RouteService`, and sent between components by
+/// the `RouteAgent`, so on top of being a [`RouteState`](trait.RouteState.html), it must be
+/// serializable.
+/// Most apps don't store any state, and use `()`, which is the default state of every type that
+/// allows one.
+pub trait RouterState: RouteState + fmt::Debug + PartialEq + Serialize + DeserializeOwned {}
+impl<T> RouterState for T where
+    T: RouteState + fmt::Debug + PartialEq + Serialize + DeserializeOwned
+{
+}
+
 /// The representation of a route, segmented into different sections for easy access.
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
-pub struct Route<T> {
+pub struct Route<T = ()> {
     /// The route string
     pub route: String,
     /// The state stored in the history api
     pub state: Option<T>,
 }
 
+/// Finds where the query and the fragment of a route string start.
+///
+/// Sections that are missing start where the next one does, so slicing with the returned offsets
+/// gives empty sections.
+fn section_starts(route: &str) -> (usize, usize) {
+    let fragment_start = route.find('#').unwrap_or_else(|| route.len());
+    let query_start = route[..fragment_start].find('?').unwrap_or(fragment_start);
+    (query_start, fragment_start)
+}
+
 /// Formats a path, query, and fragment into a string.
 ///
-/// # Note
-/// This expects that all three already have their expected separators (?, #, etc)
+/// The query and fragment may be provided with or without their `?` and `#` separators, and empty
+/// ones are left out.
+/// Nothing is percent-encoded, use [`RouteParts`](struct.RouteParts.html) for that.
 pub(crate) fn format_route_string(path: &str, query: &str, fragment: &str) -> String {
+    let section = |separator: char, section: &str| {
+        if section.trim_start_matches(separator).is_empty() {
+            String::new()
+        } else {
+            with_separator(separator, section)
+        }
+    };
     format!(
         "{path}{query}{fragment}",
         path = path,
-        query = query,
-        fragment = fragment
+        query = section('?', query),
+        fragment = section('#', fragment)
+    )
+}
+
+/// Replaces the provided sections of a route string, keeping the others.
+///
+/// The query and fragment may be provided with or without their `?` and `#` separators, and an
+/// empty one removes the section.
+pub(crate) fn splice_route_string(
+    route: &str,
+    path: Option<&str>,
+    query: Option<&str>,
+    fragment: Option<&str>,
+) -> String {
+    let (query_start, fragment_start) = section_starts(route);
+    let query = query.map(|query| with_separator('?', query));
+    let fragment = fragment.map(|fragment| with_separator('#', fragment));
+    format_route_string(
+        path.unwrap_or(&route[..query_start]),
+        query
+            .as_ref()
+            .map_or(&route[query_start..fragment_start], String::as_str),
+        fragment
+            .as_ref()
+            .map_or(&route[fragment_start..], String::as_str),
     )
 }
 
+/// Prefixes a non-empty section of a route with its separator, if it doesn't have it already.
+fn with_separator(separator: char, section: &str) -> String {
+    if section.is_empty() || section.starts_with(separator) {
+        section.to_string()
+    } else {
+        format!("{}{}", separator, section)
+    }
+}
+
+/// Resolves a relative route against the path of the current route.
+///
+/// The current path is treated as a directory, so from `/users/5/edit`, `details` resolves to
+/// `/users/5/edit/details`, and `../view` resolves to `/users/5/view`.
+/// Relative routes that start with a `/` are absolute, and ones that start with a `?` or `#` only
+/// replace the query and fragment, or the fragment.
+#[cfg_attr(not(feature = "service"), allow(dead_code))]
+pub(crate) fn resolve_relative_route(current: &str, relative: &str) -> String {
+    if relative.starts_with('/') {
+        return relative.to_string();
+    }
+    let (query_start, fragment_start) = section_starts(current);
+    if relative.starts_with('#') {
+        return format!("{}{}", &current[..fragment_start], relative);
+    }
+    if relative.starts_with('?') {
+        return format!("{}{}", &current[..query_start], relative);
+    }
+    let path_end = relative
+        .find(|c| c == '?' || c == '#')
+        .unwrap_or_else(|| relative.len());
+    let (relative_path, rest) = relative.split_at(path_end);
+    let directory = current[..query_start].trim_end_matches('/');
+    let path = if relative_path.is_empty() {
+        current[..query_start].to_string()
+    } else {
+        resolve_dot_segments(&format!("{}/{}", directory, relative_path))
+    };
+    format!("{}{}", path, rest)
+}
+
 impl<T> Route<T> {
     /// Gets the current route from the route service.
     ///
@@ -39,14 +163,475 @@ impl<T> Route<T> {
     /// That is only provided via events.
     /// See [RouteService.register_callback](struct.RouteService.html#method.register_callback) to
     /// acquire state.
+    #[cfg(feature = "service")]
     pub fn current_route(route_service: &RouteService<T>) -> Self {
         let route = route_service.get_route();
         // TODO, should try to get the state using the history api once that is exposed through
         // stdweb. https://github.com/koute/stdweb/issues/371
         Route { route, state: None }
     }
+
+    /// Creates a route from a url, which may be absolute, like `https://example.com/users?page=2`,
+    /// or a route, like `/users?page=2`.
+    ///
+    /// The origin of absolute urls is removed.
+    /// When running in a browser, it must be the origin of the current page, as the History API
+    /// can't navigate to other origins.
+    /// Use `Route::parse` to accept urls with any origin.
+    pub fn from_url(url: &str) -> Result<Self, RouteParseError> {
+        parse_url(url, current_origin().as_ref().map(String::as_str))
+    }
+
+    /// Creates a route from a url like `Route::from_url`, but accepts urls with any origin.
+    pub fn parse(url: &str) -> Result<Self, RouteParseError> {
+        parse_url(url, None)
+    }
+
+    /// Gets the path of the route, like `/users` for `/users?page=2#top`.
+    pub fn path(&self) -> &str {
+        let (query_start, _) = section_starts(&self.route);
+        &self.route[..query_start]
+    }
+
+    /// Gets the query of the route without its `?`, like `page=2` for `/users?page=2#top`, if the
+    /// route has one.
+    ///
+    /// Like `RouteParts::query`, a `?` that isn't followed by anything makes for an empty query.
+    pub fn query(&self) -> Option<&str> {
+        let (query_start, fragment_start) = section_starts(&self.route);
+        self.route.get(query_start + 1..fragment_start)
+    }
+
+    /// Gets the fragment of the route without its `#`, like `top` for `/users?page=2#top`, if the
+    /// route has one.
+    pub fn fragment(&self) -> Option<&str> {
+        let (_, fragment_start) = section_starts(&self.route);
+        self.route.get(fragment_start + 1..)
+    }
+
+    /// Gets the parameters in the query of the route, keeping repeated keys.
+    pub fn query_pairs(&self) -> QueryPairs {
+        QueryPairs::parse(self.query().unwrap_or_default())
+    }
+
+    /// Replaces the query of the route with the pairs, removing it if there are none.
+    pub fn set_query_pairs(&mut self, pairs: &QueryPairs) {
+        self.route = splice_route_string(&self.route, None, Some(&pairs.to_string()), None);
+    }
+
+    /// Adds a query parameter, keeping the other values of its key.
+    pub fn append_query_pair(&mut self, key: &str, value: &str) {
+        self.update_query_pairs(|pairs| pairs.append(key, value));
+    }
+
+    /// Sets a query parameter, replacing every value of its key.
+    pub fn set_query_pair(&mut self, key: &str, value: &str) {
+        self.update_query_pairs(|pairs| pairs.set(key, value));
+    }
+
+    /// Removes every value of a query parameter.
+    pub fn remove_query_pair(&mut self, key: &str) {
+        self.update_query_pairs(|pairs| {
+            pairs.remove(key);
+        });
+    }
+
+    /// Remembers the route that is being redirected away from in the query of this one, so it can
+    /// be returned to, like after logging in.
+    ///
+    /// # Example
+    /// ```
+    /// use yew_router::route::Route;
+    ///
+    /// let settings = Route::<()>::from("/settings?tab=2");
+    /// let login = Route::<()>::from("/login").with_intended_route(&settings);
+    /// assert_eq!(login.route, "/login?return_to=/settings%3Ftab%3D2");
+    /// assert_eq!(login.intended_route(), Some("/settings?tab=2".to_string()));
+    /// ```
+    pub fn with_intended_route<U>(mut self, intended: &Route<U>) -> Self {
+        self.set_query_pair(RETURN_TO_PARAM, &intended.route);
+        self
+    }
+
+    /// Gets the route that was remembered with `with_intended_route`.
+    ///
+    /// Only routes of this app are returned, so links that were crafted to return to another
+    /// site, like `/login?return_to=//example.com`, are ignored.
+    pub fn intended_route(&self) -> Option<String> {
+        let pairs = self.query_pairs();
+        // Browsers remove tabs and newlines from urls, so `/\t/example.com` leads to
+        // `//example.com`.
+        let intended: String = pairs
+            .get(RETURN_TO_PARAM)?
+            .chars()
+            .filter(|c| *c != '\t' && *c != '\n' && *c != '\r')
+            .collect();
+        let is_local = intended.starts_with('/')
+            && !intended.starts_with("//")
+            && !intended.starts_with("/\\");
+        if is_local {
+            Some(intended)
+        } else {
+            None
+        }
+    }
+
+    fn update_query_pairs(&mut self, update: impl FnOnce(&mut QueryPairs)) {
+        let mut pairs = self.query_pairs();
+        update(&mut pairs);
+        self.set_query_pairs(&pairs);
+    }
+
+    /// Splits the route into its path, query, and fragment.
+    pub fn parts(&self) -> RouteParts {
+        RouteParts::new(&self.route)
+    }
+
+    /// Determines if the path of the other route is a prefix of this route's path, made up of
+    /// whole segments.
+    ///
+    /// `/foo/bar` starts with `/foo` and `/foo/`, but `/foobar` doesn't start with `/foo`.
+    /// Every path starts with `/`, and the queries and fragments of the routes aren't compared.
+    pub fn starts_with<U>(&self, other: &Route<U>) -> bool {
+        path_prefix_len(self.path(), other.path()).is_some()
+    }
+
+    /// Gets the rest of this route after the path of the other route, if it starts with it.
+    ///
+    /// The rest keeps this route's query, fragment, and state, so `/users/5/edit?tab=2` relative
+    /// to `/users` is `/5/edit?tab=2`.
+    ///
+    /// # Example
+    /// ```
+    /// use yew_router::route::Route;
+    ///
+    /// let route = Route::<()>::from("/users/5/edit?tab=2");
+    /// let rest = route.relative_to(&Route::<()>::from("/users")).unwrap();
+    /// assert_eq!(rest.route, "/5/edit?tab=2");
+    /// assert!(route.relative_to(&Route::<()>::from("/user")).is_none());
+    /// ```
+    pub fn relative_to<U>(&self, other: &Route<U>) -> Option<Self>
+    where
+        T: Clone,
+    {
+        let prefix_len = path_prefix_len(self.path(), other.path())?;
+        Some(Route {
+            route: self.route[prefix_len..].to_string(),
+            state: self.state.clone(),
+        })
+    }
+
+    /// Gets the longest path, made up of whole segments, that both routes start with.
+    ///
+    /// `/users/5/edit` and `/users/6` share `/users`, and routes that share nothing but their
+    /// leading `/` share `/`.
+    /// The shared route doesn't have a query, fragment, or state.
+    pub fn shared_prefix<U>(&self, other: &Route<U>) -> Self {
+        let (path, other_path) = (self.path(), other.path());
+        let mut len = 0;
+        for (segment, other_segment) in path.split('/').zip(other_path.split('/')) {
+            if segment != other_segment {
+                break;
+            }
+            len += segment.len() + 1;
+        }
+        let shared = &path[..len.saturating_sub(1).min(path.len())];
+        let shared = if shared.is_empty() && path.starts_with('/') && other_path.starts_with('/') {
+            "/"
+        } else {
+            shared
+        };
+        Route {
+            route: shared.to_string(),
+            state: None,
+        }
+    }
+}
+
+/// Gets the length of the part of the path that the prefix covers, if the prefix ends at a segment
+/// boundary of the path.
+///
+/// A trailing `/` of the prefix is ignored, so `/foo/` is a prefix of `/foo` and `/foo/bar`.
+fn path_prefix_len(path: &str, prefix: &str) -> Option<usize> {
+    let prefix = prefix.trim_end_matches('/');
+    if !path.starts_with(prefix) {
+        return None;
+    }
+    let rest = &path[prefix.len()..];
+    if rest.is_empty() || rest.starts_with('/') {
+        Some(prefix.len())
+    } else {
+        None
+    }
+}
+
+/// A route split into its path, query, and fragment, so they can be read and changed without
+/// parsing the route string again.
+///
+/// Its `Display` implementation assembles the route string again.
+///
+/// It can also be used to build routes, with the `with_` methods, which percent-encode what they
+/// are given with the default [`RouteEncoding`](struct.RouteEncoding.html), and insert the `?` and
+/// `#` separators that the route needs.
+/// Other encodings can be applied by assigning the fields, like
+/// `parts.path = encoding.path.encode(path)`.
+///
+/// # Example
+/// ```
+/// use yew_router::route::{Route, RouteParts};
+///
+/// let mut parts = RouteParts::new("/users?page=2#top");
+/// assert_eq!(parts.query(), Some("page=2"));
+/// parts.fragment = None;
+/// let route: Route<()> = parts.into();
+/// assert_eq!(route.route, "/users?page=2");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RouteParts {
+    /// The path, like `/users`.
+    pub path: String,
+    /// The query without its `?`, like `page=2`, if the route has one.
+    pub query: Option<String>,
+    /// The fragment without its `#`, like `top`, if the route has one.
+    pub fragment: Option<String>,
+}
+
+impl RouteParts {
+    /// Splits a route string into its parts.
+    ///
+    /// A `?` or `#` that isn't followed by anything still makes for an empty query or fragment, so
+    /// the route string is assembled the same way again.
+    pub fn new(route: &str) -> Self {
+        let (query_start, fragment_start) = section_starts(route);
+        let section = |start: usize, end: usize| {
+            if start < end {
+                Some(route[start + 1..end].to_string())
+            } else {
+                None
+            }
+        };
+        RouteParts {
+            path: route[..query_start].to_string(),
+            query: section(query_start, fragment_start),
+            fragment: section(fragment_start, route.len()),
+        }
+    }
+
+    /// Gets the path.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Gets the query without its `?`.
+    pub fn query(&self) -> Option<&str> {
+        self.query.as_ref().map(String::as_str)
+    }
+
+    /// Gets the fragment without its `#`.
+    pub fn fragment(&self) -> Option<&str> {
+        self.fragment.as_ref().map(String::as_str)
+    }
+
+    /// Sets the path, percent-encoding it.
+    ///
+    /// # Example
+    /// ```
+    /// use yew_router::route::RouteParts;
+    ///
+    /// let parts = RouteParts::default()
+    ///     .with_path("/search/rust lang")
+    ///     .with_query_pair("q", "a&b")
+    ///     .with_fragment("#results");
+    /// assert_eq!(parts.to_string(), "/search/rust%20lang?q=a%26b#results");
+    /// ```
+    pub fn with_path(self, path: &str) -> Self {
+        RouteParts {
+            path: RouteEncoding::default().path.encode(path),
+            ..self
+        }
+    }
+
+    /// Replaces the query with the pairs, leaving it out if there are none.
+    pub fn with_query_pairs(self, pairs: &QueryPairs) -> Self {
+        let query = if pairs.is_empty() {
+            None
+        } else {
+            Some(pairs.to_string())
+        };
+        RouteParts { query, ..self }
+    }
+
+    /// Adds a pair to the query, percent-encoding its key and value.
+    pub fn with_query_pair(self, key: &str, value: &str) -> Self {
+        let mut pairs = QueryPairs::parse(self.query().unwrap_or_default());
+        pairs.append(key, value);
+        self.with_query_pairs(&pairs)
+    }
+
+    /// Sets the fragment, percent-encoding it, or removes it if it is empty.
+    ///
+    /// A leading `#` is not part of the fragment.
+    pub fn with_fragment(self, fragment: &str) -> Self {
+        let fragment = fragment.trim_start_matches('#');
+        let fragment = if fragment.is_empty() {
+            None
+        } else {
+            Some(RouteEncoding::default().fragment.encode(fragment))
+        };
+        RouteParts { fragment, ..self }
+    }
+}
+
+impl fmt::Display for RouteParts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.path)?;
+        if let Some(query) = &self.query {
+            write!(f, "?{}", query)?;
+        }
+        if let Some(fragment) = &self.fragment {
+            write!(f, "#{}", fragment)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<&str> for RouteParts {
+    fn from(route: &str) -> Self {
+        RouteParts::new(route)
+    }
+}
+
+impl<T> From<RouteParts> for Route<T> {
+    fn from(parts: RouteParts) -> Self {
+        Route {
+            route: parts.to_string(),
+            state: None,
+        }
+    }
+}
+
+/// Gets the origin of the current page, if running in a browser.
+#[cfg(feature = "service")]
+fn current_origin() -> Option<String> {
+    if cfg!(target_arch = "wasm32") {
+        stdweb::web::window().location()?.origin().ok()
+    } else {
+        None
+    }
+}
+
+/// Without the `service` feature, the origin of the current page is never known.
+#[cfg(not(feature = "service"))]
+fn current_origin() -> Option<String> {
+    None
 }
 
+/// Creates a route from a url, checking that its origin, if it has one, is the expected one.
+fn parse_url<T>(url: &str, expected_origin: Option<&str>) -> Result<Route<T>, RouteParseError> {
+    let route = if url.starts_with("//") {
+        // Protocol relative urls use the scheme of the current page.
+        let (authority, route) = split_authority(&url[2..]);
+        if let Some(expected_origin) = expected_origin {
+            let scheme = expected_origin
+                .find("://")
+                .map_or("", |end| &expected_origin[..end]);
+            check_origin(&origin(scheme, authority), expected_origin)?;
+        }
+        route
+    } else if url.starts_with(|c| c == '/' || c == '?' || c == '#') {
+        url
+    } else {
+        let scheme_end = url
+            .find(':')
+            .filter(|end| is_scheme(&url[..*end]))
+            .ok_or(RouteParseError::NotARoute)?;
+        let rest = &url[scheme_end + 1..];
+        if !rest.starts_with("//") {
+            // Urls like `mailto:` ones don't have a path that could be a route.
+            return Err(RouteParseError::NotARoute);
+        }
+        let (authority, route) = split_authority(&rest[2..]);
+        if let Some(expected_origin) = expected_origin {
+            check_origin(&origin(&url[..scheme_end], authority), expected_origin)?;
+        }
+        route
+    };
+    let route = if route.starts_with('/') {
+        route.to_string()
+    } else {
+        // Urls without a path, like `https://example.com?page=2`, are at the root.
+        format!("/{}", route)
+    };
+    Ok(Route { route, state: None })
+}
+
+/// Splits the authority, like `user@example.com:8080`, off of the rest of a url.
+fn split_authority(url: &str) -> (&str, &str) {
+    let end = url
+        .find(|c| c == '/' || c == '?' || c == '#')
+        .unwrap_or_else(|| url.len());
+    url.split_at(end)
+}
+
+/// Determines if the text is a url scheme, like `https`.
+fn is_scheme(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars.next().map_or(false, |c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.')
+}
+
+/// Formats the origin of a url the way browsers do, without user info and default ports.
+fn origin(scheme: &str, authority: &str) -> String {
+    let scheme = scheme.to_ascii_lowercase();
+    let host = authority
+        .rfind('@')
+        .map_or(authority, |end| &authority[end + 1..])
+        .to_ascii_lowercase();
+    let default_port = match scheme.as_str() {
+        "http" | "ws" => Some(":80"),
+        "https" | "wss" => Some(":443"),
+        _ => None,
+    };
+    let host = match default_port {
+        Some(port) if host.ends_with(port) => &host[..host.len() - port.len()],
+        _ => &host,
+    };
+    format!("{}://{}", scheme, host)
+}
+
+fn check_origin(origin: &str, expected_origin: &str) -> Result<(), RouteParseError> {
+    if origin == expected_origin {
+        Ok(())
+    } else {
+        Err(RouteParseError::ForeignOrigin(origin.to_string()))
+    }
+}
+
+/// Error for when a url can't be turned into a route.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RouteParseError {
+    /// The url is neither absolute, nor a route starting with `/`, `?`, or `#`, or it can't have a
+    /// path, like `mailto:` urls.
+    NotARoute,
+    /// The url has an origin other than the one of the current page.
+    ForeignOrigin(String),
+}
+
+impl fmt::Display for RouteParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RouteParseError::NotARoute => f.write_str("the url doesn't contain a route"),
+            RouteParseError::ForeignOrigin(origin) => write!(
+                f,
+                "the url's origin '{}' isn't the origin of the current page",
+                origin
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RouteParseError {}
+
 impl<T> fmt::Display for Route<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.route.fmt(f)
@@ -70,4 +655,273 @@ impl<T> Deref for Route<T> {
     }
 }
 
-impl<T> Transferable for Route<T> where for<'de> T: Serialize + Deserialize<'de> {}
+#[cfg(feature = "service")]
+impl<T: RouterState> Transferable for Route<T> {}
+
+/// Formats the path, query, and fragment of a url into a route string.
+#[cfg(feature = "url")]
+pub(crate) fn route_string_from_url(url: &url::Url) -> String {
+    let query = url.query().map(|query| format!("?{}", query));
+    let fragment = url.fragment().map(|fragment| format!("#{}", fragment));
+    format_route_string(
+        url.path(),
+        query.as_ref().map_or("", String::as_str),
+        fragment.as_ref().map_or("", String::as_str),
+    )
+}
+
+/// Creates a route from the path, query, and fragment of the url.
+///
+/// The scheme, host, and port are discarded.
+/// Unlike the `TryFrom<&Url>` implementation, this doesn't check that the url has a path that
+/// could be a route.
+#[cfg(feature = "url")]
+impl<T> From<url::Url> for Route<T> {
+    fn from(url: url::Url) -> Self {
+        Route {
+            route: route_string_from_url(&url),
+            state: None,
+        }
+    }
+}
+
+/// Creates a route from the path, query, and fragment of the url.
+///
+/// Fails if the url can't be a base, like `mailto:` urls, as their path isn't a route.
+#[cfg(feature = "url")]
+impl<T> std::convert::TryFrom<&url::Url> for Route<T> {
+    type Error = UrlNotARouteError;
+
+    fn try_from(url: &url::Url) -> Result<Self, Self::Error> {
+        if url.cannot_be_a_base() {
+            return Err(UrlNotARouteError);
+        }
+        Ok(Route {
+            route: route_string_from_url(url),
+            state: None,
+        })
+    }
+}
+
+/// Error for when a url doesn't contain a route.
+#[cfg(feature = "url")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UrlNotARouteError;
+
+#[cfg(feature = "url")]
+impl fmt::Display for UrlNotARouteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the url can't be a base, so its path isn't a route")
+    }
+}
+
+#[cfg(feature = "url")]
+impl std::error::Error for UrlNotARouteError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splice_replaces_provided_sections() {
+        let route = "/search?q=rust#results";
+        assert_eq!(
+            splice_route_string(route, Some("/find"), None, None),
+            "/find?q=rust#results"
+        );
+        assert_eq!(
+            splice_route_string(route, None, Some("q=yew"), None),
+            "/search?q=yew#results"
+        );
+        assert_eq!(
+            splice_route_string(route, None, None, Some("#top")),
+            "/search?q=rust#top"
+        );
+        assert_eq!(
+            splice_route_string("/search", None, Some("?q=yew"), Some("top")),
+            "/search?q=yew#top"
+        );
+    }
+
+    #[test]
+    fn splice_removes_empty_sections() {
+        assert_eq!(
+            splice_route_string("/search?q=rust#results", None, Some(""), Some("")),
+            "/search"
+        );
+    }
+
+    #[test]
+    fn resolves_relative_routes() {
+        let current = "/users/5/edit?tab=name#top";
+        assert_eq!(
+            resolve_relative_route(current, "details"),
+            "/users/5/edit/details"
+        );
+        assert_eq!(resolve_relative_route(current, "../view"), "/users/5/view");
+        assert_eq!(
+            resolve_relative_route(current, "../../../../about?from=user"),
+            "/about?from=user"
+        );
+        assert_eq!(resolve_relative_route(current, "/about"), "/about");
+        assert_eq!(
+            resolve_relative_route(current, "?tab=email"),
+            "/users/5/edit?tab=email"
+        );
+        assert_eq!(
+            resolve_relative_route(current, "#bottom"),
+            "/users/5/edit?tab=name#bottom"
+        );
+        assert_eq!(resolve_relative_route("/", "users"), "/users");
+        assert_eq!(resolve_relative_route("/users/", "5"), "/users/5");
+    }
+
+    #[test]
+    fn prefixes_respect_segment_boundaries() {
+        let route = Route::<()>::from("/foo/bar?q=1#top");
+        assert!(route.starts_with(&Route::<()>::from("/foo")));
+        assert!(route.starts_with(&Route::<()>::from("/foo/")));
+        assert!(route.starts_with(&Route::<()>::from("/")));
+        assert!(!route.starts_with(&Route::<()>::from("/fo")));
+        assert!(!Route::<()>::from("/foobar").starts_with(&Route::<()>::from("/foo")));
+
+        let base = Route::<()>::from("/foo");
+        assert_eq!(route.relative_to(&base).unwrap().route, "/bar?q=1#top");
+        assert_eq!(
+            Route::<()>::from("/foo?q=1")
+                .relative_to(&base)
+                .unwrap()
+                .route,
+            "?q=1"
+        );
+        assert!(Route::<()>::from("/foobar").relative_to(&base).is_none());
+
+        let shared = |a: &str, b: &str| Route::<()>::from(a).shared_prefix(&Route::<()>::from(b));
+        assert_eq!(shared("/users/5/edit", "/users/6").route, "/users");
+        assert_eq!(shared("/users/5", "/users/5?tab=2").route, "/users/5");
+        assert_eq!(shared("/foo", "/foobar").route, "/");
+        assert_eq!(shared("/foo/", "/foo/bar").route, "/foo");
+    }
+
+    #[test]
+    fn parses_absolute_urls() {
+        let route = Route::<()>::parse("https://example.com/users?page=2#top").unwrap();
+        assert_eq!(route.route, "/users?page=2#top");
+        assert_eq!(route.path(), "/users");
+        assert_eq!(route.query(), Some("page=2"));
+        assert_eq!(route.fragment(), Some("top"));
+
+        let route = Route::<()>::parse("https://example.com?page=2").unwrap();
+        assert_eq!(route.route, "/?page=2");
+        assert_eq!(route.fragment(), None);
+        assert_eq!(Route::<()>::parse("//example.com").unwrap().route, "/");
+        assert_eq!(Route::<()>::parse("/users#top").unwrap().query(), None);
+        assert_eq!(
+            Route::<()>::parse("mailto:user@example.com"),
+            Err(RouteParseError::NotARoute)
+        );
+        assert_eq!(Route::<()>::parse("users"), Err(RouteParseError::NotARoute));
+    }
+
+    #[test]
+    fn splits_routes_into_parts() {
+        let parts = RouteParts::new("/users?page=2#top");
+        assert_eq!(parts.path(), "/users");
+        assert_eq!(parts.query(), Some("page=2"));
+        assert_eq!(parts.fragment(), Some("top"));
+
+        let parts = RouteParts::new("/users#top?page=2");
+        assert_eq!(parts.query(), None);
+        assert_eq!(parts.fragment(), Some("top?page=2"));
+
+        for route in &["/users?page=2#top", "/users", "/users?#", "?page=2", ""] {
+            assert_eq!(RouteParts::new(route).to_string(), *route);
+            let whole = Route::<()>::from(*route);
+            assert_eq!(RouteParts::new(route).query(), whole.query());
+            assert_eq!(RouteParts::new(route).fragment(), whole.fragment());
+        }
+    }
+
+    #[test]
+    fn builds_routes_from_parts() {
+        let parts = RouteParts::default()
+            .with_path("/posts/a b")
+            .with_query_pair("tag", "c&d")
+            .with_query_pair("page", "2")
+            .with_fragment("#top");
+        assert_eq!(parts.to_string(), "/posts/a%20b?tag=c%26d&page=2#top");
+
+        let parts = RouteParts::new("/posts?tag=a#top")
+            .with_query_pairs(&QueryPairs::default())
+            .with_fragment("");
+        assert_eq!(parts.to_string(), "/posts");
+
+        assert_eq!(
+            format_route_string("/posts", "tag=a", "top"),
+            "/posts?tag=a#top"
+        );
+        assert_eq!(
+            format_route_string("/posts", "?tag=a", "#top"),
+            "/posts?tag=a#top"
+        );
+        assert_eq!(format_route_string("/posts", "?", "#"), "/posts");
+    }
+
+    #[test]
+    fn query_pairs_stay_in_sync() {
+        let mut route = Route::<()>::from("/posts?tag=a&tag=b#top");
+        assert_eq!(route.query_pairs().get_all("tag"), vec!["a", "b"]);
+        route.append_query_pair("tag", "c d");
+        assert_eq!(route.route, "/posts?tag=a&tag=b&tag=c%20d#top");
+        route.set_query_pair("tag", "e");
+        assert_eq!(route.route, "/posts?tag=e#top");
+        route.remove_query_pair("tag");
+        assert_eq!(route.route, "/posts#top");
+    }
+
+    #[test]
+    fn remembers_local_intended_routes() {
+        let login =
+            Route::<()>::from("/login#form").with_intended_route(&Route::<()>::from("/a b"));
+        assert_eq!(login.route, "/login?return_to=/a%20b#form");
+        assert_eq!(login.intended_route(), Some("/a b".to_string()));
+
+        let intended = |route: &str| Route::<()>::from(route).intended_route();
+        assert_eq!(intended("/login"), None);
+        assert_eq!(intended("/login?return_to=https://example.com"), None);
+        assert_eq!(intended("/login?return_to=//example.com"), None);
+        assert_eq!(intended("/login?return_to=/%5Cexample.com"), None);
+        assert_eq!(intended("/login?return_to=/\t/example.com"), None);
+        assert_eq!(intended("/login?return_to=/%09/example.com"), None);
+        assert_eq!(intended("/login?return_to=%0A//example.com"), None);
+        assert_eq!(
+            intended("/login?return_to=/a%0D%0A/b"),
+            Some("/a/b".to_string())
+        );
+    }
+
+    #[test]
+    fn checks_the_origin() {
+        let expected = Some("https://example.com");
+        let parse = |url: &str| parse_url::<()>(url, expected).map(|route| route.route);
+        assert_eq!(parse("https://example.com/a"), Ok("/a".to_string()));
+        assert_eq!(
+            parse("HTTPS://user@Example.com:443/a"),
+            Ok("/a".to_string())
+        );
+        assert_eq!(parse("//example.com/a"), Ok("/a".to_string()));
+        assert_eq!(parse("/a"), Ok("/a".to_string()));
+        assert_eq!(
+            parse("http://example.com/a"),
+            Err(RouteParseError::ForeignOrigin(
+                "http://example.com".to_string()
+            ))
+        );
+        assert_eq!(
+            parse("//other.com/a"),
+            Err(RouteParseError::ForeignOrigin(
+                "https://other.com".to_string()
+            ))
+        );
+    }
+}
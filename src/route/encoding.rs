@@ -0,0 +1,204 @@
+//! Configures which characters are percent-encoded when routes are built.
+use crate::{route::Route, switch::Switch};
+
+/// Sets the bits of the ASCII characters.
+macro_rules! ascii {
+    ($($character: expr),*) => {
+        0 $(| 1u128 << $character)*
+    };
+}
+
+/// The characters that are percent-encoded when written into a route, following the
+/// [percent-encode sets](https://url.spec.whatwg.org/#percent-encoded-bytes) of the URL standard.
+///
+/// Bytes that aren't ASCII, like those of unicode slugs, are always encoded.
+///
+/// # Example
+/// ```
+/// use yew_router::route::EncodeSet;
+///
+/// let set = EncodeSet::QUERY.with("+&");
+/// assert_eq!(set.encode("a+b c&d"), "a%2Bb%20c%26d");
+/// assert_eq!(EncodeSet::PATH.encode("/café"), "/caf%C3%A9");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct EncodeSet(u128);
+
+impl EncodeSet {
+    /// The C0 control percent-encode set, which only encodes control characters.
+    pub const CONTROLS: EncodeSet = EncodeSet(((1 << 0x20) - 1) | 1 << 0x7F);
+    /// The fragment percent-encode set.
+    pub const FRAGMENT: EncodeSet =
+        EncodeSet(Self::CONTROLS.0 | ascii!(b' ', b'"', b'<', b'>', b'`'));
+    /// The query percent-encode set.
+    pub const QUERY: EncodeSet = EncodeSet(Self::CONTROLS.0 | ascii!(b' ', b'"', b'#', b'<', b'>'));
+    /// The path percent-encode set.
+    pub const PATH: EncodeSet = EncodeSet(Self::QUERY.0 | ascii!(b'?', b'`', b'{', b'}'));
+    /// The userinfo percent-encode set.
+    pub const USERINFO: EncodeSet = EncodeSet(
+        Self::PATH.0 | ascii!(b'/', b':', b';', b'=', b'@', b'[', b'\\', b']', b'^', b'|'),
+    );
+    /// The component percent-encode set, which also encodes the characters that separate the
+    /// pairs of a query and the sections of a path.
+    pub const COMPONENT: EncodeSet =
+        EncodeSet(Self::USERINFO.0 | ascii!(b'$', b'%', b'&', b'+', b','));
+    /// The `application/x-www-form-urlencoded` percent-encode set.
+    pub const FORM: EncodeSet =
+        EncodeSet(Self::COMPONENT.0 | ascii!(b'!', b'\'', b'(', b')', b'~'));
+
+    /// Combines the characters of both sets.
+    pub fn union(self, other: EncodeSet) -> Self {
+        EncodeSet(self.0 | other.0)
+    }
+
+    /// Adds the ASCII characters to the set.
+    pub fn with(self, characters: &str) -> Self {
+        let bits = characters
+            .bytes()
+            .filter(u8::is_ascii)
+            .fold(0, |bits, byte| bits | 1 << byte);
+        EncodeSet(self.0 | bits)
+    }
+
+    /// Removes the ASCII characters from the set.
+    pub fn without(self, characters: &str) -> Self {
+        let bits = characters
+            .bytes()
+            .filter(u8::is_ascii)
+            .fold(0, |bits, byte| bits | 1 << byte);
+        EncodeSet(self.0 & !bits)
+    }
+
+    /// Whether the byte is encoded.
+    pub fn contains(&self, byte: u8) -> bool {
+        !byte.is_ascii() || self.0 & 1 << byte != 0
+    }
+
+    /// Percent-encodes the bytes of the text that are in the set.
+    pub fn encode(&self, text: &str) -> String {
+        let mut encoded = String::with_capacity(text.len());
+        for byte in text.bytes() {
+            if self.contains(byte) {
+                encoded.push_str(&format!("%{:02X}", byte));
+            } else {
+                encoded.push(char::from(byte));
+            }
+        }
+        encoded
+    }
+}
+
+/// The sets of characters that are percent-encoded in each section of a route when it is built.
+///
+/// It defaults to the sets that browsers use for urls, so spaces, quotes, and unicode are encoded,
+/// but `%` is not, so values that are already encoded aren't encoded again.
+/// Values captured in the query are encoded with `EncodeSet::COMPONENT` as well, so the `&`, `=`,
+/// `+`, and `%` within them can't be mistaken for the structure of the query.
+///
+/// Reverse routing only encodes the values it captures when the route is built with
+/// `RouteEncoding::build_route`, and `RouteParts` encodes the path and fragment it is given with
+/// the default encoding.
+///
+/// # Example
+/// ```
+/// use yew_router::{
+///     route::{EncodeSet, Route, RouteEncoding},
+///     Switch,
+/// };
+///
+/// #[derive(Switch)]
+/// enum AppRoute {
+///     #[to = "/tags/{tag}?related={related}"]
+///     Tag { tag: String, related: String },
+/// }
+///
+/// let encoding = RouteEncoding {
+///     path: EncodeSet::PATH.with("+"),
+///     ..RouteEncoding::default()
+/// };
+/// let route: Route<()> = encoding.build_route(AppRoute::Tag {
+///     tag: "c++ lang".to_string(),
+///     related: "R&D".to_string(),
+/// });
+/// assert_eq!(route.route, "/tags/c%2B%2B%20lang?related=R%26D");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RouteEncoding {
+    /// The characters encoded in the path.
+    pub path: EncodeSet,
+    /// The characters encoded in the query.
+    pub query: EncodeSet,
+    /// The characters encoded in the fragment.
+    pub fragment: EncodeSet,
+}
+
+impl Default for RouteEncoding {
+    fn default() -> Self {
+        RouteEncoding {
+            path: EncodeSet::PATH,
+            query: EncodeSet::QUERY,
+            fragment: EncodeSet::FRAGMENT,
+        }
+    }
+}
+
+impl RouteEncoding {
+    /// Builds the route of a switch, percent-encoding the values that it captures.
+    pub fn build_route<SW: Switch, T>(&self, switch: SW) -> Route<T> {
+        let mut route = String::new();
+        let state = switch.build_encoded_route_section(&mut route, Some(self));
+        Route { route, state }
+    }
+
+    /// Writes a value captured by reverse routing to the end of the route, encoding it with the
+    /// set of the section that the route ends in, if there is an encoding.
+    pub(crate) fn write_capture(route: &mut String, capture: &str, encoding: Option<&Self>) {
+        match encoding {
+            Some(encoding) => {
+                let set = if route.contains('#') {
+                    encoding.fragment
+                } else if route.contains('?') {
+                    encoding.query.union(EncodeSet::COMPONENT)
+                } else {
+                    encoding.path
+                };
+                route.push_str(&set.encode(capture))
+            }
+            None => route.push_str(capture),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sets_follow_the_url_standard() {
+        assert_eq!(EncodeSet::PATH.encode("/a b/{c}?"), "/a%20b/%7Bc%7D%3F");
+        assert_eq!(EncodeSet::QUERY.encode("a=b c&d+e?"), "a=b%20c&d+e?");
+        assert_eq!(EncodeSet::FRAGMENT.encode("#a b"), "#a%20b");
+        assert_eq!(EncodeSet::COMPONENT.encode("a/b+c%"), "a%2Fb%2Bc%25");
+        assert_eq!(EncodeSet::CONTROLS.encode("\n~ü"), "%0A~%C3%BC");
+        assert_eq!(EncodeSet::PATH.without(" ü").encode("a bü"), "a b%C3%BC");
+    }
+
+    #[test]
+    fn captures_are_encoded_for_their_section() {
+        let mut route = "/a/".to_string();
+        RouteEncoding::write_capture(&mut route, "b c?", None);
+        assert_eq!(route, "/a/b c?");
+
+        let encoding = RouteEncoding {
+            path: EncodeSet::PATH.with("+"),
+            ..RouteEncoding::default()
+        };
+        let mut route = "/a/".to_string();
+        RouteEncoding::write_capture(&mut route, "b+c?", Some(&encoding));
+        route.push_str("?q=");
+        RouteEncoding::write_capture(&mut route, "d&e=f+g%", Some(&encoding));
+        route.push('#');
+        RouteEncoding::write_capture(&mut route, "h`", Some(&encoding));
+        assert_eq!(route, "/a/b%2Bc%3F?q=d%26e%3Df%2Bg%25#h%60");
+    }
+}
@@ -0,0 +1,215 @@
+//! The query parameters of a route, as an ordered multimap.
+
+use crate::strategy::{percent_decode, percent_encode};
+use serde::{Deserialize, Serialize};
+use std::{fmt, mem};
+
+mod serializer;
+pub use serializer::QueryError;
+
+/// The `key=value` pairs of a query, in the order they appear in the route.
+///
+/// Keys may be repeated, like in `?tag=a&tag=b`, so every value of a key is kept.
+/// Keys and values are percent-decoded, and a `+` is decoded as a space.
+///
+/// Its `Display` implementation formats the pairs as a percent-encoded query without its `?`.
+///
+/// # Example
+/// ```
+/// use yew_router::route::QueryPairs;
+///
+/// let mut pairs = QueryPairs::parse("?tag=a&tag=b&page=2");
+/// assert_eq!(pairs.get_all("tag"), vec!["a", "b"]);
+/// pairs.set("page", "3");
+/// pairs.remove("tag");
+/// assert_eq!(pairs.to_string(), "page=3");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct QueryPairs(Vec<(String, String)>);
+
+impl QueryPairs {
+    /// Parses a query, which may start with its `?`.
+    ///
+    /// Pairs without a `=` get an empty value.
+    pub fn parse(query: &str) -> Self {
+        let decode = |text: &str| percent_decode(&text.replace('+', " "));
+        let pairs = query
+            .trim_start_matches('?')
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let mut split = pair.splitn(2, '=');
+                let key = split.next().unwrap_or_default();
+                let value = split.next().unwrap_or_default();
+                (decode(key), decode(value))
+            })
+            .collect();
+        QueryPairs(pairs)
+    }
+
+    /// Creates the pairs from the fields of a struct, or the entries of a map.
+    ///
+    /// Fields that are `None` are left out, sequences become a pair for each of their values, and
+    /// unit variants of enums are written as their names.
+    /// Fields that are structs, maps, or enum variants with fields can't be written as a single
+    /// value, so they are rejected.
+    ///
+    /// # Example
+    /// ```
+    /// use serde::Serialize;
+    /// use yew_router::route::QueryPairs;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Search {
+    ///     q: String,
+    ///     tag: Vec<&'static str>,
+    ///     page: Option<u32>,
+    /// }
+    ///
+    /// let search = Search {
+    ///     q: "rust lang".to_string(),
+    ///     tag: vec!["a", "b"],
+    ///     page: None,
+    /// };
+    /// let pairs = QueryPairs::from_serialize(&search).unwrap();
+    /// assert_eq!(pairs.to_string(), "q=rust%20lang&tag=a&tag=b");
+    /// ```
+    pub fn from_serialize<S: Serialize + ?Sized>(value: &S) -> Result<Self, QueryError> {
+        let mut pairs = Vec::new();
+        value.serialize(serializer::PairsSerializer::new(&mut pairs))?;
+        Ok(QueryPairs(pairs))
+    }
+
+    /// Gets the first value of the key.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.iter()
+            .find(|(pair_key, _)| *pair_key == key)
+            .map(|(_, value)| value)
+    }
+
+    /// Gets every value of the key, in order.
+    pub fn get_all(&self, key: &str) -> Vec<&str> {
+        self.iter()
+            .filter(|(pair_key, _)| *pair_key == key)
+            .map(|(_, value)| value)
+            .collect()
+    }
+
+    /// Determines if the key has a value.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Iterates over the pairs, in order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    /// Adds a value for the key after the existing pairs, keeping the key's other values.
+    pub fn append(&mut self, key: &str, value: &str) {
+        self.0.push((key.to_string(), value.to_string()));
+    }
+
+    /// Replaces every value of the key with a single value.
+    ///
+    /// The value takes the place of the key's first value, or is added after the existing pairs if
+    /// the key doesn't have a value yet.
+    pub fn set(&mut self, key: &str, value: &str) {
+        match self.0.iter().position(|(pair_key, _)| pair_key == key) {
+            Some(index) => {
+                self.0[index].1 = value.to_string();
+                let mut is_first = true;
+                self.0
+                    .retain(|(pair_key, _)| pair_key != key || mem::replace(&mut is_first, false));
+            }
+            None => self.append(key, value),
+        }
+    }
+
+    /// Removes every value of the key, returning whether it had any.
+    pub fn remove(&mut self, key: &str) -> bool {
+        let len = self.0.len();
+        self.0.retain(|(pair_key, _)| pair_key != key);
+        self.0.len() != len
+    }
+
+    /// Gets the number of pairs.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Determines if there are no pairs.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Display for QueryPairs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, (key, value)) in self.0.iter().enumerate() {
+            if index > 0 {
+                f.write_str("&")?;
+            }
+            write!(f, "{}={}", percent_encode(key), percent_encode(value))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_repeated_keys_in_order() {
+        let pairs = QueryPairs::parse("?tag=a&page=2&tag=b");
+        assert_eq!(pairs.get("tag"), Some("a"));
+        assert_eq!(pairs.get_all("tag"), vec!["a", "b"]);
+        assert_eq!(
+            pairs.iter().collect::<Vec<_>>(),
+            vec![("tag", "a"), ("page", "2"), ("tag", "b")]
+        );
+        assert_eq!(pairs.to_string(), "tag=a&page=2&tag=b");
+    }
+
+    #[test]
+    fn decodes_and_encodes() {
+        let pairs = QueryPairs::parse("q=rust+lang&name=%C3%A9&flag");
+        assert_eq!(pairs.get("q"), Some("rust lang"));
+        assert_eq!(pairs.get("name"), Some("é"));
+        assert_eq!(pairs.get("flag"), Some(""));
+        assert_eq!(pairs.to_string(), "q=rust%20lang&name=%C3%A9&flag=");
+    }
+
+    #[test]
+    fn serializes_maps_and_rejects_nested_values() {
+        use std::collections::BTreeMap;
+
+        let mut map = BTreeMap::new();
+        map.insert("sort", Some("name"));
+        map.insert("filter", None);
+        assert_eq!(
+            QueryPairs::from_serialize(&map).unwrap().to_string(),
+            "sort=name"
+        );
+
+        let mut nested = BTreeMap::new();
+        nested.insert("inner", map);
+        assert!(QueryPairs::from_serialize(&nested).is_err());
+        assert!(QueryPairs::from_serialize(&5).is_err());
+    }
+
+    #[test]
+    fn set_replaces_every_value() {
+        let mut pairs = QueryPairs::parse("tag=a&page=2&tag=b");
+        pairs.set("tag", "c");
+        assert_eq!(pairs.to_string(), "tag=c&page=2");
+        pairs.set("sort", "name");
+        assert_eq!(pairs.to_string(), "tag=c&page=2&sort=name");
+        assert!(pairs.remove("tag"));
+        assert!(!pairs.remove("tag"));
+        assert_eq!(pairs.to_string(), "page=2&sort=name");
+    }
+}
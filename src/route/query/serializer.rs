@@ -0,0 +1,382 @@
+//! Serializes structs and maps into the pairs of a query.
+use serde::ser::{self, Impossible, Serialize, SerializeMap, SerializeSeq, SerializeStruct};
+use std::{error::Error, fmt};
+
+/// The reason why a value couldn't be serialized into a query.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueryError(String);
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Error for QueryError {}
+
+impl ser::Error for QueryError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        QueryError(msg.to_string())
+    }
+}
+
+/// Fails because `what` has no representation in a query.
+fn unsupported<T>(what: &str) -> Result<T, QueryError> {
+    Err(QueryError(format!(
+        "{} can't be serialized into a query",
+        what
+    )))
+}
+
+/// Implements the methods for values that are written as text, by passing the text to `scalar`.
+macro_rules! serialize_scalars {
+    ($($method:ident: $ty:ty),*) => {
+        $(
+            fn $method(self, v: $ty) -> Result<Self::Ok, Self::Error> {
+                self.scalar(v.to_string())
+            }
+        )*
+        fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+            self.scalar(v.to_string())
+        }
+        fn serialize_bytes(self, _v: &[u8]) -> Result<Self::Ok, Self::Error> {
+            unsupported("Bytes")
+        }
+        fn serialize_unit_variant(
+            self,
+            _name: &'static str,
+            _variant_index: u32,
+            variant: &'static str,
+        ) -> Result<Self::Ok, Self::Error> {
+            self.scalar(variant.to_string())
+        }
+        fn serialize_newtype_struct<T: ?Sized + Serialize>(
+            self,
+            _name: &'static str,
+            value: &T,
+        ) -> Result<Self::Ok, Self::Error> {
+            value.serialize(self)
+        }
+        fn serialize_newtype_variant<T: ?Sized + Serialize>(
+            self,
+            _name: &'static str,
+            _variant_index: u32,
+            _variant: &'static str,
+            _value: &T,
+        ) -> Result<Self::Ok, Self::Error> {
+            unsupported("Enum variants with fields")
+        }
+        fn serialize_tuple_struct(
+            self,
+            _name: &'static str,
+            _len: usize,
+        ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+            unsupported("Tuple structs")
+        }
+        fn serialize_tuple_variant(
+            self,
+            _name: &'static str,
+            _variant_index: u32,
+            _variant: &'static str,
+            _len: usize,
+        ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+            unsupported("Enum variants with fields")
+        }
+        fn serialize_struct_variant(
+            self,
+            _name: &'static str,
+            _variant_index: u32,
+            _variant: &'static str,
+            _len: usize,
+        ) -> Result<Self::SerializeStructVariant, Self::Error> {
+            unsupported("Enum variants with fields")
+        }
+    };
+}
+
+/// Serializes the fields of a struct, or the entries of a map, into pairs.
+pub(super) struct PairsSerializer<'a> {
+    pairs: &'a mut Vec<(String, String)>,
+    /// The key of the map entry whose value is serialized next.
+    key: Option<String>,
+}
+
+impl<'a> PairsSerializer<'a> {
+    pub(super) fn new(pairs: &'a mut Vec<(String, String)>) -> Self {
+        PairsSerializer { pairs, key: None }
+    }
+
+    fn scalar(self, _text: String) -> Result<(), QueryError> {
+        unsupported("Values outside of a struct or map")
+    }
+}
+
+impl<'a> ser::Serializer for PairsSerializer<'a> {
+    type Ok = ();
+    type Error = QueryError;
+    type SerializeSeq = Impossible<(), QueryError>;
+    type SerializeTuple = Impossible<(), QueryError>;
+    type SerializeTupleStruct = Impossible<(), QueryError>;
+    type SerializeTupleVariant = Impossible<(), QueryError>;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Impossible<(), QueryError>;
+
+    serialize_scalars!(
+        serialize_bool: bool,
+        serialize_i8: i8,
+        serialize_i16: i16,
+        serialize_i32: i32,
+        serialize_i64: i64,
+        serialize_u8: u8,
+        serialize_u16: u16,
+        serialize_u32: u32,
+        serialize_u64: u64,
+        serialize_f32: f32,
+        serialize_f64: f64,
+        serialize_char: char
+    );
+
+    fn serialize_none(self) -> Result<(), QueryError> {
+        Ok(())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), QueryError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), QueryError> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), QueryError> {
+        Ok(())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, QueryError> {
+        unsupported("Sequences outside of a struct or map")
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, QueryError> {
+        unsupported("Tuples")
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self, QueryError> {
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self, QueryError> {
+        Ok(self)
+    }
+}
+
+impl<'a> SerializeMap for PairsSerializer<'a> {
+    type Ok = ();
+    type Error = QueryError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), QueryError> {
+        self.key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), QueryError> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| QueryError("A map value was serialized before its key".to_string()))?;
+        value.serialize(ValueSerializer {
+            key: &key,
+            pairs: self.pairs,
+        })
+    }
+
+    fn end(self) -> Result<(), QueryError> {
+        Ok(())
+    }
+}
+
+impl<'a> SerializeStruct for PairsSerializer<'a> {
+    type Ok = ();
+    type Error = QueryError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), QueryError> {
+        value.serialize(ValueSerializer {
+            key,
+            pairs: self.pairs,
+        })
+    }
+
+    fn end(self) -> Result<(), QueryError> {
+        Ok(())
+    }
+}
+
+/// Serializes a key of a map as text.
+struct KeySerializer;
+
+impl KeySerializer {
+    fn scalar(self, text: String) -> Result<String, QueryError> {
+        Ok(text)
+    }
+}
+
+impl ser::Serializer for KeySerializer {
+    type Ok = String;
+    type Error = QueryError;
+    type SerializeSeq = Impossible<String, QueryError>;
+    type SerializeTuple = Impossible<String, QueryError>;
+    type SerializeTupleStruct = Impossible<String, QueryError>;
+    type SerializeTupleVariant = Impossible<String, QueryError>;
+    type SerializeMap = Impossible<String, QueryError>;
+    type SerializeStruct = Impossible<String, QueryError>;
+    type SerializeStructVariant = Impossible<String, QueryError>;
+
+    serialize_scalars!(
+        serialize_bool: bool,
+        serialize_i8: i8,
+        serialize_i16: i16,
+        serialize_i32: i32,
+        serialize_i64: i64,
+        serialize_u8: u8,
+        serialize_u16: u16,
+        serialize_u32: u32,
+        serialize_u64: u64,
+        serialize_f32: f32,
+        serialize_f64: f64,
+        serialize_char: char
+    );
+
+    fn serialize_none(self) -> Result<String, QueryError> {
+        unsupported("Missing keys")
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<String, QueryError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<String, QueryError> {
+        unsupported("Missing keys")
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String, QueryError> {
+        unsupported("Missing keys")
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, QueryError> {
+        unsupported("Keys that are sequences")
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, QueryError> {
+        unsupported("Keys that are tuples")
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, QueryError> {
+        unsupported("Keys that are maps")
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, QueryError> {
+        unsupported("Keys that are structs")
+    }
+}
+
+/// Serializes a field into pairs with its key, one for each of its values if it is a sequence.
+///
+/// Fields that are `None` aren't serialized.
+struct ValueSerializer<'a, 'k> {
+    key: &'k str,
+    pairs: &'a mut Vec<(String, String)>,
+}
+
+impl<'a, 'k> ValueSerializer<'a, 'k> {
+    fn scalar(self, text: String) -> Result<(), QueryError> {
+        self.pairs.push((self.key.to_string(), text));
+        Ok(())
+    }
+}
+
+impl<'a, 'k> ser::Serializer for ValueSerializer<'a, 'k> {
+    type Ok = ();
+    type Error = QueryError;
+    type SerializeSeq = Self;
+    type SerializeTuple = Impossible<(), QueryError>;
+    type SerializeTupleStruct = Impossible<(), QueryError>;
+    type SerializeTupleVariant = Impossible<(), QueryError>;
+    type SerializeMap = Impossible<(), QueryError>;
+    type SerializeStruct = Impossible<(), QueryError>;
+    type SerializeStructVariant = Impossible<(), QueryError>;
+
+    serialize_scalars!(
+        serialize_bool: bool,
+        serialize_i8: i8,
+        serialize_i16: i16,
+        serialize_i32: i32,
+        serialize_i64: i64,
+        serialize_u8: u8,
+        serialize_u16: u16,
+        serialize_u32: u32,
+        serialize_u64: u64,
+        serialize_f32: f32,
+        serialize_f64: f64,
+        serialize_char: char
+    );
+
+    fn serialize_none(self) -> Result<(), QueryError> {
+        Ok(())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), QueryError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), QueryError> {
+        self.scalar(String::new())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), QueryError> {
+        self.scalar(String::new())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self, QueryError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, QueryError> {
+        unsupported("Fields that are tuples")
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, QueryError> {
+        unsupported("Fields that are maps")
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, QueryError> {
+        unsupported("Fields that are structs")
+    }
+}
+
+impl<'a, 'k> SerializeSeq for ValueSerializer<'a, 'k> {
+    type Ok = ();
+    type Error = QueryError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), QueryError> {
+        value.serialize(ValueSerializer {
+            key: self.key,
+            pairs: self.pairs,
+        })
+    }
+
+    fn end(self) -> Result<(), QueryError> {
+        Ok(())
+    }
+}
@@ -1,28 +1,127 @@
 //! Router Component.
 
 use crate::{
-    agent::{RouteAgentBridge, RouteRequest},
-    route::Route,
+    agent::{DefaultNamespace, RouteAgentBridge, RouteBroadcast, RouteNamespace, RouteRequest},
+    matcher::{Captures, RouteMatcher},
+    route::{Route, RouterState},
+    strategy,
+    switch::{breadcrumbs, FeatureFlags, SwitchError},
     Switch,
 };
 use std::{
+    cell::RefCell,
     fmt::{self, Debug, Error as FmtError, Formatter},
     marker::PhantomData,
+    mem,
     rc::Rc,
 };
 use yew::{
-    html, virtual_dom::VNode, Callback, Component, ComponentLink, Html, Properties, Renderable,
-    ShouldRender,
+    html, html::ChildrenWithProps, virtual_dom::VNode, Callback, Component, ComponentLink, Html,
+    Properties, Renderable, ShouldRender,
 };
 
-use crate::agent::AgentState;
+use guard::GuardState;
+use stdweb::web::set_timeout;
 
-/// Any state that can be managed by the `Router` must meet the criteria of this trait.
-pub trait RouterState<'de>: AgentState<'de> + PartialEq {}
-impl<'de, T> RouterState<'de> for T where T: AgentState<'de> + PartialEq {}
+mod chunk;
+mod context;
+mod focus;
+mod guard;
+mod migrations;
+mod outlet;
+mod phase;
+mod readiness;
+mod restore;
+mod route_view;
+mod routed;
+mod shortcuts;
+mod table;
+pub use chunk::ChunkLoader;
+pub use context::RouteContext;
+pub use focus::FocusManager;
+pub use guard::{GuardResult, PendingGuard};
+pub use migrations::RouteMigrations;
+pub use outlet::{Outlet, OutletProps, OutletRender};
+pub use phase::NavigationPhase;
+use phase::RouterPhase;
+pub use readiness::RouteReadiness;
+pub use restore::{RestoreLastRoute, RouteStorage};
+pub use route_view::{FromSwitch, RouteComponent, RouteView, RouteViewProps, RoutedPage};
+pub use routed::RoutedComponent;
+pub use shortcuts::RouteShortcuts;
+pub use table::{RouteConflict, RouterTable};
 
 /// Rendering control flow component.
 ///
+/// The stored state `T` comes first and has no default, so a router that doesn't store state is
+/// written `Router<(), AppRoute, Msg>`, or through the unit alias in the prelude.
+///
+/// Its render function can receive the route and the captures from its `matcher` as a
+/// [`RouteContext`](struct.RouteContext.html), which can be passed down to nested components.
+///
+/// Several `Router`s can switch on different parts of the url by giving each one its own
+/// [`RouteNamespace`](../agent/trait.RouteNamespace.html) `N`, like `Router<(), PanelRoute, Msg,
+/// Panel>` for a side panel controlled by a query parameter.
+///
+/// Giving it a [`FocusManager`](struct.FocusManager.html) through the `focus` prop moves keyboard
+/// focus to the new page's heading and announces it to screen readers after each route change.
+///
+/// Layouts that don't need a Switch type can be rendered with
+/// [`Router::with_raw_render`](struct.Router.html#method.with_raw_render), which receives the route
+/// and the captures of the `matcher` prop directly.
+///
+/// Routes can be loaded lazily by giving them a chunk with `#[meta(chunk = "name")]` and giving
+/// the Router a [`ChunkLoader`](struct.ChunkLoader.html), in which case its `placeholder` is
+/// rendered until the chunk is ready.
+///
+/// If its `wait_for_ready` prop is set, the Router keeps showing the previous page after
+/// navigating to a route with `#[meta(wait_for_ready)]`, until the new page signals that it's
+/// ready through the [`RouteReadiness`](struct.RouteReadiness.html) of its `RouteContext`.
+/// Meanwhile, the new page is rendered, but hidden, so it can load its data.
+///
+/// Apps whose urls are localized, like `/de/ueber-uns`, can use a
+/// [`LocalizedStrategy`](../strategy/struct.LocalizedStrategy.html), in which case the Router
+/// switches on the canonical route, like `/about`, and gives the locale of the url to its render
+/// function in the `RouteContext`.
+///
+/// Old routes, like deep links that were published before the app's routes changed, can be
+/// moved to their modern routes by giving it [`RouteMigrations`](struct.RouteMigrations.html)
+/// through the `migrations` prop.
+/// These can also be created from a redirect map that is loaded at runtime, with
+/// [`RouteMigrations::from_redirects`](struct.RouteMigrations.html#method.from_redirects), in
+/// which case the current route is migrated as soon as the map is given to the Router.
+///
+/// Apps that are rendered on the server can give it the route of the page through its
+/// `initial_route` prop, which it renders until it has read the route from the url after being
+/// mounted, so hydrating the server-rendered markup doesn't change it.
+///
+/// Giving it a [`RestoreLastRoute`](struct.RestoreLastRoute.html) through the `restore_last_route`
+/// prop makes it return to the last visited route when the app is loaded at `/`.
+///
+/// Pages that fail to render, because their render function created with
+/// [`Router::try_render`](struct.Router.html#method.try_render) returns an error, or their chunk
+/// fails to load, are replaced by its `error_view`, which can retry the navigation.
+///
+/// Instead of a render closure, the page of each route can be declared as a child of the Router,
+/// with a [`RouteView`](struct.RouteView.html), in which case its `render` function is
+/// [`Router::render_views`](struct.Router.html#method.render_views).
+///
+/// Layouts that are shared by several routes render the routes nested inside of their own with an
+/// [`Outlet`](struct.Outlet.html).
+///
+/// Routes that are only known at runtime, like the pages of feature modules that are loaded on
+/// demand, can be registered in a [`RouterTable`](struct.RouterTable.html) given to it through its
+/// `table` prop, which it renders whenever they change.
+///
+/// Its `guard` decides whether the page of each route that it switches to may be shown, and can
+/// redirect to another route, render the `forbidden_view` instead, or render the `pending_view`
+/// while it decides asynchronously, like while a token is refreshed.
+///
+/// Each navigation goes through the phases of a
+/// [`NavigationPhase`](enum.NavigationPhase.html), from matching the route, through loading what
+/// its page needs, to rendering it, which UI like a progress bar can follow through its `on_phase`
+/// prop.
+///
 /// # Example
 /// ```
 /// use yew::prelude::*;
@@ -65,17 +164,53 @@ impl<'de, T> RouterState<'de> for T where T: AgentState<'de> + PartialEq {}
 /// }
 /// ```
 #[derive(Debug)]
-pub struct Router<T: for<'de> RouterState<'de>, SW: Switch + 'static, M: 'static> {
+pub struct Router<
+    T: RouterState,
+    SW: Switch + 'static,
+    M: 'static,
+    N: RouteNamespace = DefaultNamespace,
+> {
     route: Route<T>,
-    props: Props<T, SW, M>,
-    router_agent: RouteAgentBridge<T>,
+    /// Whether the route that the page was loaded with has been received.
+    has_route: bool,
+    props: Props<T, SW, M, N>,
+    router_agent: RouteAgentBridge<T, N>,
+    /// Re-renders the router once a chunk that it is waiting for has loaded.
+    chunk_loaded: Callback<()>,
+    /// The route whose page is still shown while the page of `route` isn't ready.
+    previous_route: Option<Route<T>>,
+    /// Counts the navigations to routes that wait for their page to be ready.
+    navigation: usize,
+    /// Which of the two slots that pages are rendered in when waiting for readiness is shown.
+    ///
+    /// Pages stay in the same slot while they are shown, so they aren't created again when the
+    /// page in the other slot is revealed.
+    shown_slot: usize,
+    /// Reveals the page of `route` once it is ready.
+    route_ready: Callback<usize>,
+    /// Re-renders the router when the routes of its table change.
+    table_changed: Callback<()>,
+    /// What the guard decided about `route`.
+    guard: GuardState,
+    /// Counts the checks of the guard, so decisions about routes that were left are ignored.
+    guard_checks: usize,
+    /// Receives the decisions that the guard makes asynchronously.
+    guard_resolved: Callback<(usize, GuardResult<T>)>,
+    /// Ends the rendering phase of a navigation once the page is rendered.
+    rendered: Callback<()>,
+    /// The phase of the latest navigation.
+    phase: RouterPhase,
+    /// The error that the page failed to render with, which fails the navigation once the Router
+    /// has rendered.
+    render_error: RefCell<Option<String>>,
 }
 
-impl<T, SW, M> Router<T, SW, M>
+impl<T, SW, M, N> Router<T, SW, M, N>
 where
-    T: for<'de> RouterState<'de>,
+    T: RouterState,
     SW: Switch + 'static,
     M: 'static,
+    N: RouteNamespace,
 {
     /// Wrap a render closure so that it can be used by the Router.
     /// # Example
@@ -98,23 +233,675 @@ where
     /// });
     /// # }
     /// ```
-    pub fn render<F: RenderFn<Router<T, SW, M>, SW> + 'static>(f: F) -> Render<T, SW, M> {
+    pub fn render<F: RenderFn<Router<T, SW, M, N>, SW> + 'static>(f: F) -> Render<T, SW, M, N> {
         Render::new(f)
     }
 
+    /// Renders the first of the [`RouteView`](struct.RouteView.html) children of the Router that
+    /// renders the switched route.
+    ///
+    /// If none of them do, the route is rendered like a route that doesn't match, which fails.
+    pub fn render_views() -> Render<T, SW, M, N> {
+        Render(RenderFunction::Children)
+    }
+
+    /// Renders the components that the variants of `SW` are routed to with
+    /// `#[switch(component = "UserPage")]`, building their props from the fields of the variants.
+    ///
+    /// Variants that don't declare a component are rendered by `fallback`.
+    /// See [`RoutedComponent`](trait.RoutedComponent.html) for how the props are built.
+    /// # Example
+    /// ```
+    /// # use yew_router::Switch;
+    /// # use yew_router::router::Router;
+    /// # use yew::{html, Component, ComponentLink, Html, Renderable, ShouldRender};
+    /// # pub struct Page;
+    /// # impl Component for Page {
+    /// #     type Message = ();
+    /// #     type Properties = ();
+    /// #     fn create(_: (), _: ComponentLink<Self>) -> Self { Page }
+    /// #     fn update(&mut self, _: ()) -> ShouldRender { false }
+    /// # }
+    /// # impl Renderable<Page> for Page {
+    /// #     fn view(&self) -> Html<Self> { html! {"Page"} }
+    /// # }
+    /// #[derive(Switch)]
+    /// enum S {
+    ///     #[to = "/page"]
+    ///     #[switch(component = "Page")]
+    ///     Page,
+    ///     #[to = "/other"]
+    ///     Other,
+    /// }
+    /// # pub enum Msg {}
+    ///
+    /// # fn dont_execute() {
+    /// let render = Router::<(), S, Msg>::render_components(|_: S| html! {"Not a page"});
+    /// # }
+    /// ```
+    pub fn render_components<F: RenderFn<Router<T, SW, M, N>, SW> + 'static>(
+        fallback: F,
+    ) -> Render<T, SW, M, N>
+    where
+        SW: RoutedComponent,
+    {
+        Render::new(move |switch: SW| switch.render_component().unwrap_or_else(&fallback))
+    }
+
+    /// Wrap a render closure that also receives the current route and the raw captures produced by
+    /// the Router's `matcher` prop.
+    ///
+    /// This allows access to parts of the route that the Switch intentionally doesn't model.
+    /// If no `matcher` is provided, or it doesn't match the route, the captures will be empty.
+    /// # Example
+    /// ```
+    /// # use yew_router::Switch;
+    /// # use yew_router::router::Router;
+    /// # use yew_router::route::Route;
+    /// # use yew_router::matcher::Captures;
+    /// # use yew::{html, Html};
+    /// # #[derive(Switch)]
+    /// # enum S {
+    /// #     #[to = "/route"]
+    /// #     Variant
+    /// # }
+    /// # pub enum Msg {}
+    ///
+    /// # fn dont_execute() {
+    /// let render = Router::render_with_matches(
+    ///     |switch: S, route: &Route<()>, captures: &Captures| -> Html<Router<(), S, Msg>> {
+    ///         match switch {
+    ///             S::Variant => html! {format!("{} {:?}", route, captures.get("debug"))},
+    ///         }
+    ///     },
+    /// );
+    /// # }
+    /// ```
+    pub fn render_with_matches<F: RenderWithMatchesFn<Router<T, SW, M, N>, SW, T> + 'static>(
+        f: F,
+    ) -> Render<T, SW, M, N> {
+        Render::with_matches(f)
+    }
+
+    /// Wrap a render closure that also receives the context of the route, which can be passed
+    /// down to the components nested beneath the page as a single prop.
+    ///
+    /// The context holds the route, the captures produced by the Router's `matcher` prop, and the
+    /// locale of the url.
+    /// # Example
+    /// ```
+    /// # use yew_router::Switch;
+    /// # use yew_router::router::{RouteContext, Router};
+    /// # use yew::{html, Html};
+    /// # #[derive(Switch)]
+    /// # enum S {
+    /// #     #[to = "/route"]
+    /// #     Variant
+    /// # }
+    /// # pub enum Msg {}
+    ///
+    /// # fn dont_execute() {
+    /// let render = Router::render_with_context(
+    ///     |switch: S, context: &RouteContext<()>| -> Html<Router<(), S, Msg>> {
+    ///         match switch {
+    ///             S::Variant => html! {format!("{} {:?}", context.route(), context.capture("debug"))},
+    ///         }
+    ///     },
+    /// );
+    /// # }
+    /// ```
+    pub fn render_with_context<F: RenderWithContextFn<Router<T, SW, M, N>, SW, T> + 'static>(
+        f: F,
+    ) -> Render<T, SW, M, N> {
+        Render::with_context(f)
+    }
+
+    /// Wrap a render closure that can fail, in which case the Router renders its `error_view`
+    /// instead.
+    ///
+    /// Panics aren't caught, as they abort on `wasm32-unknown-unknown`, so the closure should
+    /// return an error instead.
+    /// # Example
+    /// ```
+    /// # use yew_router::Switch;
+    /// # use yew_router::router::Router;
+    /// # use yew::{html, Html};
+    /// # #[derive(Switch)]
+    /// # enum S {
+    /// #     #[to = "/route"]
+    /// #     Variant
+    /// # }
+    /// # pub enum Msg {}
+    ///
+    /// # fn dont_execute() {
+    /// let render = Router::try_render(|switch: S| -> Result<Html<Router<(), S, Msg>>, String> {
+    ///     match switch {
+    ///         S::Variant => Err("The variant isn't available yet".to_string()),
+    ///     }
+    /// });
+    /// # }
+    /// ```
+    pub fn try_render<F: TryRenderFn<Router<T, SW, M, N>, SW> + 'static>(
+        f: F,
+    ) -> Render<T, SW, M, N> {
+        Render::fallible(f)
+    }
+
+    /// Wrap a render closure that only receives the current route and the raw captures produced by
+    /// the Router's `matcher` prop.
+    ///
+    /// The closure is called for every route, without switching it first, so generic layouts, like
+    /// wrappers that log every route, don't have to declare a Switch type.
+    /// As the Switch type isn't used, any type can be given for it, like `String`, but `redirect`,
+    /// `bad_request`, and `chunk_loader` have no effect.
+    /// # Example
+    /// ```
+    /// # use yew_router::router::Router;
+    /// # use yew_router::route::Route;
+    /// # use yew_router::matcher::Captures;
+    /// # use yew::{html, Html};
+    /// # pub enum Msg {}
+    ///
+    /// # fn dont_execute() {
+    /// let render = Router::with_raw_render(
+    ///     |route: &Route<()>, captures: &Captures| -> Html<Router<(), String, Msg>> {
+    ///         html! {format!("{} {:?}", route, captures.get("id"))}
+    ///     },
+    /// );
+    /// # }
+    /// ```
+    pub fn with_raw_render<F: RawRenderFn<Router<T, SW, M, N>, T> + 'static>(
+        f: F,
+    ) -> Render<T, SW, M, N> {
+        Render::raw(f)
+    }
+
+    /// Builds the breadcrumb trail for a route from the `title`s in the metadata of `SW`.
+    ///
+    /// See [`switch::breadcrumbs`](../switch/fn.breadcrumbs.html) for how the trail is built.
+    pub fn breadcrumbs(route: &Route<T>) -> Vec<(&'static str, Route<T>)> {
+        breadcrumbs::<SW, T>(&route.route)
+    }
+
     /// Wrap a redirect function so that it can be used by the Router.
     pub fn redirect<F: RedirectFn<SW, T> + 'static>(f: F) -> Option<Redirect<SW, T, M>> {
         Some(Redirect::new(f))
     }
+
+    /// Wrap a function that renders a "bad request" page so that it can be used by the Router.
+    ///
+    /// It is rendered when a route matches, but contains a section that can't be converted into
+    /// its field, like `/users/abc` for `#[to = "/users/{id}"] User { id: u32 }`.
+    pub fn bad_request<F: Fn(&SwitchError) -> Html<Router<T, SW, M, N>> + 'static>(
+        f: F,
+    ) -> Option<BadRequest<T, SW, M, N>> {
+        Some(BadRequest(Rc::new(f)))
+    }
+
+    /// Wrap a placeholder function so that it can be used by the Router while the chunk of a
+    /// route is loading.
+    pub fn placeholder<F: Fn() -> Html<Router<T, SW, M, N>> + 'static>(
+        f: F,
+    ) -> Option<Placeholder<T, SW, M, N>> {
+        Some(Placeholder(Rc::new(f)))
+    }
+
+    /// Wrap a function that renders the page for routes that failed to render, so that it can be
+    /// used by the Router.
+    ///
+    /// The page can retry the navigation by sending `Msg::Retry`, which loads the chunks that
+    /// failed to load again, and navigates to the route once more.
+    /// # Example
+    /// ```
+    /// # use yew_router::Switch;
+    /// # use yew_router::router::{Msg as RouterMsg, RouteFailure, Router};
+    /// # use yew::{html, Html};
+    /// # #[derive(Switch)]
+    /// # enum S {
+    /// #     #[to = "/route"]
+    /// #     Variant
+    /// # }
+    /// # pub enum Msg {}
+    ///
+    /// # fn dont_execute() {
+    /// let error_view = Router::error_view(
+    ///     |failure: &RouteFailure<()>| -> Html<Router<(), S, Msg>> {
+    ///         html! {
+    ///             <div>
+    ///                 <p>{format!("{} couldn't be shown: {}", failure.route, failure.error)}</p>
+    ///                 <button onclick=|_| RouterMsg::Retry,>{"Retry"}</button>
+    ///             </div>
+    ///         }
+    ///     },
+    /// );
+    /// # }
+    /// ```
+    pub fn error_view<F: Fn(&RouteFailure<T>) -> Html<Router<T, SW, M, N>> + 'static>(
+        f: F,
+    ) -> Option<ErrorView<T, SW, M, N>> {
+        Some(ErrorView(Rc::new(f)))
+    }
+
+    /// Wrap a route guard so that it can be used by the Router.
+    ///
+    /// The guard is called with each route that is switched to, before its page is rendered.
+    /// # Example
+    /// ```
+    /// # use yew_router::{route::Route, Switch};
+    /// # use yew_router::router::{GuardResult, Router};
+    /// # use yew::Callback;
+    /// # pub enum Msg {}
+    /// #[derive(Switch)]
+    /// enum S {
+    ///     #[to = "/admin"]
+    ///     #[meta(requires_auth)]
+    ///     Admin,
+    ///     #[to = "/login"]
+    ///     Login,
+    /// }
+    ///
+    /// # fn is_logged_in() -> bool { false }
+    /// # fn dont_execute() {
+    /// let guard = Router::<(), S, Msg>::guard(|switch: &S, _route: &Route<()>| {
+    ///     if !switch.meta().contains("requires_auth") || is_logged_in() {
+    ///         GuardResult::Allow
+    ///     } else {
+    ///         GuardResult::pending(|done: Callback<GuardResult<()>>| {
+    ///             // Try to refresh the session here, then decide.
+    ///             done.emit(GuardResult::Redirect(Route::from("/login")))
+    ///         })
+    ///     }
+    /// });
+    /// # }
+    /// ```
+    pub fn guard<F: Fn(&SW, &Route<T>) -> GuardResult<T> + 'static>(
+        f: F,
+    ) -> Option<RouteGuard<T, SW, M, N>> {
+        Some(RouteGuard(Rc::new(f), PhantomData))
+    }
+
+    /// Wrap a function that renders the page for routes that the guard forbids, with the reason
+    /// that it gave, so that it can be used by the Router.
+    pub fn forbidden_view<F: Fn(&Route<T>, &str) -> Html<Router<T, SW, M, N>> + 'static>(
+        f: F,
+    ) -> Option<ForbiddenView<T, SW, M, N>> {
+        Some(ForbiddenView(Rc::new(f)))
+    }
+
+    /// Wrap a function that renders the page for routes whose guard hasn't decided yet, so that it
+    /// can be used by the Router.
+    pub fn pending_view<F: Fn(&Route<T>) -> Html<Router<T, SW, M, N>> + 'static>(
+        f: F,
+    ) -> Option<PendingView<T, SW, M, N>> {
+        Some(PendingView(Rc::new(f)))
+    }
+
+    /// Asks the guard about the route, returning the route to redirect to, if it redirects.
+    fn check_guard(&mut self, route: &Route<T>) -> Option<Route<T>> {
+        self.guard_checks += 1;
+        // Routes that don't switch are rendered as they would be without a guard.
+        let result = match (&self.props.guard, self.switch(route.clone())) {
+            (Some(guard), Some(switch)) => (guard.0)(&switch, route),
+            _ => GuardResult::Allow,
+        };
+        self.apply_guard_result(result)
+    }
+
+    /// Applies a decision of the guard, returning the route to redirect to, if it redirects.
+    fn apply_guard_result(&mut self, result: GuardResult<T>) -> Option<Route<T>> {
+        self.guard
+            .apply(self.guard_checks, result, &self.guard_resolved)
+    }
+
+    /// Gets the route that the Router received last, if it received one.
+    fn current_route(&self) -> Option<&Route<T>> {
+        if self.has_route {
+            Some(&self.route)
+        } else {
+            None
+        }
+    }
+
+    /// Moves the navigation to the phase, and tells the `on_phase` callback if it changed.
+    fn set_phase(&mut self, phase: NavigationPhase) {
+        self.phase.set(phase, self.props.on_phase.as_ref());
+    }
+
+    /// Moves the navigation to its rendering phase, which ends once the Router has rendered, or to
+    /// its loading phase if the page waits for something before it can be shown.
+    fn begin_render(&mut self) {
+        let page_waits = self.previous_route.is_some() || self.waits_for_chunks(&self.route);
+        self.set_phase(phase_before_render(&self.guard, page_waits));
+        *self.render_error.get_mut() = None;
+        let rendered = self.rendered.clone();
+        // Timeouts run after yew is done with the messages it is handling, and the renders they
+        // cause.
+        set_timeout(move || rendered.emit(()), 0);
+    }
+
+    /// Renders the page of the current route, or what its guard decided to render instead.
+    fn render_guarded_route(&self) -> Html<Self> {
+        match &self.guard {
+            GuardState::Allowed => self.render_route(&self.route, self.readiness()),
+            GuardState::Forbidden(reason) => match &self.props.forbidden_view {
+                Some(forbidden_view) => (forbidden_view.0)(&self.route, reason),
+                None => html! {format!{"{} is forbidden: {}", self.route.route, reason}},
+            },
+            GuardState::Pending(_) | GuardState::Redirecting => match &self.props.pending_view {
+                Some(pending_view) => (pending_view.0)(&self.route),
+                None => html! {},
+            },
+        }
+    }
+
+    /// Gets the flags that decide which of the routes gated behind feature flags are enabled.
+    fn flags(&self) -> FeatureFlags {
+        self.props.flags.clone().unwrap_or_default()
+    }
+
+    /// Switches on the route, skipping the routes that the `flags` disable.
+    fn switch(&self, route: Route<T>) -> Option<SW> {
+        SW::switch_with_flags(route, &self.flags())
+    }
+
+    /// Determines if the page of the route is kept hidden until it is ready.
+    fn waits_for_ready(&self, route: &Route<T>) -> bool {
+        self.props.wait_for_ready
+            && self.switch(route.clone()).map_or(false, |switch| {
+                switch
+                    .nested_meta()
+                    .iter()
+                    .any(|meta| meta.contains("wait_for_ready"))
+            })
+    }
+
+    /// Replaces the route with the one it migrates to, if any of the `migrations` apply to it,
+    /// returning whether one did.
+    ///
+    /// The migrated route is broadcast back to this Router, so nothing should be rendered until
+    /// then.
+    fn migrate(&mut self, route: &Route<T>) -> bool {
+        let migrated = self
+            .props
+            .migrations
+            .as_ref()
+            .and_then(|migrations| migrations.migrate_route(&route.route));
+        match migrated {
+            Some(migrated) => {
+                self.router_agent.send(RouteRequest::ReplaceRoute(Route {
+                    route: migrated,
+                    state: route.state.clone(),
+                }));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Replaces the received route, instead of rendering it, with the route it migrates to, the
+    /// route to restore, or the route that its guard redirects to, returning whether it was
+    /// replaced.
+    ///
+    /// The route that replaces it is broadcast back to this Router, which handles it like any
+    /// other route, so nothing should be rendered until then.
+    fn replace_route(&mut self, route: &Route<T>, forced: bool) -> bool {
+        if self.migrate(route) {
+            return true;
+        }
+        if !self.has_route {
+            if let Some(last_route) = self.route_to_restore(route) {
+                self.router_agent
+                    .send(RouteRequest::ReplaceRoute(Route::from(last_route.as_str())));
+                return true;
+            }
+        }
+        // Checking a route again would restart the pending decisions about it.
+        let is_checked = self.route == *route && !forced && self.guard != GuardState::Redirecting;
+        if !is_checked {
+            if let Some(redirect) = self.check_guard(route) {
+                self.router_agent.send(RouteRequest::ReplaceRoute(redirect));
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Starts loading the chunks that the page of the route needs, which the Router renders its
+    /// `placeholder` for until they're loaded.
+    ///
+    /// Every chunk is requested, so they load in parallel.
+    fn load_chunks(&self, route: &Route<T>) {
+        if let Some(chunk_loader) = &self.props.chunk_loader {
+            for chunk in self.route_chunks(route) {
+                chunk_loader.load(chunk, &self.chunk_loaded);
+            }
+        }
+    }
+
+    /// Determines if the page of the route is waiting for its chunks to load.
+    fn waits_for_chunks(&self, route: &Route<T>) -> bool {
+        match &self.props.chunk_loader {
+            Some(chunk_loader) => self
+                .route_chunks(route)
+                .into_iter()
+                .any(|chunk| chunk_loader.is_loaded(chunk) == Ok(false)),
+            None => false,
+        }
+    }
+
+    /// Gets the chunks that the page of the route needs.
+    fn route_chunks(&self, route: &Route<T>) -> Vec<&'static str> {
+        let switch = self.switch(route.clone()).or_else(|| {
+            self.props
+                .redirect
+                .as_ref()
+                .map(|redirect_fn| (redirect_fn.0)(route.clone()))
+        });
+        switch.map_or_else(Vec::new, |switch| {
+            switch
+                .nested_meta()
+                .iter()
+                .filter_map(|meta| meta.get("chunk"))
+                .collect()
+        })
+    }
+
+    /// Determines if the route may be stored and restored by `restore_last_route`.
+    fn is_restorable(&self, restore: &RestoreLastRoute, route: &str) -> bool {
+        !restore.is_excluded(route)
+            && self.switch(Route::<T>::from(route)).map_or(true, |switch| {
+                !switch
+                    .nested_meta()
+                    .iter()
+                    .any(|meta| meta.contains("no_restore"))
+            })
+    }
+
+    /// Gets the stored route to replace the route that the page was loaded with, if there is one.
+    fn route_to_restore(&self, loaded_route: &Route<T>) -> Option<String> {
+        let restore = self.props.restore_last_route.as_ref()?;
+        restore::pick_last_route(
+            &loaded_route.route,
+            || restore.last_route(),
+            |last_route| self.is_restorable(restore, last_route),
+        )
+    }
+
+    /// Gets the handle that the page of the current route tells the Router that it's ready with,
+    /// if the Router waits for pages to be ready.
+    fn readiness(&self) -> Option<RouteReadiness> {
+        if self.props.wait_for_ready {
+            Some(RouteReadiness::new(
+                self.navigation,
+                self.route_ready.clone(),
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Renders the page of a route, or the error view if it fails to render.
+    ///
+    /// The page is given the `readiness` handle through its context.
+    fn render_route(&self, route: &Route<T>, readiness: Option<RouteReadiness>) -> Html<Self> {
+        let captures = route_captures(self.props.matcher.as_ref(), &route.route);
+        let locale = strategy::locale_for_current_location(&*N::strategy());
+        let context = RouteContext::with_readiness(route.clone(), &captures, locale, readiness);
+        self.try_render_route(&context, &captures)
+            .unwrap_or_else(|error| {
+                log::error!("{} couldn't be rendered: {}", route.route, error);
+                *self.render_error.borrow_mut() = Some(error.clone());
+                match &self.props.error_view {
+                    Some(error_view) => (error_view.0)(&RouteFailure {
+                        route: route.clone(),
+                        error,
+                    }),
+                    None => html! {format!{"Couldn't render {}", route.route}},
+                }
+            })
+    }
+
+    /// Renders the route with the routes of the table that are tried before or after the Switch.
+    fn render_table_route(&self, route: &Route<T>, before_switch: bool) -> Option<Html<Self>> {
+        let table = self.props.table.as_ref()?;
+        table.render(route, before_switch)
+    }
+
+    fn try_render_route(
+        &self,
+        context: &RouteContext<T>,
+        captures: &Captures,
+    ) -> Result<Html<Self>, String> {
+        let route = context.route();
+        if let Some(rendered) = self.render_table_route(route, true) {
+            return Ok(rendered);
+        }
+        if let RenderFunction::Raw(render) = &self.props.render.0 {
+            return Ok(render(route, captures));
+        }
+        let switch: Result<SW, SwitchError> =
+            SW::try_switch_with_flags(route.clone(), &self.flags());
+        match switch {
+            Ok(switch) => self.render_switch(switch, context, captures),
+            Err(error) => {
+                if let (SwitchError::InvalidField { .. }, Some(bad_request)) =
+                    (&error, &self.props.bad_request)
+                {
+                    Ok((bad_request.0)(&error))
+                } else if let Some(rendered) = self.render_table_route(route, false) {
+                    Ok(rendered)
+                } else if let Some(redirect_fn) = &self.props.redirect {
+                    let switch: SW = (redirect_fn.0)(route.clone()); // TODO This should be used to set the route in the browser
+                    self.render_switch(switch, context, captures)
+                } else {
+                    Ok(html! {format!{"No route for {}", route.route}})
+                }
+            }
+        }
+    }
+
+    /// Renders the switched route, or the placeholder if the chunks it needs aren't loaded yet.
+    fn render_switch(
+        &self,
+        switch: SW,
+        context: &RouteContext<T>,
+        captures: &Captures,
+    ) -> Result<Html<Self>, String> {
+        let route = context.route();
+        if let Some(chunk_loader) = &self.props.chunk_loader {
+            // The chunks are requested by `load_chunks` once the Router receives the route.
+            let mut pending_chunks = 0;
+            for chunk in switch
+                .nested_meta()
+                .iter()
+                .filter_map(|meta| meta.get("chunk"))
+            {
+                let is_loaded = chunk_loader
+                    .is_loaded(chunk)
+                    .map_err(|error| format!("The chunk '{}' failed to load: {}", chunk, error))?;
+                if !is_loaded {
+                    pending_chunks += 1;
+                }
+            }
+            if pending_chunks > 0 {
+                return Ok(match &self.props.placeholder {
+                    Some(placeholder) => (placeholder.0)(),
+                    None => html! {},
+                });
+            }
+        }
+        match &self.props.render.0 {
+            RenderFunction::Switched(render) => render(switch, context, captures),
+            RenderFunction::Raw(render) => Ok(render(route, captures)),
+            RenderFunction::Children => route_view::render_views(&self.props.children, switch)
+                .ok_or_else(|| format!("No RouteView renders {}", route.route)),
+        }
+    }
+}
+
+/// Determines if a route that the Router received has to be handled, which it doesn't if it is
+/// the route that the Router already has, unless the navigation to it was forced.
+///
+/// The agent doesn't broadcast navigations to the current route, but it does reply to requests
+/// for the current route, and other Routers may navigate to the route before it is received.
+fn needs_update<T: PartialEq>(current: Option<&Route<T>>, route: &Route<T>, forced: bool) -> bool {
+    forced || current != Some(route)
+}
+
+/// Gets the phase of a navigation whose page is about to be rendered, according to what its
+/// guard decided, and whether the page waits for its chunks or its readiness.
+fn phase_before_render(guard: &GuardState, page_waits: bool) -> NavigationPhase {
+    match guard {
+        GuardState::Pending(_) | GuardState::Redirecting => NavigationPhase::Loading,
+        GuardState::Allowed if page_waits => NavigationPhase::Loading,
+        GuardState::Allowed | GuardState::Forbidden(_) => NavigationPhase::Rendering,
+    }
+}
+
+/// Makes the received route the Router's route, keeping the route it replaces in
+/// `previous_route`, whose page stays shown, if the page of the received route `waits` until it
+/// is ready.
+///
+/// Returns whether a hidden page was replaced by one that is shown right away, which moves the
+/// shown page to the other slot.
+/// Hidden pages stay hidden when the route they were rendered for is received again.
+fn receive_route<T: PartialEq>(
+    route: &mut Route<T>,
+    previous_route: &mut Option<Route<T>>,
+    received: Route<T>,
+    waits: bool,
+) -> bool {
+    let did_change = *route != received;
+    let replaced = mem::replace(route, received);
+    if waits {
+        if previous_route.is_none() {
+            *previous_route = Some(replaced);
+        }
+        false
+    } else {
+        did_change && previous_route.take().is_some()
+    }
 }
 
 /// Message for Router.
 #[derive(Debug, Clone)]
 pub enum Msg<T, M> {
     /// Updates the route
-    UpdateRoute(Route<T>),
+    UpdateRoute(RouteBroadcast<T>),
     /// Inner message
     InnerMessage(M),
+    /// A chunk that the route needs has loaded, or failed to load
+    ChunkLoaded,
+    /// The page rendered for a navigation is ready to be shown
+    RouteReady(usize),
+    /// Loads the chunks that failed to load again, and navigates to the current route once more
+    Retry,
+    /// Routes were registered in or unregistered from the table
+    TableChanged,
+    /// The guard decided about the route that it was asked about in the check with this number
+    GuardResolved(usize, GuardResult<T>),
+    /// The Router has rendered the page of a navigation
+    Rendered,
 }
 
 impl<T, M> From<M> for Msg<T, M> {
@@ -126,17 +913,96 @@ impl<T, M> From<M> for Msg<T, M> {
 /// Render function that takes a switched route and converts it to HTML
 pub trait RenderFn<CTX: Component, SW>: Fn(SW) -> Html<CTX> {}
 impl<T, CTX: Component, SW> RenderFn<CTX, SW> for T where T: Fn(SW) -> Html<CTX> {}
+/// Render function that takes a switched route, along with the route it was switched from and the
+/// raw captures from the Router's matcher, and converts it to HTML.
+pub trait RenderWithMatchesFn<CTX: Component, SW, STATE>:
+    Fn(SW, &Route<STATE>, &Captures) -> Html<CTX>
+{
+}
+impl<T, CTX: Component, SW, STATE> RenderWithMatchesFn<CTX, SW, STATE> for T where
+    T: Fn(SW, &Route<STATE>, &Captures) -> Html<CTX>
+{
+}
+/// Render function that takes a switched route, along with the context of the route it was
+/// switched from, and converts it to HTML.
+pub trait RenderWithContextFn<CTX: Component, SW, STATE>:
+    Fn(SW, &RouteContext<STATE>) -> Html<CTX>
+{
+}
+impl<T, CTX: Component, SW, STATE> RenderWithContextFn<CTX, SW, STATE> for T where
+    T: Fn(SW, &RouteContext<STATE>) -> Html<CTX>
+{
+}
+/// Render function that takes a switched route and converts it to HTML, or fails with an error.
+pub trait TryRenderFn<CTX: Component, SW>: Fn(SW) -> Result<Html<CTX>, String> {}
+impl<T, CTX: Component, SW> TryRenderFn<CTX, SW> for T where T: Fn(SW) -> Result<Html<CTX>, String> {}
+/// Render function that takes the route and the raw captures from the Router's matcher, without
+/// switching the route, and converts them to HTML.
+pub trait RawRenderFn<CTX: Component, STATE>: Fn(&Route<STATE>, &Captures) -> Html<CTX> {}
+impl<T, CTX: Component, STATE> RawRenderFn<CTX, STATE> for T where
+    T: Fn(&Route<STATE>, &Captures) -> Html<CTX>
+{
+}
 /// Owned Render function.
-pub struct Render<T: for<'de> RouterState<'de>, SW: Switch + 'static, M: 'static>(
-    pub(crate) Rc<dyn RenderFn<Router<T, SW, M>, SW>>,
-);
-impl<T: for<'de> RouterState<'de>, SW: Switch, M> Render<T, SW, M> {
+pub struct Render<
+    T: RouterState,
+    SW: Switch + 'static,
+    M: 'static,
+    N: RouteNamespace = DefaultNamespace,
+>(pub(crate) RenderFunction<T, SW, M, N>);
+impl<T: RouterState, SW: Switch, M, N: RouteNamespace> Render<T, SW, M, N> {
     /// New render function
-    fn new<F: RenderFn<Router<T, SW, M>, SW> + 'static>(f: F) -> Self {
-        Render(Rc::new(f))
+    fn new<F: RenderFn<Router<T, SW, M, N>, SW> + 'static>(f: F) -> Self {
+        Render(RenderFunction::Switched(Rc::new(
+            move |switch: SW, _context: &RouteContext<T>, _captures: &Captures| Ok(f(switch)),
+        )))
+    }
+
+    /// New render function that has access to the route and raw captures.
+    fn with_matches<F: RenderWithMatchesFn<Router<T, SW, M, N>, SW, T> + 'static>(f: F) -> Self {
+        Render(RenderFunction::Switched(Rc::new(
+            move |switch: SW, context: &RouteContext<T>, captures: &Captures| {
+                Ok(f(switch, context.route(), captures))
+            },
+        )))
+    }
+
+    /// New render function that has access to the context of the route.
+    fn with_context<F: RenderWithContextFn<Router<T, SW, M, N>, SW, T> + 'static>(f: F) -> Self {
+        Render(RenderFunction::Switched(Rc::new(
+            move |switch: SW, context: &RouteContext<T>, _captures: &Captures| {
+                Ok(f(switch, context))
+            },
+        )))
+    }
+
+    /// New render function that can fail.
+    fn fallible<F: TryRenderFn<Router<T, SW, M, N>, SW> + 'static>(f: F) -> Self {
+        Render(RenderFunction::Switched(Rc::new(
+            move |switch: SW, _context: &RouteContext<T>, _captures: &Captures| f(switch),
+        )))
+    }
+
+    /// New render function that is called without switching the route.
+    fn raw<F: RawRenderFn<Router<T, SW, M, N>, T> + 'static>(f: F) -> Self {
+        Render(RenderFunction::Raw(Rc::new(f)))
     }
 }
-impl<T: for<'de> RouterState<'de>, SW: Switch, M> Debug for Render<T, SW, M> {
+
+/// The ways that a Render function can be called.
+pub(crate) enum RenderFunction<T: RouterState, SW: Switch + 'static, M: 'static, N: RouteNamespace>
+{
+    /// Called with the switched route, along with the context of the route and its raw captures.
+    #[allow(clippy::type_complexity)]
+    Switched(
+        Rc<dyn Fn(SW, &RouteContext<T>, &Captures) -> Result<Html<Router<T, SW, M, N>>, String>>,
+    ),
+    /// Called with the route and its raw captures, whether or not it can be switched.
+    Raw(Rc<dyn RawRenderFn<Router<T, SW, M, N>, T>>),
+    /// Renders the first `RouteView` child of the Router for the switched route.
+    Children,
+}
+impl<T: RouterState, SW: Switch, M, N: RouteNamespace> Debug for Render<T, SW, M, N> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.debug_struct("Render").finish()
     }
@@ -147,61 +1013,259 @@ impl<T: for<'de> RouterState<'de>, SW: Switch, M> Debug for Render<T, SW, M> {
 pub trait RedirectFn<SW, STATE>: Fn(Route<STATE>) -> SW {}
 impl<T, SW, STATE> RedirectFn<SW, STATE> for T where T: Fn(Route<STATE>) -> SW {}
 /// Clonable Redirect function
-pub struct Redirect<SW: Switch + 'static, STATE: for<'de> RouterState<'de>, M>(
+pub struct Redirect<SW: Switch + 'static, STATE: RouterState, M>(
     pub(crate) Rc<dyn RedirectFn<SW, STATE>>,
     /// This phantom data is here to allow type inference when using it inside a Router component.
     PhantomData<M>,
 );
-impl<STATE: for<'de> RouterState<'de>, SW: Switch + 'static, M> Redirect<SW, STATE, M> {
+impl<STATE: RouterState, SW: Switch + 'static, M> Redirect<SW, STATE, M> {
     fn new<F: RedirectFn<SW, STATE> + 'static>(f: F) -> Self {
         Redirect(Rc::new(f), PhantomData)
     }
 }
-impl<STATE: for<'de> RouterState<'de>, SW: Switch, M> Debug for Redirect<SW, STATE, M> {
+impl<STATE: RouterState, SW: Switch, M> Debug for Redirect<SW, STATE, M> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.debug_struct("Redirect").finish()
     }
 }
 
+/// Clonable route guard, which decides whether the page of a route may be rendered.
+pub struct RouteGuard<
+    T: RouterState,
+    SW: Switch + 'static,
+    M: 'static,
+    N: RouteNamespace = DefaultNamespace,
+>(
+    pub(crate) Rc<dyn Fn(&SW, &Route<T>) -> GuardResult<T>>,
+    /// This phantom data is here to allow type inference when using it inside a Router component.
+    PhantomData<(M, N)>,
+);
+impl<T: RouterState, SW: Switch, M, N: RouteNamespace> Debug for RouteGuard<T, SW, M, N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RouteGuard").finish()
+    }
+}
+
+/// Clonable function that renders a page for routes that the guard forbids.
+pub struct ForbiddenView<
+    T: RouterState,
+    SW: Switch + 'static,
+    M: 'static,
+    N: RouteNamespace = DefaultNamespace,
+>(pub(crate) Rc<dyn Fn(&Route<T>, &str) -> Html<Router<T, SW, M, N>>>);
+impl<T: RouterState, SW: Switch, M, N: RouteNamespace> Debug for ForbiddenView<T, SW, M, N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ForbiddenView").finish()
+    }
+}
+
+/// Clonable function that renders a page while the guard decides about a route.
+pub struct PendingView<
+    T: RouterState,
+    SW: Switch + 'static,
+    M: 'static,
+    N: RouteNamespace = DefaultNamespace,
+>(pub(crate) Rc<dyn Fn(&Route<T>) -> Html<Router<T, SW, M, N>>>);
+impl<T: RouterState, SW: Switch, M, N: RouteNamespace> Debug for PendingView<T, SW, M, N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PendingView").finish()
+    }
+}
+
+/// Clonable function that renders a placeholder while a route's chunk is loading.
+pub struct Placeholder<
+    T: RouterState,
+    SW: Switch + 'static,
+    M: 'static,
+    N: RouteNamespace = DefaultNamespace,
+>(pub(crate) Rc<dyn Fn() -> Html<Router<T, SW, M, N>>>);
+impl<T: RouterState, SW: Switch, M, N: RouteNamespace> Debug for Placeholder<T, SW, M, N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Placeholder").finish()
+    }
+}
+
+/// Clonable function that renders a page for routes that contain invalid values.
+pub struct BadRequest<
+    T: RouterState,
+    SW: Switch + 'static,
+    M: 'static,
+    N: RouteNamespace = DefaultNamespace,
+>(pub(crate) Rc<dyn Fn(&SwitchError) -> Html<Router<T, SW, M, N>>>);
+impl<T: RouterState, SW: Switch, M, N: RouteNamespace> Debug for BadRequest<T, SW, M, N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BadRequest").finish()
+    }
+}
+
+/// A route whose page failed to render, along with why.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteFailure<T = ()> {
+    /// The route that failed to render.
+    pub route: Route<T>,
+    /// The error of the render function, or why its chunk failed to load.
+    pub error: String,
+}
+
+/// Clonable function that renders a page for routes that failed to render.
+pub struct ErrorView<
+    T: RouterState,
+    SW: Switch + 'static,
+    M: 'static,
+    N: RouteNamespace = DefaultNamespace,
+>(pub(crate) Rc<dyn Fn(&RouteFailure<T>) -> Html<Router<T, SW, M, N>>>);
+impl<T: RouterState, SW: Switch, M, N: RouteNamespace> Debug for ErrorView<T, SW, M, N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ErrorView").finish()
+    }
+}
+
+/// Gets the captures that the matcher produces for the route, or none if there isn't a matcher or
+/// it doesn't match the route.
+fn route_captures<'a>(matcher: Option<&'a RouteMatcher>, route: &str) -> Captures<'a> {
+    matcher
+        .and_then(|matcher| matcher.capture_route_into_map(route).ok())
+        .map(|(_, captures)| captures)
+        .unwrap_or_default()
+}
+
 /// Properties for Router.
 #[derive(Properties)]
-pub struct Props<T: for<'de> RouterState<'de>, SW: Switch + 'static, M: 'static> {
-    /// Render function that
+pub struct Props<T: RouterState, SW: Switch + 'static, M: 'static, N: RouteNamespace> {
+    /// Render function that renders the page of the route.
+    ///
+    /// Use `Router::render_views` to render the `RouteView` children of the Router instead.
     #[props(required)]
-    pub render: Render<T, SW, M>,
+    pub render: Render<T, SW, M, N>,
+    /// The pages of the routes, which are rendered if the `render` function is
+    /// `Router::render_views`.
+    pub children: ChildrenWithProps<RouteView<SW>, Router<T, SW, M, N>>,
     /// Optional redirect function that will convert the route to a known switch variant if explicit matching fails.
     /// This should mostly be used to handle 404s and redirection.
     /// It is not strictly necessary as your Switch is capable of handling unknown routes using `#[to="/{*:any}"]`.
     pub redirect: Option<Redirect<SW, T, M>>,
+    /// Optional function that renders a "bad request" page for routes that match, but contain a
+    /// section that can't be converted into its field.
+    ///
+    /// If it isn't provided, these routes are treated like routes that don't match at all.
+    pub bad_request: Option<BadRequest<T, SW, M, N>>,
     /// Optional Callback for propagating messages to parent components.
     pub callback: Option<Callback<M>>,
+    /// Optional matcher used to collect raw captures from the route for render functions created
+    /// with `Router::render_with_matches` or `Router::with_raw_render`.
+    pub matcher: Option<RouteMatcher>,
+    /// Optional focus manager that makes route changes perceivable to keyboard and screen-reader
+    /// users.
+    pub focus: Option<FocusManager>,
+    /// Optional loader for the chunks that routes declare with `#[meta(chunk = "name")]`.
+    pub chunk_loader: Option<ChunkLoader>,
+    /// Optional placeholder that is rendered while a route's chunks are loading.
+    ///
+    /// If it isn't provided, nothing is rendered instead.
+    pub placeholder: Option<Placeholder<T, SW, M, N>>,
+    /// Keeps showing the previous page after navigating to a route with
+    /// `#[meta(wait_for_ready)]`, until the new page signals that it's ready through a
+    /// `RouteReadiness`.
+    ///
+    /// Setting this wraps each page in a `div`, so two pages can be rendered at once.
+    pub wait_for_ready: bool,
+    /// Optional storage of the last visited route, which is restored when the app is loaded at
+    /// `/`.
+    pub restore_last_route: Option<RestoreLastRoute>,
+    /// Optional function that renders a page for routes that failed to render, because their
+    /// render function returned an error, or their chunk failed to load.
+    ///
+    /// If it isn't provided, errors are rendered as text.
+    pub error_view: Option<ErrorView<T, SW, M, N>>,
+    /// Optional migrations from old routes to the routes that replaced them, which are applied
+    /// with a replace-navigation before routes are matched.
+    pub migrations: Option<RouteMigrations>,
+    /// Optional feature flags, which skip the routes gated behind the flags they disable.
+    ///
+    /// If they aren't provided, every flag is enabled.
+    pub flags: Option<FeatureFlags>,
+    /// Optional route that is rendered before the route is read from the url, like the route
+    /// that the page was rendered for on the server.
+    ///
+    /// The url is only read once the Router is mounted, so its first render matches the
+    /// server-rendered markup that it hydrates.
+    pub initial_route: Option<Route<T>>,
+    /// Optional table of routes that are registered at runtime, which are rendered alongside the
+    /// routes of the Switch, according to their priority.
+    pub table: Option<RouterTable<Router<T, SW, M, N>, T>>,
+    /// Optional guard that decides whether the page of each route that is switched to may be
+    /// rendered.
+    pub guard: Option<RouteGuard<T, SW, M, N>>,
+    /// Optional function that renders a page for routes that the guard forbids.
+    ///
+    /// If it isn't provided, the reason is rendered as text.
+    pub forbidden_view: Option<ForbiddenView<T, SW, M, N>>,
+    /// Optional function that renders a page while the guard decides about a route.
+    ///
+    /// If it isn't provided, nothing is rendered instead.
+    pub pending_view: Option<PendingView<T, SW, M, N>>,
+    /// Optional callback that receives the phase of the Router's navigations whenever it changes,
+    /// like for a progress bar.
+    pub on_phase: Option<Callback<NavigationPhase>>,
 }
 
-impl<T: for<'de> RouterState<'de>, SW: Switch, M> Debug for Props<T, SW, M> {
+impl<T: RouterState, SW: Switch, M, N: RouteNamespace> Debug for Props<T, SW, M, N> {
     fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
         f.debug_struct("Props").finish()
     }
 }
 
-impl<T, SW, M> Component for Router<T, SW, M>
+impl<T, SW, M, N> Component for Router<T, SW, M, N>
 where
-    T: for<'de> RouterState<'de>,
+    T: RouterState,
     SW: Switch + 'static,
     M: 'static,
+    N: RouteNamespace,
 {
     type Message = Msg<T, M>;
-    type Properties = Props<T, SW, M>;
+    type Properties = Props<T, SW, M, N>;
 
     fn create(props: Self::Properties, mut link: ComponentLink<Self>) -> Self {
         let callback = link.send_back(Msg::UpdateRoute);
-        let router_agent = RouteAgentBridge::new(callback);
+        let router_agent = RouteAgentBridge::with_broadcasts(callback);
+        let chunk_loaded = link.send_back(|_| Msg::ChunkLoaded);
+        let route_ready = link.send_back(Msg::RouteReady);
+        let table_changed = link.send_back(|_| Msg::TableChanged);
+        let guard_resolved = link.send_back(|(check, result)| Msg::GuardResolved(check, result));
+        let rendered = link.send_back(|_| Msg::Rendered);
+        if let Some(table) = &props.table {
+            table.subscribe(&table_changed);
+        }
 
-        Router {
-            route: Default::default(), /* This must be updated by immediately requesting a route
-                                        * update from the service bridge. */
+        let mut router = Router {
+            // This must be updated by immediately requesting a route update from the service
+            // bridge, once the first render, which may hydrate server-rendered markup, is done.
+            route: props.initial_route.clone().unwrap_or_default(),
+            has_route: false,
             props,
             router_agent,
+            chunk_loaded,
+            previous_route: None,
+            navigation: 0,
+            shown_slot: 0,
+            route_ready,
+            table_changed,
+            guard: GuardState::Allowed,
+            guard_checks: 0,
+            guard_resolved,
+            rendered,
+            phase: RouterPhase::default(),
+            render_error: RefCell::new(None),
+        };
+        if let Some(initial_route) = router.props.initial_route.clone() {
+            router.load_chunks(&initial_route);
+            if router.check_guard(&initial_route).is_some() {
+                // Redirects can only be navigated to once the Router is mounted, which checks the
+                // route again.
+                router.guard = GuardState::Redirecting;
+            }
         }
+        router
     }
 
     fn mounted(&mut self) -> ShouldRender {
@@ -211,10 +1275,41 @@ where
 
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match msg {
-            Msg::UpdateRoute(route) => {
+            Msg::UpdateRoute(RouteBroadcast { route, forced }) => {
+                if !needs_update(self.current_route(), &route, forced) {
+                    // Like the reply to the Router's request for the current route, when it
+                    // already received it.
+                    return false;
+                }
+                self.phase.start_matching(self.props.on_phase.as_ref());
+                if self.replace_route(&route, forced) {
+                    // The agent drops replacements that lead to the route it is at, like a
+                    // redirect to the current route, so they may never be received.
+                    self.phase.cancel_matching(self.props.on_phase.as_ref());
+                    return false;
+                }
                 let did_change = self.route != route;
-                self.route = route;
-                did_change
+                if let Some(restore) = &self.props.restore_last_route {
+                    if self.is_restorable(restore, &route.route) {
+                        restore.save(&route.route);
+                    }
+                }
+                self.load_chunks(&route);
+                let waits = did_change && self.has_route && self.waits_for_ready(&route);
+                if waits {
+                    self.navigation += 1;
+                } else if did_change && self.has_route {
+                    if let Some(focus) = &self.props.focus {
+                        focus.route_changed(&route.route);
+                    }
+                }
+                if receive_route(&mut self.route, &mut self.previous_route, route, waits) {
+                    // The hidden page is replaced by one that can be shown right away.
+                    self.shown_slot = 1 - self.shown_slot;
+                }
+                self.has_route = true;
+                self.begin_render();
+                true
             }
             Msg::InnerMessage(m) => {
                 if let Some(cb) = &self.props.callback {
@@ -222,30 +1317,378 @@ where
                 }
                 false
             }
+            Msg::ChunkLoaded | Msg::TableChanged => {
+                self.begin_render();
+                true
+            }
+            Msg::GuardResolved(check, result) => {
+                if !self.guard.awaits(check) {
+                    return false;
+                }
+                match self.apply_guard_result(result) {
+                    Some(redirect) => {
+                        self.router_agent.send(RouteRequest::ReplaceRoute(redirect));
+                        false
+                    }
+                    None => {
+                        self.begin_render();
+                        true
+                    }
+                }
+            }
+            Msg::Retry => {
+                if let Some(chunk_loader) = &self.props.chunk_loader {
+                    chunk_loader.retry_failed();
+                }
+                // The route is broadcast back to this Router, which renders it again.
+                self.router_agent
+                    .send(RouteRequest::ForceChangeRoute(self.route.clone()));
+                false
+            }
+            Msg::RouteReady(navigation) => {
+                if navigation != self.navigation || self.previous_route.take().is_none() {
+                    return false;
+                }
+                self.shown_slot = 1 - self.shown_slot;
+                if let Some(focus) = &self.props.focus {
+                    focus.route_changed(&self.route.route);
+                }
+                self.begin_render();
+                true
+            }
+            Msg::Rendered => {
+                let error = self.render_error.borrow_mut().take();
+                if let Some(phase) = self.phase.get().after_render(error) {
+                    self.set_phase(phase);
+                }
+                false
+            }
         }
     }
 
     fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if props.table != self.props.table {
+            if let Some(table) = &self.props.table {
+                table.unsubscribe(&self.table_changed);
+            }
+            if let Some(table) = &props.table {
+                table.subscribe(&self.table_changed);
+            }
+        }
         self.props = props;
+        if self.has_route {
+            // Migrations may be loaded after the current route was matched, like redirect maps
+            // that are fetched at runtime.
+            let route = self.route.clone();
+            if self.migrate(&route) {
+                return false;
+            }
+            // The loader may have been replaced by one that hasn't loaded the route's chunks.
+            self.load_chunks(&route);
+        }
         true
     }
+
+    fn destroy(&mut self) {
+        if let Some(table) = &self.props.table {
+            table.unsubscribe(&self.table_changed);
+        }
+    }
 }
 
-impl<T: for<'de> RouterState<'de>, SW: Switch + 'static, M: 'static> Renderable<Router<T, SW, M>>
-    for Router<T, SW, M>
+impl<T, SW, M, N> Renderable<Router<T, SW, M, N>> for Router<T, SW, M, N>
+where
+    T: RouterState,
+    SW: Switch + 'static,
+    M: 'static,
+    N: RouteNamespace,
 {
     fn view(&self) -> VNode<Self> {
-        let switch: Option<SW> = SW::switch(self.route.clone());
-        match switch {
-            Some(switch) => (&self.props.render.0)(switch),
-            None => {
-                if let Some(redirect_fn) = &self.props.redirect {
-                    let switch: SW = (redirect_fn.0)(self.route.clone()); // TODO This should be used to set the route in the browser
-                    (&self.props.render.0)(switch)
-                } else {
-                    html! {format!{"No route for {}", self.route.route}}
-                }
+        if !self.props.wait_for_ready {
+            return self.render_guarded_route();
+        }
+        // The previous page was revealed already, so it isn't given a handle that could reveal the
+        // new one.
+        let previous = self
+            .previous_route
+            .as_ref()
+            .map(|previous_route| self.render_route(previous_route, None));
+        let current = self.render_guarded_route();
+        let (shown, hidden) = match previous {
+            Some(previous) => (previous, current),
+            None => (current, html! {}),
+        };
+        let (first, second) = if self.shown_slot == 0 {
+            (shown, hidden)
+        } else {
+            (hidden, shown)
+        };
+        let style = |slot: usize| {
+            if slot == self.shown_slot {
+                ""
+            } else {
+                "display: none;"
+            }
+        };
+        html! {
+            <>
+                <div style=style(0),>{first}</div>
+                <div style=style(1),>{second}</div>
+            </>
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    type RawRouter = Router<(), String, ()>;
+
+    /// Calls the raw render function with the route and the captures the matcher produces for it,
+    /// returning what the function received.
+    fn render_raw(matcher: Option<&RouteMatcher>, route: &str) -> (String, Option<String>) {
+        let received = Rc::new(RefCell::new(None));
+        let render = {
+            let received = received.clone();
+            RawRouter::with_raw_render(move |route: &Route<()>, captures: &Captures| {
+                *received.borrow_mut() = Some((route.route.clone(), captures.get("id").cloned()));
+                html! {}
+            })
+        };
+        match &render.0 {
+            RenderFunction::Raw(render) => {
+                render(&Route::from(route), &route_captures(matcher, route));
             }
+            _ => panic!("with_raw_render should create a raw render function"),
         }
+        let received = received.borrow_mut().take();
+        received.expect("the render function should be called")
+    }
+
+    #[test]
+    fn raw_render_receives_the_route_and_its_captures() {
+        let matcher = RouteMatcher::try_from("/users/{id}").expect("should parse");
+        assert_eq!(
+            render_raw(Some(&matcher), "/users/42"),
+            ("/users/42".to_string(), Some("42".to_string()))
+        )
+    }
+
+    #[test]
+    fn raw_render_receives_no_captures_without_a_matcher() {
+        assert_eq!(
+            render_raw(None, "/users/42"),
+            ("/users/42".to_string(), None)
+        )
+    }
+
+    #[test]
+    fn raw_render_receives_no_captures_when_the_matcher_rejects_the_route() {
+        let matcher = RouteMatcher::try_from("/users/{id}").expect("should parse");
+        assert_eq!(
+            render_raw(Some(&matcher), "/posts/42"),
+            ("/posts/42".to_string(), None)
+        )
+    }
+
+    #[test]
+    fn routes_that_did_not_change_are_only_handled_if_they_were_forced() {
+        let route = Route::<()>::from("/users/42");
+        assert!(needs_update(None, &route, false));
+        assert!(needs_update(Some(&Route::from("/users/43")), &route, false));
+        assert!(!needs_update(Some(&route), &route, false));
+        assert!(needs_update(Some(&route), &route, true));
+    }
+
+    #[test]
+    fn pages_that_wait_keep_the_previous_page_shown() {
+        let mut route = Route::<()>::from("/");
+        let mut previous_route = None;
+        let revealed = receive_route(
+            &mut route,
+            &mut previous_route,
+            Route::from("/reports"),
+            true,
+        );
+        assert!(!revealed);
+        assert_eq!(route, Route::from("/reports"));
+        assert_eq!(previous_route, Some(Route::from("/")));
+
+        // Navigating to another page that waits keeps the page that is shown.
+        receive_route(
+            &mut route,
+            &mut previous_route,
+            Route::from("/reports/2019"),
+            true,
+        );
+        assert_eq!(route, Route::from("/reports/2019"));
+        assert_eq!(previous_route, Some(Route::from("/")));
+    }
+
+    #[test]
+    fn hidden_pages_stay_hidden_when_their_route_is_received_again() {
+        let mut route = Route::<()>::from("/reports");
+        let mut previous_route = Some(Route::from("/"));
+        let revealed = receive_route(
+            &mut route,
+            &mut previous_route,
+            Route::from("/reports"),
+            false,
+        );
+        assert!(!revealed);
+        assert_eq!(previous_route, Some(Route::from("/")));
+    }
+
+    #[test]
+    fn hidden_pages_are_replaced_by_pages_that_do_not_wait() {
+        let mut route = Route::<()>::from("/reports");
+        let mut previous_route = Some(Route::from("/"));
+        let revealed = receive_route(
+            &mut route,
+            &mut previous_route,
+            Route::from("/about"),
+            false,
+        );
+        assert!(revealed);
+        assert_eq!(route, Route::from("/about"));
+        assert_eq!(previous_route, None);
+
+        let revealed = receive_route(&mut route, &mut previous_route, Route::from("/"), false);
+        assert!(!revealed);
+    }
+
+    #[test]
+    fn pages_that_wait_are_loading() {
+        assert_eq!(
+            phase_before_render(&GuardState::Allowed, false),
+            NavigationPhase::Rendering
+        );
+        assert_eq!(
+            phase_before_render(&GuardState::Allowed, true),
+            NavigationPhase::Loading
+        );
+        assert_eq!(
+            phase_before_render(&GuardState::Pending(1), false),
+            NavigationPhase::Loading
+        );
+        assert_eq!(
+            phase_before_render(&GuardState::Redirecting, false),
+            NavigationPhase::Loading
+        );
+    }
+
+    #[test]
+    fn redirects_to_the_current_route_cancel_the_navigation() {
+        let current = Route::<()>::from("/home");
+        let mut guard = GuardState::Allowed;
+        let mut phase = RouterPhase::default();
+        phase.start_matching(None);
+        let redirect = guard
+            .apply(
+                1,
+                GuardResult::Redirect(current.clone()),
+                &Callback::from(|_| ()),
+            )
+            .expect("the guard should redirect");
+        phase.cancel_matching(None);
+        // The redirect isn't handled once it is received, as the Router already has the route.
+        assert!(!needs_update(Some(&current), &redirect, false));
+        assert_eq!(phase.get(), &NavigationPhase::Idle);
+        assert!(!phase.get().is_busy());
+    }
+
+    #[test]
+    fn forbidden_pages_are_rendered() {
+        let forbidden = GuardState::Forbidden("Admins only".to_string());
+        assert_eq!(
+            phase_before_render(&forbidden, true),
+            NavigationPhase::Rendering
+        );
+    }
+
+    #[test]
+    fn routes_with_other_states_are_handled() {
+        let route = Route {
+            route: "/users/42".to_string(),
+            state: Some(1),
+        };
+        let other_state = Route {
+            state: Some(2),
+            ..route.clone()
+        };
+        assert!(needs_update(Some(&route), &other_state, false));
+        assert!(!needs_update(Some(&route), &route.clone(), false));
+    }
+
+    /// Calls the switched render function like the Router does for a route that `/users/{id}`
+    /// matches, with the route itself as the switched value, returning the error it failed with.
+    fn render_switched(render: &Render<(), String, ()>, route: &str) -> Result<(), String> {
+        let matcher = RouteMatcher::try_from("/users/{id}").expect("should parse");
+        let captures = route_captures(Some(&matcher), route);
+        let context = RouteContext::with_locale(Route::from(route), &captures, None);
+        match &render.0 {
+            RenderFunction::Switched(render) => {
+                render(route.to_string(), &context, &captures).map(|_| ())
+            }
+            _ => panic!("the render function should be called with the switched route"),
+        }
+    }
+
+    #[test]
+    fn switched_render_receives_the_route_and_its_captures() {
+        let received = Rc::new(RefCell::new(None));
+        let render = {
+            let received = received.clone();
+            RawRouter::render_with_matches(
+                move |switch: String, route: &Route<()>, captures: &Captures| {
+                    *received.borrow_mut() =
+                        Some((switch, route.route.clone(), captures.get("id").cloned()));
+                    html! {}
+                },
+            )
+        };
+        render_switched(&render, "/users/42").expect("should render");
+        assert_eq!(
+            received.borrow_mut().take(),
+            Some((
+                "/users/42".to_string(),
+                "/users/42".to_string(),
+                Some("42".to_string())
+            ))
+        )
+    }
+
+    #[test]
+    fn context_render_receives_the_context_of_the_route() {
+        let received = Rc::new(RefCell::new(None));
+        let render = {
+            let received = received.clone();
+            RawRouter::render_with_context(move |switch: String, context: &RouteContext<()>| {
+                *received.borrow_mut() = Some((switch, context.clone()));
+                html! {}
+            })
+        };
+        render_switched(&render, "/users/42").expect("should render");
+        let (switch, context) = received.borrow_mut().take().expect("should render");
+        assert_eq!(switch, "/users/42");
+        assert_eq!(context.route(), &Route::from("/users/42"));
+        assert_eq!(context.parse::<u32>("id"), Some(42));
+    }
+
+    #[test]
+    fn fallible_render_reports_its_error() {
+        let render = RawRouter::try_render(|switch: String| -> Result<Html<RawRouter>, String> {
+            if switch == "/users/0" {
+                Err("There is no user 0".to_string())
+            } else {
+                Ok(html! {})
+            }
+        });
+        assert_eq!(render_switched(&render, "/users/42"), Ok(()));
+        assert_eq!(
+            render_switched(&render, "/users/0"),
+            Err("There is no user 0".to_string())
+        );
     }
 }
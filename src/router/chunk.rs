@@ -0,0 +1,259 @@
+//! Lazily loads the code that some routes need before they can be rendered.
+
+use std::{
+    cell::RefCell,
+    collections::{hash_map::Entry, HashMap},
+    fmt::{self, Debug, Formatter},
+    rc::Rc,
+};
+use yew::Callback;
+
+/// The loading state of a chunk.
+enum ChunkState {
+    /// The chunk is being loaded, and these callbacks are waiting for it.
+    Loading(Vec<Callback<()>>),
+    Loaded,
+    /// The chunk failed to load, with this error.
+    Failed(String),
+}
+
+/// The chunks that have been requested from a loader, by name.
+type Chunks = Rc<RefCell<HashMap<&'static str, ChunkState>>>;
+
+/// Loads chunks of code that routes depend on, so large apps can split their code by route.
+///
+/// Routes declare the chunk they need with `#[meta(chunk = "name")]`.
+/// When a `Router` with a `chunk_loader` navigates to one of them, the loader is called with the
+/// name of each chunk that the route and the routes nested in it need, along with a callback to
+/// call once the chunk is ready.
+/// Until then, the Router renders its `placeholder`.
+///
+/// Each chunk is only loaded once by a loader and its clones, so Routers that should share their
+/// chunks should be given clones of the same loader.
+/// Loading it is up to the loader, which will likely use a dynamic `import()` through `js!`.
+/// If the chunk fails to load, the callback shouldn't be called, unless the loader was created
+/// with `ChunkLoader::fallible`, in which case the Router renders its `error_view` until the
+/// failure is retried.
+///
+/// # Example
+/// ```
+/// use yew::Callback;
+/// use yew_router::router::ChunkLoader;
+///
+/// let loader = ChunkLoader::new(|chunk: &'static str, loaded: Callback<()>| {
+///     // Start loading the chunk here, and call `loaded.emit(())` once it is ready.
+///     loaded.emit(())
+/// });
+/// ```
+#[derive(Clone)]
+pub struct ChunkLoader {
+    load: Rc<dyn Fn(&'static str, Callback<Result<(), String>>)>,
+    chunks: Chunks,
+}
+
+impl ChunkLoader {
+    /// Creates a chunk loader from a function that starts loading a chunk.
+    pub fn new<F: Fn(&'static str, Callback<()>) + 'static>(f: F) -> Self {
+        ChunkLoader::fallible(move |chunk, done: Callback<Result<(), String>>| {
+            f(chunk, Callback::from(move |_| done.emit(Ok(()))))
+        })
+    }
+
+    /// Creates a chunk loader from a function that starts loading a chunk, and reports whether it
+    /// loaded through its callback.
+    ///
+    /// # Example
+    /// ```
+    /// use yew::Callback;
+    /// use yew_router::router::ChunkLoader;
+    ///
+    /// let loader = ChunkLoader::fallible(
+    ///     |chunk: &'static str, done: Callback<Result<(), String>>| {
+    ///         done.emit(Err(format!("The chunk '{}' isn't available offline", chunk)))
+    ///     },
+    /// );
+    /// ```
+    pub fn fallible<F: Fn(&'static str, Callback<Result<(), String>>) + 'static>(f: F) -> Self {
+        ChunkLoader {
+            load: Rc::new(f),
+            chunks: Rc::new(RefCell::new(HashMap::new())),
+        }
+    }
+
+    /// Starts loading the chunk, unless it was already requested.
+    ///
+    /// If it is still loading, `on_load` is called once it loads, or once it fails to load.
+    pub(crate) fn load(&self, chunk: &'static str, on_load: &Callback<()>) {
+        let should_load = match self.chunks.borrow_mut().entry(chunk) {
+            Entry::Occupied(mut entry) => {
+                if let ChunkState::Loading(waiting) = entry.get_mut() {
+                    if !waiting.contains(on_load) {
+                        waiting.push(on_load.clone());
+                    }
+                }
+                false
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(ChunkState::Loading(vec![on_load.clone()]));
+                true
+            }
+        };
+        // The loader is called after the chunks are released, as it may finish loading
+        // immediately.
+        if should_load {
+            let chunks = self.chunks.clone();
+            (self.load)(
+                chunk,
+                Callback::from(move |result| chunk_loaded(&chunks, chunk, result)),
+            );
+        }
+    }
+
+    /// Determines if the chunk has loaded, without loading it.
+    ///
+    /// If it failed to load, the error is returned.
+    pub(crate) fn is_loaded(&self, chunk: &'static str) -> Result<bool, String> {
+        match self.chunks.borrow().get(chunk) {
+            Some(ChunkState::Loaded) => Ok(true),
+            Some(ChunkState::Failed(error)) => Err(error.clone()),
+            Some(ChunkState::Loading(_)) | None => Ok(false),
+        }
+    }
+
+    /// Forgets the chunks that failed to load, so they are loaded again when they're needed.
+    pub(crate) fn retry_failed(&self) {
+        self.chunks.borrow_mut().retain(|_, state| match state {
+            ChunkState::Failed(_) => false,
+            _ => true,
+        })
+    }
+}
+
+/// Marks the chunk as loaded, or as failed, and notifies everything that was waiting for it.
+fn chunk_loaded(chunks: &Chunks, chunk: &'static str, result: Result<(), String>) {
+    let state = match result {
+        Ok(()) => ChunkState::Loaded,
+        Err(error) => {
+            log::error!("The chunk '{}' failed to load: {}", chunk, error);
+            ChunkState::Failed(error)
+        }
+    };
+    let previous = chunks.borrow_mut().insert(chunk, state);
+    if let Some(ChunkState::Loading(waiting)) = previous {
+        for callback in waiting {
+            callback.emit(())
+        }
+    }
+}
+
+impl Debug for ChunkLoader {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChunkLoader").finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::cell::Cell;
+
+    /// A loader whose chunks finish loading once they're resolved, along with the chunks it was
+    /// asked to load.
+    fn pending_loader() -> (
+        ChunkLoader,
+        Rc<RefCell<Vec<(&'static str, Callback<Result<(), String>>)>>>,
+    ) {
+        let requested = Rc::new(RefCell::new(Vec::new()));
+        let loader = {
+            let requested = requested.clone();
+            ChunkLoader::fallible(move |chunk, done| requested.borrow_mut().push((chunk, done)))
+        };
+        (loader, requested)
+    }
+
+    fn counter() -> (Callback<()>, Rc<Cell<usize>>) {
+        let count = Rc::new(Cell::new(0));
+        let callback = {
+            let count = count.clone();
+            Callback::from(move |_| count.set(count.get() + 1))
+        };
+        (callback, count)
+    }
+
+    #[test]
+    fn chunks_that_load_immediately_are_loaded() {
+        let loader = ChunkLoader::new(|_, loaded: Callback<()>| loaded.emit(()));
+        let (on_load, loads) = counter();
+        assert_eq!(loader.is_loaded("admin"), Ok(false));
+        loader.load("admin", &on_load);
+        assert_eq!(loader.is_loaded("admin"), Ok(true));
+        assert_eq!(loads.get(), 1);
+    }
+
+    #[test]
+    fn chunks_are_only_loaded_once() {
+        let (loader, requested) = pending_loader();
+        let (first, first_loads) = counter();
+        let (second, second_loads) = counter();
+        loader.load("admin", &first);
+        loader.load("admin", &first);
+        loader.clone().load("admin", &second);
+        assert_eq!(requested.borrow().len(), 1);
+        assert_eq!(loader.is_loaded("admin"), Ok(false));
+
+        let (_, done) = requested.borrow_mut().remove(0);
+        done.emit(Ok(()));
+        assert_eq!(loader.is_loaded("admin"), Ok(true));
+        assert_eq!((first_loads.get(), second_loads.get()), (1, 1));
+
+        loader.load("admin", &first);
+        assert!(requested.borrow().is_empty());
+        assert_eq!(first_loads.get(), 1);
+    }
+
+    #[test]
+    fn loaders_do_not_share_their_chunks() {
+        let (loader, requested) = pending_loader();
+        let (other, other_requested) = pending_loader();
+        let (on_load, _) = counter();
+        loader.load("admin", &on_load);
+        other.load("admin", &on_load);
+        assert_eq!(requested.borrow().len(), 1);
+        assert_eq!(other_requested.borrow().len(), 1);
+    }
+
+    #[test]
+    fn failed_chunks_report_their_error_until_they_are_retried() {
+        let (loader, requested) = pending_loader();
+        let (on_load, loads) = counter();
+        loader.load("admin", &on_load);
+        let (_, done) = requested.borrow_mut().remove(0);
+        done.emit(Err("offline".to_string()));
+        assert_eq!(loads.get(), 1);
+        assert_eq!(loader.is_loaded("admin"), Err("offline".to_string()));
+
+        loader.load("admin", &on_load);
+        assert!(requested.borrow().is_empty());
+
+        loader.retry_failed();
+        assert_eq!(loader.is_loaded("admin"), Ok(false));
+        loader.load("admin", &on_load);
+        assert_eq!(requested.borrow().len(), 1);
+    }
+
+    #[test]
+    fn retrying_only_forgets_the_failures_of_the_loader() {
+        let (loader, requested) = pending_loader();
+        let (other, other_requested) = pending_loader();
+        let (on_load, _) = counter();
+        for (loader, requested) in &[(&loader, &requested), (&other, &other_requested)] {
+            loader.load("admin", &on_load);
+            let (_, done) = requested.borrow_mut().remove(0);
+            done.emit(Err("offline".to_string()));
+        }
+
+        other.retry_failed();
+        assert_eq!(loader.is_loaded("admin"), Err("offline".to_string()));
+        assert_eq!(other.is_loaded("admin"), Ok(false));
+    }
+}
@@ -0,0 +1,174 @@
+//! The route matched by a `Router`, which is passed down to the components nested beneath it.
+use super::RouteReadiness;
+use crate::{
+    matcher::Captures,
+    route::{Route, RouterState},
+    Switch,
+};
+use std::{collections::HashMap, rc::Rc, str::FromStr};
+
+/// A cheaply clonable handle to the route a `Router` rendered, along with the captures produced by
+/// its `matcher` prop.
+///
+/// The Router gives it to render functions created with
+/// [`Router::render_with_context`](struct.Router.html#method.render_with_context), which can pass
+/// it down to deeply nested components as a single prop, instead of the route and each of its
+/// captures.
+#[derive(Debug)]
+pub struct RouteContext<T = ()> {
+    inner: Rc<Inner<T>>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Inner<T> {
+    route: Route<T>,
+    captures: HashMap<String, String>,
+    locale: Option<String>,
+    readiness: Option<RouteReadiness>,
+}
+
+impl<T> Clone for RouteContext<T> {
+    fn clone(&self) -> Self {
+        RouteContext {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T: RouterState> PartialEq for RouteContext<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.inner, &other.inner) || self.inner == other.inner
+    }
+}
+
+impl<T: RouterState> RouteContext<T> {
+    /// Creates a handle from a route and the captures that were collected from it.
+    pub fn new(route: Route<T>, captures: &Captures) -> Self {
+        RouteContext::with_locale(route, captures, None)
+    }
+
+    /// Creates a handle from a route, the captures that were collected from it, and the locale of
+    /// its url.
+    pub fn with_locale(route: Route<T>, captures: &Captures, locale: Option<String>) -> Self {
+        RouteContext::with_readiness(route, captures, locale, None)
+    }
+
+    /// Creates the handle that a Router gives to the page it renders, which tells the Router
+    /// when it is ready through `readiness`.
+    pub(crate) fn with_readiness(
+        route: Route<T>,
+        captures: &Captures,
+        locale: Option<String>,
+        readiness: Option<RouteReadiness>,
+    ) -> Self {
+        let captures = captures
+            .iter()
+            .map(|(key, value)| ((*key).to_string(), value.clone()))
+            .collect();
+        RouteContext {
+            inner: Rc::new(Inner {
+                route,
+                captures,
+                locale,
+                readiness,
+            }),
+        }
+    }
+
+    /// The route that was rendered.
+    pub fn route(&self) -> &Route<T> {
+        &self.inner.route
+    }
+
+    /// The locale of the route's url, if the `RoutingStrategy` of the Router's namespace
+    /// localizes routes, like `LocalizedStrategy` does.
+    pub fn locale(&self) -> Option<&str> {
+        self.inner.locale.as_ref().map(String::as_str)
+    }
+
+    /// The handle that the page tells the Router it's ready to be shown with, if the Router
+    /// waits for pages to be ready.
+    ///
+    /// See [`RouteReadiness`](struct.RouteReadiness.html).
+    pub fn readiness(&self) -> Option<&RouteReadiness> {
+        self.inner.readiness.as_ref()
+    }
+
+    /// Gets the raw value captured under the given name.
+    pub fn capture(&self, name: &str) -> Option<&str> {
+        self.inner.captures.get(name).map(String::as_str)
+    }
+
+    /// Parses the value captured under the given name.
+    ///
+    /// Returns `None` if nothing was captured under the name, or it couldn't be parsed.
+    pub fn parse<U: FromStr>(&self, name: &str) -> Option<U> {
+        self.capture(name).and_then(|value| value.parse().ok())
+    }
+
+    /// Switches the route into the given type.
+    ///
+    /// This allows nested components to use a `Switch` that only describes the part of the route
+    /// they care about.
+    pub fn switch<SW: Switch>(&self) -> Option<SW> {
+        SW::switch(self.inner.route.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::cell::Cell;
+    use yew::Callback;
+
+    fn context(route: &str, captures: &[(&'static str, &str)]) -> RouteContext<()> {
+        let captures: Captures = captures
+            .iter()
+            .map(|(key, value)| (*key, (*value).to_string()))
+            .collect();
+        RouteContext::with_locale(Route::from(route), &captures, Some("de".to_string()))
+    }
+
+    #[test]
+    fn captures_can_be_read_and_parsed() {
+        let context = context("/users/42", &[("id", "42"), ("tab", "posts")]);
+        assert_eq!(context.route(), &Route::from("/users/42"));
+        assert_eq!(context.locale(), Some("de"));
+        assert_eq!(context.capture("tab"), Some("posts"));
+        assert_eq!(context.parse::<u32>("id"), Some(42));
+        assert_eq!(context.parse::<u32>("tab"), None);
+        assert_eq!(context.capture("missing"), None);
+    }
+
+    #[test]
+    fn readiness_tells_the_router_that_rendered_the_page() {
+        let ready = Rc::new(Cell::new(None));
+        let readiness = {
+            let ready = ready.clone();
+            RouteReadiness::new(
+                3,
+                Callback::from(move |navigation| ready.set(Some(navigation))),
+            )
+        };
+        let page_context = RouteContext::<()>::with_readiness(
+            Route::from("/users/42"),
+            &Captures::new(),
+            None,
+            Some(readiness),
+        );
+        page_context
+            .readiness()
+            .expect("the Router gave the page a handle")
+            .ready();
+        assert_eq!(ready.get(), Some(3));
+        assert!(context("/users/42", &[]).readiness().is_none());
+    }
+
+    #[test]
+    fn contexts_of_the_same_route_and_captures_are_equal() {
+        let first = context("/users/42", &[("id", "42")]);
+        assert_eq!(first, first.clone());
+        assert_eq!(first, context("/users/42", &[("id", "42")]));
+        assert_ne!(first, context("/users/43", &[("id", "43")]));
+    }
+}
@@ -0,0 +1,107 @@
+//! Moves keyboard focus and announces the new page after the route changes.
+
+use stdweb::{
+    unstable::TryInto,
+    web::{
+        document, set_timeout, Element, HtmlElement, IElement, IHtmlElement, INode,
+        INonElementParentNode, IParentNode,
+    },
+};
+
+/// The id of the live region that the `FocusManager` announces pages with.
+const ANNOUNCER_ID: &str = "yew-router-announcer";
+
+/// Hides the live region visually, while keeping it available to screen readers.
+const VISUALLY_HIDDEN: &str = "position: absolute; width: 1px; height: 1px; margin: -1px; \
+                               padding: 0; overflow: hidden; clip: rect(0, 0, 0, 0); \
+                               white-space: nowrap; border: 0;";
+
+/// Makes route changes perceivable for keyboard and screen-reader users.
+///
+/// When given to a `Router` through its `focus` prop, after each route change the first element
+/// matching `selector` is focused, and if `announce` is set, its text is read out through an
+/// [ARIA live region](https://developer.mozilla.org/en-US/docs/Web/Accessibility/ARIA/ARIA_Live_Regions).
+///
+/// The route that the page was loaded with is not announced, as the browser already does that.
+///
+/// # Example
+/// ```
+/// use yew_router::router::FocusManager;
+/// let focus = FocusManager {
+///     selector: "main h2".to_string(),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct FocusManager {
+    /// The CSS selector of the element that receives focus, which defaults to `h1`.
+    ///
+    /// Elements that aren't focusable are given a `tabindex` of `-1`, so they can be focused
+    /// without being added to the tab order.
+    pub selector: String,
+    /// Whether the new page is announced, which defaults to `true`.
+    ///
+    /// The text of the focused element is announced, or the route if nothing matched `selector`.
+    pub announce: bool,
+}
+
+impl Default for FocusManager {
+    fn default() -> Self {
+        FocusManager {
+            selector: "h1".to_string(),
+            announce: true,
+        }
+    }
+}
+
+impl FocusManager {
+    /// Focuses and announces the page for the new route, once it has been rendered.
+    pub(crate) fn route_changed(&self, route: &str) {
+        let focus = self.clone();
+        let route = route.to_string();
+        // Yew patches the DOM after `update` returns, so this has to wait until then.
+        set_timeout(move || focus.focus_and_announce(&route), 0);
+    }
+
+    fn focus_and_announce(&self, route: &str) {
+        let target: Option<Element> = document()
+            .query_selector(&self.selector)
+            .ok()
+            .and_then(|element| element);
+        let title = target.and_then(|element| {
+            if !element.has_attribute("tabindex") {
+                let _ = element.set_attribute("tabindex", "-1");
+            }
+            let html_element: Option<HtmlElement> = element.clone().try_into().ok();
+            if let Some(html_element) = html_element {
+                html_element.focus();
+            }
+            element.text_content()
+        });
+
+        if self.announce {
+            let message = title
+                .map(|title| title.trim().to_string())
+                .filter(|title| !title.is_empty())
+                .unwrap_or_else(|| route.to_string());
+            if let Some(announcer) = announcer() {
+                announcer.set_text_content(&message);
+            }
+        }
+    }
+}
+
+/// Gets the live region, creating it if it doesn't exist yet.
+fn announcer() -> Option<Element> {
+    if let Some(announcer) = document().get_element_by_id(ANNOUNCER_ID) {
+        return Some(announcer);
+    }
+    let announcer = document().create_element("div").ok()?;
+    let _ = announcer.set_attribute("id", ANNOUNCER_ID);
+    let _ = announcer.set_attribute("role", "status");
+    let _ = announcer.set_attribute("aria-live", "polite");
+    let _ = announcer.set_attribute("aria-atomic", "true");
+    let _ = announcer.set_attribute("style", VISUALLY_HIDDEN);
+    document().body()?.append_child(&announcer);
+    Some(announcer)
+}
@@ -0,0 +1,214 @@
+//! Decides whether the routes that a `Router` switches to may be shown.
+
+use crate::route::Route;
+use std::{
+    fmt::{self, Debug, Formatter},
+    rc::Rc,
+};
+use yew::Callback;
+
+/// What a route guard decides about a route.
+///
+/// Guards are given to a `Router` through its `guard` prop, with
+/// [`Router::guard`](struct.Router.html#method.guard).
+#[derive(Debug, Clone, PartialEq)]
+pub enum GuardResult<T = ()> {
+    /// The page of the route is rendered.
+    Allow,
+    /// The route is replaced by another one, like a login page.
+    Redirect(Route<T>),
+    /// The page isn't rendered, and the `forbidden_view` of the Router is rendered instead, with
+    /// the reason.
+    Forbidden(String),
+    /// The decision isn't known yet, like while an expired token is refreshed.
+    ///
+    /// The `pending_view` of the Router is rendered until the check is done.
+    Pending(PendingGuard<T>),
+}
+
+impl<T> GuardResult<T> {
+    /// Decides about the route asynchronously.
+    ///
+    /// The check is started once the guard returns, with a callback that it calls with the
+    /// decision once it is known.
+    ///
+    /// # Example
+    /// ```
+    /// use yew::Callback;
+    /// use yew_router::router::GuardResult;
+    ///
+    /// let result: GuardResult<()> = GuardResult::pending(|done: Callback<GuardResult<()>>| {
+    ///     // Refresh the token here, and call `done` with the decision once it is refreshed.
+    ///     done.emit(GuardResult::Allow)
+    /// });
+    /// ```
+    pub fn pending<F: Fn(Callback<GuardResult<T>>) + 'static>(check: F) -> Self {
+        GuardResult::Pending(PendingGuard(Rc::new(check)))
+    }
+
+    /// Redirects to a page like `/login`, remembering the route that was rejected in its query,
+    /// so it can be returned to with `RouteService::take_intended_route`, or the
+    /// `ReturnToIntendedRoute` request of a `RouteAgent`.
+    ///
+    /// # Example
+    /// ```
+    /// use yew_router::{route::Route, router::GuardResult};
+    ///
+    /// let rejected = Route::<()>::from("/settings");
+    /// assert_eq!(
+    ///     GuardResult::redirect_remembering(Route::from("/login"), &rejected),
+    ///     GuardResult::Redirect(Route::from("/login?return_to=/settings"))
+    /// );
+    /// ```
+    pub fn redirect_remembering(redirect: Route<T>, rejected: &Route<T>) -> Self {
+        GuardResult::Redirect(redirect.with_intended_route(rejected))
+    }
+}
+
+/// A route guard's decision that is made asynchronously.
+pub struct PendingGuard<T = ()>(Rc<dyn Fn(Callback<GuardResult<T>>)>);
+
+impl<T> PendingGuard<T> {
+    /// Starts the check, which calls `done` with the decision.
+    pub(crate) fn start(&self, done: Callback<GuardResult<T>>) {
+        (self.0)(done)
+    }
+}
+
+impl<T> Clone for PendingGuard<T> {
+    fn clone(&self) -> Self {
+        PendingGuard(self.0.clone())
+    }
+}
+
+impl<T> PartialEq for PendingGuard<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl<T> Debug for PendingGuard<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("PendingGuard").finish()
+    }
+}
+
+/// What the `Router` renders for its route, according to its guard.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum GuardState {
+    Allowed,
+    Forbidden(String),
+    /// The decision is pending, for the check with this number.
+    Pending(usize),
+    /// The guard redirects away from the route, which is navigated to once the Router is mounted.
+    Redirecting,
+}
+
+impl GuardState {
+    /// Applies a decision of the guard, made for the check with the given number, returning the
+    /// route to redirect to, if it redirects.
+    ///
+    /// Pending decisions are started with `resolved`, which receives the decision along with the
+    /// number of the check.
+    pub(crate) fn apply<T: 'static>(
+        &mut self,
+        check: usize,
+        result: GuardResult<T>,
+        resolved: &Callback<(usize, GuardResult<T>)>,
+    ) -> Option<Route<T>> {
+        match result {
+            GuardResult::Allow => {
+                *self = GuardState::Allowed;
+                None
+            }
+            GuardResult::Redirect(redirect) => Some(redirect),
+            GuardResult::Forbidden(reason) => {
+                *self = GuardState::Forbidden(reason);
+                None
+            }
+            GuardResult::Pending(pending) => {
+                *self = GuardState::Pending(check);
+                pending.start(resolved.reform(move |result: GuardResult<T>| (check, result)));
+                None
+            }
+        }
+    }
+
+    /// Determines if a decision that was made asynchronously for the check with the given number
+    /// is still awaited, which it isn't once the Router has checked another route.
+    pub(crate) fn awaits(&self, check: usize) -> bool {
+        *self == GuardState::Pending(check)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::cell::RefCell;
+
+    /// A callback that records the decisions it receives.
+    fn recorder() -> (
+        Callback<(usize, GuardResult<()>)>,
+        Rc<RefCell<Vec<(usize, GuardResult<()>)>>>,
+    ) {
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let callback = {
+            let received = received.clone();
+            Callback::from(move |decision| received.borrow_mut().push(decision))
+        };
+        (callback, received)
+    }
+
+    #[test]
+    fn decisions_are_applied_to_the_state() {
+        let (resolved, _) = recorder();
+        let mut state = GuardState::Pending(1);
+        assert_eq!(state.apply(1, GuardResult::Allow, &resolved), None);
+        assert_eq!(state, GuardState::Allowed);
+
+        let forbidden = GuardResult::Forbidden("Admins only".to_string());
+        assert_eq!(state.apply(2, forbidden, &resolved), None);
+        assert_eq!(state, GuardState::Forbidden("Admins only".to_string()));
+    }
+
+    #[test]
+    fn redirects_are_returned_without_changing_the_state() {
+        let (resolved, _) = recorder();
+        let mut state = GuardState::Allowed;
+        let redirect = GuardResult::Redirect(Route::from("/login"));
+        assert_eq!(
+            state.apply(1, redirect, &resolved),
+            Some(Route::from("/login"))
+        );
+        assert_eq!(state, GuardState::Allowed);
+    }
+
+    #[test]
+    fn pending_decisions_are_started_for_their_check() {
+        let (resolved, received) = recorder();
+        let mut state = GuardState::Allowed;
+        let pending =
+            GuardResult::pending(|done: Callback<GuardResult<()>>| done.emit(GuardResult::Allow));
+        assert_eq!(state.apply(3, pending, &resolved), None);
+        assert_eq!(state, GuardState::Pending(3));
+        assert_eq!(*received.borrow(), vec![(3, GuardResult::Allow)]);
+    }
+
+    #[test]
+    fn stale_decisions_are_not_awaited() {
+        let (resolved, _) = recorder();
+        let mut state = GuardState::Allowed;
+        let pending = GuardResult::pending(|_: Callback<GuardResult<()>>| {});
+        state.apply(1, pending.clone(), &resolved);
+        assert!(state.awaits(1));
+
+        // The Router checked another route, whose decision is pending too.
+        state.apply(2, pending, &resolved);
+        assert!(!state.awaits(1));
+        assert!(state.awaits(2));
+
+        state.apply(3, GuardResult::Allow, &resolved);
+        assert!(!state.awaits(2));
+        assert!(!GuardState::Redirecting.awaits(3));
+    }
+}
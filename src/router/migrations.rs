@@ -0,0 +1,306 @@
+//! Moves old routes, like published deep links, to the routes that replaced them.
+use crate::{
+    matcher::{MatcherToken, RouteMatcher},
+    route::RouteParts,
+};
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug, Formatter},
+    hash::BuildHasher,
+    rc::Rc,
+};
+use yew_router_route_parser::PrettyParseError;
+
+type Remap = Rc<dyn Fn(&mut HashMap<String, String>)>;
+
+/// A migration from the routes matched by one matcher, to the route built from another.
+#[derive(Clone)]
+struct Migration {
+    from: RouteMatcher,
+    to: RouteMatcher,
+    /// Whether `from` mentions a query or fragment, which are otherwise kept as they are.
+    matches_whole_route: bool,
+    remap: Option<Remap>,
+}
+
+/// A table of migrations from old routes to the routes that replaced them.
+///
+/// When given to a `Router` through its `migrations` prop, routes that one of the migrations
+/// applies to are replaced by their new route before they are matched, so the browser shows the
+/// modern url.
+///
+/// A migration is declared with a matcher string for the old routes, like `/posts/{id}`, and one
+/// for the new route, like `/blog/{id}`, whose named captures are filled with the values captured
+/// from the old route.
+/// If the old matcher doesn't mention a query or fragment, those of the old route are kept.
+///
+/// Migrations are tried in the order they are declared, and the route they produce is migrated
+/// again, so routes can be moved several times as an app evolves.
+///
+/// # Example
+/// ```
+/// use std::collections::HashMap;
+/// use yew_router::router::RouteMigrations;
+///
+/// let migrations = RouteMigrations::new()
+///     .migrate("/posts/{id}", "/blog/{id}")
+///     .migrate_with(
+///         "/user.php?uid={uid}",
+///         "/users/{id}",
+///         |captures: &mut HashMap<String, String>| {
+///             if let Some(uid) = captures.remove("uid") {
+///                 captures.insert("id".to_string(), uid);
+///             }
+///         },
+///     );
+/// assert_eq!(
+///     migrations.migrate_route("/posts/42?ref=feed"),
+///     Some("/blog/42?ref=feed".to_string())
+/// );
+/// assert_eq!(
+///     migrations.migrate_route("/user.php?uid=7"),
+///     Some("/users/7".to_string())
+/// );
+/// assert_eq!(migrations.migrate_route("/blog/42"), None);
+/// ```
+#[derive(Clone, Default)]
+pub struct RouteMigrations {
+    migrations: Vec<Migration>,
+}
+
+impl RouteMigrations {
+    /// Creates an empty table.
+    pub fn new() -> Self {
+        RouteMigrations::default()
+    }
+
+    /// Migrates the routes matched by `from` to the route `to`, with its captures filled in.
+    ///
+    /// # Panics
+    /// Panics if one of the matcher strings can't be parsed.
+    pub fn migrate(self, from: &str, to: &str) -> Self {
+        self.add(from, to, None)
+    }
+
+    /// Migrates the routes matched by `from` to the route `to`, after the captures of the old
+    /// route are changed by `remap`, which can rename, convert, or add them.
+    ///
+    /// # Panics
+    /// Panics if one of the matcher strings can't be parsed.
+    pub fn migrate_with<F: Fn(&mut HashMap<String, String>) + 'static>(
+        self,
+        from: &str,
+        to: &str,
+        remap: F,
+    ) -> Self {
+        self.add(from, to, Some(Rc::new(remap)))
+    }
+
+    /// Migrates the routes matched by `from` to the route `to`, like `migrate`, but returns an
+    /// error instead of panicking if one of the matcher strings can't be parsed.
+    pub fn try_migrate<'a>(self, from: &'a str, to: &'a str) -> Result<Self, PrettyParseError<'a>> {
+        self.try_add(from, to, None)
+    }
+
+    /// Creates migrations from a map of matcher strings for old routes to the routes that they
+    /// redirect to, like one that is loaded at runtime, so routes can be fixed up without
+    /// rebuilding the app.
+    ///
+    /// As maps aren't ordered, the migrations are tried from the most specific matcher string to
+    /// the least, so literal sections, like `/old/special`, are tried before captures at the same
+    /// place, like `/old/{id}`.
+    pub fn from_redirects<'a, S: BuildHasher>(
+        redirects: &'a HashMap<String, String, S>,
+    ) -> Result<Self, PrettyParseError<'a>> {
+        let mut redirects = redirects
+            .iter()
+            .map(|(from, to)| {
+                let matcher = RouteMatcher::try_from(from)?;
+                Ok((specificity(&matcher.tokens), from, to))
+            })
+            .collect::<Result<Vec<_>, PrettyParseError<'a>>>()?;
+        redirects.sort();
+        redirects
+            .into_iter()
+            .try_fold(RouteMigrations::new(), |migrations, (_, from, to)| {
+                migrations.try_migrate(from, to)
+            })
+    }
+
+    fn add(self, from: &str, to: &str, remap: Option<Remap>) -> Self {
+        self.try_add(from, to, remap).unwrap_or_else(|error| {
+            panic!("The route '{}' couldn't be parsed: {}", error.input, error)
+        })
+    }
+
+    fn try_add<'a>(
+        mut self,
+        from: &'a str,
+        to: &'a str,
+        remap: Option<Remap>,
+    ) -> Result<Self, PrettyParseError<'a>> {
+        self.migrations.push(Migration {
+            from: RouteMatcher::try_from(from)?,
+            to: RouteMatcher::try_from(to)?,
+            matches_whole_route: from.contains('?') || from.contains('#'),
+            remap,
+        });
+        Ok(self)
+    }
+
+    /// Gets the route that the route migrates to, if any of the migrations apply to it.
+    pub fn migrate_route(&self, route: &str) -> Option<String> {
+        let mut applied = vec![false; self.migrations.len()];
+        let mut migrated = route.to_string();
+        // Every migration applies once at most, so migrations that lead in a circle end.
+        while let Some((index, next)) = self.migrate_once(&migrated, &applied) {
+            applied[index] = true;
+            migrated = next;
+        }
+        if migrated == route {
+            None
+        } else {
+            Some(migrated)
+        }
+    }
+
+    /// Applies the first migration that applies to the route and hasn't been applied yet,
+    /// returning its index along with the new route.
+    fn migrate_once(&self, route: &str, applied: &[bool]) -> Option<(usize, String)> {
+        let parts = RouteParts::new(route);
+        self.migrations
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !applied[*index])
+            .find_map(|(index, migration)| {
+                let matched = if migration.matches_whole_route {
+                    route
+                } else {
+                    parts.path()
+                };
+                let (_, captures) = migration.from.capture_route_into_map(matched).ok()?;
+                let mut captures: HashMap<String, String> = captures
+                    .into_iter()
+                    .map(|(name, value)| (name.to_string(), value))
+                    .collect();
+                if let Some(remap) = &migration.remap {
+                    remap(&mut captures);
+                }
+                let migrated = migration.to.fill(|name| captures.get(name).cloned())?;
+                if migration.matches_whole_route {
+                    return Some((index, migrated));
+                }
+                let mut migrated = RouteParts::new(&migrated);
+                migrated.query = migrated.query.or_else(|| parts.query.clone());
+                migrated.fragment = migrated.fragment.or_else(|| parts.fragment.clone());
+                Some((index, migrated.to_string()))
+            })
+    }
+}
+
+/// A unit of a matcher that routes are compared against.
+///
+/// Literals sort before captures, so more specific matchers sort first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Specificity {
+    Literal(char),
+    End,
+    Capture,
+}
+
+/// Splits the tokens of a matcher into units that sort from the most specific matcher to the
+/// least.
+fn specificity(tokens: &[MatcherToken]) -> Vec<Specificity> {
+    tokens
+        .iter()
+        .flat_map(|token| match token {
+            MatcherToken::Exact(literal) => literal.chars().map(Specificity::Literal).collect(),
+            MatcherToken::Capture(_) => vec![Specificity::Capture],
+            MatcherToken::End => vec![Specificity::End],
+        })
+        .collect()
+}
+
+impl Debug for RouteMigrations {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RouteMigrations")
+            .field("migrations", &self.migrations.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrations_are_chained() {
+        let migrations = RouteMigrations::new()
+            .migrate("/v1/items/{id}", "/v2/items/{id}")
+            .migrate("/v2/items/{id}", "/items/{id}#details");
+        assert_eq!(
+            migrations.migrate_route("/v1/items/3?x=1#top"),
+            Some("/items/3?x=1#details".to_string())
+        );
+        assert_eq!(
+            migrations.migrate_route("/v2/items/3"),
+            Some("/items/3#details".to_string())
+        );
+        assert_eq!(migrations.migrate_route("/items/3"), None);
+    }
+
+    #[test]
+    fn cycles_end() {
+        let migrations = RouteMigrations::new()
+            .migrate("/a", "/b")
+            .migrate("/b", "/c")
+            .migrate("/c", "/b");
+        assert_eq!(migrations.migrate_route("/a"), Some("/b".to_string()));
+        assert_eq!(migrations.migrate_route("/b"), None);
+    }
+
+    #[test]
+    fn captures_can_be_remapped() {
+        let migrations = RouteMigrations::new().migrate_with(
+            "/search?q={q}",
+            "/find?query={query}&v={v}",
+            |captures: &mut HashMap<String, String>| {
+                let q = captures.remove("q").unwrap_or_default();
+                captures.insert("query".to_string(), q);
+                captures.insert("v".to_string(), "2".to_string());
+            },
+        );
+        assert_eq!(
+            migrations.migrate_route("/search?q=rust"),
+            Some("/find?query=rust&v=2".to_string())
+        );
+        assert_eq!(migrations.migrate_route("/search"), None);
+    }
+
+    #[test]
+    fn redirects_try_literals_first() {
+        let mut redirects = HashMap::new();
+        redirects.insert("/old/{id}".to_string(), "/new/{id}".to_string());
+        redirects.insert("/old/special".to_string(), "/special".to_string());
+        // `~` sorts after `{`, so it would be tried after the capture if the matcher strings
+        // were sorted as they are.
+        redirects.insert("/old/~team".to_string(), "/team".to_string());
+        let migrations = RouteMigrations::from_redirects(&redirects).unwrap();
+        assert_eq!(
+            migrations.migrate_route("/old/special"),
+            Some("/special".to_string())
+        );
+        assert_eq!(
+            migrations.migrate_route("/old/~team"),
+            Some("/team".to_string())
+        );
+        assert_eq!(
+            migrations.migrate_route("/old/7"),
+            Some("/new/7".to_string())
+        );
+
+        redirects.insert("/broken/{".to_string(), "/".to_string());
+        let error = RouteMigrations::from_redirects(&redirects).unwrap_err();
+        assert_eq!(error.input, "/broken/{");
+    }
+}
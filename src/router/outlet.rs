@@ -0,0 +1,225 @@
+//! Renders the nested route of a layout, for layouts shared by several routes.
+use super::{route_view, RouteView};
+use crate::{
+    route::{Route, RouterState},
+    switch::Switch,
+};
+use std::{
+    fmt::{self, Debug, Formatter},
+    rc::Rc,
+};
+use yew::{
+    html, html::ChildrenWithProps, Component, ComponentLink, Html, Properties, Renderable,
+    ShouldRender,
+};
+
+/// Renders the route nested inside of the route of a layout, so layouts with chrome like sidebars
+/// and tabs can be shared by several routes without passing render functions down to them.
+///
+/// A variant of `PSW`, the `Switch` that the layout is rendered for, delegates the rest of the
+/// route to `SW` by capturing it with a trailing `{*}`, like `Settings(SettingsRoute)` for
+/// `/settings{*}`.
+/// The `Outlet` switches that nested route into `SW`, and renders it with its `render` function,
+/// or, if it doesn't have one, with its [`RouteView`](struct.RouteView.html) children.
+///
+/// The layout gives it the route that it was rendered for through its `route` prop, like the
+/// route that a Router's render function created with
+/// [`Router::render_with_matches`](struct.Router.html#method.render_with_matches) receives.
+/// `Outlet`s can be nested inside of the routes rendered by other `Outlet`s, by giving them the
+/// nested route that the render function created with `Outlet::render_with_route` receives.
+///
+/// # Example
+/// ```
+/// use yew::prelude::*;
+/// use yew_router::{route::Route, router::Outlet, Switch};
+///
+/// #[derive(Switch)]
+/// enum AppRoute {
+///     #[to = "/settings{*}"]
+///     Settings(SettingsRoute),
+///     #[to = "/"]
+///     Home,
+/// }
+///
+/// #[derive(Switch)]
+/// enum SettingsRoute {
+///     #[to = "/profile"]
+///     Profile,
+///     #[to = "/security"]
+///     Security,
+/// }
+///
+/// pub struct SettingsLayout {
+///     props: SettingsProps,
+/// }
+///
+/// #[derive(Properties)]
+/// pub struct SettingsProps {
+///     #[props(required)]
+///     pub route: Route<()>,
+/// }
+///
+/// impl Component for SettingsLayout {
+///     type Message = ();
+///     type Properties = SettingsProps;
+///
+///     fn create(props: Self::Properties, _link: ComponentLink<Self>) -> Self {
+///         SettingsLayout { props }
+///     }
+///
+///     fn update(&mut self, _msg: Self::Message) -> ShouldRender {
+///         false
+///     }
+///
+///     fn change(&mut self, props: Self::Properties) -> ShouldRender {
+///         let changed = self.props.route != props.route;
+///         self.props = props;
+///         changed
+///     }
+/// }
+///
+/// impl Renderable<SettingsLayout> for SettingsLayout {
+///     fn view(&self) -> Html<Self> {
+///         html! {
+///             <div>
+///                 <nav>{"Settings"}</nav>
+///                 <Outlet<(), AppRoute, SettingsRoute>
+///                     route = self.props.route.clone()
+///                     render = Outlet::render(|route: SettingsRoute| match route {
+///                         SettingsRoute::Profile => html! {"Profile"},
+///                         SettingsRoute::Security => html! {"Security"},
+///                     })
+///                 />
+///             </div>
+///         }
+///     }
+/// }
+/// ```
+pub struct Outlet<T: RouterState, PSW: Switch + 'static, SW: Switch + 'static> {
+    props: OutletProps<T, PSW, SW>,
+}
+
+/// Properties for `Outlet`.
+#[derive(Properties)]
+pub struct OutletProps<T: RouterState, PSW: Switch + 'static, SW: Switch + 'static> {
+    /// The route that the layout was rendered for, which `PSW` switches on.
+    #[props(required)]
+    pub route: Route<T>,
+    /// Render function that renders the page of the nested route.
+    ///
+    /// If it isn't provided, the `RouteView` children of the Outlet are rendered instead.
+    pub render: Option<OutletRender<T, PSW, SW>>,
+    /// The pages of the nested routes, which are rendered if no `render` function is provided.
+    pub children: ChildrenWithProps<RouteView<SW>, Outlet<T, PSW, SW>>,
+}
+
+/// Clonable function that renders the nested route of an `Outlet`.
+#[allow(clippy::type_complexity)]
+pub struct OutletRender<T: RouterState, PSW: Switch + 'static, SW: Switch + 'static>(
+    Rc<dyn Fn(SW, &Route<T>) -> Html<Outlet<T, PSW, SW>>>,
+);
+
+impl<T, PSW, SW> Outlet<T, PSW, SW>
+where
+    T: RouterState,
+    PSW: Switch + 'static,
+    SW: Switch + 'static,
+{
+    /// Wrap a render closure so that it can be used by the Outlet.
+    pub fn render<F: Fn(SW) -> Html<Outlet<T, PSW, SW>> + 'static>(
+        f: F,
+    ) -> OutletRender<T, PSW, SW> {
+        OutletRender(Rc::new(move |switch: SW, _route: &Route<T>| f(switch)))
+    }
+
+    /// Wrap a render closure that also receives the nested route, so it can be given to the
+    /// `Outlet`s of the layouts that it renders.
+    pub fn render_with_route<F: Fn(SW, &Route<T>) -> Html<Outlet<T, PSW, SW>> + 'static>(
+        f: F,
+    ) -> OutletRender<T, PSW, SW> {
+        OutletRender(Rc::new(f))
+    }
+
+    /// Gets the route nested inside of the route of the layout.
+    fn nested_route(&self) -> Option<Route<T>> {
+        let route = self.props.route.clone();
+        let state = route.state.clone();
+        PSW::switch(route)
+            .and_then(Switch::nested_route)
+            .map(|nested| Route {
+                route: nested,
+                state,
+            })
+    }
+}
+
+impl<T, PSW, SW> Component for Outlet<T, PSW, SW>
+where
+    T: RouterState,
+    PSW: Switch + 'static,
+    SW: Switch + 'static,
+{
+    type Message = ();
+    type Properties = OutletProps<T, PSW, SW>;
+
+    fn create(props: Self::Properties, _link: ComponentLink<Self>) -> Self {
+        Outlet { props }
+    }
+
+    fn update(&mut self, _msg: Self::Message) -> ShouldRender {
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props = props;
+        true
+    }
+}
+
+impl<T, PSW, SW> Renderable<Outlet<T, PSW, SW>> for Outlet<T, PSW, SW>
+where
+    T: RouterState,
+    PSW: Switch + 'static,
+    SW: Switch + 'static,
+{
+    fn view(&self) -> Html<Self> {
+        let nested_route = match self.nested_route() {
+            Some(nested_route) => nested_route,
+            None => {
+                log::warn!(
+                    "The Outlet has no nested route to render in {}",
+                    self.props.route.route
+                );
+                return html! {};
+            }
+        };
+
+        let switch = match SW::switch(nested_route.clone()) {
+            Some(switch) => switch,
+            None => return html! {format!{"No route for {}", nested_route.route}},
+        };
+        match &self.props.render {
+            Some(render) => (render.0)(switch, &nested_route),
+            None => route_view::render_views(&self.props.children, switch)
+                .unwrap_or_else(|| html! {format!{"No RouteView renders {}", nested_route.route}}),
+        }
+    }
+}
+
+impl<T: RouterState, PSW: Switch, SW: Switch> Debug for Outlet<T, PSW, SW> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("Outlet").finish()
+    }
+}
+
+impl<T: RouterState, PSW: Switch, SW: Switch> Debug for OutletProps<T, PSW, SW> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("OutletProps").finish()
+    }
+}
+
+impl<T: RouterState, PSW: Switch, SW: Switch> Debug for OutletRender<T, PSW, SW> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("OutletRender").finish()
+    }
+}
@@ -0,0 +1,184 @@
+//! Tracks the phases that the navigations of a `Router` go through, for UI like progress bars.
+
+use yew::Callback;
+
+/// The phase that the latest navigation of a `Router` is in.
+///
+/// A navigation starts by matching the new route, which includes migrating it and asking the
+/// guard about it, then loads what its page needs, like the chunks of its code, or the decision of
+/// an asynchronous guard, and ends once its page is rendered.
+///
+/// Each Router reports the phases of its own navigations to the callback of its `on_phase` prop.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum NavigationPhase {
+    /// No navigation is in progress.
+    #[default]
+    Idle,
+    /// The route is being matched.
+    Matching,
+    /// The page is waiting for something before it can be shown, like its chunks, its guard, or
+    /// its readiness, while the Router renders its placeholder, its pending view, or the previous
+    /// page.
+    Loading,
+    /// The page is being rendered.
+    Rendering,
+    /// The page failed to render, or its chunk failed to load, with this error.
+    Failed(String),
+}
+
+impl NavigationPhase {
+    /// Determines if a navigation is in progress, like while a progress bar should be shown.
+    pub fn is_busy(&self) -> bool {
+        match self {
+            NavigationPhase::Matching | NavigationPhase::Loading | NavigationPhase::Rendering => {
+                true
+            }
+            NavigationPhase::Idle | NavigationPhase::Failed(_) => false,
+        }
+    }
+
+    /// Gets the phase that a navigation is in once its page was rendered, if the render ended it.
+    ///
+    /// Navigations that are loading stay in that phase until the page is rendered again.
+    pub(crate) fn after_render(&self, error: Option<String>) -> Option<NavigationPhase> {
+        if *self != NavigationPhase::Rendering {
+            return None;
+        }
+        Some(match error {
+            Some(error) => NavigationPhase::Failed(error),
+            None => NavigationPhase::Idle,
+        })
+    }
+}
+
+/// The phase of the navigations of a `Router`, which tells the callback of its `on_phase` prop
+/// whenever it changes.
+#[derive(Debug, Default)]
+pub(crate) struct RouterPhase {
+    phase: NavigationPhase,
+    /// The phase that the Router was in before it started matching its latest route.
+    before_matching: NavigationPhase,
+}
+
+impl RouterPhase {
+    /// Gets the phase of the latest navigation.
+    pub(crate) fn get(&self) -> &NavigationPhase {
+        &self.phase
+    }
+
+    /// Moves the navigation to the phase, and tells `on_phase` if it changed.
+    pub(crate) fn set(
+        &mut self,
+        phase: NavigationPhase,
+        on_phase: Option<&Callback<NavigationPhase>>,
+    ) {
+        if self.phase == phase {
+            return;
+        }
+        self.phase = phase.clone();
+        if let Some(on_phase) = on_phase {
+            on_phase.emit(phase)
+        }
+    }
+
+    /// Moves the navigation to its matching phase, remembering the phase that it goes back to if
+    /// the route isn't rendered.
+    pub(crate) fn start_matching(&mut self, on_phase: Option<&Callback<NavigationPhase>>) {
+        if self.phase != NavigationPhase::Matching {
+            self.before_matching = self.phase.clone();
+        }
+        self.set(NavigationPhase::Matching, on_phase);
+    }
+
+    /// Moves a navigation whose route isn't rendered, like one that its guard redirects, back to
+    /// the phase it was in before its route was matched.
+    ///
+    /// The route that replaces it starts a navigation of its own once it is received, which it
+    /// isn't if it is the route that the Router already has.
+    pub(crate) fn cancel_matching(&mut self, on_phase: Option<&Callback<NavigationPhase>>) {
+        if self.phase == NavigationPhase::Matching {
+            let phase = self.before_matching.clone();
+            self.set(phase, on_phase);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{cell::RefCell, rc::Rc};
+
+    #[test]
+    fn changed_phases_are_reported() {
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let on_phase = {
+            let received = received.clone();
+            Callback::from(move |phase| received.borrow_mut().push(phase))
+        };
+        let mut phase = RouterPhase::default();
+        phase.set(NavigationPhase::Matching, Some(&on_phase));
+        phase.set(NavigationPhase::Rendering, Some(&on_phase));
+        phase.set(NavigationPhase::Rendering, Some(&on_phase));
+        assert_eq!(phase.get(), &NavigationPhase::Rendering);
+        phase.set(NavigationPhase::Idle, None);
+        assert_eq!(
+            *received.borrow(),
+            vec![NavigationPhase::Matching, NavigationPhase::Rendering]
+        );
+        assert!(!phase.get().is_busy());
+    }
+
+    #[test]
+    fn cancelled_navigations_go_back_to_their_previous_phase() {
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let on_phase = {
+            let received = received.clone();
+            Callback::from(move |phase| received.borrow_mut().push(phase))
+        };
+        let mut phase = RouterPhase::default();
+        phase.start_matching(Some(&on_phase));
+        phase.cancel_matching(Some(&on_phase));
+        assert_eq!(phase.get(), &NavigationPhase::Idle);
+
+        phase.set(NavigationPhase::Loading, None);
+        phase.start_matching(Some(&on_phase));
+        phase.cancel_matching(Some(&on_phase));
+        assert_eq!(phase.get(), &NavigationPhase::Loading);
+        assert_eq!(
+            *received.borrow(),
+            vec![
+                NavigationPhase::Matching,
+                NavigationPhase::Idle,
+                NavigationPhase::Matching,
+                NavigationPhase::Loading
+            ]
+        );
+    }
+
+    #[test]
+    fn rendered_navigations_are_not_cancelled() {
+        let mut phase = RouterPhase::default();
+        phase.start_matching(None);
+        phase.set(NavigationPhase::Rendering, None);
+        phase.cancel_matching(None);
+        assert_eq!(phase.get(), &NavigationPhase::Rendering);
+    }
+
+    #[test]
+    fn rendering_ends_the_navigation() {
+        assert_eq!(
+            NavigationPhase::Rendering.after_render(None),
+            Some(NavigationPhase::Idle)
+        );
+        assert_eq!(
+            NavigationPhase::Rendering.after_render(Some("No such user".to_string())),
+            Some(NavigationPhase::Failed("No such user".to_string()))
+        );
+    }
+
+    #[test]
+    fn loading_navigations_are_not_ended_by_rendering() {
+        assert_eq!(NavigationPhase::Loading.after_render(None), None);
+        assert_eq!(NavigationPhase::Idle.after_render(None), None);
+    }
+}
@@ -0,0 +1,78 @@
+//! Lets the page of a route tell the `Router` when it is ready to be shown.
+
+use std::fmt::{self, Debug, Formatter};
+use yew::Callback;
+
+/// A handle that the page of a route uses to tell the `Router` that rendered it that it's ready to
+/// be shown, for example once its data has loaded.
+///
+/// If the `Router`'s `wait_for_ready` prop is set, routes with `#[meta(wait_for_ready)]` are
+/// rendered hidden while the Router keeps showing the previous page, until the new page calls
+/// `ready`.
+/// This avoids a flash of empty content between pages.
+///
+/// The page gets the handle from the [`RouteContext`](struct.RouteContext.html) that the Router
+/// gives to render functions created with
+/// [`Router::render_with_context`](struct.Router.html#method.render_with_context), so each page
+/// tells the Router that rendered it.
+/// Routes that don't declare `wait_for_ready` are shown right away, so calling `ready` for them
+/// does nothing.
+///
+/// # Example
+/// ```
+/// use yew_router::router::RouteContext;
+///
+/// # fn dont_execute(context: RouteContext) {
+/// // Once the page's data has loaded.
+/// if let Some(readiness) = context.readiness() {
+///     readiness.ready();
+/// }
+/// # }
+/// ```
+#[derive(Clone, PartialEq)]
+pub struct RouteReadiness {
+    /// Identifies the navigation that the page was rendered for, so pages of routes that were
+    /// navigated away from can't reveal a newer page.
+    navigation: usize,
+    ready: Callback<usize>,
+}
+
+impl RouteReadiness {
+    /// Creates a handle for the page rendered for a navigation.
+    pub(crate) fn new(navigation: usize, ready: Callback<usize>) -> Self {
+        RouteReadiness { navigation, ready }
+    }
+
+    /// Tells the `Router` that the page is ready to be shown.
+    pub fn ready(&self) {
+        self.ready.emit(self.navigation)
+    }
+}
+
+impl Debug for RouteReadiness {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RouteReadiness")
+            .field("navigation", &self.navigation)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{cell::RefCell, rc::Rc};
+
+    #[test]
+    fn ready_tells_the_router_the_navigation_it_was_rendered_for() {
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let ready = {
+            let received = received.clone();
+            Callback::from(move |navigation| received.borrow_mut().push(navigation))
+        };
+        let first = RouteReadiness::new(1, ready.clone());
+        let second = RouteReadiness::new(2, ready);
+        second.ready();
+        first.ready();
+        assert_eq!(*received.borrow(), vec![2, 1]);
+    }
+}
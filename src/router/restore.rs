@@ -0,0 +1,185 @@
+//! Persists the last visited route, so apps that are opened at `/` can continue where they left off.
+
+use std::{
+    fmt::{self, Debug, Formatter},
+    rc::Rc,
+};
+use stdweb::web::{window, Storage};
+
+/// The key that the last route is stored under, unless another one is given.
+const DEFAULT_KEY: &str = "yew_router_last_route";
+
+/// Where a `RestoreLastRoute` keeps the last visited route.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RouteStorage {
+    /// `sessionStorage`, which is kept while the tab is open, including across reloads.
+    Session,
+    /// `localStorage`, which is kept across browser restarts.
+    Local,
+}
+
+/// Makes a `Router` return to the last visited route when the app is loaded at `/`.
+///
+/// When given to a `Router` through its `restore_last_route` prop, every route the Router receives
+/// is stored in the chosen storage.
+/// If the page is later loaded at `/`, the Router replaces it with the stored route before
+/// rendering anything.
+///
+/// Routes that shouldn't be persisted, like ones that contain tokens, can be excluded with
+/// `excluding`, or by giving their variants `#[meta(no_restore)]`.
+/// Excluded routes are neither stored nor restored.
+///
+/// # Example
+/// ```
+/// use yew_router::router::{RestoreLastRoute, RouteStorage};
+///
+/// let restore = RestoreLastRoute::new(RouteStorage::Local)
+///     .excluding(|route: &str| route.starts_with("/reset-password"));
+/// ```
+#[derive(Clone)]
+pub struct RestoreLastRoute {
+    storage: RouteStorage,
+    key: String,
+    /// Routes that any of these return `true` for aren't stored or restored.
+    exclusions: Vec<Rc<dyn Fn(&str) -> bool>>,
+}
+
+impl RestoreLastRoute {
+    /// Creates a restorer that keeps the last route in the given storage.
+    pub fn new(storage: RouteStorage) -> Self {
+        RestoreLastRoute {
+            storage,
+            key: DEFAULT_KEY.to_string(),
+            exclusions: Vec::new(),
+        }
+    }
+
+    /// Sets the key that the last route is stored under, so apps sharing an origin, or Routers
+    /// with different namespaces, don't restore each other's routes.
+    pub fn with_key(self, key: &str) -> Self {
+        RestoreLastRoute {
+            key: key.to_string(),
+            ..self
+        }
+    }
+
+    /// Excludes the routes that the function returns `true` for.
+    ///
+    /// This can be called several times, in which case routes that any of the functions return
+    /// `true` for are excluded.
+    pub fn excluding<F: Fn(&str) -> bool + 'static>(mut self, exclude: F) -> Self {
+        self.exclusions.push(Rc::new(exclude));
+        self
+    }
+
+    /// Determines if one of the exclusions applies to the route.
+    pub(crate) fn is_excluded(&self, route: &str) -> bool {
+        self.exclusions.iter().any(|exclude| exclude(route))
+    }
+
+    /// Gets the stored route.
+    pub(crate) fn last_route(&self) -> Option<String> {
+        self.storage().get(&self.key)
+    }
+
+    /// Stores the route, replacing the one stored before.
+    pub(crate) fn save(&self, route: &str) {
+        if self.storage().insert(&self.key, route).is_err() {
+            log::warn!("The last route couldn't be stored under '{}'", self.key);
+        }
+    }
+
+    fn storage(&self) -> Storage {
+        match self.storage {
+            RouteStorage::Session => window().session_storage(),
+            RouteStorage::Local => window().local_storage(),
+        }
+    }
+}
+
+/// Picks the stored route that replaces the route that the page was loaded with.
+///
+/// Only pages loaded at `/` are restored, and stored routes that are `/` themselves, or that
+/// `is_restorable` rejects, are ignored.
+pub(crate) fn pick_last_route<L, R>(
+    loaded_route: &str,
+    last_route: L,
+    is_restorable: R,
+) -> Option<String>
+where
+    L: FnOnce() -> Option<String>,
+    R: Fn(&str) -> bool,
+{
+    if loaded_route != "/" {
+        return None;
+    }
+    last_route().filter(|last_route| last_route != "/" && is_restorable(last_route))
+}
+
+impl Debug for RestoreLastRoute {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RestoreLastRoute")
+            .field("storage", &self.storage)
+            .field("key", &self.key)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn routes_excluded_by_any_exclusion_are_excluded() {
+        let restore = RestoreLastRoute::new(RouteStorage::Session)
+            .excluding(|route: &str| route.starts_with("/reset-password"))
+            .excluding(|route: &str| route.contains("token="));
+        assert!(restore.is_excluded("/reset-password/42"));
+        assert!(restore.is_excluded("/login?token=abc"));
+        assert!(!restore.is_excluded("/users/42"));
+    }
+
+    #[test]
+    fn nothing_is_excluded_without_exclusions() {
+        let restore = RestoreLastRoute::new(RouteStorage::Local);
+        assert!(!restore.is_excluded("/reset-password/42"));
+    }
+
+    #[test]
+    fn last_route_is_restored_when_loaded_at_root() {
+        let last_route = pick_last_route("/", || Some("/users/42".to_string()), |_| true);
+        assert_eq!(last_route, Some("/users/42".to_string()))
+    }
+
+    #[test]
+    fn last_route_is_not_restored_when_loaded_elsewhere() {
+        let last_route = pick_last_route(
+            "/settings",
+            || panic!("the storage shouldn't be read"),
+            |_| true,
+        );
+        assert_eq!(last_route, None)
+    }
+
+    #[test]
+    fn stored_root_is_not_restored() {
+        let last_route = pick_last_route("/", || Some("/".to_string()), |_| true);
+        assert_eq!(last_route, None)
+    }
+
+    #[test]
+    fn unrestorable_last_route_is_not_restored() {
+        let last_route = pick_last_route(
+            "/",
+            || Some("/reset-password/42".to_string()),
+            |route| !route.starts_with("/reset-password"),
+        );
+        assert_eq!(last_route, None)
+    }
+
+    #[test]
+    fn nothing_is_restored_without_a_stored_route() {
+        let last_route = pick_last_route("/", || None, |_| true);
+        assert_eq!(last_route, None)
+    }
+}
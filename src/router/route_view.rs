@@ -0,0 +1,374 @@
+//! Declares the page of a route as a child of the Router.
+use crate::switch::Switch;
+use std::{
+    fmt::{self, Debug, Formatter},
+    mem,
+    rc::Rc,
+};
+use yew::{
+    html,
+    html::{Children, ChildrenWithProps},
+    Component, ComponentLink, Html, Properties, Renderable, ShouldRender,
+};
+
+/// A child of a `Router`, or of an `Outlet`, that renders the page of the routes it is given.
+///
+/// The page is either a `component`, whose props are built from the switched route with
+/// [`FromSwitch`](trait.FromSwitch.html), so it receives the values of the fields that the route
+/// matched, or the children of the `RouteView`.
+///
+/// The `to` prop limits it to a single variant.
+/// Only the variant of `to` is compared, so it is meant for variants without fields, and can be
+/// left out if the props of the component are only built for the variants it renders.
+/// The first `RouteView` that renders the route is rendered; if none do, the Router treats the
+/// route like a route that doesn't match.
+///
+/// The children of a Router are only rendered if it is given
+/// [`Router::render_views`](struct.Router.html#method.render_views) as its `render` function.
+///
+/// # Example
+/// ```
+/// use yew::prelude::*;
+/// use yew_router::{
+///     router::{FromSwitch, RouteView, Router},
+///     Switch,
+/// };
+///
+/// # pub struct HomePage;
+/// # impl Component for HomePage {
+/// #     type Message = ();
+/// #     type Properties = ();
+/// #     fn create(_: (), _: ComponentLink<Self>) -> Self { HomePage }
+/// #     fn update(&mut self, _: ()) -> ShouldRender { false }
+/// # }
+/// # impl Renderable<HomePage> for HomePage {
+/// #     fn view(&self) -> Html<Self> { html! {"Home"} }
+/// # }
+/// # pub struct UserPage;
+/// # impl Component for UserPage {
+/// #     type Message = ();
+/// #     type Properties = UserProps;
+/// #     fn create(_: UserProps, _: ComponentLink<Self>) -> Self { UserPage }
+/// #     fn update(&mut self, _: ()) -> ShouldRender { false }
+/// # }
+/// # impl Renderable<UserPage> for UserPage {
+/// #     fn view(&self) -> Html<Self> { html! {"User"} }
+/// # }
+/// #[derive(Switch)]
+/// enum AppRoute {
+///     #[to = "/users/{id}"]
+///     User { id: u32 },
+///     #[to = "/about"]
+///     About,
+///     #[to = "/"]
+///     Home,
+/// }
+///
+/// #[derive(Properties, Clone)]
+/// pub struct UserProps {
+///     #[props(required)]
+///     pub id: u32,
+/// }
+///
+/// impl FromSwitch<AppRoute> for UserProps {
+///     fn from_switch(route: AppRoute) -> Result<Self, AppRoute> {
+///         match route {
+///             AppRoute::User { id } => Ok(UserProps { id }),
+///             other => Err(other),
+///         }
+///     }
+/// }
+///
+/// # pub struct Model;
+/// # impl Component for Model {
+/// #     type Message = ();
+/// #     type Properties = ();
+/// #     fn create(_: (), _: ComponentLink<Self>) -> Self { Model }
+/// #     fn update(&mut self, _: ()) -> ShouldRender { false }
+/// # }
+/// impl Renderable<Model> for Model {
+///     fn view(&self) -> Html<Self> {
+///         html! {
+///             <Router<(), AppRoute, ()> render = Router::render_views()>
+///                 <RouteView<AppRoute> component = RouteView::component::<UserPage>() />
+///                 <RouteView<AppRoute> to = AppRoute::About>
+///                     {"About"}
+///                 </RouteView<AppRoute>>
+///                 <RouteView<AppRoute>
+///                     to = AppRoute::Home
+///                     component = RouteView::component::<HomePage>()
+///                 />
+///             </Router<(), AppRoute, ()>>
+///         }
+///     }
+/// }
+/// ```
+pub struct RouteView<SW: Switch + 'static> {
+    props: RouteViewProps<SW>,
+}
+
+/// Properties for `RouteView`.
+#[derive(Properties)]
+pub struct RouteViewProps<SW: Switch + 'static> {
+    /// The variant that the page is rendered for.
+    ///
+    /// If it isn't provided, the page is rendered for every variant that the props of its
+    /// `component` can be built from.
+    pub to: Option<SW>,
+    /// The component that is rendered with props built from the switched route.
+    ///
+    /// If it isn't provided, the children are rendered instead.
+    pub component: Option<RouteComponent<SW>>,
+    /// The page of the route, which is rendered if no `component` is provided.
+    pub children: Children<RouteView<SW>>,
+    /// The component that the `Router` or `Outlet` built for the route it rendered this for.
+    pub page: Option<RoutedPage<SW>>,
+}
+
+/// Props of a component that a `RouteView` renders, which are built from the switched route.
+///
+/// Components whose props are `()` are rendered for every route, so they are usually limited to a
+/// variant with the `to` prop of the `RouteView`.
+pub trait FromSwitch<SW>: Sized {
+    /// Builds the props from the route, or returns the route if the component isn't rendered for
+    /// it.
+    fn from_switch(switch: SW) -> Result<Self, SW>;
+}
+
+impl<SW> FromSwitch<SW> for () {
+    fn from_switch(_switch: SW) -> Result<Self, SW> {
+        Ok(())
+    }
+}
+
+/// Clonable function that builds the component of a `RouteView` for a switched route.
+pub struct RouteComponent<SW: Switch + 'static>(Rc<dyn Fn(SW) -> Result<RoutedPage<SW>, SW>>);
+
+/// Clonable function that renders the component of a `RouteView`, with the props that were built
+/// for the route it is rendered for.
+pub struct RoutedPage<SW: Switch + 'static>(Rc<dyn Fn() -> Html<RouteView<SW>>>);
+
+impl<SW: Switch> RouteView<SW> {
+    /// Renders the component with props built from the switched route with `FromSwitch`.
+    pub fn component<COMP>() -> RouteComponent<SW>
+    where
+        COMP: Component + Renderable<COMP>,
+        COMP::Properties: FromSwitch<SW> + Clone,
+    {
+        RouteComponent(Rc::new(|switch: SW| {
+            let props = COMP::Properties::from_switch(switch)?;
+            Ok(RoutedPage(Rc::new(move || {
+                let props = props.clone();
+                html! { <COMP with props /> }
+            })))
+        }))
+    }
+}
+
+impl<SW: Switch> RouteViewProps<SW> {
+    /// Builds the page for the switched route, which is `None` if the children are rendered, or
+    /// returns the route if it isn't rendered for it.
+    fn route_to(&self, switch: SW) -> Result<Option<RoutedPage<SW>>, SW> {
+        if let Some(to) = &self.to {
+            if mem::discriminant(to) != mem::discriminant(&switch) {
+                return Err(switch);
+            }
+        }
+        match &self.component {
+            Some(component) => (component.0)(switch).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Renders the first of the `RouteView`s that renders the switched route.
+///
+/// Returns `None` if none of them do.
+pub(crate) fn render_views<SW, PARENT>(
+    views: &ChildrenWithProps<RouteView<SW>, PARENT>,
+    switch: SW,
+) -> Option<Html<PARENT>>
+where
+    SW: Switch + 'static,
+    PARENT: Component,
+{
+    let mut switch = switch;
+    for mut view in views.iter() {
+        match view.props.route_to(switch) {
+            Ok(page) => {
+                view.props.page = page;
+                return Some(Html::from(view));
+            }
+            Err(unrouted) => switch = unrouted,
+        }
+    }
+    None
+}
+
+impl<SW: Switch> Debug for RouteView<SW> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("RouteView").finish()
+    }
+}
+
+impl<SW: Switch> Debug for RouteViewProps<SW> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("RouteViewProps").finish()
+    }
+}
+
+impl<SW: Switch> Clone for RouteComponent<SW> {
+    fn clone(&self) -> Self {
+        RouteComponent(self.0.clone())
+    }
+}
+
+impl<SW: Switch> Debug for RouteComponent<SW> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("RouteComponent").finish()
+    }
+}
+
+impl<SW: Switch> Clone for RoutedPage<SW> {
+    fn clone(&self) -> Self {
+        RoutedPage(self.0.clone())
+    }
+}
+
+impl<SW: Switch> Debug for RoutedPage<SW> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("RoutedPage").finish()
+    }
+}
+
+impl<SW: Switch + 'static> Component for RouteView<SW> {
+    type Message = ();
+    type Properties = RouteViewProps<SW>;
+
+    fn create(props: Self::Properties, _link: ComponentLink<Self>) -> Self {
+        RouteView { props }
+    }
+
+    fn update(&mut self, _msg: Self::Message) -> ShouldRender {
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props = props;
+        true
+    }
+}
+
+impl<SW: Switch + 'static> Renderable<RouteView<SW>> for RouteView<SW> {
+    fn view(&self) -> Html<Self> {
+        match &self.props.page {
+            Some(page) => (page.0)(),
+            None => html! {
+                { for self.props.children.iter() }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::route::{Route, RouteState};
+
+    struct Page;
+
+    impl Component for Page {
+        type Message = ();
+        type Properties = PageProps;
+
+        fn create(_: Self::Properties, _link: ComponentLink<Self>) -> Self {
+            Page
+        }
+
+        fn update(&mut self, _msg: Self::Message) -> ShouldRender {
+            false
+        }
+    }
+
+    impl Renderable<Page> for Page {
+        fn view(&self) -> Html<Self> {
+            html! {}
+        }
+    }
+
+    #[derive(Properties, Clone)]
+    struct PageProps {
+        #[props(required)]
+        id: u32,
+    }
+
+    /// A switched route, whose routes don't matter, as the views are given switched values.
+    #[derive(Debug, PartialEq)]
+    enum UserRoute {
+        User(u32),
+        Other,
+    }
+
+    impl Switch for UserRoute {
+        fn from_route_part<T: RouteState>(part: Route<T>) -> (Option<Self>, Option<T>) {
+            (None, part.state)
+        }
+
+        fn build_route_section<T>(self, _route: &mut String) -> Option<T> {
+            None
+        }
+    }
+
+    impl FromSwitch<UserRoute> for PageProps {
+        fn from_switch(switch: UserRoute) -> Result<Self, UserRoute> {
+            match switch {
+                UserRoute::User(id) => Ok(PageProps { id }),
+                other => Err(other),
+            }
+        }
+    }
+
+    fn props(to: Option<UserRoute>, component: bool) -> RouteViewProps<UserRoute> {
+        RouteViewProps {
+            to,
+            component: if component {
+                Some(RouteView::component::<Page>())
+            } else {
+                None
+            },
+            children: Children::default(),
+            page: None,
+        }
+    }
+
+    #[test]
+    fn component_is_built_for_the_routes_its_props_are_built_from() {
+        let view = props(None, true);
+        assert!(view.route_to(UserRoute::User(42)).unwrap().is_some());
+        assert_eq!(
+            view.route_to(UserRoute::Other).err(),
+            Some(UserRoute::Other)
+        );
+    }
+
+    #[test]
+    fn only_the_variant_of_to_is_rendered() {
+        let view = props(Some(UserRoute::Other), false);
+        assert!(view.route_to(UserRoute::Other).unwrap().is_none());
+        assert_eq!(
+            view.route_to(UserRoute::User(42)).err(),
+            Some(UserRoute::User(42))
+        );
+
+        let view = props(Some(UserRoute::User(0)), true);
+        assert!(view.route_to(UserRoute::User(42)).unwrap().is_some());
+    }
+
+    #[test]
+    fn unit_props_are_built_for_every_route() {
+        assert_eq!(
+            <() as FromSwitch<UserRoute>>::from_switch(UserRoute::Other),
+            Ok(())
+        );
+    }
+}
@@ -0,0 +1,62 @@
+//! Renders the components that the variants of a `Switch` are routed to.
+use crate::switch::Switch;
+use yew::{Component, Html};
+
+/// Implemented by the `Switch` derive for enums whose variants declare the component they are
+/// routed to with `#[switch(component = "UserPage")]`.
+///
+/// The props of the component are built from the fields of the variant:
+/// named fields are given to the props of the same name, a single unnamed field is converted
+/// into the props with `From`, and unit variants render the component with its default props.
+///
+/// Routers can render these components without a `match`, by being given
+/// [`Router::render_components`](struct.Router.html#method.render_components).
+///
+/// # Example
+/// ```
+/// use yew::{html, Component, ComponentLink, Html, Properties, Renderable, ShouldRender};
+/// use yew_router::Switch;
+///
+/// pub struct UserPage {
+///     props: UserProps,
+/// }
+///
+/// #[derive(Properties)]
+/// pub struct UserProps {
+///     #[props(required)]
+///     pub id: u32,
+/// }
+///
+/// impl Component for UserPage {
+///     type Message = ();
+///     type Properties = UserProps;
+///
+///     fn create(props: Self::Properties, _link: ComponentLink<Self>) -> Self {
+///         UserPage { props }
+///     }
+///
+///     fn update(&mut self, _msg: Self::Message) -> ShouldRender {
+///         false
+///     }
+/// }
+///
+/// impl Renderable<UserPage> for UserPage {
+///     fn view(&self) -> Html<Self> {
+///         html! { <h1>{ format!("User {}", self.props.id) }</h1> }
+///     }
+/// }
+///
+/// #[derive(Switch)]
+/// enum AppRoute {
+///     #[to = "/users/{id}"]
+///     #[switch(component = "UserPage")]
+///     User { id: u32 },
+///     #[to = "/"]
+///     Home,
+/// }
+/// ```
+pub trait RoutedComponent: Switch {
+    /// Renders the component that the variant is routed to, or returns the variant if it
+    /// doesn't declare one.
+    fn render_component<CTX: Component>(self) -> Result<Html<CTX>, Self>;
+}
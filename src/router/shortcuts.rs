@@ -0,0 +1,295 @@
+//! Navigates to routes when the key chords declared with `#[meta(shortcut)]` are typed.
+use stdweb::{
+    unstable::TryInto,
+    web::{
+        document,
+        event::{IEvent, IKeyboardEvent, KeyDownEvent},
+        Date, EventListenerHandle, EventTarget, IEventTarget,
+    },
+    Value,
+};
+
+use crate::{
+    agent::{DefaultNamespace, RouteAgentDispatcher, RouteNamespace, RouteRequest},
+    route::{Route, RouterState},
+    switch::Switch,
+};
+use std::{
+    cell::RefCell,
+    fmt::{self, Debug, Formatter},
+    marker::PhantomData,
+    rc::Rc,
+};
+
+/// The key of the metadata that declares the chord of a route.
+const SHORTCUT_KEY: &str = "shortcut";
+
+/// How long, in milliseconds, the next key of a chord may take to be typed.
+const CHORD_TIMEOUT: f64 = 1000.0;
+
+/// Navigates to routes when their key chords are typed, through the `RouteAgent` of the
+/// namespace `N`.
+///
+/// Chords are declared on the variants of a `Switch` with `#[meta(shortcut = "g h")]`, as keys
+/// separated by spaces that are typed one after another.
+/// Keys are named like
+/// [`KeyboardEvent.key`](https://developer.mozilla.org/en-US/docs/Web/API/KeyboardEvent/key),
+/// so `?` and `ArrowLeft` are keys too.
+///
+/// Only variants that are turned into a single route, because their matcher doesn't capture
+/// anything, can have a shortcut.
+/// The keys of a chord have to be typed within a second of each other, and without holding
+/// `Ctrl`, `Alt` or `Meta`.
+/// Keys typed into inputs, text areas, selects and editable content are ignored.
+///
+/// Keys are listened to until this is dropped.
+///
+/// # Example
+/// ```
+/// use yew_router::{router::RouteShortcuts, Switch};
+///
+/// #[derive(Switch, Clone)]
+/// enum AppRoute {
+///     #[to = "/settings!"]
+///     #[meta(shortcut = "g s")]
+///     Settings,
+///     #[to = "/!"]
+///     #[meta(shortcut = "g h")]
+///     Home,
+/// }
+///
+/// # fn dont_execute() {
+/// let shortcuts = RouteShortcuts::<AppRoute, ()>::new();
+/// # }
+/// ```
+pub struct RouteShortcuts<SW, T, N = DefaultNamespace>
+where
+    SW: Switch,
+    T: RouterState,
+    N: RouteNamespace,
+{
+    listener: Option<EventListenerHandle>,
+    chords: Rc<RefCell<Chords>>,
+    /// Shared with the listener, which sends the navigations through it.
+    _dispatcher: Rc<RefCell<RouteAgentDispatcher<T, N>>>,
+    _switch: PhantomData<SW>,
+}
+
+impl<SW, T, N> RouteShortcuts<SW, T, N>
+where
+    SW: Switch,
+    T: RouterState,
+    N: RouteNamespace,
+{
+    /// Listens for the chords declared by the routes of `SW` anywhere in the document.
+    pub fn new() -> Self {
+        let shortcuts = SW::routes().into_iter().filter_map(|info| {
+            let chord = info.meta.get(SHORTCUT_KEY)?;
+            let route = match info.route {
+                Some(route) => route,
+                None => {
+                    log::warn!(
+                        "The shortcut '{}' of {} is ignored, as its route captures values",
+                        chord,
+                        info.name
+                    );
+                    return None;
+                }
+            };
+            let route = if route.is_empty() { "/" } else { route };
+            Some((chord, route))
+        });
+        let chords = Rc::new(RefCell::new(Chords::new(shortcuts)));
+        let dispatcher = Rc::new(RefCell::new(RouteAgentDispatcher::<T, N>::new()));
+
+        let listener = {
+            let chords = chords.clone();
+            let dispatcher = dispatcher.clone();
+            move |event: KeyDownEvent| {
+                if event.default_prevented()
+                    || event.ctrl_key()
+                    || event.alt_key()
+                    || event.meta_key()
+                    || event.repeat()
+                    || is_editable(event.target())
+                {
+                    return;
+                }
+                let route = chords.borrow_mut().press(&event.key(), Date::now());
+                if let Some(route) = route {
+                    event.prevent_default();
+                    dispatcher
+                        .borrow_mut()
+                        .send(RouteRequest::ChangeRoute(Route {
+                            route: route.to_string(),
+                            state: None,
+                        }));
+                }
+            }
+        };
+
+        RouteShortcuts {
+            listener: Some(document().add_event_listener(listener)),
+            chords,
+            _dispatcher: dispatcher,
+            _switch: PhantomData,
+        }
+    }
+}
+
+impl<SW, T, N> Default for RouteShortcuts<SW, T, N>
+where
+    SW: Switch,
+    T: RouterState,
+    N: RouteNamespace,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<SW, T, N> Drop for RouteShortcuts<SW, T, N>
+where
+    SW: Switch,
+    T: RouterState,
+    N: RouteNamespace,
+{
+    fn drop(&mut self) {
+        if let Some(listener) = self.listener.take() {
+            listener.remove();
+        }
+    }
+}
+
+impl<SW, T, N> Debug for RouteShortcuts<SW, T, N>
+where
+    SW: Switch,
+    T: RouterState,
+    N: RouteNamespace,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RouteShortcuts")
+            .field("chords", &self.chords.borrow())
+            .finish()
+    }
+}
+
+/// Determines if the target of a key press is an element that keys are typed into.
+fn is_editable(target: Option<EventTarget>) -> bool {
+    let target = match target {
+        Some(target) => target,
+        None => return false,
+    };
+    let editable: Value = js! {
+        var target = @{target};
+        var tag = target.tagName;
+        return tag === "INPUT" || tag === "TEXTAREA" || tag === "SELECT"
+            || !!target.isContentEditable;
+    };
+    editable.try_into().unwrap_or(false)
+}
+
+/// Matches the keys that are typed against the chords of the routes.
+#[derive(Debug)]
+struct Chords {
+    /// The keys of each chord, and the route that it navigates to.
+    shortcuts: Vec<(Vec<&'static str>, &'static str)>,
+    /// The keys of a chord typed so far.
+    typed: Vec<String>,
+    /// When the last key was typed.
+    last_press: f64,
+}
+
+impl Chords {
+    fn new<I: IntoIterator<Item = (&'static str, &'static str)>>(shortcuts: I) -> Self {
+        let shortcuts = shortcuts
+            .into_iter()
+            .map(|(chord, route)| (chord.split_whitespace().collect::<Vec<_>>(), route))
+            .filter(|(keys, _)| !keys.is_empty())
+            .collect();
+        Chords {
+            shortcuts,
+            typed: Vec::new(),
+            last_press: 0.0,
+        }
+    }
+
+    /// Records a key that was typed at `now`, getting the route of the chord that it completes.
+    ///
+    /// If the key doesn't continue any chord, the key starts a new one instead.
+    fn press(&mut self, key: &str, now: f64) -> Option<&'static str> {
+        if now - self.last_press > CHORD_TIMEOUT {
+            self.typed.clear();
+        }
+        self.last_press = now;
+
+        self.typed.push(key.to_string());
+        if !self.continues_chord() {
+            self.typed = vec![key.to_string()];
+            if !self.continues_chord() {
+                self.typed.clear();
+                return None;
+            }
+        }
+
+        let typed = &self.typed;
+        let route = self
+            .shortcuts
+            .iter()
+            .find(|(keys, _)| {
+                keys.len() == typed.len() && keys.iter().zip(typed).all(|(a, b)| a == b)
+            })
+            .map(|(_, route)| *route);
+        if route.is_some() {
+            self.typed.clear();
+        }
+        route
+    }
+
+    /// Determines if the keys typed so far begin one of the chords.
+    fn continues_chord(&self) -> bool {
+        self.shortcuts.iter().any(|(keys, _)| {
+            keys.len() >= self.typed.len() && keys.iter().zip(&self.typed).all(|(a, b)| a == b)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn chords() -> Chords {
+        Chords::new(vec![("g h", "/"), ("g s", "/settings"), ("?", "/help")])
+    }
+
+    #[test]
+    fn completes_chords() {
+        let mut chords = chords();
+        assert_eq!(chords.press("g", 0.0), None);
+        assert_eq!(chords.press("h", 100.0), Some("/"));
+        assert_eq!(chords.press("?", 200.0), Some("/help"));
+        assert_eq!(chords.press("g", 300.0), None);
+        assert_eq!(chords.press("s", 400.0), Some("/settings"));
+    }
+
+    #[test]
+    fn unrelated_keys_restart_chords() {
+        let mut chords = chords();
+        assert_eq!(chords.press("x", 0.0), None);
+        assert_eq!(chords.press("g", 100.0), None);
+        assert_eq!(chords.press("x", 200.0), None);
+        assert_eq!(chords.press("h", 300.0), None);
+        assert_eq!(chords.press("g", 400.0), None);
+        assert_eq!(chords.press("g", 500.0), None);
+        assert_eq!(chords.press("h", 600.0), Some("/"));
+    }
+
+    #[test]
+    fn chords_time_out() {
+        let mut chords = chords();
+        assert_eq!(chords.press("g", 0.0), None);
+        assert_eq!(chords.press("h", 1500.0), None);
+        assert_eq!(chords.press("g", 2000.0), None);
+        assert_eq!(chords.press("s", 2900.0), Some("/settings"));
+    }
+}
@@ -0,0 +1,394 @@
+//! Routes that are registered while the app is running, like the pages of feature modules that are
+//! loaded on demand.
+
+use crate::{
+    matcher::{Captures, RouteMatcher},
+    route::Route,
+};
+use std::{
+    cell::RefCell,
+    error::Error,
+    fmt::{self, Debug, Formatter},
+    rc::Rc,
+};
+use yew::{Callback, Component, Html};
+
+/// Renders a route of a `RouterTable`, with the captures of its matcher.
+type TableRender<CTX, T> = Rc<dyn Fn(&Route<T>, &Captures) -> Html<CTX>>;
+
+/// A route of a `RouterTable`.
+struct TableRoute<CTX: Component, T> {
+    key: String,
+    matcher: RouteMatcher,
+    priority: i32,
+    render: TableRender<CTX, T>,
+}
+
+struct Inner<CTX: Component, T> {
+    /// The routes, ordered from the highest priority to the lowest, and in the order they were
+    /// registered within a priority.
+    routes: Vec<Rc<TableRoute<CTX, T>>>,
+    /// Called whenever the routes change, so the `Router`s using the table render again.
+    listeners: Vec<Callback<()>>,
+}
+
+/// Routes that can be registered and unregistered while the app is running, which a `Router`
+/// given the table through its `table` prop renders alongside the routes of its `Switch`.
+///
+/// This lets apps made up of plugins or feature modules that are loaded on demand add their pages
+/// once they're loaded, without the `Switch` knowing about them.
+///
+/// Each route has a priority, which decides when it is tried:
+/// routes with a positive priority are tried before the `Switch`, so they can take over its routes,
+/// and the others are only tried for routes that the `Switch` doesn't match, before the `redirect`
+/// of the `Router`.
+/// Routes with a higher priority are tried first, and routes with the same priority are tried in
+/// the order they were registered.
+///
+/// The table is a cheaply clonable handle, so the clone given to the `Router` sees the routes
+/// registered through any other clone, and the `Router` renders again whenever they change.
+///
+/// # Example
+/// ```
+/// use yew::html;
+/// use yew_router::{
+///     matcher::RouteMatcher,
+///     router::{Router, RouterTable},
+///     Switch,
+/// };
+///
+/// #[derive(Switch)]
+/// enum AppRoute {
+///     #[to = "/"]
+///     Home,
+/// }
+///
+/// let table: RouterTable<Router<(), AppRoute, ()>> = RouterTable::new();
+/// table
+///     .register(
+///         "reports",
+///         RouteMatcher::try_from("/reports/{id}").unwrap(),
+///         0,
+///         |_route, captures| html! { format!("Report {}", captures["id"]) },
+///     )
+///     .expect("no other route is registered as reports");
+/// assert!(table.contains("reports"));
+/// table.unregister("reports");
+/// ```
+pub struct RouterTable<CTX: Component, T = ()> {
+    inner: Rc<RefCell<Inner<CTX, T>>>,
+}
+
+impl<CTX: Component, T> RouterTable<CTX, T> {
+    /// Creates an empty table.
+    pub fn new() -> Self {
+        RouterTable {
+            inner: Rc::new(RefCell::new(Inner {
+                routes: Vec::new(),
+                listeners: Vec::new(),
+            })),
+        }
+    }
+
+    /// Registers a route under a key, which renders the routes that the matcher matches with the
+    /// render function.
+    ///
+    /// Fails if a route is already registered under the key, or if a route with the same
+    /// priority has the same matcher, as it would never be rendered.
+    pub fn register<F>(
+        &self,
+        key: &str,
+        matcher: RouteMatcher,
+        priority: i32,
+        render: F,
+    ) -> Result<(), RouteConflict>
+    where
+        F: Fn(&Route<T>, &Captures) -> Html<CTX> + 'static,
+    {
+        {
+            let mut inner = self.inner.borrow_mut();
+            if inner.routes.iter().any(|route| route.key == key) {
+                return Err(RouteConflict::DuplicateKey(key.to_string()));
+            }
+            if let Some(route) = inner
+                .routes
+                .iter()
+                .find(|route| route.priority == priority && route.matcher == matcher)
+            {
+                return Err(RouteConflict::SameMatcher {
+                    key: key.to_string(),
+                    registered: route.key.clone(),
+                });
+            }
+            let index = inner
+                .routes
+                .iter()
+                .position(|route| route.priority < priority)
+                .unwrap_or_else(|| inner.routes.len());
+            inner.routes.insert(
+                index,
+                Rc::new(TableRoute {
+                    key: key.to_string(),
+                    matcher,
+                    priority,
+                    render: Rc::new(render),
+                }),
+            );
+        }
+        self.notify();
+        Ok(())
+    }
+
+    /// Unregisters the route registered under the key, returning whether there was one.
+    pub fn unregister(&self, key: &str) -> bool {
+        let removed = {
+            let mut inner = self.inner.borrow_mut();
+            let count = inner.routes.len();
+            inner.routes.retain(|route| route.key != key);
+            inner.routes.len() != count
+        };
+        if removed {
+            self.notify();
+        }
+        removed
+    }
+
+    /// Determines if a route is registered under the key.
+    pub fn contains(&self, key: &str) -> bool {
+        self.inner
+            .borrow()
+            .routes
+            .iter()
+            .any(|route| route.key == key)
+    }
+
+    /// Gets the keys of the registered routes, in the order they are tried.
+    pub fn keys(&self) -> Vec<String> {
+        self.inner
+            .borrow()
+            .routes
+            .iter()
+            .map(|route| route.key.clone())
+            .collect()
+    }
+
+    /// Renders the route with the first registered route that matches it, out of the ones that
+    /// are tried before the `Switch`, or the ones tried after it.
+    pub(crate) fn render(&self, route: &Route<T>, before_switch: bool) -> Option<Html<CTX>> {
+        // The routes are copied out of the table, so render functions can register routes.
+        let routes: Vec<Rc<TableRoute<CTX, T>>> = self
+            .inner
+            .borrow()
+            .routes
+            .iter()
+            .filter(|table_route| (table_route.priority > 0) == before_switch)
+            .cloned()
+            .collect();
+        let (table_route, captures) = routes.iter().find_map(|table_route| {
+            table_route
+                .matcher
+                .capture_route_into_map(&route.route)
+                .ok()
+                .map(|(_, captures)| (table_route, captures))
+        })?;
+        Some((table_route.render)(route, &captures))
+    }
+
+    /// Calls the callback whenever the registered routes change.
+    pub(crate) fn subscribe(&self, listener: &Callback<()>) {
+        let mut inner = self.inner.borrow_mut();
+        if !inner.listeners.contains(listener) {
+            inner.listeners.push(listener.clone());
+        }
+    }
+
+    /// Stops calling the callback when the registered routes change.
+    pub(crate) fn unsubscribe(&self, listener: &Callback<()>) {
+        self.inner
+            .borrow_mut()
+            .listeners
+            .retain(|subscribed| subscribed != listener);
+    }
+
+    fn notify(&self) {
+        let listeners = self.inner.borrow().listeners.clone();
+        for listener in listeners {
+            listener.emit(());
+        }
+    }
+}
+
+impl<CTX: Component, T> Clone for RouterTable<CTX, T> {
+    fn clone(&self) -> Self {
+        RouterTable {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<CTX: Component, T> Default for RouterTable<CTX, T> {
+    fn default() -> Self {
+        RouterTable::new()
+    }
+}
+
+impl<CTX: Component, T> PartialEq for RouterTable<CTX, T> {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl<CTX: Component, T> Debug for RouterTable<CTX, T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("RouterTable")
+            .field("keys", &self.keys())
+            .finish()
+    }
+}
+
+/// The reason why a route couldn't be registered in a `RouterTable`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RouteConflict {
+    /// Another route is registered under the key.
+    DuplicateKey(String),
+    /// The route registered under `registered` has the same matcher and priority, so the route
+    /// under `key` would never be rendered.
+    SameMatcher {
+        /// The key of the route that couldn't be registered.
+        key: String,
+        /// The key of the route that is already registered.
+        registered: String,
+    },
+}
+
+impl fmt::Display for RouteConflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            RouteConflict::DuplicateKey(key) => {
+                write!(f, "a route is already registered as '{}'", key)
+            }
+            RouteConflict::SameMatcher { key, registered } => write!(
+                f,
+                "'{}' has the same matcher and priority as '{}', so it would never be rendered",
+                key, registered
+            ),
+        }
+    }
+}
+
+impl Error for RouteConflict {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use yew::{html, ComponentLink, ShouldRender};
+
+    struct Page;
+
+    impl Component for Page {
+        type Message = ();
+        type Properties = ();
+
+        fn create(_: Self::Properties, _link: ComponentLink<Self>) -> Self {
+            Page
+        }
+
+        fn update(&mut self, _msg: Self::Message) -> ShouldRender {
+            false
+        }
+    }
+
+    fn register(table: &RouterTable<Page>, key: &str, matcher: &str, priority: i32) {
+        table
+            .register(
+                key,
+                RouteMatcher::try_from(matcher).expect("should parse"),
+                priority,
+                |_, _| html! {},
+            )
+            .expect("should register")
+    }
+
+    #[test]
+    fn orders_routes_by_priority_then_registration() {
+        let table = RouterTable::new();
+        register(&table, "a", "/a", 0);
+        register(&table, "b", "/b", 5);
+        register(&table, "c", "/c", 0);
+        register(&table, "d", "/d", 5);
+        register(&table, "e", "/e", -1);
+        assert_eq!(table.keys(), vec!["b", "d", "a", "c", "e"]);
+
+        assert!(table.unregister("d"));
+        assert!(!table.unregister("d"));
+        assert_eq!(table.keys(), vec!["b", "a", "c", "e"]);
+    }
+
+    #[test]
+    fn rejects_conflicting_routes() {
+        let table = RouterTable::<Page>::new();
+        register(&table, "a", "/a", 0);
+        let register_again = |key: &str, priority| {
+            table.register(
+                key,
+                RouteMatcher::try_from("/a").unwrap(),
+                priority,
+                |_, _| html! {},
+            )
+        };
+        assert_eq!(
+            register_again("a", 1),
+            Err(RouteConflict::DuplicateKey("a".to_string()))
+        );
+        assert_eq!(
+            register_again("b", 0),
+            Err(RouteConflict::SameMatcher {
+                key: "b".to_string(),
+                registered: "a".to_string()
+            })
+        );
+        assert_eq!(register_again("b", 1), Ok(()));
+    }
+
+    /// Registers a route whose render function records the value captured under `capture`.
+    fn register_recording(
+        table: &RouterTable<Page>,
+        key: &str,
+        matcher: &str,
+        priority: i32,
+        capture: &'static str,
+    ) -> Rc<RefCell<Option<String>>> {
+        let captured = Rc::new(RefCell::new(None));
+        let recorded = captured.clone();
+        table
+            .register(
+                key,
+                RouteMatcher::try_from(matcher).expect("should parse"),
+                priority,
+                move |_: &Route<()>, captures: &Captures| {
+                    *recorded.borrow_mut() = captures.get(capture).cloned();
+                    html! {}
+                },
+            )
+            .expect("should register");
+        captured
+    }
+
+    #[test]
+    fn renders_the_first_match_on_its_side_of_the_switch() {
+        let table = RouterTable::new();
+        let rest = register_recording(&table, "fallback", "/{*:rest}", 0, "rest");
+        let id = register_recording(&table, "report", "/reports/{id}", 1, "id");
+        let route = Route::from("/reports/42");
+
+        assert!(table.render(&route, true).is_some());
+        assert_eq!(id.borrow_mut().take(), Some("42".to_string()));
+        assert_eq!(rest.borrow_mut().take(), None);
+
+        assert!(table.render(&route, false).is_some());
+        assert_eq!(rest.borrow_mut().take(), Some("reports/42".to_string()));
+
+        table.unregister("fallback");
+        assert!(table.render(&route, false).is_none());
+    }
+}
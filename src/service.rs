@@ -1,30 +1,88 @@
 //! Service that interfaces with the browser to handle routing.
 
-use stdweb::{
-    web::{event::PopStateEvent, window, EventListenerHandle, History, IEventTarget, Location},
-    Value,
-};
 use yew::callback::Callback;
 
-use crate::route::RouteState;
-use std::marker::PhantomData;
+use crate::{
+    matcher::Normalization,
+    route::{resolve_relative_route, Route, RouterState, RETURN_TO_PARAM},
+    strategy::{self, RoutingStrategy},
+};
+use std::{
+    cell::{Cell, RefCell},
+    marker::PhantomData,
+    rc::Rc,
+};
+
+mod codec;
+pub use codec::RouteCodec;
+
+mod deep_link;
+pub(crate) use codec::{decode_route, encode_route};
+pub use deep_link::DeepLink;
+
+mod history;
+pub use history::{
+    default_history, set_default_history, BrowserHistory, CurrentUrl, HistoryBackend,
+    HistoryListener, HistoryState,
+};
+
+mod state_budget;
+pub use state_budget::{OversizedState, StateBudget};
+
+mod state_version;
+pub use state_version::{set_default_state_version, StateVersion};
+
+// The events are chosen by strategies, which are available without this module.
+pub use crate::strategy::RouteEvents;
 
 /// A service that facilitates manipulation of the browser's URL bar and responding to browser events
 /// when users press 'forward' or 'back'.
 ///
 /// The `T` determines what route state can be stored in the route service.
+///
+/// Where in the url the route is stored is determined by a
+/// [`RoutingStrategy`](../strategy/trait.RoutingStrategy.html), which defaults to the one set by
+/// `strategy::set_default_strategy`.
+///
+/// The url is read from, and entries are added to, a [`HistoryBackend`](trait.HistoryBackend.html),
+/// which defaults to the one set by `set_default_history`.
 #[derive(Debug)]
-pub struct RouteService<T> {
-    history: History,
-    location: Location,
-    event_listener: Option<EventListenerHandle>,
+pub struct RouteService<T = ()> {
+    history: Rc<dyn HistoryBackend>,
+    /// Listens to the history while callbacks are registered.
+    listener: Option<HistoryListener>,
+    /// Shared with the event listeners, so callbacks can be added and removed without attaching
+    /// the listeners again.
+    callbacks: Rc<RefCell<Vec<(CallbackHandle, Callback<(String, T)>)>>>,
+    /// The id that the next registered callback gets.
+    next_callback_id: usize,
+    /// Overrides the events that the strategy listens to.
+    route_events: Option<RouteEvents>,
+    // The settings that routes are read with are shared with the event listener, so changing them
+    // also applies to the routes that the browser navigates to.
+    /// How routes read from the url are normalized.
+    normalization: Rc<Cell<Normalization>>,
+    /// Where in the url the route is stored.
+    strategy: Rc<RefCell<Rc<dyn RoutingStrategy>>>,
+    /// Whether the host is prepended to the routes read from the url.
+    include_host: Rc<Cell<bool>>,
+    /// Transforms the query and fragment of routes stored in the url, if set.
+    codec: Rc<RefCell<Option<Rc<dyn RouteCodec>>>>,
+    /// The route that `get_route` returns until the url is read.
+    ///
+    /// Shared with the event listener, so the url is read once the browser navigates.
+    initial_route: Rc<RefCell<Option<String>>>,
+    /// Limits the size of the state stored in history entries.
+    state_budget: Option<StateBudget>,
+    /// Shared with the event listener, so changes apply to states from browser navigation too.
+    state_version: Rc<RefCell<Option<StateVersion<T>>>>,
 
     phantom_data: PhantomData<T>,
 }
 
 impl<T> Default for RouteService<T>
 where
-    T: RouteState,
+    T: RouterState,
 {
     fn default() -> Self {
         RouteService::<T>::new()
@@ -34,65 +92,321 @@ where
 impl<T> RouteService<T> {
     /// Creates the route service.
     pub fn new() -> RouteService<T> {
-        let location = window()
-            .location()
-            .expect("browser does not support location API");
         RouteService {
-            history: window().history(),
-            location,
-            event_listener: None,
+            history: history::default_history(),
+            listener: None,
+            callbacks: Rc::new(RefCell::new(Vec::new())),
+            next_callback_id: 0,
+            route_events: None,
+            normalization: Rc::new(Cell::new(Normalization::default())),
+            strategy: Rc::new(RefCell::new(strategy::default_strategy())),
+            include_host: Rc::new(Cell::new(false)),
+            codec: Rc::new(RefCell::new(None)),
+            initial_route: Rc::new(RefCell::new(None)),
+            state_budget: None,
+            state_version: Rc::new(RefCell::new(None)),
             phantom_data: PhantomData,
         }
     }
 
+    /// Creates the route service with the route that the page was rendered for, like the one that
+    /// a server rendered it for, which `get_route` returns instead of reading the url.
+    ///
+    /// The url is only read once `read_url` is called, which should happen after the first render,
+    /// so hydrating the server-rendered markup doesn't change it, or once the route is changed by
+    /// this service or the browser.
+    pub fn with_initial_route(route: &str) -> RouteService<T> {
+        let route_service = RouteService::new();
+        *route_service.initial_route.borrow_mut() = Some(route.to_string());
+        route_service
+    }
+
+    /// Reads the route from the url from now on, instead of returning the route given to
+    /// `with_initial_route`.
+    pub fn read_url(&mut self) {
+        self.initial_route.borrow_mut().take();
+    }
+
+    /// Sets how routes are normalized when they are read from the browser.
+    ///
+    /// This doesn't change the url bar, only the routes that are provided by this service.
+    pub fn set_normalization(&mut self, normalization: Normalization) {
+        self.normalization.set(normalization);
+    }
+
+    /// Sets which browser events are listened to in order to notice route changes.
+    ///
+    /// By default, this is determined by the strategy.
+    /// This takes effect the next time `register_callback` is called.
+    pub fn set_route_events(&mut self, route_events: RouteEvents) {
+        self.route_events = Some(route_events);
+    }
+
+    /// Sets where in the url the route is stored.
+    pub fn set_strategy<S: RoutingStrategy + 'static>(&mut self, strategy: S) {
+        self.set_shared_strategy(Rc::new(strategy));
+    }
+
+    /// Sets where in the url the route is stored, using a strategy that may be shared.
+    pub(crate) fn set_shared_strategy(&mut self, strategy: Rc<dyn RoutingStrategy>) {
+        *self.strategy.borrow_mut() = strategy;
+    }
+
+    /// Sets whether the host, including the port, is prepended to routes, so matchers like
+    /// `{tenant}.example.com/dashboard` can match on subdomains.
+    ///
+    /// Routes that are set while this is enabled may start with a host, which is removed before
+    /// the url is changed, as the History API can't navigate to other hosts.
+    pub fn set_include_host(&mut self, include_host: bool) {
+        self.include_host.set(include_host);
+    }
+
+    /// Sets the codec that transforms the query and fragment of routes when they are stored in the
+    /// url, and back when they are read from it.
+    ///
+    /// Providing `None` stores routes as they are, which is the default.
+    pub fn set_codec(&mut self, codec: Option<Rc<dyn RouteCodec>>) {
+        *self.codec.borrow_mut() = codec;
+    }
+
+    /// Sets a limit on the size of the state stored in history entries, and what is done with
+    /// state that exceeds it.
+    ///
+    /// Browsers reject navigations whose state is too large, so this lets oversized state be
+    /// noticed, discarded, or spilled into `sessionStorage`.
+    /// Providing `None` stores all state as it is, which is the default.
+    pub fn set_state_budget(&mut self, state_budget: Option<StateBudget>) {
+        self.state_budget = state_budget;
+    }
+
+    /// Sets the version that the state is stored with in history entries, along with the hook
+    /// that upgrades the states of older versions.
+    ///
+    /// Providing `None` uses the version set by `set_default_state_version`, if there is one, and
+    /// stores the state without a version otherwise, which is the default.
+    pub fn set_state_version(&mut self, state_version: Option<StateVersion<T>>) {
+        *self.state_version.borrow_mut() = state_version;
+    }
+
     #[inline]
-    fn get_route_from_location(location: &Location) -> String {
-        let path = location.pathname().unwrap();
-        let query = location.search().unwrap();
-        let fragment = location.hash().unwrap();
-        crate::route::format_route_string(&path, &query, &fragment)
+    fn get_route_from_location(
+        url: &CurrentUrl,
+        strategy: &dyn RoutingStrategy,
+        codec: Option<&dyn RouteCodec>,
+        normalization: Normalization,
+        include_host: bool,
+    ) -> String {
+        let route = strategy.route_from_url(&url.path, &url.query, &url.fragment);
+        let route = decode_route(codec, &route);
+        let route = normalization.normalize(&route);
+        if include_host {
+            format!("{}{}", url.host, route)
+        } else {
+            route.into_owned()
+        }
     }
 
-    /// Gets the concatenated path, query, and fragment.
+    /// Gets the route from the current url.
+    ///
+    /// For the default `PathStrategy`, this is the concatenated path, query, and fragment.
+    /// The query and fragment are decoded by the codec set by `set_codec`.
+    /// The path is normalized according to the normalization set by `set_normalization`, and
+    /// prefixed with the host if `set_include_host` is enabled.
+    ///
+    /// For services created with `with_initial_route`, this is the initial route until the url is
+    /// read.
     pub fn get_route(&self) -> String {
-        Self::get_route_from_location(&self.location)
+        if let Some(initial_route) = &*self.initial_route.borrow() {
+            return initial_route.clone();
+        }
+        Self::get_route_from_location(
+            &self.history.current_url(),
+            &**self.strategy.borrow(),
+            self.codec.borrow().as_ref().map(|codec| &**codec),
+            self.normalization.get(),
+            self.include_host.get(),
+        )
+    }
+
+    /// Gets the url, relative to the current one, that should be navigated to for the route.
+    fn url_for(&self, route: &str) -> String {
+        let url = self.history.current_url();
+        self.strategy
+            .borrow()
+            .url_for_route(route, &url.path, &url.query, &url.fragment)
+    }
+
+    /// Gets the current time of the history, in milliseconds.
+    pub fn now(&self) -> f64 {
+        self.history.now()
+    }
+
+    /// Encodes a route with the codec set by `set_codec`.
+    fn encode(&self, route: &str) -> String {
+        encode_route(self.codec.borrow().as_ref().map(|codec| &**codec), route)
+    }
+
+    /// Removes the host from the start of a route, if hosts are included in routes.
+    fn without_host<'a>(&self, route: &'a str) -> &'a str {
+        if self.include_host.get() {
+            route
+                .find(|c| c == '/' || c == '?' || c == '#')
+                .map_or("", |start| &route[start..])
+        } else {
+            route
+        }
+    }
+
+    /// Removes the callback that the handle was returned for by `register_callback`.
+    ///
+    /// Once the last callback is removed, the service stops listening to browser events.
+    /// Returns `false` if the callback was already removed.
+    pub fn deregister_callback(&mut self, handle: CallbackHandle) -> bool {
+        let mut callbacks = self.callbacks.borrow_mut();
+        let count = callbacks.len();
+        callbacks.retain(|(registered, _)| *registered != handle);
+        let removed = callbacks.len() != count;
+        if callbacks.is_empty() {
+            drop(callbacks);
+            self.remove_history_listener();
+        }
+        removed
+    }
+
+    /// Removes every callback registered with `register_callback`, along with the history
+    /// listener that calls them.
+    ///
+    /// This also happens when the service is dropped, as the listeners would stay attached to the
+    /// window otherwise.
+    pub fn clear_callbacks(&mut self) {
+        self.callbacks.borrow_mut().clear();
+        self.remove_history_listener();
+    }
+
+    fn remove_history_listener(&mut self) {
+        self.listener = None;
     }
 
     /// Gets the path name of the current url.
     pub fn get_path(&self) -> String {
-        self.location.pathname().unwrap()
+        self.history.current_url().path
     }
 
     /// Gets the query string of the current url.
     pub fn get_query(&self) -> String {
-        self.location.search().unwrap()
+        self.history.current_url().query
     }
 
     /// Gets the fragment of the current url.
     pub fn get_fragment(&self) -> String {
-        self.location.hash().unwrap()
+        self.history.current_url().fragment
     }
 }
 
 impl<T> RouteService<T>
 where
-    T: RouteState,
+    T: RouterState,
 {
+    /// Sets the history that the url is read from, and entries are added to.
+    ///
+    /// The registered callbacks are called for the changes of this history from now on.
+    pub fn set_history(&mut self, history: Rc<dyn HistoryBackend>) {
+        self.history = history;
+        if !self.callbacks.borrow().is_empty() {
+            self.attach_history_listener();
+        }
+    }
+
     /// Registers a callback to the route service.
     /// Callbacks will be called when the History API experiences a change such as
     /// popping a state off of its stack when the forward or back buttons are pressed.
-    pub fn register_callback(&mut self, callback: Callback<(String, T)>) {
-        self.event_listener = Some(window().add_event_listener(move |event: PopStateEvent| {
-            let state_value: Value = event.state();
-            let state: T = T::try_from(state_value).unwrap_or_default();
+    ///
+    /// Depending on the [`RouteEvents`](enum.RouteEvents.html), changes to the fragment of the url,
+    /// like ones made by hand in the url bar, will be observed as well.
+    /// If both events fire for a single navigation, the callback will only be called once.
+    ///
+    /// Any number of callbacks can be registered, and each of them is called for every change, so
+    /// devtools and other services can observe route changes alongside the `RouteAgent`.
+    /// The returned handle removes the callback again with `deregister_callback`.
+    pub fn register_callback(&mut self, callback: Callback<(String, T)>) -> CallbackHandle {
+        let handle = CallbackHandle(self.next_callback_id);
+        self.next_callback_id += 1;
+        self.callbacks.borrow_mut().push((handle, callback));
+        // The listener is attached again, so changes made by `set_route_events` apply.
+        self.attach_history_listener();
+        handle
+    }
+
+    /// Attaches the history listener that calls the registered callbacks.
+    fn attach_history_listener(&mut self) {
+        let route_events = self
+            .route_events
+            .unwrap_or_else(|| self.strategy.borrow().route_events());
+        let normalization = self.normalization.clone();
+        let strategy = self.strategy.clone();
+        let include_host = self.include_host.clone();
+        let codec = self.codec.clone();
+        let state_version = self.state_version.clone();
+        let initial_route = self.initial_route.clone();
+        let callbacks = self.callbacks.clone();
+        let history = self.history.clone();
+        // The event name and route of the previous event, used to drop the second of a pair of
+        // events that were fired for the same navigation.
+        let last_event: RefCell<Option<(&'static str, String)>> = RefCell::new(None);
+        let emit = Rc::new(move |event_name: &'static str, state: HistoryState| {
+            initial_route.borrow_mut().take();
+            let state_version = state_version::resolve(&state_version);
+            let state: T = Self::state_from_entry(state, state_version.as_ref());
+            let route: String = Self::get_route_from_location(
+                &history.current_url(),
+                &**strategy.borrow(),
+                codec.borrow().as_ref().map(|codec| &**codec),
+                normalization.get(),
+                include_host.get(),
+            );
 
-            // Can't use the existing location, because this is a callback, and can't move it in
-            // here.
-            let location: Location = window().location().unwrap();
-            let route: String = Self::get_route_from_location(&location);
+            let mut last_event = last_event.borrow_mut();
+            let is_duplicate = match &*last_event {
+                Some((last_name, last_route)) => *last_name != event_name && *last_route == route,
+                None => false,
+            };
+            if is_duplicate {
+                *last_event = None;
+            } else {
+                *last_event = Some((event_name, route.clone()));
+                // Callbacks may register or remove callbacks while they are called.
+                let callbacks = callbacks.borrow().clone();
+                for (_, callback) in callbacks {
+                    callback.emit((route.clone(), state.clone()))
+                }
+            }
+        });
 
-            callback.emit((route.clone(), state))
-        }));
+        self.remove_history_listener();
+        self.listener = Some(self.history.listen(route_events, emit));
+    }
+
+    /// Converts the state into the one that is stored in a history entry.
+    fn entry_state(&self, state: T) -> HistoryState {
+        if self.history.keeps_native_states() {
+            return HistoryState::Native(Rc::new(state));
+        }
+        let state_version = state_version::resolve(&self.state_version);
+        HistoryState::Value(state_budget::history_value(
+            state,
+            self.state_budget,
+            state_version.as_ref(),
+        ))
+    }
+
+    /// Converts the state stored in a history entry back, which is the default state if it can't
+    /// be.
+    fn state_from_entry(state: HistoryState, state_version: Option<&StateVersion<T>>) -> T {
+        match state {
+            HistoryState::Empty => T::default(),
+            HistoryState::Value(value) => state_budget::state_from_history(value, state_version),
+            HistoryState::Native(state) => state.downcast_ref::<T>().cloned().unwrap_or_default(),
+        }
     }
 
     /// Sets the browser's url bar to contain the provided route,
@@ -100,12 +414,123 @@ where
     ///
     /// The route should be a relative path that starts with a `/`.
     pub fn set_route(&mut self, route: &str, state: T) {
-        self.history.push_state(state, "", Some(route));
+        let route = self.encode(self.without_host(route));
+        let url = self.url_for(&route);
+        self.history.push(&url, self.entry_state(state));
+        self.read_url();
+    }
+
+    /// Resolves a relative route, like `../sibling`, against the path of the current route.
+    ///
+    /// The current path is treated as a directory, so from `/users/5/edit`, `details` resolves to
+    /// `/users/5/edit/details`, and `../view` resolves to `/users/5/view`.
+    /// Routes that start with a `/` are returned unchanged.
+    pub fn resolve_relative(&self, relative: &str) -> String {
+        let current = self.get_route();
+        resolve_relative_route(self.without_host(&current), relative)
+    }
+
+    /// Sets the browser's url bar to contain a route relative to the current one,
+    /// and creates a history entry that can be navigated via the forward and back buttons.
+    ///
+    /// See `resolve_relative` for how the route is resolved.
+    pub fn push_relative(&mut self, relative: &str, state: T) {
+        let route = self.resolve_relative(relative);
+        self.set_route(&route, state);
     }
 
     /// Replaces the route with another one removing the most recent history event and
     /// creating another history event in its place.
     pub fn replace_route(&mut self, route: &str, state: T) {
-        let _ = self.history.replace_state(state, "", Some(route));
+        let route = self.encode(self.without_host(route));
+        let url = self.url_for(&route);
+        self.history.replace(&url, self.entry_state(state));
+        self.read_url();
+    }
+
+    /// Moves the current route to the url of another locale, and creates a history entry that can
+    /// be navigated via the forward and back buttons.
+    ///
+    /// This only works if the strategy localizes routes, like `LocalizedStrategy` does, and knows
+    /// the locale.
+    /// Returns whether the route was moved, which it isn't if the strategy doesn't know the locale.
+    pub fn switch_locale(&mut self, locale: &str, state: T) -> bool {
+        let url = self.strategy.borrow().url_in_locale(
+            locale,
+            &self.get_path(),
+            &self.get_query(),
+            &self.get_fragment(),
+        );
+        match url {
+            Some(url) => {
+                self.history.push(&url, self.entry_state(state));
+                self.read_url();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Saves the current route with the provided state to the deep link, and redirects the
+    /// browser to another url, like the authorization endpoint of an OAuth provider.
+    ///
+    /// The route can be returned to with `restore_deep_link` once the app is loaded again.
+    pub fn redirect_with_deep_link(&self, deep_link: &DeepLink, state: T, url: &str) {
+        self.history
+            .save_deep_link(deep_link, &self.get_route(), self.entry_state(state));
+        self.history.redirect(url);
+    }
+
+    /// Takes the route that the current one remembers as the one to return to, like the page that
+    /// a guard redirected to the login page from, with
+    /// [`Route::with_intended_route`](../route/struct.Route.html#method.with_intended_route).
+    ///
+    /// The route is removed from the url, without creating a history entry, so it is only
+    /// returned once, and the code that runs after logging in can navigate back to it.
+    /// Returns `None` if there is no route to return to, or it isn't a route of this app.
+    pub fn take_intended_route(&mut self) -> Option<String> {
+        let current = self.get_route();
+        let mut route = Route::<()>::from(self.without_host(&current));
+        let intended = route.intended_route()?;
+        route.remove_query_pair(RETURN_TO_PARAM);
+        let route = self.encode(&route.route);
+        let url = self.url_for(&route);
+        self.history.replace_url(&url);
+        self.read_url();
+        Some(intended)
+    }
+
+    /// Replaces the current route with the one saved to the deep link by
+    /// `redirect_with_deep_link`, without creating a history entry, and returns it.
+    ///
+    /// This should be called when the app starts, after it has read what it needs from the url it
+    /// was loaded at, like the authorization code that an OAuth provider redirected to it with.
+    /// The saved route is removed, so it is only restored once.
+    /// Returns `None` if no route was saved, or it was saved too long ago.
+    pub fn restore_deep_link(&mut self, deep_link: &DeepLink) -> Option<Route<T>> {
+        let (route, state) = self.history.take_deep_link(deep_link)?;
+        let state_version = state_version::resolve(&self.state_version);
+        let state = Self::state_from_entry(state, state_version.as_ref());
+        self.replace_route(&route, state.clone());
+        Some(Route {
+            route,
+            state: Some(state),
+        })
     }
 }
+
+impl<T> Drop for RouteService<T> {
+    fn drop(&mut self) {
+        self.clear_callbacks();
+    }
+}
+
+/// Gets the parts of the current url of the history set by `set_default_history`.
+pub(crate) fn current_url() -> CurrentUrl {
+    history::default_history().current_url()
+}
+
+/// Identifies a callback registered with `RouteService::register_callback`, so it can be removed
+/// again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CallbackHandle(usize);
@@ -0,0 +1,121 @@
+//! Transforms the query and fragment of routes on their way into and out of the url.
+
+use crate::route::RouteParts;
+use std::fmt::Debug;
+
+/// Transforms the query and fragment of routes when they are stored in the url, and back when
+/// they are read from it, like to sign or encrypt tokens that urls carry.
+///
+/// The codec of a `RouteService` is applied to every route that it navigates to, and every route
+/// that it reads from the url, so the rest of the app only sees decoded routes.
+///
+/// The path is provided so that signatures can cover it, but it should be left as it is.
+///
+/// # Example
+/// ```
+/// use yew_router::{route::RouteParts, service::RouteCodec};
+///
+/// /// Keeps the query in the fragment, so it isn't sent to the server.
+/// #[derive(Debug)]
+/// struct HiddenQuery;
+///
+/// impl RouteCodec for HiddenQuery {
+///     fn encode(&self, parts: RouteParts) -> RouteParts {
+///         RouteParts {
+///             query: None,
+///             fragment: parts.query,
+///             ..parts
+///         }
+///     }
+///
+///     fn decode(&self, parts: RouteParts) -> RouteParts {
+///         RouteParts {
+///             query: parts.fragment,
+///             fragment: None,
+///             ..parts
+///         }
+///     }
+/// }
+/// ```
+pub trait RouteCodec: Debug {
+    /// Encodes the query and fragment of a route before it is stored in the url.
+    fn encode(&self, parts: RouteParts) -> RouteParts;
+
+    /// Decodes the query and fragment of a route that was read from the url.
+    ///
+    /// Parts that can't be decoded, like ones whose signature doesn't match, should be removed,
+    /// as they may have been tampered with.
+    fn decode(&self, parts: RouteParts) -> RouteParts;
+}
+
+/// Encodes a route with the codec, if there is one.
+pub(crate) fn encode_route(codec: Option<&dyn RouteCodec>, route: &str) -> String {
+    match codec {
+        Some(codec) => codec.encode(RouteParts::new(route)).to_string(),
+        None => route.to_string(),
+    }
+}
+
+/// Decodes a route with the codec, if there is one.
+pub(crate) fn decode_route(codec: Option<&dyn RouteCodec>, route: &str) -> String {
+    match codec {
+        Some(codec) => codec.decode(RouteParts::new(route)).to_string(),
+        None => route.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Keeps the query in the fragment, so it isn't sent to the server.
+    #[derive(Debug)]
+    struct HiddenQuery;
+
+    impl RouteCodec for HiddenQuery {
+        fn encode(&self, parts: RouteParts) -> RouteParts {
+            RouteParts {
+                query: None,
+                fragment: parts.query,
+                ..parts
+            }
+        }
+
+        fn decode(&self, parts: RouteParts) -> RouteParts {
+            RouteParts {
+                query: parts.fragment,
+                fragment: None,
+                ..parts
+            }
+        }
+    }
+
+    #[test]
+    fn routes_are_unchanged_without_a_codec() {
+        let route = "/users?page=2#bio";
+        assert_eq!(encode_route(None, route), route);
+        assert_eq!(decode_route(None, route), route);
+    }
+
+    #[test]
+    fn encoded_routes_decode_to_themselves() {
+        let codec: &dyn RouteCodec = &HiddenQuery;
+        for route in &["/users?page=2", "/users", "/", "/search?q=a%26b&page=1"] {
+            let encoded = encode_route(Some(codec), route);
+            assert_eq!(decode_route(Some(codec), &encoded), *route);
+        }
+    }
+
+    #[test]
+    fn only_the_query_and_fragment_are_encoded() {
+        let codec: &dyn RouteCodec = &HiddenQuery;
+        assert_eq!(
+            encode_route(Some(codec), "/users/42?tab=posts"),
+            "/users/42#tab=posts"
+        );
+        assert_eq!(
+            decode_route(Some(codec), "/users/42#tab=posts"),
+            "/users/42?tab=posts"
+        );
+    }
+}
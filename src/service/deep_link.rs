@@ -0,0 +1,141 @@
+//! Keeps the route that an app was at while it redirects away, like to an OAuth provider, so it
+//! can return to it when it is loaded again.
+
+use stdweb::{unstable::TryInto, web::window, Value};
+
+use serde::{Deserialize, Serialize};
+
+/// The key that deep links are stored under, unless another one is given.
+const DEFAULT_KEY: &str = "yew_router_deep_link";
+
+/// How long, in milliseconds, a deep link is restored for by default.
+const DEFAULT_MAX_AGE: u64 = 10 * 60 * 1000;
+
+/// Where a `RouteService` keeps the route it was at, along with its state, while the app is
+/// redirected away, like to the login page of an OAuth provider.
+///
+/// The route is saved in `sessionStorage` by `RouteService::redirect_with_deep_link`, which
+/// survives the redirect as long as the provider sends the user back in the same tab.
+/// Histories other than the browser's keep it wherever they keep their entries.
+/// When the app is loaded again at its redirect url, `RouteService::restore_deep_link`, or the
+/// `RestoreDeepLink` request of a `RouteAgent`, replaces that url with the saved route, once the
+/// app is done reading the response of the provider from it.
+///
+/// Deep links are only restored once, and only if they were saved within their maximum age, so an
+/// abandoned login doesn't redirect the app later on.
+///
+/// # Example
+/// ```
+/// use yew_router::service::DeepLink;
+///
+/// let deep_link = DeepLink::new().with_key("login").with_max_age(5 * 60 * 1000);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeepLink {
+    key: String,
+    max_age: u64,
+}
+
+impl DeepLink {
+    /// Creates a deep link that is restored for ten minutes after it is saved.
+    pub fn new() -> Self {
+        DeepLink {
+            key: DEFAULT_KEY.to_string(),
+            max_age: DEFAULT_MAX_AGE,
+        }
+    }
+
+    /// Sets the key that the route is stored under, so several redirect flows, or namespaces,
+    /// don't restore each other's routes.
+    pub fn with_key(self, key: &str) -> Self {
+        DeepLink {
+            key: key.to_string(),
+            ..self
+        }
+    }
+
+    /// Sets how long, in milliseconds, the route is restored for after it is saved.
+    pub fn with_max_age(self, max_age: u64) -> Self {
+        DeepLink { max_age, ..self }
+    }
+
+    /// Gets the key that the route is stored under.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Determines if a route that was saved at `saved_at` may still be restored at `now`, both in
+    /// milliseconds.
+    pub fn is_fresh(&self, saved_at: f64, now: f64) -> bool {
+        is_fresh(saved_at, now, self.max_age)
+    }
+
+    /// Stores the route and its state in `sessionStorage`, replacing the ones stored before.
+    pub(crate) fn save(&self, route: &str, state: Value) {
+        let json: Option<String> = js! {
+            try {
+                return JSON.stringify({ route: @{route}, state: @{state}, savedAt: Date.now() });
+            } catch (error) {
+                return null;
+            }
+        }
+        .try_into()
+        .ok();
+        let saved = json.map_or(false, |json| {
+            window().session_storage().insert(&self.key, &json).is_ok()
+        });
+        if !saved {
+            log::warn!("The deep link couldn't be stored under '{}'", self.key);
+        }
+    }
+
+    /// Removes the route and its state from `sessionStorage`, returning them if they are recent
+    /// enough to be restored at `now`.
+    pub(crate) fn take(&self, now: f64) -> Option<(String, Value)> {
+        let storage = window().session_storage();
+        let json = storage.get(&self.key)?;
+        storage.remove(&self.key);
+
+        let link: Value = js! {
+            try {
+                var link = JSON.parse(@{json});
+                return link && typeof link.route === "string" ? link : null;
+            } catch (error) {
+                return null;
+            }
+        };
+        let route: String = js! { return @{&link}.route; }.try_into().ok()?;
+        let saved_at: f64 = js! { return @{&link}.savedAt; }.try_into().ok()?;
+        if !self.is_fresh(saved_at, now) {
+            log::info!("The deep link to '{}' expired, so it isn't restored", route);
+            return None;
+        }
+        let state: Value = js! { return @{&link}.state; };
+        Some((route, state))
+    }
+}
+
+impl Default for DeepLink {
+    fn default() -> Self {
+        DeepLink::new()
+    }
+}
+
+/// Determines if a deep link saved at `saved_at` may still be restored at `now`.
+fn is_fresh(saved_at: f64, now: f64, max_age: u64) -> bool {
+    // Links from the future, because the clock changed, are treated as expired.
+    saved_at <= now && now - saved_at <= max_age as f64
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn links_expire() {
+        assert!(is_fresh(1000.0, 1000.0, 500));
+        assert!(is_fresh(1000.0, 1500.0, 500));
+        assert!(!is_fresh(1000.0, 1501.0, 500));
+        assert!(!is_fresh(1000.0, 999.0, 500));
+    }
+}
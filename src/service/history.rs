@@ -0,0 +1,243 @@
+//! The history that a `RouteService` reads its url from and adds its entries to, which is the
+//! browser's unless another one is set.
+
+use stdweb::web::{
+    event::{HashChangeEvent, PopStateEvent},
+    window, Date, EventListenerHandle, IEventTarget,
+};
+use stdweb::Value;
+
+use super::DeepLink;
+use crate::strategy::RouteEvents;
+use std::{
+    any::Any,
+    cell::RefCell,
+    fmt::{self, Debug, Formatter},
+    rc::Rc,
+};
+
+thread_local! {
+    static DEFAULT_HISTORY: RefCell<Rc<dyn HistoryBackend>> = RefCell::new(Rc::new(BrowserHistory));
+}
+
+/// The parts of a url, with the `?` and `#` of the query and fragment, like the ones of
+/// `Location`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CurrentUrl {
+    /// The path, like `/users/42`.
+    pub path: String,
+    /// The query, like `?tab=posts`, or an empty string.
+    pub query: String,
+    /// The fragment, like `#bio`, or an empty string.
+    pub fragment: String,
+    /// The host, including the port, like `example.com:8080`.
+    pub host: String,
+}
+
+/// The state of a history entry, as a `HistoryBackend` stores it.
+#[derive(Clone)]
+pub enum HistoryState {
+    /// No state, like for entries that were created by changing the fragment of the url.
+    ///
+    /// The `RouteService` reads it as the default state.
+    Empty,
+    /// A state converted to a JS value by the `RouteService`, along with the budget and version
+    /// that it was set up with.
+    Value(Value),
+    /// A state as it was given to the `RouteService`, for histories that keep their entries in
+    /// memory.
+    ///
+    /// States of another type than the one of the `RouteService` are read as the default state.
+    Native(Rc<dyn Any>),
+}
+
+impl Debug for HistoryState {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            HistoryState::Empty => f.write_str("Empty"),
+            HistoryState::Value(value) => f.debug_tuple("Value").field(value).finish(),
+            HistoryState::Native(_) => f.debug_tuple("Native").field(&"-").finish(),
+        }
+    }
+}
+
+/// Stops a listener that was started with `HistoryBackend::listen` once it is dropped.
+pub struct HistoryListener {
+    stop: Option<Box<dyn FnOnce()>>,
+}
+
+impl HistoryListener {
+    /// Creates the listener, which calls `stop` once it is dropped.
+    pub fn new<F: FnOnce() + 'static>(stop: F) -> Self {
+        HistoryListener {
+            stop: Some(Box::new(stop)),
+        }
+    }
+}
+
+impl Drop for HistoryListener {
+    fn drop(&mut self) {
+        if let Some(stop) = self.stop.take() {
+            stop()
+        }
+    }
+}
+
+impl Debug for HistoryListener {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("HistoryListener").finish()
+    }
+}
+
+/// The history that a `RouteService` reads its url from and adds its entries to.
+///
+/// This is the browser's `BrowserHistory`, unless another one is set with
+/// `RouteService::set_history` or `set_default_history`, like the
+/// [`MockHistory`](../testing/struct.MockHistory.html) that tests route with.
+pub trait HistoryBackend: Debug {
+    /// Gets the parts of the url of the current entry.
+    fn current_url(&self) -> CurrentUrl;
+
+    /// Determines if states are kept as they are, as `HistoryState::Native`, instead of being
+    /// converted to JS values.
+    ///
+    /// Budgets and versions only apply to states that are converted.
+    fn keeps_native_states(&self) -> bool {
+        false
+    }
+
+    /// Adds an entry for the url after the current one, removing the entries after it.
+    fn push(&self, url: &str, state: HistoryState);
+
+    /// Replaces the current entry with one for the url.
+    fn replace(&self, url: &str, state: HistoryState);
+
+    /// Changes the url of the current entry, keeping its state.
+    fn replace_url(&self, url: &str);
+
+    /// Leaves the app for a url outside of it, like the login page of an OAuth provider.
+    fn redirect(&self, url: &str);
+
+    /// Calls the listener with the name of the event and the state of the current entry whenever
+    /// it changes without the app navigating, like when the back button is pressed, until the
+    /// returned `HistoryListener` is dropped.
+    fn listen(
+        &self,
+        route_events: RouteEvents,
+        listener: Rc<dyn Fn(&'static str, HistoryState)>,
+    ) -> HistoryListener;
+
+    /// Stores the route and its state under the key of the deep link, replacing the ones stored
+    /// before.
+    fn save_deep_link(&self, deep_link: &DeepLink, route: &str, state: HistoryState);
+
+    /// Removes the route and its state stored under the key of the deep link, returning them if
+    /// they are recent enough to be restored.
+    fn take_deep_link(&self, deep_link: &DeepLink) -> Option<(String, HistoryState)>;
+
+    /// Gets the current time in milliseconds, which navigations are timed with.
+    fn now(&self) -> f64;
+}
+
+/// The browser's history, which is changed through the History API.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct BrowserHistory;
+
+impl HistoryBackend for BrowserHistory {
+    fn current_url(&self) -> CurrentUrl {
+        let location = window()
+            .location()
+            .expect("browser does not support location API");
+        CurrentUrl {
+            path: location.pathname().unwrap(),
+            query: location.search().unwrap(),
+            fragment: location.hash().unwrap(),
+            host: location.host().unwrap(),
+        }
+    }
+
+    fn push(&self, url: &str, state: HistoryState) {
+        window()
+            .history()
+            .push_state(js_state(state), "", Some(url));
+    }
+
+    fn replace(&self, url: &str, state: HistoryState) {
+        let _ = window()
+            .history()
+            .replace_state(js_state(state), "", Some(url));
+    }
+
+    fn replace_url(&self, url: &str) {
+        // The state of the history entry is kept.
+        js! { @(no_return) window.history.replaceState(window.history.state, "", @{url}); }
+    }
+
+    fn redirect(&self, url: &str) {
+        js! { @(no_return) window.location.assign(@{url}); }
+    }
+
+    fn listen(
+        &self,
+        route_events: RouteEvents,
+        listener: Rc<dyn Fn(&'static str, HistoryState)>,
+    ) -> HistoryListener {
+        let mut handles: Vec<EventListenerHandle> = Vec::new();
+        if route_events != RouteEvents::HashChange {
+            let listener = listener.clone();
+            handles.push(window().add_event_listener(move |event: PopStateEvent| {
+                listener("popstate", HistoryState::Value(event.state()))
+            }));
+        }
+        if route_events != RouteEvents::PopState {
+            handles.push(window().add_event_listener(move |_: HashChangeEvent| {
+                // The fragment can't carry any state.
+                listener("hashchange", HistoryState::Empty)
+            }));
+        }
+        HistoryListener::new(move || {
+            for handle in handles {
+                handle.remove();
+            }
+        })
+    }
+
+    fn save_deep_link(&self, deep_link: &DeepLink, route: &str, state: HistoryState) {
+        deep_link.save(route, js_state(state));
+    }
+
+    fn take_deep_link(&self, deep_link: &DeepLink) -> Option<(String, HistoryState)> {
+        deep_link
+            .take(Date::now())
+            .map(|(route, state)| (route, HistoryState::Value(state)))
+    }
+
+    fn now(&self) -> f64 {
+        Date::now()
+    }
+}
+
+/// Gets the JS value that the browser stores for a state.
+///
+/// Native states can't be stored, so they are stored as `null`, which is read as the default
+/// state.
+fn js_state(state: HistoryState) -> Value {
+    match state {
+        HistoryState::Value(value) => value,
+        HistoryState::Empty | HistoryState::Native(_) => Value::Null,
+    }
+}
+
+/// Sets the history that `RouteService`s use, unless another one is set with
+/// `RouteService::set_history`.
+///
+/// This only applies to services that are created afterwards, including the ones of the
+/// `RouteAgent`s.
+pub fn set_default_history(history: Rc<dyn HistoryBackend>) {
+    DEFAULT_HISTORY.with(|default| *default.borrow_mut() = history);
+}
+
+/// Gets the history set by `set_default_history`, or `BrowserHistory` if none was set.
+pub fn default_history() -> Rc<dyn HistoryBackend> {
+    DEFAULT_HISTORY.with(|default| default.borrow().clone())
+}
@@ -0,0 +1,258 @@
+//! Keeps the route state stored in history entries within the size that browsers accept.
+//!
+//! Browsers limit how large the serialized state of a history entry may be, and throw when a
+//! navigation exceeds it, so the size of the state is measured before it is stored.
+
+use stdweb::{
+    unstable::TryInto,
+    web::{window, Date},
+    Value,
+};
+
+use super::state_version::{self, StateVersion};
+use crate::route::RouterState;
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+
+/// The prefix of the `sessionStorage` keys that spilled states are stored under.
+const SPILLED_STATE_PREFIX: &str = "yew_router_state_";
+
+thread_local! {
+    /// Distinguishes states that are spilled within the same millisecond.
+    static SPILL_COUNTER: Cell<u32> = Cell::new(0);
+}
+
+/// What a `RouteService` does with route state that exceeds its `StateBudget`.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum OversizedState {
+    /// Logs a warning, and stores the state in the history entry anyway.
+    Warn,
+    /// Logs an error, and stores the default state in the history entry instead.
+    Discard,
+    /// Stores the state in `sessionStorage`, and only the key it is stored under in the history
+    /// entry.
+    ///
+    /// The state is read back from `sessionStorage` when the entry is navigated to with the back
+    /// and forward buttons.
+    /// If `sessionStorage` can't hold the state either, it is discarded like with `Discard`.
+    SpillToSessionStorage,
+}
+
+/// A limit on the size of the route state that a `RouteService` stores in history entries.
+///
+/// The size is measured in bytes of the state serialized as JSON.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub struct StateBudget {
+    /// The largest size of state that is stored in history entries as it is.
+    pub max_bytes: usize,
+    /// What is done with state that is larger.
+    pub oversized: OversizedState,
+}
+
+impl StateBudget {
+    /// Creates a budget.
+    pub fn new(max_bytes: usize, oversized: OversizedState) -> Self {
+        StateBudget {
+            max_bytes,
+            oversized,
+        }
+    }
+
+    /// Determines if state of the given serialized size exceeds the budget.
+    fn is_exceeded_by(&self, bytes: usize) -> bool {
+        bytes > self.max_bytes
+    }
+}
+
+impl Default for StateBudget {
+    /// The 640 KiB that the most restrictive browsers accept, with a warning for larger state.
+    fn default() -> Self {
+        StateBudget::new(640 * 1024, OversizedState::Warn)
+    }
+}
+
+/// Converts the state into the value that is stored in a history entry, enforcing the budget.
+///
+/// The budget applies to the state along with its version, if it has one.
+pub(crate) fn history_value<T: RouterState>(
+    state: T,
+    budget: Option<StateBudget>,
+    version: Option<&StateVersion<T>>,
+) -> Value {
+    let value: Value = state_version::wrap(js! { return @{state}; }, version);
+    let budget = match budget {
+        Some(budget) => budget,
+        None => return value,
+    };
+    let json: String = match js! { return JSON.stringify(@{&value}); }.try_into() {
+        Ok(json) => json,
+        // Values like `undefined` don't serialize to anything.
+        Err(_) => return value,
+    };
+    match stored_state(budget, json.len(), || spill(&json)) {
+        StoredState::State => value,
+        StoredState::Default => state_version::wrap(js! { return @{T::default()}; }, version),
+        StoredState::Spilled(key) => js! { return { yewRouterSpilledState: @{key} }; },
+    }
+}
+
+/// What is stored in a history entry in place of a route state.
+#[derive(Debug, PartialEq)]
+enum StoredState {
+    /// The state itself.
+    State,
+    /// The default state, as the state was discarded.
+    Default,
+    /// The key that the state was spilled to `sessionStorage` under.
+    Spilled(String),
+}
+
+/// Decides what is stored in a history entry for a state of the given serialized size, spilling
+/// the state with `spill` if the budget says so.
+fn stored_state<F: FnOnce() -> Option<String>>(
+    budget: StateBudget,
+    bytes: usize,
+    spill: F,
+) -> StoredState {
+    if !budget.is_exceeded_by(bytes) {
+        return StoredState::State;
+    }
+    let spilled = match budget.oversized {
+        OversizedState::Warn => {
+            log::warn!(
+                "The route state is {} bytes, which exceeds the budget of {} bytes",
+                bytes,
+                budget.max_bytes
+            );
+            return StoredState::State;
+        }
+        OversizedState::Discard => None,
+        OversizedState::SpillToSessionStorage => spill(),
+    };
+    match spilled {
+        Some(key) => StoredState::Spilled(key),
+        None => {
+            log::error!(
+                "The route state is {} bytes, which exceeds the budget of {} bytes, so the \
+                 default state is stored instead",
+                bytes,
+                budget.max_bytes
+            );
+            StoredState::Default
+        }
+    }
+}
+
+/// Gets the state of a history entry, reading it from `sessionStorage` if it was spilled there.
+pub(crate) fn state_from_history<T: RouterState>(
+    value: Value,
+    version: Option<&StateVersion<T>>,
+) -> T {
+    let spilled_key: Option<String> = js! {
+        var state = @{&value};
+        if (state && typeof state.yewRouterSpilledState === "string") {
+            return state.yewRouterSpilledState;
+        }
+        return null;
+    }
+    .try_into()
+    .ok();
+
+    let value = match spilled_key {
+        Some(key) => match window().session_storage().get(&key) {
+            Some(json) => js! {
+                try {
+                    return JSON.parse(@{json});
+                } catch (error) {
+                    return null;
+                }
+            },
+            None => {
+                log::warn!(
+                    "The route state stored in sessionStorage under '{}' is missing",
+                    key
+                );
+                return T::default();
+            }
+        },
+        None => value,
+    };
+    state_version::unwrap(value, version)
+}
+
+/// Stores serialized state in `sessionStorage`, returning the key it is stored under.
+///
+/// Spilled states are kept for the rest of the session, as history entries may refer to them
+/// until then.
+fn spill(json: &str) -> Option<String> {
+    let count = SPILL_COUNTER.with(|counter| {
+        let count = counter.get();
+        counter.set(count.wrapping_add(1));
+        count
+    });
+    // The time keeps keys unique across reloads, which share the session.
+    let key = format!("{}{}_{}", SPILLED_STATE_PREFIX, Date::now() as u64, count);
+    match window().session_storage().insert(&key, json) {
+        Ok(()) => Some(key),
+        Err(_) => {
+            log::error!("The route state couldn't be stored in sessionStorage");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn budget_is_exceeded_by_larger_state() {
+        let budget = StateBudget::new(4, OversizedState::Warn);
+        assert!(!budget.is_exceeded_by(3));
+        assert!(!budget.is_exceeded_by(4));
+        assert!(budget.is_exceeded_by(5));
+    }
+
+    fn spilled_to(key: &str) -> impl FnOnce() -> Option<String> {
+        let key = key.to_string();
+        move || Some(key)
+    }
+
+    #[test]
+    fn state_within_the_budget_is_stored_as_it_is() {
+        let budget = StateBudget::new(4, OversizedState::SpillToSessionStorage);
+        let stored = stored_state(budget, 4, || panic!("state within the budget isn't spilled"));
+        assert_eq!(stored, StoredState::State);
+    }
+
+    #[test]
+    fn oversized_state_is_stored_anyway_with_warn() {
+        let budget = StateBudget::new(4, OversizedState::Warn);
+        assert_eq!(
+            stored_state(budget, 5, spilled_to("key")),
+            StoredState::State
+        );
+    }
+
+    #[test]
+    fn oversized_state_is_replaced_by_the_default_with_discard() {
+        let budget = StateBudget::new(4, OversizedState::Discard);
+        let stored = stored_state(budget, 5, || panic!("discarded state isn't spilled"));
+        assert_eq!(stored, StoredState::Default);
+    }
+
+    #[test]
+    fn oversized_state_is_spilled_with_spill_to_session_storage() {
+        let budget = StateBudget::new(4, OversizedState::SpillToSessionStorage);
+        assert_eq!(
+            stored_state(budget, 5, spilled_to("yew_router_state_1_0")),
+            StoredState::Spilled("yew_router_state_1_0".to_string())
+        );
+    }
+
+    #[test]
+    fn state_that_can_not_be_spilled_is_discarded() {
+        let budget = StateBudget::new(4, OversizedState::SpillToSessionStorage);
+        assert_eq!(stored_state(budget, 5, || None), StoredState::Default);
+    }
+}
@@ -0,0 +1,208 @@
+//! Stores the route state of history entries along with its version, so apps can change their
+//! state type without losing the entries that older builds of the app stored.
+
+use stdweb::{unstable::TryInto, Value};
+
+use crate::route::RouterState;
+use std::{
+    any::{Any, TypeId},
+    cell::RefCell,
+    collections::HashMap,
+    fmt::{self, Debug, Formatter},
+    rc::Rc,
+};
+
+thread_local! {
+    /// The versions set by `set_default_state_version`, by the type of the state.
+    static DEFAULT_VERSIONS: RefCell<HashMap<TypeId, Box<dyn Any>>> = RefCell::new(HashMap::new());
+}
+
+/// The version of the route state that a `RouteService` stores in history entries, with a hook
+/// that upgrades the state of entries that were stored by older versions.
+///
+/// Once a version is set, states are stored as `{ version, payload }`.
+/// When an entry is navigated back to, like after a deploy changed the state type, the payload of
+/// an older version is given to the upgrade hook as JSON, instead of failing to convert it.
+/// Entries that were stored without a version have version `0`.
+/// States that can't be upgraded, including those of newer versions, are replaced by the default
+/// state.
+///
+/// # Example
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use yew_router::service::StateVersion;
+///
+/// #[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+/// struct ScrollState {
+///     x: f64,
+///     y: f64,
+/// }
+///
+/// // Version 1 only stored the vertical offset, as a number.
+/// let version = StateVersion::new(2).with_upgrade(|version, payload| match version {
+///     1 => payload.parse().ok().map(|y| ScrollState { x: 0.0, y }),
+///     _ => None,
+/// });
+/// assert_eq!(version.version(), 2);
+/// ```
+pub struct StateVersion<T = ()> {
+    version: u32,
+    upgrade: Option<Rc<dyn Fn(u32, &str) -> Option<T>>>,
+}
+
+impl<T> StateVersion<T> {
+    /// Creates the version, which states of other versions are discarded for.
+    pub fn new(version: u32) -> Self {
+        StateVersion {
+            version,
+            upgrade: None,
+        }
+    }
+
+    /// Sets the hook that converts the payloads of older versions, given as JSON, into the
+    /// current state, returning `None` for payloads that can't be upgraded.
+    pub fn with_upgrade<F: Fn(u32, &str) -> Option<T> + 'static>(self, upgrade: F) -> Self {
+        StateVersion {
+            upgrade: Some(Rc::new(upgrade)),
+            ..self
+        }
+    }
+
+    /// Gets the version that states are stored with.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Upgrades the payload of a state that was stored with another version.
+    ///
+    /// Newer versions are never upgraded, as they can't be known yet.
+    pub(crate) fn upgrade(&self, version: u32, payload: &str) -> Option<T> {
+        if version >= self.version {
+            return None;
+        }
+        self.upgrade
+            .as_ref()
+            .and_then(|upgrade| upgrade(version, payload))
+    }
+}
+
+impl<T> Clone for StateVersion<T> {
+    fn clone(&self) -> Self {
+        StateVersion {
+            version: self.version,
+            upgrade: self.upgrade.clone(),
+        }
+    }
+}
+
+impl<T> Debug for StateVersion<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("StateVersion")
+            .field("version", &self.version)
+            .field("upgrade", &self.upgrade.is_some())
+            .finish()
+    }
+}
+
+/// Sets the version of the states of type `T`, which is used by every `RouteService` that doesn't
+/// have its own, including the ones of `RouteAgent`s.
+///
+/// This should be called before the first agent is created, like at the start of `main`.
+pub fn set_default_state_version<T: RouterState>(version: StateVersion<T>) {
+    DEFAULT_VERSIONS.with(|versions| {
+        versions
+            .borrow_mut()
+            .insert(TypeId::of::<T>(), Box::new(version))
+    });
+}
+
+/// Gets the version set by `set_default_state_version` for the states of type `T`.
+pub(crate) fn default_state_version<T: RouterState>() -> Option<StateVersion<T>> {
+    DEFAULT_VERSIONS.with(|versions| {
+        versions
+            .borrow()
+            .get(&TypeId::of::<T>())
+            .and_then(|version| version.downcast_ref::<StateVersion<T>>())
+            .cloned()
+    })
+}
+
+/// Gets the version that a `RouteService` stores states with, falling back to the default one.
+pub(crate) fn resolve<T: RouterState>(
+    version: &RefCell<Option<StateVersion<T>>>,
+) -> Option<StateVersion<T>> {
+    version.borrow().clone().or_else(default_state_version)
+}
+
+/// Wraps the value of a state in an envelope with the version.
+pub(crate) fn wrap<T>(value: Value, version: Option<&StateVersion<T>>) -> Value {
+    match version {
+        Some(version) => js! { return { version: @{version.version}, payload: @{value} }; },
+        None => value,
+    }
+}
+
+/// Gets the state out of the value of a history entry, upgrading it if it is of an older version.
+pub(crate) fn unwrap<T: RouterState>(value: Value, version: Option<&StateVersion<T>>) -> T {
+    let version = match version {
+        Some(version) => version,
+        None => return T::try_from(value).unwrap_or_default(),
+    };
+    let stored_version: Option<u32> = js! {
+        var state = @{&value};
+        if (state && typeof state === "object" && typeof state.version === "number"
+                && "payload" in state) {
+            return state.version;
+        }
+        return null;
+    }
+    .try_into()
+    .ok();
+    let (stored_version, payload) = match stored_version {
+        Some(stored_version) => (stored_version, js! { return @{&value}.payload; }),
+        None => (0, value),
+    };
+    if stored_version == version.version {
+        return T::try_from(payload).unwrap_or_default();
+    }
+
+    let json: Option<String> = js! {
+        try {
+            return JSON.stringify(@{&payload});
+        } catch (error) {
+            return null;
+        }
+    }
+    .try_into()
+    .ok();
+    match json.and_then(|json| version.upgrade(stored_version, &json)) {
+        Some(state) => state,
+        None => {
+            log::warn!(
+                "The route state of version {} couldn't be upgraded to version {}, so the default \
+                 state is used instead",
+                stored_version,
+                version.version
+            );
+            T::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn only_older_versions_are_upgraded() {
+        let version = StateVersion::new(2)
+            .with_upgrade(|version, payload| Some(format!("{} from version {}", payload, version)));
+        assert_eq!(
+            version.upgrade(1, "\"home\""),
+            Some("\"home\" from version 1".to_string())
+        );
+        assert_eq!(version.upgrade(2, "\"home\""), None);
+        assert_eq!(version.upgrade(3, "\"home\""), None);
+        assert_eq!(StateVersion::<String>::new(2).upgrade(1, "\"home\""), None);
+    }
+}
@@ -0,0 +1,370 @@
+//! Generates a `sitemap.xml` from the routes of a `Switch`.
+//!
+//! This doesn't depend on the browser, so it can be used in build scripts and server binaries
+//! that share their `Switch` with the app.
+use crate::{
+    matcher::{MatcherSettings, RouteMatcher},
+    switch::{RouteInfo, Switch},
+};
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug, Formatter},
+};
+
+/// The flag that leaves a route out of the sitemap.
+const EXCLUDE_KEY: &str = "no_sitemap";
+
+/// The key of the metadata that declares how often the page of a route changes.
+const CHANGEFREQ_KEY: &str = "changefreq";
+
+/// The key of the metadata that declares the priority of a route.
+const PRIORITY_KEY: &str = "priority";
+
+type Expand<'a> = Box<dyn Fn(&RouteInfo) -> Vec<HashMap<String, String>> + 'a>;
+
+/// Generates sitemaps, which list the urls of an app for search engines, from the routes of a
+/// `Switch`.
+///
+/// Routes whose matchers don't capture anything are listed as they are.
+/// Routes that capture values are listed once for every set of values returned by the function
+/// given to `expand`, with the values filled into the named captures of their matchers.
+/// Values are inserted as they are, so they should already be percent-encoded.
+///
+/// Variants can be left out with `#[meta(no_sitemap)]`, and `#[meta(changefreq = "weekly")]` and
+/// `#[meta(priority = "0.8")]` are passed through to their entries.
+///
+/// # Example
+/// ```
+/// use std::collections::HashMap;
+/// use yew_router::{sitemap::Sitemap, Switch};
+///
+/// #[derive(Switch)]
+/// enum AppRoute {
+///     #[to = "/posts/{id}"]
+///     Post { id: u32 },
+///     #[to = "/admin"]
+///     #[meta(no_sitemap)]
+///     Admin,
+///     #[to = "/!"]
+///     #[meta(changefreq = "daily")]
+///     Home,
+/// }
+///
+/// let sitemap = Sitemap::new("https://example.com").expand(|info| {
+///     if info.name == "Post" {
+///         (1..=2)
+///             .map(|id| {
+///                 let mut captures = HashMap::new();
+///                 captures.insert("id".to_string(), id.to_string());
+///                 captures
+///             })
+///             .collect()
+///     } else {
+///         Vec::new()
+///     }
+/// });
+///
+/// let urls: Vec<String> = sitemap
+///     .entries::<AppRoute>()
+///     .into_iter()
+///     .map(|entry| entry.url)
+///     .collect();
+/// assert_eq!(
+///     urls,
+///     vec![
+///         "https://example.com/posts/1",
+///         "https://example.com/posts/2",
+///         "https://example.com/",
+///     ]
+/// );
+/// assert!(sitemap
+///     .to_xml::<AppRoute>()
+///     .contains("<url><loc>https://example.com/</loc><changefreq>daily</changefreq></url>"));
+/// ```
+pub struct Sitemap<'a> {
+    base_url: String,
+    expand: Option<Expand<'a>>,
+}
+
+/// An url listed in a sitemap.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SitemapEntry {
+    /// The absolute url of the page.
+    pub url: String,
+    /// How often the page changes, from the `changefreq` metadata of its route.
+    pub changefreq: Option<&'static str>,
+    /// The priority of the page relative to the others, from the `priority` metadata of its
+    /// route.
+    pub priority: Option<&'static str>,
+}
+
+impl<'a> Sitemap<'a> {
+    /// Creates a generator for the urls of the site at `base_url`, like `https://example.com`.
+    pub fn new(base_url: &str) -> Self {
+        Sitemap {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            expand: None,
+        }
+    }
+
+    /// Sets the function that gets the values of the captures of every route that is listed in
+    /// the sitemap, for each of the urls it should be listed as.
+    ///
+    /// Without this, routes that capture values aren't listed.
+    pub fn expand<F: Fn(&RouteInfo) -> Vec<HashMap<String, String>> + 'a>(
+        mut self,
+        expand: F,
+    ) -> Self {
+        self.expand = Some(Box::new(expand));
+        self
+    }
+
+    /// Lists the urls of the routes of `SW`, in the order the routes are declared.
+    pub fn entries<SW: Switch>(&self) -> Vec<SitemapEntry> {
+        self.entries_of(SW::routes())
+    }
+
+    /// Renders the sitemap of the routes of `SW`.
+    pub fn to_xml<SW: Switch>(&self) -> String {
+        let mut xml = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+        );
+        for entry in self.entries::<SW>() {
+            xml.push_str("<url><loc>");
+            xml.push_str(&escape(&entry.url));
+            xml.push_str("</loc>");
+            if let Some(changefreq) = entry.changefreq {
+                xml.push_str("<changefreq>");
+                xml.push_str(&escape(changefreq));
+                xml.push_str("</changefreq>");
+            }
+            if let Some(priority) = entry.priority {
+                xml.push_str("<priority>");
+                xml.push_str(&escape(priority));
+                xml.push_str("</priority>");
+            }
+            xml.push_str("</url>\n");
+        }
+        xml.push_str("</urlset>\n");
+        xml
+    }
+
+    fn entries_of(&self, routes: Vec<RouteInfo>) -> Vec<SitemapEntry> {
+        routes
+            .into_iter()
+            .filter(|info| !info.meta.contains(EXCLUDE_KEY))
+            .flat_map(|info| {
+                let routes = self.routes_of(&info);
+                let changefreq = info.meta.get(CHANGEFREQ_KEY);
+                let priority = info.meta.get(PRIORITY_KEY);
+                routes.into_iter().map(move |route| SitemapEntry {
+                    url: self.url(&route),
+                    changefreq,
+                    priority,
+                })
+            })
+            .collect()
+    }
+
+    /// Gets the routes that a route is listed as.
+    fn routes_of(&self, info: &RouteInfo) -> Vec<String> {
+        if let Some(route) = info.route {
+            return vec![route.to_string()];
+        }
+        let expand = match &self.expand {
+            Some(expand) => expand,
+            None => return Vec::new(),
+        };
+        let captures = expand(info);
+        if captures.is_empty() {
+            return Vec::new();
+        }
+        let settings = MatcherSettings {
+            grammar_version: info.grammar_version,
+            ..MatcherSettings::default()
+        };
+        let matcher = match RouteMatcher::new(info.matcher, settings) {
+            Ok(matcher) => matcher,
+            Err(error) => {
+                log::error!(
+                    "{} is left out of the sitemap, as its matcher can't be parsed: {}",
+                    info.name,
+                    error
+                );
+                return Vec::new();
+            }
+        };
+        captures
+            .iter()
+            .filter_map(|captures| {
+                let route = matcher.fill(|name| captures.get(name).cloned());
+                if route.is_none() {
+                    log::warn!(
+                        "{} is left out of the sitemap, as its captures {:?} can't fill '{}'",
+                        info.name,
+                        captures,
+                        info.matcher
+                    );
+                }
+                route
+            })
+            .collect()
+    }
+
+    fn url(&self, route: &str) -> String {
+        if route.starts_with('/') {
+            format!("{}{}", self.base_url, route)
+        } else {
+            format!("{}/{}", self.base_url, route)
+        }
+    }
+}
+
+impl<'a> Debug for Sitemap<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sitemap")
+            .field("base_url", &self.base_url)
+            .field("expand", &self.expand.is_some())
+            .finish()
+    }
+}
+
+/// Escapes the characters that can't appear in the text of an XML element.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        matcher::GrammarVersion,
+        switch::{CaptureInfo, RouteMeta},
+    };
+
+    fn info(
+        name: &'static str,
+        matcher: &'static str,
+        captures: &[&'static str],
+        meta: &'static [(&'static str, Option<&'static str>)],
+    ) -> RouteInfo {
+        RouteInfo {
+            name,
+            matcher,
+            captures: captures
+                .iter()
+                .map(|name| CaptureInfo {
+                    name: Some(name),
+                    ty: Some("String"),
+                })
+                .collect(),
+            route: if captures.is_empty() {
+                Some(matcher.trim_end_matches('!'))
+            } else {
+                None
+            },
+            grammar_version: GrammarVersion::V1,
+            meta: RouteMeta::new(meta),
+        }
+    }
+
+    fn routes() -> Vec<RouteInfo> {
+        vec![
+            info(
+                "Tag",
+                "/tags/{name}",
+                &["name"],
+                &[("priority", Some("0.5"))],
+            ),
+            info("Admin", "/admin", &[], &[("no_sitemap", None)]),
+            info("Search", "/search?q=a&b", &[], &[]),
+            info("Home", "!", &[], &[]),
+        ]
+    }
+
+    #[test]
+    fn routes_with_captures_are_left_out_without_expand() {
+        let urls: Vec<String> = Sitemap::new("https://example.com/")
+            .entries_of(routes())
+            .into_iter()
+            .map(|entry| entry.url)
+            .collect();
+        assert_eq!(
+            urls,
+            vec!["https://example.com/search?q=a&b", "https://example.com/"]
+        );
+    }
+
+    #[test]
+    fn captures_are_filled() {
+        let sitemap = Sitemap::new("https://example.com").expand(|info| {
+            ["rust", "", "web"]
+                .iter()
+                .map(|tag| {
+                    let mut captures = HashMap::new();
+                    if !tag.is_empty() {
+                        captures.insert("name".to_string(), tag.to_string());
+                    }
+                    captures
+                })
+                .filter(|_| info.name == "Tag")
+                .collect()
+        });
+        let entries = sitemap.entries_of(routes());
+        assert_eq!(
+            entries[..2],
+            [
+                SitemapEntry {
+                    url: "https://example.com/tags/rust".to_string(),
+                    changefreq: None,
+                    priority: Some("0.5"),
+                },
+                SitemapEntry {
+                    url: "https://example.com/tags/web".to_string(),
+                    changefreq: None,
+                    priority: Some("0.5"),
+                },
+            ]
+        );
+        assert_eq!(entries.len(), 4);
+    }
+
+    #[test]
+    fn matchers_are_parsed_with_their_grammar_version() {
+        let route = RouteInfo {
+            grammar_version: GrammarVersion::V2,
+            ..info("Topic", r"/topics/c\{\}/{name}", &["name"], &[])
+        };
+        let sitemap = Sitemap::new("https://example.com").expand(|_| {
+            let mut captures = HashMap::new();
+            captures.insert("name".to_string(), "rust".to_string());
+            vec![captures]
+        });
+        let urls: Vec<String> = sitemap
+            .entries_of(vec![route])
+            .into_iter()
+            .map(|entry| entry.url)
+            .collect();
+        assert_eq!(urls, vec!["https://example.com/topics/c{}/rust"]);
+    }
+
+    #[test]
+    fn urls_are_escaped() {
+        assert_eq!(
+            escape("https://example.com/search?q=a&b=<c>"),
+            "https://example.com/search?q=a&amp;b=&lt;c&gt;"
+        );
+    }
+}
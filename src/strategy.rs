@@ -0,0 +1,287 @@
+//! Strategies that determine where in the url the route is stored.
+//!
+//! Most apps keep their route in the path of the url, but hosting environments that can't serve
+//! the app from every path (static file hosts, `file://` urls, etc.) need it somewhere else.
+use std::{cell::RefCell, fmt::Debug, rc::Rc};
+
+mod localized;
+pub use localized::{CaptureTranslation, LocalizedStrategy, RouteTranslations};
+
+thread_local! {
+    static DEFAULT_STRATEGY: RefCell<Rc<dyn RoutingStrategy>> = RefCell::new(Rc::new(PathStrategy));
+}
+
+/// The browser events that a `RouteService` listens to in order to notice route changes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RouteEvents {
+    /// Listen to `popstate`, which fires when the back and forward buttons are used.
+    PopState,
+    /// Listen to `hashchange`, which fires when the fragment of the url changes, including when
+    /// it is edited by hand.
+    HashChange,
+    /// Listen to both `popstate` and `hashchange`.
+    Both,
+}
+
+/// Determines where the route lives in the browser's url.
+pub trait RoutingStrategy: Debug {
+    /// Extracts the route from the components of the current url.
+    ///
+    /// The query and fragment include their leading `?` and `#` if they aren't empty.
+    fn route_from_url(&self, path: &str, query: &str, fragment: &str) -> String;
+
+    /// Creates the url, relative to the current one, that should be navigated to for the route.
+    ///
+    /// The components of the current url are provided so strategies that only occupy part of the
+    /// url can preserve the rest of it.
+    fn url_for_route(&self, route: &str, path: &str, query: &str, fragment: &str) -> String;
+
+    /// The browser events that indicate that the route may have changed.
+    fn route_events(&self) -> RouteEvents {
+        RouteEvents::PopState
+    }
+
+    /// Gets the locale of the url, for strategies that localize the route.
+    fn locale(&self, _path: &str, _query: &str, _fragment: &str) -> Option<String> {
+        None
+    }
+
+    /// Creates the url of the current route in another locale, for strategies that localize the
+    /// route.
+    ///
+    /// The components of the current url are provided, and `None` is returned if the locale isn't
+    /// known.
+    fn url_in_locale(
+        &self,
+        _locale: &str,
+        _path: &str,
+        _query: &str,
+        _fragment: &str,
+    ) -> Option<String> {
+        None
+    }
+}
+
+/// Sets the strategy used by `RouteService`s that are created afterwards, as well as the hrefs of
+/// `RouterLink`s.
+///
+/// Because the `RouteAgent` creates its own `RouteService`, this should be called before any
+/// component that uses the agent is created.
+pub fn set_default_strategy<S: RoutingStrategy + 'static>(strategy: S) {
+    DEFAULT_STRATEGY.with(|default| *default.borrow_mut() = Rc::new(strategy));
+}
+
+/// Gets the strategy set by `set_default_strategy`, or `PathStrategy` if none was set.
+pub fn default_strategy() -> Rc<dyn RoutingStrategy> {
+    DEFAULT_STRATEGY.with(|default| default.borrow().clone())
+}
+
+/// Creates the url for the route using the components of the browser's current url.
+#[cfg(feature = "service")]
+pub(crate) fn url_for_current_location(strategy: &dyn RoutingStrategy, route: &str) -> String {
+    let url = crate::service::current_url();
+    strategy.url_for_route(route, &url.path, &url.query, &url.fragment)
+}
+
+/// Gets the locale of the browser's current url.
+#[cfg(feature = "service")]
+pub(crate) fn locale_for_current_location(strategy: &dyn RoutingStrategy) -> Option<String> {
+    let url = crate::service::current_url();
+    strategy.locale(&url.path, &url.query, &url.fragment)
+}
+
+/// Stores the route in the path, query, and fragment of the url.
+///
+/// `/users/42?tab=posts`
+///
+/// Because the route occupies the whole url, changing it replaces everything after the origin.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct PathStrategy;
+
+impl RoutingStrategy for PathStrategy {
+    fn route_from_url(&self, path: &str, query: &str, fragment: &str) -> String {
+        crate::route::format_route_string(path, query, fragment)
+    }
+
+    fn url_for_route(&self, route: &str, _path: &str, _query: &str, _fragment: &str) -> String {
+        route.to_string()
+    }
+}
+
+/// Stores the route in the fragment of the url.
+///
+/// `/index.html#/users/42?tab=posts`
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct HashStrategy;
+
+impl RoutingStrategy for HashStrategy {
+    fn route_from_url(&self, _path: &str, _query: &str, fragment: &str) -> String {
+        let route = fragment.trim_start_matches('#');
+        if route.is_empty() {
+            "/".to_string()
+        } else {
+            route.to_string()
+        }
+    }
+
+    fn url_for_route(&self, route: &str, _path: &str, _query: &str, _fragment: &str) -> String {
+        format!("#{}", route)
+    }
+
+    /// Fragments edited by hand only fire `hashchange`, so both events are listened to.
+    fn route_events(&self) -> RouteEvents {
+        RouteEvents::Both
+    }
+}
+
+/// Stores the route, percent-encoded, in a query parameter of the url.
+///
+/// `/index.html?p=/users/42%3Ftab%3Dposts`
+///
+/// The rest of the url is preserved when the route changes, so this can be used by a second router
+/// that controls part of the page, like a side panel, alongside the main one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryStrategy {
+    /// The name of the query parameter that holds the route.
+    pub param: String,
+}
+
+impl QueryStrategy {
+    /// Creates a strategy that stores the route in the given query parameter.
+    pub fn new<S: Into<String>>(param: S) -> Self {
+        QueryStrategy {
+            param: param.into(),
+        }
+    }
+}
+
+impl Default for QueryStrategy {
+    fn default() -> Self {
+        QueryStrategy::new("p")
+    }
+}
+
+impl RoutingStrategy for QueryStrategy {
+    fn route_from_url(&self, _path: &str, query: &str, _fragment: &str) -> String {
+        query
+            .trim_start_matches('?')
+            .split('&')
+            .filter_map(|pair| {
+                let mut split = pair.splitn(2, '=');
+                match (split.next(), split.next()) {
+                    (Some(key), Some(value)) if key == self.param => Some(percent_decode(value)),
+                    _ => None,
+                }
+            })
+            .next()
+            .unwrap_or_else(|| "/".to_string())
+    }
+
+    fn url_for_route(&self, route: &str, path: &str, query: &str, fragment: &str) -> String {
+        let param = format!("{}={}", self.param, percent_encode(route));
+        let query = query
+            .trim_start_matches('?')
+            .split('&')
+            .filter(|pair| !pair.is_empty() && pair.split('=').next() != Some(self.param.as_str()))
+            .chain(std::iter::once(param.as_str()))
+            .collect::<Vec<&str>>()
+            .join("&");
+        format!("{}?{}{}", path, query, fragment)
+    }
+}
+
+/// Encodes everything except unreserved characters and `/`, which is left alone for legibility.
+pub(crate) fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                encoded.push(char::from(byte))
+            }
+            byte => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+pub(crate) fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = if bytes[index] == b'%' {
+            value
+                .get(index + 1..index + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        } else {
+            None
+        };
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path_strategy() {
+        assert_eq!(PathStrategy.route_from_url("/a", "?b=c", "#d"), "/a?b=c#d");
+        assert_eq!(
+            PathStrategy.url_for_route("/a?b=c#d", "/x", "?y", "#z"),
+            "/a?b=c#d"
+        );
+    }
+
+    #[test]
+    fn hash_strategy() {
+        assert_eq!(
+            HashStrategy.route_from_url("/index.html", "", "#/a?b=c"),
+            "/a?b=c"
+        );
+        assert_eq!(HashStrategy.route_from_url("/index.html", "", ""), "/");
+        assert_eq!(
+            HashStrategy.url_for_route("/a?b=c", "/index.html", "", ""),
+            "#/a?b=c"
+        );
+    }
+
+    #[test]
+    fn query_strategy_round_trips() {
+        let strategy = QueryStrategy::default();
+        let url = strategy.url_for_route("/a b/ü?c=d&e#f", "/index.html", "", "");
+        assert_eq!(url, "/index.html?p=/a%20b/%C3%BC%3Fc%3Dd%26e%23f");
+        let query = &url["/index.html".len()..];
+        assert_eq!(
+            strategy.route_from_url("/index.html", query, ""),
+            "/a b/ü?c=d&e#f"
+        );
+    }
+
+    #[test]
+    fn query_strategy_finds_param() {
+        let strategy = QueryStrategy::new("route");
+        assert_eq!(strategy.route_from_url("/", "?x=1&route=/a&y=2", ""), "/a");
+        assert_eq!(strategy.route_from_url("/", "?x=1", ""), "/");
+        assert_eq!(strategy.route_from_url("/", "?route=%ZZ", ""), "%ZZ");
+    }
+
+    #[test]
+    fn query_strategy_preserves_rest_of_url() {
+        let strategy = QueryStrategy::new("panel");
+        assert_eq!(
+            strategy.url_for_route("/b", "/users", "?x=1&panel=/a&y=2", "#top"),
+            "/users?x=1&y=2&panel=/b#top"
+        );
+    }
+}
@@ -0,0 +1,414 @@
+//! Routes whose urls are prefixed with a locale, and translated into its language.
+use super::RoutingStrategy;
+use crate::{
+    matcher::RouteMatcher,
+    route::{format_route_string, RouteParts},
+};
+use std::{
+    fmt::{self, Debug, Formatter},
+    rc::Rc,
+};
+
+/// The forms of a canonical route, matched by the matchers of the route and each of its forms.
+type Translation = (RouteMatcher, Vec<(String, RouteMatcher)>);
+
+type CaptureTranslator = Rc<dyn Fn(&CaptureTranslation) -> Option<String>>;
+
+/// A value captured from a route whose locale is switched, which can be translated into the new
+/// locale by the function given to `RouteTranslations::translating_captures`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CaptureTranslation<'a> {
+    /// The name of the capture.
+    pub name: &'a str,
+    /// The value that was captured.
+    pub value: &'a str,
+    /// The locale that the value is in.
+    pub from: &'a str,
+    /// The locale that the value is translated into.
+    pub to: &'a str,
+}
+
+/// A table that maps canonical routes to their form in each locale.
+///
+/// Both the canonical route and its forms are matcher strings for the path of the route, like
+/// `/users/{id}` and `/benutzer/{id}`, whose named captures are carried over when the path is
+/// translated.
+/// Paths that aren't in the table are the same in every locale.
+///
+/// # Example
+/// ```
+/// use yew_router::strategy::RouteTranslations;
+///
+/// let translations = RouteTranslations::new()
+///     .route("/about", &[("de", "/ueber-uns"), ("fr", "/a-propos")])
+///     .route("/users/{id}", &[("de", "/benutzer/{id}")]);
+/// assert_eq!(translations.localize("/users/42", "de"), "/benutzer/42");
+/// assert_eq!(translations.canonicalize("/ueber-uns", "de"), "/about");
+/// assert_eq!(translations.localize("/about", "en"), "/about");
+/// assert_eq!(translations.switch_locale("/ueber-uns", "de", "fr"), "/a-propos");
+/// ```
+#[derive(Clone, Default)]
+pub struct RouteTranslations {
+    routes: Vec<Translation>,
+    /// Translates the values captured from routes whose locale is switched.
+    translate_capture: Option<CaptureTranslator>,
+}
+
+impl RouteTranslations {
+    /// Creates an empty table.
+    pub fn new() -> Self {
+        RouteTranslations::default()
+    }
+
+    /// Adds the forms of a canonical route in some locales.
+    ///
+    /// Routes are tried in the order they are added, so more specific routes should come first.
+    /// A route can have several forms in a locale, in which case they are all turned into the
+    /// canonical route, while the first one is used for urls.
+    ///
+    /// # Panics
+    /// Panics if one of the matcher strings can't be parsed, or a form doesn't capture the same
+    /// names as the canonical route.
+    pub fn route(mut self, canonical: &str, localized: &[(&str, &str)]) -> Self {
+        let parse = |matcher: &str| {
+            RouteMatcher::try_from(matcher).unwrap_or_else(|error| {
+                panic!("The route '{}' couldn't be parsed: {}", matcher, error)
+            })
+        };
+        let canonical_matcher = parse(canonical);
+        let forms = localized
+            .iter()
+            .map(|(locale, form)| {
+                let matcher = parse(form);
+                assert_eq!(
+                    matcher.capture_names(),
+                    canonical_matcher.capture_names(),
+                    "The form '{}' of '{}' must capture the same names",
+                    form,
+                    canonical
+                );
+                ((*locale).to_string(), matcher)
+            })
+            .collect();
+        self.routes.push((canonical_matcher, forms));
+        self
+    }
+
+    /// Translates the values that are captured from routes whose locale is switched, like slugs
+    /// of articles that are named differently in each language.
+    ///
+    /// Values that the function returns `None` for are kept as they are.
+    ///
+    /// # Example
+    /// ```
+    /// use yew_router::strategy::{CaptureTranslation, RouteTranslations};
+    ///
+    /// let translations = RouteTranslations::new()
+    ///     .route("/articles/{slug}", &[("de", "/artikel/{slug}")])
+    ///     .translating_captures(|capture: &CaptureTranslation| {
+    ///         match (capture.name, capture.value, capture.to) {
+    ///             ("slug", "hello-world", "de") => Some("hallo-welt".to_string()),
+    ///             _ => None,
+    ///         }
+    ///     });
+    /// assert_eq!(
+    ///     translations.switch_locale("/articles/hello-world", "en", "de"),
+    ///     "/artikel/hallo-welt"
+    /// );
+    /// ```
+    pub fn translating_captures<F>(self, translate: F) -> Self
+    where
+        F: Fn(&CaptureTranslation) -> Option<String> + 'static,
+    {
+        RouteTranslations {
+            translate_capture: Some(Rc::new(translate)),
+            ..self
+        }
+    }
+
+    /// Translates a canonical path into its form in the locale.
+    pub fn localize(&self, path: &str, locale: &str) -> String {
+        self.routes
+            .iter()
+            .filter_map(|route| translate(path, &route.0, forms_in(route, locale)[0], |_, _| None))
+            .next()
+            .unwrap_or_else(|| path.to_string())
+    }
+
+    /// Translates a path in the locale into its canonical path.
+    pub fn canonicalize(&self, path: &str, locale: &str) -> String {
+        self.routes
+            .iter()
+            .filter_map(|route| {
+                forms_in(route, locale)
+                    .into_iter()
+                    .find_map(|form| translate(path, form, &route.0, |_, _| None))
+            })
+            .next()
+            .unwrap_or_else(|| path.to_string())
+    }
+
+    /// Translates a path in one locale into its form in another, translating the values that it
+    /// captures with the function given to `translating_captures`.
+    pub fn switch_locale(&self, path: &str, from: &str, to: &str) -> String {
+        let translate_capture = |name: &str, value: &str| {
+            let capture = CaptureTranslation {
+                name,
+                value,
+                from,
+                to,
+            };
+            self.translate_capture
+                .as_ref()
+                .and_then(|translate_capture| translate_capture(&capture))
+        };
+        self.routes
+            .iter()
+            .filter_map(|route| {
+                let to_form = forms_in(route, to)[0];
+                forms_in(route, from)
+                    .into_iter()
+                    .find_map(|form| translate(path, form, to_form, translate_capture))
+            })
+            .next()
+            .unwrap_or_else(|| path.to_string())
+    }
+}
+
+impl Debug for RouteTranslations {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RouteTranslations")
+            .field("routes", &self.routes)
+            .finish()
+    }
+}
+
+/// Gets the matchers of a route's forms in the locale, or of the canonical route if it has none.
+fn forms_in<'a>(route: &'a Translation, locale: &str) -> Vec<&'a RouteMatcher> {
+    let (canonical, forms) = route;
+    let forms: Vec<&RouteMatcher> = forms
+        .iter()
+        .filter(|(form_locale, _)| form_locale == locale)
+        .map(|(_, form)| form)
+        .collect();
+    if forms.is_empty() {
+        vec![canonical]
+    } else {
+        forms
+    }
+}
+
+/// Matches the path against one matcher, and fills the captures into the other, translating
+/// them with `translate_capture`.
+fn translate<F: Fn(&str, &str) -> Option<String>>(
+    path: &str,
+    from: &RouteMatcher,
+    to: &RouteMatcher,
+    translate_capture: F,
+) -> Option<String> {
+    let (_, captures) = from.capture_route_into_map(path).ok()?;
+    to.fill(|name| {
+        let value = captures.get(name)?;
+        Some(translate_capture(name, value).unwrap_or_else(|| value.clone()))
+    })
+}
+
+/// Stores the route in the path of the url, after a locale, and translated into its language.
+///
+/// `/de/benutzer/42?tab=posts`
+///
+/// The route that the app sees is the canonical one, like `/users/42?tab=posts`, no matter which
+/// locale the url is in, so a `Switch` only has to describe the canonical routes.
+/// When the app navigates, the route is translated into the locale of the current url.
+///
+/// Urls that don't start with one of the locales are in the first, default one.
+///
+/// The current route can be moved to another locale by sending `RouteRequest::SwitchLocale` to
+/// the `RouteAgent`, which translates it, and the values it captures, through the
+/// `RouteTranslations`.
+///
+/// # Example
+/// ```
+/// use yew_router::strategy::{LocalizedStrategy, RouteTranslations, RoutingStrategy};
+///
+/// let strategy = LocalizedStrategy::new(
+///     &["en", "de"],
+///     RouteTranslations::new().route("/about", &[("de", "/ueber-uns")]),
+/// );
+/// assert_eq!(strategy.route_from_url("/de/ueber-uns", "?x=1", ""), "/about?x=1");
+/// assert_eq!(strategy.url_for_route("/about", "/de/", "", ""), "/de/ueber-uns");
+/// assert_eq!(strategy.url_for_route("/about", "/en/", "", ""), "/en/about");
+/// assert_eq!(
+///     strategy.url_in_locale("de", "/en/about", "?x=1", ""),
+///     Some("/de/ueber-uns?x=1".to_string())
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct LocalizedStrategy {
+    /// The locales, the first of which is the default.
+    locales: Vec<String>,
+    translations: RouteTranslations,
+}
+
+impl LocalizedStrategy {
+    /// Creates a strategy for the locales, the first of which is the default.
+    ///
+    /// # Panics
+    /// Panics if no locales are given.
+    pub fn new(locales: &[&str], translations: RouteTranslations) -> Self {
+        assert!(!locales.is_empty(), "At least one locale is required");
+        LocalizedStrategy {
+            locales: locales.iter().map(|locale| (*locale).to_string()).collect(),
+            translations,
+        }
+    }
+
+    /// Gets the locales, the first of which is the default.
+    pub fn locales(&self) -> impl Iterator<Item = &str> {
+        self.locales.iter().map(String::as_str)
+    }
+
+    /// Gets the locale that urls without one are in.
+    pub fn default_locale(&self) -> &str {
+        &self.locales[0]
+    }
+
+    /// Gets the translations of the routes.
+    pub fn translations(&self) -> &RouteTranslations {
+        &self.translations
+    }
+
+    /// Splits the path of a url into its locale, and the rest of the path.
+    fn split_locale<'a>(&'a self, path: &'a str) -> (&'a str, &'a str) {
+        let rest = path.trim_start_matches('/');
+        let end = rest.find('/').unwrap_or(rest.len());
+        match self.locales().find(|locale| *locale == &rest[..end]) {
+            Some(locale) => (locale, &rest[end..]),
+            None => (self.default_locale(), path),
+        }
+    }
+}
+
+impl RoutingStrategy for LocalizedStrategy {
+    fn route_from_url(&self, path: &str, query: &str, fragment: &str) -> String {
+        let (locale, path) = self.split_locale(path);
+        let path = if path.is_empty() { "/" } else { path };
+        let canonical = self.translations.canonicalize(path, locale);
+        format_route_string(&canonical, query, fragment)
+    }
+
+    fn url_for_route(&self, route: &str, path: &str, _query: &str, _fragment: &str) -> String {
+        let (locale, _) = self.split_locale(path);
+        let mut parts = RouteParts::new(route);
+        parts.path = format!(
+            "/{}{}",
+            locale,
+            self.translations.localize(&parts.path, locale)
+        );
+        parts.to_string()
+    }
+
+    fn locale(&self, path: &str, _query: &str, _fragment: &str) -> Option<String> {
+        Some(self.split_locale(path).0.to_string())
+    }
+
+    fn url_in_locale(
+        &self,
+        locale: &str,
+        path: &str,
+        query: &str,
+        fragment: &str,
+    ) -> Option<String> {
+        let to = self.locales().find(|known| *known == locale)?;
+        let (from, path) = self.split_locale(path);
+        let path = if path.is_empty() { "/" } else { path };
+        let path = self.translations.switch_locale(path, from, to);
+        Some(format!("/{}{}{}{}", to, path, query, fragment))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strategy() -> LocalizedStrategy {
+        LocalizedStrategy::new(
+            &["en", "de"],
+            RouteTranslations::new()
+                .route("/about", &[("de", "/ueber-uns")])
+                .route(
+                    "/users/{id}/posts/{*:rest}",
+                    &[("de", "/benutzer/{id}/beitraege/{*:rest}")],
+                ),
+        )
+    }
+
+    #[test]
+    fn routes_are_canonical_in_every_locale() {
+        let strategy = strategy();
+        assert_eq!(strategy.route_from_url("/en/about", "", ""), "/about");
+        assert_eq!(
+            strategy.route_from_url("/de/ueber-uns", "", "#top"),
+            "/about#top"
+        );
+        assert_eq!(
+            strategy.route_from_url("/de/benutzer/42/beitraege/1/2", "?x=1", ""),
+            "/users/42/posts/1/2?x=1"
+        );
+        assert_eq!(strategy.route_from_url("/de/kontakt", "", ""), "/kontakt");
+        assert_eq!(strategy.route_from_url("/de", "", ""), "/");
+        assert_eq!(strategy.route_from_url("/about", "", ""), "/about");
+    }
+
+    #[test]
+    fn urls_are_in_the_current_locale() {
+        let strategy = strategy();
+        assert_eq!(
+            strategy.url_for_route("/users/42/posts/7?x=1", "/de/kontakt", "", ""),
+            "/de/benutzer/42/beitraege/7?x=1"
+        );
+        assert_eq!(strategy.url_for_route("/about", "/en", "", ""), "/en/about");
+        assert_eq!(strategy.url_for_route("/about", "/", "", ""), "/en/about");
+        assert_eq!(strategy.url_for_route("/", "/de/", "", ""), "/de/");
+    }
+
+    #[test]
+    fn locale_comes_from_the_first_segment() {
+        let strategy = strategy();
+        assert_eq!(
+            strategy.locale("/de/ueber-uns", "", ""),
+            Some("de".to_string())
+        );
+        assert_eq!(strategy.locale("/fr/about", "", ""), Some("en".to_string()));
+        assert_eq!(strategy.locale("/denmark", "", ""), Some("en".to_string()));
+    }
+
+    #[test]
+    fn switching_locales_translates_captures() {
+        let strategy = LocalizedStrategy::new(
+            &["en", "de", "fr"],
+            RouteTranslations::new()
+                .route("/articles/{slug}", &[("de", "/artikel/{slug}")])
+                .translating_captures(|capture: &CaptureTranslation| {
+                    match (capture.value, capture.from, capture.to) {
+                        ("hello", "en", "de") => Some("hallo".to_string()),
+                        ("hallo", "de", _) => Some("hello".to_string()),
+                        _ => None,
+                    }
+                }),
+        );
+        assert_eq!(
+            strategy.url_in_locale("de", "/en/articles/hello", "?x=1", "#top"),
+            Some("/de/artikel/hallo?x=1#top".to_string())
+        );
+        assert_eq!(
+            strategy.url_in_locale("fr", "/de/artikel/hallo", "", ""),
+            Some("/fr/articles/hello".to_string())
+        );
+        assert_eq!(
+            strategy.url_in_locale("de", "/contact", "", ""),
+            Some("/de/contact".to_string())
+        );
+        assert_eq!(strategy.url_in_locale("es", "/en/", "", ""), None);
+    }
+}
@@ -1,12 +1,25 @@
 //! Parses routes into enums or structs.
-use crate::{route::Route, RouteState};
-use std::fmt::Write;
+use crate::{
+    debug::SwitchTimer,
+    matcher::GrammarVersion,
+    route::{Route, RouteEncoding, RouteState},
+};
+use std::{cell::RefCell, error::Error, fmt, fmt::Write};
+
+mod flags;
+pub use flags::FeatureFlags;
 
 /// Derivable routing trait that allows instances of implementors to be constructed from Routes.
 ///
 /// # Note
 /// Don't try to implement this yourself, rely on the derive macro.
 ///
+/// Besides the implementations for strings and numbers, Switch is implemented for `Option`,
+/// `Result`, `Box`, `Vec`, and tuples of up to six elements wrapping other implementors, so they
+/// can be used as fields without declaring a new enum.
+/// `Result` tries to produce an `Ok` before an `Err`, and the elements of a tuple are separated by
+/// `/`, with the last element getting the rest of the route.
+///
 /// # Example
 /// ```
 /// use yew_router::{route::Route, Switch};
@@ -44,15 +57,111 @@ use std::fmt::Write;
 pub trait Switch: Sized {
     /// Based on a route, possibly produce an itself.
     fn switch<T: RouteState>(route: Route<T>) -> Option<Self> {
-        Self::from_route_part(route).0
+        Self::switch_with_flags(route, &FeatureFlags::all())
+    }
+
+    /// Based on a route, possibly produce an itself, skipping the routes gated behind the feature
+    /// flags that `flags` disable.
+    fn switch_with_flags<T: RouteState>(route: Route<T>, flags: &FeatureFlags) -> Option<Self> {
+        let timer = SwitchTimer::start(&route.route);
+        let switched = Self::from_route_part_with_flags(route, flags).0;
+        timer.finish(switched.is_some());
+        switched
+    }
+
+    /// Based on a route, produce an itself, or describe why it couldn't be produced.
+    ///
+    /// This distinguishes routes that don't match at all from routes that match, but contain a
+    /// section that can't be converted into its field, like `abc` for a `u32`, so the latter can
+    /// be shown a "bad request" page instead of a "not found" page.
+    ///
+    /// # Example
+    /// ```
+    /// use yew_router::{route::Route, switch::SwitchError, Switch};
+    /// #[derive(Debug, Switch, PartialEq)]
+    /// enum AppRoute {
+    ///     #[to = "/users/{id}"]
+    ///     User { id: u32 },
+    /// }
+    ///
+    /// assert_eq!(
+    ///     AppRoute::try_switch(Route::<()>::from("/users/abc")),
+    ///     Err(SwitchError::InvalidField {
+    ///         item: "AppRoute::User",
+    ///         field: "id",
+    ///         value: Some("abc".to_string()),
+    ///     })
+    /// );
+    /// assert_eq!(
+    ///     AppRoute::try_switch(Route::<()>::from("/posts/1")),
+    ///     Err(SwitchError::NoMatch)
+    /// );
+    /// ```
+    fn try_switch<T: RouteState>(route: Route<T>) -> Result<Self, SwitchError> {
+        Self::try_switch_with_flags(route, &FeatureFlags::all())
+    }
+
+    /// Based on a route, produce an itself, like `try_switch`, skipping the routes gated behind
+    /// the feature flags that `flags` disable.
+    fn try_switch_with_flags<T: RouteState>(
+        route: Route<T>,
+        flags: &FeatureFlags,
+    ) -> Result<Self, SwitchError> {
+        FIELD_REJECTION.with(|rejection| *rejection.borrow_mut() = None);
+        match Self::switch_with_flags(route, flags) {
+            Some(switched) => Ok(switched),
+            None => Err(FIELD_REJECTION
+                .with(|rejection| rejection.borrow_mut().take())
+                .unwrap_or(SwitchError::NoMatch)),
+        }
     }
 
     /// Get self from a part of the state
     fn from_route_part<T: RouteState>(part: Route<T>) -> (Option<Self>, Option<T>);
 
+    /// Get self from a part of the state, skipping the routes gated behind the feature flags
+    /// that `flags` disable.
+    ///
+    /// It defaults to `from_route_part`, which enables every flag.
+    fn from_route_part_with_flags<T: RouteState>(
+        part: Route<T>,
+        flags: &FeatureFlags,
+    ) -> (Option<Self>, Option<T>) {
+        let _ = flags;
+        Self::from_route_part(part)
+    }
+
     /// Build part of a route from itself.
     fn build_route_section<T>(self, route: &mut String) -> Option<T>;
 
+    /// Build part of a route from itself, percent-encoding the values it captures with
+    /// `encoding`, or writing them as they are if there is none.
+    ///
+    /// This is used by `RouteEncoding::build_route`.
+    /// It defaults to `build_route_section`, which doesn't encode anything.
+    fn build_encoded_route_section<T>(
+        self,
+        route: &mut String,
+        encoding: Option<&RouteEncoding>,
+    ) -> Option<T> {
+        let _ = encoding;
+        self.build_route_section(route)
+    }
+
+    /// Build part of a route from itself, like `build_encoded_route_section`, but fail if a value
+    /// would be built into a route that doesn't switch back to it.
+    ///
+    /// The derive fails for the extension of a file name, like `{ext}` in
+    /// `/files/{name:stem}.{ext}`, that contains a `.`.
+    /// It defaults to `build_encoded_route_section`, which never fails.
+    fn try_build_route_section<T>(
+        self,
+        route: &mut String,
+        encoding: Option<&RouteEncoding>,
+    ) -> Result<Option<T>, SwitchError> {
+        Ok(self.build_encoded_route_section(route, encoding))
+    }
+
     /// Called when the key (the named capture group) can't be located. Instead of failing outright,
     /// a default item can be provided instead.
     ///
@@ -64,6 +173,431 @@ pub trait Switch: Sized {
     fn key_not_available() -> Option<Self> {
         None
     }
+
+    /// Describes the routes that this can be created from.
+    ///
+    /// Derived implementations list every variant of an enum, or the struct itself, in the order
+    /// that they are tried.
+    /// This can be used to generate things like sitemaps from the same source of truth as the
+    /// router.
+    ///
+    /// Types that aren't derived don't describe any routes.
+    ///
+    /// # Example
+    /// ```
+    /// use yew_router::Switch;
+    /// #[derive(Switch)]
+    /// enum AppRoute {
+    ///     #[to = "/users/{id}"]
+    ///     User { id: usize },
+    ///     #[to = "/about"]
+    ///     About,
+    /// }
+    ///
+    /// let routes = AppRoute::routes();
+    /// assert_eq!(routes[0].name, "User");
+    /// assert_eq!(routes[0].matcher, "/users/{id}");
+    /// assert_eq!(routes[0].captures[0].name, Some("id"));
+    /// assert_eq!(routes[0].captures[0].ty, Some("usize"));
+    /// assert_eq!(routes[1].matcher, "/about");
+    /// assert_eq!(routes[1].route, Some("/about"));
+    /// ```
+    fn routes() -> Vec<RouteInfo> {
+        Vec::new()
+    }
+
+    /// Gets the metadata attached to the route with `#[meta(...)]` attributes.
+    ///
+    /// This allows things like guards, breadcrumbs, and page titles to be declared next to the
+    /// route, instead of in a separate match statement.
+    ///
+    /// Types that aren't derived don't have any metadata.
+    ///
+    /// # Example
+    /// ```
+    /// use yew_router::Switch;
+    /// #[derive(Switch)]
+    /// enum AppRoute {
+    ///     #[to = "/settings"]
+    ///     #[meta(title = "Settings", requires_auth)]
+    ///     Settings,
+    ///     #[to = "/"]
+    ///     Home,
+    /// }
+    ///
+    /// let meta = AppRoute::Settings.meta();
+    /// assert_eq!(meta.get("title"), Some("Settings"));
+    /// assert!(meta.contains("requires_auth"));
+    /// assert!(AppRoute::Home.meta().is_empty());
+    /// ```
+    fn meta(&self) -> RouteMeta {
+        RouteMeta::default()
+    }
+
+    /// Gets the metadata of this route, followed by the metadata of the routes nested inside of
+    /// it.
+    ///
+    /// Derived implementations treat a field that captures the rest of the route, with `#[rest]`
+    /// or a trailing `{*}`, as a nested route.
+    fn nested_meta(&self) -> Vec<RouteMeta> {
+        vec![self.meta()]
+    }
+
+    /// Gets the part of the route that is delegated to the route nested inside of this one, if it
+    /// has one.
+    ///
+    /// Like `nested_meta`, derived implementations treat a field that captures the rest of the
+    /// route as the nested route.
+    ///
+    /// # Example
+    /// ```
+    /// use yew_router::{route::Route, Switch};
+    /// #[derive(Switch)]
+    /// enum AppRoute {
+    ///     #[to = "/settings{*}"]
+    ///     Settings(SettingsRoute),
+    ///     #[to = "/"]
+    ///     Home,
+    /// }
+    ///
+    /// #[derive(Switch)]
+    /// enum SettingsRoute {
+    ///     #[to = "/profile"]
+    ///     Profile,
+    /// }
+    ///
+    /// let settings = AppRoute::switch(Route::<()>::from("/settings/profile")).unwrap();
+    /// assert_eq!(settings.nested_route(), Some("/profile".to_string()));
+    /// assert_eq!(AppRoute::Home.nested_route(), None);
+    /// ```
+    fn nested_route(self) -> Option<String> {
+        None
+    }
+
+    /// Builds the route of this, unless it, or a route nested inside of it, is gated behind a
+    /// feature flag that `flags` disable, or contains a value that `try_build_route_section`
+    /// rejects.
+    ///
+    /// Converting it into a `Route` with `From` builds the route regardless.
+    fn try_build_route<T>(self, flags: &FeatureFlags) -> Result<Route<T>, SwitchError> {
+        let disabled_flag = self
+            .nested_meta()
+            .iter()
+            .filter_map(|meta| meta.get("flag"))
+            .find(|flag| !flags.is_enabled(flag));
+        match disabled_flag {
+            Some(flag) => Err(SwitchError::Disabled { flag }),
+            None => {
+                let mut route = String::new();
+                let state = self.try_build_route_section(&mut route, None)?;
+                Ok(Route { route, state })
+            }
+        }
+    }
+}
+
+/// The reason why a route couldn't be switched to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SwitchError {
+    /// No struct or enum variant matched the route.
+    NoMatch,
+    /// A route matcher matched, but a section that it captured couldn't be converted into the
+    /// corresponding field, and no later variant matched instead.
+    ///
+    /// If several fields were rejected, this describes the first one.
+    InvalidField {
+        /// The struct or enum variant that was rejected, like `AppRoute::User`.
+        item: &'static str,
+        /// The name of the field, or its index for tuple structs and variants.
+        field: &'static str,
+        /// The section of the route that couldn't be converted.
+        ///
+        /// This is `None` if the section wasn't present in the route at all.
+        value: Option<String>,
+    },
+    /// The route is gated behind a feature flag, with `#[meta(flag = "name")]`, that is disabled.
+    Disabled {
+        /// The name of the flag.
+        flag: &'static str,
+    },
+    /// A field's value can't be built into a route that switches back to it, like the extension
+    /// of a file name that contains a `.`.
+    Unbuildable {
+        /// The struct or enum variant that was built, like `AppRoute::File`.
+        item: &'static str,
+        /// The name of the field, or its index for tuple structs and variants.
+        field: &'static str,
+        /// The value that the field was built into.
+        value: String,
+    },
+}
+
+impl fmt::Display for SwitchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SwitchError::NoMatch => f.write_str("no route matched"),
+            SwitchError::InvalidField {
+                item,
+                field,
+                value: Some(value),
+            } => write!(
+                f,
+                "`{}` matched, but '{}' isn't a valid value for its field `{}`",
+                item, value, field
+            ),
+            SwitchError::InvalidField {
+                item,
+                field,
+                value: None,
+            } => write!(
+                f,
+                "`{}` matched, but the route doesn't contain its field `{}`",
+                item, field
+            ),
+            SwitchError::Disabled { flag } => {
+                write!(f, "the route is disabled by the feature flag '{}'", flag)
+            }
+            SwitchError::Unbuildable { item, field, value } => write!(
+                f,
+                "'{}' can't be built into the route of `{}` for its field `{}`",
+                value, item, field
+            ),
+        }
+    }
+}
+
+impl Error for SwitchError {}
+
+/// Checks the value of a field while a route is matched, for fields with `#[switch(validate)]`.
+///
+/// A value that isn't valid rejects the struct or enum variant like a section that couldn't be
+/// converted does, so the next variant is tried, and `Switch::try_switch` reports the field.
+///
+/// # Example
+/// ```
+/// use yew_router::{route::Route, switch::Validate, Switch};
+///
+/// #[derive(Debug, Switch, PartialEq)]
+/// #[to = "{}"]
+/// struct Month(u8);
+///
+/// impl Validate for Month {
+///     fn validate(&self) -> bool {
+///         self.0 >= 1 && self.0 <= 12
+///     }
+/// }
+///
+/// #[derive(Debug, Switch, PartialEq)]
+/// enum AppRoute {
+///     #[to = "/archive/{month}"]
+///     Archive {
+///         #[switch(validate)]
+///         month: Month,
+///     },
+///     #[to = "/archive/{slug}"]
+///     Article { slug: String },
+/// }
+///
+/// assert_eq!(
+///     AppRoute::switch(Route::<()>::from("/archive/5")),
+///     Some(AppRoute::Archive { month: Month(5) })
+/// );
+/// assert_eq!(
+///     AppRoute::switch(Route::<()>::from("/archive/13")),
+///     Some(AppRoute::Article {
+///         slug: "13".to_string()
+///     })
+/// );
+/// ```
+pub trait Validate {
+    /// Determines if the value is acceptable for the route.
+    fn validate(&self) -> bool;
+}
+
+thread_local! {
+    /// The first field that was rejected since `Switch::try_switch` started.
+    static FIELD_REJECTION: RefCell<Option<SwitchError>> = RefCell::new(None);
+}
+
+/// Notes that a route matched, but a field couldn't be created from what was captured.
+///
+/// This is called by the code generated by the `Switch` derive.
+#[doc(hidden)]
+pub fn reject_field(item: &'static str, field: &'static str, value: Option<String>) {
+    crate::debug::log_field_rejected(item, field);
+    FIELD_REJECTION.with(|rejection| {
+        let mut rejection = rejection.borrow_mut();
+        if rejection.is_none() {
+            *rejection = Some(SwitchError::InvalidField { item, field, value });
+        }
+    });
+}
+
+/// Builds a breadcrumb trail for a route, from the root to the route itself.
+///
+/// Each ancestor of the route's path, like `/settings` for `/settings/profile`, that can be
+/// switched to becomes a crumb, titled with the `title` in the metadata of its innermost nested
+/// route that has one.
+/// The routes that it is nested in become crumbs too, if they have a `title`, so a layout like
+/// `/settings{*}` has a crumb even if `/settings` itself can't be switched to.
+/// Crumbs with the same title are merged, keeping the longest route they were the innermost title
+/// of.
+///
+/// # Example
+/// ```
+/// use yew_router::{switch::breadcrumbs, Switch};
+/// #[derive(Switch)]
+/// enum AppRoute {
+///     #[to = "/settings{*}"]
+///     #[meta(title = "Settings")]
+///     Settings(SettingsRoute),
+///     #[to = "/"]
+///     #[meta(title = "Home")]
+///     Home,
+/// }
+///
+/// #[derive(Switch)]
+/// enum SettingsRoute {
+///     #[to = "/profile"]
+///     #[meta(title = "Profile")]
+///     Profile,
+///     #[end]
+///     Index,
+/// }
+///
+/// let trail = breadcrumbs::<AppRoute, ()>("/settings/profile");
+/// let titles: Vec<&str> = trail.iter().map(|(title, _)| *title).collect();
+/// assert_eq!(titles, vec!["Home", "Settings", "Profile"]);
+/// assert_eq!(trail[1].1.route, "/settings");
+/// ```
+pub fn breadcrumbs<SW: Switch, T>(route: &str) -> Vec<(&'static str, Route<T>)> {
+    let path_end = route.find(|c| c == '?' || c == '#').unwrap_or(route.len());
+    let mut ancestors: Vec<&str> = route[..path_end]
+        .match_indices('/')
+        .map(|(index, _)| if index == 0 { "/" } else { &route[..index] })
+        .collect();
+    ancestors.push(route);
+    ancestors.dedup();
+
+    let mut trail: Vec<(&'static str, Route<T>)> = Vec::new();
+    for ancestor in ancestors {
+        let switch = match SW::switch(Route::<()>::from(ancestor)) {
+            Some(switch) => switch,
+            None => continue,
+        };
+        let titles: Vec<(usize, &'static str)> = switch
+            .nested_meta()
+            .iter()
+            .enumerate()
+            .filter_map(|(depth, meta)| meta.get("title").map(|title| (depth, title)))
+            .collect();
+        // The outermost route ends where the part that is delegated to its nested route starts.
+        let outermost = switch
+            .nested_route()
+            .filter(|nested| ancestor.len() > nested.len() && ancestor.ends_with(nested.as_str()))
+            .map_or(ancestor, |nested| {
+                &ancestor[..ancestor.len() - nested.len()]
+            });
+        let innermost = titles.last().map_or(0, |(depth, _)| *depth);
+        for (depth, title) in titles {
+            let crumb = trail
+                .iter_mut()
+                .find(|(crumb_title, _)| *crumb_title == title);
+            match crumb {
+                Some((_, crumb_route)) if depth == innermost => {
+                    *crumb_route = Route::from(ancestor);
+                }
+                Some(_) => {}
+                None => {
+                    // Only the routes of the outermost and innermost levels are known, so the
+                    // levels between them link to the ancestor.
+                    let crumb_route = if depth == 0 && depth != innermost {
+                        outermost
+                    } else {
+                        ancestor
+                    };
+                    trail.push((title, Route::from(crumb_route)));
+                }
+            }
+        }
+    }
+    trail
+}
+
+/// Static metadata attached to a route with `#[meta(...)]` attributes.
+///
+/// Entries are either flags, like `requires_auth`, or keys with a string value, like
+/// `title = "Settings"`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct RouteMeta(&'static [(&'static str, Option<&'static str>)]);
+
+impl RouteMeta {
+    /// Creates metadata from its entries.
+    ///
+    /// Flags don't have a value.
+    pub fn new(entries: &'static [(&'static str, Option<&'static str>)]) -> Self {
+        RouteMeta(entries)
+    }
+
+    /// Gets the value of an entry.
+    ///
+    /// This is `None` for flags, as well as for keys that aren't present.
+    pub fn get(&self, key: &str) -> Option<&'static str> {
+        self.0
+            .iter()
+            .find(|(entry_key, _)| *entry_key == key)
+            .and_then(|(_, value)| *value)
+    }
+
+    /// Determines if an entry is present, either as a flag or as a key with a value.
+    pub fn contains(&self, key: &str) -> bool {
+        self.0.iter().any(|(entry_key, _)| *entry_key == key)
+    }
+
+    /// Iterates over the entries in the order they were declared.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, Option<&'static str>)> {
+        self.0.iter().cloned()
+    }
+
+    /// Determines if there are no entries.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Describes a route that a `Switch` implementor can be created from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteInfo {
+    /// Name of the enum variant or struct that is created by the route.
+    pub name: &'static str,
+    /// The route matcher string, assembled from the route attributes.
+    pub matcher: &'static str,
+    /// The sections of the route that are captured, in the order they appear in the matcher.
+    pub captures: Vec<CaptureInfo>,
+    /// The only route that the matcher matches, if it doesn't capture anything.
+    ///
+    /// This is the route that the struct or variant is turned back into.
+    pub route: Option<&'static str>,
+    /// The version of the grammar that the matcher string is written in.
+    pub grammar_version: GrammarVersion,
+    /// The metadata attached with `#[meta(...)]` attributes.
+    pub meta: RouteMeta,
+}
+
+/// Describes a section captured by a route matcher.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CaptureInfo {
+    /// The name of the capture.
+    ///
+    /// This is only present for structs and variants with named fields, as the captures of tuple
+    /// structs and variants are assigned in order.
+    pub name: Option<&'static str>,
+    /// The type of the field that the capture is converted into, as `stringify!` renders it.
+    ///
+    /// Depending on the compiler, there may be spaces between its tokens, like `Option < String >`.
+    ///
+    /// This is `None` if the captured section isn't stored in any field.
+    pub ty: Option<&'static str>,
 }
 
 /// Wrapper that requires that an implementor of Switch must start with a `/`.
@@ -76,12 +610,19 @@ pub trait Switch: Sized {
 pub struct LeadingSlash<T>(pub T);
 impl<U: Switch> Switch for LeadingSlash<U> {
     fn from_route_part<T: RouteState>(part: Route<T>) -> (Option<Self>, Option<T>) {
+        Self::from_route_part_with_flags(part, &FeatureFlags::all())
+    }
+
+    fn from_route_part_with_flags<T: RouteState>(
+        part: Route<T>,
+        flags: &FeatureFlags,
+    ) -> (Option<Self>, Option<T>) {
         if part.route.starts_with('/') {
             let route = Route {
                 route: part.route[1..].to_string(),
                 state: part.state,
             };
-            let (inner, state) = U::from_route_part(route);
+            let (inner, state) = U::from_route_part_with_flags(route, flags);
             (inner.map(LeadingSlash), state)
         } else {
             (None, None)
@@ -89,15 +630,47 @@ impl<U: Switch> Switch for LeadingSlash<U> {
     }
 
     fn build_route_section<T>(self, route: &mut String) -> Option<T> {
+        self.build_encoded_route_section(route, None)
+    }
+
+    fn build_encoded_route_section<T>(
+        self,
+        route: &mut String,
+        encoding: Option<&RouteEncoding>,
+    ) -> Option<T> {
         write!(route, "/").ok()?;
-        self.0.build_route_section(route)
+        self.0.build_encoded_route_section(route, encoding)
+    }
+
+    fn try_build_route_section<T>(
+        self,
+        route: &mut String,
+        encoding: Option<&RouteEncoding>,
+    ) -> Result<Option<T>, SwitchError> {
+        route.push('/');
+        self.0.try_build_route_section(route, encoding)
+    }
+
+    fn meta(&self) -> RouteMeta {
+        self.0.meta()
+    }
+
+    fn nested_meta(&self) -> Vec<RouteMeta> {
+        self.0.nested_meta()
     }
 }
 
 impl<U: Switch> Switch for Option<U> {
     /// Option is very permissive in what is allowed.
     fn from_route_part<T: RouteState>(part: Route<T>) -> (Option<Self>, Option<T>) {
-        let (inner, inner_state) = U::from_route_part(part);
+        Self::from_route_part_with_flags(part, &FeatureFlags::all())
+    }
+
+    fn from_route_part_with_flags<T: RouteState>(
+        part: Route<T>,
+        flags: &FeatureFlags,
+    ) -> (Option<Self>, Option<T>) {
+        let (inner, inner_state) = U::from_route_part_with_flags(part, flags);
         if inner.is_some() {
             (Some(inner), inner_state)
         } else {
@@ -107,26 +680,326 @@ impl<U: Switch> Switch for Option<U> {
     }
 
     fn build_route_section<T>(self, route: &mut String) -> Option<T> {
+        self.build_encoded_route_section(route, None)
+    }
+
+    fn build_encoded_route_section<T>(
+        self,
+        route: &mut String,
+        encoding: Option<&RouteEncoding>,
+    ) -> Option<T> {
         if let Some(inner) = self {
-            inner.build_route_section(route)
+            inner.build_encoded_route_section(route, encoding)
         } else {
             None
         }
     }
 
+    fn try_build_route_section<T>(
+        self,
+        route: &mut String,
+        encoding: Option<&RouteEncoding>,
+    ) -> Result<Option<T>, SwitchError> {
+        match self {
+            Some(inner) => inner.try_build_route_section(route, encoding),
+            None => Ok(None),
+        }
+    }
+
     fn key_not_available() -> Option<Self> {
         Some(None)
     }
+
+    fn meta(&self) -> RouteMeta {
+        self.as_ref().map(U::meta).unwrap_or_default()
+    }
+
+    fn nested_meta(&self) -> Vec<RouteMeta> {
+        self.as_ref().map(U::nested_meta).unwrap_or_default()
+    }
 }
 
+impl<U: Switch, E: Switch> Switch for Result<U, E> {
+    /// Tries to produce an `Ok`, and an `Err` if that doesn't work.
+    fn from_route_part<T: RouteState>(part: Route<T>) -> (Option<Self>, Option<T>) {
+        Self::from_route_part_with_flags(part, &FeatureFlags::all())
+    }
+
+    fn from_route_part_with_flags<T: RouteState>(
+        part: Route<T>,
+        flags: &FeatureFlags,
+    ) -> (Option<Self>, Option<T>) {
+        let route = part.route.clone();
+        let (inner, inner_state) = U::from_route_part_with_flags(part, flags);
+        if let Some(inner) = inner {
+            return (Some(Ok(inner)), inner_state);
+        }
+        let (inner, inner_state) = E::from_route_part_with_flags(
+            Route {
+                route,
+                state: inner_state,
+            },
+            flags,
+        );
+        (inner.map(Err), inner_state)
+    }
+
+    fn build_route_section<T>(self, route: &mut String) -> Option<T> {
+        self.build_encoded_route_section(route, None)
+    }
+
+    fn build_encoded_route_section<T>(
+        self,
+        route: &mut String,
+        encoding: Option<&RouteEncoding>,
+    ) -> Option<T> {
+        match self {
+            Ok(inner) => inner.build_encoded_route_section(route, encoding),
+            Err(inner) => inner.build_encoded_route_section(route, encoding),
+        }
+    }
+
+    fn try_build_route_section<T>(
+        self,
+        route: &mut String,
+        encoding: Option<&RouteEncoding>,
+    ) -> Result<Option<T>, SwitchError> {
+        match self {
+            Ok(inner) => inner.try_build_route_section(route, encoding),
+            Err(inner) => inner.try_build_route_section(route, encoding),
+        }
+    }
+
+    fn key_not_available() -> Option<Self> {
+        U::key_not_available()
+            .map(Ok)
+            .or_else(|| E::key_not_available().map(Err))
+    }
+
+    fn meta(&self) -> RouteMeta {
+        match self {
+            Ok(inner) => inner.meta(),
+            Err(inner) => inner.meta(),
+        }
+    }
+
+    fn nested_meta(&self) -> Vec<RouteMeta> {
+        match self {
+            Ok(inner) => inner.nested_meta(),
+            Err(inner) => inner.nested_meta(),
+        }
+    }
+}
+
+impl<U: Switch> Switch for Box<U> {
+    fn from_route_part<T: RouteState>(part: Route<T>) -> (Option<Self>, Option<T>) {
+        Self::from_route_part_with_flags(part, &FeatureFlags::all())
+    }
+
+    fn from_route_part_with_flags<T: RouteState>(
+        part: Route<T>,
+        flags: &FeatureFlags,
+    ) -> (Option<Self>, Option<T>) {
+        let (inner, inner_state) = U::from_route_part_with_flags(part, flags);
+        (inner.map(Box::new), inner_state)
+    }
+
+    fn build_route_section<T>(self, route: &mut String) -> Option<T> {
+        (*self).build_route_section(route)
+    }
+
+    fn build_encoded_route_section<T>(
+        self,
+        route: &mut String,
+        encoding: Option<&RouteEncoding>,
+    ) -> Option<T> {
+        (*self).build_encoded_route_section(route, encoding)
+    }
+
+    fn try_build_route_section<T>(
+        self,
+        route: &mut String,
+        encoding: Option<&RouteEncoding>,
+    ) -> Result<Option<T>, SwitchError> {
+        (*self).try_build_route_section(route, encoding)
+    }
+
+    fn key_not_available() -> Option<Self> {
+        U::key_not_available().map(Box::new)
+    }
+
+    fn routes() -> Vec<RouteInfo> {
+        U::routes()
+    }
+
+    fn meta(&self) -> RouteMeta {
+        (**self).meta()
+    }
+
+    fn nested_meta(&self) -> Vec<RouteMeta> {
+        (**self).nested_meta()
+    }
+}
+
+/// The elements are separated by `&`, which is how repeated query parameters, like
+/// `?tag={*:tags}`, are captured.
+///
+/// The `&`s within the elements are escaped as `%26`, as they are in the query of a route, and
+/// when a route that is split into its parts is matched.
+impl<U: Switch> Switch for Vec<U> {
+    fn from_route_part<T: RouteState>(part: Route<T>) -> (Option<Self>, Option<T>) {
+        Self::from_route_part_with_flags(part, &FeatureFlags::all())
+    }
+
+    fn from_route_part_with_flags<T: RouteState>(
+        part: Route<T>,
+        flags: &FeatureFlags,
+    ) -> (Option<Self>, Option<T>) {
+        let mut state = part.state;
+        let mut elements = Vec::new();
+        if part.route.is_empty() {
+            return (Some(elements), state);
+        }
+        for section in part.route.split('&') {
+            let (element, element_state) = U::from_route_part_with_flags(
+                Route {
+                    route: section.replace("%26", "&"),
+                    state,
+                },
+                flags,
+            );
+            state = element_state;
+            match element {
+                Some(element) => elements.push(element),
+                None => return (None, state),
+            }
+        }
+        (Some(elements), state)
+    }
+
+    fn build_route_section<T>(self, route: &mut String) -> Option<T> {
+        self.build_encoded_route_section(route, None)
+    }
+
+    fn build_encoded_route_section<T>(
+        self,
+        route: &mut String,
+        encoding: Option<&RouteEncoding>,
+    ) -> Option<T> {
+        let mut state = None;
+        for (index, element) in self.into_iter().enumerate() {
+            if index > 0 {
+                route.push('&');
+            }
+            // The element is built onto the route, so it is encoded for the section it is in.
+            let start = route.len();
+            state = state.or(element.build_encoded_route_section(route, encoding));
+            let section = route.split_off(start);
+            route.push_str(&section.replace('&', "%26"));
+        }
+        state
+    }
+
+    fn key_not_available() -> Option<Self> {
+        Some(Vec::new())
+    }
+}
+
+/// Implements Switch for tuples, which are made up of one section of the route per element,
+/// separated by `/`.
+///
+/// The last element gets the rest of the route, so it can contain more sections.
+/// Elements that don't have a section use `Switch::key_not_available`.
+macro_rules! impl_switch_for_tuple {
+    ($count: expr; $Head: ident $head: ident $(, $Tail: ident $tail: ident)*) => {
+        impl<$Head: Switch $(, $Tail: Switch)*> Switch for ($Head, $($Tail),*) {
+            fn from_route_part<T: RouteState>(part: Route<T>) -> (Option<Self>, Option<T>) {
+                Self::from_route_part_with_flags(part, &FeatureFlags::all())
+            }
+
+            fn from_route_part_with_flags<T: RouteState>(
+                part: Route<T>,
+                flags: &FeatureFlags,
+            ) -> (Option<Self>, Option<T>) {
+                let mut state = part.state;
+                let mut sections = part.route.splitn($count, '/');
+                let $head = impl_switch_for_tuple!(@element $Head, sections, state, flags);
+                $(
+                let $tail = impl_switch_for_tuple!(@element $Tail, sections, state, flags);
+                )*
+                (Some(($head, $($tail),*)), state)
+            }
+
+            fn build_route_section<T>(self, route: &mut String) -> Option<T> {
+                self.build_encoded_route_section(route, None)
+            }
+
+            fn build_encoded_route_section<T>(
+                self,
+                route: &mut String,
+                encoding: Option<&RouteEncoding>,
+            ) -> Option<T> {
+                let ($head, $($tail),*) = self;
+                let mut state = $head.build_encoded_route_section(route, encoding);
+                $(
+                route.push('/');
+                state = state.or($tail.build_encoded_route_section(route, encoding));
+                )*
+                state
+            }
+
+            fn key_not_available() -> Option<Self> {
+                Some(($Head::key_not_available()?, $($Tail::key_not_available()?),*))
+            }
+        }
+    };
+    (@element $Element: ident, $sections: ident, $state: ident, $flags: ident) => {
+        match $sections.next() {
+            Some(section) => {
+                let (element, element_state) = $Element::from_route_part_with_flags(
+                    Route {
+                        route: section.to_string(),
+                        state: $state,
+                    },
+                    $flags,
+                );
+                match element {
+                    Some(element) => {
+                        $state = element_state;
+                        element
+                    }
+                    None => return (None, element_state),
+                }
+            }
+            None => match $Element::key_not_available() {
+                Some(element) => element,
+                None => return (None, $state),
+            },
+        }
+    };
+}
+
+impl_switch_for_tuple!(2; A a, B b);
+impl_switch_for_tuple!(3; A a, B b, C c);
+impl_switch_for_tuple!(4; A a, B b, C c, D d);
+impl_switch_for_tuple!(5; A a, B b, C c, D d, E e);
+impl_switch_for_tuple!(6; A a, B b, C c, D d, E e, F f);
+
 /// Allows a section to match if its contents are entirely missing, or starts with a '/'.
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub struct AllowMissing<T: std::fmt::Debug>(pub Option<T>);
 impl<U: Switch + std::fmt::Debug> Switch for AllowMissing<U> {
     fn from_route_part<T: RouteState>(part: Route<T>) -> (Option<Self>, Option<T>) {
+        Self::from_route_part_with_flags(part, &FeatureFlags::all())
+    }
+
+    fn from_route_part_with_flags<T: RouteState>(
+        part: Route<T>,
+        flags: &FeatureFlags,
+    ) -> (Option<Self>, Option<T>) {
         println!("{:?}", &part.route);
         let route = part.route.clone();
-        let (inner, inner_state) = U::from_route_part(part);
+        let (inner, inner_state) = U::from_route_part_with_flags(part, flags);
         println!("{:?}", &inner);
         if inner.is_some() {
             (Some(AllowMissing(inner)), inner_state)
@@ -142,12 +1015,31 @@ impl<U: Switch + std::fmt::Debug> Switch for AllowMissing<U> {
     }
 
     fn build_route_section<T>(self, route: &mut String) -> Option<T> {
+        self.build_encoded_route_section(route, None)
+    }
+
+    fn build_encoded_route_section<T>(
+        self,
+        route: &mut String,
+        encoding: Option<&RouteEncoding>,
+    ) -> Option<T> {
         if let AllowMissing(Some(inner)) = self {
-            inner.build_route_section(route)
+            inner.build_encoded_route_section(route, encoding)
         } else {
             None
         }
     }
+
+    fn try_build_route_section<T>(
+        self,
+        route: &mut String,
+        encoding: Option<&RouteEncoding>,
+    ) -> Result<Option<T>, SwitchError> {
+        match self {
+            AllowMissing(Some(inner)) => inner.try_build_route_section(route, encoding),
+            AllowMissing(None) => Ok(None),
+        }
+    }
 }
 
 macro_rules! impl_switch_for_from_to_str {
@@ -162,7 +1054,15 @@ macro_rules! impl_switch_for_from_to_str {
             }
 
             fn build_route_section<T>(self, f: &mut String) -> Option<T> {
-                write!(f, "{}", self).expect("Writing to string should never fail.");
+                self.build_encoded_route_section(f, None)
+            }
+
+            fn build_encoded_route_section<T>(
+                self,
+                f: &mut String,
+                encoding: Option<&RouteEncoding>,
+            ) -> Option<T> {
+                RouteEncoding::write_capture(f, &self.to_string(), encoding);
                 None
             }
         }
@@ -265,4 +1165,62 @@ mod test {
         });
         assert_eq!(s, Some(Some("".to_string())))
     }
+
+    #[test]
+    fn result_falls_back_to_err() {
+        let ok = Result::<u32, String>::switch::<()>(Route::from("42"));
+        assert_eq!(ok, Some(Ok(42)));
+        let err = Result::<u32, String>::switch::<()>(Route::from("abc"));
+        assert_eq!(err, Some(Err("abc".to_string())));
+        let none = Result::<u32, u16>::switch::<()>(Route::from("abc"));
+        assert_eq!(none, None);
+    }
+
+    #[test]
+    fn box_round_trips() {
+        let boxed = Box::<u32>::switch::<()>(Route::from("42"));
+        assert_eq!(boxed, Some(Box::new(42)));
+        let route: Route<()> = Route::from(Box::new(42u32));
+        assert_eq!(route.route, "42");
+    }
+
+    #[test]
+    fn tuple_elements_are_separated_by_slashes() {
+        let pair = <(u32, String)>::switch::<()>(Route::from("42/lorem/ipsum"));
+        assert_eq!(pair, Some((42, "lorem/ipsum".to_string())));
+        let route: Route<()> = Route::from((42u32, "lorem".to_string(), true));
+        assert_eq!(route.route, "42/lorem/true");
+    }
+
+    #[test]
+    fn tuple_rejects_invalid_element() {
+        let pair = <(u32, u32)>::switch::<()>(Route::from("42/abc"));
+        assert_eq!(pair, None);
+    }
+
+    #[test]
+    fn tuple_missing_elements_use_key_not_available() {
+        let pair = <(u32, Option<u32>)>::switch::<()>(Route::from("42"));
+        assert_eq!(pair, Some((42, None)));
+        let pair = <(u32, u32)>::switch::<()>(Route::from("42"));
+        assert_eq!(pair, None);
+    }
+
+    #[test]
+    fn vec_elements_are_separated_by_ampersands() {
+        let tags = Vec::<u32>::switch::<()>(Route::from("1&2&3"));
+        assert_eq!(tags, Some(vec![1, 2, 3]));
+        assert_eq!(Vec::<u32>::switch::<()>(Route::from("1&a")), None);
+        assert_eq!(Vec::<u32>::switch::<()>(Route::from("")), Some(vec![]));
+        let route: Route<()> = Route::from(vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(route.route, "a&b");
+    }
+
+    #[test]
+    fn vec_elements_escape_ampersands() {
+        let tags = vec!["salt & pepper".to_string(), "b".to_string()];
+        let route: Route<()> = Route::from(tags.clone());
+        assert_eq!(route.route, "salt %26 pepper&b");
+        assert_eq!(Vec::<String>::switch(route), Some(tags));
+    }
 }
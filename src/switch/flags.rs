@@ -0,0 +1,93 @@
+//! Enables the routes gated behind feature flags at runtime.
+
+use std::{
+    fmt::{self, Debug, Formatter},
+    rc::Rc,
+};
+
+/// Decides which of the routes gated behind a feature flag with `#[meta(flag = "name")]` are
+/// enabled.
+///
+/// Routes that the flags given to
+/// [`Switch::switch_with_flags`](trait.Switch.html#method.switch_with_flags) disable are skipped,
+/// so the route falls through to the variants after them, and
+/// [`Switch::try_build_route`](trait.Switch.html#method.try_build_route) refuses to build them.
+/// This lets one route enum serve every combination of flags, instead of one enum per
+/// combination.
+///
+/// `Switch::switch` enables every flag. A `Router` uses the flags given to it through its `flags`
+/// prop.
+///
+/// # Example
+/// ```
+/// use yew_router::{
+///     route::Route,
+///     switch::{FeatureFlags, SwitchError},
+///     Switch,
+/// };
+///
+/// #[derive(Debug, Switch, PartialEq)]
+/// enum AppRoute {
+///     #[to = "/reports"]
+///     #[meta(flag = "new_reports")]
+///     NewReports,
+///     #[to = "/reports"]
+///     Reports,
+/// }
+///
+/// let flags = FeatureFlags::new(|flag| flag != "new_reports");
+/// assert_eq!(
+///     AppRoute::switch_with_flags(Route::<()>::from("/reports"), &flags),
+///     Some(AppRoute::Reports)
+/// );
+/// assert_eq!(
+///     AppRoute::NewReports.try_build_route::<()>(&flags),
+///     Err(SwitchError::Disabled {
+///         flag: "new_reports"
+///     })
+/// );
+/// ```
+#[derive(Clone)]
+pub struct FeatureFlags(Option<Rc<dyn Fn(&str) -> bool>>);
+
+impl FeatureFlags {
+    /// Creates a provider that enables the flags for which the function returns `true`.
+    pub fn new<F: Fn(&str) -> bool + 'static>(is_enabled: F) -> Self {
+        FeatureFlags(Some(Rc::new(is_enabled)))
+    }
+
+    /// Creates a provider that enables every flag.
+    pub fn all() -> Self {
+        FeatureFlags(None)
+    }
+
+    /// Determines if the flag is enabled.
+    pub fn is_enabled(&self, flag: &str) -> bool {
+        self.0.as_ref().map_or(true, |is_enabled| is_enabled(flag))
+    }
+}
+
+impl Default for FeatureFlags {
+    fn default() -> Self {
+        FeatureFlags::all()
+    }
+}
+
+impl Debug for FeatureFlags {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("FeatureFlags").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_are_enabled_by_the_provider() {
+        assert!(FeatureFlags::all().is_enabled("beta"));
+        let flags = FeatureFlags::new(|flag| flag.starts_with("stable_"));
+        assert!(!flags.is_enabled("beta"));
+        assert!(flags.is_enabled("stable_search"));
+    }
+}
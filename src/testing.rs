@@ -0,0 +1,966 @@
+//! Replaces the browser's history with one in memory, so components and agents that route can be
+//! tested without a browser.
+
+use crate::{
+    agent::NavigationKind,
+    route::{RouteParts, RouterState},
+    service::{
+        self, BrowserHistory, CurrentUrl, DeepLink, HistoryBackend, HistoryListener, HistoryState,
+        RouteEvents,
+    },
+};
+use std::{
+    any::Any,
+    cell::RefCell,
+    collections::HashMap,
+    fmt::{self, Debug, Formatter},
+    marker::PhantomData,
+    rc::Rc,
+};
+
+/// The host of the urls of a `MockHistory`.
+const MOCK_HOST: &str = "localhost";
+
+/// A history that replaces the browser's while it is installed, so tests can navigate like a user
+/// would, and check where the app navigated to.
+///
+/// Installing it makes it the default [`HistoryBackend`](../service/trait.HistoryBackend.html),
+/// so every `RouteService` that is created afterwards, including the ones of `RouteAgent`s, reads
+/// its url from this history, and adds its entries to it, instead of the browser's.
+/// It should be installed before the agents of a test are created, and a service that already
+/// exists can be moved to it with `RouteService::set_history` and [`backend`](#method.backend).
+/// Moving through it with [`back`](#method.back), [`forward`](#method.forward), or
+/// [`push`](#method.push) tells the services like the browser's `popstate` and `hashchange`
+/// events do.
+///
+/// States are kept as they are, so they are compared with the `T` of this history, while
+/// budgets and versions only apply to the states in the browser's history.
+/// Deep links are kept along with the entries, and time only passes when
+/// [`advance`](#method.advance) is called, so they expire without waiting.
+///
+/// The browser's history is the default again once this is dropped.
+///
+/// # Example
+/// ```
+/// use yew_router::{
+///     agent::{NavigationKind, RouteAgentDispatcher, RouteRequest},
+///     route::Route,
+///     testing::MockHistory,
+/// };
+///
+/// let history = MockHistory::<()>::install("/");
+/// let mut dispatcher: RouteAgentDispatcher = RouteAgentDispatcher::new();
+/// // Like a `RouterButton` that is clicked.
+/// dispatcher.send(RouteRequest::ChangeRoute(Route::from("/settings")));
+///
+/// let navigations = history.take_navigations();
+/// assert_eq!(navigations[0].kind, NavigationKind::Push);
+/// assert_eq!(navigations[0].url, "/settings");
+/// assert_eq!(history.urls(), vec!["/", "/settings"]);
+///
+/// let snapshot = history.snapshot();
+/// history.back();
+/// assert_eq!(history.url(), "/");
+/// history.restore(&snapshot);
+/// assert_eq!(history.url(), "/settings");
+/// ```
+pub struct MockHistory<T = ()> {
+    browser: Rc<RefCell<MockBrowser>>,
+    backend: Rc<MockBackend>,
+    state: PhantomData<T>,
+}
+
+impl<T: RouterState> MockHistory<T> {
+    /// Installs a history that starts with a single entry at the url, with the default state,
+    /// replacing any other `MockHistory` that is installed.
+    pub fn install(url: &str) -> Self {
+        let browser = Rc::new(RefCell::new(MockBrowser::new(url)));
+        let backend = Rc::new(MockBackend(browser.clone()));
+        service::set_default_history(backend.clone());
+        MockHistory {
+            browser,
+            backend,
+            state: PhantomData,
+        }
+    }
+
+    /// Gets the backend of this history, so a `RouteService` that already exists can be moved to
+    /// it with `RouteService::set_history`.
+    pub fn backend(&self) -> Rc<dyn HistoryBackend> {
+        self.backend.clone()
+    }
+
+    /// Lets the milliseconds pass on the clock of this history, which starts at 0.
+    ///
+    /// Navigations are timed with this clock, and deep links expire by it.
+    pub fn advance(&self, millis: f64) {
+        self.browser.borrow_mut().now += millis;
+    }
+
+    /// Adds an entry after the current one, removing the entries after it, like when a user
+    /// follows a link from outside the app, and tells the `RouteService`s about it.
+    ///
+    /// This isn't recorded as a navigation of the app.
+    pub fn push(&self, url: &str, state: T) {
+        let previous = self.browser.borrow().current_url();
+        self.browser.borrow_mut().visit(url, Rc::new(state));
+        self.notify(&previous);
+    }
+
+    /// Moves back by one entry, like the back button, returning `false` if this is the first one.
+    pub fn back(&self) -> bool {
+        self.go(-1)
+    }
+
+    /// Moves forward by one entry, like the forward button, returning `false` if this is the last
+    /// one.
+    pub fn forward(&self) -> bool {
+        self.go(1)
+    }
+
+    /// Moves by the number of entries, returning `false` if there is no entry that far away.
+    pub fn go(&self, delta: isize) -> bool {
+        let previous = self.browser.borrow().current_url();
+        let moved = self.browser.borrow_mut().go(delta);
+        if moved {
+            self.notify(&previous);
+        }
+        moved
+    }
+
+    /// Gets the url of the current entry.
+    pub fn url(&self) -> String {
+        self.browser.borrow().url().to_string()
+    }
+
+    /// Gets the state of the current entry, which is the default state if it is of another type.
+    pub fn state(&self) -> T {
+        self.browser.borrow().state().unwrap_or_default()
+    }
+
+    /// Gets the urls of all entries, from oldest to newest.
+    pub fn urls(&self) -> Vec<String> {
+        self.browser.borrow().urls()
+    }
+
+    /// Gets the position of the current entry, counting from the oldest one.
+    pub fn index(&self) -> usize {
+        self.browser.borrow().index
+    }
+
+    /// Takes the navigations that the app made since the history was installed, or since this was
+    /// last called, from oldest to newest.
+    ///
+    /// Navigations with states of other types are left out.
+    pub fn take_navigations(&self) -> Vec<MockNavigation<T>> {
+        let navigations = std::mem::replace(&mut self.browser.borrow_mut().navigations, Vec::new());
+        navigations
+            .into_iter()
+            .filter_map(|(kind, entry)| {
+                Some(MockNavigation {
+                    kind,
+                    url: entry.url,
+                    state: entry.state.downcast_ref::<T>()?.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Gets the urls that the app redirected to outside of itself, like with
+    /// `RouteRequest::RedirectWithDeepLink`, from oldest to newest.
+    pub fn redirects(&self) -> Vec<String> {
+        self.browser.borrow().redirects.clone()
+    }
+
+    /// Captures the entries and the position in them, so they can be restored later on.
+    pub fn snapshot(&self) -> HistorySnapshot {
+        let browser = self.browser.borrow();
+        HistorySnapshot {
+            entries: browser.entries.clone(),
+            index: browser.index,
+        }
+    }
+
+    /// Replaces the entries and the position in them with the captured ones, and tells the
+    /// `RouteService`s about the current entry.
+    pub fn restore(&self, snapshot: &HistorySnapshot) {
+        let previous = self.browser.borrow().current_url();
+        {
+            let mut browser = self.browser.borrow_mut();
+            browser.entries = snapshot.entries.clone();
+            browser.index = snapshot.index;
+        }
+        self.notify(&previous);
+    }
+
+    /// Tells the listening `RouteService`s that the current entry changed from the one at the
+    /// previous url, like the browser's `popstate` event does, followed by its `hashchange`
+    /// event if the fragment changed.
+    fn notify(&self, previous: &CurrentUrl) {
+        // The listeners are copied out first, as they read the history.
+        let (listeners, state, fragment_changed) = {
+            let browser = self.browser.borrow();
+            (
+                browser.listeners(),
+                browser.entries[browser.index].state.clone(),
+                browser.current_url().fragment != previous.fragment,
+            )
+        };
+        for (route_events, listener) in listeners {
+            if route_events != RouteEvents::HashChange {
+                listener("popstate", HistoryState::Native(state.clone()))
+            }
+            if route_events != RouteEvents::PopState && fragment_changed {
+                listener("hashchange", HistoryState::Empty)
+            }
+        }
+    }
+}
+
+impl<T> Drop for MockHistory<T> {
+    fn drop(&mut self) {
+        // Another history may have been installed since.
+        let default = service::default_history();
+        let is_installed = &*default as *const dyn HistoryBackend as *const ()
+            == &*self.backend as *const MockBackend as *const ();
+        if is_installed {
+            service::set_default_history(Rc::new(BrowserHistory));
+        }
+    }
+}
+
+impl<T> Debug for MockHistory<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let browser = self.browser.borrow();
+        f.debug_struct("MockHistory")
+            .field("urls", &browser.urls())
+            .field("index", &browser.index)
+            .finish()
+    }
+}
+
+/// A navigation that the app made while a `MockHistory` was installed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockNavigation<T = ()> {
+    /// Whether an entry was added, or the current one was replaced.
+    pub kind: NavigationKind,
+    /// The url that was navigated to, which is the route for the default `PathStrategy`.
+    pub url: String,
+    /// The state that was stored with the entry.
+    pub state: T,
+}
+
+/// The entries of a `MockHistory`, and the position in them, captured by
+/// [`MockHistory::snapshot`](struct.MockHistory.html#method.snapshot).
+#[derive(Clone)]
+pub struct HistorySnapshot {
+    entries: Vec<MockEntry>,
+    index: usize,
+}
+
+impl HistorySnapshot {
+    /// Gets the urls of the captured entries, from oldest to newest.
+    pub fn urls(&self) -> Vec<String> {
+        self.entries.iter().map(|entry| entry.url.clone()).collect()
+    }
+
+    /// Gets the position of the captured current entry.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl Debug for HistorySnapshot {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("HistorySnapshot")
+            .field("urls", &self.urls())
+            .field("index", &self.index)
+            .finish()
+    }
+}
+
+/// An entry of a `MockHistory`, whose state may be of any type.
+#[derive(Clone)]
+struct MockEntry {
+    url: String,
+    state: Rc<dyn Any>,
+}
+
+/// The listener of a `RouteService`, which is called when the current entry changes.
+type MockListener = Rc<dyn Fn(&'static str, HistoryState)>;
+
+/// The entries of a `MockHistory`, which its backend and the `MockHistory` share.
+struct MockBrowser {
+    entries: Vec<MockEntry>,
+    index: usize,
+    /// The navigations that the app made, which haven't been taken yet.
+    navigations: Vec<(NavigationKind, MockEntry)>,
+    redirects: Vec<String>,
+    listeners: Vec<(usize, RouteEvents, MockListener)>,
+    next_listener_id: usize,
+    /// The routes saved to deep links, along with their states and when they were saved, by key.
+    deep_links: HashMap<String, (String, HistoryState, f64)>,
+    /// The time of the clock, in milliseconds.
+    now: f64,
+}
+
+impl MockBrowser {
+    fn new(url: &str) -> Self {
+        MockBrowser {
+            entries: vec![MockEntry {
+                url: url.to_string(),
+                state: Rc::new(()),
+            }],
+            index: 0,
+            navigations: Vec::new(),
+            redirects: Vec::new(),
+            listeners: Vec::new(),
+            next_listener_id: 0,
+            deep_links: HashMap::new(),
+            now: 0.0,
+        }
+    }
+
+    fn url(&self) -> &str {
+        &self.entries[self.index].url
+    }
+
+    fn urls(&self) -> Vec<String> {
+        self.entries.iter().map(|entry| entry.url.clone()).collect()
+    }
+
+    /// Gets the state of the current entry, if it is of type `T`.
+    fn state<T: Clone + 'static>(&self) -> Option<T> {
+        self.entries[self.index].state.downcast_ref::<T>().cloned()
+    }
+
+    /// Gets the parts of the url of the current entry.
+    fn current_url(&self) -> CurrentUrl {
+        let parts = RouteParts::new(self.url());
+        let with_prefix = |prefix: &str, section: Option<String>| {
+            section.map_or_else(String::new, |section| format!("{}{}", prefix, section))
+        };
+        CurrentUrl {
+            path: parts.path,
+            query: with_prefix("?", parts.query),
+            fragment: with_prefix("#", parts.fragment),
+            host: MOCK_HOST.to_string(),
+        }
+    }
+
+    /// Resolves a url against the current one, like the History API does for urls that only
+    /// contain a query or a fragment.
+    fn resolve(&self, url: &str) -> String {
+        let current = self.current_url();
+        if url.starts_with('#') {
+            format!("{}{}{}", current.path, current.query, url)
+        } else if url.starts_with('?') {
+            format!("{}{}", current.path, url)
+        } else {
+            url.to_string()
+        }
+    }
+
+    /// Adds an entry after the current one, removing the ones after it.
+    fn visit(&mut self, url: &str, state: Rc<dyn Any>) {
+        let entry = MockEntry {
+            url: self.resolve(url),
+            state,
+        };
+        self.entries.truncate(self.index + 1);
+        self.entries.push(entry);
+        self.index += 1;
+    }
+
+    /// Adds an entry for a navigation of the app, or replaces the current one, and records it.
+    fn navigate(&mut self, kind: NavigationKind, url: &str, state: Rc<dyn Any>) {
+        if kind == NavigationKind::Replace {
+            let entry = MockEntry {
+                url: self.resolve(url),
+                state,
+            };
+            self.entries[self.index] = entry;
+        } else {
+            self.visit(url, state);
+        }
+        let entry = self.entries[self.index].clone();
+        self.navigations.push((kind, entry));
+    }
+
+    /// Changes the url of the current entry, keeping its state, like when the app removes a part
+    /// of the url that it has read.
+    fn replace_url(&mut self, url: &str) {
+        let state = self.entries[self.index].state.clone();
+        self.navigate(NavigationKind::Replace, url, state);
+    }
+
+    fn go(&mut self, delta: isize) -> bool {
+        let index = self.index as isize + delta;
+        if index < 0 || index >= self.entries.len() as isize {
+            return false;
+        }
+        self.index = index as usize;
+        true
+    }
+
+    fn listeners(&self) -> Vec<(RouteEvents, MockListener)> {
+        self.listeners
+            .iter()
+            .map(|(_, route_events, listener)| (*route_events, listener.clone()))
+            .collect()
+    }
+}
+
+/// The backend that the `RouteService`s of a `MockHistory` use.
+#[derive(Debug)]
+struct MockBackend(Rc<RefCell<MockBrowser>>);
+
+impl HistoryBackend for MockBackend {
+    fn current_url(&self) -> CurrentUrl {
+        self.0.borrow().current_url()
+    }
+
+    fn keeps_native_states(&self) -> bool {
+        true
+    }
+
+    fn push(&self, url: &str, state: HistoryState) {
+        self.0
+            .borrow_mut()
+            .navigate(NavigationKind::Push, url, native_state(state));
+    }
+
+    fn replace(&self, url: &str, state: HistoryState) {
+        self.0
+            .borrow_mut()
+            .navigate(NavigationKind::Replace, url, native_state(state));
+    }
+
+    fn replace_url(&self, url: &str) {
+        self.0.borrow_mut().replace_url(url);
+    }
+
+    fn redirect(&self, url: &str) {
+        self.0.borrow_mut().redirects.push(url.to_string());
+    }
+
+    fn listen(
+        &self,
+        route_events: RouteEvents,
+        listener: Rc<dyn Fn(&'static str, HistoryState)>,
+    ) -> HistoryListener {
+        let id = {
+            let mut browser = self.0.borrow_mut();
+            let id = browser.next_listener_id;
+            browser.next_listener_id += 1;
+            browser.listeners.push((id, route_events, listener));
+            id
+        };
+        let browser = self.0.clone();
+        HistoryListener::new(move || {
+            browser
+                .borrow_mut()
+                .listeners
+                .retain(|(listener_id, _, _)| *listener_id != id);
+        })
+    }
+
+    fn save_deep_link(&self, deep_link: &DeepLink, route: &str, state: HistoryState) {
+        let mut browser = self.0.borrow_mut();
+        let saved_at = browser.now;
+        browser.deep_links.insert(
+            deep_link.key().to_string(),
+            (route.to_string(), state, saved_at),
+        );
+    }
+
+    fn take_deep_link(&self, deep_link: &DeepLink) -> Option<(String, HistoryState)> {
+        let mut browser = self.0.borrow_mut();
+        let (route, state, saved_at) = browser.deep_links.remove(deep_link.key())?;
+        if deep_link.is_fresh(saved_at, browser.now) {
+            Some((route, state))
+        } else {
+            None
+        }
+    }
+
+    fn now(&self) -> f64 {
+        self.0.borrow().now
+    }
+}
+
+impl Debug for MockBrowser {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("MockBrowser")
+            .field("urls", &self.urls())
+            .field("index", &self.index)
+            .finish()
+    }
+}
+
+/// Gets the state that a `MockHistory` keeps for a state given to its backend.
+fn native_state(state: HistoryState) -> Rc<dyn Any> {
+    match state {
+        HistoryState::Native(state) => state,
+        // Services only convert states for histories that don't keep them as they are.
+        HistoryState::Empty | HistoryState::Value(_) => Rc::new(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        agent::{
+            Navigation, NavigationListener, RouteAgentBridge, RouteAgentDispatcher, RouteRequest,
+        },
+        route::Route,
+        service::RouteService,
+    };
+    use yew::Callback;
+
+    /// Navigates like a `RouteService` of the app does.
+    fn navigate<T: RouterState>(history: &MockHistory<T>, kind: NavigationKind, url: &str) {
+        let state = HistoryState::Native(Rc::new(()));
+        match kind {
+            NavigationKind::Replace => history.backend().replace(url, state),
+            _ => history.backend().push(url, state),
+        }
+    }
+
+    /// Creates a callback that collects the values it is called with.
+    fn collect<T: 'static>() -> (Callback<T>, Rc<RefCell<Vec<T>>>) {
+        let collected = Rc::new(RefCell::new(Vec::new()));
+        let callback = {
+            let collected = collected.clone();
+            Callback::from(move |value: T| collected.borrow_mut().push(value))
+        };
+        (callback, collected)
+    }
+
+    #[test]
+    fn navigations_are_recorded_and_replace_forward_entries() {
+        let history = MockHistory::<()>::install("/");
+        navigate(&history, NavigationKind::Push, "/a");
+        navigate(&history, NavigationKind::Push, "#top");
+        assert_eq!(history.urls(), vec!["/", "/a", "/a#top"]);
+
+        assert!(history.back());
+        assert!(history.back());
+        assert!(!history.back());
+        navigate(&history, NavigationKind::Push, "/b");
+        navigate(&history, NavigationKind::Replace, "/c");
+        assert_eq!(history.urls(), vec!["/", "/c"]);
+        assert_eq!(
+            history.take_navigations(),
+            vec![
+                MockNavigation {
+                    kind: NavigationKind::Push,
+                    url: "/a".to_string(),
+                    state: (),
+                },
+                MockNavigation {
+                    kind: NavigationKind::Push,
+                    url: "/a#top".to_string(),
+                    state: (),
+                },
+                MockNavigation {
+                    kind: NavigationKind::Push,
+                    url: "/b".to_string(),
+                    state: (),
+                },
+                MockNavigation {
+                    kind: NavigationKind::Replace,
+                    url: "/c".to_string(),
+                    state: (),
+                },
+            ]
+        );
+        assert!(history.take_navigations().is_empty());
+    }
+
+    #[test]
+    fn restoring_a_snapshot_notifies_listeners() {
+        let history = MockHistory::<String>::install("/");
+        history.push("/a?page=2", "scrolled".to_string());
+        let snapshot = history.snapshot();
+
+        let notified = Rc::new(RefCell::new(Vec::new()));
+        let listener = {
+            let notified = notified.clone();
+            history.backend().listen(
+                RouteEvents::PopState,
+                Rc::new(move |_: &'static str, state: HistoryState| {
+                    if let HistoryState::Native(state) = state {
+                        let state = state.downcast_ref::<String>().cloned();
+                        notified.borrow_mut().push(state.unwrap_or_default());
+                    }
+                }),
+            )
+        };
+        history.push("/b", "other".to_string());
+        history.restore(&snapshot);
+        drop(listener);
+        history.back();
+
+        assert_eq!(snapshot.urls(), vec!["/", "/a?page=2"]);
+        assert_eq!(*notified.borrow(), vec!["other", "scrolled"]);
+        assert_eq!(history.state(), String::new());
+        let url = history.backend().current_url();
+        assert_eq!((url.path.as_str(), url.query.as_str()), ("/", ""));
+    }
+
+    #[test]
+    fn dropping_the_history_uninstalls_it() {
+        let first = MockHistory::<()>::install("/first");
+        let second = MockHistory::<()>::install("/second");
+        // The first history was already replaced, so the second one stays installed.
+        drop(first);
+        assert_eq!(service::default_history().current_url().path, "/second");
+        drop(second);
+        assert_eq!(
+            format!("{:?}", service::default_history()),
+            format!("{:?}", BrowserHistory)
+        );
+    }
+
+    #[test]
+    fn route_service_navigates_in_the_history() {
+        let history = MockHistory::<String>::install("/");
+        let mut route_service = RouteService::<String>::new();
+        let (callback, routes) = collect();
+        route_service.register_callback(callback);
+
+        route_service.set_route("/users?page=2", "first".to_string());
+        route_service.replace_route("/users?page=3", "second".to_string());
+        route_service.push_relative("42", "third".to_string());
+        assert_eq!(route_service.get_route(), "/users/42");
+        assert_eq!(route_service.get_query(), "");
+        assert_eq!(history.urls(), vec!["/", "/users?page=3", "/users/42"]);
+        assert_eq!(
+            history
+                .take_navigations()
+                .into_iter()
+                .map(|navigation| (navigation.kind, navigation.state))
+                .collect::<Vec<_>>(),
+            vec![
+                (NavigationKind::Push, "first".to_string()),
+                (NavigationKind::Replace, "second".to_string()),
+                (NavigationKind::Push, "third".to_string()),
+            ]
+        );
+        // Navigations of the app aren't reported back to it.
+        assert!(routes.borrow().is_empty());
+
+        history.back();
+        history.push("/settings", "visited".to_string());
+        assert_eq!(
+            *routes.borrow(),
+            vec![
+                ("/users?page=3".to_string(), "second".to_string()),
+                ("/settings".to_string(), "visited".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn route_service_returns_the_initial_route_until_the_url_is_read() {
+        let _history = MockHistory::<()>::install("/loading");
+        let mut route_service = RouteService::<()>::with_initial_route("/users/42");
+        assert_eq!(route_service.get_route(), "/users/42");
+        assert_eq!(
+            Route::current_route(&route_service),
+            Route::from("/users/42")
+        );
+        route_service.read_url();
+        assert_eq!(route_service.get_route(), "/loading");
+    }
+
+    #[test]
+    fn route_service_reads_the_url_once_the_route_changes() {
+        let history = MockHistory::<()>::install("/");
+        let mut route_service = RouteService::<()>::with_initial_route("/users/42");
+        route_service.set_route("/settings", ());
+        assert_eq!(route_service.get_route(), "/settings");
+
+        let mut route_service = RouteService::<()>::with_initial_route("/users/42");
+        let (callback, routes) = collect();
+        route_service.register_callback(callback);
+        history.push("/about", ());
+        assert_eq!(*routes.borrow(), vec![("/about".to_string(), ())]);
+        assert_eq!(route_service.get_route(), "/about");
+    }
+
+    #[test]
+    fn route_service_stops_listening_once_callbacks_are_removed() {
+        let history = MockHistory::<()>::install("/");
+        let mut route_service = RouteService::<()>::new();
+        let (callback, routes) = collect();
+        let handle = route_service.register_callback(callback);
+        assert!(route_service.deregister_callback(handle));
+        history.push("/a", ());
+        assert!(routes.borrow().is_empty());
+
+        let (callback, routes) = collect();
+        route_service.register_callback(callback);
+        drop(route_service);
+        history.push("/b", ());
+        assert!(routes.borrow().is_empty());
+    }
+
+    #[test]
+    fn route_service_calls_every_callback() {
+        let history = MockHistory::<()>::install("/");
+        let mut route_service = RouteService::<()>::new();
+        let (first, first_routes) = collect();
+        let (second, second_routes) = collect();
+        let first = route_service.register_callback(first);
+        route_service.register_callback(second);
+
+        history.push("/a", ());
+        assert!(route_service.deregister_callback(first));
+        assert!(!route_service.deregister_callback(first));
+        history.push("/b", ());
+        route_service.clear_callbacks();
+        history.push("/c", ());
+
+        assert_eq!(*first_routes.borrow(), vec![("/a".to_string(), ())]);
+        assert_eq!(
+            *second_routes.borrow(),
+            vec![("/a".to_string(), ()), ("/b".to_string(), ())]
+        );
+    }
+
+    #[test]
+    fn route_service_reports_a_navigation_once_when_both_events_fire() {
+        let history = MockHistory::<String>::install("/a");
+        let mut route_service = RouteService::<String>::new();
+        route_service.set_route_events(RouteEvents::Both);
+        let (callback, routes) = collect();
+        route_service.register_callback(callback);
+
+        history.push("/a#top", "scrolled".to_string());
+        history.back();
+        history.push("/b", "other".to_string());
+
+        assert_eq!(
+            *routes.borrow(),
+            vec![
+                ("/a#top".to_string(), "scrolled".to_string()),
+                ("/a".to_string(), String::new()),
+                ("/b".to_string(), "other".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn route_service_can_listen_to_fragment_changes_only() {
+        let history = MockHistory::<String>::install("/");
+        let mut route_service = RouteService::<String>::new();
+        route_service.set_route_events(RouteEvents::HashChange);
+        let (callback, routes) = collect();
+        route_service.register_callback(callback);
+
+        history.push("/a", "ignored".to_string());
+        history.push("/a#top", "dropped".to_string());
+
+        // The fragment can't carry any state.
+        assert_eq!(
+            *routes.borrow(),
+            vec![("/a#top".to_string(), String::new())]
+        );
+    }
+
+    #[test]
+    fn route_service_moves_to_another_history() {
+        let history = MockHistory::<()>::install("/");
+        let mut route_service = RouteService::<()>::new();
+        let (callback, routes) = collect();
+        route_service.register_callback(callback);
+
+        let other = MockHistory::<()>::install("/other");
+        route_service.set_history(history.backend());
+        other.push("/ignored", ());
+        history.push("/noticed", ());
+        route_service.set_route("/added", ());
+
+        assert_eq!(*routes.borrow(), vec![("/noticed".to_string(), ())]);
+        assert_eq!(history.url(), "/added");
+        assert_eq!(other.urls(), vec!["/other", "/ignored"]);
+    }
+
+    #[test]
+    fn deep_links_are_restored_until_they_expire() {
+        let history = MockHistory::<String>::install("/cart?step=2");
+        let mut route_service = RouteService::<String>::new();
+        let deep_link = DeepLink::new().with_max_age(1000);
+        let provider = "https://login.example.com/authorize";
+
+        route_service.redirect_with_deep_link(&deep_link, "filled".to_string(), provider);
+        assert_eq!(history.redirects(), vec![provider]);
+        history.push("/callback?code=1234", String::new());
+        history.advance(1000.0);
+        let restored = route_service.restore_deep_link(&deep_link);
+        assert_eq!(
+            restored,
+            Some(Route {
+                route: "/cart?step=2".to_string(),
+                state: Some("filled".to_string()),
+            })
+        );
+        assert_eq!(history.url(), "/cart?step=2");
+        assert_eq!(history.state(), "filled");
+        // Deep links are only restored once.
+        assert_eq!(route_service.restore_deep_link(&deep_link), None);
+
+        route_service.redirect_with_deep_link(&deep_link, String::new(), provider);
+        history.advance(1001.0);
+        assert_eq!(route_service.restore_deep_link(&deep_link), None);
+    }
+
+    #[test]
+    fn intended_route_is_taken_from_the_url() {
+        let history = MockHistory::<String>::install("/");
+        let login =
+            Route::<()>::from("/login").with_intended_route(&Route::<()>::from("/dashboard"));
+        history.push(&login.route, "kept".to_string());
+        let mut route_service = RouteService::<String>::new();
+
+        assert_eq!(
+            route_service.take_intended_route(),
+            Some("/dashboard".to_string())
+        );
+        assert_eq!(history.url(), "/login");
+        assert_eq!(history.state(), "kept");
+        assert_eq!(route_service.take_intended_route(), None);
+    }
+
+    #[test]
+    fn route_agent_navigates_and_broadcasts() {
+        let history = MockHistory::<()>::install("/");
+        let (callback, routes) = collect();
+        let mut bridge: RouteAgentBridge = RouteAgentBridge::new(callback);
+
+        bridge.send(RouteRequest::ChangeRoute(Route::from("/a")));
+        // Navigating to the current route does nothing.
+        bridge.send(RouteRequest::ChangeRoute(Route::from("/a")));
+        bridge.send(RouteRequest::ReplaceRoute(Route::from("/b")));
+        assert_eq!(history.urls(), vec!["/", "/b"]);
+        assert_eq!(
+            history
+                .take_navigations()
+                .into_iter()
+                .map(|navigation| (navigation.kind, navigation.url))
+                .collect::<Vec<_>>(),
+            vec![
+                (NavigationKind::Push, "/a".to_string()),
+                (NavigationKind::Replace, "/b".to_string()),
+            ]
+        );
+
+        history.back();
+        let routes: Vec<String> = routes.borrow().iter().map(|r| r.route.clone()).collect();
+        assert_eq!(routes, vec!["/a", "/b", "/"]);
+    }
+
+    #[test]
+    fn route_agent_ignores_links_to_the_route_navigated_back_to() {
+        let history = MockHistory::<()>::install("/");
+        let (callback, _routes) = collect();
+        let mut bridge: RouteAgentBridge = RouteAgentBridge::new(callback);
+        bridge.send(RouteRequest::ChangeRoute(Route::from("/a")));
+
+        // The entry navigated back to has a state, while the link to it has none.
+        history.back();
+        bridge.send(RouteRequest::ChangeRoute(Route::from("/")));
+        assert_eq!(history.urls(), vec!["/", "/a"]);
+        assert_eq!(history.index(), 0);
+        assert_eq!(history.take_navigations().len(), 1);
+    }
+
+    #[test]
+    fn navigation_listener_reports_every_navigation() {
+        let history = MockHistory::<()>::install("/");
+        let (callback, navigations) = collect();
+        let _listener: NavigationListener = NavigationListener::new(callback);
+        let mut dispatcher: RouteAgentDispatcher = RouteAgentDispatcher::new();
+
+        dispatcher.send(RouteRequest::ChangeRoute(Route::from("/a")));
+        dispatcher.send(RouteRequest::ReplaceRoute(Route::from("/b")));
+        history.back();
+
+        let navigation = |from: Route, to: Route, kind| Navigation { from, to, kind };
+        assert_eq!(
+            *navigations.borrow(),
+            vec![
+                navigation(Route::from("/"), Route::from("/a"), NavigationKind::Push),
+                navigation(
+                    Route::from("/a"),
+                    Route::from("/b"),
+                    NavigationKind::Replace
+                ),
+                navigation(
+                    Route::from("/b"),
+                    Route {
+                        route: "/".to_string(),
+                        state: Some(()),
+                    },
+                    NavigationKind::Pop
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn route_agent_times_navigations_with_the_history() {
+        let history = MockHistory::<()>::install("/");
+        history.advance(1500.0);
+        let (callback, _routes) = collect();
+        let mut bridge: RouteAgentBridge = RouteAgentBridge::new(callback);
+        bridge.send(RouteRequest::SetHistoryLimit(10));
+        bridge.send(RouteRequest::ChangeRoute(Route::from("/a")));
+
+        let recorded = crate::agent::RouteAgent::<()>::recorded_history();
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].timestamp, 1500.0);
+    }
+
+    #[cfg(feature = "components")]
+    #[test]
+    fn router_button_clicks_navigate() {
+        use crate::components::Props;
+
+        let history = MockHistory::<()>::install("/");
+        let mut dispatcher: RouteAgentDispatcher = RouteAgentDispatcher::new();
+        let props = |link: &str, force: bool, disabled: bool| Props {
+            link: link.to_string(),
+            force,
+            disabled,
+            ..Props::default()
+        };
+        // A `RouterButton` sends the request of its props when it is clicked.
+        let mut click = |props: Props| {
+            if let Some(request) = props.click_request() {
+                dispatcher.send(request);
+            }
+        };
+
+        click(props("/settings", false, false));
+        click(props("/settings", false, false));
+        click(props("/settings", true, false));
+        click(props("/profile", false, true));
+
+        assert_eq!(
+            history
+                .take_navigations()
+                .into_iter()
+                .map(|navigation| (navigation.kind, navigation.url))
+                .collect::<Vec<_>>(),
+            vec![
+                (NavigationKind::Push, "/settings".to_string()),
+                (NavigationKind::Replace, "/settings".to_string()),
+            ]
+        );
+        assert_eq!(history.urls(), vec!["/", "/settings"]);
+    }
+}
@@ -176,6 +176,22 @@ mod tests {
         )
     }
 
+    #[test]
+    fn deny_shadowed_accepts_reachable_variants() {
+        #[derive(Debug, Switch, PartialEq)]
+        #[switch(deny_shadowed)]
+        pub enum Test {
+            #[to = "/variant/stuff"]
+            Variant2,
+            #[to = "/variant"]
+            Variant1,
+        }
+        let switched = Test::switch(Route::from("/variant/stuff")).expect("should produce item");
+        assert_eq!(switched, Test::Variant2);
+        let switched = Test::switch(Route::from("/variant")).expect("should produce item");
+        assert_eq!(switched, Test::Variant1);
+    }
+
     #[test]
     fn single_enum_variant_convert_usize() {
         #[derive(Debug, Switch, PartialEq)]
@@ -386,4 +402,887 @@ mod tests {
             Test::Variant("lorem".to_string(), "dolor".to_string())
         )
     }
+
+    #[test]
+    fn grammar_v2_escaped_end_token() {
+        #[derive(Debug, Switch, PartialEq)]
+        #[grammar = "v2"]
+        pub enum Test {
+            #[to = r"/variant\!"]
+            Variant,
+        }
+        let route = Route::from("/variant!");
+        let switched = Test::switch(route).expect("should produce item");
+        assert_eq!(switched, Test::Variant)
+    }
+
+    #[test]
+    fn routes_describe_variants() {
+        use yew_router::{
+            matcher::GrammarVersion,
+            switch::{CaptureInfo, RouteInfo, RouteMeta},
+        };
+        #[derive(Debug, Switch, PartialEq)]
+        pub enum Test {
+            #[to = "/variant/{item}"]
+            Named { item: String },
+            #[to = "/tuple/{}"]
+            #[end]
+            Tuple(usize),
+            #[to = "/inner"]
+            #[rest]
+            Unit,
+            #[to = "/about!"]
+            #[meta(title = "About")]
+            About,
+        }
+        assert_eq!(
+            Test::routes(),
+            vec![
+                RouteInfo {
+                    name: "Named",
+                    matcher: "/variant/{item}",
+                    captures: vec![CaptureInfo {
+                        name: Some("item"),
+                        ty: Some("String")
+                    }],
+                    route: None,
+                    grammar_version: GrammarVersion::V1,
+                    meta: RouteMeta::default(),
+                },
+                RouteInfo {
+                    name: "Tuple",
+                    matcher: "/tuple/{}!",
+                    captures: vec![CaptureInfo {
+                        name: None,
+                        ty: Some("usize")
+                    }],
+                    route: None,
+                    grammar_version: GrammarVersion::V1,
+                    meta: RouteMeta::default(),
+                },
+                RouteInfo {
+                    name: "Unit",
+                    matcher: "/inner{*}",
+                    captures: vec![CaptureInfo {
+                        name: None,
+                        ty: None
+                    }],
+                    route: None,
+                    grammar_version: GrammarVersion::V1,
+                    meta: RouteMeta::default(),
+                },
+                RouteInfo {
+                    name: "About",
+                    matcher: "/about!",
+                    captures: vec![],
+                    route: Some("/about"),
+                    grammar_version: GrammarVersion::V1,
+                    meta: RouteMeta::new(&[("title", Some("About"))]),
+                },
+            ]
+        );
+
+        #[derive(Debug, Switch, PartialEq)]
+        pub enum Generic {
+            #[to = "/variant/{item}"]
+            Named { item: Option<String> },
+        }
+        let ty = Generic::routes()[0].captures[0].ty.expect("should have a type");
+        // `stringify!` may put spaces between the tokens of the type.
+        assert_eq!(ty.replace(' ', ""), "Option<String>");
+    }
+
+    #[test]
+    fn priority_is_tried_before_declaration_order() {
+        #[derive(Debug, Switch, PartialEq)]
+        pub enum Test {
+            #[to = "/{}"]
+            Any(String),
+            #[to = "/about"]
+            #[priority = 1]
+            About,
+        }
+        let route = Route::from("/about");
+        let switched = Test::switch(route).expect("should produce item");
+        assert_eq!(switched, Test::About);
+
+        let route = Route::from("/lorem");
+        let switched = Test::switch(route).expect("should produce item");
+        assert_eq!(switched, Test::Any("lorem".to_string()))
+    }
+
+    #[test]
+    fn lenient_query_ignores_extra_query_and_fragment() {
+        #[derive(Debug, Switch, PartialEq)]
+        #[query = "lenient"]
+        pub enum Test {
+            #[to = "/list!"]
+            List,
+        }
+        let route = Route::from("/list?utm_source=email#top");
+        let switched = Test::switch(route).expect("should produce item");
+        assert_eq!(switched, Test::List)
+    }
+
+    #[test]
+    fn strict_query_rejects_unknown_params() {
+        #[derive(Debug, Switch, PartialEq)]
+        #[query = "strict"]
+        pub enum Test {
+            #[to = "/list?page={page}"]
+            List { page: usize },
+        }
+        let route = Route::from("/list?page=2");
+        let switched = Test::switch(route).expect("should produce item");
+        assert_eq!(switched, Test::List { page: 2 });
+        let route = Route::from("/list?page=2&pgae=3");
+        assert!(Test::switch(route).is_none())
+    }
+
+    #[test]
+    fn meta_is_attached_to_variants() {
+        #[derive(Debug, Switch, PartialEq)]
+        pub enum Test {
+            #[to = "/settings/{tab}"]
+            #[meta(title = "Settings", requires_auth)]
+            Settings { tab: String },
+            #[to = "/users/{}"]
+            #[meta(title = "User")]
+            User(usize),
+            #[to = "/"]
+            Home,
+        }
+        let settings = Test::Settings {
+            tab: "profile".to_string(),
+        }
+        .meta();
+        assert_eq!(settings.get("title"), Some("Settings"));
+        assert_eq!(settings.get("requires_auth"), None);
+        assert!(settings.contains("requires_auth"));
+        assert_eq!(
+            settings.iter().collect::<Vec<_>>(),
+            vec![("title", Some("Settings")), ("requires_auth", None)]
+        );
+        assert_eq!(Test::User(1).meta().get("title"), Some("User"));
+        assert!(!Test::User(1).meta().contains("requires_auth"));
+        assert!(Test::Home.meta().is_empty());
+    }
+
+    #[test]
+    fn nested_meta_includes_rest_field() {
+        #[derive(Debug, Switch, PartialEq)]
+        pub enum Inner {
+            #[to = "/profile"]
+            #[meta(title = "Profile")]
+            Profile,
+        }
+        #[derive(Debug, Switch, PartialEq)]
+        pub enum Test {
+            #[to = "/settings{*:inner}"]
+            #[meta(title = "Settings")]
+            Settings { inner: Inner },
+        }
+        let switched = Test::switch(Route::from("/settings/profile")).expect("should produce item");
+        let titles: Vec<_> = switched
+            .nested_meta()
+            .iter()
+            .map(|meta| meta.get("title"))
+            .collect();
+        assert_eq!(titles, vec![Some("Settings"), Some("Profile")]);
+
+        let trail = yew_router::switch::breadcrumbs::<Test, ()>("/settings/profile");
+        let trail: Vec<_> = trail
+            .iter()
+            .map(|(title, route)| (*title, route.route.as_str()))
+            .collect();
+        assert_eq!(
+            trail,
+            vec![("Settings", "/settings"), ("Profile", "/settings/profile")]
+        );
+    }
+
+    #[test]
+    fn breadcrumbs_include_every_ancestor_title() {
+        #[derive(Debug, Switch, PartialEq)]
+        pub enum Team {
+            #[to = "/members"]
+            #[meta(title = "Members")]
+            Members,
+        }
+        #[derive(Debug, Switch, PartialEq)]
+        pub enum Admin {
+            #[to = "/teams{*:team}"]
+            #[meta(title = "Teams")]
+            Teams { team: Team },
+            #[end]
+            #[meta(title = "Admin")]
+            Index,
+        }
+        #[derive(Debug, Switch, PartialEq)]
+        pub enum Test {
+            #[to = "/admin{*:admin}"]
+            #[meta(title = "Admin")]
+            Admin { admin: Admin },
+            #[to = "/!"]
+            #[meta(title = "Home")]
+            Home,
+        }
+        let trail = yew_router::switch::breadcrumbs::<Test, ()>("/admin/teams/members");
+        let trail: Vec<_> = trail
+            .iter()
+            .map(|(title, route)| (*title, route.route.as_str()))
+            .collect();
+        assert_eq!(
+            trail,
+            vec![
+                ("Home", "/"),
+                ("Admin", "/admin"),
+                ("Teams", "/admin/teams/members"),
+                ("Members", "/admin/teams/members"),
+            ]
+        );
+    }
+
+    #[test]
+    fn nested_route_is_built_from_rest_field() {
+        #[derive(Debug, Switch, PartialEq)]
+        pub enum Inner {
+            #[to = "/users/{id}"]
+            User { id: u32 },
+        }
+        #[derive(Debug, Switch, PartialEq)]
+        pub enum Test {
+            #[to = "/admin{*}"]
+            Admin(Inner),
+            #[to = "/"]
+            Home,
+        }
+        #[derive(Debug, Switch, PartialEq)]
+        #[to = "/v1{*:inner}"]
+        pub struct Versioned {
+            inner: Inner,
+        }
+
+        let switched =
+            Test::switch(Route::from("/admin/users/5")).expect("should produce item");
+        assert_eq!(switched.nested_route(), Some("/users/5".to_string()));
+        assert_eq!(Test::Home.nested_route(), None);
+
+        let switched = Versioned::switch(Route::from("/v1/users/7")).expect("should produce item");
+        assert_eq!(switched.nested_route(), Some("/users/7".to_string()));
+    }
+
+    #[test]
+    fn captures_are_encoded_with_the_given_encoding() {
+        use yew_router::route::{EncodeSet, RouteEncoding};
+        #[derive(Debug, Switch, PartialEq)]
+        pub enum Test {
+            #[to = "/tags/{tag}?related={related}#{section}"]
+            Tag {
+                tag: String,
+                related: String,
+                section: String,
+            },
+        }
+        let tag = || Test::Tag {
+            tag: "c++ lang".to_string(),
+            related: "c++".to_string(),
+            section: "top ü".to_string(),
+        };
+
+        let route: Route = tag().into();
+        assert_eq!(route.route, "/tags/c++ lang?related=c++#top ü");
+
+        let route: Route = RouteEncoding::default().build_route(tag());
+        assert_eq!(route.route, "/tags/c++%20lang?related=c%2B%2B#top%20%C3%BC");
+
+        let encoding = RouteEncoding {
+            path: EncodeSet::PATH.with("+"),
+            ..RouteEncoding::default()
+        };
+        let route: Route = encoding.build_route(tag());
+        assert_eq!(route.route, "/tags/c%2B%2B%20lang?related=c%2B%2B#top%20%C3%BC");
+    }
+
+    #[test]
+    fn disabled_flags_skip_variants() {
+        use yew_router::switch::{FeatureFlags, SwitchError};
+        #[derive(Debug, Switch, PartialEq)]
+        pub enum Test {
+            #[to = "/reports{*}"]
+            #[meta(flag = "reports")]
+            Reports(Nested),
+            #[to = "/{*:any}"]
+            NotFound { any: String },
+        }
+        #[derive(Debug, Switch, PartialEq)]
+        pub enum Nested {
+            #[to = "/beta"]
+            #[meta(flag = "beta")]
+            Beta,
+            #[to = "/{*:rest}"]
+            Stable { rest: String },
+        }
+        let switch = |route: &str, flags: &FeatureFlags| {
+            Test::switch_with_flags(Route::from(route), flags)
+        };
+        let all = FeatureFlags::all();
+
+        assert_eq!(
+            switch("/reports/beta", &all),
+            Some(Test::Reports(Nested::Beta))
+        );
+        assert_eq!(
+            Test::switch(Route::from("/reports/beta")),
+            Some(Test::Reports(Nested::Beta))
+        );
+
+        let flags = FeatureFlags::new(|flag| flag != "beta");
+        assert_eq!(
+            switch("/reports/beta", &flags),
+            Some(Test::Reports(Nested::Stable {
+                rest: "beta".to_string()
+            }))
+        );
+        assert_eq!(
+            Test::Reports(Nested::Beta).try_build_route::<()>(&flags),
+            Err(SwitchError::Disabled { flag: "beta" })
+        );
+
+        let flags = FeatureFlags::new(|flag| flag != "reports");
+        assert_eq!(
+            switch("/reports/beta", &flags),
+            Some(Test::NotFound {
+                any: "reports/beta".to_string()
+            })
+        );
+        assert_eq!(
+            Test::Reports(Nested::Beta).try_build_route::<()>(&flags),
+            Err(SwitchError::Disabled { flag: "reports" })
+        );
+        let route: Route = Test::Reports(Nested::Beta).into();
+        assert_eq!(route.route, "/reports/beta");
+
+        assert_eq!(
+            Test::Reports(Nested::Beta)
+                .try_build_route::<()>(&all)
+                .map(|route| route.route),
+            Ok("/reports/beta".to_string())
+        );
+    }
+
+    #[test]
+    fn try_switch_reports_invalid_field() {
+        use yew_router::switch::SwitchError;
+        #[derive(Debug, Switch, PartialEq)]
+        pub enum Test {
+            #[to = "/user/{}"]
+            User(u32),
+        }
+        assert_eq!(
+            Test::try_switch(Route::from("/user/abc")),
+            Err(SwitchError::InvalidField {
+                item: "Test::User",
+                field: "0",
+                value: Some("abc".to_string()),
+            })
+        );
+        assert_eq!(Test::try_switch(Route::from("/user/1")), Ok(Test::User(1)));
+        assert_eq!(
+            Test::try_switch(Route::from("/other")),
+            Err(SwitchError::NoMatch)
+        );
+    }
+
+    #[test]
+    fn empty_matcher_matches_root() {
+        #[derive(Debug, Switch, PartialEq)]
+        pub enum Inner {
+            #[to = ""]
+            Index,
+            #[to = "/profile"]
+            Profile,
+        }
+        #[derive(Debug, Switch, PartialEq)]
+        pub enum Test {
+            #[to = "/settings{*:inner}"]
+            Settings { inner: Inner },
+        }
+        assert_eq!(Inner::switch(Route::from("/")), Some(Inner::Index));
+        assert_eq!(
+            Test::switch(Route::from("/settings")),
+            Some(Test::Settings {
+                inner: Inner::Index
+            })
+        );
+        assert_eq!(
+            Test::switch(Route::from("/settings/profile")),
+            Some(Test::Settings {
+                inner: Inner::Profile
+            })
+        );
+    }
+
+    #[test]
+    fn slash_matcher_matches_empty_route() {
+        #[derive(Debug, Switch, PartialEq)]
+        pub enum Inner {
+            #[to = "/!"]
+            Index,
+            #[to = "/profile"]
+            Profile,
+        }
+        #[derive(Debug, Switch, PartialEq)]
+        pub enum Test {
+            #[to = "/settings{*:inner}"]
+            Settings { inner: Inner },
+        }
+        assert_eq!(Inner::switch(Route::from("")), Some(Inner::Index));
+        assert_eq!(
+            Test::switch(Route::from("/settings")),
+            Some(Test::Settings {
+                inner: Inner::Index
+            })
+        );
+    }
+
+    #[test]
+    fn separated_captures() {
+        #[derive(Debug, Switch, PartialEq)]
+        pub enum Test {
+            #[to = "/posts/{3:date;sep=-}-{slug}"]
+            Post { date: String, slug: String },
+        }
+        let switched =
+            Test::switch(Route::from("/posts/2020-05-01-my-title")).expect("should produce item");
+        assert_eq!(
+            switched,
+            Test::Post {
+                date: "2020-05-01".to_string(),
+                slug: "my-title".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn matrix_params() {
+        #[derive(Debug, Switch, PartialEq)]
+        pub enum Test {
+            #[to = "/users;role={role}/list"]
+            List { role: String },
+        }
+        let switched =
+            Test::switch(Route::from("/users;role=admin/list")).expect("should produce item");
+        assert_eq!(
+            switched,
+            Test::List {
+                role: "admin".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn numbered_unnamed_capture_binds_each_section() {
+        #[derive(Debug, Switch, PartialEq, Clone)]
+        pub enum Test {
+            #[to = "/archive/{2}"]
+            Archive(String, String),
+        }
+        let switched = Test::switch(Route::from("/archive/2020/05")).expect("should produce item");
+        assert_eq!(
+            switched,
+            Test::Archive("2020".to_string(), "05".to_string())
+        );
+        assert_eq!(Route::from(switched).route, "/archive/2020/05");
+    }
+
+    #[test]
+    fn greedy_many_capture() {
+        #[derive(Debug, Switch, PartialEq)]
+        pub enum Test {
+            #[to = "/{**:path}/edit"]
+            Edit { path: String },
+        }
+        let switched = Test::switch(Route::from("/a/edit/b/edit")).expect("should produce item");
+        assert_eq!(
+            switched,
+            Test::Edit {
+                path: "a/edit/b".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn exact_option() {
+        #[derive(Debug, Switch, PartialEq)]
+        pub enum Test {
+            #[to("/home", exact)]
+            Home,
+            #[to = "/home/{section}"]
+            Section { section: String },
+        }
+        assert_eq!(Test::switch(Route::from("/home")), Some(Test::Home));
+        assert_eq!(
+            Test::switch(Route::from("/home/news")),
+            Some(Test::Section {
+                section: "news".to_string()
+            })
+        );
+        assert_eq!(Test::routes()[0].matcher, "/home!");
+    }
+
+    #[test]
+    fn file_extension() {
+        use yew_router::switch::{FeatureFlags, SwitchError};
+        #[derive(Debug, Switch, PartialEq, Clone)]
+        #[to = "/files/{name:stem}.{ext}"]
+        pub struct File {
+            name: String,
+            ext: String,
+        }
+        let switched =
+            File::switch(Route::from("/files/archive.tar.gz")).expect("should produce item");
+        assert_eq!(
+            switched,
+            File {
+                name: "archive.tar".to_string(),
+                ext: "gz".to_string()
+            }
+        );
+        assert_eq!(Route::from(switched).route, "/files/archive.tar.gz");
+
+        #[derive(Debug, Switch, PartialEq, Clone)]
+        pub enum AppRoute {
+            #[to = "/download{*}"]
+            Download(File),
+        }
+        let file = File {
+            name: "archive".to_string(),
+            ext: "tar.gz".to_string(),
+        };
+        assert_eq!(
+            AppRoute::Download(file).try_build_route::<()>(&FeatureFlags::all()),
+            Err(SwitchError::Unbuildable {
+                item: "File",
+                field: "ext",
+                value: "tar.gz".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn partly_captured_query_value() {
+        #[derive(Debug, Switch, PartialEq, Clone)]
+        #[to = "/issues?filter=status:{status}"]
+        pub struct Issues {
+            status: String,
+        }
+        let switched =
+            Issues::switch(Route::from("/issues?filter=status:open")).expect("should produce item");
+        assert_eq!(
+            switched,
+            Issues {
+                status: "open".to_string()
+            }
+        );
+        assert_eq!(Route::from(switched).route, "/issues?filter=status:open");
+    }
+
+    #[test]
+    fn renamed_capture() {
+        #[derive(Debug, Switch, PartialEq, Clone)]
+        pub enum Test {
+            #[to = "/users/{user_id}/posts/{*:path}"]
+            Posts {
+                #[switch(rename = "user_id")]
+                id: String,
+                path: String,
+            },
+        }
+        let switched =
+            Test::switch(Route::from("/users/42/posts/a/b")).expect("should produce item");
+        assert_eq!(
+            switched,
+            Test::Posts {
+                id: "42".to_string(),
+                path: "a/b".to_string()
+            }
+        );
+        assert_eq!(Route::from(switched).route, "/users/42/posts/a/b");
+    }
+
+    #[test]
+    fn renamed_capture_struct() {
+        #[derive(Debug, Switch, PartialEq)]
+        #[to = "/search?q={query}"]
+        pub struct Search {
+            #[switch(rename = "query")]
+            text: String,
+        }
+        assert_eq!(
+            Search::switch(Route::from("/search?q=rust")),
+            Some(Search {
+                text: "rust".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn validated_capture_falls_through() {
+        #[allow(clippy::ptr_arg)]
+        fn is_known_slug(slug: &String) -> bool {
+            ["rust", "yew"].contains(&slug.as_str())
+        }
+        #[derive(Debug, Switch, PartialEq, Clone)]
+        pub enum Test {
+            #[to = "/topics/{slug}"]
+            Topic {
+                #[switch(validate = "is_known_slug")]
+                slug: String,
+            },
+            #[to = "/topics/{}"]
+            Unknown(#[switch(validate = "is_known_slug")] String),
+            #[to = "/topics/{other}"]
+            Other { other: String },
+        }
+        assert_eq!(
+            Test::switch(Route::from("/topics/yew")),
+            Some(Test::Topic {
+                slug: "yew".to_string()
+            })
+        );
+        assert_eq!(
+            Test::switch(Route::from("/topics/go")),
+            Some(Test::Other {
+                other: "go".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn validated_capture_with_trait() {
+        use yew_router::switch::{SwitchError, Validate};
+
+        #[derive(Debug, Switch, PartialEq, Clone)]
+        #[to = "{}"]
+        pub struct Month(u8);
+        impl Validate for Month {
+            fn validate(&self) -> bool {
+                self.0 >= 1 && self.0 <= 12
+            }
+        }
+        #[derive(Debug, Switch, PartialEq, Clone)]
+        #[to = "/archive/{month}"]
+        pub struct Archive {
+            #[switch(validate)]
+            month: Month,
+        }
+        assert_eq!(
+            Archive::switch(Route::from("/archive/5")),
+            Some(Archive { month: Month(5) })
+        );
+        assert_eq!(
+            Archive::try_switch(Route::from("/archive/13")),
+            Err(SwitchError::InvalidField {
+                item: "Archive",
+                field: "month",
+                value: Some("13".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn generated_matcher_strings() {
+        #[derive(Debug, Switch, PartialEq, Clone)]
+        pub enum Test {
+            #[to = "/!"]
+            Home,
+            UserProfile {
+                id: u32,
+            },
+            Post(u32),
+            Settings,
+        }
+        assert_eq!(
+            Test::switch(Route::from("/user-profile/42")),
+            Some(Test::UserProfile { id: 42 })
+        );
+        assert_eq!(Test::switch(Route::from("/post/7")), Some(Test::Post(7)));
+        assert_eq!(Test::switch(Route::from("/settings")), Some(Test::Settings));
+        assert_eq!(Test::routes()[1].matcher, "/user-profile/{id}");
+        assert_eq!(
+            Route::from(Test::UserProfile { id: 42 }).route,
+            "/user-profile/42"
+        );
+    }
+
+    #[test]
+    fn generated_matcher_strings_rename_all() {
+        #[derive(Debug, Switch, PartialEq, Clone)]
+        #[switch(rename_all = "snake_case")]
+        pub enum Test {
+            UserProfile {
+                #[switch(rename = "user_id")]
+                id: String,
+            },
+        }
+        assert_eq!(
+            Test::switch(Route::from("/user_profile/42")),
+            Some(Test::UserProfile {
+                id: "42".to_string()
+            })
+        );
+        assert_eq!(Test::routes()[0].matcher, "/user_profile/{user_id}");
+    }
+
+    #[test]
+    fn std_wrappers_as_fields() {
+        #[derive(Debug, Switch, PartialEq, Clone)]
+        pub enum Inner {
+            #[to = "/inner"]
+            Variant,
+        }
+
+        #[derive(Debug, Switch, PartialEq, Clone)]
+        pub enum Test {
+            #[to = "/pair/{*}"]
+            Pair((u32, String)),
+            #[to = "/either/{id}"]
+            Either { id: Result<u32, String> },
+            #[to = "/boxed{*}"]
+            Boxed(Box<Inner>),
+        }
+        assert_eq!(
+            Test::switch(Route::from("/pair/42/lorem")),
+            Some(Test::Pair((42, "lorem".to_string())))
+        );
+        assert_eq!(
+            Test::switch(Route::from("/either/abc")),
+            Some(Test::Either {
+                id: Err("abc".to_string())
+            })
+        );
+        assert_eq!(
+            Test::switch(Route::from("/boxed/inner")),
+            Some(Test::Boxed(Box::new(Inner::Variant)))
+        );
+        assert_eq!(
+            Route::from(Test::Pair((42, "lorem".to_string()))).route,
+            "/pair/42/lorem"
+        );
+        assert_eq!(Route::from(Test::Either { id: Ok(42) }).route, "/either/42");
+    }
+
+    #[test]
+    fn typed_captures_fall_through() {
+        use yew_router::switch::SwitchError;
+        #[derive(Debug, Switch, PartialEq, Clone)]
+        pub enum Test {
+            #[to = "/users/{id:u32}"]
+            User { id: u32 },
+            #[to = "/users/{:uuid}"]
+            Session(String),
+            #[to = "/users/new"]
+            New,
+        }
+        assert_eq!(
+            Test::switch(Route::from("/users/42")),
+            Some(Test::User { id: 42 })
+        );
+        assert_eq!(
+            Test::switch(Route::from("/users/5dc48134-35b5-4b8c-aa93-767bf00ae1d8")),
+            Some(Test::Session(
+                "5dc48134-35b5-4b8c-aa93-767bf00ae1d8".to_string()
+            ))
+        );
+        assert_eq!(Test::switch(Route::from("/users/new")), Some(Test::New));
+        assert_eq!(
+            Test::try_switch(Route::from("/users/abc")),
+            Err(SwitchError::NoMatch)
+        );
+        assert_eq!(Route::from(Test::User { id: 42 }).route, "/users/42");
+    }
+
+    #[test]
+    fn repeated_query_params_into_vec() {
+        #[derive(Debug, Switch, PartialEq, Clone)]
+        pub enum Test {
+            #[to = "/posts?tag={*:tags}&page={page}"]
+            Posts { tags: Vec<String>, page: u32 },
+            #[to = "/ids?id={*}"]
+            Ids(Vec<u32>),
+        }
+        assert_eq!(
+            Test::switch(Route::from("/posts?tag=rust&tag=web&page=2")),
+            Some(Test::Posts {
+                tags: vec!["rust".to_string(), "web".to_string()],
+                page: 2
+            })
+        );
+        assert_eq!(
+            Test::switch(Route::from("/ids?id=1&id=2&id=3")),
+            Some(Test::Ids(vec![1, 2, 3]))
+        );
+        assert_eq!(
+            Route::from(Test::Posts {
+                tags: vec!["rust".to_string(), "web".to_string()],
+                page: 2
+            })
+            .route,
+            "/posts?tag=rust&tag=web&page=2"
+        );
+        assert_eq!(Route::from(Test::Ids(vec![1, 2])).route, "/ids?id=1&id=2");
+    }
+
+    #[test]
+    fn repeated_query_params_with_ampersands_round_trip() {
+        #[derive(Debug, Switch, PartialEq, Clone)]
+        pub enum Test {
+            #[to = "/posts?tag={*:tags}&page={page}"]
+            Posts { tags: Vec<String>, page: u32 },
+        }
+        let posts = Test::Posts {
+            tags: vec!["R&D".to_string(), "web".to_string()],
+            page: 2,
+        };
+        let route = Route::from(posts.clone());
+        assert_eq!(route.route, "/posts?tag=R%26D&tag=web&page=2");
+        assert_eq!(Test::switch(route), Some(posts));
+    }
+
+    #[test]
+    fn route_macro_uses_default_settings() {
+        use yew_router::{matcher::MatcherSettings, route};
+        let matcher = route!("/users/{id}");
+        assert_eq!(matcher.settings, MatcherSettings::default());
+        assert!(matcher.capture_route_into_map("/users/42").is_ok());
+        assert!(matcher.capture_route_into_map("/Users/42").is_err());
+    }
+
+    #[test]
+    fn route_macro_uses_given_settings() {
+        use yew_router::{
+            matcher::{GrammarVersion, MatcherSettings, QueryMode, RouteMatcher},
+            route,
+        };
+        let matcher = route!(
+            r"/hello\!",
+            grammar = "v2",
+            query = "lenient",
+            case_insensitive,
+            incomplete
+        );
+        let settings = MatcherSettings {
+            complete: false,
+            case_insensitive: true,
+            grammar_version: GrammarVersion::V2,
+            query_mode: QueryMode::Lenient,
+        };
+        assert_eq!(
+            matcher,
+            RouteMatcher::new(r"/hello\!", settings).expect("should parse")
+        );
+        assert!(matcher.capture_route_into_map("/HELLO!/world").is_ok());
+    }
 }
@@ -0,0 +1,114 @@
+//! Property tests for matching routes.
+//!
+//! Matching happens on every navigation, with routes that can come from anywhere, so neither the
+//! matcher string nor the route should be able to make the matcher panic.
+use proptest::prelude::*;
+use std::collections::HashMap;
+use yew_router::matcher::{CaptureVariant, MatcherToken, RouteMatcher};
+
+/// Strings made mostly of the characters that have a meaning in the grammar.
+fn matcher_string() -> impl Strategy<Value = String> {
+    prop_oneof![
+        "[a-z0-9/{}*:;?&=#!._ -]{0,32}",
+        prop::collection::vec(
+            prop_oneof![
+                "[a-z]{1,6}".prop_map(|s| format!("/{}", s)),
+                "[a-z]{1,6}".prop_map(|s| format!("/{{{}}}", s)),
+                "[a-z]{1,6}".prop_map(|s| format!("/{{*:{}}}", s)),
+                "[a-z]{1,6}".prop_map(|s| format!("/{{**:{}}}", s)),
+                (1usize..4, "[a-z]{1,6}").prop_map(|(n, s)| format!("/{{{}:{}}}", n, s)),
+                "[a-z]{1,6}".prop_map(|s| format!(";{}={{{}}}", s, s)),
+                "[a-z]{1,6}".prop_map(|s| format!("?{}={{{}}}", s, s)),
+                "[a-z]{1,6}".prop_map(|s| format!("&{}={{{}}}", s, s)),
+                "[a-z]{1,6}".prop_map(|s| format!("#{}", s)),
+                Just("!".to_string()),
+            ],
+            0..6
+        )
+        .prop_map(|sections| sections.concat()),
+    ]
+}
+
+fn route_string() -> impl Strategy<Value = String> {
+    prop_oneof!["[a-z0-9/;?&=#!._%-]{0,32}", any::<String>()]
+}
+
+/// A section of a matcher made of literals and named single section captures.
+#[derive(Debug, Clone)]
+enum Section {
+    Literal(String),
+    Capture(String),
+}
+
+/// Matcher strings, alongside values for each of their captures.
+fn reversible_matcher() -> impl Strategy<Value = (Vec<Section>, Vec<String>)> {
+    prop::collection::vec(
+        prop_oneof![
+            "[a-z]{1,6}".prop_map(Section::Literal),
+            "[a-z]{1,6}".prop_map(Section::Capture),
+        ],
+        1..6,
+    )
+    .prop_flat_map(|sections| {
+        let len = sections.len();
+        (
+            Just(sections),
+            prop::collection::vec("[a-zA-Z0-9_-]{1,8}", len),
+        )
+    })
+}
+
+/// Renders a route by replacing each capture of the matcher with a value.
+///
+/// This mirrors how a derived `Switch` builds a route from its fields.
+fn reverse_route(tokens: &[MatcherToken], values: &HashMap<String, String>) -> String {
+    tokens
+        .iter()
+        .map(|token| match token {
+            MatcherToken::Exact(literal) => literal.clone(),
+            MatcherToken::Capture(CaptureVariant::Named(name)) => values[name].clone(),
+            MatcherToken::Capture(capture) => panic!("unexpected capture: {:?}", capture),
+            MatcherToken::End => String::new(),
+        })
+        .collect()
+}
+
+proptest! {
+    #[test]
+    fn matching_never_panics(matcher in matcher_string(), route in route_string()) {
+        if let Ok(matcher) = RouteMatcher::try_from(&matcher) {
+            let _ = matcher.capture_route_into_map(&route);
+            let _ = matcher.capture_route_into_vec(&route);
+        }
+    }
+
+    #[test]
+    fn reversed_routes_match((sections, values) in reversible_matcher()) {
+        // Captures are renamed so that every name is unique.
+        let mut captured = HashMap::new();
+        let matcher: String = sections
+            .iter()
+            .zip(values)
+            .enumerate()
+            .map(|(index, (section, value))| match section {
+                Section::Literal(literal) => format!("/{}", literal),
+                Section::Capture(name) => {
+                    let name = format!("{}{}", name, index);
+                    let section = format!("/{{{}}}", name);
+                    captured.insert(name, value);
+                    section
+                }
+            })
+            .collect();
+        let matcher = RouteMatcher::try_from(&matcher).expect("should parse");
+        let route = reverse_route(&matcher.tokens, &captured);
+
+        let (_, matches) = matcher
+            .capture_route_into_map(&route)
+            .expect("reversed route should match");
+        prop_assert_eq!(matches.len(), captured.len());
+        for (name, value) in &captured {
+            prop_assert_eq!(&matches[name.as_str()], value);
+        }
+    }
+}